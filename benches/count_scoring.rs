@@ -0,0 +1,113 @@
+use binggan::{black_box, InputGroup};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tantivy::collector::{Collector, Count, SegmentCollector};
+use tantivy::query::{BooleanQuery, PhraseQuery, Query, TermQuery};
+use tantivy::schema::{IndexRecordOption, Schema, TEXT};
+use tantivy::{doc, DocId, Index, IndexWriter, Score, SegmentReader, Term};
+
+const NUM_DOCS: usize = 500_000;
+const VOCABULARY: [&str; 8] = [
+    "apple", "banana", "cherry", "date", "elder", "fig", "grape", "lemon",
+];
+
+type QueryInput = (Index, Box<dyn Query>);
+
+/// Counts the matching documents like `Count`, but requires the scores.
+struct CountWithScores;
+
+struct CountWithScoresSegmentCollector(usize);
+
+impl Collector for CountWithScores {
+    type Fruit = usize;
+    type Child = CountWithScoresSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_ord: u32,
+        _reader: &SegmentReader,
+    ) -> tantivy::Result<CountWithScoresSegmentCollector> {
+        Ok(CountWithScoresSegmentCollector(0))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, counts: Vec<usize>) -> tantivy::Result<usize> {
+        Ok(counts.into_iter().sum())
+    }
+}
+
+impl SegmentCollector for CountWithScoresSegmentCollector {
+    type Fruit = usize;
+
+    fn collect(&mut self, _doc: DocId, score: Score) {
+        black_box(score);
+        self.0 += 1;
+    }
+
+    fn harvest(self) -> usize {
+        self.0
+    }
+}
+
+fn main() {
+    let index = build_index().unwrap();
+    let body = index.schema().get_field("body").unwrap();
+    let term = |text: &str| Term::from_field_text(body, text);
+    let term_query = |text: &str| -> Box<dyn Query> {
+        Box::new(TermQuery::new(term(text), IndexRecordOption::WithFreqs))
+    };
+    let queries: Vec<(String, QueryInput)> = vec![
+        ("term".to_string(), term_query("apple")),
+        (
+            "union".to_string(),
+            Box::new(BooleanQuery::union(vec![
+                term_query("apple"),
+                term_query("banana"),
+            ])) as Box<dyn Query>,
+        ),
+        (
+            "intersection".to_string(),
+            Box::new(BooleanQuery::intersection(vec![
+                term_query("apple"),
+                term_query("banana"),
+            ])),
+        ),
+        (
+            "phrase".to_string(),
+            Box::new(PhraseQuery::new(vec![term("apple"), term("banana")])),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, query)| (name, (index.clone(), query)))
+    .collect();
+    let mut group = InputGroup::new_with_inputs(queries);
+    group.register("count_scoring_disabled", |(index, query)| {
+        let searcher = index.reader().unwrap().searcher();
+        black_box(searcher.search(query.as_ref(), &Count).unwrap());
+    });
+    group.register("count_scoring_enabled", |(index, query)| {
+        let searcher = index.reader().unwrap().searcher();
+        black_box(searcher.search(query.as_ref(), &CountWithScores).unwrap());
+    });
+    group.run();
+}
+
+fn build_index() -> tantivy::Result<Index> {
+    let mut schema_builder = Schema::builder();
+    let body = schema_builder.add_text_field("body", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut rng = StdRng::from_seed([3u8; 32]);
+    let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 200_000_000)?;
+    for _ in 0..NUM_DOCS {
+        let num_words = rng.gen_range(1..12);
+        let text: Vec<&str> = (0..num_words)
+            .map(|_| VOCABULARY[rng.gen_range(0..VOCABULARY.len())])
+            .collect();
+        index_writer.add_document(doc!(body => text.join(" ")))?;
+    }
+    index_writer.commit()?;
+    Ok(index)
+}
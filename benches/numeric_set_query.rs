@@ -0,0 +1,70 @@
+use binggan::{black_box, InputGroup};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tantivy::collector::Count;
+use tantivy::query::{BooleanQuery, NumericSetQuery, Query, TermQuery};
+use tantivy::schema::{IndexRecordOption, Schema, FAST, INDEXED};
+use tantivy::{doc, Index, IndexWriter, Term};
+
+const NUM_DOCS: u64 = 1_000_000;
+const NUM_DISTINCT_VALUES: u64 = 10_000;
+
+fn main() {
+    let index = build_index().unwrap();
+    let mut rng = StdRng::from_seed([1u8; 32]);
+    let inputs: Vec<(String, (Index, Vec<u64>))> = [3, 100, 1_000]
+        .into_iter()
+        .map(|num_values| {
+            let values: Vec<u64> = (0..num_values)
+                .map(|_| rng.gen_range(0..NUM_DISTINCT_VALUES))
+                .collect();
+            (format!("{num_values}_values"), (index.clone(), values))
+        })
+        .collect();
+    let mut group = InputGroup::new_with_inputs(inputs);
+    group.register("boolean_or_indexed", |(index, values)| {
+        let status = index.schema().get_field("status").unwrap();
+        let term_queries: Vec<Box<dyn Query>> = values
+            .iter()
+            .map(|&value| -> Box<dyn Query> {
+                Box::new(TermQuery::new(
+                    Term::from_field_u64(status, value),
+                    IndexRecordOption::Basic,
+                ))
+            })
+            .collect();
+        count(index, &BooleanQuery::union(term_queries));
+    });
+    group.register("numeric_set_indexed", |(index, values)| {
+        let status = index.schema().get_field("status").unwrap();
+        count(index, &NumericSetQuery::new(status, values.iter().copied()));
+    });
+    group.register("numeric_set_fast", |(index, values)| {
+        let status_fast = index.schema().get_field("status_fast").unwrap();
+        count(
+            index,
+            &NumericSetQuery::new(status_fast, values.iter().copied()),
+        );
+    });
+    group.run();
+}
+
+fn count(index: &Index, query: &dyn Query) {
+    let searcher = index.reader().unwrap().searcher();
+    black_box(searcher.search(query, &Count).unwrap());
+}
+
+fn build_index() -> tantivy::Result<Index> {
+    let mut schema_builder = Schema::builder();
+    let status = schema_builder.add_u64_field("status", INDEXED);
+    let status_fast = schema_builder.add_u64_field("status_fast", FAST);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut rng = StdRng::from_seed([2u8; 32]);
+    let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 200_000_000)?;
+    for _ in 0..NUM_DOCS {
+        let value = rng.gen_range(0..NUM_DISTINCT_VALUES);
+        index_writer.add_document(doc!(status => value, status_fast => value))?;
+    }
+    index_writer.commit()?;
+    Ok(index)
+}
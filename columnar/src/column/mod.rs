@@ -6,11 +6,12 @@ use std::io::Write;
 use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 
-use common::BinarySerializable;
+use common::{BinarySerializable, BitSet};
 pub use dictionary_encoded::{BytesColumn, StrColumn};
+pub(crate) use serialize::{DEFAULT_U64_CODEC_TYPES, serialize_column_mappable_to_u64_with_codecs};
 pub use serialize::{
-    open_column_bytes, open_column_str, open_column_u64, open_column_u128,
-    open_column_u128_as_compact_u64, serialize_column_mappable_to_u64,
+    open_column_bytes, open_column_str, open_column_u64, open_column_u64_with_codecs,
+    open_column_u128, open_column_u128_as_compact_u64, serialize_column_mappable_to_u64,
     serialize_column_mappable_to_u128,
 };
 
@@ -167,6 +168,53 @@ impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
         output.extend(self.values_for_doc(row_id));
     }
 
+    /// Returns the values of a dense column as a contiguous slice, indexed by doc id.
+    ///
+    /// This makes it possible to process a column in bulk (e.g. with SIMD) without going
+    /// through one method call per document.
+    ///
+    /// The fast path is only available if the column is full (every document has exactly one
+    /// value) and its values are stored uncompressed, as in a [`VecColumn`]. Columns read
+    /// from an index are compressed (bitpacked, linear, ...) and always return `None`:
+    /// [`ColumnValues::get_range`] is then the most efficient way to decode values in bulk.
+    ///
+    /// [`VecColumn`]: crate::column_values::VecColumn
+    pub fn as_dense_slice(&self) -> Option<&[T]> {
+        match &self.index {
+            ColumnIndex::Full => self.values.as_slice(),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of the column and, for a column where some documents have no value,
+    /// the bitmap of the documents having a value.
+    ///
+    /// Values are dense: they are stored only for the documents having a value, in doc id
+    /// order. The value at index `i` therefore belongs to the `i`-th document set in the
+    /// bitmap. If the column is full, there is no bitmap and the value at index `i` belongs to
+    /// document `i`.
+    ///
+    /// This makes it possible to decode the values in bulk, with [`ColumnValues::get_range`],
+    /// and to handle missing values separately, rather than matching an `Option` per document.
+    ///
+    /// Returns `None` for a multivalued column, as its values cannot be aligned with a bitmap
+    /// of documents.
+    pub fn values_and_nulls(&self) -> Option<(Arc<dyn ColumnValues<T>>, Option<BitSet>)> {
+        let null_bitmap = match &self.index {
+            ColumnIndex::Empty { num_docs } => Some(BitSet::with_max_value(*num_docs)),
+            ColumnIndex::Full => None,
+            ColumnIndex::Optional(optional_index) => {
+                let mut null_bitmap = BitSet::with_max_value(optional_index.num_docs());
+                for doc_id in optional_index.iter_docs() {
+                    null_bitmap.insert(doc_id);
+                }
+                Some(null_bitmap)
+            }
+            ColumnIndex::Multivalued(_) => return None,
+        };
+        Some((self.values.clone(), null_bitmap))
+    }
+
     pub fn first_or_default_col(self, default_value: T) -> Arc<dyn ColumnValues<T>> {
         Arc::new(FirstValueWithDefault {
             column: self,
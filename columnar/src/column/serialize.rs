@@ -8,8 +8,9 @@ use sstable::Dictionary;
 use crate::column::{BytesColumn, Column};
 use crate::column_index::{SerializableColumnIndex, serialize_column_index};
 use crate::column_values::{
-    CodecType, MonotonicallyMappableToU64, MonotonicallyMappableToU128,
-    load_u64_based_column_values, serialize_column_values_u128, serialize_u64_based_column_values,
+    CodecType, FastFieldCodecs, MonotonicallyMappableToU64, MonotonicallyMappableToU128,
+    load_u64_based_column_values, load_u64_based_column_values_with_codecs,
+    serialize_column_values_u128, serialize_u64_based_column_values_inner,
 };
 use crate::iterable::Iterable;
 use crate::{StrColumn, Version};
@@ -25,17 +26,36 @@ pub fn serialize_column_mappable_to_u128<T: MonotonicallyMappableToU128>(
     Ok(())
 }
 
+/// The codecs competing to encode the values of a column, unless it selects its codec.
+pub(crate) const DEFAULT_U64_CODEC_TYPES: &[CodecType] =
+    &[CodecType::Bitpacked, CodecType::BlockwiseLinear];
+
 pub fn serialize_column_mappable_to_u64<T: MonotonicallyMappableToU64>(
     column_index: SerializableColumnIndex<'_>,
     column_values: &impl Iterable<T>,
+    codec_types: &[CodecType],
     output: &mut impl Write,
 ) -> io::Result<()> {
-    let column_index_num_bytes = serialize_column_index(column_index, output)?;
-    serialize_u64_based_column_values(
+    serialize_column_mappable_to_u64_with_codecs(
+        column_index,
         column_values,
-        &[CodecType::Bitpacked, CodecType::BlockwiseLinear],
+        codec_types,
+        None,
         output,
-    )?;
+    )
+}
+
+/// Serializes a column of u64-mapped values, looking the custom codecs of `codec_types` up in
+/// `codecs`.
+pub(crate) fn serialize_column_mappable_to_u64_with_codecs<T: MonotonicallyMappableToU64>(
+    column_index: SerializableColumnIndex<'_>,
+    column_values: &impl Iterable<T>,
+    codec_types: &[CodecType],
+    codecs: Option<&FastFieldCodecs>,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let column_index_num_bytes = serialize_column_index(column_index, output)?;
+    serialize_u64_based_column_values_inner(column_values, codec_types, codecs, output)?;
     output.write_all(&column_index_num_bytes.to_le_bytes())?;
     Ok(())
 }
@@ -43,6 +63,24 @@ pub fn serialize_column_mappable_to_u64<T: MonotonicallyMappableToU64>(
 pub fn open_column_u64<T: MonotonicallyMappableToU64>(
     bytes: OwnedBytes,
     format_version: Version,
+) -> io::Result<Column<T>> {
+    open_column_u64_inner(bytes, format_version, None)
+}
+
+/// Opens a column of u64-mapped values, looking its codec up in `codecs` if it is a custom
+/// codec.
+pub fn open_column_u64_with_codecs<T: MonotonicallyMappableToU64>(
+    bytes: OwnedBytes,
+    format_version: Version,
+    codecs: &FastFieldCodecs,
+) -> io::Result<Column<T>> {
+    open_column_u64_inner(bytes, format_version, Some(codecs))
+}
+
+fn open_column_u64_inner<T: MonotonicallyMappableToU64>(
+    bytes: OwnedBytes,
+    format_version: Version,
+    codecs: Option<&FastFieldCodecs>,
 ) -> io::Result<Column<T>> {
     let (body, column_index_num_bytes_payload) = bytes.rsplit(4);
     let column_index_num_bytes = u32::from_le_bytes(
@@ -53,7 +91,10 @@ pub fn open_column_u64<T: MonotonicallyMappableToU64>(
     );
     let (column_index_data, column_values_data) = body.split(column_index_num_bytes as usize);
     let column_index = crate::column_index::open_column_index(column_index_data, format_version)?;
-    let column_values = load_u64_based_column_values(column_values_data)?;
+    let column_values = match codecs {
+        Some(codecs) => load_u64_based_column_values_with_codecs(column_values_data, codecs)?,
+        None => load_u64_based_column_values(column_values_data)?,
+    };
     Ok(Column {
         index: column_index,
         values: column_values,
@@ -26,9 +26,13 @@ mod monotonic_column;
 
 pub(crate) use merge::MergedColumnValues;
 pub use stats::ColumnStats;
+pub(crate) use u64_based::serialize_u64_based_column_values_inner;
 pub use u64_based::{
-    ALL_U64_CODEC_TYPES, CodecType, load_u64_based_column_values,
+    ALL_U64_CODEC_TYPES, BitpackedCodec, BlockwiseLinearCodec, CodecType, ColumnCodecEstimator,
+    FIRST_CUSTOM_CODEC_CODE, FastFieldCodec, FastFieldCodecs, LinearCodec,
+    load_u64_based_column_values, load_u64_based_column_values_with_codecs,
     serialize_and_load_u64_based_column_values, serialize_u64_based_column_values,
+    serialize_u64_based_column_values_with_codecs,
 };
 pub use u128_based::{
     CompactSpaceU64Accessor, open_u128_as_compact_u64, open_u128_mapped,
@@ -171,6 +175,13 @@ pub trait ColumnValues<T: PartialOrd = u64>: Send + Sync + DowncastSync {
     fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = T> + 'a> {
         Box::new((0..self.num_vals()).map(|idx| self.get_val(idx)))
     }
+
+    /// Returns the values as a contiguous slice, if they are stored as such.
+    ///
+    /// Compressed column values return `None`.
+    fn as_slice(&self) -> Option<&[T]> {
+        None
+    }
 }
 downcast_rs::impl_downcast!(sync ColumnValues<T> where T: PartialOrd);
 
@@ -231,6 +242,11 @@ impl<T: Copy + PartialOrd + Debug + 'static> ColumnValues<T> for Arc<dyn ColumnV
         self.as_ref().get_range(start, output)
     }
 
+    #[inline(always)]
+    fn as_slice(&self) -> Option<&[T]> {
+        self.as_ref().as_slice()
+    }
+
     #[inline(always)]
     fn get_row_ids_for_value_range(
         &self,
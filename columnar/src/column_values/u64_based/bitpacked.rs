@@ -126,6 +126,7 @@ impl ColumnCodecEstimator for BitpackedCodecEstimator {
     }
 }
 
+/// Codec bitpacking the values, see [`CodecType::Bitpacked`](super::CodecType::Bitpacked).
 pub struct BitpackedCodec;
 
 impl ColumnCodec for BitpackedCodec {
@@ -165,6 +165,8 @@ impl ColumnCodecEstimator for BlockwiseLinearEstimator {
     }
 }
 
+/// Codec bitpacking the offsets from a line per block, see
+/// [`CodecType::BlockwiseLinear`](super::CodecType::BlockwiseLinear).
 pub struct BlockwiseLinearCodec;
 
 impl ColumnCodec<u64> for BlockwiseLinearCodec {
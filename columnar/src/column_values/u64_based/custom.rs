@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::{fmt, io};
+
+use common::OwnedBytes;
+
+use crate::ColumnValues;
+use crate::column_values::u64_based::{
+    BitpackedCodec, BlockwiseLinearCodec, ColumnCodec, ColumnCodecEstimator, LinearCodec,
+};
+
+/// The first code available to the codecs registered in [`FastFieldCodecs`].
+///
+/// The codes below are reserved for the built-in codecs.
+pub const FIRST_CUSTOM_CODEC_CODE: u8 = 128;
+
+/// A codec for the values of the `u64`-based columns, that is the numerical, bool and date
+/// columns.
+///
+/// A codec taking advantage of the structure of some data, such as monotonic timestamps or
+/// clustered ids, can be registered in [`FastFieldCodecs`] and then selected for a column as
+/// [`CodecType::Custom`](super::CodecType::Custom).
+///
+/// The codec receives the values mapped to `u64` in a way that preserves their order, see
+/// [`MonotonicallyMappableToU64`](crate::MonotonicallyMappableToU64).
+pub trait FastFieldCodec: Send + Sync + 'static {
+    /// Returns an estimator, which collects the values of the column in a first pass,
+    /// then serializes them in a second pass.
+    ///
+    /// The serialization of columns fails if the estimator returns no estimation.
+    fn estimator(&self) -> Box<dyn ColumnCodecEstimator>;
+
+    /// Loads the values serialized by the estimator.
+    fn load(&self, bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>>;
+}
+
+macro_rules! impl_fast_field_codec {
+    ($codec:ty) => {
+        impl FastFieldCodec for $codec {
+            fn estimator(&self) -> Box<dyn ColumnCodecEstimator> {
+                <$codec as ColumnCodec>::boxed_estimator()
+            }
+
+            fn load(&self, bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>> {
+                Ok(Arc::new(<$codec as ColumnCodec>::load(bytes)?))
+            }
+        }
+    };
+}
+
+impl_fast_field_codec!(BitpackedCodec);
+impl_fast_field_codec!(LinearCodec);
+impl_fast_field_codec!(BlockwiseLinearCodec);
+
+/// A registry of the custom codecs of the `u64`-based columns, by code.
+///
+/// The codecs are not shared by the whole process: a registry is handed to the
+/// [`ColumnarWriter`](crate::ColumnarWriter) writing the columns, and to the
+/// [`ColumnarReader`](crate::ColumnarReader) reading them. Cloning a registry is cheap, and the
+/// clones share their codecs.
+#[derive(Clone, Default)]
+pub struct FastFieldCodecs {
+    codecs: Arc<RwLock<BTreeMap<u8, Arc<dyn FastFieldCodec>>>>,
+}
+
+impl fmt::Debug for FastFieldCodecs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let codes: Vec<u8> = self
+            .codecs
+            .read()
+            .expect("Lock poisoned")
+            .keys()
+            .copied()
+            .collect();
+        f.debug_struct("FastFieldCodecs")
+            .field("codes", &codes)
+            .finish()
+    }
+}
+
+impl FastFieldCodecs {
+    /// Registers a codec under the given `code`.
+    ///
+    /// The code is written along with the values of the columns encoded with the codec, to pick
+    /// the codec back when they are read: the codec has to be registered under the same code
+    /// before opening these columns, and it cannot change its format without changing its code.
+    ///
+    /// Returns an error if the code is below [`FIRST_CUSTOM_CODEC_CODE`], or if it is already
+    /// taken.
+    pub fn register(&self, code: u8, codec: Arc<dyn FastFieldCodec>) -> io::Result<()> {
+        if code < FIRST_CUSTOM_CODEC_CODE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Codec code {code} is reserved for the built-in codecs."),
+            ));
+        }
+        let mut codecs = self.codecs.write().expect("Lock poisoned");
+        if codecs.contains_key(&code) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("A codec is already registered with the code {code}."),
+            ));
+        }
+        codecs.insert(code, codec);
+        Ok(())
+    }
+
+    /// Unregisters the codec registered under the given `code`, and returns it.
+    ///
+    /// The columns encoded with the codec cannot be read anymore, unless a codec is registered
+    /// again under the same code.
+    pub fn unregister(&self, code: u8) -> Option<Arc<dyn FastFieldCodec>> {
+        self.codecs.write().expect("Lock poisoned").remove(&code)
+    }
+
+    /// Returns the codec registered under the given code, if any.
+    pub fn get(&self, code: u8) -> Option<Arc<dyn FastFieldCodec>> {
+        self.codecs
+            .read()
+            .expect("Lock poisoned")
+            .get(&code)
+            .cloned()
+    }
+}
+
+/// Returns the codec registered with the given code in `codecs`.
+pub(crate) fn custom_codec(
+    codecs: Option<&FastFieldCodecs>,
+    code: u8,
+) -> io::Result<Arc<dyn FastFieldCodec>> {
+    codecs.and_then(|codecs| codecs.get(code)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("No codec is registered with the code {code}."),
+        )
+    })
+}
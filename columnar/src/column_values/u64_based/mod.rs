@@ -1,5 +1,6 @@
 mod bitpacked;
 mod blockwise_linear;
+mod custom;
 mod line;
 mod linear;
 mod stats_collector;
@@ -15,6 +16,10 @@ use crate::column_values::monotonic_mapping::{
 };
 pub use crate::column_values::u64_based::bitpacked::BitpackedCodec;
 pub use crate::column_values::u64_based::blockwise_linear::BlockwiseLinearCodec;
+use crate::column_values::u64_based::custom::custom_codec;
+pub use crate::column_values::u64_based::custom::{
+    FIRST_CUSTOM_CODEC_CODE, FastFieldCodec, FastFieldCodecs,
+};
 pub use crate::column_values::u64_based::linear::LinearCodec;
 pub use crate::column_values::u64_based::stats_collector::StatsCollector;
 use crate::column_values::{ColumnStats, monotonic_map_column};
@@ -86,6 +91,8 @@ pub enum CodecType {
     Linear = 1u8,
     /// Same as [`CodecType::Linear`], but encodes in blocks of 512 elements.
     BlockwiseLinear = 2u8,
+    /// The codec registered in [`FastFieldCodecs`] under the given code.
+    Custom(u8),
 }
 
 /// List of all available u64-base codecs.
@@ -97,7 +104,12 @@ pub const ALL_U64_CODEC_TYPES: [CodecType; 3] = [
 
 impl CodecType {
     fn to_code(self) -> u8 {
-        self as u8
+        match self {
+            CodecType::Bitpacked => 0u8,
+            CodecType::Linear => 1u8,
+            CodecType::BlockwiseLinear => 2u8,
+            CodecType::Custom(code) => code,
+        }
     }
 
     fn try_from_code(code: u8) -> Option<CodecType> {
@@ -105,6 +117,7 @@ impl CodecType {
             0u8 => Some(CodecType::Bitpacked),
             1u8 => Some(CodecType::Linear),
             2u8 => Some(CodecType::BlockwiseLinear),
+            code if code >= FIRST_CUSTOM_CODEC_CODE => Some(CodecType::Custom(code)),
             _ => None,
         }
     }
@@ -112,11 +125,22 @@ impl CodecType {
     fn load<T: MonotonicallyMappableToU64>(
         &self,
         bytes: OwnedBytes,
+        codecs: Option<&FastFieldCodecs>,
     ) -> io::Result<Arc<dyn ColumnValues<T>>> {
         match self {
             CodecType::Bitpacked => load_specific_codec::<BitpackedCodec, T>(bytes),
             CodecType::Linear => load_specific_codec::<LinearCodec, T>(bytes),
             CodecType::BlockwiseLinear => load_specific_codec::<BlockwiseLinearCodec, T>(bytes),
+            CodecType::Custom(code) => {
+                let reader = custom_codec(codecs, *code)?.load(bytes)?;
+                let reader_typed = monotonic_map_column(
+                    reader,
+                    StrictlyMonotonicMappingInverter::from(
+                        StrictlyMonotonicMappingToInternal::<T>::new(),
+                    ),
+                );
+                Ok(Arc::new(reader_typed))
+            }
         }
     }
 }
@@ -133,27 +157,56 @@ fn load_specific_codec<C: ColumnCodec, T: MonotonicallyMappableToU64>(
 }
 
 impl CodecType {
-    /// Returns a boxed codec estimator associated to a given `CodecType`.
-    pub fn estimator(&self) -> Box<dyn ColumnCodecEstimator> {
-        match self {
+    /// Returns a boxed codec estimator associated to a given `CodecType`, looking custom codecs
+    /// up in `codecs`.
+    ///
+    /// Returns an error for a custom codec which is not registered.
+    pub fn try_estimator(
+        &self,
+        codecs: Option<&FastFieldCodecs>,
+    ) -> io::Result<Box<dyn ColumnCodecEstimator>> {
+        Ok(match self {
             CodecType::Bitpacked => BitpackedCodec::boxed_estimator(),
             CodecType::Linear => LinearCodec::boxed_estimator(),
             CodecType::BlockwiseLinear => BlockwiseLinearCodec::boxed_estimator(),
-        }
+            CodecType::Custom(code) => custom_codec(codecs, *code)?.estimator(),
+        })
     }
 }
 
 /// Serializes a given column of u64-mapped values.
+///
+/// Custom codecs are not available, see [`serialize_u64_based_column_values_with_codecs`].
 pub fn serialize_u64_based_column_values<T: MonotonicallyMappableToU64>(
     vals: &dyn Iterable<T>,
     codec_types: &[CodecType],
     wrt: &mut dyn Write,
+) -> io::Result<()> {
+    serialize_u64_based_column_values_inner(vals, codec_types, None, wrt)
+}
+
+/// Serializes a given column of u64-mapped values, looking the custom codecs of `codec_types`
+/// up in `codecs`.
+pub fn serialize_u64_based_column_values_with_codecs<T: MonotonicallyMappableToU64>(
+    vals: &dyn Iterable<T>,
+    codec_types: &[CodecType],
+    codecs: &FastFieldCodecs,
+    wrt: &mut dyn Write,
+) -> io::Result<()> {
+    serialize_u64_based_column_values_inner(vals, codec_types, Some(codecs), wrt)
+}
+
+pub(crate) fn serialize_u64_based_column_values_inner<T: MonotonicallyMappableToU64>(
+    vals: &dyn Iterable<T>,
+    codec_types: &[CodecType],
+    codecs: Option<&FastFieldCodecs>,
+    wrt: &mut dyn Write,
 ) -> io::Result<()> {
     let mut stats_collector = StatsCollector::default();
     let mut estimators: Vec<(CodecType, Box<dyn ColumnCodecEstimator>)> =
         Vec::with_capacity(codec_types.len());
     for &codec_type in codec_types {
-        estimators.push((codec_type, codec_type.estimator()));
+        estimators.push((codec_type, codec_type.try_estimator(codecs)?));
     }
     for val in vals.boxed_iter() {
         let val_u64 = val.to_u64();
@@ -188,16 +241,34 @@ pub fn serialize_u64_based_column_values<T: MonotonicallyMappableToU64>(
 /// Load u64-based column values.
 ///
 /// This method first identifies the codec off the first byte.
+///
+/// Custom codecs are not available, see [`load_u64_based_column_values_with_codecs`].
 pub fn load_u64_based_column_values<T: MonotonicallyMappableToU64>(
+    bytes: OwnedBytes,
+) -> io::Result<Arc<dyn ColumnValues<T>>> {
+    load_u64_based_column_values_inner(bytes, None)
+}
+
+/// Load u64-based column values, looking their codec up in `codecs` if it is a custom codec.
+pub fn load_u64_based_column_values_with_codecs<T: MonotonicallyMappableToU64>(
+    bytes: OwnedBytes,
+    codecs: &FastFieldCodecs,
+) -> io::Result<Arc<dyn ColumnValues<T>>> {
+    load_u64_based_column_values_inner(bytes, Some(codecs))
+}
+
+fn load_u64_based_column_values_inner<T: MonotonicallyMappableToU64>(
     mut bytes: OwnedBytes,
+    codecs: Option<&FastFieldCodecs>,
 ) -> io::Result<Arc<dyn ColumnValues<T>>> {
-    let codec_type: CodecType = bytes
+    let code = bytes
         .first()
         .copied()
-        .and_then(CodecType::try_from_code)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Failed to read codec type"))?;
+    let codec_type: CodecType = CodecType::try_from_code(code)
         .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Failed to read codec type"))?;
     bytes.advance(1);
-    codec_type.load(bytes)
+    codec_type.load(bytes, codecs)
 }
 
 /// Helper function to serialize a column (autodetect from all codecs) and then open it
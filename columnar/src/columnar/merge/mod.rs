@@ -2,7 +2,7 @@ mod merge_dict_column;
 mod merge_mapping;
 mod term_merger;
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
 use std::net::Ipv6Addr;
 use std::sync::Arc;
@@ -10,8 +10,11 @@ use std::sync::Arc;
 pub use merge_mapping::{MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
 
 use super::writer::ColumnarSerializer;
-use crate::column::{serialize_column_mappable_to_u64, serialize_column_mappable_to_u128};
-use crate::column_values::MergedColumnValues;
+use crate::column::{
+    DEFAULT_U64_CODEC_TYPES, serialize_column_mappable_to_u64_with_codecs,
+    serialize_column_mappable_to_u128,
+};
+use crate::column_values::{CodecType, FastFieldCodecs, MergedColumnValues};
 use crate::columnar::ColumnarReader;
 use crate::columnar::merge::merge_dict_column::merge_bytes_or_str_column;
 use crate::columnar::writer::CompatibleNumericalTypes;
@@ -78,6 +81,29 @@ pub fn merge_columnar(
     required_columns: &[(String, ColumnType)],
     merge_row_order: MergeRowOrder,
     output: &mut impl io::Write,
+) -> io::Result<()> {
+    merge_columnar_with_codecs(
+        columnar_readers,
+        required_columns,
+        &HashMap::new(),
+        &FastFieldCodecs::default(),
+        merge_row_order,
+        output,
+    )
+}
+
+/// Merge several columnar table together, like [`merge_columnar`], encoding the values of the
+/// columns of `column_codecs` with their codec. The custom codecs are looked up in
+/// `fast_field_codecs`.
+///
+/// See [`ColumnarWriter::set_column_codec`](crate::ColumnarWriter::set_column_codec).
+pub fn merge_columnar_with_codecs(
+    columnar_readers: &[&ColumnarReader],
+    required_columns: &[(String, ColumnType)],
+    column_codecs: &HashMap<String, CodecType>,
+    fast_field_codecs: &FastFieldCodecs,
+    merge_row_order: MergeRowOrder,
+    output: &mut impl io::Write,
 ) -> io::Result<()> {
     let mut serializer = ColumnarSerializer::new(output);
     let num_docs_per_columnar = columnar_readers
@@ -102,11 +128,17 @@ pub fn merge_columnar(
 
         let mut column_serializer =
             serializer.start_serialize_column(column_name.as_bytes(), column_type_after_merge);
+        let codec_types = column_codecs
+            .get(&column_name)
+            .map(std::slice::from_ref)
+            .unwrap_or(DEFAULT_U64_CODEC_TYPES);
         merge_column(
             column_type_after_merge,
             &num_docs_per_columnar,
             columns,
             &merge_row_order,
+            codec_types,
+            fast_field_codecs,
             &mut column_serializer,
         )?;
         column_serializer.finalize()?;
@@ -132,6 +164,8 @@ fn merge_column(
     num_docs_per_column: &[u32],
     columns_to_merge: Vec<Option<DynamicColumn>>,
     merge_row_order: &MergeRowOrder,
+    codec_types: &[CodecType],
+    fast_field_codecs: &FastFieldCodecs,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
     match column_type {
@@ -164,7 +198,13 @@ fn merge_column(
                 column_values: &column_values[..],
                 merge_row_order,
             };
-            serialize_column_mappable_to_u64(merged_column_index, &merge_column_values, wrt)?;
+            serialize_column_mappable_to_u64_with_codecs(
+                merged_column_index,
+                &merge_column_values,
+                codec_types,
+                Some(fast_field_codecs),
+                wrt,
+            )?;
         }
         ColumnType::IpAddr => {
             let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
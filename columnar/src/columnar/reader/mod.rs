@@ -5,6 +5,7 @@ use common::file_slice::FileSlice;
 use common::json_path_writer::JSON_PATH_SEGMENT_SEP;
 use sstable::{Dictionary, RangeSSTable};
 
+use crate::column_values::FastFieldCodecs;
 use crate::columnar::{ColumnType, format_version};
 use crate::dynamic_column::DynamicColumnHandle;
 use crate::{RowId, Version};
@@ -21,6 +22,7 @@ pub struct ColumnarReader {
     column_data: FileSlice,
     num_docs: RowId,
     format_version: Version,
+    fast_field_codecs: FastFieldCodecs,
 }
 
 impl fmt::Debug for ColumnarReader {
@@ -56,6 +58,7 @@ fn read_all_columns_in_stream(
     mut stream: sstable::Streamer<'_, RangeSSTable>,
     column_data: &FileSlice,
     format_version: Version,
+    fast_field_codecs: &FastFieldCodecs,
 ) -> io::Result<Vec<DynamicColumnHandle>> {
     let mut results = Vec::new();
     while stream.advance() {
@@ -71,6 +74,7 @@ fn read_all_columns_in_stream(
             file_slice,
             column_type,
             format_version,
+            fast_field_codecs: fast_field_codecs.clone(),
         };
         results.push(dynamic_column_handle);
     }
@@ -94,10 +98,25 @@ impl ColumnarReader {
     /// Opens a new Columnar file.
     pub fn open<F>(file_slice: F) -> io::Result<ColumnarReader>
     where FileSlice: From<F> {
-        Self::open_inner(file_slice.into())
+        Self::open_inner(file_slice.into(), FastFieldCodecs::default())
     }
 
-    fn open_inner(file_slice: FileSlice) -> io::Result<ColumnarReader> {
+    /// Opens a new Columnar file, whose columns encoded with a custom codec are decoded with the
+    /// codecs of `fast_field_codecs`.
+    pub fn open_with_codecs<F>(
+        file_slice: F,
+        fast_field_codecs: FastFieldCodecs,
+    ) -> io::Result<ColumnarReader>
+    where
+        FileSlice: From<F>,
+    {
+        Self::open_inner(file_slice.into(), fast_field_codecs)
+    }
+
+    fn open_inner(
+        file_slice: FileSlice,
+        fast_field_codecs: FastFieldCodecs,
+    ) -> io::Result<ColumnarReader> {
         let (file_slice_without_sstable_len, footer_slice) = file_slice
             .split_from_end(mem::size_of::<u64>() + 4 + format_version::VERSION_FOOTER_NUM_BYTES);
         let footer_bytes = footer_slice.read_bytes()?;
@@ -114,6 +133,7 @@ impl ColumnarReader {
             column_data,
             num_docs: num_rows,
             format_version,
+            fast_field_codecs,
         })
     }
 
@@ -145,6 +165,7 @@ impl ColumnarReader {
                     file_slice,
                     column_type,
                     format_version: self.format_version,
+                    fast_field_codecs: self.fast_field_codecs.clone(),
                 };
                 Some((column_name, column_handle))
             } else {
@@ -168,7 +189,12 @@ impl ColumnarReader {
             .prefix_range(prefix)
             .into_stream_async()
             .await?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.fast_field_codecs,
+        )
     }
 
     /// Get all columns for the given column name.
@@ -178,7 +204,12 @@ impl ColumnarReader {
     pub fn read_columns(&self, column_name: &str) -> io::Result<Vec<DynamicColumnHandle>> {
         let prefix = column_dictionary_prefix_for_column_name(column_name);
         let stream = self.column_dictionary.prefix_range(prefix).into_stream()?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.fast_field_codecs,
+        )
     }
 
     pub async fn read_subpath_columns_async(
@@ -191,7 +222,12 @@ impl ColumnarReader {
             .prefix_range(prefix)
             .into_stream_async()
             .await?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.fast_field_codecs,
+        )
     }
 
     /// Get all inner columns for a given JSON prefix, i.e columns for which the name starts
@@ -205,7 +241,12 @@ impl ColumnarReader {
             .column_dictionary
             .prefix_range(prefix.as_bytes())
             .into_stream()?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.fast_field_codecs,
+        )
     }
 
     /// Return the number of columns in the columnar.
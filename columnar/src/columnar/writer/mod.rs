@@ -3,6 +3,7 @@ mod column_writers;
 mod serializer;
 mod value_index;
 
+use std::collections::HashMap;
 use std::io;
 use std::net::Ipv6Addr;
 
@@ -13,8 +14,11 @@ use common::json_path_writer::JSON_END_OF_PATH;
 pub(crate) use serializer::ColumnarSerializer;
 use stacker::{Addr, ArenaHashMap, MemoryArena};
 
+use crate::column::DEFAULT_U64_CODEC_TYPES;
 use crate::column_index::{SerializableColumnIndex, SerializableOptionalIndex};
-use crate::column_values::{MonotonicallyMappableToU64, MonotonicallyMappableToU128};
+use crate::column_values::{
+    CodecType, FastFieldCodecs, MonotonicallyMappableToU64, MonotonicallyMappableToU128,
+};
 use crate::columnar::column_type::ColumnType;
 use crate::columnar::writer::column_writers::{
     ColumnWriter, NumericalColumnWriter, StrOrBytesColumnWriter,
@@ -58,6 +62,8 @@ pub struct ColumnarWriter {
     // Dictionaries used to store dictionary-encoded values.
     dictionaries: Vec<DictionaryBuilder>,
     buffers: SpareBuffers,
+    column_codecs: HashMap<Vec<u8>, CodecType>,
+    fast_field_codecs: FastFieldCodecs,
 }
 
 impl ColumnarWriter {
@@ -76,6 +82,23 @@ impl ColumnarWriter {
                 .sum::<usize>()
     }
 
+    /// Sets the codec encoding the values of the numerical, bool and date columns named
+    /// `column_name`.
+    ///
+    /// By default, the most compact of the built-in codecs is picked for each column.
+    /// A [`CodecType::Custom`] codec is looked up in the registry set with
+    /// [`ColumnarWriter::set_fast_field_codecs`].
+    pub fn set_column_codec(&mut self, column_name: &str, codec_type: CodecType) {
+        self.column_codecs
+            .insert(column_name.as_bytes().to_vec(), codec_type);
+    }
+
+    /// Sets the registry of the custom codecs selected with
+    /// [`ColumnarWriter::set_column_codec`].
+    pub fn set_fast_field_codecs(&mut self, fast_field_codecs: FastFieldCodecs) {
+        self.fast_field_codecs = fast_field_codecs;
+    }
+
     /// Records a column type. This is useful to bypass the coercion process,
     /// makes sure the empty is present in the resulting columnar, or set
     /// the `sort_values_within_row`.
@@ -294,6 +317,11 @@ impl ColumnarWriter {
                 // index).
                 continue;
             }
+            let codec_types = self
+                .column_codecs
+                .get(column_name)
+                .map(std::slice::from_ref)
+                .unwrap_or(DEFAULT_U64_CODEC_TYPES);
             match column_type {
                 ColumnType::Bool => {
                     let column_writer: ColumnWriter = self.bool_field_hash_map.read(addr);
@@ -304,6 +332,8 @@ impl ColumnarWriter {
                         cardinality,
                         num_docs,
                         column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        codec_types,
+                        Some(&self.fast_field_codecs),
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -362,6 +392,8 @@ impl ColumnarWriter {
                         num_docs,
                         numerical_type,
                         numerical_column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        codec_types,
+                        Some(&self.fast_field_codecs),
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -377,6 +409,8 @@ impl ColumnarWriter {
                         num_docs,
                         NumericalType::I64,
                         column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        codec_types,
+                        Some(&self.fast_field_codecs),
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -427,6 +461,8 @@ fn serialize_bytes_or_str_column(
         cardinality,
         num_docs,
         sort_values_within_row,
+        DEFAULT_U64_CODEC_TYPES,
+        None,
         value_index_builders,
         u64_values,
         &mut wrt,
@@ -435,11 +471,14 @@ fn serialize_bytes_or_str_column(
     Ok(())
 }
 
+#[expect(clippy::too_many_arguments)]
 fn serialize_numerical_column(
     cardinality: Cardinality,
     num_docs: RowId,
     numerical_type: NumericalType,
     op_iterator: impl Iterator<Item = ColumnOperation<NumericalValue>>,
+    codec_types: &[CodecType],
+    codecs: Option<&FastFieldCodecs>,
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -455,6 +494,8 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
+                codecs,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -466,6 +507,8 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
+                codecs,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -477,6 +520,8 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
+                codecs,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -490,6 +535,8 @@ fn serialize_bool_column(
     cardinality: Cardinality,
     num_docs: RowId,
     column_operations_it: impl Iterator<Item = ColumnOperation<bool>>,
+    codec_types: &[CodecType],
+    codecs: Option<&FastFieldCodecs>,
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -506,6 +553,8 @@ fn serialize_bool_column(
         cardinality,
         num_docs,
         false,
+        codec_types,
+        codecs,
         value_index_builders,
         u64_values,
         wrt,
@@ -581,11 +630,14 @@ fn send_to_serialize_column_mappable_to_u128<
     Ok(())
 }
 
+#[expect(clippy::too_many_arguments)]
 fn send_to_serialize_column_mappable_to_u64(
     op_iterator: impl Iterator<Item = ColumnOperation<u64>>,
     cardinality: Cardinality,
     num_rows: RowId,
     sort_values_within_row: bool,
+    codec_types: &[CodecType],
+    codecs: Option<&FastFieldCodecs>,
     value_index_builders: &mut PreallocatedIndexBuilders,
     values: &mut Vec<u64>,
     mut wrt: impl io::Write,
@@ -622,9 +674,11 @@ fn send_to_serialize_column_mappable_to_u64(
             SerializableColumnIndex::Multivalued(serializable_multivalued_index)
         }
     };
-    crate::column::serialize_column_mappable_to_u64(
+    crate::column::serialize_column_mappable_to_u64_with_codecs(
         serializable_column_index,
         &&values[..],
+        codec_types,
+        codecs,
         &mut wrt,
     )?;
     Ok(())
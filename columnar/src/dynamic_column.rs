@@ -6,9 +6,11 @@ use common::file_slice::FileSlice;
 use common::{ByteCount, DateTime, HasLen, OwnedBytes};
 
 use crate::column::{BytesColumn, Column, StrColumn};
-use crate::column_values::{StrictlyMonotonicFn, monotonic_map_column};
+use crate::column_values::{FastFieldCodecs, StrictlyMonotonicFn, monotonic_map_column};
 use crate::columnar::ColumnType;
-use crate::{Cardinality, ColumnIndex, ColumnValues, NumericalType, Version};
+use crate::{
+    Cardinality, ColumnIndex, ColumnValues, MonotonicallyMappableToU64, NumericalType, Version,
+};
 
 #[derive(Clone)]
 pub enum DynamicColumn {
@@ -233,6 +235,7 @@ pub struct DynamicColumnHandle {
     pub(crate) file_slice: FileSlice,
     pub(crate) column_type: ColumnType,
     pub(crate) format_version: Version,
+    pub(crate) fast_field_codecs: FastFieldCodecs,
 }
 
 impl DynamicColumnHandle {
@@ -277,13 +280,23 @@ impl DynamicColumnHandle {
             | ColumnType::U64
             | ColumnType::F64
             | ColumnType::DateTime => {
-                let column =
-                    crate::column::open_column_u64::<u64>(column_bytes, self.format_version)?;
+                let column = self.open_column_u64::<u64>(column_bytes)?;
                 Ok(Some(column))
             }
         }
     }
 
+    fn open_column_u64<T: MonotonicallyMappableToU64>(
+        &self,
+        column_bytes: OwnedBytes,
+    ) -> io::Result<Column<T>> {
+        crate::column::open_column_u64_with_codecs(
+            column_bytes,
+            self.format_version,
+            &self.fast_field_codecs,
+        )
+    }
+
     fn open_internal(&self, column_bytes: OwnedBytes) -> io::Result<DynamicColumn> {
         let dynamic_column: DynamicColumn = match self.column_type {
             ColumnType::Bytes => {
@@ -292,26 +305,15 @@ impl DynamicColumnHandle {
             ColumnType::Str => {
                 crate::column::open_column_str(column_bytes, self.format_version)?.into()
             }
-            ColumnType::I64 => {
-                crate::column::open_column_u64::<i64>(column_bytes, self.format_version)?.into()
-            }
-            ColumnType::U64 => {
-                crate::column::open_column_u64::<u64>(column_bytes, self.format_version)?.into()
-            }
-            ColumnType::F64 => {
-                crate::column::open_column_u64::<f64>(column_bytes, self.format_version)?.into()
-            }
-            ColumnType::Bool => {
-                crate::column::open_column_u64::<bool>(column_bytes, self.format_version)?.into()
-            }
+            ColumnType::I64 => self.open_column_u64::<i64>(column_bytes)?.into(),
+            ColumnType::U64 => self.open_column_u64::<u64>(column_bytes)?.into(),
+            ColumnType::F64 => self.open_column_u64::<f64>(column_bytes)?.into(),
+            ColumnType::Bool => self.open_column_u64::<bool>(column_bytes)?.into(),
             ColumnType::IpAddr => {
                 crate::column::open_column_u128::<Ipv6Addr>(column_bytes, self.format_version)?
                     .into()
             }
-            ColumnType::DateTime => {
-                crate::column::open_column_u64::<DateTime>(column_bytes, self.format_version)?
-                    .into()
-            }
+            ColumnType::DateTime => self.open_column_u64::<DateTime>(column_bytes)?.into(),
         };
         Ok(dynamic_column)
     }
@@ -49,6 +49,7 @@ pub use column_values::{
 pub use columnar::{
     CURRENT_VERSION, ColumnType, ColumnarReader, ColumnarWriter, HasAssociatedColumnType,
     MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, Version, merge_columnar,
+    merge_columnar_with_codecs,
 };
 use sstable::VoidSSTable;
 pub use value::{NumericalType, NumericalValue};
@@ -38,6 +38,8 @@ use super::metric::{
     MaxAggregation, MinAggregation, PercentilesAggregationReq, StatsAggregation, SumAggregation,
     TopHitsAggregationReq,
 };
+use super::Key;
+use crate::{MissingValuePolicy, TantivyError};
 
 /// The top-level aggregation request structure, which contains [`Aggregation`] and their user
 /// defined names. It is also used in buckets aggregations to define sub-aggregations.
@@ -187,6 +189,51 @@ impl AggregationVariants {
         }
     }
 
+    /// Sets how the documents without a value for the field of the aggregation are handled.
+    ///
+    /// This sets the `missing` parameter of the request: [`MissingValuePolicy::Skip`] removes
+    /// it, and [`MissingValuePolicy::Default`] sets it to the given value. The metric
+    /// aggregations require a numerical value.
+    ///
+    /// Returns an [`InvalidArgument`](TantivyError::InvalidArgument) error for
+    /// [`MissingValuePolicy::First`] and [`MissingValuePolicy::Last`], see
+    /// [`MissingValuePolicy::into_aggregation_missing`], and for a default value on an
+    /// aggregation without `missing` parameter: `range`, `histogram`, `date_histogram` and
+    /// `top_hits`.
+    pub fn set_missing_value_policy(
+        &mut self,
+        missing_value_policy: MissingValuePolicy<Key>,
+    ) -> crate::Result<()> {
+        let missing = missing_value_policy.into_aggregation_missing()?;
+        match self {
+            AggregationVariants::Terms(terms) => terms.missing = missing,
+            AggregationVariants::Cardinality(cardinality) => cardinality.missing = missing,
+            AggregationVariants::Average(avg) => avg.missing = numerical_missing(missing)?,
+            AggregationVariants::Count(count) => count.missing = numerical_missing(missing)?,
+            AggregationVariants::Max(max) => max.missing = numerical_missing(missing)?,
+            AggregationVariants::Min(min) => min.missing = numerical_missing(missing)?,
+            AggregationVariants::Stats(stats) => stats.missing = numerical_missing(missing)?,
+            AggregationVariants::ExtendedStats(extended_stats) => {
+                extended_stats.missing = numerical_missing(missing)?
+            }
+            AggregationVariants::Sum(sum) => sum.missing = numerical_missing(missing)?,
+            AggregationVariants::Percentiles(per) => per.missing = numerical_missing(missing)?,
+            AggregationVariants::Range(_)
+            | AggregationVariants::Histogram(_)
+            | AggregationVariants::DateHistogram(_)
+            | AggregationVariants::TopHits(_) => {
+                if missing.is_some() {
+                    return Err(TantivyError::InvalidArgument(
+                        "The aggregation has no `missing` parameter, only the `Skip` missing \
+                         value policy is supported."
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn as_range(&self) -> Option<&RangeAggregation> {
         match &self {
             AggregationVariants::Range(range) => Some(range),
@@ -223,6 +270,19 @@ impl AggregationVariants {
     }
 }
 
+/// Converts the `missing` parameter of a metric aggregation, which has to be numerical.
+fn numerical_missing(missing: Option<Key>) -> crate::Result<Option<f64>> {
+    match missing {
+        None => Ok(None),
+        Some(Key::F64(value)) => Ok(Some(value)),
+        Some(Key::I64(value)) => Ok(Some(value as f64)),
+        Some(Key::U64(value)) => Ok(Some(value as f64)),
+        Some(Key::Str(value)) => Err(TantivyError::InvalidArgument(format!(
+            "The missing value of a metric aggregation has to be numerical, got {value:?}."
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -5,8 +5,11 @@
 //! intermediate average results, which is the sum and the number of values. The actual average is
 //! calculated on the step from intermediate to final aggregation result tree.
 
+use std::cmp::Ordering;
+
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::bucket::GetDocCount;
 use super::metric::{
@@ -19,7 +22,64 @@ use crate::TantivyError;
 /// The final aggegation result.
 pub struct AggregationResults(pub FxHashMap<String, AggregationResult>);
 
+/// A row of the flattened aggregation results, mapping the column names to their values.
+///
+/// See [`AggregationResults::to_flat_rows`].
+pub type FlatRow = FxHashMap<String, Value>;
+
 impl AggregationResults {
+    /// Flattens the aggregation tree into rows, one row per leaf bucket, as expected by BI tools.
+    ///
+    /// The columns are named after the path of the aggregation in the tree, the names of the
+    /// aggregations being joined with `.`:
+    /// - a bucket aggregation gives a dimension column, holding the key of the bucket (its
+    ///   `key_as_string` if it has one), and a `<path>.doc_count` column.
+    /// - a single value metric gives a column holding its value. The values of a multi value
+    ///   metric, such as `stats` or `percentiles`, go in a column per value, `<path>.<value>`:
+    ///   `by_category.price_stats.avg`. The lists, such as the hits of `top_hits`, are kept in a
+    ///   single column.
+    ///
+    /// Each row holds the columns of the leaf bucket and of all its ancestor buckets, along with
+    /// the metrics computed at these levels. A bucket without sub-buckets is a leaf bucket, even
+    /// if it has (empty) bucket sub-aggregations. Sibling bucket aggregations do not combine:
+    /// each of them gives its own rows, which lack the columns of the other ones. Without any
+    /// bucket aggregation, the metrics are returned in a single row.
+    ///
+    /// A column name can be ambiguous if an aggregation is named `doc_count`, or contains `.`.
+    pub fn to_flat_rows(&self) -> crate::Result<Vec<FlatRow>> {
+        let mut rows = Vec::new();
+        if !self.0.is_empty() {
+            self.flatten_into("", &FlatRow::default(), &mut rows)?;
+        }
+        Ok(rows)
+    }
+
+    fn flatten_into(
+        &self,
+        path: &str,
+        parent_row: &FlatRow,
+        rows: &mut Vec<FlatRow>,
+    ) -> crate::Result<()> {
+        let mut names: Vec<&String> = self.0.keys().collect();
+        names.sort();
+        let mut row = parent_row.clone();
+        for &name in &names {
+            if let AggregationResult::MetricResult(metric) = &self.0[name] {
+                metric.flatten_into(column_name(path, name), &mut row)?;
+            }
+        }
+        let num_rows_before = rows.len();
+        for &name in &names {
+            if let AggregationResult::BucketResult(bucket_result) = &self.0[name] {
+                bucket_result.flatten_into(&column_name(path, name), &row, rows)?;
+            }
+        }
+        if rows.len() == num_rows_before {
+            rows.push(row);
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_bucket_count(&self) -> u64 {
         self.0
             .values()
@@ -104,6 +164,24 @@ pub enum MetricResult {
 }
 
 impl MetricResult {
+    fn flatten_into(&self, column: String, row: &mut FlatRow) -> crate::Result<()> {
+        let single_metric = match self {
+            MetricResult::Average(single_metric)
+            | MetricResult::Count(single_metric)
+            | MetricResult::Max(single_metric)
+            | MetricResult::Min(single_metric)
+            | MetricResult::Sum(single_metric)
+            | MetricResult::Cardinality(single_metric) => single_metric,
+            _ => {
+                let value = serde_json::to_value(self)?;
+                flatten_value_into(column, value, row);
+                return Ok(());
+            }
+        };
+        row.insert(column, Value::from(single_metric.value));
+        Ok(())
+    }
+
     fn get_value(&self, agg_property: &str) -> crate::Result<Option<f64>> {
         match self {
             MetricResult::Average(avg) => Ok(avg.value),
@@ -158,7 +236,88 @@ pub enum BucketResult {
     },
 }
 
+fn column_name(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+fn key_to_value(key: &Key) -> Value {
+    match key {
+        Key::Str(text) => Value::from(text.clone()),
+        Key::I64(val) => Value::from(*val),
+        Key::U64(val) => Value::from(*val),
+        Key::F64(val) => Value::from(*val),
+    }
+}
+
+// Adds the fields of the objects of `value` as separate columns, under `column`.
+fn flatten_value_into(column: String, value: Value, row: &mut FlatRow) {
+    match value {
+        Value::Object(fields) => {
+            for (field, field_value) in fields {
+                flatten_value_into(column_name(&column, &field), field_value, row);
+            }
+        }
+        value => {
+            row.insert(column, value);
+        }
+    }
+}
+
+fn flatten_bucket_into(
+    path: &str,
+    key: Value,
+    doc_count: u64,
+    sub_aggregation: &AggregationResults,
+    parent_row: &FlatRow,
+    rows: &mut Vec<FlatRow>,
+) -> crate::Result<()> {
+    let mut row = parent_row.clone();
+    row.insert(path.to_string(), key);
+    row.insert(column_name(path, "doc_count"), Value::from(doc_count));
+    sub_aggregation.flatten_into(path, &row, rows)
+}
+
 impl BucketResult {
+    fn flatten_into(
+        &self,
+        path: &str,
+        parent_row: &FlatRow,
+        rows: &mut Vec<FlatRow>,
+    ) -> crate::Result<()> {
+        match self {
+            BucketResult::Range { buckets } => {
+                for bucket in buckets.iter_sorted_by(RangeBucketEntry::cmp_range) {
+                    flatten_bucket_into(
+                        path,
+                        key_to_value(&bucket.key),
+                        bucket.doc_count,
+                        &bucket.sub_aggregation,
+                        parent_row,
+                        rows,
+                    )?;
+                }
+            }
+            BucketResult::Histogram { buckets } => {
+                let cmp_key = |left: &BucketEntry, right: &BucketEntry| {
+                    left.key.partial_cmp(&right.key).unwrap_or(Ordering::Equal)
+                };
+                for bucket in buckets.iter_sorted_by(cmp_key) {
+                    bucket.flatten_into(path, parent_row, rows)?;
+                }
+            }
+            BucketResult::Terms { buckets, .. } => {
+                for bucket in buckets {
+                    bucket.flatten_into(path, parent_row, rows)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_bucket_count(&self) -> u64 {
         match self {
             BucketResult::Range { buckets } => {
@@ -194,6 +353,21 @@ impl<T> BucketEntries<T> {
             BucketEntries::HashMap(map) => Box::new(map.values()),
         }
     }
+
+    // Iterates in the order of the vector, or in the order given by `compare` for the hashmap.
+    fn iter_sorted_by(
+        &self,
+        mut compare: impl FnMut(&T, &T) -> Ordering,
+    ) -> Box<dyn Iterator<Item = &T> + '_> {
+        match self {
+            BucketEntries::Vec(vec) => Box::new(vec.iter()),
+            BucketEntries::HashMap(map) => {
+                let mut entries: Vec<&T> = map.values().collect();
+                entries.sort_by(|left, right| compare(left, right));
+                Box::new(entries.into_iter())
+            }
+        }
+    }
 }
 
 /// This is the default entry for a bucket, which contains a key, count, and optionally
@@ -239,6 +413,26 @@ impl BucketEntry {
     pub(crate) fn get_bucket_count(&self) -> u64 {
         1 + self.sub_aggregation.get_bucket_count()
     }
+
+    fn flatten_into(
+        &self,
+        path: &str,
+        parent_row: &FlatRow,
+        rows: &mut Vec<FlatRow>,
+    ) -> crate::Result<()> {
+        let key = match &self.key_as_string {
+            Some(key_as_string) => Value::from(key_as_string.clone()),
+            None => key_to_value(&self.key),
+        };
+        flatten_bucket_into(
+            path,
+            key,
+            self.doc_count,
+            &self.sub_aggregation,
+            parent_row,
+            rows,
+        )
+    }
 }
 impl GetDocCount for &BucketEntry {
     fn doc_count(&self) -> u64 {
@@ -307,4 +501,13 @@ impl RangeBucketEntry {
     pub(crate) fn get_bucket_count(&self) -> u64 {
         1 + self.sub_aggregation.get_bucket_count()
     }
+
+    // Orders the buckets by their range, the unbounded `from` coming first.
+    fn cmp_range(&self, other: &RangeBucketEntry) -> Ordering {
+        let from = |bucket: &RangeBucketEntry| bucket.from.unwrap_or(f64::MIN);
+        let to = |bucket: &RangeBucketEntry| bucket.to.unwrap_or(f64::MAX);
+        from(self)
+            .total_cmp(&from(other))
+            .then_with(|| to(self).total_cmp(&to(other)))
+    }
 }
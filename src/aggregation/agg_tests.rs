@@ -1,16 +1,22 @@
+use std::collections::BTreeMap;
+
 use serde_json::Value;
 
-use crate::aggregation::agg_req::{Aggregation, Aggregations};
-use crate::aggregation::agg_result::AggregationResults;
+use crate::aggregation::agg_req::{Aggregation, AggregationVariants, Aggregations};
+use crate::aggregation::agg_result::{AggregationResults, FlatRow};
 use crate::aggregation::buf_collector::DOC_BLOCK_SIZE;
 use crate::aggregation::collector::AggregationCollector;
 use crate::aggregation::intermediate_agg_result::IntermediateAggregationResults;
+use crate::aggregation::metric::AverageAggregation;
 use crate::aggregation::segment_agg_result::AggregationLimitsGuard;
-use crate::aggregation::tests::{get_test_index_2_segments, get_test_index_from_values_and_terms};
-use crate::aggregation::DistributedAggregationCollector;
-use crate::query::{AllQuery, TermQuery};
+use crate::aggregation::tests::{
+    exec_request, get_test_index_2_segments, get_test_index_from_values_and_terms,
+};
+use crate::aggregation::{DistributedAggregationCollector, Key};
+use crate::collector::{MultiCollector, TopDocs};
+use crate::query::{AllQuery, EnableScoring, TermQuery};
 use crate::schema::{IndexRecordOption, Schema, FAST};
-use crate::{Index, IndexWriter, Term};
+use crate::{Executor, Index, IndexWriter, MissingValuePolicy, TantivyError, Term};
 
 fn get_avg_req(field_name: &str) -> Aggregation {
     serde_json::from_value(json!({
@@ -1008,3 +1014,299 @@ fn test_aggregation_on_json_object_mixed_numerical_segments() {
         )
     );
 }
+
+#[test]
+fn test_aggregation_with_top_docs() -> crate::Result<()> {
+    let index = get_test_index_2_segments(false)?;
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "stats": { "stats": { "field": "score" } },
+        "terms": { "terms": { "field": "text" } }
+    }))
+    .unwrap();
+    let searcher = index.reader()?.searcher();
+    let text_field = searcher.schema().get_field("text")?;
+    let query = TermQuery::new(
+        Term::from_field_text(text_field, "cool"),
+        IndexRecordOption::WithFreqs,
+    );
+
+    let separate_hits = searcher.search(&query, &TopDocs::with_limit(2))?;
+    let separate_aggs =
+        serde_json::to_value(searcher.search(&query, &get_collector(agg_req.clone()))?)?;
+    assert_eq!(separate_hits.len(), 2);
+
+    let (hits, aggs) = searcher.search(
+        &query,
+        &(TopDocs::with_limit(2), get_collector(agg_req.clone())),
+    )?;
+    assert_eq!(hits, separate_hits);
+    assert_eq!(serde_json::to_value(aggs)?, separate_aggs);
+
+    let mut multi_collector = MultiCollector::new();
+    let hits_handle = multi_collector.add_collector(TopDocs::with_limit(2));
+    let aggs_handle = multi_collector.add_collector(get_collector(agg_req));
+    let mut multi_fruit = searcher.search(&query, &multi_collector)?;
+    assert_eq!(hits_handle.extract(&mut multi_fruit), separate_hits);
+    // The aggregations cover all of the matches, not only the top docs.
+    let aggs = serde_json::to_value(aggs_handle.extract(&mut multi_fruit))?;
+    assert_eq!(aggs, separate_aggs);
+    assert!(aggs["stats"]["count"].as_u64().unwrap() > 2);
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_multithreaded() -> crate::Result<()> {
+    let segments: Vec<Vec<(f64, String)>> = (0..8)
+        .map(|segment| {
+            (0..50)
+                .map(|i| {
+                    let value = (segment * 50 + i) as f64;
+                    (value, format!("term{}", (segment + i) % 7))
+                })
+                .collect()
+        })
+        .collect();
+    let mut index = get_test_index_from_values_and_terms(false, &segments)?;
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "terms": {
+            "terms": { "field": "string_id" },
+            "aggs": { "avg_score": { "avg": { "field": "score" } } }
+        },
+        "histogram": {
+            "histogram": { "field": "score_f64", "interval": 25.0 },
+            "aggs": { "stats": { "stats": { "field": "score_i64" } } }
+        },
+        "percentiles": { "percentiles": { "field": "fraction_f64" } },
+        "cardinality": { "cardinality": { "field": "string_id" } }
+    }))
+    .unwrap();
+    let collector = get_collector(agg_req.clone());
+
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 8);
+    let search = |executor: &Executor| -> crate::Result<Value> {
+        let agg_res = searcher.search_with_executor(
+            &AllQuery,
+            &collector,
+            executor,
+            EnableScoring::disabled_from_searcher(&searcher),
+        )?;
+        Ok(serde_json::to_value(agg_res)?)
+    };
+    let serial_res = search(&Executor::single_thread())?;
+    let parallel_res = search(&Executor::multi_thread(4, "agg-test-")?)?;
+    assert_eq!(serial_res, parallel_res);
+    assert_eq!(serial_res["histogram"]["buckets"][0]["doc_count"], 25);
+
+    // The executor of the index is used by `Searcher::search`.
+    index.set_multithread_executor(4)?;
+    let searcher = index.reader()?.searcher();
+    let agg_res = searcher.search(&AllQuery, &collector)?;
+    assert_eq!(serde_json::to_value(agg_res)?, serial_res);
+
+    let distributed_collector = DistributedAggregationCollector::from_aggs(
+        agg_req.clone(),
+        AggregationLimitsGuard::default(),
+    );
+    let intermediate_res = searcher.search(&AllQuery, &distributed_collector)?;
+    let agg_res = intermediate_res.into_final_result(agg_req, Default::default())?;
+    assert_eq!(serde_json::to_value(agg_res)?, serial_res);
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_to_flat_rows() -> crate::Result<()> {
+    let values: Vec<(f64, String)> = [(1.0, "a"), (2.0, "a"), (12.0, "a"), (3.0, "b")]
+        .into_iter()
+        .chain([(15.0, "b"), (17.0, "b"), (19.0, "b")])
+        .map(|(value, term)| (value, term.to_string()))
+        .collect();
+    let index = get_test_index_from_values_and_terms(true, &[values])?;
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "stats": { "stats": { "field": "score" } },
+        "terms": {
+            "terms": { "field": "string_id" },
+            "aggs": {
+                "max_score": { "max": { "field": "score" } },
+                "histogram": {
+                    "histogram": { "field": "score_f64", "interval": 10.0 },
+                    "aggs": { "avg_score": { "avg": { "field": "score" } } }
+                }
+            }
+        }
+    }))
+    .unwrap();
+    let searcher = index.reader()?.searcher();
+    let agg_res: AggregationResults = searcher.search(&AllQuery, &get_collector(agg_req))?;
+
+    let stats = json!({
+        "stats.count": 7,
+        "stats.sum": 69.0,
+        "stats.min": 1.0,
+        "stats.max": 19.0,
+        "stats.avg": 69.0 / 7.0,
+    });
+    let row = |term: &str, term_doc_count: u64, max_score: f64, histogram: Value| {
+        let mut row: FlatRow = serde_json::from_value(stats.clone()).unwrap();
+        row.insert("terms".to_string(), json!(term));
+        row.insert("terms.doc_count".to_string(), json!(term_doc_count));
+        row.insert("terms.max_score".to_string(), json!(max_score));
+        row.extend(serde_json::from_value::<FlatRow>(histogram).unwrap());
+        row
+    };
+    let expected = vec![
+        row(
+            "b",
+            4,
+            19.0,
+            json!({ "terms.histogram": 0.0, "terms.histogram.doc_count": 1,
+                    "terms.histogram.avg_score": 3.0 }),
+        ),
+        row(
+            "b",
+            4,
+            19.0,
+            json!({ "terms.histogram": 10.0, "terms.histogram.doc_count": 3,
+                    "terms.histogram.avg_score": 17.0 }),
+        ),
+        row(
+            "a",
+            3,
+            12.0,
+            json!({ "terms.histogram": 0.0, "terms.histogram.doc_count": 2,
+                    "terms.histogram.avg_score": 1.5 }),
+        ),
+        row(
+            "a",
+            3,
+            12.0,
+            json!({ "terms.histogram": 10.0, "terms.histogram.doc_count": 1,
+                    "terms.histogram.avg_score": 12.0 }),
+        ),
+    ];
+    assert_eq!(agg_res.to_flat_rows()?, expected);
+    assert!(AggregationResults::default().to_flat_rows()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_to_flat_rows_keyed_range() -> crate::Result<()> {
+    let values: Vec<(f64, String)> = [1.0, 7.0, 12.0]
+        .into_iter()
+        .map(|value| (value, "a".to_string()))
+        .collect();
+    let index = get_test_index_from_values_and_terms(true, &[values])?;
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "range": {
+            "range": {
+                "field": "score",
+                "ranges": [{ "to": 5.0 }, { "from": 5.0, "to": 10.0 }, { "from": 10.0 }],
+                "keyed": true
+            }
+        }
+    }))
+    .unwrap();
+    let searcher = index.reader()?.searcher();
+    let agg_res: AggregationResults = searcher.search(&AllQuery, &get_collector(agg_req))?;
+    // The rows follow the ranges, and not the order of their keys: `10-*` sorts before `5-10`.
+    let keys: Vec<Value> = agg_res
+        .to_flat_rows()?
+        .into_iter()
+        .map(|mut row| row.remove("range").unwrap())
+        .collect();
+    assert_eq!(keys, vec![json!("*-5"), json!("5-10"), json!("10-*")]);
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_missing_value_policy() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let score = schema_builder.add_f64_field("score", FAST);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(score => 10.0))?;
+    index_writer.add_document(doc!(score => 20.0))?;
+    index_writer.add_document(doc!())?;
+    index_writer.commit()?;
+
+    let agg_with_missing =
+        |mut agg: AggregationVariants, missing: MissingValuePolicy<Key>| -> crate::Result<Value> {
+            agg.set_missing_value_policy(missing)?;
+            let agg_req: Aggregations = vec![(
+                "agg".to_string(),
+                Aggregation {
+                    agg,
+                    sub_aggregation: Default::default(),
+                },
+            )]
+            .into_iter()
+            .collect();
+            let res = exec_request(agg_req, &index)?;
+            Ok(res["agg"].clone())
+        };
+    let avg =
+        AggregationVariants::Average(AverageAggregation::from_field_name("score".to_string()));
+    let avg_with_missing = |missing| -> crate::Result<Value> {
+        Ok(agg_with_missing(avg.clone(), missing)?["value"].clone())
+    };
+    assert_eq!(avg_with_missing(MissingValuePolicy::Skip)?, 15.0);
+    assert_eq!(
+        avg_with_missing(MissingValuePolicy::Default(Key::F64(0.0)))?,
+        10.0
+    );
+    assert_eq!(
+        avg_with_missing(MissingValuePolicy::Default(Key::U64(30)))?,
+        20.0
+    );
+    for missing in [MissingValuePolicy::First, MissingValuePolicy::Last] {
+        let err = avg_with_missing(missing).unwrap_err();
+        assert!(matches!(err, TantivyError::InvalidArgument(_)));
+        assert!(err.to_string().contains("not `First` nor `Last`"));
+    }
+    let err =
+        avg_with_missing(MissingValuePolicy::Default(Key::Str("zero".to_string()))).unwrap_err();
+    assert!(matches!(err, TantivyError::InvalidArgument(_)));
+
+    // The missing documents get a bucket of their own.
+    let terms: AggregationVariants = serde_json::from_value(json!({
+        "terms": { "field": "score" }
+    }))?;
+    let doc_counts = |missing| -> crate::Result<BTreeMap<String, u64>> {
+        let res = agg_with_missing(terms.clone(), missing)?;
+        Ok(res["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| {
+                (
+                    bucket["key"].to_string(),
+                    bucket["doc_count"].as_u64().unwrap(),
+                )
+            })
+            .collect())
+    };
+    assert_eq!(
+        doc_counts(MissingValuePolicy::Skip)?,
+        BTreeMap::from([("10".to_string(), 1), ("20".to_string(), 1)])
+    );
+    assert_eq!(
+        doc_counts(MissingValuePolicy::Default(Key::Str("none".to_string())))?,
+        BTreeMap::from([
+            ("10".to_string(), 1),
+            ("20".to_string(), 1),
+            ("\"none\"".to_string(), 1)
+        ])
+    );
+    assert!(doc_counts(MissingValuePolicy::Last).is_err());
+
+    // The histogram has no missing parameter.
+    let mut histogram: AggregationVariants = serde_json::from_value(json!({
+        "histogram": { "field": "score", "interval": 10.0 }
+    }))?;
+    histogram.set_missing_value_policy(MissingValuePolicy::Skip)?;
+    let err = histogram
+        .set_missing_value_policy(MissingValuePolicy::Default(Key::F64(0.0)))
+        .unwrap_err();
+    assert!(matches!(err, TantivyError::InvalidArgument(_)));
+    Ok(())
+}
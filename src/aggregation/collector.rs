@@ -20,6 +20,25 @@ pub const DEFAULT_MEMORY_LIMIT: u64 = 500_000_000;
 /// Collector for aggregations.
 ///
 /// The collector collects all aggregations by the underlying aggregation request.
+///
+/// # Concurrency
+///
+/// The segments are collected independently of each other, so the aggregation runs on as
+/// many threads as the executor of the search offers: see [`Index::set_multithread_executor`]
+/// for [`Searcher::search`], or pass the executor explicitly to
+/// [`Searcher::search_with_executor`]. Each segment gets its own [`AggregationSegmentCollector`],
+/// which holds the buckets and metrics of this segment only and is never shared between
+/// threads. The results of the segments are merged once all of them are collected, in the
+/// order of the segments, so the result does not depend on the executor.
+///
+/// The only state shared by the segments is the [`AggregationLimitsGuard`]: its memory
+/// counter is atomic, and the memory limit applies to the sum of the memory used by all of
+/// the segments. With a multithreaded executor, several segments are in memory at the same
+/// time, so an aggregation close to the limit may fail where a single threaded one would not.
+///
+/// [`Index::set_multithread_executor`]: crate::Index::set_multithread_executor
+/// [`Searcher::search`]: crate::Searcher::search
+/// [`Searcher::search_with_executor`]: crate::Searcher::search_with_executor
 pub struct AggregationCollector {
     agg: Aggregations,
     limits: AggregationLimitsGuard,
@@ -43,6 +62,8 @@ impl AggregationCollector {
 /// AggregationCollector returns `IntermediateAggregationResults` and not the final
 /// `AggregationResults`, so that results from different indices can be merged and then converted
 /// into the final `AggregationResults` via the `into_final_result()` method.
+///
+/// Segments can be collected concurrently, as for [`AggregationCollector`].
 pub struct DistributedAggregationCollector {
     agg: Aggregations,
     limits: AggregationLimitsGuard,
@@ -43,6 +43,7 @@ pub use sum::*;
 pub use top_hits::*;
 
 use crate::schema::OwnedValue;
+use crate::DocAddress;
 
 /// Single-metric aggregations use this common result structure.
 ///
@@ -103,6 +104,15 @@ pub struct TopHitsVecEntry {
     #[serde(rename = "docvalue_fields")]
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub doc_value_fields: HashMap<String, OwnedValue>,
+
+    /// The address of the document in the [`Searcher`](crate::Searcher) that ran the
+    /// aggregation, e.g. to fetch its stored fields with
+    /// [`Searcher::doc`](crate::Searcher::doc).
+    ///
+    /// It is not serialized, and is `None` for deserialized results, as it is meaningless
+    /// outside of the searcher.
+    #[serde(skip)]
+    pub doc_address: Option<DocAddress>,
 }
 
 /// The top_hits metric aggregation results a list of top hits by sort criteria.
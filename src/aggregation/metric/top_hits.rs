@@ -33,13 +33,19 @@ use crate::{DocAddress, DocId, SegmentOrdinal};
 /// used as a sub-aggregation, inside a `terms` aggregation or a `filters` aggregation,
 /// for example.
 ///
-/// Note that this aggregator does not return the actual document addresses, but
+/// The serialized result does not contain the actual document addresses, but
 /// rather a list of the values of the fields that were requested to be retrieved.
 /// These values can be specified in the `docvalue_fields` parameter, which can include
 /// a list of fast fields to be retrieved. At the moment, only fast fields are supported
 /// but it is possible that we support the `fields` parameter to retrieve any stored
 /// field in the future.
 ///
+/// The [`TopHitsVecEntry::doc_address`] of each hit gives access to the document in the
+/// searcher that ran the aggregation, so that its stored fields can be fetched with
+/// [`Searcher::doc`](crate::Searcher::doc).
+///
+/// Aggregations are collected without scoring, so hits can only be sorted by fast fields.
+///
 /// The following example demonstrates a request for the top_hits aggregation:
 /// ```JSON
 /// {
@@ -475,6 +481,7 @@ impl TopHitsTopNComputer {
                     .into_iter()
                     .map(|(k, v)| (k, v.into()))
                     .collect(),
+                doc_address: Some(doc.doc),
             })
             .collect();
 
@@ -818,14 +825,17 @@ mod tests {
                     super::TopHitsVecEntry {
                         sort: vec![docs[0].feature.sorts[0].value],
                         doc_value_fields: Default::default(),
+                        doc_address: Some(docs[0].doc),
                     },
                     super::TopHitsVecEntry {
                         sort: vec![docs[1].feature.sorts[0].value],
                         doc_value_fields: Default::default(),
+                        doc_address: Some(docs[1].doc),
                     },
                     super::TopHitsVecEntry {
                         sort: vec![docs[2].feature.sorts[0].value],
                         doc_value_fields: Default::default(),
+                        doc_address: Some(docs[2].doc),
                     },
                 ]
             }
@@ -913,4 +923,90 @@ mod tests {
     fn test_aggregation_top_hits_multi_segment() -> crate::Result<()> {
         test_aggregation_top_hits(false)
     }
+
+    #[test]
+    fn test_aggregation_top_hits_per_bucket_doc_address() -> crate::Result<()> {
+        use crate::aggregation::agg_result::{AggregationResult, BucketResult, MetricResult};
+        use crate::aggregation::Key;
+        use crate::schema::{Schema, Value, FAST, STORED, STRING};
+        use crate::{Index, IndexWriter, TantivyDocument};
+
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let title = schema_builder.add_text_field("title", STORED);
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category => "shoe", title => "boot", price => 120u64))?;
+        index_writer.add_document(doc!(category => "hat", title => "cap", price => 15u64))?;
+        index_writer.add_document(doc!(category => "shoe", title => "sandal", price => 40u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(category => "shoe", title => "sneaker", price => 90u64))?;
+        index_writer.add_document(doc!(category => "hat", title => "beret", price => 30u64))?;
+        index_writer.commit()?;
+
+        let aggs: Aggregations = serde_json::from_value(json!({
+            "categories": {
+                "terms": { "field": "category" },
+                "aggs": {
+                    "top_products": {
+                        "top_hits": {
+                            "size": 2,
+                            "sort": [{ "price": "desc" }]
+                        }
+                    }
+                }
+            }
+        }))?;
+        let collector = AggregationCollector::from_aggs(aggs, Default::default());
+        let searcher = index.reader()?.searcher();
+        let agg_res: AggregationResults = searcher.search(&AllQuery, &collector)?;
+
+        let AggregationResult::BucketResult(BucketResult::Terms { buckets, .. }) =
+            &agg_res.0["categories"]
+        else {
+            panic!("expected a terms aggregation result");
+        };
+        let mut top_titles_per_category: Vec<(String, Vec<String>)> = Vec::new();
+        for bucket in buckets {
+            let Key::Str(category) = &bucket.key else {
+                panic!("expected a string key");
+            };
+            let AggregationResult::MetricResult(MetricResult::TopHits(top_hits)) =
+                &bucket.sub_aggregation.0["top_products"]
+            else {
+                panic!("expected a top hits result");
+            };
+            let titles = top_hits
+                .hits
+                .iter()
+                .map(|hit| {
+                    let doc: TantivyDocument = searcher.doc(hit.doc_address.unwrap())?;
+                    Ok(doc.get_first(title).unwrap().as_str().unwrap().to_string())
+                })
+                .collect::<crate::Result<Vec<String>>>()?;
+            top_titles_per_category.push((category.clone(), titles));
+        }
+        top_titles_per_category.sort();
+        assert_eq!(
+            top_titles_per_category,
+            vec![
+                (
+                    "hat".to_string(),
+                    vec!["beret".to_string(), "cap".to_string()]
+                ),
+                (
+                    "shoe".to_string(),
+                    vec!["boot".to_string(), "sneaker".to_string()]
+                ),
+            ]
+        );
+        // The document addresses are not serialized.
+        let agg_res_json = serde_json::to_value(&agg_res)?;
+        assert_eq!(
+            agg_res_json["categories"]["buckets"][0]["top_products"]["hits"][0],
+            json!({ "sort": [120] })
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+
+use columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+
+use crate::collector::{Collector, SegmentCollector, TopNComputer};
+use crate::schema::Type;
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// A bucket of the [`HistogramWithTopHitsCollector`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistogramBucketWithTopHits {
+    /// The lower bound of the bucket, a multiple of the bucket size. The bucket holds the
+    /// values of `[key, key + bucket_size)`.
+    pub key: f64,
+    /// The number of matching documents in the bucket.
+    pub doc_count: u64,
+    /// The best scoring documents of the bucket, sorted by decreasing score.
+    pub top_hits: Vec<(Score, DocAddress)>,
+}
+
+/// `HistogramWithTopHitsCollector` buckets the matching documents by the value of a numerical
+/// fast field, and computes in the same pass the number of documents and the best scoring
+/// documents of each bucket.
+///
+/// The buckets have the same width, and are aligned on the multiples of the bucket size: the
+/// bucket of a value `v` starts at `floor(v / bucket_size) * bucket_size`, as for a price
+/// distribution. Only the buckets holding matching documents are returned, sorted by key.
+///
+/// The field has to be a `u64`, `i64` or `f64` fast field. The documents without value, or
+/// with a NaN or infinite value, are not counted, and only the first value of the documents
+/// with several values is considered.
+///
+/// ```rust
+/// use tantivy::collector::HistogramWithTopHitsCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let price = schema_builder.add_f64_field("price", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "red shoe", price => 12.5f64))?;
+/// index_writer.add_document(doc!(title => "red red shoe", price => 17.0f64))?;
+/// index_writer.add_document(doc!(title => "red hat", price => 31.0f64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("red")?;
+/// let collector = HistogramWithTopHitsCollector::new("price".to_string(), 10.0, 1);
+/// let buckets = searcher.search(&query, &collector)?;
+/// assert_eq!(buckets.len(), 2);
+/// assert_eq!(buckets[0].key, 10.0);
+/// assert_eq!(buckets[0].doc_count, 2);
+/// assert_eq!(buckets[0].top_hits.len(), 1);
+/// assert_eq!(buckets[1].key, 30.0);
+/// assert_eq!(buckets[1].doc_count, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct HistogramWithTopHitsCollector {
+    field: String,
+    bucket_size: f64,
+    per_bucket_hits: usize,
+}
+
+impl HistogramWithTopHitsCollector {
+    /// Creates a collector bucketing the documents by the value of `field`, in buckets of
+    /// `bucket_size`, and keeping the `per_bucket_hits` best scoring documents of each bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_size` is not strictly positive.
+    pub fn new(
+        field: String,
+        bucket_size: f64,
+        per_bucket_hits: usize,
+    ) -> HistogramWithTopHitsCollector {
+        assert!(
+            bucket_size > 0.0,
+            "The bucket size has to be strictly positive, got {bucket_size}."
+        );
+        HistogramWithTopHitsCollector {
+            field,
+            bucket_size,
+            per_bucket_hits,
+        }
+    }
+}
+
+struct SegmentBucket {
+    doc_count: u64,
+    top_hits: TopNComputer<Score, DocId>,
+}
+
+/// The segment collector of the [`HistogramWithTopHitsCollector`].
+pub struct HistogramWithTopHitsSegmentCollector {
+    column_opt: Option<(Column<u64>, ColumnType)>,
+    bucket_size: f64,
+    per_bucket_hits: usize,
+    segment_ord: SegmentOrdinal,
+    buckets: BTreeMap<i64, SegmentBucket>,
+}
+
+fn to_f64(value: u64, column_type: ColumnType) -> f64 {
+    match column_type {
+        ColumnType::I64 => i64::from_u64(value) as f64,
+        ColumnType::F64 => f64::from_u64(value),
+        _ => value as f64,
+    }
+}
+
+impl SegmentCollector for HistogramWithTopHitsSegmentCollector {
+    /// The buckets of the segment, along with their ordinal: the key divided by the bucket size.
+    type Fruit = Vec<(i64, HistogramBucketWithTopHits)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let Some((column, column_type)) = &self.column_opt else {
+            return;
+        };
+        let Some(value) = column.first(doc) else {
+            return;
+        };
+        let value = to_f64(value, *column_type);
+        if !value.is_finite() {
+            return;
+        }
+        let bucket_ord = (value / self.bucket_size).floor() as i64;
+        let per_bucket_hits = self.per_bucket_hits;
+        let bucket = self
+            .buckets
+            .entry(bucket_ord)
+            .or_insert_with(|| SegmentBucket {
+                doc_count: 0,
+                top_hits: TopNComputer::new(per_bucket_hits),
+            });
+        bucket.doc_count += 1;
+        if per_bucket_hits > 0 {
+            bucket.top_hits.push(score, doc);
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let segment_ord = self.segment_ord;
+        self.buckets
+            .into_iter()
+            .map(|(bucket_ord, bucket)| {
+                let bucket_with_top_hits = HistogramBucketWithTopHits {
+                    key: bucket_ord as f64 * self.bucket_size,
+                    doc_count: bucket.doc_count,
+                    top_hits: bucket
+                        .top_hits
+                        .into_sorted_vec()
+                        .into_iter()
+                        .map(|hit| (hit.feature, DocAddress::new(segment_ord, hit.doc)))
+                        .collect(),
+                };
+                (bucket_ord, bucket_with_top_hits)
+            })
+            .collect()
+    }
+}
+
+impl Collector for HistogramWithTopHitsCollector {
+    type Fruit = Vec<HistogramBucketWithTopHits>;
+    type Child = HistogramWithTopHitsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let schema = segment.schema();
+        let field = schema.get_field(&self.field)?;
+        let field_type = schema.get_field_entry(field).field_type();
+        if !matches!(field_type.value_type(), Type::U64 | Type::I64 | Type::F64)
+            || !field_type.is_fast()
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64, i64 or f64 fast field.",
+                self.field
+            )));
+        }
+        let column_opt = segment.fast_fields().u64_lenient_for_type(
+            Some(&[ColumnType::U64, ColumnType::I64, ColumnType::F64]),
+            &self.field,
+        )?;
+        Ok(HistogramWithTopHitsSegmentCollector {
+            column_opt,
+            bucket_size: self.bucket_size,
+            per_bucket_hits: self.per_bucket_hits,
+            segment_ord: segment_local_id,
+            buckets: BTreeMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        // The scores are only needed to select the top hits.
+        self.per_bucket_hits > 0
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(i64, HistogramBucketWithTopHits)>>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut buckets: BTreeMap<i64, (u64, TopNComputer<Score, DocAddress>)> = BTreeMap::new();
+        for (bucket_ord, bucket) in segment_fruits.into_iter().flatten() {
+            let (doc_count, top_hits) = buckets
+                .entry(bucket_ord)
+                .or_insert_with(|| (0, TopNComputer::new(self.per_bucket_hits)));
+            *doc_count += bucket.doc_count;
+            for (score, doc_address) in bucket.top_hits {
+                top_hits.push(score, doc_address);
+            }
+        }
+        Ok(buckets
+            .into_iter()
+            .map(
+                |(bucket_ord, (doc_count, top_hits))| HistogramBucketWithTopHits {
+                    key: bucket_ord as f64 * self.bucket_size,
+                    doc_count,
+                    top_hits: top_hits
+                        .into_sorted_vec()
+                        .into_iter()
+                        .map(|hit| (hit.feature, hit.doc))
+                        .collect(),
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::HistogramWithTopHitsCollector;
+    use crate::collector::{Collector, Count, TopDocs};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Schema, FAST, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Score};
+
+    #[test]
+    fn test_histogram_with_top_hits() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let price = schema_builder.add_i64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let docs = [
+            ("shoe", 5i64),
+            ("shoe shoe", 7),
+            ("shoe shoe shoe", 12),
+            ("hat", 14),
+            ("shoe", 19),
+            ("shoe shoe", -3),
+            ("shoe hat", 25),
+        ];
+        for (i, (title_value, price_value)) in docs.iter().enumerate() {
+            index_writer.add_document(doc!(title => *title_value, price => *price_value))?;
+            if i % 3 == 2 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.add_document(doc!(title => "shoe without price"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("shoe")?;
+
+        let collector = HistogramWithTopHitsCollector::new("price".to_string(), 10.0, 2);
+        let buckets = searcher.search(&query, &collector)?;
+        let keys: Vec<f64> = buckets.iter().map(|bucket| bucket.key).collect();
+        assert_eq!(keys, vec![-10.0, 0.0, 10.0, 20.0]);
+        let doc_counts: Vec<u64> = buckets.iter().map(|bucket| bucket.doc_count).collect();
+        assert_eq!(doc_counts, vec![1, 2, 2, 1]);
+        assert_eq!(
+            doc_counts.iter().sum::<u64>() + 1,
+            searcher.search(&query, &Count)? as u64
+        );
+
+        // The top hits of a bucket are the best matches among the documents of the bucket.
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(100))?;
+        let price_of = |doc_address: DocAddress| {
+            searcher
+                .segment_reader(doc_address.segment_ord)
+                .fast_fields()
+                .i64("price")
+                .unwrap()
+                .first(doc_address.doc_id)
+        };
+        for bucket in &buckets {
+            let expected: Vec<(Score, DocAddress)> = top_docs
+                .iter()
+                .copied()
+                .filter(|(_, doc_address)| {
+                    price_of(*doc_address).is_some_and(|price_value| {
+                        (price_value as f64 / 10.0).floor() * 10.0 == bucket.key
+                    })
+                })
+                .take(2)
+                .collect();
+            assert_eq!(bucket.top_hits, expected);
+        }
+        let bucket_10 = &buckets[2];
+        assert!(bucket_10.top_hits[0].0 > bucket_10.top_hits[1].0);
+        assert_eq!(price_of(bucket_10.top_hits[0].1), Some(12));
+
+        // The buckets can be limited to their counts.
+        let collector = HistogramWithTopHitsCollector::new("price".to_string(), 10.0, 0);
+        assert!(!collector.requires_scoring());
+        let buckets = searcher.search(&AllQuery, &collector)?;
+        let doc_counts: HashMap<i64, u64> = buckets
+            .iter()
+            .map(|bucket| (bucket.key as i64, bucket.doc_count))
+            .collect();
+        assert_eq!(
+            doc_counts,
+            HashMap::from([(-10, 1), (0, 2), (10, 3), (20, 1)])
+        );
+        assert!(buckets.iter().all(|bucket| bucket.top_hits.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_with_top_hits_skips_non_finite_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for price_value in [0.25, f64::NAN, f64::INFINITY] {
+            index_writer.add_document(doc!(price => price_value))?;
+        }
+        index_writer.commit()?;
+        for price_value in [0.28, f64::NEG_INFINITY, 0.31] {
+            index_writer.add_document(doc!(price => price_value))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let collector = HistogramWithTopHitsCollector::new("price".to_string(), 0.1, 1);
+        let buckets = searcher.search(&AllQuery, &collector)?;
+        let doc_counts: Vec<(f64, u64)> = buckets
+            .iter()
+            .map(|bucket| (bucket.key, bucket.doc_count))
+            .collect();
+        assert_eq!(doc_counts, vec![(2.0 * 0.1, 2), (3.0 * 0.1, 1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_with_top_hits_requires_numerical_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "shoe"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let collector = HistogramWithTopHitsCollector::new("title".to_string(), 10.0, 1);
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        let collector = HistogramWithTopHitsCollector::new("missing".to_string(), 10.0, 1);
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use columnar::{Column, DynamicColumn, HasAssociatedColumnType};
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// The `MinMaxCollector` computes the minimum and the maximum values of a fast field
+/// over the matching documents, in a single pass.
+///
+/// The fruit is a `(min, max)` pair. Both are `None` if no matching document
+/// has a value for the field.
+///
+/// For multivalued fields, all of the values of each matching document are considered.
+///
+/// ```rust
+/// use tantivy::collector::MinMaxCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let price = schema_builder.add_u64_field("price", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind", price => 30_200u64))?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib", price => 29_240u64))?;
+/// index_writer.add_document(doc!(title => "A Dairy Cow", price => 21_240u64))?;
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl", price => 20_120u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+/// let query = query_parser.parse_query("diary")?;
+/// let (min, max) = searcher.search(&query, &MinMaxCollector::<u64>::new("price"))?;
+/// assert_eq!(min, Some(20_120u64));
+/// assert_eq!(max, Some(29_240u64));
+/// # Ok(())
+/// # }
+/// ```
+pub struct MinMaxCollector<T> {
+    field: String,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> MinMaxCollector<T> {
+    /// Creates a new `MinMaxCollector` for the given fast field.
+    pub fn new(field: impl ToString) -> MinMaxCollector<T> {
+        MinMaxCollector {
+            field: field.to_string(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+fn merge_min_max<T: PartialOrd + Copy>(
+    (left_min, left_max): (Option<T>, Option<T>),
+    (right_min, right_max): (Option<T>, Option<T>),
+) -> (Option<T>, Option<T>) {
+    let min = match (left_min, right_min) {
+        (Some(left), Some(right)) => Some(if right < left { right } else { left }),
+        (left, right) => left.or(right),
+    };
+    let max = match (left_max, right_max) {
+        (Some(left), Some(right)) => Some(if right > left { right } else { left }),
+        (left, right) => left.or(right),
+    };
+    (min, max)
+}
+
+impl<T> Collector for MinMaxCollector<T>
+where
+    T: HasAssociatedColumnType + PartialOrd + Copy + Debug + Send + Sync + 'static,
+    DynamicColumn: Into<Option<Column<T>>>,
+{
+    type Fruit = (Option<T>, Option<T>);
+
+    type Child = MinMaxSegmentCollector<T>;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<MinMaxSegmentCollector<T>> {
+        let column_opt = segment_reader.fast_fields().column_opt(&self.field)?;
+        Ok(MinMaxSegmentCollector {
+            column_opt,
+            min: None,
+            max: None,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<(Option<T>, Option<T>)>,
+    ) -> crate::Result<(Option<T>, Option<T>)> {
+        Ok(segment_fruits.into_iter().fold((None, None), merge_min_max))
+    }
+}
+
+/// Segment collector for the [`MinMaxCollector`].
+pub struct MinMaxSegmentCollector<T> {
+    column_opt: Option<Column<T>>,
+    min: Option<T>,
+    max: Option<T>,
+}
+
+impl<T> SegmentCollector for MinMaxSegmentCollector<T>
+where T: PartialOrd + Copy + Debug + Send + Sync + 'static
+{
+    type Fruit = (Option<T>, Option<T>);
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let Some(column) = self.column_opt.as_ref() else {
+            return;
+        };
+        for value in column.values_for_doc(doc) {
+            if self.min.map(|min| value < min).unwrap_or(true) {
+                self.min = Some(value);
+            }
+            if self.max.map(|max| value > max).unwrap_or(true) {
+                self.max = Some(value);
+            }
+        }
+    }
+
+    fn harvest(self) -> (Option<T>, Option<T>) {
+        (self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MinMaxCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_min_max_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING);
+        let price = schema_builder.add_i64_field("price", FAST);
+        let sizes = schema_builder.add_u64_field("sizes", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category => "shoe", price => 40i64, sizes => 38u64))?;
+        index_writer.add_document(doc!(category => "hat", price => -5i64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(
+            category => "shoe",
+            price => 12i64,
+            sizes => 36u64,
+            sizes => 44u64,
+        ))?;
+        index_writer.add_document(doc!(category => "shoe"))?;
+        index_writer.add_document(doc!(category => "hat", price => 100i64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let shoe_query = TermQuery::new(
+            Term::from_field_text(category, "shoe"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(
+            searcher.search(&shoe_query, &MinMaxCollector::<i64>::new("price"))?,
+            (Some(12i64), Some(40i64))
+        );
+        assert_eq!(
+            searcher.search(&shoe_query, &MinMaxCollector::<u64>::new("sizes"))?,
+            (Some(36u64), Some(44u64))
+        );
+        assert_eq!(
+            searcher.search(&AllQuery, &MinMaxCollector::<i64>::new("price"))?,
+            (Some(-5i64), Some(100i64))
+        );
+        let hat_query = TermQuery::new(
+            Term::from_field_text(category, "hat"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(
+            searcher.search(&hat_query, &MinMaxCollector::<u64>::new("sizes"))?,
+            (None, None)
+        );
+        let no_match_query = TermQuery::new(
+            Term::from_field_text(category, "sock"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(
+            searcher.search(&no_match_query, &MinMaxCollector::<i64>::new("price"))?,
+            (None, None)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// The `MinScoreCollector` passes on to the wrapped collector only the documents scored at
+/// least `min_score` by the query.
+///
+/// This keeps the low relevance matches out of the results, instead of ranking them last,
+/// whatever the wrapped collector: a [`Count`](crate::collector::Count) wrapped this way
+/// counts the confident matches only. The query gets scored, even if the wrapped collector
+/// does not need the scores.
+///
+/// To rank the documents by score, [`TopDocs::with_min_score`](crate::collector::TopDocs)
+/// is faster, as it skips the blocks of documents which cannot reach `min_score`.
+///
+/// ```rust
+/// use tantivy::collector::{Count, MinScoreCollector};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "diary"))?;
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl, and of her family"))?;
+/// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// // The shorter title scores higher.
+/// let confident_count = MinScoreCollector::new(Count, 0.6);
+/// assert_eq!(searcher.search(&query, &confident_count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MinScoreCollector<TCollector> {
+    collector: TCollector,
+    min_score: Option<Score>,
+}
+
+impl<TCollector: Collector> MinScoreCollector<TCollector> {
+    /// Wraps `collector`, dropping the documents scored below `min_score`.
+    pub fn new(collector: TCollector, min_score: Score) -> MinScoreCollector<TCollector> {
+        MinScoreCollector {
+            collector,
+            min_score: Some(min_score),
+        }
+    }
+
+    /// Wraps `collector`, dropping the documents scored below `min_score` if any. Without a
+    /// minimum, the collector is left as is.
+    pub(crate) fn with_optional_min_score(
+        collector: TCollector,
+        min_score: Option<Score>,
+    ) -> MinScoreCollector<TCollector> {
+        MinScoreCollector {
+            collector,
+            min_score,
+        }
+    }
+}
+
+impl<TCollector: Collector> Collector for MinScoreCollector<TCollector> {
+    type Fruit = TCollector::Fruit;
+
+    type Child = MinScoreSegmentCollector<TCollector::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        Ok(MinScoreSegmentCollector {
+            segment_collector: self.collector.for_segment(segment_local_id, segment)?,
+            min_score: self.min_score,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.min_score.is_some() || self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<TCollector::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        self.collector.merge_fruits(segment_fruits)
+    }
+}
+
+/// The segment collector of the [`MinScoreCollector`].
+pub struct MinScoreSegmentCollector<TSegmentCollector> {
+    segment_collector: TSegmentCollector,
+    min_score: Option<Score>,
+}
+
+impl<TSegmentCollector: SegmentCollector> SegmentCollector
+    for MinScoreSegmentCollector<TSegmentCollector>
+{
+    type Fruit = TSegmentCollector::Fruit;
+
+    #[inline]
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.min_score.is_none_or(|min_score| score >= min_score) {
+            self.segment_collector.collect(doc, score);
+        }
+    }
+
+    fn collect_block(&mut self, docs: &[DocId]) {
+        // Blocks of unscored documents are only collected if the collector does not require
+        // scoring, which is never the case with a minimum score: the documents of the block have
+        // no score to compare to it.
+        debug_assert!(
+            self.min_score.is_none(),
+            "unscored documents collected with a minimum score"
+        );
+        if self.min_score.is_none() {
+            self.segment_collector.collect_block(docs);
+        }
+    }
+
+    fn harvest(self) -> TSegmentCollector::Fruit {
+        self.segment_collector.harvest()
+    }
+}
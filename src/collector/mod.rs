@@ -91,6 +91,20 @@ pub use self::count_collector::Count;
 mod histogram_collector;
 pub use histogram_collector::HistogramCollector;
 
+mod histogram_with_top_hits_collector;
+pub use self::histogram_with_top_hits_collector::{
+    HistogramBucketWithTopHits, HistogramWithTopHitsCollector, HistogramWithTopHitsSegmentCollector,
+};
+
+mod min_max_collector;
+pub use self::min_max_collector::{MinMaxCollector, MinMaxSegmentCollector};
+
+mod weighted_avg_collector;
+pub use self::weighted_avg_collector::{WeightedAvgCollector, WeightedAvgSegmentCollector};
+
+mod segment_match_collector;
+pub use self::segment_match_collector::SegmentMatchCollector;
+
 mod multi_collector;
 pub use self::multi_collector::{FruitHandle, MultiCollector, MultiFruit};
 
@@ -100,6 +114,19 @@ mod top_score_collector;
 pub use self::top_collector::ComparableDoc;
 pub use self::top_score_collector::{TopDocs, TopNComputer};
 
+mod top_docs_with_payload;
+
+mod top_docs_with_bm25_factors;
+pub use self::top_docs_with_bm25_factors::{
+    DocWithBm25Factors, TermBm25Factors, TopDocsWithBm25Factors,
+    TopDocsWithBm25FactorsSegmentCollector,
+};
+
+mod top_docs_with_matches;
+pub use self::top_docs_with_matches::{
+    DocWithMatches, TermMatch, TopDocsWithMatches, TopDocsWithMatchesSegmentCollector,
+};
+
 mod custom_score_top_collector;
 pub use self::custom_score_top_collector::{CustomScorer, CustomSegmentScorer};
 
@@ -115,6 +142,9 @@ pub use self::docset_collector::DocSetCollector;
 mod filter_collector_wrapper;
 pub use self::filter_collector_wrapper::{BytesFilterCollector, FilterCollector};
 
+mod min_score_collector;
+pub use self::min_score_collector::{MinScoreCollector, MinScoreSegmentCollector};
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast_rs::Downcast {}
@@ -161,6 +161,19 @@ impl<TFruit: Fruit> FruitHandle<TFruit> {
 /// # Ok(())
 /// # }
 /// ```
+///
+/// # Hits and aggregations
+///
+/// All of the collectors are fed from the same traversal of the matching documents: for each
+/// segment, the query is scored once, and each match is passed to every collector along with
+/// its score. A page of hits and the aggregations over all of the matches can hence be
+/// computed by a single search, by adding a [`TopDocs`](crate::collector::TopDocs) and an
+/// [`AggregationCollector`](crate::aggregation::AggregationCollector) to the same
+/// `MultiCollector`, or by grouping them in a tuple. The `TopDocs` only keeps the best
+/// matches, while the aggregations still see all of them.
+///
+/// When none of the collectors requires the scores, the matches are passed by blocks, without
+/// computing their scores.
 #[expect(clippy::type_complexity)]
 #[derive(Default)]
 pub struct MultiCollector<'a> {
@@ -250,6 +263,12 @@ impl SegmentCollector for MultiCollectorChild {
         }
     }
 
+    fn collect_block(&mut self, docs: &[DocId]) {
+        for child in &mut self.children {
+            child.collect_block(docs);
+        }
+    }
+
     fn harvest(self) -> MultiFruit {
         MultiFruit {
             sub_fruits: self
@@ -0,0 +1,153 @@
+use super::Collector;
+use crate::collector::SegmentCollector;
+use crate::query::Weight;
+use crate::{DocId, DocSet, Score, SegmentOrdinal, SegmentReader, TERMINATED};
+
+/// `SegmentMatchCollector` reports, for each segment, whether it contains
+/// at least one document matching the query.
+///
+/// The fruit is a `Vec<bool>` indexed by segment ordinal.
+///
+/// Unlike [`Count`](crate::collector::Count), the scan of a segment stops as soon as
+/// one alive matching document is found. This makes it a cheap probe to decide which
+/// segments are worth querying further.
+///
+/// ```rust
+/// use tantivy::collector::SegmentMatchCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.commit()?;
+///
+/// let reader = index.reader()?;
+/// let searcher = reader.searcher();
+///
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+/// let query = query_parser.parse_query("diary")?;
+/// let segment_matches: Vec<bool> = searcher.search(&query, &SegmentMatchCollector)?;
+///
+/// assert_eq!(segment_matches, vec![true]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SegmentMatchCollector;
+
+impl Collector for SegmentMatchCollector {
+    type Fruit = Vec<bool>;
+
+    type Child = SegmentMatchSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _: SegmentOrdinal,
+        _: &SegmentReader,
+    ) -> crate::Result<SegmentMatchSegmentCollector> {
+        Ok(SegmentMatchSegmentCollector::default())
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_matches: Vec<bool>) -> crate::Result<Vec<bool>> {
+        Ok(segment_matches)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        _segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<bool> {
+        let mut scorer = weight.scorer(reader, 1.0)?;
+        let alive_bitset_opt = reader.alive_bitset();
+        let mut doc = scorer.doc();
+        while doc != TERMINATED {
+            if alive_bitset_opt.is_none_or(|alive_bitset| alive_bitset.is_alive(doc)) {
+                return Ok(true);
+            }
+            doc = scorer.advance();
+        }
+        Ok(false)
+    }
+}
+
+#[derive(Default)]
+pub struct SegmentMatchSegmentCollector {
+    matched: bool,
+}
+
+impl SegmentCollector for SegmentMatchSegmentCollector {
+    type Fruit = bool;
+
+    fn collect(&mut self, _: DocId, _: Score) {
+        self.matched = true;
+    }
+
+    fn harvest(self) -> bool {
+        self.matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SegmentMatchCollector;
+    use crate::collector::{Collector, Count};
+    use crate::query::{EnableScoring, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_segment_match_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.add_document(doc!(tag => "b"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(tag => "b"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.commit()?;
+        // The only match of this segment gets deleted.
+        index_writer.add_document(doc!(tag => "c"))?;
+        index_writer.add_document(doc!(tag => "a"))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(tag, "c"));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 4);
+        let query = TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic);
+        let segment_matches = searcher.search(&query, &SegmentMatchCollector)?;
+        assert_eq!(
+            segment_matches.iter().filter(|matched| **matched).count(),
+            3
+        );
+        let query_c = TermQuery::new(Term::from_field_text(tag, "c"), IndexRecordOption::Basic);
+        assert_eq!(
+            searcher.search(&query_c, &SegmentMatchCollector)?,
+            vec![false; 4]
+        );
+
+        // The probe agrees with a full count on every segment.
+        let weight = query.weight(EnableScoring::disabled_from_searcher(&searcher))?;
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let count =
+                Count.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)?;
+            assert_eq!(segment_matches[segment_ord], count > 0);
+        }
+        Ok(())
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use super::top_score_collector::TopNComputer;
 use crate::index::SegmentReader;
-use crate::{DocAddress, DocId, SegmentOrdinal};
+use crate::{DocAddress, DocId, Order, SegmentOrdinal};
 
 /// Contains a feature (field, score, etc.) of a document along with the document address.
 ///
@@ -71,9 +71,31 @@ impl<T: PartialOrd, D: PartialOrd, const R: bool> Eq for ComparableDoc<T, D, R>
 pub(crate) struct TopCollector<T> {
     pub limit: usize,
     pub offset: usize,
+    /// The order of the documents with the same feature.
+    pub doc_order: Order,
     _marker: PhantomData<T>,
 }
 
+/// Maps a doc id to the key its ties are broken with, and the key back to the doc id.
+///
+/// Flipping all of the bits of a `u32` reverses its order: ranking the flipped doc ids in
+/// ascending order ranks the doc ids in descending order.
+#[inline]
+fn flip_doc_id(doc: DocId, reverse: bool) -> DocId {
+    if reverse {
+        !doc
+    } else {
+        doc
+    }
+}
+
+fn flip_doc_address(doc_address: DocAddress, reverse: bool) -> DocAddress {
+    DocAddress {
+        segment_ord: flip_doc_id(doc_address.segment_ord, reverse),
+        doc_id: flip_doc_id(doc_address.doc_id, reverse),
+    }
+}
+
 impl<T> TopCollector<T>
 where T: PartialOrd + Clone
 {
@@ -86,6 +108,7 @@ where T: PartialOrd + Clone
         Self {
             limit,
             offset: 0,
+            doc_order: Order::Asc,
             _marker: PhantomData,
         }
     }
@@ -99,6 +122,12 @@ where T: PartialOrd + Clone
         self
     }
 
+    /// Sets the order of the documents with the same feature.
+    pub fn and_doc_order(mut self, doc_order: Order) -> TopCollector<T> {
+        self.doc_order = doc_order;
+        self
+    }
+
     pub fn merge_fruits(
         &self,
         children: Vec<Vec<(T, DocAddress)>>,
@@ -106,10 +135,11 @@ where T: PartialOrd + Clone
         if self.limit == 0 {
             return Ok(Vec::new());
         }
+        let reverse = self.doc_order.is_desc();
         let mut top_collector: TopNComputer<_, _> = TopNComputer::new(self.limit + self.offset);
         for child_fruit in children {
             for (feature, doc) in child_fruit {
-                top_collector.push(feature, doc);
+                top_collector.push(feature, flip_doc_address(doc, reverse));
             }
         }
 
@@ -117,7 +147,7 @@ where T: PartialOrd + Clone
             .into_sorted_vec()
             .into_iter()
             .skip(self.offset)
-            .map(|cdoc| (cdoc.feature, cdoc.doc))
+            .map(|cdoc| (cdoc.feature, flip_doc_address(cdoc.doc, reverse)))
             .collect())
     }
 
@@ -126,7 +156,9 @@ where T: PartialOrd + Clone
         segment_id: SegmentOrdinal,
         _: &SegmentReader,
     ) -> TopSegmentCollector<F> {
-        TopSegmentCollector::new(segment_id, self.limit + self.offset)
+        let mut segment_collector = TopSegmentCollector::new(segment_id, self.limit + self.offset);
+        segment_collector.reverse_doc_order = self.doc_order.is_desc();
+        segment_collector
     }
 
     /// Create a new TopCollector with the same limit and offset.
@@ -138,6 +170,7 @@ where T: PartialOrd + Clone
         TopCollector {
             limit: self.limit,
             offset: self.offset,
+            doc_order: self.doc_order,
             _marker: PhantomData,
         }
     }
@@ -154,6 +187,7 @@ pub(crate) struct TopSegmentCollector<T> {
     /// have top-semantics instead of bottom semantics.
     topn_computer: TopNComputer<T, DocId>,
     segment_ord: u32,
+    reverse_doc_order: bool,
 }
 
 impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
@@ -161,6 +195,7 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
         TopSegmentCollector {
             topn_computer: TopNComputer::new(limit),
             segment_ord,
+            reverse_doc_order: false,
         }
     }
 }
@@ -168,6 +203,7 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
 impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
     pub fn harvest(self) -> Vec<(T, DocAddress)> {
         let segment_ord = self.segment_ord;
+        let reverse = self.reverse_doc_order;
         self.topn_computer
             .into_sorted_vec()
             .into_iter()
@@ -176,7 +212,7 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
                     comparable_doc.feature,
                     DocAddress {
                         segment_ord,
-                        doc_id: comparable_doc.doc,
+                        doc_id: flip_doc_id(comparable_doc.doc, reverse),
                     },
                 )
             })
@@ -189,7 +225,8 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
     /// will compare the lowest scoring item with the given one and keep whichever is greater.
     #[inline]
     pub fn collect(&mut self, doc: DocId, feature: T) {
-        self.topn_computer.push(feature, doc);
+        self.topn_computer
+            .push(feature, flip_doc_id(doc, self.reverse_doc_order));
     }
 }
 
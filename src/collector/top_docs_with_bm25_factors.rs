@@ -0,0 +1,404 @@
+use std::collections::BTreeSet;
+
+use crate::collector::top_docs_with_payload::{
+    HitPayload, ScoredHit, SegmentHitPayload, TopDocsWithPayload,
+    TopDocsWithPayloadSegmentCollector,
+};
+use crate::collector::{Collector, SegmentCollector};
+use crate::fieldnorm::FieldNormReader;
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::{bm25_term_score, idf, Bm25StatisticsProvider, Query, Weight};
+use crate::schema::IndexRecordOption;
+use crate::{DocAddress, DocId, DocSet, Score, SegmentOrdinal, SegmentReader, Term};
+
+/// The inputs of the BM25 score of a term in a document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermBm25Factors {
+    /// The term of the query.
+    pub term: Term,
+    /// The number of occurrences of the term in the field of the document.
+    ///
+    /// It is 1 if the field was indexed without term frequencies.
+    pub term_freq: u32,
+    /// The field-length norm: the number of tokens of the field of the document.
+    ///
+    /// Unless the field norms are exact, this number is rounded, as it is for the score.
+    pub fieldnorm: u32,
+    /// The inverse document frequency of the term.
+    pub idf: Score,
+    /// The average number of tokens of the field, over all of the documents.
+    pub average_fieldnorm: Score,
+}
+
+impl TermBm25Factors {
+    /// Returns the BM25 score of the term in the document:
+    ///
+    /// `idf * (1 + k1) * tf / (tf + k1 * (1 - b + b * fieldnorm / average_fieldnorm))`, with
+    /// `k1 = 1.2` and `b = 0.75`.
+    pub fn score(&self) -> Score {
+        bm25_term_score(
+            self.idf,
+            self.average_fieldnorm,
+            self.fieldnorm,
+            self.term_freq,
+        )
+    }
+}
+
+/// A hit of the [`TopDocsWithBm25Factors`] collector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocWithBm25Factors {
+    /// The score of the document.
+    pub score: Score,
+    /// The address of the document.
+    pub doc_address: DocAddress,
+    /// The BM25 factors of the terms of the query found in the document, sorted by term.
+    pub factors: Vec<TermBm25Factors>,
+}
+
+/// The `TopDocsWithBm25Factors` collector works as [`TopDocs`](crate::collector::TopDocs), and also
+/// reports, for each of the top documents and each term of the query it contains, the inputs of the
+/// BM25 score: the term frequency, the field-length norm and the idf.
+///
+/// This is meant for relevance tuning: contrary to [`Query::explain`], which runs the query
+/// again for each document, the factors are read during the collection, for the top documents
+/// of each segment only.
+///
+/// The terms are the ones reported by [`Query::query_terms`], and the factors are the ones of
+/// each term taken separately. For a disjunction of term queries, the score of a document is
+/// the sum of the scores of its terms, see [`TermBm25Factors::score`]. Boosts, and the scoring
+/// of phrases as a whole, are not reflected in the factors.
+///
+/// ```rust
+/// use tantivy::collector::TopDocsWithBm25Factors;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+/// let collector = TopDocsWithBm25Factors::with_limit(&searcher, &query, 10)?;
+/// let hits = searcher.search(&query, &collector)?;
+/// assert_eq!(hits.len(), 1);
+/// let factors = &hits[0].factors[0];
+/// assert_eq!(factors.term_freq, 1);
+/// assert_eq!(factors.fieldnorm, 4);
+/// assert_eq!(factors.score(), hits[0].score);
+/// # Ok(())
+/// # }
+/// ```
+pub struct TopDocsWithBm25Factors {
+    collector: TopDocsWithPayload<QueryTermBm25Factors>,
+}
+
+impl TopDocsWithBm25Factors {
+    /// Creates a collector keeping track of the top `limit` documents for `query`, along with
+    /// the BM25 factors of the terms of `query` in these documents.
+    ///
+    /// The idf and the average field norms are computed from `statistics`, typically the
+    /// searcher the collector is used with.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn with_limit(
+        statistics: &dyn Bm25StatisticsProvider,
+        query: &dyn Query,
+        limit: usize,
+    ) -> crate::Result<TopDocsWithBm25Factors> {
+        let mut query_terms = BTreeSet::new();
+        query.query_terms(&mut |term, _| {
+            query_terms.insert(term.clone());
+        });
+        let total_num_docs = statistics.total_num_docs()?;
+        let mut terms = Vec::with_capacity(query_terms.len());
+        for term in query_terms {
+            let total_num_tokens = statistics.total_num_tokens(term.field())?;
+            let doc_freq = statistics.doc_freq(&term)?;
+            terms.push(TermStatistics {
+                idf: idf(doc_freq, total_num_docs),
+                average_fieldnorm: total_num_tokens as Score / total_num_docs as Score,
+                term,
+            });
+        }
+        let query_term_factors = QueryTermBm25Factors { terms };
+        Ok(TopDocsWithBm25Factors {
+            collector: TopDocsWithPayload::with_limit(limit, query_term_factors),
+        })
+    }
+
+    /// Skip the first "offset" documents when collecting.
+    ///
+    /// See [`TopDocs::and_offset`](crate::collector::TopDocs::and_offset).
+    #[must_use]
+    pub fn and_offset(self, offset: usize) -> TopDocsWithBm25Factors {
+        TopDocsWithBm25Factors {
+            collector: self.collector.and_offset(offset),
+        }
+    }
+}
+
+impl ScoredHit for DocWithBm25Factors {
+    fn score(&self) -> Score {
+        self.score
+    }
+
+    fn doc_address(&self) -> DocAddress {
+        self.doc_address
+    }
+}
+
+#[derive(Clone)]
+struct TermStatistics {
+    term: Term,
+    idf: Score,
+    average_fieldnorm: Score,
+}
+
+/// Reads the BM25 factors of the terms of the query.
+struct QueryTermBm25Factors {
+    terms: Vec<TermStatistics>,
+}
+
+impl HitPayload for QueryTermBm25Factors {
+    type Child = SegmentTermBm25Factors;
+
+    fn segment_payload(&self, reader: &SegmentReader) -> crate::Result<SegmentTermBm25Factors> {
+        let mut term_postings = Vec::new();
+        for term_statistics in &self.terms {
+            let field = term_statistics.term.field();
+            let inverted_index = reader.inverted_index(field)?;
+            if let Some(postings) =
+                inverted_index.read_postings(&term_statistics.term, IndexRecordOption::WithFreqs)?
+            {
+                let fieldnorm_reader = reader
+                    .fieldnorms_readers()
+                    .get_field(field)?
+                    .unwrap_or_else(|| FieldNormReader::constant(reader.max_doc(), 1));
+                term_postings.push(TermPostings {
+                    term_statistics: term_statistics.clone(),
+                    postings,
+                    fieldnorm_reader,
+                });
+            }
+        }
+        Ok(SegmentTermBm25Factors { term_postings })
+    }
+}
+
+struct TermPostings {
+    term_statistics: TermStatistics,
+    postings: SegmentPostings,
+    fieldnorm_reader: FieldNormReader,
+}
+
+struct SegmentTermBm25Factors {
+    term_postings: Vec<TermPostings>,
+}
+
+impl SegmentHitPayload for SegmentTermBm25Factors {
+    type Hit = DocWithBm25Factors;
+
+    fn hit(&mut self, score: Score, doc_address: DocAddress) -> DocWithBm25Factors {
+        let doc = doc_address.doc_id;
+        let mut factors = Vec::new();
+        for term_postings in &mut self.term_postings {
+            let postings = &mut term_postings.postings;
+            if postings.doc() <= doc && postings.seek(doc) == doc {
+                let term_statistics = &term_postings.term_statistics;
+                let fieldnorm_reader = &term_postings.fieldnorm_reader;
+                // Reads the fieldnorm the way the scorer does.
+                let fieldnorm = fieldnorm_reader.exact_fieldnorm(doc).unwrap_or_else(|| {
+                    FieldNormReader::id_to_fieldnorm(fieldnorm_reader.fieldnorm_id(doc))
+                });
+                factors.push(TermBm25Factors {
+                    term: term_statistics.term.clone(),
+                    term_freq: postings.term_freq(),
+                    fieldnorm,
+                    idf: term_statistics.idf,
+                    average_fieldnorm: term_statistics.average_fieldnorm,
+                });
+            }
+        }
+        DocWithBm25Factors {
+            score,
+            doc_address,
+            factors,
+        }
+    }
+}
+
+impl Collector for TopDocsWithBm25Factors {
+    type Fruit = Vec<DocWithBm25Factors>;
+
+    type Child = TopDocsWithBm25FactorsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let collector = self.collector.for_segment(segment_local_id, reader)?;
+        Ok(TopDocsWithBm25FactorsSegmentCollector { collector })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<DocWithBm25Factors>>,
+    ) -> crate::Result<Self::Fruit> {
+        self.collector.merge_fruits(segment_fruits)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<Vec<DocWithBm25Factors>> {
+        self.collector.collect_segment(weight, segment_ord, reader)
+    }
+}
+
+/// Segment collector for the [`TopDocsWithBm25Factors`] collector.
+pub struct TopDocsWithBm25FactorsSegmentCollector {
+    collector: TopDocsWithPayloadSegmentCollector<SegmentTermBm25Factors>,
+}
+
+impl SegmentCollector for TopDocsWithBm25FactorsSegmentCollector {
+    type Fruit = Vec<DocWithBm25Factors>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> Vec<DocWithBm25Factors> {
+        self.collector.harvest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopDocsWithBm25Factors;
+    use crate::collector::TopDocs;
+    use crate::fieldnorm::FieldNormPrecision;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions, STRING, TEXT};
+    use crate::{Index, IndexWriter, Score, Term};
+
+    #[test]
+    fn test_top_docs_with_bm25_factors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "rust rust rust", tag => "lang"))?;
+        index_writer.add_document(doc!(title => "python is not rust", tag => "lang"))?;
+        index_writer.add_document(doc!(title => "a snake"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "the rust belt", tag => "place"))?;
+        index_writer.add_document(doc!(title => "python"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("rust python")?;
+        let collector = TopDocsWithBm25Factors::with_limit(&searcher, &query, 3)?;
+        let hits = searcher.search(&query, &collector)?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert_eq!(hits.len(), 3);
+        // 5 docs, with 3 + 4 + 2 + 3 + 1 tokens.
+        let average_fieldnorm = 13.0 / 5.0;
+        let idf = |doc_freq: u64| -> Score {
+            (1.0 + (5.0 - doc_freq as Score + 0.5) / (doc_freq as Score + 0.5)).ln()
+        };
+        for (hit, (score, doc_address)) in hits.iter().zip(top_docs) {
+            assert_eq!(hit.doc_address, doc_address);
+            assert_eq!(hit.score, score);
+            let mut reconstructed_score = 0.0;
+            for factors in &hit.factors {
+                let doc_freq = searcher.doc_freq(&factors.term)?;
+                assert_eq!(factors.idf, idf(doc_freq));
+                assert_eq!(factors.average_fieldnorm, average_fieldnorm);
+                let tf = factors.term_freq as Score;
+                let norm =
+                    1.2 * (1.0 - 0.75 + 0.75 * factors.fieldnorm as Score / average_fieldnorm);
+                let term_score = factors.idf * (1.0 + 1.2) * tf / (tf + norm);
+                assert!((term_score - factors.score()).abs() < 1e-5);
+                reconstructed_score += term_score;
+            }
+            assert!((reconstructed_score - hit.score).abs() < 1e-5);
+        }
+        // The document with both terms.
+        let both_terms = hits.iter().find(|hit| hit.factors.len() == 2).unwrap();
+        assert_eq!(
+            both_terms.factors[0].term,
+            Term::from_field_text(title, "python")
+        );
+        assert_eq!(both_terms.factors[0].term_freq, 1);
+        assert_eq!(both_terms.factors[0].fieldnorm, 4);
+        assert_eq!(
+            both_terms.factors[1].term,
+            Term::from_field_text(title, "rust")
+        );
+        let rust_rust_rust = hits
+            .iter()
+            .find(|hit| hit.factors.iter().any(|factors| factors.term_freq == 3))
+            .unwrap();
+        assert_eq!(rust_rust_rust.factors[0].fieldnorm, 3);
+
+        // Fields without term frequencies.
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("tag:lang")?;
+        let collector = TopDocsWithBm25Factors::with_limit(&searcher, &query, 10)?;
+        let hits = searcher.search(&query, &collector)?;
+        assert_eq!(hits.len(), 2);
+        for hit in hits {
+            assert_eq!(hit.factors.len(), 1);
+            assert_eq!(hit.factors[0].term_freq, 1);
+            assert!((hit.factors[0].score() - hit.score).abs() < 1e-5);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_with_bm25_factors_exact_fieldnorms() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(
+            "title",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default().set_fieldnorm_precision(FieldNormPrecision::Exact),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for num_tokens in [1_000, 1_010] {
+            let text = format!("rust{}", " filler".repeat(num_tokens - 1));
+            index_writer.add_document(doc!(title => text))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+        let collector = TopDocsWithBm25Factors::with_limit(&searcher, &query, 2)?;
+        let hits = searcher.search(&query, &collector)?;
+        let fieldnorms: Vec<u32> = hits.iter().map(|hit| hit.factors[0].fieldnorm).collect();
+        // Both fieldnorms would be quantized to the same value.
+        assert_eq!(fieldnorms, [1_000, 1_010]);
+        for hit in &hits {
+            assert!((hit.factors[0].score() - hit.score).abs() < 1e-5);
+        }
+        assert!(hits[0].score > hits[1].score);
+        Ok(())
+    }
+}
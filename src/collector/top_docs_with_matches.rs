@@ -0,0 +1,396 @@
+use std::collections::BTreeSet;
+
+use crate::collector::top_docs_with_payload::{
+    HitPayload, ScoredHit, SegmentHitPayload, TopDocsWithPayload,
+    TopDocsWithPayloadSegmentCollector,
+};
+use crate::collector::{Collector, SegmentCollector};
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::{Query, Weight};
+use crate::schema::IndexRecordOption;
+use crate::{DocAddress, DocId, DocSet, Score, SegmentOrdinal, SegmentReader, Term};
+
+/// The positions at which a term of the query occurs in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermMatch {
+    /// The term of the query.
+    pub term: Term,
+    /// The positions of the term in the document, in increasing order.
+    ///
+    /// Positions are token positions, as produced by the tokenizer of the field. They are empty
+    /// if the field was indexed without positions.
+    pub positions: Vec<u32>,
+}
+
+/// A hit of the [`TopDocsWithMatches`] collector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocWithMatches {
+    /// The score of the document.
+    pub score: Score,
+    /// The address of the document.
+    pub doc_address: DocAddress,
+    /// The terms of the query found in the document, sorted by term.
+    pub matches: Vec<TermMatch>,
+    /// The number of distinct terms of the query.
+    pub num_query_terms: usize,
+}
+
+impl DocWithMatches {
+    /// Returns the number of distinct terms of the query found in the document.
+    pub fn num_matched_terms(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Returns the fraction of the distinct terms of the query found in the document,
+    /// between 0 and 1.
+    ///
+    /// For instance, a document matching 3 of the 5 terms of a query has a coverage of 0.6.
+    /// The coverage is 0 if the query has no terms.
+    pub fn coverage(&self) -> f32 {
+        if self.num_query_terms == 0 {
+            return 0.0;
+        }
+        self.num_matched_terms() as f32 / self.num_query_terms as f32
+    }
+}
+
+/// The `TopDocsWithMatches` collector works as [`TopDocs`](crate::collector::TopDocs), and also
+/// reports, for each of the top documents, which terms of the query it contains, along with their
+/// positions.
+///
+/// The terms are the ones reported by [`Query::query_terms`]. For each of them, all of its
+/// positions in the document are reported, including the ones that do not take part in the
+/// match. For instance, for a phrase query, occurrences of a term outside of the phrase are
+/// reported as well.
+///
+/// The positions are extracted during the collection, for the top documents of each segment
+/// only. Reporting positions requires the fields of the terms to be indexed with positions.
+/// Positions are token positions, not byte offsets: byte offsets can be obtained by tokenizing
+/// the stored text again, as [`SnippetGenerator`](crate::snippet::SnippetGenerator) does.
+///
+/// The number of terms found also gives the coverage of the query by each document, see
+/// [`DocWithMatches::coverage`]. This is useful to explain results, or to rank documents
+/// matching more of the terms of a disjunction first. Terms are counted once, even if the
+/// query contains them several times.
+///
+/// ```rust
+/// use tantivy::collector::TopDocsWithMatches;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+/// let hits = searcher.search(&query, &TopDocsWithMatches::with_limit(&query, 10))?;
+/// assert_eq!(hits.len(), 1);
+/// assert_eq!(hits[0].matches[0].term, Term::from_field_text(title, "diary"));
+/// assert_eq!(hits[0].matches[0].positions, vec![1]);
+/// assert_eq!(hits[0].coverage(), 1.0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct TopDocsWithMatches {
+    collector: TopDocsWithPayload<QueryTermMatches>,
+}
+
+impl TopDocsWithMatches {
+    /// Creates a collector keeping track of the top `limit` documents for `query`, along with
+    /// the positions of the terms of `query` in these documents.
+    ///
+    /// The collector is meant to be used for the search of `query`.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn with_limit(query: &dyn Query, limit: usize) -> TopDocsWithMatches {
+        let mut terms = BTreeSet::new();
+        query.query_terms(&mut |term, _| {
+            terms.insert(term.clone());
+        });
+        let query_term_matches = QueryTermMatches {
+            terms: terms.into_iter().collect(),
+        };
+        TopDocsWithMatches {
+            collector: TopDocsWithPayload::with_limit(limit, query_term_matches),
+        }
+    }
+
+    /// Skip the first "offset" documents when collecting.
+    ///
+    /// See [`TopDocs::and_offset`](crate::collector::TopDocs::and_offset).
+    #[must_use]
+    pub fn and_offset(self, offset: usize) -> TopDocsWithMatches {
+        TopDocsWithMatches {
+            collector: self.collector.and_offset(offset),
+        }
+    }
+}
+
+impl ScoredHit for DocWithMatches {
+    fn score(&self) -> Score {
+        self.score
+    }
+
+    fn doc_address(&self) -> DocAddress {
+        self.doc_address
+    }
+}
+
+/// Reads the positions of the terms of the query.
+struct QueryTermMatches {
+    terms: Vec<Term>,
+}
+
+impl HitPayload for QueryTermMatches {
+    type Child = SegmentTermMatches;
+
+    fn segment_payload(&self, reader: &SegmentReader) -> crate::Result<SegmentTermMatches> {
+        let mut term_postings = Vec::new();
+        for term in &self.terms {
+            let inverted_index = reader.inverted_index(term.field())?;
+            if let Some(postings) =
+                inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+            {
+                term_postings.push((term.clone(), postings));
+            }
+        }
+        Ok(SegmentTermMatches {
+            term_postings,
+            num_query_terms: self.terms.len(),
+        })
+    }
+}
+
+struct SegmentTermMatches {
+    term_postings: Vec<(Term, SegmentPostings)>,
+    num_query_terms: usize,
+}
+
+impl SegmentHitPayload for SegmentTermMatches {
+    type Hit = DocWithMatches;
+
+    fn hit(&mut self, score: Score, doc_address: DocAddress) -> DocWithMatches {
+        let doc = doc_address.doc_id;
+        let mut matches = Vec::new();
+        for (term, postings) in &mut self.term_postings {
+            if postings.doc() <= doc && postings.seek(doc) == doc {
+                let mut positions = Vec::new();
+                postings.positions(&mut positions);
+                matches.push(TermMatch {
+                    term: term.clone(),
+                    positions,
+                });
+            }
+        }
+        DocWithMatches {
+            score,
+            doc_address,
+            matches,
+            num_query_terms: self.num_query_terms,
+        }
+    }
+}
+
+impl Collector for TopDocsWithMatches {
+    type Fruit = Vec<DocWithMatches>;
+
+    type Child = TopDocsWithMatchesSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let collector = self.collector.for_segment(segment_local_id, reader)?;
+        Ok(TopDocsWithMatchesSegmentCollector { collector })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<DocWithMatches>>) -> crate::Result<Self::Fruit> {
+        self.collector.merge_fruits(segment_fruits)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<Vec<DocWithMatches>> {
+        self.collector.collect_segment(weight, segment_ord, reader)
+    }
+}
+
+/// Segment collector for the [`TopDocsWithMatches`] collector.
+pub struct TopDocsWithMatchesSegmentCollector {
+    collector: TopDocsWithPayloadSegmentCollector<SegmentTermMatches>,
+}
+
+impl SegmentCollector for TopDocsWithMatchesSegmentCollector {
+    type Fruit = Vec<DocWithMatches>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> Vec<DocWithMatches> {
+        self.collector.harvest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TermMatch, TopDocsWithMatches};
+    use crate::collector::TopDocs;
+    use crate::query::{PhraseQuery, Query, QueryParser};
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_top_docs_with_matches_phrase_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "a b c"))?;
+        index_writer.add_document(doc!(text => "the quick brown fox jumps over the lazy dog"))?;
+        index_writer.add_document(doc!(text => "quick as a fox, quick brown fox"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let term = |word: &str| Term::from_field_text(text, word);
+        let query = PhraseQuery::new(vec![term("quick"), term("brown"), term("fox")]);
+        let collector = TopDocsWithMatches::with_limit(&query, 10);
+        let mut hits = searcher.search(&query, &collector)?;
+        hits.sort_by_key(|hit| hit.doc_address);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(hits.len(), top_docs.len());
+        assert_eq!(hits.len(), 2);
+
+        assert_eq!(hits[0].doc_address.doc_id, 1);
+        assert_eq!(
+            hits[0].matches,
+            vec![
+                TermMatch {
+                    term: term("brown"),
+                    positions: vec![2],
+                },
+                TermMatch {
+                    term: term("fox"),
+                    positions: vec![3],
+                },
+                TermMatch {
+                    term: term("quick"),
+                    positions: vec![1],
+                },
+            ]
+        );
+        // The positions of the terms outside of the phrase are reported as well.
+        assert_eq!(hits[1].doc_address.doc_id, 2);
+        assert_eq!(
+            hits[1].matches,
+            vec![
+                TermMatch {
+                    term: term("brown"),
+                    positions: vec![5],
+                },
+                TermMatch {
+                    term: term("fox"),
+                    positions: vec![3, 6],
+                },
+                TermMatch {
+                    term: term("quick"),
+                    positions: vec![0, 4],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_with_matches_multiple_segments() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "rust rust rust", tag => "lang"))?;
+        index_writer.add_document(doc!(title => "python", tag => "lang"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "rust belt", tag => "place"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = QueryParser::for_index(&index, vec![title, tag]).parse_query("rust")?;
+        let hits = searcher.search(&query, &TopDocsWithMatches::with_limit(&query, 1))?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].score, hits[0].doc_address), top_docs[0]);
+        assert_eq!(
+            hits[0].matches,
+            vec![TermMatch {
+                term: Term::from_field_text(title, "rust"),
+                positions: vec![0, 1, 2],
+            }]
+        );
+
+        // The tag is indexed without positions.
+        let query = QueryParser::for_index(&index, vec![title, tag]).parse_query("tag:lang")?;
+        let hits = searcher.search(&query, &TopDocsWithMatches::with_limit(&query, 10))?;
+        assert_eq!(hits.len(), 2);
+        for hit in hits {
+            assert_eq!(
+                hit.matches,
+                vec![TermMatch {
+                    term: Term::from_field_text(tag, "lang"),
+                    positions: Vec::new(),
+                }]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_with_matches_coverage() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "alpha beta gamma delta epsilon"))?;
+        index_writer.add_document(doc!(text => "alpha beta zeta"))?;
+        index_writer.add_document(doc!(text => "gamma gamma gamma"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "beta delta epsilon"))?;
+        index_writer.add_document(doc!(text => "zeta"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        // The repeated term is only counted once.
+        let query = QueryParser::for_index(&index, vec![text])
+            .parse_query("alpha beta gamma delta epsilon alpha")?;
+        let hits = searcher.search(&query, &TopDocsWithMatches::with_limit(&query, 10))?;
+        let mut coverages: Vec<(usize, usize, f32)> = hits
+            .iter()
+            .map(|hit| (hit.num_matched_terms(), hit.num_query_terms, hit.coverage()))
+            .collect();
+        coverages.sort_by_key(|(num_matched_terms, _, _)| std::cmp::Reverse(*num_matched_terms));
+        assert_eq!(
+            coverages,
+            vec![(5, 5, 1.0), (3, 5, 0.6), (2, 5, 0.4), (1, 5, 0.2)]
+        );
+        let mut query_terms = Vec::new();
+        query.query_terms(&mut |term, _| query_terms.push(term.clone()));
+        assert_eq!(query_terms.len(), 6);
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::collector::top_score_collector::TopScoreSegmentCollector;
+use crate::collector::{Collector, SegmentCollector, TopDocs};
+use crate::query::Weight;
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// A hit reported by the [`TopDocsWithPayload`] collector.
+pub(crate) trait ScoredHit: Send + 'static {
+    /// Returns the score of the document.
+    fn score(&self) -> Score;
+    /// Returns the address of the document.
+    fn doc_address(&self) -> DocAddress;
+}
+
+/// Computes the hits of the top documents of a segment, along with their payload.
+///
+/// It is the segment local version of the [`HitPayload`].
+pub(crate) trait SegmentHitPayload: 'static {
+    /// Type of the hits.
+    type Hit: ScoredHit;
+
+    /// Returns the hit of the document at `doc_address`.
+    ///
+    /// The method is only called on the top documents of the segment, by increasing doc id, so
+    /// that the postings read to compute the payload only move forward.
+    fn hit(&mut self, score: Score, doc_address: DocAddress) -> Self::Hit;
+}
+
+/// Builds the [`SegmentHitPayload`] of each segment.
+pub(crate) trait HitPayload: Sync + Send {
+    /// Type of the associated [`SegmentHitPayload`].
+    type Child: SegmentHitPayload;
+
+    /// Builds the payload computation of a specific segment.
+    fn segment_payload(&self, reader: &SegmentReader) -> crate::Result<Self::Child>;
+}
+
+/// Works as [`TopDocs`], and attaches a payload to each of the top documents.
+///
+/// The payloads are computed for the top documents of each segment only, once the segment has
+/// been collected.
+pub(crate) struct TopDocsWithPayload<T> {
+    top_docs: TopDocs,
+    hit_payload: T,
+}
+
+impl<T: HitPayload> TopDocsWithPayload<T> {
+    /// Creates a collector keeping track of the top `limit` documents.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub(crate) fn with_limit(limit: usize, hit_payload: T) -> TopDocsWithPayload<T> {
+        TopDocsWithPayload {
+            top_docs: TopDocs::with_limit(limit),
+            hit_payload,
+        }
+    }
+
+    /// Skip the first "offset" documents when collecting.
+    pub(crate) fn and_offset(self, offset: usize) -> TopDocsWithPayload<T> {
+        TopDocsWithPayload {
+            top_docs: self.top_docs.and_offset(offset),
+            hit_payload: self.hit_payload,
+        }
+    }
+}
+
+type Hit<T> = <<T as HitPayload>::Child as SegmentHitPayload>::Hit;
+
+/// Computes the hits of the top documents of a segment, keeping the order of `top_docs`.
+fn with_payloads<T: SegmentHitPayload>(
+    top_docs: Vec<(Score, DocAddress)>,
+    mut segment_payload: T,
+) -> Vec<T::Hit> {
+    let mut ranked_docs: Vec<(usize, Score, DocAddress)> = top_docs
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (score, doc_address))| (rank, score, doc_address))
+        .collect();
+    ranked_docs.sort_unstable_by_key(|(_, _, doc_address)| doc_address.doc_id);
+    let mut ranked_hits: Vec<(usize, T::Hit)> = ranked_docs
+        .into_iter()
+        .map(|(rank, score, doc_address)| (rank, segment_payload.hit(score, doc_address)))
+        .collect();
+    ranked_hits.sort_unstable_by_key(|(rank, _)| *rank);
+    ranked_hits.into_iter().map(|(_, hit)| hit).collect()
+}
+
+impl<T: HitPayload> Collector for TopDocsWithPayload<T> {
+    type Fruit = Vec<Hit<T>>;
+
+    type Child = TopDocsWithPayloadSegmentCollector<T::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        Ok(TopDocsWithPayloadSegmentCollector {
+            top_docs: self.top_docs.for_segment(segment_local_id, reader)?,
+            segment_payload: self.hit_payload.segment_payload(reader)?,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Vec<Hit<T>>>) -> crate::Result<Self::Fruit> {
+        let mut hits: HashMap<DocAddress, Hit<T>> = HashMap::new();
+        let top_docs_fruits = segment_fruits
+            .into_iter()
+            .map(|segment_fruit| {
+                segment_fruit
+                    .into_iter()
+                    .map(|hit| {
+                        let top_doc = (hit.score(), hit.doc_address());
+                        hits.insert(hit.doc_address(), hit);
+                        top_doc
+                    })
+                    .collect()
+            })
+            .collect();
+        let top_docs = self.top_docs.merge_fruits(top_docs_fruits)?;
+        Ok(top_docs
+            .into_iter()
+            .filter_map(|(_, doc_address)| hits.remove(&doc_address))
+            .collect())
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<Vec<Hit<T>>> {
+        let top_docs = self.top_docs.collect_segment(weight, segment_ord, reader)?;
+        Ok(with_payloads(
+            top_docs,
+            self.hit_payload.segment_payload(reader)?,
+        ))
+    }
+}
+
+/// Segment collector for the [`TopDocsWithPayload`] collector.
+pub(crate) struct TopDocsWithPayloadSegmentCollector<T> {
+    top_docs: TopScoreSegmentCollector,
+    segment_payload: T,
+}
+
+impl<T: SegmentHitPayload> SegmentCollector for TopDocsWithPayloadSegmentCollector<T> {
+    type Fruit = Vec<T::Hit>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.top_docs.collect(doc, score);
+    }
+
+    fn harvest(self) -> Vec<T::Hit> {
+        with_payloads(self.top_docs.harvest(), self.segment_payload)
+    }
+}
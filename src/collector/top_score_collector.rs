@@ -2,7 +2,7 @@ use std::fmt;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
-use columnar::ColumnValues;
+use columnar::{Column, ColumnValues};
 use serde::{Deserialize, Serialize};
 
 use super::Collector;
@@ -10,11 +10,15 @@ use crate::collector::custom_score_top_collector::CustomScoreTopCollector;
 use crate::collector::top_collector::{ComparableDoc, TopCollector, TopSegmentCollector};
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
-    CustomScorer, CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
+    CustomScorer, CustomSegmentScorer, MinScoreCollector, ScoreSegmentTweaker, ScoreTweaker,
+    SegmentCollector,
 };
 use crate::fastfield::{FastFieldNotAvailableError, FastValue};
 use crate::query::Weight;
-use crate::{DocAddress, DocId, Order, Score, SegmentOrdinal, SegmentReader, TantivyError};
+use crate::{
+    DocAddress, DocId, MissingValuePolicy, Order, Score, SegmentOrdinal, SegmentReader,
+    TantivyError,
+};
 
 struct FastFieldConvertCollector<
     TCollector: Collector<Fruit = Vec<(u64, DocAddress)>>,
@@ -40,23 +44,7 @@ where
         segment_local_id: crate::SegmentOrdinal,
         segment: &SegmentReader,
     ) -> crate::Result<Self::Child> {
-        let schema = segment.schema();
-        let field = schema.get_field(&self.field)?;
-        let field_entry = schema.get_field_entry(field);
-        if !field_entry.is_fast() {
-            return Err(TantivyError::SchemaError(format!(
-                "Field {:?} is not a fast field.",
-                field_entry.name()
-            )));
-        }
-        let schema_type = TFastValue::to_type();
-        let requested_type = field_entry.field_type().value_type();
-        if schema_type != requested_type {
-            return Err(TantivyError::SchemaError(format!(
-                "Field {:?} is of type {schema_type:?}!={requested_type:?}",
-                field_entry.name()
-            )));
-        }
+        check_fast_field_type::<TFastValue>(segment, &self.field)?;
         self.collector.for_segment(segment_local_id, segment)
     }
 
@@ -83,6 +71,142 @@ where
     }
 }
 
+/// Checks that `field_name` is a fast field of type `TFastValue`.
+fn check_fast_field_type<TFastValue: FastValue>(
+    segment: &SegmentReader,
+    field_name: &str,
+) -> crate::Result<()> {
+    let schema = segment.schema();
+    let field = schema.get_field(field_name)?;
+    let field_entry = schema.get_field_entry(field);
+    if !field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not a fast field.",
+            field_entry.name()
+        )));
+    }
+    let schema_type = TFastValue::to_type();
+    let requested_type = field_entry.field_type().value_type();
+    if schema_type != requested_type {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is of type {schema_type:?}!={requested_type:?}",
+            field_entry.name()
+        )));
+    }
+    Ok(())
+}
+
+/// Sort key of [`TopDocs::order_by_fast_field_with_missing`]: the rank of the document, followed
+/// by the `u64` representation of its value, reversed for an ascending order.
+type MissingValueSortKey = (u8, u64);
+
+/// Rank of the documents without a value, when they come first.
+const MISSING_FIRST_RANK: u8 = 2;
+/// Rank of the documents with a value, or with a default value.
+const HAS_VALUE_RANK: u8 = 1;
+/// Rank of the documents without a value, when they come last.
+const MISSING_LAST_RANK: u8 = 0;
+
+struct MissingValueTopCollector<TFastValue> {
+    collector: TopCollector<MissingValueSortKey>,
+    field: String,
+    order: Order,
+    missing: MissingValuePolicy<TFastValue>,
+}
+
+impl<TFastValue: FastValue> Collector for MissingValueTopCollector<TFastValue> {
+    type Fruit = Vec<(Option<TFastValue>, DocAddress)>;
+
+    type Child = MissingValueTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        check_fast_field_type::<TFastValue>(segment, &self.field)?;
+        // A segment in which none of the documents have a value has no column.
+        let column_opt = segment
+            .fast_fields()
+            .u64_lenient(&self.field)?
+            .map(|(column, _column_type)| column);
+        let missing = match &self.missing {
+            MissingValuePolicy::Skip => MissingValuePolicy::Skip,
+            MissingValuePolicy::Default(value) => MissingValuePolicy::Default(value.to_u64()),
+            MissingValuePolicy::First => MissingValuePolicy::First,
+            MissingValuePolicy::Last => MissingValuePolicy::Last,
+        };
+        Ok(MissingValueTopSegmentCollector {
+            segment_collector: self.collector.for_segment(segment_local_id, segment),
+            column_opt,
+            order: self.order.clone(),
+            missing,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(MissingValueSortKey, DocAddress)>>,
+    ) -> crate::Result<Self::Fruit> {
+        let raw_result = self.collector.merge_fruits(segment_fruits)?;
+        let transformed_result = raw_result
+            .into_iter()
+            .map(|((rank, key), doc_address)| {
+                if rank != HAS_VALUE_RANK {
+                    return (None, doc_address);
+                }
+                let value = if self.order.is_desc() {
+                    key
+                } else {
+                    u64::MAX - key
+                };
+                (Some(TFastValue::from_u64(value)), doc_address)
+            })
+            .collect();
+        Ok(transformed_result)
+    }
+}
+
+struct MissingValueTopSegmentCollector {
+    segment_collector: TopSegmentCollector<MissingValueSortKey>,
+    column_opt: Option<Column<u64>>,
+    order: Order,
+    missing: MissingValuePolicy<u64>,
+}
+
+impl SegmentCollector for MissingValueTopSegmentCollector {
+    type Fruit = Vec<(MissingValueSortKey, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let value_opt = self
+            .column_opt
+            .as_ref()
+            .and_then(|column| column.first(doc));
+        let sort_key = match (value_opt, &self.missing) {
+            (Some(value), _) | (None, &MissingValuePolicy::Default(value)) => {
+                let key = if self.order.is_desc() {
+                    value
+                } else {
+                    u64::MAX - value
+                };
+                (HAS_VALUE_RANK, key)
+            }
+            (None, MissingValuePolicy::Skip) => return,
+            (None, MissingValuePolicy::First) => (MISSING_FIRST_RANK, 0),
+            (None, MissingValuePolicy::Last) => (MISSING_LAST_RANK, 0),
+        };
+        self.segment_collector.collect(doc, sort_key);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.segment_collector.harvest()
+    }
+}
+
 /// The `TopDocs` collector keeps track of the top `K` documents
 /// sorted by their score.
 ///
@@ -127,18 +251,33 @@ where
 /// # Ok(())
 /// # }
 /// ```
-pub struct TopDocs(TopCollector<Score>);
+pub struct TopDocs {
+    collector: TopCollector<Score>,
+    min_score: Option<Score>,
+}
 
 impl fmt::Debug for TopDocs {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "TopDocs(limit={}, offset={})",
-            self.0.limit, self.0.offset
-        )
+            "TopDocs(limit={}, offset={}",
+            self.collector.limit, self.collector.offset
+        )?;
+        if let Some(min_score) = self.min_score {
+            write!(f, ", min_score={min_score}")?;
+        }
+        write!(f, ")")
     }
 }
 
+/// Returns a score below `score`, and close to it.
+///
+/// The pruning skips the documents scored the same as the threshold: pruning with this
+/// threshold keeps the documents scored exactly `score`.
+fn score_below(score: Score) -> Score {
+    score - score.abs() * Score::EPSILON - Score::MIN_POSITIVE
+}
+
 struct ScorerByFastFieldReader {
     sort_column: Arc<dyn ColumnValues<u64>>,
     order: Order,
@@ -191,7 +330,10 @@ impl TopDocs {
     /// # Panics
     /// The method panics if limit is 0
     pub fn with_limit(limit: usize) -> TopDocs {
-        TopDocs(TopCollector::with_limit(limit))
+        TopDocs {
+            collector: TopCollector::with_limit(limit),
+            min_score: None,
+        }
     }
 
     /// Skip the first "offset" documents when collecting.
@@ -236,7 +378,103 @@ impl TopDocs {
     /// ```
     #[must_use]
     pub fn and_offset(self, offset: usize) -> TopDocs {
-        TopDocs(self.0.and_offset(offset))
+        TopDocs {
+            collector: self.collector.and_offset(offset),
+            ..self
+        }
+    }
+
+    /// Sets the order of the documents ranked the same, by the global doc ordinal of the
+    /// searcher: the segment ordinal, then the doc id within the segment.
+    ///
+    /// The ties are broken by ascending doc ordinal by default. The doc ordinal follows the
+    /// insertion order within a segment, as long as the index is not sorted, so
+    /// [`Order::Asc`] ranks the oldest documents first and [`Order::Desc`] the newest ones.
+    /// Segments do not follow the insertion order once they are merged.
+    ///
+    /// This applies to the orders set afterwards, such as
+    /// [`TopDocs::order_by_fast_field`]: it has to be called before them.
+    ///
+    /// A descending order disables the pruning of the documents which cannot beat the current
+    /// top `K`, making the search of scored queries slower.
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{Schema, TEXT};
+    /// use tantivy::{doc, DocAddress, Index, Order};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// for i in 0..3 {
+    ///     index_writer.add_document(doc!(title => format!("title {i}")))?;
+    /// }
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// // All of the documents have the same score.
+    /// let newest_first = TopDocs::with_limit(2).tie_break_by_doc_id(Order::Desc);
+    /// let top_docs = searcher.search(&AllQuery, &newest_first)?;
+    /// assert_eq!(top_docs[0].1, DocAddress::new(0, 2));
+    /// assert_eq!(top_docs[1].1, DocAddress::new(0, 1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn tie_break_by_doc_id(self, order: Order) -> TopDocs {
+        TopDocs {
+            collector: self.collector.and_doc_order(order),
+            ..self
+        }
+    }
+
+    /// Drops the documents scored below `min_score`, instead of ranking them last.
+    ///
+    /// The top docs may then hold less than `limit` documents, or none at all, which keeps
+    /// the low relevance matches out of the results. The documents scored exactly
+    /// `min_score` are kept. The minimum also speeds up the search, as the blocks of
+    /// documents which cannot reach it are skipped.
+    ///
+    /// The minimum applies to the score of the query. With the rankings set afterwards,
+    /// such as [`TopDocs::tweak_score`], [`TopDocs::custom_score`] or
+    /// [`TopDocs::order_by_fast_field`], the documents scored below `min_score` by the query
+    /// are dropped before being ranked, which requires the query to be scored. To keep the
+    /// documents above a threshold with another collector, wrap it in a
+    /// [`MinScoreCollector`].
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::QueryParser;
+    /// use tantivy::schema::{Schema, TEXT};
+    /// use tantivy::{doc, DocAddress, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(title => "diary"))?;
+    /// index_writer.add_document(doc!(title => "The Diary of a Young Girl, and of her family"))?;
+    /// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+    /// let top_docs = searcher.search(&query, &TopDocs::with_limit(10).with_min_score(0.6))?;
+    /// assert_eq!(top_docs.len(), 1);
+    /// assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_min_score(self, min_score: Score) -> TopDocs {
+        TopDocs {
+            min_score: Some(min_score),
+            ..self
+        }
     }
 
     /// Set top-K to rank documents by a given fast field.
@@ -315,13 +553,14 @@ impl TopDocs {
         field: impl ToString,
         order: Order,
     ) -> impl Collector<Fruit = Vec<(u64, DocAddress)>> {
-        CustomScoreTopCollector::new(
+        let collector = CustomScoreTopCollector::new(
             ScorerByField {
                 field: field.to_string(),
                 order,
             },
-            self.0.into_tscore(),
-        )
+            self.collector.into_tscore(),
+        );
+        MinScoreCollector::with_optional_min_score(collector, self.min_score)
     }
 
     /// Set top-K to rank documents by a given fast field.
@@ -393,6 +632,15 @@ impl TopDocs {
     ///     Ok(resulting_docs)
     /// }
     /// ```
+    ///
+    /// # Missing values
+    ///
+    /// The documents without a value come after the documents with a value, whatever the order.
+    /// They are however ranked like the lowest value in a descending order, and like the highest
+    /// value in an ascending order, so they may be interleaved with the documents having this
+    /// value. They are returned with this value, converted to `TFastValue`.
+    ///
+    /// Use [`TopDocs::order_by_fast_field_with_missing`] to pick how they are handled.
     pub fn order_by_fast_field<TFastValue>(
         self,
         fast_field: impl ToString,
@@ -410,6 +658,70 @@ impl TopDocs {
         }
     }
 
+    /// Set top-K to rank documents by a given fast field, handling the documents without a value
+    /// according to `missing`.
+    ///
+    /// This works as [`TopDocs::order_by_fast_field`], except for the documents without a
+    /// value:
+    /// - [`MissingValuePolicy::Skip`] leaves them out of the results.
+    /// - [`MissingValuePolicy::Default`] ranks them as if they had the given value, and returns
+    ///   them with this value.
+    /// - [`MissingValuePolicy::First`] and [`MissingValuePolicy::Last`] rank them before or after
+    ///   all of the documents with a value, whatever the order, and return them with `None`.
+    ///
+    /// Documents with the same rank are sorted by their [`DocAddress`].
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{Schema, FAST};
+    /// use tantivy::{doc, DocAddress, Index, MissingValuePolicy, Order};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let rating = schema_builder.add_u64_field("rating", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(rating => 3u64))?;
+    /// index_writer.add_document(doc!())?;
+    /// index_writer.add_document(doc!(rating => 5u64))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let top_docs = TopDocs::with_limit(3).order_by_fast_field_with_missing(
+    ///     "rating",
+    ///     Order::Desc,
+    ///     MissingValuePolicy::First,
+    /// );
+    /// assert_eq!(
+    ///     searcher.search(&AllQuery, &top_docs)?,
+    ///     vec![
+    ///         (None, DocAddress::new(0, 1)),
+    ///         (Some(5u64), DocAddress::new(0, 2)),
+    ///         (Some(3u64), DocAddress::new(0, 0)),
+    ///     ]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn order_by_fast_field_with_missing<TFastValue>(
+        self,
+        fast_field: impl ToString,
+        order: Order,
+        missing: MissingValuePolicy<TFastValue>,
+    ) -> impl Collector<Fruit = Vec<(Option<TFastValue>, DocAddress)>>
+    where
+        TFastValue: FastValue,
+    {
+        let collector = MissingValueTopCollector {
+            collector: self.collector.into_tscore(),
+            field: fast_field.to_string(),
+            order,
+            missing,
+        };
+        MinScoreCollector::with_optional_min_score(collector, self.min_score)
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to tweak or replace
@@ -515,7 +827,8 @@ impl TopDocs {
         TScoreSegmentTweaker: ScoreSegmentTweaker<TScore> + 'static,
         TScoreTweaker: ScoreTweaker<TScore, Child = TScoreSegmentTweaker> + Send + Sync,
     {
-        TweakedScoreTopCollector::new(score_tweaker, self.0.into_tscore())
+        let collector = TweakedScoreTopCollector::new(score_tweaker, self.collector.into_tscore());
+        MinScoreCollector::with_optional_min_score(collector, self.min_score)
     }
 
     /// Ranks the documents using a custom score.
@@ -628,7 +941,8 @@ impl TopDocs {
         TCustomSegmentScorer: CustomSegmentScorer<TScore> + 'static,
         TCustomScorer: CustomScorer<TScore, Child = TCustomSegmentScorer> + Send + Sync,
     {
-        CustomScoreTopCollector::new(custom_score, self.0.into_tscore())
+        let collector = CustomScoreTopCollector::new(custom_score, self.collector.into_tscore());
+        MinScoreCollector::with_optional_min_score(collector, self.min_score)
     }
 }
 
@@ -642,8 +956,11 @@ impl Collector for TopDocs {
         segment_local_id: SegmentOrdinal,
         reader: &SegmentReader,
     ) -> crate::Result<Self::Child> {
-        let collector = self.0.for_segment(segment_local_id, reader);
-        Ok(TopScoreSegmentCollector(collector))
+        let collector = self.collector.for_segment(segment_local_id, reader);
+        Ok(TopScoreSegmentCollector {
+            collector,
+            min_score: self.min_score,
+        })
     }
 
     fn requires_scoring(&self) -> bool {
@@ -654,7 +971,7 @@ impl Collector for TopDocs {
         &self,
         child_fruits: Vec<Vec<(Score, DocAddress)>>,
     ) -> crate::Result<Self::Fruit> {
-        self.0.merge_fruits(child_fruits)
+        self.collector.merge_fruits(child_fruits)
     }
 
     fn collect_segment(
@@ -663,24 +980,41 @@ impl Collector for TopDocs {
         segment_ord: u32,
         reader: &SegmentReader,
     ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
-        let heap_len = self.0.limit + self.0.offset;
+        if self.collector.doc_order.is_desc() {
+            // The pruning skips the documents scored the same as the current threshold, whereas
+            // the later documents win the ties.
+            let mut segment_collector = self.for_segment(segment_ord, reader)?;
+            let alive_bitset = reader.alive_bitset();
+            weight.for_each(reader, &mut |doc, score| {
+                if alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(doc)) {
+                    segment_collector.collect(doc, score);
+                }
+            })?;
+            return Ok(segment_collector.harvest());
+        }
+        let heap_len = self.collector.limit + self.collector.offset;
         let mut top_n: TopNComputer<_, _> = TopNComputer::new(heap_len);
+        let min_threshold = self.min_score.map(score_below).unwrap_or(Score::MIN);
+        let is_above_min_score =
+            |score: Score| self.min_score.is_none_or(|min_score| score >= min_score);
 
         if let Some(alive_bitset) = reader.alive_bitset() {
-            let mut threshold = Score::MIN;
+            let mut threshold = min_threshold;
             top_n.threshold = Some(threshold);
-            weight.for_each_pruning(Score::MIN, reader, &mut |doc, score| {
-                if alive_bitset.is_deleted(doc) {
+            weight.for_each_pruning(min_threshold, reader, &mut |doc, score| {
+                if alive_bitset.is_deleted(doc) || !is_above_min_score(score) {
                     return threshold;
                 }
                 top_n.push(score, doc);
-                threshold = top_n.threshold.unwrap_or(Score::MIN);
+                threshold = top_n.threshold.unwrap_or(Score::MIN).max(min_threshold);
                 threshold
             })?;
         } else {
-            weight.for_each_pruning(Score::MIN, reader, &mut |doc, score| {
-                top_n.push(score, doc);
-                top_n.threshold.unwrap_or(Score::MIN)
+            weight.for_each_pruning(min_threshold, reader, &mut |doc, score| {
+                if is_above_min_score(score) {
+                    top_n.push(score, doc);
+                }
+                top_n.threshold.unwrap_or(Score::MIN).max(min_threshold)
             })?;
         }
 
@@ -702,17 +1036,22 @@ impl Collector for TopDocs {
 }
 
 /// Segment Collector associated with `TopDocs`.
-pub struct TopScoreSegmentCollector(TopSegmentCollector<Score>);
+pub struct TopScoreSegmentCollector {
+    collector: TopSegmentCollector<Score>,
+    min_score: Option<Score>,
+}
 
 impl SegmentCollector for TopScoreSegmentCollector {
     type Fruit = Vec<(Score, DocAddress)>;
 
     fn collect(&mut self, doc: DocId, score: Score) {
-        self.0.collect(doc, score);
+        if self.min_score.is_none_or(|min_score| score >= min_score) {
+            self.collector.collect(doc, score);
+        }
     }
 
     fn harvest(self) -> Vec<(Score, DocAddress)> {
-        self.0.harvest()
+        self.collector.harvest()
     }
 }
 
@@ -865,14 +1204,15 @@ where
 mod tests {
     use super::{TopDocs, TopNComputer};
     use crate::collector::top_collector::ComparableDoc;
-    use crate::collector::Collector;
-    use crate::query::{AllQuery, Query, QueryParser};
-    use crate::schema::{Field, Schema, FAST, STORED, TEXT};
+    use crate::collector::{Collector, Count, MinScoreCollector};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, Query, QueryParser, TermQuery};
+    use crate::schema::{Field, IndexRecordOption, Schema, FAST, STORED, TEXT};
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
     use crate::{
-        assert_nearly_equals, DateTime, DocAddress, DocId, Index, IndexWriter, Order, Score,
-        SegmentReader,
+        assert_nearly_equals, DateTime, DocAddress, DocId, Index, IndexWriter, MissingValuePolicy,
+        Order, Score, SegmentReader, Term,
     };
 
     fn make_index() -> crate::Result<Index> {
@@ -1066,6 +1406,79 @@ mod tests {
         assert_eq!(page_0, &page_2[..page_0.len()]);
     }
 
+    #[test]
+    fn test_top_collector_tie_break_by_doc_id() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let size = schema_builder.add_u64_field(SIZE, FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment in 0..2 {
+            for i in 0..5u64 {
+                index_writer.add_document(doc!(title => "rust", size => i % 2 + segment))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let doc_addresses = |top_docs: Vec<(_, DocAddress)>| -> Vec<DocAddress> {
+            top_docs
+                .into_iter()
+                .map(|(_, doc_address)| doc_address)
+                .collect()
+        };
+        let mut all_docs: Vec<DocAddress> = (0..2)
+            .flat_map(|segment_ord| (0..5).map(move |doc_id| DocAddress::new(segment_ord, doc_id)))
+            .collect();
+
+        // All of the documents have the same score.
+        let query = TermQuery::new(
+            Term::from_field_text(title, "rust"),
+            IndexRecordOption::WithFreqs,
+        );
+        let oldest_first = TopDocs::with_limit(3).tie_break_by_doc_id(Order::Asc);
+        let top_docs = searcher.search(&query, &oldest_first)?;
+        assert!(top_docs.iter().all(|(score, _)| *score == top_docs[0].0));
+        assert_eq!(doc_addresses(top_docs), &all_docs[..3]);
+        let newest_first = TopDocs::with_limit(3).tie_break_by_doc_id(Order::Desc);
+        all_docs.reverse();
+        assert_eq!(
+            doc_addresses(searcher.search(&query, &newest_first)?),
+            &all_docs[..3]
+        );
+        assert_eq!(
+            doc_addresses(searcher.search(&query, &newest_first.and_offset(8))?),
+            &all_docs[8..]
+        );
+
+        // The ties on a fast field: the documents with a size of 1.
+        let top_docs = searcher.search(
+            &AllQuery,
+            &TopDocs::with_limit(10)
+                .tie_break_by_doc_id(Order::Desc)
+                .order_by_fast_field::<u64>(SIZE, Order::Asc),
+        )?;
+        let size_one: Vec<DocAddress> = top_docs
+            .into_iter()
+            .filter(|(size, _)| *size == 1)
+            .map(|(_, doc_address)| doc_address)
+            .collect();
+        let expected: Vec<DocAddress> = all_docs
+            .into_iter()
+            .filter(|doc_address| {
+                let size_column = searcher
+                    .segment_reader(doc_address.segment_ord)
+                    .fast_fields()
+                    .u64(SIZE)
+                    .unwrap();
+                size_column.first(doc_address.doc_id) == Some(1)
+            })
+            .collect();
+        assert_eq!(expected.len(), 5);
+        assert_eq!(size_one, expected);
+        Ok(())
+    }
+
     #[test]
     #[should_panic]
     fn test_top_0() {
@@ -1313,6 +1726,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_top_collector_with_min_score() -> crate::Result<()> {
+        let index = make_index()?;
+        let field = index.schema().get_field("text").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![field]);
+        let text_query = query_parser.parse_query("droopy tax")?;
+        let searcher = index.reader()?.searcher();
+        let all_docs = searcher.search(&text_query, &TopDocs::with_limit(4))?;
+        assert_eq!(all_docs.len(), 3);
+        // The documents scored exactly the minimum are kept.
+        let min_score = all_docs[1].0;
+        for order in [Order::Asc, Order::Desc] {
+            let collector = TopDocs::with_limit(4)
+                .tie_break_by_doc_id(order)
+                .with_min_score(min_score);
+            assert_eq!(searcher.search(&text_query, &collector)?, &all_docs[..2]);
+        }
+        let collector = TopDocs::with_limit(4)
+            .and_offset(1)
+            .with_min_score(min_score);
+        assert_eq!(searcher.search(&text_query, &collector)?, &all_docs[1..2]);
+        let collector = TopDocs::with_limit(4).with_min_score(all_docs[0].0 + 1.0);
+        assert!(searcher.search(&text_query, &collector)?.is_empty());
+
+        // The documents are dropped before being ranked by the tweaked score.
+        let collector = TopDocs::with_limit(4)
+            .with_min_score(min_score)
+            .tweak_score(move |_segment_reader: &SegmentReader| {
+                move |doc: DocId, _original_score: Score| doc
+            });
+        let score_docs: Vec<(u32, DocAddress)> = searcher.search(&text_query, &collector)?;
+        assert_eq!(
+            score_docs,
+            vec![(2, DocAddress::new(0, 2)), (1, DocAddress::new(0, 1))]
+        );
+        let count = searcher.search(&text_query, &MinScoreCollector::new(Count, min_score))?;
+        assert_eq!(count, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_collector_with_min_score_and_deletes() -> crate::Result<()> {
+        let index = make_index()?;
+        let field = index.schema().get_field("text").unwrap();
+        let query_parser = QueryParser::for_index(&index, vec![field]);
+        let text_query = query_parser.parse_query("droopy tax")?;
+        let min_score = index
+            .reader()?
+            .searcher()
+            .search(&text_query, &TopDocs::with_limit(4))?[1]
+            .0;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.delete_term(Term::from_field_text(field, "like"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let collector = TopDocs::with_limit(4).with_min_score(min_score);
+        let score_docs = searcher.search(&text_query, &collector)?;
+        assert_eq!(score_docs.len(), 1);
+        assert_eq!(score_docs[0].1, DocAddress::new(0, 1));
+        Ok(())
+    }
+
     fn index(
         query: &str,
         query_field: Field,
@@ -1373,4 +1848,121 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_fast_field_missing_value_policy() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let rating = schema_builder.add_i64_field("rating", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(rating => 3i64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(rating => -2i64))?;
+        index_writer.commit()?;
+        // None of the documents of this segment have a value.
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_ord = |num_docs: u32| {
+            searcher
+                .segment_readers()
+                .iter()
+                .position(|segment_reader| segment_reader.num_docs() == num_docs)
+                .unwrap() as u32
+        };
+        let values_segment = segment_ord(3);
+        let empty_segment = segment_ord(1);
+        let top_docs = |order: Order,
+                        missing: MissingValuePolicy<i64>|
+         -> crate::Result<Vec<(Option<i64>, DocAddress)>> {
+            let collector =
+                TopDocs::with_limit(10).order_by_fast_field_with_missing("rating", order, missing);
+            searcher.search(&AllQuery, &collector)
+        };
+        let three = (Some(3), DocAddress::new(values_segment, 0));
+        let minus_two = (Some(-2), DocAddress::new(values_segment, 2));
+        let mut missing_docs = [
+            DocAddress::new(values_segment, 1),
+            DocAddress::new(empty_segment, 0),
+        ];
+        missing_docs.sort();
+        let [first_missing, second_missing] = missing_docs;
+
+        assert_eq!(
+            top_docs(Order::Desc, MissingValuePolicy::Skip)?,
+            [three, minus_two]
+        );
+        assert_eq!(
+            top_docs(Order::Asc, MissingValuePolicy::Skip)?,
+            [minus_two, three]
+        );
+        assert_eq!(
+            top_docs(Order::Desc, MissingValuePolicy::First)?,
+            [
+                (None, first_missing),
+                (None, second_missing),
+                three,
+                minus_two
+            ]
+        );
+        assert_eq!(
+            top_docs(Order::Asc, MissingValuePolicy::First)?,
+            [
+                (None, first_missing),
+                (None, second_missing),
+                minus_two,
+                three
+            ]
+        );
+        assert_eq!(
+            top_docs(Order::Desc, MissingValuePolicy::Last)?,
+            [
+                three,
+                minus_two,
+                (None, first_missing),
+                (None, second_missing)
+            ]
+        );
+        assert_eq!(
+            top_docs(Order::Asc, MissingValuePolicy::Last)?,
+            [
+                minus_two,
+                three,
+                (None, first_missing),
+                (None, second_missing)
+            ]
+        );
+        assert_eq!(
+            top_docs(Order::Desc, MissingValuePolicy::Default(0))?,
+            [
+                three,
+                (Some(0), first_missing),
+                (Some(0), second_missing),
+                minus_two
+            ]
+        );
+        assert_eq!(
+            top_docs(Order::Asc, MissingValuePolicy::Default(0))?,
+            [
+                minus_two,
+                (Some(0), first_missing),
+                (Some(0), second_missing),
+                three
+            ]
+        );
+        // The limit applies to the documents without a value as well.
+        let collector = TopDocs::with_limit(1)
+            .and_offset(1)
+            .order_by_fast_field_with_missing(
+                "rating",
+                Order::Desc,
+                MissingValuePolicy::<i64>::First,
+            );
+        assert_eq!(
+            searcher.search(&AllQuery, &collector)?,
+            [(None, second_missing)]
+        );
+        Ok(())
+    }
 }
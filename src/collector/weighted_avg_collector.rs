@@ -0,0 +1,198 @@
+use columnar::{Column, ColumnType};
+
+use crate::aggregation::f64_from_fastfield_u64;
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+const NUMERICAL_COLUMN_TYPES: [ColumnType; 4] = [
+    ColumnType::U64,
+    ColumnType::I64,
+    ColumnType::F64,
+    ColumnType::Bool,
+];
+
+/// A numerical column, read as `u64`, along with its original type.
+type NumericalColumn = (Column<u64>, ColumnType);
+
+/// The `WeightedAvgCollector` computes `sum(value * weight) / sum(weight)` over the matching
+/// documents, where both the value and the weight are read from numerical fast fields.
+///
+/// The fields can be of any numerical type, and their values are converted to `f64`.
+/// Documents lacking a value or a weight are ignored. For multivalued fields, only the first
+/// value of each document is considered.
+///
+/// The fruit is `None` if the total weight of the matching documents is zero.
+///
+/// ```rust
+/// use tantivy::collector::WeightedAvgCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let rating = schema_builder.add_f64_field("rating", FAST);
+/// let num_votes = schema_builder.add_u64_field("num_votes", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(rating => 4.0f64, num_votes => 3u64))?;
+/// index_writer.add_document(doc!(rating => 2.0f64, num_votes => 1u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let weighted_avg = searcher.search(
+///     &AllQuery,
+///     &WeightedAvgCollector::new("rating", "num_votes"),
+/// )?;
+/// assert_eq!(weighted_avg, Some(3.5));
+/// # Ok(())
+/// # }
+/// ```
+pub struct WeightedAvgCollector {
+    value_field: String,
+    weight_field: String,
+}
+
+impl WeightedAvgCollector {
+    /// Creates a new `WeightedAvgCollector` for the given value and weight fast fields.
+    pub fn new(value_field: impl ToString, weight_field: impl ToString) -> WeightedAvgCollector {
+        WeightedAvgCollector {
+            value_field: value_field.to_string(),
+            weight_field: weight_field.to_string(),
+        }
+    }
+}
+
+fn open_numerical_column(
+    segment_reader: &SegmentReader,
+    field_name: &str,
+) -> crate::Result<Option<NumericalColumn>> {
+    segment_reader
+        .fast_fields()
+        .u64_lenient_for_type(Some(&NUMERICAL_COLUMN_TYPES), field_name)
+}
+
+impl Collector for WeightedAvgCollector {
+    type Fruit = Option<f64>;
+
+    type Child = WeightedAvgSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<WeightedAvgSegmentCollector> {
+        let value_column_opt = open_numerical_column(segment_reader, &self.value_field)?;
+        let weight_column_opt = open_numerical_column(segment_reader, &self.weight_field)?;
+        Ok(WeightedAvgSegmentCollector {
+            columns_opt: value_column_opt.zip(weight_column_opt),
+            weighted_sum: 0.0,
+            weight_sum: 0.0,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<(f64, f64)>) -> crate::Result<Option<f64>> {
+        let (weighted_sum, weight_sum) = segment_fruits.into_iter().fold(
+            (0.0, 0.0),
+            |(weighted_sum, weight_sum), (segment_weighted_sum, segment_weight_sum)| {
+                (
+                    weighted_sum + segment_weighted_sum,
+                    weight_sum + segment_weight_sum,
+                )
+            },
+        );
+        if weight_sum == 0.0 {
+            return Ok(None);
+        }
+        Ok(Some(weighted_sum / weight_sum))
+    }
+}
+
+/// Segment collector for the [`WeightedAvgCollector`].
+///
+/// Its fruit is the weighted sum of the values and the sum of the weights.
+pub struct WeightedAvgSegmentCollector {
+    columns_opt: Option<(NumericalColumn, NumericalColumn)>,
+    weighted_sum: f64,
+    weight_sum: f64,
+}
+
+impl SegmentCollector for WeightedAvgSegmentCollector {
+    type Fruit = (f64, f64);
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let Some(((value_column, value_type), (weight_column, weight_type))) = &self.columns_opt
+        else {
+            return;
+        };
+        let (Some(value), Some(weight)) = (value_column.first(doc), weight_column.first(doc))
+        else {
+            return;
+        };
+        let value = f64_from_fastfield_u64(value, value_type);
+        let weight = f64_from_fastfield_u64(weight, weight_type);
+        self.weighted_sum += value * weight;
+        self.weight_sum += weight;
+    }
+
+    fn harvest(self) -> (f64, f64) {
+        (self.weighted_sum, self.weight_sum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WeightedAvgCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_weighted_avg_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING);
+        let rating = schema_builder.add_i64_field("rating", FAST);
+        let weight = schema_builder.add_f64_field("weight", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category => "book", rating => 5i64, weight => 2.0f64))?;
+        index_writer.add_document(doc!(category => "film", rating => 1i64, weight => 0.0f64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(category => "book", rating => 2i64, weight => 0.5f64))?;
+        // Documents lacking a value or a weight are ignored.
+        index_writer.add_document(doc!(category => "book", rating => 1i64))?;
+        index_writer.add_document(doc!(category => "book", weight => 10.0f64))?;
+        index_writer.add_document(doc!(category => "game", rating => -4i64, weight => 1.5f64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let collector = WeightedAvgCollector::new("rating", "weight");
+        let book_query = TermQuery::new(
+            Term::from_field_text(category, "book"),
+            IndexRecordOption::Basic,
+        );
+        // (5 * 2 + 2 * 0.5) / (2 + 0.5)
+        assert_eq!(searcher.search(&book_query, &collector)?, Some(4.4));
+        // (5 * 2 + 1 * 0 + 2 * 0.5 - 4 * 1.5) / (2 + 0 + 0.5 + 1.5)
+        assert_eq!(searcher.search(&AllQuery, &collector)?, Some(1.25));
+        // The total weight is zero.
+        let film_query = TermQuery::new(
+            Term::from_field_text(category, "film"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&film_query, &collector)?, None);
+        let no_match_query = TermQuery::new(
+            Term::from_field_text(category, "music"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&no_match_query, &collector)?, None);
+        Ok(())
+    }
+}
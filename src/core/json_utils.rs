@@ -61,6 +61,12 @@ impl IndexingPositionsPerPath {
     pub fn clear(&mut self) {
         self.positions_per_path.clear();
     }
+    /// Returns true if tokens were dropped because their position exceeded `u32::MAX`.
+    pub fn positions_overflow(&self) -> bool {
+        self.positions_per_path
+            .values()
+            .any(|indexing_position| indexing_position.positions_overflow)
+    }
 }
 
 /// Convert JSON_PATH_SEGMENT_SEP to a dot.
@@ -2,13 +2,15 @@ mod executor;
 #[doc(hidden)]
 pub mod json_utils;
 pub mod searcher;
+mod sorted_doc_stream;
 
 use std::path::Path;
 
 use once_cell::sync::Lazy;
 
 pub use self::executor::Executor;
-pub use self::searcher::{Searcher, SearcherGeneration};
+pub use self::searcher::{Searcher, SearcherGeneration, SegmentHits};
+pub use self::sorted_doc_stream::SortedDocStream;
 
 /// The meta file contains all the information about the list of segments and the schema
 /// of the index.
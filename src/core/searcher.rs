@@ -1,16 +1,44 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use std::{fmt, io};
 
-use crate::collector::Collector;
+use columnar::{Column, DynamicColumn, HasAssociatedColumnType};
+use common::OwnedBytes;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::core::sorted_doc_stream::{check_sort_field, SortedDocStream};
 use crate::core::Executor;
+use crate::fastfield::FastValue;
 use crate::index::{SegmentId, SegmentReader};
-use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::query::{
+    intersect_scorers, Bm25StatisticsProvider, EmptyQuery, EnableScoring, PhraseQuery, Query,
+    TermQuery, Weight,
+};
 use crate::schema::document::DocumentDeserialize;
-use crate::schema::{Schema, Term};
+use crate::schema::{Facet, Field, FieldType, IndexRecordOption, Schema, Term, TextFieldIndexing};
 use crate::space_usage::SearcherSpaceUsage;
 use crate::store::{CacheStats, StoreReader};
-use crate::{DocAddress, Index, Opstamp, TrackedObject};
+use crate::{
+    DocAddress, DocId, DocSet, Index, Inventory, Opstamp, Order, SegmentOrdinal, TantivyError,
+    TrackedObject, TERMINATED,
+};
+
+/// Number of documents matched in a segment between two checks of the deadline of
+/// [`Searcher::search_with_deadline`].
+pub(crate) const DEADLINE_CHECK_INTERVAL: u32 = 1_024;
+
+/// The hits of a search that reside in a given segment, see [`Searcher::segments_of_hits`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentHits {
+    /// Ordinal of the segment within the searcher.
+    pub segment_ord: SegmentOrdinal,
+    /// Id of the segment, which is stable across searchers.
+    pub segment_id: SegmentId,
+    /// Number of hits in the segment.
+    pub num_hits: usize,
+}
 
 /// Identifies the searcher generation accessed by a [`Searcher`].
 ///
@@ -90,6 +118,87 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
+    /// Fetches several documents from tantivy's store, typically a page of hits.
+    ///
+    /// The documents are returned in the order of `doc_addresses`. They are read segment by
+    /// segment and by increasing doc id, so that each block of the doc store is decompressed
+    /// at most once, whereas calling [`Searcher::doc`] for each hit decompresses a block again
+    /// whenever it was evicted from the cache.
+    pub fn docs<D: DocumentDeserialize>(
+        &self,
+        doc_addresses: &[DocAddress],
+    ) -> crate::Result<Vec<D>> {
+        let mut doc_ids_per_segment: Vec<Vec<DocId>> =
+            vec![Vec::new(); self.inner.store_readers.len()];
+        for doc_address in doc_addresses {
+            doc_ids_per_segment
+                .get_mut(doc_address.segment_ord as usize)
+                .ok_or_else(|| {
+                    TantivyError::InvalidArgument(format!(
+                        "Invalid segment ordinal {}",
+                        doc_address.segment_ord
+                    ))
+                })?
+                .push(doc_address.doc_id);
+        }
+        let mut docs_per_segment: Vec<std::vec::IntoIter<D>> = self
+            .inner
+            .store_readers
+            .iter()
+            .zip(&doc_ids_per_segment)
+            .map(|(store_reader, doc_ids)| Ok(store_reader.get_many(doc_ids)?.into_iter()))
+            .collect::<crate::Result<_>>()?;
+        Ok(doc_addresses
+            .iter()
+            .flat_map(|doc_address| docs_per_segment[doc_address.segment_ord as usize].next())
+            .collect())
+    }
+
+    /// Fetches the raw source of a document given its [`DocAddress`].
+    ///
+    /// Returns `None` if the index does not keep the source of the documents, see
+    /// [`IndexSettings::store_source`](crate::IndexSettings::store_source), or if the
+    /// document was added without a source.
+    ///
+    /// Contrary to [`Searcher::doc`], no block cache is used.
+    pub fn source(&self, doc_address: DocAddress) -> crate::Result<Option<OwnedBytes>> {
+        self.segment_reader(doc_address.segment_ord)
+            .source(doc_address.doc_id)
+    }
+
+    /// Fetches the document containing `term`, typically a unique identifier.
+    ///
+    /// Returns `None` if no alive document contains the term.
+    ///
+    /// The term is assumed to identify at most one document. If several documents contain it,
+    /// the one with the smallest [`DocAddress`] is returned, which is not necessarily the
+    /// last one added. The order of the segments changes as they get merged.
+    ///
+    /// The field of the term must be indexed, and the document must be stored.
+    pub fn doc_by_term<D: DocumentDeserialize>(
+        &self,
+        term: &Term,
+    ) -> crate::Result<Option<(DocAddress, D)>> {
+        for (segment_ord, segment_reader) in self.inner.segment_readers.iter().enumerate() {
+            let inverted_index = segment_reader.inverted_index(term.field())?;
+            let Some(mut postings) =
+                inverted_index.read_postings(term, IndexRecordOption::Basic)?
+            else {
+                continue;
+            };
+            let alive_bitset = segment_reader.alive_bitset();
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(doc)) {
+                    let doc_address = DocAddress::new(segment_ord as u32, doc);
+                    return Ok(Some((doc_address, self.doc(doc_address)?)));
+                }
+                doc = postings.advance();
+            }
+        }
+        Ok(None)
+    }
+
     /// The cache stats for the underlying store reader.
     ///
     /// Aggregates the sum for each segment store reader.
@@ -128,6 +237,98 @@ impl Searcher {
             .sum::<u64>()
     }
 
+    /// Returns the number of documents of the segments, including the deleted ones.
+    pub(crate) fn max_doc(&self) -> u64 {
+        self.inner
+            .segment_readers
+            .iter()
+            .map(|segment_reader| u64::from(segment_reader.max_doc()))
+            .sum::<u64>()
+    }
+
+    /// Returns an a priori estimate of the work of running `query`, as the number of postings
+    /// entries it reads.
+    ///
+    /// The estimate is computed from the document frequencies of the terms, without running the
+    /// query: a term query costs its document frequency, a disjunction the sum of the costs of
+    /// its clauses, and a conjunction the cost of its cheapest required clause, as the
+    /// intersection skips ahead on the other ones. This makes it possible, for instance, to
+    /// order the clauses of a query, or to pick between a term query and a fast field filter.
+    ///
+    /// The cost is about the work, not about the results: a phrase query costs as much as its
+    /// rarest term, even if few of its documents contain the phrase. The queries that cannot be
+    /// estimated, such as the range or the fuzzy queries, conservatively cost the number of
+    /// documents of the searcher, deleted ones included, as if they scanned all of them.
+    pub fn query_cost(&self, query: &dyn Query) -> crate::Result<u64> {
+        Ok(query.cost(self)?.unwrap_or_else(|| self.max_doc()))
+    }
+
+    /// Builds a phrase query matching `text` in the text field `field`, with the given `slop`.
+    ///
+    /// The text goes through the analyzer of the field, so the terms of the phrase are the
+    /// terms of the indexed documents. Their positions are the positions of the tokens: when
+    /// a filter removes a token mid-phrase, such as a stop word, the phrase keeps the gap it
+    /// leaves, as in the indexed documents.
+    ///
+    /// A text producing a single token gives a [`TermQuery`], and a text producing no token
+    /// an [`EmptyQuery`], which matches nothing.
+    ///
+    /// Returns an error if the field is not a text field, or if the text gives several tokens
+    /// while the positions of the field are not indexed.
+    pub fn phrase_query(
+        &self,
+        field: Field,
+        text: &str,
+        slop: u32,
+    ) -> crate::Result<Box<dyn Query>> {
+        let field_entry = self.schema().get_field_entry(field);
+        let FieldType::Str(text_options) = field_entry.field_type() else {
+            return Err(TantivyError::SchemaError(format!(
+                "{:?} is not a text field.",
+                field_entry.name()
+            )));
+        };
+        let index_record_option = text_options
+            .get_indexing_options()
+            .map(TextFieldIndexing::index_option)
+            .unwrap_or(IndexRecordOption::Basic);
+        let mut terms: Vec<(usize, Term)> = self
+            .index()
+            .analyze(field, text)?
+            .into_iter()
+            .map(|token| (token.position, Term::from_field_text(field, &token.text)))
+            .collect();
+        match terms.len() {
+            0 => Ok(Box::new(EmptyQuery)),
+            1 => {
+                let (_, term) = terms.pop().unwrap();
+                Ok(Box::new(TermQuery::new(term, index_record_option)))
+            }
+            _ => {
+                if !index_record_option.has_positions() {
+                    return Err(TantivyError::SchemaError(format!(
+                        "The positions of the field {:?} are not indexed.",
+                        field_entry.name()
+                    )));
+                }
+                Ok(Box::new(PhraseQuery::new_with_offset_and_slop(terms, slop)))
+            }
+        }
+    }
+
+    /// Returns the distinct immediate children of `facet` in the facet field `field_name`, over
+    /// all of the segments, in sorted order.
+    ///
+    /// See [`FacetReader::children_of`](crate::fastfield::FacetReader::children_of).
+    pub fn facet_children_of(&self, field_name: &str, facet: &Facet) -> crate::Result<Vec<Facet>> {
+        let mut children: BTreeSet<Facet> = BTreeSet::new();
+        for segment_reader in &self.inner.segment_readers {
+            let facet_reader = segment_reader.facet_reader(field_name)?;
+            children.extend(facet_reader.children_of(facet)?);
+        }
+        Ok(children.into_iter().collect())
+    }
+
     /// Return the overall number of documents containing
     /// the given term.
     pub fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
@@ -140,6 +341,53 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Returns the `n` terms of `field` with the highest document frequency across all
+    /// segments, sorted by decreasing document frequency.
+    ///
+    /// The candidates are the top `n` terms of each segment, as returned by
+    /// [`InvertedIndexReader::top_terms_by_doc_freq`](crate::InvertedIndexReader::top_terms_by_doc_freq).
+    /// The document frequencies of the candidates are then summed over all of the segments,
+    /// so the returned document frequencies are exact. The result is however approximate when
+    /// there are several segments: a term that is not in the top `n` of any segment is missed,
+    /// even if its overall document frequency would rank it in the top `n`.
+    ///
+    /// Document frequencies include deleted documents.
+    pub fn top_terms_by_doc_freq(
+        &self,
+        field: Field,
+        n: usize,
+    ) -> crate::Result<Vec<(Vec<u8>, u64)>> {
+        let inverted_indexes = self
+            .inner
+            .segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(field))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let mut candidates: HashSet<Vec<u8>> = HashSet::new();
+        for inverted_index in &inverted_indexes {
+            for (term, _) in inverted_index.top_terms_by_doc_freq(n)? {
+                candidates.insert(term);
+            }
+        }
+        let mut top_terms: Vec<(Vec<u8>, u64)> = Vec::with_capacity(candidates.len());
+        for term in candidates {
+            let mut doc_freq = 0u64;
+            for inverted_index in &inverted_indexes {
+                if let Some(term_info) = inverted_index.terms().get(&term)? {
+                    doc_freq += u64::from(term_info.doc_freq);
+                }
+            }
+            top_terms.push((term, doc_freq));
+        }
+        top_terms.sort_by(|(left_term, left_doc_freq), (right_term, right_doc_freq)| {
+            right_doc_freq
+                .cmp(left_doc_freq)
+                .then_with(|| left_term.cmp(right_term))
+        });
+        top_terms.truncate(n);
+        Ok(top_terms)
+    }
+
     /// Return the overall number of documents containing
     /// the given term in an asynchronous manner.
     #[cfg(feature = "quickwit")]
@@ -153,6 +401,73 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Returns the set of fields that hold data in at least one segment.
+    ///
+    /// See [`SegmentReader::populated_fields`] for the details and the cost of this operation.
+    pub fn populated_fields(&self) -> crate::Result<HashSet<Field>> {
+        let mut populated_fields = HashSet::new();
+        for segment_reader in &self.inner.segment_readers {
+            populated_fields.extend(segment_reader.populated_fields()?);
+        }
+        Ok(populated_fields)
+    }
+
+    /// Returns, for each fast or indexed field, the fraction of the alive documents having at
+    /// least one value for it.
+    ///
+    /// The fill rate of a field is the number of alive documents with a value for the field,
+    /// summed over the segments, divided by [`Searcher::num_docs`]. It is 0.0 for all of the
+    /// fields of an empty index. See [`SegmentReader::num_docs_with_value_per_field`] for how
+    /// the values are detected: this reads the postings of the indexed fields without fast
+    /// field or field norms, which can be costly.
+    pub fn field_fill_rates(&self) -> crate::Result<HashMap<Field, f64>> {
+        let mut num_docs_with_value: HashMap<Field, u64> = HashMap::new();
+        for segment_reader in &self.inner.segment_readers {
+            for (field, num_docs) in segment_reader.num_docs_with_value_per_field()? {
+                *num_docs_with_value.entry(field).or_default() += u64::from(num_docs);
+            }
+        }
+        let num_docs = self.num_docs();
+        Ok(num_docs_with_value
+            .into_iter()
+            .map(|(field, num_docs_with_value)| {
+                let fill_rate = if num_docs == 0 {
+                    0.0
+                } else {
+                    num_docs_with_value as f64 / num_docs as f64
+                };
+                (field, fill_rate)
+            })
+            .collect())
+    }
+
+    /// Returns the number of documents matching both `query_a` and `query_b`.
+    ///
+    /// The scorers of both queries are intersected segment by segment, with scoring disabled.
+    /// Deleted documents are not counted.
+    pub fn intersection_count(
+        &self,
+        query_a: &dyn Query,
+        query_b: &dyn Query,
+    ) -> crate::Result<u64> {
+        let enable_scoring = EnableScoring::disabled_from_searcher(self);
+        let weight_a = query_a.weight(enable_scoring)?;
+        let weight_b = query_b.weight(enable_scoring)?;
+        let mut count = 0u64;
+        for segment_reader in &self.inner.segment_readers {
+            let scorer_a = weight_a.scorer(segment_reader, 1.0)?;
+            let scorer_b = weight_b.scorer(segment_reader, 1.0)?;
+            let mut intersection = intersect_scorers(vec![scorer_a, scorer_b]);
+            let segment_count = if let Some(alive_bitset) = segment_reader.alive_bitset() {
+                intersection.count(alive_bitset)
+            } else {
+                intersection.count_including_deleted()
+            };
+            count += u64::from(segment_count);
+        }
+        Ok(count)
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.inner.segment_readers
@@ -163,6 +478,69 @@ impl Searcher {
         &self.inner.segment_readers[segment_ord as usize]
     }
 
+    /// Returns the segments holding the given hits, such as the ones returned by
+    /// [`TopDocs`](crate::collector::TopDocs), along with their number of hits.
+    ///
+    /// The segments are ordered by segment ordinal, and the segments without hits are left out.
+    /// This tells which segments were productive for a query, for instance to decide which
+    /// segments to cache or to prefetch.
+    ///
+    /// Panics if a hit does not belong to this searcher.
+    pub fn segments_of_hits(
+        &self,
+        doc_addresses: impl IntoIterator<Item = DocAddress>,
+    ) -> Vec<SegmentHits> {
+        let mut num_hits_per_segment: BTreeMap<SegmentOrdinal, usize> = BTreeMap::new();
+        for doc_address in doc_addresses {
+            *num_hits_per_segment
+                .entry(doc_address.segment_ord)
+                .or_default() += 1;
+        }
+        num_hits_per_segment
+            .into_iter()
+            .map(|(segment_ord, num_hits)| SegmentHits {
+                segment_ord,
+                segment_id: self.segment_reader(segment_ord).segment_id(),
+                num_hits,
+            })
+            .collect()
+    }
+
+    /// Returns a searcher over the segments of this searcher, except the given ones.
+    ///
+    /// This makes it possible to check whether a result comes from a given segment, or to keep
+    /// serving from the other segments while a segment is suspected to be corrupted.
+    ///
+    /// The excluded segments are left out of everything: their documents are not searched nor
+    /// counted in [`Searcher::num_docs`], and they are not taken into account by the scoring
+    /// statistics. The segment ordinals of the returned searcher are the positions of the
+    /// remaining segments, and may differ from the ones of this searcher. Segment ids that do not
+    /// belong to this searcher are ignored.
+    ///
+    /// The returned searcher keeps the generation id of this searcher, but is not tracked by the
+    /// [`IndexReader`](crate::IndexReader), and is therefore not warmed.
+    pub fn without_segments(&self, segment_ids: &[SegmentId]) -> crate::Result<Searcher> {
+        let segment_readers: Vec<SegmentReader> = self
+            .inner
+            .segment_readers
+            .iter()
+            .filter(|segment_reader| !segment_ids.contains(&segment_reader.segment_id()))
+            .cloned()
+            .collect();
+        let generation = SearcherGeneration::from_segment_readers(
+            &segment_readers,
+            self.generation().generation_id(),
+        );
+        let searcher_inner = SearcherInner::new(
+            self.inner.schema.clone(),
+            self.inner.index.clone(),
+            segment_readers,
+            Inventory::default().track(generation),
+            self.inner.doc_store_cache_num_blocks,
+        )?;
+        Ok(Arc::new(searcher_inner).into())
+    }
+
     /// Runs a query on the segment readers wrapped by the searcher.
     ///
     /// Search works as follows :
@@ -235,6 +613,108 @@ impl Searcher {
         collector.merge_fruits(fruits)
     }
 
+    /// Same as [`search(...)`](Searcher::search), but stops searching once `deadline` is
+    /// passed, and returns the results collected so far.
+    ///
+    /// Returns the fruit of the collector along with `true` if the search timed out, in which
+    /// case the fruit is incomplete.
+    ///
+    /// The segments are dispatched to the search executor of the index, as with
+    /// [`search(...)`](Searcher::search). The deadline is checked before searching each segment,
+    /// and then every 1,024 documents matched in the segment: the search can overrun the
+    /// deadline by the time it takes to match and collect as many documents. The partial
+    /// results of a segment are the documents preceding the check that found the deadline
+    /// passed, in the order of their ids.
+    ///
+    /// The documents of each segment are scored and collected one at a time, without the
+    /// specialized [`Collector::collect_segment`] of the collector, such as the pruning of
+    /// [`TopDocs`](crate::collector::TopDocs).
+    pub fn search_with_deadline<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        deadline: Instant,
+    ) -> crate::Result<(C::Fruit, bool)> {
+        self.search_until(query, collector, || Instant::now() >= deadline)
+    }
+
+    /// Same as [`search_with_deadline(...)`](Searcher::search_with_deadline), but stops
+    /// searching once `should_stop` returns true.
+    pub(crate) fn search_until<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        should_stop: impl Fn() -> bool + Sync,
+    ) -> crate::Result<(C::Fruit, bool)> {
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        };
+        let weight = query.weight(enabled_scoring)?;
+        let timed_out = AtomicBool::new(false);
+        let executor = self.inner.index.search_executor();
+        let segment_fruits = executor.map(
+            |(segment_ord, segment_reader)| {
+                if should_stop() {
+                    timed_out.store(true, Ordering::Relaxed);
+                    return Ok(None);
+                }
+                let (segment_fruit, segment_timed_out) = collect_segment_until(
+                    collector,
+                    weight.as_ref(),
+                    segment_ord as SegmentOrdinal,
+                    segment_reader,
+                    &should_stop,
+                )?;
+                if segment_timed_out {
+                    timed_out.store(true, Ordering::Relaxed);
+                }
+                Ok(Some(segment_fruit))
+            },
+            self.segment_readers().iter().enumerate(),
+        )?;
+        let fruits = segment_fruits.into_iter().flatten().collect();
+        Ok((collector.merge_fruits(fruits)?, timed_out.into_inner()))
+    }
+
+    /// Returns an iterator over all of the documents matching `query`, sorted by the
+    /// fast field `field_name`.
+    ///
+    /// Unlike [`TopDocs::order_by_fast_field`](crate::collector::TopDocs::order_by_fast_field),
+    /// there is no limit: documents are merged lazily across segments with a k-way merge heap
+    /// over per-segment cursors, so the caller can stop consuming the iterator at any time.
+    ///
+    /// The iterator yields the address of each document together with the first value of
+    /// `field_name` for this document. Documents without a value are yielded last, in both
+    /// orders. Ties are broken by `DocAddress`.
+    ///
+    /// # Cost
+    ///
+    /// Segments are not physically sorted by the field: the index has no `sort_by_field`
+    /// setting, so `field_name` is never the order of the doc ids. Each segment cursor hence
+    /// runs the query once over its segment when the iterator is created, and sorts the
+    /// matching documents by their value. For a segment with `m` matching documents, this
+    /// costs `O(m log m)` and buffers the `m` sort keys, whatever the number of documents
+    /// consumed. Stored documents are not loaded.
+    ///
+    /// Returns an error if the field does not exist, if it is not a fast field of type `T`, or
+    /// if the query fails on a segment.
+    pub fn search_sorted_streaming<T>(
+        &self,
+        query: &dyn Query,
+        field_name: &str,
+        order: Order,
+    ) -> crate::Result<SortedDocStream<T>>
+    where
+        T: FastValue + HasAssociatedColumnType,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        check_sort_field::<T>(self.schema(), field_name)?;
+        let weight = query.weight(EnableScoring::disabled_from_searcher(self))?;
+        SortedDocStream::new(weight.as_ref(), self.segment_readers(), field_name, order)
+    }
+
     /// Summarize total space usage of this searcher.
     pub fn space_usage(&self) -> io::Result<SearcherSpaceUsage> {
         let mut space_usage = SearcherSpaceUsage::new();
@@ -261,6 +741,7 @@ pub(crate) struct SearcherInner {
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
     generation: TrackedObject<SearcherGeneration>,
+    doc_store_cache_num_blocks: usize,
 }
 
 impl SearcherInner {
@@ -291,10 +772,47 @@ impl SearcherInner {
             segment_readers,
             store_readers,
             generation,
+            doc_store_cache_num_blocks,
         })
     }
 }
 
+/// Collects the documents of a segment matching `weight`, checking `should_stop` every
+/// [`DEADLINE_CHECK_INTERVAL`] documents.
+///
+/// Returns the fruit of the documents collected so far, along with `true` if `should_stop`
+/// stopped the collection before the last document of the segment.
+fn collect_segment_until<C: Collector>(
+    collector: &C,
+    weight: &dyn Weight,
+    segment_ord: SegmentOrdinal,
+    segment_reader: &SegmentReader,
+    should_stop: &dyn Fn() -> bool,
+) -> crate::Result<(<C::Child as SegmentCollector>::Fruit, bool)> {
+    let mut segment_collector = collector.for_segment(segment_ord, segment_reader)?;
+    let requires_scoring = collector.requires_scoring();
+    let alive_bitset = segment_reader.alive_bitset();
+    let mut scorer = weight.scorer(segment_reader, 1.0)?;
+    let mut num_docs = 0u32;
+    let mut doc = scorer.doc();
+    while doc != TERMINATED {
+        if alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(doc)) {
+            let score = if requires_scoring {
+                scorer.score()
+            } else {
+                0.0
+            };
+            segment_collector.collect(doc, score);
+        }
+        doc = scorer.advance();
+        num_docs += 1;
+        if doc != TERMINATED && num_docs % DEADLINE_CHECK_INTERVAL == 0 && should_stop() {
+            return Ok((segment_collector.harvest(), true));
+        }
+    }
+    Ok((segment_collector.harvest(), false))
+}
+
 impl fmt::Debug for Searcher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let segment_ids = self
@@ -0,0 +1,172 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::marker::PhantomData;
+
+use columnar::{Column, DynamicColumn, HasAssociatedColumnType};
+
+use crate::fastfield::FastValue;
+use crate::index::SegmentReader;
+use crate::query::Weight;
+use crate::schema::FieldType;
+use crate::{DocAddress, DocId, DocSet, Order, SegmentOrdinal, TantivyError, TERMINATED};
+
+/// Sort key of a document. Documents without a value sort after all of the
+/// documents with a value, whatever the order.
+type SortKey = (bool, u64);
+
+/// A document with its sort key, and the `u64` representation of its value.
+type SortedDoc = (SortKey, DocId, Option<u64>);
+
+/// The head of a segment cursor. Ties on the sort key are broken by `DocAddress`.
+type HeapEntry = Reverse<(SortKey, SegmentOrdinal, DocId, Option<u64>)>;
+
+fn sort_key(value: Option<u64>, order: &Order) -> SortKey {
+    match value {
+        Some(value) if order.is_desc() => (false, u64::MAX - value),
+        Some(value) => (false, value),
+        None => (true, 0),
+    }
+}
+
+/// The matching documents of a segment, in the order of their sort key.
+///
+/// Segments are not sorted by the field (the index has no `sort_by_field` setting), so the
+/// cursor advances the scorer of the query once over the whole segment, and sorts the sort keys
+/// of the matching documents.
+struct SegmentCursor {
+    sorted_docs: std::vec::IntoIter<SortedDoc>,
+}
+
+impl SegmentCursor {
+    fn open<T>(
+        weight: &dyn Weight,
+        segment_reader: &SegmentReader,
+        field_name: &str,
+        order: &Order,
+    ) -> crate::Result<SegmentCursor>
+    where
+        T: FastValue + HasAssociatedColumnType,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        let column_opt = segment_reader.fast_fields().column_opt::<T>(field_name)?;
+        let alive_bitset = segment_reader.alive_bitset();
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        let mut sorted_docs: Vec<SortedDoc> = Vec::new();
+        let mut doc = scorer.doc();
+        while doc != TERMINATED {
+            if alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(doc)) {
+                let value = column_opt
+                    .as_ref()
+                    .and_then(|column| column.first(doc))
+                    .map(|value| value.to_u64());
+                sorted_docs.push((sort_key(value, order), doc, value));
+            }
+            doc = scorer.advance();
+        }
+        sorted_docs.sort_unstable();
+        Ok(SegmentCursor {
+            sorted_docs: sorted_docs.into_iter(),
+        })
+    }
+
+    fn next(&mut self) -> Option<SortedDoc> {
+        self.sorted_docs.next()
+    }
+
+    /// Number of matching documents not returned by the cursor yet.
+    fn num_remaining_docs(&self) -> usize {
+        self.sorted_docs.len()
+    }
+}
+
+/// Iterator over the documents matching a query, sorted by a fast field, across all of the
+/// segments of a [`Searcher`](crate::Searcher).
+///
+/// See [`Searcher::search_sorted_streaming`](crate::Searcher::search_sorted_streaming).
+pub struct SortedDocStream<T> {
+    cursors: Vec<SegmentCursor>,
+    heap: BinaryHeap<HeapEntry>,
+    // segment whose head was returned last, and has to be pushed to the heap again.
+    segment_to_advance: Option<SegmentOrdinal>,
+    _value_type: PhantomData<T>,
+}
+
+impl<T: FastValue> SortedDocStream<T> {
+    pub(crate) fn new(
+        weight: &dyn Weight,
+        segment_readers: &[SegmentReader],
+        field_name: &str,
+        order: Order,
+    ) -> crate::Result<SortedDocStream<T>>
+    where
+        T: HasAssociatedColumnType,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        let mut stream = SortedDocStream {
+            cursors: Vec::with_capacity(segment_readers.len()),
+            heap: BinaryHeap::new(),
+            segment_to_advance: None,
+            _value_type: PhantomData,
+        };
+        for segment_reader in segment_readers {
+            let cursor = SegmentCursor::open::<T>(weight, segment_reader, field_name, &order)?;
+            stream.cursors.push(cursor);
+            stream.push_next(stream.cursors.len() as SegmentOrdinal - 1);
+        }
+        Ok(stream)
+    }
+
+    fn push_next(&mut self, segment_ord: SegmentOrdinal) {
+        if let Some((sort_key, doc, value)) = self.cursors[segment_ord as usize].next() {
+            self.heap.push(Reverse((sort_key, segment_ord, doc, value)));
+        }
+    }
+}
+
+impl<T: FastValue> Iterator for SortedDocStream<T> {
+    type Item = (DocAddress, Option<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(segment_ord) = self.segment_to_advance.take() {
+            self.push_next(segment_ord);
+        }
+        let Reverse((_, segment_ord, doc, value)) = self.heap.pop()?;
+        self.segment_to_advance = Some(segment_ord);
+        Some((DocAddress::new(segment_ord, doc), value.map(T::from_u64)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.heap.len()
+            + self
+                .cursors
+                .iter()
+                .map(|cursor| cursor.num_remaining_docs())
+                .sum::<usize>();
+        (len, Some(len))
+    }
+}
+
+/// Checks that `field_name` is a fast field of type `T`.
+pub(crate) fn check_sort_field<T: FastValue>(
+    schema: &crate::schema::Schema,
+    field_name: &str,
+) -> crate::Result<()> {
+    let (field, _) = schema
+        .find_field(field_name)
+        .ok_or_else(|| TantivyError::FieldNotFound(field_name.to_string()))?;
+    let field_entry = schema.get_field_entry(field);
+    if !field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {field_name:?} is not a fast field"
+        )));
+    }
+    let field_type = field_entry.field_type();
+    if !matches!(field_type, FieldType::JsonObject(_)) && field_type.value_type() != T::to_type() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {field_name:?} is of type {:?}, not {:?}",
+            field_type.value_type(),
+            T::to_type()
+        )));
+    }
+    Ok(())
+}
@@ -1,16 +1,80 @@
-use crate::collector::Count;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use crate::collector::{Count, DocSetCollector, TopDocs};
 use crate::directory::{RamDirectory, WatchCallback};
-use crate::index::SegmentId;
+use crate::index::{SegmentComponent, SegmentId};
 use crate::indexer::{LogMergePolicy, NoMergePolicy};
 use crate::postings::Postings;
-use crate::query::TermQuery;
-use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, STRING, TEXT};
-use crate::tokenizer::TokenizerManager;
+use crate::query::{
+    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, Occur, PhraseQuery, Query, QueryParser,
+    RangeQuery, TermQuery,
+};
+use crate::schema::document::Document;
+use crate::schema::{
+    Field, IndexRecordOption, JsonObjectOptions, OwnedValue, Schema, TextFieldIndexing,
+    TextOptions, Type, Value, FAST, INDEXED, STORED, STRING, TEXT,
+};
+use crate::termdict::TermDictionaryAccess;
+use crate::tokenizer::{
+    LowerCaser, RawTokenizer, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenizerManager,
+};
 use crate::{
-    Directory, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexWriter, ReloadPolicy,
-    TantivyDocument, Term,
+    Directory, DocAddress, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexWriter,
+    Order, ReloadPolicy, Searcher, TantivyDocument, Term,
 };
 
+#[test]
+fn test_validate_tokenizers() {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("title", TEXT);
+    let indexing = TextFieldIndexing::default().set_tokenizer("en_stemm");
+    schema_builder.add_text_field(
+        "body",
+        TextOptions::default().set_indexing_options(indexing.clone()),
+    );
+    schema_builder.add_text_field(
+        "summary",
+        TextOptions::default().set_indexing_options(indexing),
+    );
+    schema_builder.add_text_field("tag", TextOptions::default().set_fast(Some("lowercase")));
+    schema_builder.add_json_field(
+        "attributes",
+        JsonObjectOptions::default().set_fast(Some("raw")),
+    );
+    let index = Index::create_in_ram(schema_builder.build());
+    assert_eq!(
+        index.validate_tokenizers(),
+        Err(vec!["en_stemm".to_string(), "lowercase".to_string()])
+    );
+
+    index
+        .tokenizers()
+        .register("en_stemm", RawTokenizer::default());
+    index
+        .fast_field_tokenizer()
+        .register("lowercase", RawTokenizer::default());
+    assert_eq!(index.validate_tokenizers(), Ok(()));
+    assert!(index
+        .tokenizers()
+        .registered_names()
+        .contains(&"en_stemm".to_string()));
+}
+
+#[test]
+fn test_registered_tokenizer_names() {
+    let tokenizer_manager = TokenizerManager::new();
+    assert!(tokenizer_manager.registered_names().is_empty());
+    tokenizer_manager.register("whitespace", RawTokenizer::default());
+    tokenizer_manager.register("raw", RawTokenizer::default());
+    assert_eq!(tokenizer_manager.registered_names(), ["raw", "whitespace"]);
+    assert_eq!(
+        TokenizerManager::default().registered_names(),
+        ["cjk", "default", "en_stem", "raw", "whitespace"]
+    );
+}
+
 #[test]
 fn test_indexer_for_field() {
     let mut schema_builder = Schema::builder();
@@ -25,6 +89,45 @@ fn test_indexer_for_field() {
     );
 }
 
+#[test]
+fn test_index_analyze() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let num_likes_field = schema_builder.add_u64_field("num_likes", INDEXED);
+    let body_field = schema_builder.add_text_field(
+        "body",
+        TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("en_stem")),
+    );
+    let index = Index::create_in_ram(schema_builder.build());
+    let tokens = index.analyze(body_field, "The Running dogs, jumped!")?;
+    let tokens: Vec<(usize, &str, usize, usize)> = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.position,
+                token.text.as_str(),
+                token.offset_from,
+                token.offset_to,
+            )
+        })
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            (0, "the", 0, 3),
+            (1, "run", 4, 11),
+            (2, "dog", 12, 16),
+            (3, "jump", 18, 24)
+        ]
+    );
+    assert!(index.analyze(body_field, "")?.is_empty());
+    assert!(matches!(
+        index.analyze(num_likes_field, "12"),
+        Err(crate::TantivyError::SchemaError(_))
+    ));
+    Ok(())
+}
+
 #[test]
 fn test_set_tokenizer_manager() {
     let mut schema_builder = Schema::builder();
@@ -466,3 +569,1405 @@ fn test_non_text_json_term_freq_bitpacked() {
         assert_eq!(postings.term_freq(), 1u32);
     }
 }
+
+#[test]
+fn test_searcher_intersection_count() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(text => "a b"))?;
+    index_writer.add_document(doc!(text => "a"))?;
+    index_writer.add_document(doc!(text => "b c"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(text => "a b c"))?;
+    index_writer.add_document(doc!(text => "a b d"))?;
+    index_writer.add_document(doc!(text => "d"))?;
+    index_writer.delete_term(Term::from_field_text(text, "d"));
+    index_writer.add_document(doc!(text => "a b"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 2);
+    let term_query =
+        |word: &str| TermQuery::new(Term::from_field_text(text, word), IndexRecordOption::Basic);
+    assert_eq!(
+        searcher.intersection_count(&term_query("a"), &term_query("b"))?,
+        3
+    );
+    assert_eq!(
+        searcher.intersection_count(&term_query("b"), &term_query("c"))?,
+        2
+    );
+    assert_eq!(
+        searcher.intersection_count(&term_query("a"), &term_query("a"))?,
+        4
+    );
+    assert_eq!(
+        searcher.intersection_count(&term_query("a"), &term_query("d"))?,
+        0
+    );
+    assert_eq!(
+        searcher.intersection_count(&term_query("a"), &term_query("z"))?,
+        0
+    );
+    assert_eq!(searcher.intersection_count(&term_query("c"), &AllQuery)?, 2);
+    assert_eq!(searcher.intersection_count(&AllQuery, &EmptyQuery)?, 0);
+    Ok(())
+}
+
+#[test]
+fn test_index_segment_metas() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    assert!(index.segment_metas()?.is_empty());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    let mut first_segment_opstamp = 0;
+    for i in 0..10 {
+        first_segment_opstamp =
+            index_writer.add_document(doc!(text => if i < 2 { "deleted" } else { "kept" }))?;
+    }
+    index_writer.commit()?;
+    let mut second_segment_opstamp = 0;
+    for _ in 0..5 {
+        second_segment_opstamp = index_writer.add_document(doc!(text => "kept"))?;
+    }
+    index_writer.delete_term(Term::from_field_text(text, "deleted"));
+    index_writer.commit()?;
+
+    let mut segment_metas = index.segment_metas()?;
+    segment_metas.sort_by_key(|segment_meta| std::cmp::Reverse(segment_meta.max_doc));
+    assert_eq!(segment_metas.len(), 2);
+    assert_eq!(segment_metas[0].max_doc, 10);
+    assert_eq!(segment_metas[0].num_docs, 8);
+    assert_eq!(segment_metas[0].num_deleted_docs, 2);
+    assert_eq!(
+        segment_metas[0].creation_opstamp,
+        Some(first_segment_opstamp)
+    );
+    assert!((segment_metas[0].delete_ratio() - 0.2).abs() < 1e-9);
+    assert_eq!(segment_metas[1].max_doc, 5);
+    assert_eq!(segment_metas[1].num_docs, 5);
+    assert_eq!(segment_metas[1].num_deleted_docs, 0);
+    assert_eq!(segment_metas[1].delete_ratio(), 0.0);
+    assert_eq!(
+        segment_metas[1].creation_opstamp,
+        Some(second_segment_opstamp)
+    );
+
+    let directory = index.directory();
+    for segment_meta in index.searchable_segment_metas()? {
+        let expected_num_bytes: u64 = segment_meta
+            .list_files()
+            .iter()
+            .filter(|path| directory.exists(path).unwrap())
+            .map(|path| directory.atomic_read(path).unwrap().len() as u64)
+            .sum();
+        let segment_meta_info = segment_metas
+            .iter()
+            .find(|segment_meta_info| segment_meta_info.segment_id == segment_meta.id())
+            .unwrap();
+        assert!(expected_num_bytes > 0);
+        assert_eq!(segment_meta_info.num_bytes, expected_num_bytes);
+    }
+
+    // A merged segment was created with the last of the segments merged.
+    let segment_ids = index.searchable_segment_ids()?;
+    index_writer.merge(&segment_ids).wait()?;
+    let segment_metas = index.segment_metas()?;
+    assert_eq!(segment_metas.len(), 1);
+    assert_eq!(segment_metas[0].num_docs, 13);
+    assert_eq!(
+        segment_metas[0].creation_opstamp,
+        Some(second_segment_opstamp)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_search_sorted_streaming() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", STRING);
+    let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for segment in 0..3i64 {
+        for i in 0..20i64 {
+            let label = if i % 5 == 0 { "deleted" } else { "kept" };
+            if i % 6 == 0 {
+                index_writer.add_document(doc!(text => label))?;
+            } else {
+                let value = (i * 7919 + segment * 31) % 23 - 11;
+                index_writer.add_document(doc!(text => label, timestamp => value))?;
+            }
+        }
+        index_writer.add_document(doc!(text => "other", timestamp => 0i64))?;
+        index_writer.commit()?;
+    }
+    index_writer.delete_term(Term::from_field_text(text, "deleted"));
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 3);
+
+    let query = TermQuery::new(
+        Term::from_field_text(text, "kept"),
+        IndexRecordOption::Basic,
+    );
+    let matches: Vec<(DocAddress, Option<i64>)> = searcher
+        .search(&query, &DocSetCollector)?
+        .into_iter()
+        .map(|doc_address| {
+            let column = searcher
+                .segment_reader(doc_address.segment_ord)
+                .fast_fields()
+                .i64("timestamp")
+                .unwrap();
+            (doc_address, column.first(doc_address.doc_id))
+        })
+        .collect();
+    assert_eq!(matches.len(), 3 * 16);
+    for order in [Order::Asc, Order::Desc] {
+        let mut expected = matches.clone();
+        expected.sort_by_key(|(doc_address, value)| match value {
+            Some(value) if order.is_desc() => (false, -value, *doc_address),
+            Some(value) => (false, *value, *doc_address),
+            None => (true, 0, *doc_address),
+        });
+        let mut stream =
+            searcher.search_sorted_streaming::<i64>(&query, "timestamp", order.clone())?;
+        let mut docs = Vec::new();
+        assert_eq!(stream.size_hint(), (expected.len(), Some(expected.len())));
+        while let Some(doc) = stream.next() {
+            docs.push(doc);
+            let num_remaining_docs = expected.len() - docs.len();
+            assert_eq!(
+                stream.size_hint(),
+                (num_remaining_docs, Some(num_remaining_docs))
+            );
+        }
+        assert_eq!(docs, expected);
+    }
+
+    assert!(searcher
+        .search_sorted_streaming::<u64>(&query, "timestamp", Order::Asc)
+        .is_err());
+    assert!(searcher
+        .search_sorted_streaming::<i64>(&query, "text", Order::Asc)
+        .is_err());
+    assert!(searcher
+        .search_sorted_streaming::<i64>(&query, "missing", Order::Asc)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_top_terms_by_doc_freq() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    // Overall doc freqs: rust=6, search=5, index=4, tantivy=4, fst=2, zebra=1.
+    let segments: [&[(&str, usize)]; 2] = [
+        &[("rust", 4), ("index", 3), ("search", 1), ("fst", 2)],
+        &[
+            ("search", 4),
+            ("rust", 2),
+            ("tantivy", 4),
+            ("index", 1),
+            ("zebra", 1),
+        ],
+    ];
+    for segment in segments {
+        for &(term, doc_freq) in segment {
+            for _ in 0..doc_freq {
+                index_writer.add_document(doc!(tag => term))?;
+            }
+        }
+        index_writer.commit()?;
+    }
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 2);
+
+    let to_str = |top_terms: Vec<(Vec<u8>, u64)>| -> Vec<(String, u64)> {
+        top_terms
+            .into_iter()
+            .map(|(term, doc_freq)| (String::from_utf8(term).unwrap(), doc_freq))
+            .collect()
+    };
+    let segment_ords_by_max_doc: Vec<usize> = {
+        let mut ords: Vec<usize> = (0..2).collect();
+        ords.sort_by_key(|&ord| searcher.segment_readers()[ord].max_doc());
+        ords
+    };
+    // The first segment holds 10 docs, the second 12 docs.
+    let first_segment = &searcher.segment_readers()[segment_ords_by_max_doc[0]];
+    assert_eq!(
+        to_str(
+            first_segment
+                .inverted_index(tag)?
+                .top_terms_by_doc_freq(3)?
+        ),
+        vec![
+            ("rust".to_string(), 4),
+            ("index".to_string(), 3),
+            ("fst".to_string(), 2)
+        ]
+    );
+    let second_segment = &searcher.segment_readers()[segment_ords_by_max_doc[1]];
+    assert_eq!(
+        to_str(
+            second_segment
+                .inverted_index(tag)?
+                .top_terms_by_doc_freq(2)?
+        ),
+        vec![("search".to_string(), 4), ("tantivy".to_string(), 4)]
+    );
+    assert!(first_segment
+        .inverted_index(tag)?
+        .top_terms_by_doc_freq(0)?
+        .is_empty());
+    assert_eq!(
+        to_str(
+            first_segment
+                .inverted_index(tag)?
+                .top_terms_by_doc_freq(10)?
+        )
+        .len(),
+        4
+    );
+
+    assert_eq!(
+        to_str(searcher.top_terms_by_doc_freq(tag, 4)?),
+        vec![
+            ("rust".to_string(), 6),
+            ("search".to_string(), 5),
+            ("index".to_string(), 4),
+            ("tantivy".to_string(), 4),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_doc_by_term() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id = schema_builder.add_text_field("id", STRING | STORED);
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(id => "a", title => "first"))?;
+    index_writer.add_document(doc!(id => "b", title => "second"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(id => "c", title => "third"))?;
+    index_writer.add_document(doc!(id => "dup", title => "duplicate 1"))?;
+    index_writer.add_document(doc!(id => "dup", title => "duplicate 2"))?;
+    index_writer.add_document(doc!(id => "deleted", title => "deleted"))?;
+    index_writer.commit()?;
+    index_writer.delete_term(Term::from_field_text(id, "deleted"));
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+
+    let title_by_id = |id_value: &str| -> crate::Result<Option<(DocAddress, String)>> {
+        let doc_opt =
+            searcher.doc_by_term::<TantivyDocument>(&Term::from_field_text(id, id_value))?;
+        Ok(doc_opt.map(|(doc_address, doc)| {
+            let title_value = doc.get_first(title).and_then(|value| value.as_str());
+            (doc_address, title_value.unwrap().to_string())
+        }))
+    };
+    let (doc_address, title_value) = title_by_id("c")?.unwrap();
+    assert_eq!(title_value, "third");
+    let doc: TantivyDocument = searcher.doc(doc_address)?;
+    assert_eq!(
+        doc.get_first(id).and_then(|value| value.as_str()),
+        Some("c")
+    );
+    assert_eq!(title_by_id("a")?.unwrap().1, "first");
+    assert!(title_by_id("missing")?.is_none());
+    assert!(title_by_id("deleted")?.is_none());
+    // With duplicates, the document with the smallest address is returned.
+    let (doc_address, title_value) = title_by_id("dup")?.unwrap();
+    assert_eq!(title_value, "duplicate 1");
+    let dup_docs = searcher.search(
+        &TermQuery::new(Term::from_field_text(id, "dup"), IndexRecordOption::Basic),
+        &DocSetCollector,
+    )?;
+    assert_eq!(dup_docs.len(), 2);
+    assert_eq!(dup_docs.into_iter().min(), Some(doc_address));
+    Ok(())
+}
+
+#[test]
+fn test_index_fingerprint() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let num_likes = schema_builder.add_u64_field("num_likes", FAST | INDEXED);
+    let schema = schema_builder.build();
+    let build_index = |last_title: &str| -> crate::Result<Index> {
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(title => "the old man", num_likes => 3u64))?;
+        index_writer.add_document(doc!(title => "the sea", num_likes => 7u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => last_title, num_likes => 1u64))?;
+        index_writer.commit()?;
+        Ok(index)
+    };
+    let index = build_index("the fish")?;
+    let same_index = build_index("the fish")?;
+    let other_index = build_index("the boat")?;
+    assert_eq!(index.fingerprint()?, same_index.fingerprint()?);
+    assert_ne!(index.fingerprint()?, other_index.fingerprint()?);
+
+    let segment_fingerprints = |index: &Index| -> crate::Result<Vec<u128>> {
+        let mut segment_fingerprints: Vec<u128> = index
+            .reader()?
+            .searcher()
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.content_fingerprint())
+            .collect::<Result<_, _>>()?;
+        segment_fingerprints.sort_unstable();
+        Ok(segment_fingerprints)
+    };
+    let fingerprints = segment_fingerprints(&index)?;
+    assert_eq!(fingerprints.len(), 2);
+    assert_eq!(fingerprints, segment_fingerprints(&same_index)?);
+    // Only the second segment differs.
+    let other_fingerprints = segment_fingerprints(&other_index)?;
+    assert_eq!(
+        fingerprints
+            .iter()
+            .filter(|fingerprint| other_fingerprints.contains(fingerprint))
+            .count(),
+        1
+    );
+
+    // Deleting a document changes the fingerprint.
+    let fingerprint_before_delete = same_index.fingerprint()?;
+    let mut index_writer: IndexWriter = same_index.writer_for_tests()?;
+    index_writer.delete_term(Term::from_field_u64(num_likes, 7));
+    index_writer.commit()?;
+    assert_ne!(same_index.fingerprint()?, fingerprint_before_delete);
+    Ok(())
+}
+
+#[test]
+fn test_stream_postings() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let body = schema_builder.add_text_field("body", TEXT);
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    let docs = [
+        ("the quick fox", "animal"),
+        ("a lazy dog and a quick cat", "animal"),
+        ("quick quick slow", "tempo"),
+    ];
+    for (body_text, tag_text) in docs {
+        index_writer.add_document(doc!(body => body_text, tag => tag_text))?;
+    }
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let segment_reader = searcher.segment_reader(0);
+
+    // The body is indexed with positions: its tokens can be rebuilt from the stream.
+    let mut rebuilt_bodies: Vec<Vec<(u32, String)>> = vec![Vec::new(); docs.len()];
+    let body_index = segment_reader.inverted_index(body)?;
+    let mut previous_term: Option<Vec<u8>> = None;
+    for term_postings in body_index.stream_postings()? {
+        let (term, mut postings) = term_postings?;
+        assert!(previous_term
+            .as_ref()
+            .is_none_or(|previous| previous < &term));
+        let text = String::from_utf8(term.clone()).unwrap();
+        let term_query =
+            TermQuery::new(Term::from_field_text(body, &text), IndexRecordOption::Basic);
+        let mut expected_docs: Vec<u32> = searcher
+            .search(&term_query, &DocSetCollector)?
+            .into_iter()
+            .map(|doc_address| doc_address.doc_id)
+            .collect();
+        let mut streamed_docs = Vec::new();
+        let mut positions = Vec::new();
+        while postings.doc() != crate::TERMINATED {
+            let doc = postings.doc();
+            streamed_docs.push(doc);
+            postings.positions(&mut positions);
+            assert_eq!(positions.len() as u32, postings.term_freq());
+            for &position in &positions {
+                rebuilt_bodies[doc as usize].push((position, text.clone()));
+            }
+            postings.advance();
+        }
+        expected_docs.sort_unstable();
+        assert_eq!(streamed_docs, expected_docs);
+        previous_term = Some(term);
+    }
+    for (rebuilt_body, (body_text, _)) in rebuilt_bodies.iter_mut().zip(docs) {
+        rebuilt_body.sort_unstable();
+        let tokens: Vec<&str> = rebuilt_body
+            .iter()
+            .map(|(_, token)| token.as_str())
+            .collect();
+        assert_eq!(tokens.join(" "), body_text);
+    }
+
+    // The tag is indexed without frequencies nor positions.
+    let tag_index = segment_reader.inverted_index(tag)?;
+    let mut streamed_tags = Vec::new();
+    for term_postings in tag_index.stream_postings()? {
+        let (term, mut postings) = term_postings?;
+        let mut positions = Vec::new();
+        while postings.doc() != crate::TERMINATED {
+            assert_eq!(postings.term_freq(), 1);
+            postings.positions(&mut positions);
+            assert!(positions.is_empty());
+            streamed_tags.push((String::from_utf8(term.clone()).unwrap(), postings.doc()));
+            postings.advance();
+        }
+    }
+    assert_eq!(
+        streamed_tags,
+        vec![
+            ("animal".to_string(), 0),
+            ("animal".to_string(), 1),
+            ("tempo".to_string(), 2)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_searcher_search_with_deadline() -> crate::Result<()> {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    use crate::collector::{Collector, SegmentCollector};
+    use crate::core::searcher::DEADLINE_CHECK_INTERVAL;
+    use crate::{SegmentOrdinal, SegmentReader};
+
+    // Flips a flag once it starts searching a segment.
+    struct FlaggingTopDocs {
+        top_docs: TopDocs,
+        searched_segment: AtomicBool,
+    }
+
+    impl Collector for FlaggingTopDocs {
+        type Fruit = <TopDocs as Collector>::Fruit;
+        type Child = <TopDocs as Collector>::Child;
+
+        fn for_segment(
+            &self,
+            segment_ord: SegmentOrdinal,
+            segment_reader: &SegmentReader,
+        ) -> crate::Result<Self::Child> {
+            self.searched_segment.store(true, Ordering::Relaxed);
+            self.top_docs.for_segment(segment_ord, segment_reader)
+        }
+
+        fn requires_scoring(&self) -> bool {
+            self.top_docs.requires_scoring()
+        }
+
+        fn merge_fruits(
+            &self,
+            segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+        ) -> crate::Result<Self::Fruit> {
+            self.top_docs.merge_fruits(segment_fruits)
+        }
+    }
+
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for segment in 0..3 {
+        for i in 0..=segment {
+            index_writer.add_document(doc!(title => format!("hello doc{segment}-{i}")))?;
+        }
+        index_writer.commit()?;
+    }
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 3);
+    let query = TermQuery::new(
+        Term::from_field_text(title, "hello"),
+        IndexRecordOption::Basic,
+    );
+
+    let collector = TopDocs::with_limit(10);
+    let deadline = Instant::now() + Duration::from_secs(3600);
+    let (top_docs, timed_out) = searcher.search_with_deadline(&query, &collector, deadline)?;
+    assert!(!timed_out);
+    assert_eq!(top_docs.len(), 6);
+
+    let (count, timed_out) = searcher.search_with_deadline(&query, &Count, Instant::now())?;
+    assert!(timed_out);
+    assert_eq!(count, 0);
+
+    // The search stops once the first segment is searched: only its hits are returned.
+    let collector = FlaggingTopDocs {
+        top_docs: TopDocs::with_limit(10),
+        searched_segment: AtomicBool::new(false),
+    };
+    let (top_docs, timed_out) = searcher.search_until(&query, &collector, || {
+        collector.searched_segment.load(Ordering::Relaxed)
+    })?;
+    assert!(timed_out);
+    let first_segment_num_docs = searcher.segment_reader(0).num_docs() as usize;
+    assert_eq!(top_docs.len(), first_segment_num_docs);
+    assert!(top_docs
+        .iter()
+        .all(|(_, doc_address)| doc_address.segment_ord == 0));
+    let full_results = searcher.search(&query, &TopDocs::with_limit(10))?;
+    for hit in &top_docs {
+        assert!(full_results.contains(hit));
+    }
+
+    // The deadline is checked within a segment too: the search of a single segment stops at the
+    // first check following the start of the search.
+    let index = Index::create_in_ram(index.schema());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    for i in 0..3 * DEADLINE_CHECK_INTERVAL {
+        index_writer.add_document(doc!(title => format!("hello doc{i}")))?;
+    }
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 1);
+    let num_checks = AtomicUsize::new(0);
+    let (count, timed_out) = searcher.search_until(&query, &Count, || {
+        num_checks.fetch_add(1, Ordering::Relaxed) > 0
+    })?;
+    assert!(timed_out);
+    assert_eq!(count, DEADLINE_CHECK_INTERVAL as usize);
+    assert_eq!(num_checks.load(Ordering::Relaxed), 2);
+    Ok(())
+}
+
+#[test]
+fn test_searcher_phrase_query() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let body = schema_builder.add_text_field(
+        "body",
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("stop_words")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        ),
+    );
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let id = schema_builder.add_u64_field("id", INDEXED);
+    let index = Index::create_in_ram(schema_builder.build());
+    index.tokenizers().register(
+        "stop_words",
+        TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(StopWordFilter::remove(vec!["the".to_string()]))
+            .build(),
+    );
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(body => "The fox jumps over the lazy dog", tag => "a b"))?;
+    index_writer.add_document(doc!(body => "A fox jumps over lazy dogs"))?;
+    index_writer.add_document(doc!(body => "The lazy fox sleeps"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let count = |query: Box<dyn Query>| searcher.search(&query, &Count);
+
+    // The stop word leaves a gap between "over" and "lazy", as in the indexed documents.
+    assert_eq!(
+        count(searcher.phrase_query(body, "Jumps over the LAZY", 0)?)?,
+        1
+    );
+    assert_eq!(
+        count(searcher.phrase_query(body, "jumps over lazy", 0)?)?,
+        1
+    );
+    // The gap counts in the slop.
+    assert_eq!(count(searcher.phrase_query(body, "over dog", 2)?)?, 1);
+    assert_eq!(count(searcher.phrase_query(body, "over dog", 1)?)?, 0);
+
+    // A single term, or no term at all.
+    assert_eq!(count(searcher.phrase_query(body, "the lazy", 0)?)?, 3);
+    assert_eq!(count(searcher.phrase_query(body, "The", 0)?)?, 0);
+    assert_eq!(count(searcher.phrase_query(tag, "a b", 0)?)?, 1);
+
+    assert!(matches!(
+        searcher.phrase_query(id, "1", 0),
+        Err(crate::TantivyError::SchemaError(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_searcher_query_cost() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT);
+    let num = schema_builder.add_u64_field("num", INDEXED | FAST);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for i in 0..100u64 {
+        let text_value = if i % 50 == 0 { "common rare" } else { "common" };
+        index_writer.add_document(doc!(text => text_value, num => i))?;
+        if i == 60 {
+            index_writer.commit()?;
+        }
+    }
+    index_writer.delete_term(Term::from_field_u64(num, 1));
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 2);
+    let term_query = |text_value: &str| -> Box<dyn Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(text, text_value),
+            IndexRecordOption::Basic,
+        ))
+    };
+    let selective_cost = searcher.query_cost(&term_query("rare"))?;
+    let broad_cost = searcher.query_cost(&term_query("common"))?;
+    assert_eq!(selective_cost, 2);
+    // The deleted document is still in the postings.
+    assert_eq!(broad_cost, 100);
+    assert_eq!(searcher.query_cost(&term_query("missing"))?, 0);
+
+    let conjunction = BooleanQuery::intersection(vec![term_query("common"), term_query("rare")]);
+    assert_eq!(searcher.query_cost(&conjunction)?, selective_cost);
+    let disjunction = BooleanQuery::union(vec![term_query("common"), term_query("rare")]);
+    assert_eq!(
+        searcher.query_cost(&disjunction)?,
+        broad_cost + selective_cost
+    );
+    let exclusion = BooleanQuery::new(vec![
+        (Occur::Must, term_query("rare")),
+        (Occur::MustNot, term_query("common")),
+    ]);
+    assert_eq!(searcher.query_cost(&exclusion)?, selective_cost);
+    let pure_exclusion = BooleanQuery::new(vec![(Occur::MustNot, term_query("rare"))]);
+    assert_eq!(searcher.query_cost(&pure_exclusion)?, 100);
+    let phrase_query = PhraseQuery::new(vec![
+        Term::from_field_text(text, "common"),
+        Term::from_field_text(text, "rare"),
+    ]);
+    assert_eq!(searcher.query_cost(&phrase_query)?, selective_cost);
+    let boosted = BoostQuery::new(term_query("rare"), 2.0);
+    assert_eq!(searcher.query_cost(&boosted)?, selective_cost);
+    assert_eq!(searcher.query_cost(&AllQuery)?, 100);
+    assert_eq!(searcher.query_cost(&EmptyQuery)?, 0);
+
+    // The queries without an estimate scan every document.
+    let range_query = RangeQuery::new(
+        Bound::Included(Term::from_field_u64(num, 10)),
+        Bound::Excluded(Term::from_field_u64(num, 20)),
+    );
+    assert_eq!(searcher.query_cost(&range_query)?, 100);
+    let filtered = BooleanQuery::intersection(vec![term_query("rare"), Box::new(range_query)]);
+    assert_eq!(searcher.query_cost(&filtered)?, selective_cost);
+    Ok(())
+}
+
+#[test]
+fn test_searcher_segments_of_hits() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for texts in [
+        &["apple apple", "apple", "banana"][..],
+        &["banana", "cherry"],
+        &["apple banana"],
+    ] {
+        for text_value in texts {
+            index_writer.add_document(doc!(text => *text_value))?;
+        }
+        index_writer.commit()?;
+    }
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 3);
+    let apple_query = TermQuery::new(
+        Term::from_field_text(text, "apple"),
+        IndexRecordOption::WithFreqs,
+    );
+    let top_docs = searcher.search(&apple_query, &TopDocs::with_limit(10))?;
+    assert_eq!(top_docs.len(), 3);
+    let segments_of_hits = searcher.segments_of_hits(top_docs.iter().map(|(_, doc)| *doc));
+    assert_eq!(segments_of_hits.len(), 2);
+    for segment_hits in &segments_of_hits {
+        let segment_reader = searcher.segment_reader(segment_hits.segment_ord);
+        assert_eq!(segment_hits.segment_id, segment_reader.segment_id());
+        // The hits reside in the segment.
+        let store_reader = segment_reader.get_store_reader(0)?;
+        let num_hits = top_docs
+            .iter()
+            .filter(|(_, doc_address)| doc_address.segment_ord == segment_hits.segment_ord)
+            .map(|(_, doc_address)| {
+                let doc: TantivyDocument = store_reader.get(doc_address.doc_id).unwrap();
+                assert!(doc
+                    .get_first(text)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .contains("apple"));
+            })
+            .count();
+        assert_eq!(segment_hits.num_hits, num_hits);
+        assert_eq!(
+            segment_reader
+                .inverted_index(text)?
+                .doc_freq(&Term::from_field_text(text, "apple"))? as usize,
+            num_hits
+        );
+    }
+    assert!(segments_of_hits[0].segment_ord < segments_of_hits[1].segment_ord);
+    // Only the best hit, from the segment of "apple apple".
+    let top_doc = searcher.search(&apple_query, &TopDocs::with_limit(1))?;
+    let segments_of_hits = searcher.segments_of_hits(top_doc.iter().map(|(_, doc)| *doc));
+    assert_eq!(segments_of_hits.len(), 1);
+    assert_eq!(segments_of_hits[0].num_hits, 1);
+    assert_eq!(
+        searcher
+            .segment_reader(segments_of_hits[0].segment_ord)
+            .num_docs(),
+        3
+    );
+    assert!(searcher.segments_of_hits(Vec::new()).is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_searcher_without_segments() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT | STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(text => "apple banana"))?;
+    index_writer.add_document(doc!(text => "apple"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(text => "apple cherry"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 2);
+    let apple_query = TermQuery::new(
+        Term::from_field_text(text, "apple"),
+        IndexRecordOption::Basic,
+    );
+    assert_eq!(searcher.num_docs(), 3);
+    assert_eq!(searcher.search(&apple_query, &Count)?, 3);
+
+    let cherry_segment_ord = searcher
+        .segment_readers()
+        .iter()
+        .position(|segment_reader| segment_reader.num_docs() == 1)
+        .unwrap();
+    let cherry_segment_id = searcher.segment_readers()[cherry_segment_ord].segment_id();
+    let filtered_searcher = searcher.without_segments(&[cherry_segment_id])?;
+    assert_eq!(filtered_searcher.segment_readers().len(), 1);
+    assert_eq!(
+        filtered_searcher.generation().generation_id(),
+        searcher.generation().generation_id()
+    );
+    assert_eq!(filtered_searcher.num_docs(), 2);
+    assert_eq!(filtered_searcher.search(&apple_query, &Count)?, 2);
+    let cherry_query = TermQuery::new(
+        Term::from_field_text(text, "cherry"),
+        IndexRecordOption::Basic,
+    );
+    assert_eq!(searcher.search(&cherry_query, &Count)?, 1);
+    assert_eq!(filtered_searcher.search(&cherry_query, &Count)?, 0);
+    let banana_query = TermQuery::new(
+        Term::from_field_text(text, "banana"),
+        IndexRecordOption::Basic,
+    );
+    let doc_addresses = filtered_searcher.search(&banana_query, &DocSetCollector)?;
+    let doc: TantivyDocument = filtered_searcher.doc(*doc_addresses.iter().next().unwrap())?;
+    assert_eq!(
+        doc.get_first(text).and_then(|value| value.as_str()),
+        Some("apple banana")
+    );
+    // The original searcher is left untouched.
+    assert_eq!(searcher.num_docs(), 3);
+
+    // Unknown segment ids are ignored.
+    let same_searcher = searcher.without_segments(&[SegmentId::generate_random()])?;
+    assert_eq!(same_searcher.num_docs(), 3);
+    let empty_searcher = searcher.without_segments(
+        &searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.segment_id())
+            .collect::<Vec<_>>(),
+    )?;
+    assert_eq!(empty_searcher.num_docs(), 0);
+    assert_eq!(empty_searcher.search(&apple_query, &Count)?, 0);
+    Ok(())
+}
+
+#[test]
+fn test_store_source() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id = schema_builder.add_u64_field("id", FAST | INDEXED);
+    let title = schema_builder.add_text_field("title", TEXT);
+    let index = Index::builder()
+        .schema(schema_builder.build())
+        .settings(IndexSettings {
+            store_source: true,
+            ..Default::default()
+        })
+        .create_in_ram()?;
+    let source = |doc_id: u64| format!(r#"{{"id": {doc_id}, "title": "hello {doc_id}"}}"#);
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for doc_id in 0..3u64 {
+        let doc = doc!(id => doc_id, title => format!("hello {doc_id}"));
+        index_writer.add_document_with_source(doc, source(doc_id))?;
+    }
+    index_writer.commit()?;
+    // A document added without a source.
+    index_writer.add_document(doc!(id => 3u64, title => "hello 3"))?;
+    index_writer.add_document_with_source(doc!(id => 4u64, title => "hello 4"), source(4))?;
+    // A document added with an empty source.
+    index_writer.add_document_with_source(doc!(id => 5u64, title => "hello 5"), "")?;
+    index_writer.commit()?;
+    index_writer.delete_term(Term::from_field_u64(id, 1));
+    index_writer.commit()?;
+
+    let sources_by_id = |index: &Index| -> crate::Result<Vec<(u64, Option<String>)>> {
+        let searcher = index.reader()?.searcher();
+        let mut sources = Vec::new();
+        for doc_address in searcher.search(&AllQuery, &DocSetCollector)? {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+            let doc_id = segment_reader
+                .fast_fields()
+                .u64("id")?
+                .first(doc_address.doc_id)
+                .unwrap();
+            let source = searcher
+                .source(doc_address)?
+                .map(|bytes| String::from_utf8(bytes.as_slice().to_vec()).unwrap());
+            sources.push((doc_id, source));
+        }
+        sources.sort();
+        Ok(sources)
+    };
+    let expected = vec![
+        (0, Some(source(0))),
+        (2, Some(source(2))),
+        (3, None),
+        (4, Some(source(4))),
+        (5, Some(String::new())),
+    ];
+    assert_eq!(sources_by_id(&index)?, expected);
+    let searcher = index.reader()?.searcher();
+    assert!(searcher.space_usage()?.segments()[0].source().get_bytes() > 0);
+
+    // The sources survive merges.
+    let segment_ids = index.searchable_segment_ids()?;
+    index_writer.merge(&segment_ids).wait()?;
+    index_writer.wait_merging_threads()?;
+    assert_eq!(index.searchable_segment_ids()?.len(), 1);
+    assert_eq!(sources_by_id(&index)?, expected);
+
+    // Without the setting, there are no sources.
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    assert!(matches!(
+        index_writer.add_document_with_source(doc!(title => "hello"), "{}"),
+        Err(crate::TantivyError::InvalidArgument(_))
+    ));
+    index_writer.add_document(doc!(title => "hello"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.source(DocAddress::new(0, 0))?, None);
+    Ok(())
+}
+
+#[test]
+fn test_searcher_docs() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT | STORED);
+    let index = Index::builder()
+        .schema(schema_builder.build())
+        .settings(IndexSettings {
+            docstore_blocksize: 100,
+            ..Default::default()
+        })
+        .create_in_ram()?;
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for segment in 0..2 {
+        for i in 0..30 {
+            index_writer.add_document(doc!(title => format!("doc {segment}-{i}")))?;
+        }
+        index_writer.commit()?;
+    }
+    // Without a cache, each read of a block decompresses it.
+    let reader = index
+        .reader_builder()
+        .doc_store_cache_num_blocks(0)
+        .try_into()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.segment_readers().len(), 2);
+    let doc_addresses = [
+        DocAddress::new(1, 3),
+        DocAddress::new(0, 2),
+        DocAddress::new(0, 29),
+        DocAddress::new(0, 0),
+        DocAddress::new(1, 3),
+        DocAddress::new(0, 1),
+    ];
+    let title_of =
+        |doc: &TantivyDocument| doc.get_first(title).unwrap().as_str().unwrap().to_string();
+
+    let misses_before = searcher.doc_store_cache_stats().cache_misses;
+    let expected: Vec<String> = doc_addresses
+        .iter()
+        .map(|doc_address| Ok(title_of(&searcher.doc(*doc_address)?)))
+        .collect::<crate::Result<_>>()?;
+    let misses_one_by_one = searcher.doc_store_cache_stats().cache_misses - misses_before;
+    assert_eq!(misses_one_by_one, doc_addresses.len());
+
+    let misses_before = searcher.doc_store_cache_stats().cache_misses;
+    let docs: Vec<TantivyDocument> = searcher.docs(&doc_addresses)?;
+    let misses_batch = searcher.doc_store_cache_stats().cache_misses - misses_before;
+    let titles: Vec<String> = docs.iter().map(title_of).collect();
+    assert_eq!(titles, expected);
+    // Docs 0, 1 and 2 of the first segment share a block, and doc 3 of the second segment
+    // is only read once.
+    assert_eq!(misses_batch, 3);
+
+    assert!(searcher.docs::<TantivyDocument>(&[]).unwrap().is_empty());
+    assert!(matches!(
+        searcher.docs::<TantivyDocument>(&[DocAddress::new(2, 0)]),
+        Err(crate::TantivyError::InvalidArgument(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_reader_segment_validator() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(title => "hello corrupt"))?;
+    index_writer.add_document(doc!(title => "hello corrupt"))?;
+    index_writer.commit()?;
+    let rejected_segment_id = index.searchable_segment_ids()?[0];
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .segment_validator(Box::new(move |segment_reader| {
+            if segment_reader.segment_id() == rejected_segment_id {
+                return Err(crate::TantivyError::InternalError(
+                    "corrupt segment".to_string(),
+                ));
+            }
+            Ok(())
+        }))
+        .try_into()?;
+    assert!(reader.searcher().segment_readers().is_empty());
+    assert_eq!(reader.searcher().num_docs(), 0);
+
+    index_writer.add_document(doc!(title => "hello world"))?;
+    index_writer.commit()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    // The rejected segment is excluded from the searcher, and its documents are not counted.
+    assert_eq!(index.searchable_segment_ids()?.len(), 2);
+    assert_eq!(searcher.segment_readers().len(), 1);
+    assert_ne!(searcher.segment_reader(0).segment_id(), rejected_segment_id);
+    assert_eq!(searcher.num_docs(), 1);
+    let query = QueryParser::for_index(&index, vec![title]).parse_query("hello")?;
+    assert_eq!(searcher.search(&query, &Count)?, 1);
+    assert_eq!(
+        searcher.doc_freq(&Term::from_field_text(title, "hello"))?,
+        1
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reader_segment_validator_excludes_unopenable_segments() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(title => "hello missing"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(title => "hello world"))?;
+    index_writer.commit()?;
+    let segments = index.searchable_segments()?;
+    assert_eq!(segments.len(), 2);
+    let broken_segment_id = segments[0].id();
+    index
+        .directory()
+        .delete(&segments[0].relative_path(SegmentComponent::FastFields))
+        .unwrap();
+
+    // Without a validator, the reload fails.
+    assert!(index.reader().is_err());
+
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .segment_validator(Box::new(|_segment_reader| Ok(())))
+        .try_into()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.segment_readers().len(), 1);
+    assert_ne!(searcher.segment_reader(0).segment_id(), broken_segment_id);
+    assert_eq!(searcher.num_docs(), 1);
+    let query = QueryParser::for_index(&index, vec![title]).parse_query("hello")?;
+    assert_eq!(searcher.search(&query, &Count)?, 1);
+    Ok(())
+}
+
+#[test]
+fn test_reader_term_dictionary_access() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for i in 0..100 {
+        index_writer.add_document(doc!(
+            title => format!("title{} word{}", i % 7, i % 13),
+            tag => format!("tag{}", i % 5),
+        ))?;
+        if i % 40 == 0 {
+            index_writer.commit()?;
+        }
+    }
+    index_writer.commit()?;
+    let mmap_searcher = index.reader()?.searcher();
+    let full_load_searcher = index
+        .reader_builder()
+        .term_dictionary_access(title, TermDictionaryAccess::FullLoad)
+        .try_into()?
+        .searcher();
+    assert_eq!(full_load_searcher.segment_readers().len(), 4);
+    // The inverted index is cached, whatever the access requested afterwards.
+    let segment_reader = full_load_searcher.segment_reader(0);
+    assert!(Arc::ptr_eq(
+        &segment_reader.inverted_index(title)?,
+        &segment_reader.inverted_index_with_access(title, TermDictionaryAccess::Mmap)?
+    ));
+    let capped_searcher = index
+        .reader_builder()
+        .term_dictionary_access(title, TermDictionaryAccess::Capped { max_fst_bytes: 0 })
+        .try_into()?
+        .searcher();
+
+    // The terms of each segment, along with their postings.
+    type SegmentTerms = BTreeMap<SegmentId, Vec<(Vec<u8>, Vec<u32>)>>;
+    let terms = |searcher: &Searcher, field: Field| -> crate::Result<SegmentTerms> {
+        let mut segment_terms = BTreeMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            let mut terms = Vec::new();
+            let mut stream = inverted_index.terms().stream()?;
+            while stream.advance() {
+                let term_info = stream.value().clone();
+                let mut postings = inverted_index
+                    .read_postings_from_terminfo(&term_info, IndexRecordOption::Basic)?;
+                let mut docs = Vec::new();
+                while postings.doc() != crate::TERMINATED {
+                    docs.push(postings.doc());
+                    postings.advance();
+                }
+                terms.push((stream.key().to_vec(), docs));
+            }
+            segment_terms.insert(segment_reader.segment_id(), terms);
+        }
+        Ok(segment_terms)
+    };
+    for field in [title, tag] {
+        let expected_terms = terms(&mmap_searcher, field)?;
+        assert_eq!(expected_terms.len(), 4);
+        assert_eq!(terms(&full_load_searcher, field)?, expected_terms);
+        assert_eq!(terms(&capped_searcher, field)?, expected_terms);
+    }
+    for word in ["title3", "word12", "word13", "absent"] {
+        let term = Term::from_field_text(title, word);
+        assert_eq!(
+            full_load_searcher.doc_freq(&term)?,
+            mmap_searcher.doc_freq(&term)?
+        );
+        assert_eq!(
+            capped_searcher.doc_freq(&term)?,
+            mmap_searcher.doc_freq(&term)?
+        );
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        assert_eq!(
+            full_load_searcher.search(&query, &Count)?,
+            mmap_searcher.search(&query, &Count)?
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_field_fill_rates() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id = schema_builder.add_u64_field("id", INDEXED);
+    let title = schema_builder.add_text_field("title", TEXT);
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let price = schema_builder.add_f64_field("price", FAST);
+    let attributes = schema_builder.add_json_field("attributes", FAST);
+    let notes = schema_builder.add_text_field("notes", STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let searcher = index.reader()?.searcher();
+    assert!(searcher
+        .field_fill_rates()?
+        .values()
+        .all(|&rate| rate == 0.0));
+
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for i in 0..10u64 {
+        let mut doc = doc!(id => i, notes => "note");
+        if i < 5 {
+            doc.add_text(title, "hello world");
+        }
+        if i % 2 == 0 {
+            doc.add_text(tag, "even");
+            doc.add_text(tag, "tag");
+        }
+        if i == 0 {
+            doc.add_f64(price, 1.0);
+            doc.add_f64(price, 2.0);
+        }
+        if i >= 8 {
+            doc.add_object(
+                attributes,
+                [("color".to_string(), OwnedValue::from("red"))]
+                    .into_iter()
+                    .collect(),
+            );
+        }
+        index_writer.add_document(doc)?;
+        if i == 4 {
+            index_writer.commit()?;
+        }
+    }
+    // Extra docs, deleted right away: they do not count.
+    index_writer.add_document(doc!(id => 10u64, price => 3.0))?;
+    index_writer.add_document(doc!(id => 10u64, price => 3.0))?;
+    index_writer.delete_term(Term::from_field_u64(id, 10));
+    index_writer.commit()?;
+
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 2);
+    assert_eq!(searcher.num_docs(), 10);
+    let fill_rates = searcher.field_fill_rates()?;
+    assert_eq!(fill_rates.len(), 5);
+    assert_eq!(fill_rates[&id], 1.0);
+    assert_eq!(fill_rates[&title], 0.5);
+    assert_eq!(fill_rates[&tag], 0.5);
+    assert_eq!(fill_rates[&price], 0.1);
+    assert_eq!(fill_rates[&attributes], 0.2);
+    // Stored-only fields are not covered.
+    assert!(!fill_rates.contains_key(&notes));
+    Ok(())
+}
+
+// Builds an index with every field type, without sharing its schema with the caller.
+fn build_index_with_every_field_type(directory: RamDirectory) -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("text", TEXT | STORED | FAST);
+    schema_builder.add_u64_field("u64", INDEXED | STORED | FAST);
+    schema_builder.add_i64_field("i64", INDEXED | STORED | FAST);
+    schema_builder.add_f64_field("f64", INDEXED | STORED | FAST);
+    schema_builder.add_bool_field("bool", INDEXED | STORED | FAST);
+    schema_builder.add_date_field("date", INDEXED | STORED | FAST);
+    schema_builder.add_ip_addr_field("ip", INDEXED | STORED | FAST);
+    schema_builder.add_facet_field("facet", STORED);
+    schema_builder.add_bytes_field("bytes", INDEXED | STORED | FAST);
+    schema_builder.add_json_field("json", STRING | STORED | FAST);
+    let schema = schema_builder.build();
+    let index = Index::create(directory, schema.clone(), IndexSettings::default())?;
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    let json_docs = [
+        r#"{"text": "hello", "u64": 1, "i64": -1, "f64": 1.5, "bool": true,
+            "date": "2024-03-01T10:00:00Z", "ip": "::ffff:10.0.0.1", "facet": "/books/sf",
+            "bytes": "AQI=", "json": {"color": "red"}}"#,
+        r#"{"text": "world", "u64": 2, "i64": -2, "f64": 2.5, "bool": false,
+            "date": "2024-03-02T10:00:00Z", "ip": "::ffff:10.0.0.2", "facet": "/books/poetry",
+            "bytes": "AwQ=", "json": {"color": "blue"}}"#,
+    ];
+    for json_doc in json_docs {
+        index_writer.add_document(TantivyDocument::parse_json(&schema, json_doc)?)?;
+    }
+    index_writer.commit()?;
+    Ok(())
+}
+
+#[test]
+fn test_read_index_with_schema_from_meta() -> crate::Result<()> {
+    let directory = RamDirectory::create();
+    build_index_with_every_field_type(directory.clone())?;
+
+    // Everything below only relies on the schema loaded with the index.
+    let index = Index::open(directory)?;
+    let schema = index.schema();
+    assert_eq!(schema.num_fields(), 10);
+    let searcher = index.reader()?.searcher();
+    let segment_reader = searcher.segment_reader(0);
+    let query_parser = QueryParser::for_index(&index, Vec::new());
+    let mut queried_types = Vec::new();
+    for doc_id in 0..2 {
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, doc_id))?;
+        let named_doc = doc.to_named_doc(&schema);
+        for (field, field_entry) in schema.fields() {
+            assert_eq!(schema.get_field(field_entry.name())?, field);
+            let stored_values = &named_doc.0[field_entry.name()];
+            assert_eq!(stored_values.len(), 1);
+            let value = &stored_values[0];
+
+            // Querying the field by name, with its stored value.
+            assert!(field_entry.is_indexed());
+            let (field_name, value) = match value {
+                OwnedValue::Object(entries) => (
+                    format!("{}.{}", field_entry.name(), entries[0].0),
+                    &entries[0].1,
+                ),
+                value => (field_entry.name().to_string(), value),
+            };
+            let query = query_parser.parse_query(&format!(
+                "{field_name}:{}",
+                serde_json::to_string(value).unwrap()
+            ))?;
+            let hits = searcher.search(&query, &DocSetCollector)?;
+            assert_eq!(
+                hits.into_iter().collect::<Vec<_>>(),
+                vec![DocAddress::new(0, doc_id)],
+                "{field_name}"
+            );
+            queried_types.push(field_entry.field_type().value_type());
+
+            // Reading the fast field values.
+            assert!(field_entry.is_fast());
+            if field_entry.field_type().is_json() {
+                continue;
+            }
+            let fast_values = segment_reader
+                .fast_fields()
+                .values(field_entry.name(), doc_id)?;
+            assert_eq!(&fast_values[..], &stored_values[..], "{field_name}");
+        }
+    }
+    queried_types.sort_by_key(|value_type| value_type.to_code());
+    queried_types.dedup();
+    assert_eq!(queried_types.len(), Type::iter_values().count());
+    assert!(matches!(
+        segment_reader.fast_fields().dynamic_column("u64")?,
+        Some(columnar::DynamicColumn::U64(_))
+    ));
+    assert!(segment_reader.fast_fields().values("json", 0).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_disabled_scoring_matches_the_same_documents() -> crate::Result<()> {
+    use crate::collector::tests::TestCollector;
+    use crate::query::{DisjunctionMaxQuery, EnableScoring, WeightedTermsQuery};
+
+    let mut schema_builder = Schema::builder();
+    let body = schema_builder.add_text_field("body", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    let texts = [
+        "the quick brown fox",
+        "the lazy dog",
+        "quick quick dog",
+        "a brown dog jumps over the fox",
+        "nothing here",
+    ];
+    for i in 0..30 {
+        index_writer.add_document(doc!(body => texts[i % texts.len()]))?;
+        if i % 10 == 9 {
+            index_writer.commit()?;
+        }
+    }
+    index_writer.delete_term(Term::from_field_text(body, "lazy"));
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let term_query = |text: &str| -> Box<dyn Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(body, text),
+            IndexRecordOption::WithFreqs,
+        ))
+    };
+    let queries: Vec<Box<dyn Query>> = vec![
+        term_query("quick"),
+        Box::new(BooleanQuery::union(vec![
+            term_query("quick"),
+            term_query("fox"),
+        ])),
+        Box::new(BooleanQuery::intersection(vec![
+            term_query("dog"),
+            term_query("brown"),
+        ])),
+        Box::new(BooleanQuery::new(vec![
+            (Occur::Must, term_query("dog")),
+            (Occur::Should, term_query("quick")),
+            (Occur::MustNot, term_query("jumps")),
+        ])),
+        Box::new(PhraseQuery::new(vec![
+            Term::from_field_text(body, "brown"),
+            Term::from_field_text(body, "fox"),
+        ])),
+        Box::new(DisjunctionMaxQuery::new(vec![
+            term_query("fox"),
+            term_query("lazy"),
+        ])),
+        Box::new(WeightedTermsQuery::new(
+            body,
+            vec![
+                (Term::from_field_text(body, "fox"), 1.0),
+                (Term::from_field_text(body, "dog"), 0.5),
+            ],
+        )),
+    ];
+    for query in &queries {
+        let scored_docs = searcher.search(
+            query,
+            &TestCollector {
+                compute_score: true,
+            },
+        )?;
+        let unscored_docs = searcher.search(
+            query,
+            &TestCollector {
+                compute_score: false,
+            },
+        )?;
+        assert!(!scored_docs.docs().is_empty());
+        assert_eq!(scored_docs.docs(), unscored_docs.docs(), "{query:?}");
+        assert_eq!(searcher.search(query, &Count)?, scored_docs.docs().len());
+    }
+
+    // Without scoring, the term scorers skip BM25 and return the boost.
+    let weight = term_query("quick").weight(EnableScoring::disabled_from_searcher(&searcher))?;
+    for segment_reader in searcher.segment_readers() {
+        let mut scorer = weight.scorer(segment_reader, 2.0)?;
+        while scorer.doc() != crate::TERMINATED {
+            assert_eq!(scorer.score(), 2.0);
+            scorer.advance();
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, result};
+
+use common::{BinarySerializable, HasLen, VInt};
+
+use super::FileHandle;
+use crate::core::META_FILEPATH;
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileSlice, Lock, WatchCallback, WatchHandle, WritePtr,
+};
+use crate::Index;
+
+/// Magic number written at the very end of a bundle, to identify corruption.
+const BUNDLE_MAGIC_NUMBER: u32 = 1_338;
+
+/// Length of the bundle footer: the manifest length (`u64`) followed by the magic number
+/// (`u32`).
+const BUNDLE_FOOTER_LEN: usize = 8 + 4;
+
+/// A read-only [`Directory`] serving the files of an index from a single bundle.
+///
+/// A bundle is a flat, uncompressed, concatenation of all of the files of an index,
+/// followed by a manifest mapping each file name to its byte range in the bundle.
+/// It can be built using [`bundle_index`].
+///
+/// Reads are served by slicing the bundle [`FileSlice`], so the bundle can be memory mapped,
+/// embedded in a binary, or backed by any custom [`FileHandle`] such as an HTTP range reader.
+///
+/// All write operations fail.
+///
+/// ```rust
+/// use tantivy::directory::{bundle_index, BundleDirectory, FileSlice, RamDirectory};
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create(RamDirectory::create(), schema_builder.build(), Default::default())?;
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+/// index_writer.commit()?;
+///
+/// let bundle: Vec<u8> = bundle_index(index.directory())?;
+/// let bundle_directory = BundleDirectory::open(FileSlice::from(bundle))?;
+/// let bundled_index = Index::open(bundle_directory)?;
+/// assert_eq!(bundled_index.reader()?.searcher().num_docs(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct BundleDirectory {
+    files: Arc<HashMap<PathBuf, FileSlice>>,
+}
+
+impl fmt::Debug for BundleDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BundleDirectory")
+    }
+}
+
+fn invalid_bundle(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid bundle: {msg}"))
+}
+
+impl BundleDirectory {
+    /// Opens a bundle created with [`bundle_index`].
+    ///
+    /// Only the manifest is read. File contents are read lazily.
+    pub fn open(bundle: FileSlice) -> io::Result<BundleDirectory> {
+        if bundle.len() < BUNDLE_FOOTER_LEN {
+            return Err(invalid_bundle("file too short"));
+        }
+        let (body, footer) = bundle.split_from_end(BUNDLE_FOOTER_LEN);
+        let footer_data = footer.read_bytes()?;
+        let (manifest_len, magic_number) = <(u64, u32)>::deserialize(&mut footer_data.as_slice())?;
+        if magic_number != BUNDLE_MAGIC_NUMBER {
+            return Err(invalid_bundle("magic number mismatch"));
+        }
+        let manifest_len = manifest_len as usize;
+        if manifest_len > body.len() {
+            return Err(invalid_bundle("manifest length exceeds the bundle length"));
+        }
+        let (files_data, manifest) = body.split_from_end(manifest_len);
+        let manifest_data = manifest.read_bytes()?;
+        let mut manifest_buffer = manifest_data.as_slice();
+        let num_files = VInt::deserialize(&mut manifest_buffer)?.0 as usize;
+        let mut files = HashMap::with_capacity(num_files);
+        for _ in 0..num_files {
+            let path = String::deserialize(&mut manifest_buffer)?;
+            let start = VInt::deserialize(&mut manifest_buffer)?.0 as usize;
+            let end = VInt::deserialize(&mut manifest_buffer)?.0 as usize;
+            if start > end || end > files_data.len() {
+                return Err(invalid_bundle("file range out of bounds"));
+            }
+            files.insert(PathBuf::from(path), files_data.slice(start..end));
+        }
+        Ok(BundleDirectory {
+            files: Arc::new(files),
+        })
+    }
+
+    /// Returns the paths of the files contained in the bundle.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+}
+
+fn read_only_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "BundleDirectory is read-only",
+    )
+}
+
+impl Directory for BundleDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let file_slice = self.open_read(path)?;
+        Ok(Arc::new(file_slice))
+    }
+
+    fn open_read(&self, path: &Path) -> result::Result<FileSlice, OpenReadError> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| OpenReadError::FileDoesNotExist(PathBuf::from(path)))
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        Err(DeleteError::IoError {
+            io_error: Arc::new(read_only_error()),
+            filepath: path.to_path_buf(),
+        })
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        Ok(self.files.contains_key(path))
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        Err(OpenWriteError::wrap_io_error(
+            read_only_error(),
+            path.to_path_buf(),
+        ))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let bytes = self
+            .open_read(path)?
+            .read_bytes()
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(bytes.as_slice().to_owned())
+    }
+
+    fn atomic_write(&self, _path: &Path, _data: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Files of a bundle can never be modified or deleted, so locks are
+    /// granted unconditionally.
+    fn acquire_lock(&self, _lock: &Lock) -> Result<DirectoryLock, LockError> {
+        Ok(DirectoryLock::from(Box::new(())))
+    }
+
+    fn watch(&self, _watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(WatchHandle::empty())
+    }
+}
+
+/// Builds a bundle containing the last commit of the index stored in `source_dir`.
+///
+/// The resulting bytes can be opened with [`BundleDirectory::open`].
+pub fn bundle_index(source_dir: &dyn Directory) -> crate::Result<Vec<u8>> {
+    let index = Index::open(source_dir.box_clone())?;
+    let mut paths: Vec<PathBuf> = vec![META_FILEPATH.to_path_buf()];
+    for segment_meta in index.searchable_segment_metas()? {
+        for path in segment_meta.list_files() {
+            if source_dir.exists(&path)? {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    let mut bundle: Vec<u8> = Vec::new();
+    let mut manifest: Vec<u8> = Vec::new();
+    VInt(paths.len() as u64).serialize(&mut manifest)?;
+    for path in paths {
+        let path_str = path.to_str().ok_or_else(|| {
+            crate::TantivyError::InvalidArgument(format!("Path {path:?} is not valid UTF-8"))
+        })?;
+        // `atomic_read` returns the raw content of the file, including its footer.
+        let file_data = source_dir.atomic_read(&path)?;
+        let start = bundle.len() as u64;
+        bundle.write_all(&file_data)?;
+        let end = bundle.len() as u64;
+        path_str.to_string().serialize(&mut manifest)?;
+        VInt(start).serialize(&mut manifest)?;
+        VInt(end).serialize(&mut manifest)?;
+    }
+    bundle.write_all(&manifest)?;
+    (manifest.len() as u64, BUNDLE_MAGIC_NUMBER).serialize(&mut bundle)?;
+    Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{bundle_index, BundleDirectory};
+    use crate::directory::{Directory, FileSlice, RamDirectory};
+    use crate::query::QueryParser;
+    use crate::schema::{Document, Schema, STORED, TEXT};
+    use crate::{collector, Index, IndexSettings, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_bundle_directory_round_trip() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let index = Index::create(
+            RamDirectory::create(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "hello happy tax payer"))?;
+        index_writer.add_document(doc!(title => "hello world"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "goodbye world"))?;
+        index_writer.delete_term(Term::from_field_text(title, "happy"));
+        index_writer.commit()?;
+
+        let bundle = bundle_index(index.directory())?;
+        let bundle_directory = BundleDirectory::open(FileSlice::from(bundle))?;
+        for path in bundle_directory.files() {
+            assert_eq!(
+                bundle_directory.atomic_read(path)?,
+                index.directory().atomic_read(path)?
+            );
+        }
+        assert!(!bundle_directory.exists(Path::new("missing"))?);
+        assert!(bundle_directory.open_write(Path::new("new_file")).is_err());
+        assert!(bundle_directory
+            .atomic_write(Path::new("meta.json"), b"")
+            .is_err());
+
+        let bundled_index = Index::open(bundle_directory)?;
+        let searcher = bundled_index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        assert_eq!(searcher.num_docs(), 2);
+        let query = QueryParser::for_index(&bundled_index, vec![title]).parse_query("world")?;
+        let top_docs = searcher.search(&query, &collector::TopDocs::with_limit(10))?;
+        let mut titles: Vec<String> = top_docs
+            .into_iter()
+            .map(|(_, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                doc.to_json(&bundled_index.schema())
+            })
+            .collect();
+        titles.sort();
+        assert_eq!(
+            titles,
+            vec![
+                r#"{"title":["goodbye world"]}"#.to_string(),
+                r#"{"title":["hello world"]}"#.to_string(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bundle_directory_invalid() {
+        assert!(BundleDirectory::open(FileSlice::from(vec![1u8, 2, 3])).is_err());
+        assert!(BundleDirectory::open(FileSlice::from(vec![0u8; 32])).is_err());
+    }
+}
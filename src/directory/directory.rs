@@ -135,6 +135,14 @@ pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     /// Returns true if and only if the file exists
     fn exists(&self, path: &Path) -> Result<bool, OpenReadError>;
 
+    /// Returns the length of the file, in bytes.
+    ///
+    /// The default implementation opens the file. Implementations should override it
+    /// if they can get the length of a file more cheaply.
+    fn file_num_bytes(&self, path: &Path) -> Result<u64, OpenReadError> {
+        Ok(self.get_file_handle(path)?.len() as u64)
+    }
+
     /// Opens a writer for the *virtual file* associated with
     /// a [`Path`].
     ///
@@ -311,6 +311,10 @@ impl Directory for ManagedDirectory {
         self.directory.exists(path)
     }
 
+    fn file_num_bytes(&self, path: &Path) -> Result<u64, OpenReadError> {
+        self.directory.file_num_bytes(path)
+    }
+
     fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
         self.directory.acquire_lock(lock)
     }
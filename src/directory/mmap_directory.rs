@@ -413,6 +413,18 @@ impl Directory for MmapDirectory {
             .map_err(|io_err| OpenReadError::wrap_io_error(io_err, path.to_path_buf()))
     }
 
+    /// Reads the length of the file from its metadata, without mmapping it.
+    fn file_num_bytes(&self, path: &Path) -> Result<u64, OpenReadError> {
+        let full_path = self.resolve_path(path);
+        match fs::metadata(full_path) {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(io_err) if io_err.kind() == io::ErrorKind::NotFound => {
+                Err(OpenReadError::FileDoesNotExist(path.to_path_buf()))
+            }
+            Err(io_err) => Err(OpenReadError::wrap_io_error(io_err, path.to_path_buf())),
+        }
+    }
+
     fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
         debug!("Open Write {:?}", path);
         let full_path = self.resolve_path(path);
@@ -557,6 +569,23 @@ mod tests {
         assert_eq!(readonlymap.len(), 0);
     }
 
+    #[test]
+    fn test_file_num_bytes() {
+        let mmap_directory = MmapDirectory::create_from_tempdir().unwrap();
+        let path = PathBuf::from("test");
+        {
+            let mut w = mmap_directory.open_write(&path).unwrap();
+            w.write_all(b"abcdef").unwrap();
+            w.flush().unwrap();
+        }
+        assert_eq!(mmap_directory.file_num_bytes(&path).unwrap(), 6);
+        assert_eq!(mmap_directory.get_cache_info().mmapped.len(), 0);
+        assert!(matches!(
+            mmap_directory.file_num_bytes(Path::new("missing")),
+            Err(OpenReadError::FileDoesNotExist(_))
+        ));
+    }
+
     #[test]
     fn test_cache() {
         let content = b"abc";
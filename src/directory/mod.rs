@@ -3,12 +3,14 @@
 #[cfg(feature = "mmap")]
 mod mmap_directory;
 
+mod bundle_directory;
 mod directory;
 mod directory_lock;
 mod file_watcher;
 pub mod footer;
 mod managed_directory;
 mod ram_directory;
+mod tracing_directory;
 mod watch_event_router;
 
 /// Errors specific to the directory module.
@@ -22,10 +24,12 @@ use std::path::PathBuf;
 pub use common::file_slice::{FileHandle, FileSlice};
 pub use common::{AntiCallToken, OwnedBytes, TerminatingWrite};
 
+pub use self::bundle_directory::{bundle_index, BundleDirectory};
 pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
 pub use self::directory::{Directory, DirectoryClone, DirectoryLock};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
 pub use self::ram_directory::RamDirectory;
+pub use self::tracing_directory::{FileRead, FileReadStats, ReadSink, TracingDirectory};
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 
 /// Outcome of the Garbage collection
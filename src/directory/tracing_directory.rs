@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fmt, io};
+
+use async_trait::async_trait;
+use common::HasLen;
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, FileSlice, Lock, OwnedBytes, WatchCallback, WatchHandle,
+    WritePtr,
+};
+
+/// A read recorded by a [`TracingDirectory`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileRead {
+    /// The path of the file read.
+    pub path: PathBuf,
+    /// The byte range read, in the file as stored by the wrapped directory.
+    pub byte_range: Range<usize>,
+}
+
+/// The reads recorded by a [`TracingDirectory`] on a file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FileReadStats {
+    /// The number of reads.
+    pub num_reads: u64,
+    /// The total number of bytes read, counting the bytes read several times once per read.
+    pub num_bytes: u64,
+}
+
+/// Receives each read recorded by a [`TracingDirectory`], as it happens.
+pub type ReadSink = dyn Fn(&FileRead) + Send + Sync;
+
+struct TracingState {
+    sink: Option<Box<ReadSink>>,
+    stats: Mutex<HashMap<PathBuf, FileReadStats>>,
+}
+
+impl TracingState {
+    fn record(&self, path: &Path, byte_range: Range<usize>) {
+        {
+            let mut stats = self.stats.lock().unwrap();
+            let file_stats = stats.entry(path.to_path_buf()).or_default();
+            file_stats.num_reads += 1;
+            file_stats.num_bytes += byte_range.len() as u64;
+        }
+        if let Some(sink) = &self.sink {
+            sink(&FileRead {
+                path: path.to_path_buf(),
+                byte_range,
+            });
+        }
+    }
+}
+
+/// A [`Directory`] wrapper recording the reads made through it, to profile the IO of a
+/// workload.
+///
+/// Every read of a byte range, through the [`FileHandle`]s and [`FileSlice`]s of the
+/// directory, is counted in the per file [`FileReadStats`], and passed to the sink if any.
+/// [`Directory::atomic_read`] counts as a read of the whole file.
+///
+/// The reads are recorded as tantivy issues them: a directory serving the bytes from memory,
+/// such as the [`MmapDirectory`](crate::directory::MmapDirectory), lets tantivy read whole
+/// files once and then access their bytes without going through the directory. These accesses
+/// are not recorded. The reads are intercepted below the footer handling, so their byte ranges
+/// are the ranges of the files as stored by the wrapped directory.
+///
+/// The clones of the directory share their stats.
+///
+/// ```rust
+/// use tantivy::directory::{RamDirectory, TracingDirectory};
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let directory = TracingDirectory::wrap(Box::new(RamDirectory::create()));
+/// let index = Index::create(directory.clone(), schema_builder.build(), Default::default())?;
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+/// index_writer.commit()?;
+///
+/// directory.reset_stats();
+/// let _searcher = index.reader()?.searcher();
+/// let num_bytes_read: u64 = directory.stats().values().map(|stats| stats.num_bytes).sum();
+/// assert!(num_bytes_read > 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TracingDirectory {
+    directory: Box<dyn Directory>,
+    state: Arc<TracingState>,
+}
+
+impl fmt::Debug for TracingDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TracingDirectory({:?})", self.directory)
+    }
+}
+
+impl TracingDirectory {
+    /// Wraps `directory`, recording the stats of the reads.
+    pub fn wrap(directory: Box<dyn Directory>) -> TracingDirectory {
+        TracingDirectory::create(directory, None)
+    }
+
+    /// Wraps `directory`, recording the stats of the reads, and passing each read to `sink`.
+    ///
+    /// The sink is called on the thread making the read, which can be any of the indexing,
+    /// merging or searching threads.
+    pub fn wrap_with_sink(directory: Box<dyn Directory>, sink: Box<ReadSink>) -> TracingDirectory {
+        TracingDirectory::create(directory, Some(sink))
+    }
+
+    fn create(directory: Box<dyn Directory>, sink: Option<Box<ReadSink>>) -> TracingDirectory {
+        TracingDirectory {
+            directory,
+            state: Arc::new(TracingState {
+                sink,
+                stats: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Returns the stats of the reads recorded since the creation of the directory, or the
+    /// last call to [`TracingDirectory::reset_stats`], for all of the files read.
+    pub fn stats(&self) -> HashMap<PathBuf, FileReadStats> {
+        self.state.stats.lock().unwrap().clone()
+    }
+
+    /// Returns the stats of the reads of the file at `path`.
+    pub fn file_stats(&self, path: &Path) -> FileReadStats {
+        self.state
+            .stats
+            .lock()
+            .unwrap()
+            .get(path)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Clears the stats, for instance to profile a workload after opening the index.
+    pub fn reset_stats(&self) {
+        self.state.stats.lock().unwrap().clear();
+    }
+}
+
+struct TracingFileHandle {
+    path: PathBuf,
+    file_handle: Arc<dyn FileHandle>,
+    state: Arc<TracingState>,
+}
+
+impl fmt::Debug for TracingFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TracingFileHandle({:?})", self.path)
+    }
+}
+
+impl HasLen for TracingFileHandle {
+    fn len(&self) -> usize {
+        self.file_handle.len()
+    }
+}
+
+#[async_trait]
+impl FileHandle for TracingFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.state.record(&self.path, range.clone());
+        self.file_handle.read_bytes(range)
+    }
+
+    async fn read_bytes_async(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.state.record(&self.path, range.clone());
+        self.file_handle.read_bytes_async(range).await
+    }
+}
+
+impl Directory for TracingDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let file_handle = self.directory.get_file_handle(path)?;
+        Ok(Arc::new(TracingFileHandle {
+            path: path.to_path_buf(),
+            file_handle,
+            state: self.state.clone(),
+        }))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+        Ok(FileSlice::new(self.get_file_handle(path)?))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.directory.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.directory.exists(path)
+    }
+
+    fn file_num_bytes(&self, path: &Path) -> Result<u64, OpenReadError> {
+        self.directory.file_num_bytes(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        self.directory.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let data = self.directory.atomic_read(path)?;
+        self.state.record(path, 0..data.len());
+        Ok(data)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.directory.atomic_write(path, data)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.directory.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.directory.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    use super::{FileRead, TracingDirectory};
+    use crate::collector::TopDocs;
+    use crate::directory::{Directory, RamDirectory};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, Value, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_tracing_directory() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let ram_directory = RamDirectory::create();
+        let index = Index::create(
+            ram_directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "hello happy tax payer"))?;
+        index_writer.add_document(doc!(title => "hello world"))?;
+        index_writer.commit()?;
+
+        let reads: Arc<Mutex<Vec<FileRead>>> = Arc::default();
+        let sink_reads = reads.clone();
+        let directory = TracingDirectory::wrap_with_sink(
+            Box::new(ram_directory),
+            Box::new(move |read| sink_reads.lock().unwrap().push(read.clone())),
+        );
+        let index = Index::open(directory.clone())?;
+        let meta_stats = directory.file_stats(Path::new("meta.json"));
+        assert_eq!(meta_stats.num_reads, 1);
+        assert_eq!(
+            meta_stats.num_bytes,
+            directory.file_num_bytes(Path::new("meta.json"))?
+        );
+
+        let searcher = index.reader()?.searcher();
+        let segment_id = searcher.segment_readers()[0].segment_id();
+        let segment_file = |extension: &str| -> PathBuf {
+            PathBuf::from(format!("{}.{extension}", segment_id.uuid_string()))
+        };
+        directory.reset_stats();
+        reads.lock().unwrap().clear();
+        let query = TermQuery::new(
+            Term::from_field_text(title, "world"),
+            IndexRecordOption::Basic,
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 1);
+        let doc: TantivyDocument = searcher.doc(top_docs[0].1)?;
+        assert_eq!(doc.get_first(title).unwrap().as_str(), Some("hello world"));
+
+        // The term dictionary and the postings are read by the query.
+        assert!(directory.file_stats(&segment_file("term")).num_reads >= 1);
+        assert!(directory.file_stats(&segment_file("idx")).num_reads >= 1);
+        // Fetching the document reads a block of the doc store.
+        let store_stats = directory.file_stats(&segment_file("store"));
+        assert!(store_stats.num_reads >= 1);
+        assert!(store_stats.num_bytes > 0);
+        assert_eq!(directory.file_stats(&segment_file("pos")).num_reads, 0);
+        let stats = directory.stats();
+        let reads = reads.lock().unwrap();
+        for (path, file_stats) in &stats {
+            let file_reads: Vec<&FileRead> =
+                reads.iter().filter(|read| &read.path == path).collect();
+            assert_eq!(file_reads.len() as u64, file_stats.num_reads);
+            let num_bytes: usize = file_reads.iter().map(|read| read.byte_range.len()).sum();
+            assert_eq!(num_bytes as u64, file_stats.num_bytes);
+        }
+        assert_eq!(
+            reads.len() as u64,
+            stats.values().map(|stats| stats.num_reads).sum::<u64>()
+        );
+
+        directory.reset_stats();
+        assert!(directory.stats().is_empty());
+        Ok(())
+    }
+}
@@ -54,6 +54,38 @@ impl AliveBitSet {
         AliveBitSet::from(bitset)
     }
 
+    /// Opens an alive bitset given the bytes of its file, checking that they
+    /// describe the alive documents of a segment with `max_doc` documents.
+    pub(crate) fn open_checked(bytes: OwnedBytes, max_doc: DocId) -> crate::Result<AliveBitSet> {
+        let num_bytes_expected = 4 + max_doc.div_ceil(64) as usize * 8;
+        if bytes.len() != num_bytes_expected {
+            return Err(crate::TantivyError::InvalidArgument(format!(
+                "The alive bitset has {} bytes, expected {num_bytes_expected} bytes for a segment \
+                 with {max_doc} documents",
+                bytes.len()
+            )));
+        }
+        let max_value = u32::from_le_bytes(bytes.as_slice()[..4].try_into().unwrap());
+        if max_value != max_doc {
+            return Err(crate::TantivyError::InvalidArgument(format!(
+                "The alive bitset is for {max_value} documents, but the segment has {max_doc} \
+                 documents"
+            )));
+        }
+        Ok(AliveBitSet::open(bytes))
+    }
+
+    /// Serializes the alive bitset, in the format of the delete file of a segment.
+    pub(crate) fn to_bytes(&self) -> OwnedBytes {
+        let mut bitset = BitSet::with_max_value(self.bitset.max_value());
+        for doc in self.iter_alive() {
+            bitset.insert(doc);
+        }
+        let mut buffer = Vec::new();
+        write_alive_bitset(&bitset, &mut buffer).expect("writing to a Vec cannot fail");
+        OwnedBytes::new(buffer)
+    }
+
     /// Returns true if the document is still "alive". In other words, if it has not been deleted.
     #[inline]
     pub fn is_alive(&self, doc: DocId) -> bool {
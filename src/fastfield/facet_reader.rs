@@ -1,6 +1,6 @@
 use columnar::StrColumn;
 
-use crate::schema::Facet;
+use crate::schema::{Facet, FACET_SEP_BYTE};
 use crate::termdict::TermOrdinal;
 use crate::DocId;
 
@@ -57,10 +57,47 @@ impl FacetReader {
     pub fn facet_dict(&self) -> &columnar::Dictionary {
         self.facet_column.dictionary()
     }
+
+    /// Returns the distinct immediate children of `facet` in the segment, in sorted order.
+    ///
+    /// A facet is a child as soon as one of its descendants is in the segment: with a document
+    /// having the facet `/electronics/phones/android`, `/electronics/phones` is a child of
+    /// `/electronics`.
+    ///
+    /// Only the part of the facet dictionary under `facet` is streamed, so this is much cheaper
+    /// than a [`FacetCollector`](crate::collector::FacetCollector) to browse the taxonomy,
+    /// when the counts are not needed. As for the terms, the facets of the deleted documents
+    /// remain in the dictionary until their segment is merged.
+    pub fn children_of(&self, facet: &Facet) -> crate::Result<impl Iterator<Item = Facet> + '_> {
+        let mut prefix: Vec<u8> = facet.encoded_str().as_bytes().to_vec();
+        if !facet.is_root() {
+            prefix.push(FACET_SEP_BYTE);
+        }
+        let mut stream = self.facet_dict().prefix_range(&prefix).into_stream()?;
+        let mut last_child: Option<Vec<u8>> = None;
+        Ok(std::iter::from_fn(move || {
+            while stream.advance() {
+                let key = stream.key();
+                let child_len = key[prefix.len()..]
+                    .iter()
+                    .position(|&byte| byte == FACET_SEP_BYTE)
+                    .map_or(key.len(), |pos| prefix.len() + pos);
+                let child = &key[..child_len];
+                if last_child.as_deref() == Some(child) {
+                    continue;
+                }
+                last_child = Some(child.to_vec());
+                let child_str = String::from_utf8_lossy(child).into_owned();
+                return Some(Facet::from_encoded_string(child_str));
+            }
+            None
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::FacetReader;
     use crate::schema::{Facet, FacetOptions, SchemaBuilder, Value, STORED};
     use crate::{DocAddress, Index, IndexWriter, TantivyDocument};
 
@@ -191,4 +228,82 @@ mod tests {
         assert!(facet_reader.facet_ords(1u32).next().is_none());
         Ok(())
     }
+
+    #[test]
+    fn test_facet_children_of() -> crate::Result<()> {
+        let mut schema_builder = SchemaBuilder::default();
+        let facet_field = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let add_doc = |index_writer: &mut IndexWriter, facets: &[&str]| -> crate::Result<()> {
+            let mut doc = TantivyDocument::default();
+            for facet in facets {
+                doc.add_facet(facet_field, Facet::from(*facet));
+            }
+            index_writer.add_document(doc)?;
+            Ok(())
+        };
+        add_doc(
+            &mut index_writer,
+            &["/electronics/phones/android", "/books/fiction"],
+        )?;
+        add_doc(
+            &mut index_writer,
+            &["/electronics/phones/ios", "/electronics/laptops"],
+        )?;
+        add_doc(&mut index_writer, &["/electronic", "/electronics"])?;
+        index_writer.commit()?;
+
+        let children = |facet_reader: &FacetReader, facet: &str| -> crate::Result<Vec<String>> {
+            Ok(facet_reader
+                .children_of(&Facet::from(facet))?
+                .map(|child| child.to_string())
+                .collect())
+        };
+        let searcher = index.reader()?.searcher();
+        let facet_reader = searcher.segment_reader(0u32).facet_reader("facet")?;
+        assert_eq!(
+            children(&facet_reader, "/")?,
+            ["/books", "/electronic", "/electronics"]
+        );
+        assert_eq!(
+            children(&facet_reader, "/electronics")?,
+            ["/electronics/laptops", "/electronics/phones"]
+        );
+        assert_eq!(
+            children(&facet_reader, "/electronics/phones")?,
+            ["/electronics/phones/android", "/electronics/phones/ios"]
+        );
+        assert!(children(&facet_reader, "/electronics/laptops")?.is_empty());
+        assert!(children(&facet_reader, "/toys")?.is_empty());
+
+        // The children of the segments are merged.
+        add_doc(
+            &mut index_writer,
+            &["/electronics/tv", "/electronics/phones/android/pixel"],
+        )?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let searcher_children = |facet: &str| -> crate::Result<Vec<String>> {
+            Ok(searcher
+                .facet_children_of("facet", &Facet::from(facet))?
+                .iter()
+                .map(Facet::to_string)
+                .collect())
+        };
+        assert_eq!(
+            searcher_children("/electronics")?,
+            [
+                "/electronics/laptops",
+                "/electronics/phones",
+                "/electronics/tv"
+            ]
+        );
+        assert_eq!(
+            searcher_children("/electronics/phones/android")?,
+            ["/electronics/phones/android/pixel"]
+        );
+        Ok(())
+    }
 }
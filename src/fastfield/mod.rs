@@ -75,12 +75,17 @@ impl FastValue for DateTime {
 #[cfg(test)]
 mod tests {
 
+    use std::io;
     use std::net::Ipv6Addr;
     use std::ops::{Range, RangeInclusive};
     use std::path::Path;
+    use std::sync::Arc;
 
-    use columnar::StrColumn;
-    use common::{ByteCount, DateTimePrecision, HasLen, TerminatingWrite};
+    use columnar::column_values::{
+        ColumnCodecEstimator, ColumnStats, FastFieldCodec, FastFieldCodecs,
+    };
+    use columnar::{ColumnValues, StrColumn};
+    use common::{ByteCount, DateTimePrecision, HasLen, OwnedBytes, TerminatingWrite};
     use once_cell::sync::Lazy;
     use rand::prelude::SliceRandom;
     use rand::rngs::StdRng;
@@ -91,8 +96,8 @@ mod tests {
     use crate::index::SegmentId;
     use crate::merge_policy::NoMergePolicy;
     use crate::schema::{
-        DateOptions, Facet, FacetOptions, Field, JsonObjectOptions, Schema, SchemaBuilder,
-        TantivyDocument, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
+        DateOptions, Facet, FacetOptions, Field, JsonObjectOptions, NumericOptions, Schema,
+        SchemaBuilder, TantivyDocument, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::time::OffsetDateTime;
     use crate::tokenizer::{LowerCaser, RawTokenizer, TextAnalyzer, TokenizerManager};
@@ -111,6 +116,70 @@ mod tests {
         assert_eq!(i64::from_u64(datetime.to_u64()), 0i64);
     }
 
+    #[test]
+    fn test_column_as_dense_slice() -> crate::Result<()> {
+        use std::sync::Arc;
+
+        use columnar::column_values::VecColumn;
+        use columnar::{Column, ColumnIndex};
+
+        let vals: Vec<u64> = (0..1_000u64).map(|val| val * val % 997).collect();
+        let dense_column = Column {
+            index: ColumnIndex::Full,
+            values: Arc::new(VecColumn::from(vals.clone())),
+        };
+        let slice = dense_column.as_dense_slice().unwrap();
+        assert_eq!(slice, &vals[..]);
+        for (doc, val) in slice.iter().enumerate() {
+            assert_eq!(dense_column.first(doc as u32), Some(*val));
+        }
+
+        // By default, fast fields read from an index are compressed.
+        let index = Index::create_in_ram(SCHEMA.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for val in &vals {
+            index_writer.add_document(doc!(*FIELD => *val))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let column = searcher.segment_reader(0).fast_fields().u64("field")?;
+        assert!(column.as_dense_slice().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_column_values_and_nulls() -> crate::Result<()> {
+        let index = Index::create_in_ram(SCHEMA.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let vals: Vec<Option<u64>> = (0..100u64)
+            .map(|doc| (doc % 3 != 0).then_some(doc * 10))
+            .collect();
+        for val in &vals {
+            match val {
+                Some(val) => index_writer.add_document(doc!(*FIELD => *val))?,
+                None => index_writer.add_document(doc!())?,
+            };
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let column = searcher.segment_reader(0).fast_fields().u64("field")?;
+        let (values, null_bitmap) = column.values_and_nulls().unwrap();
+        let null_bitmap = null_bitmap.unwrap();
+        let mut dense_values = vec![0u64; values.num_vals() as usize];
+        values.get_range(0, &mut dense_values);
+        let expected_dense_values: Vec<u64> = vals.iter().flatten().copied().collect();
+        assert_eq!(dense_values, expected_dense_values);
+        let mut dense_values_iter = dense_values.iter();
+        for (doc, val) in vals.iter().enumerate() {
+            assert_eq!(null_bitmap.contains(doc as u32), val.is_some());
+            if null_bitmap.contains(doc as u32) {
+                assert_eq!(dense_values_iter.next(), val.as_ref());
+            }
+        }
+        assert_eq!(null_bitmap.len(), expected_dense_values.len());
+        Ok(())
+    }
+
     #[test]
     fn test_intfastfield_small() -> crate::Result<()> {
         let path = Path::new("test");
@@ -1303,4 +1372,184 @@ mod tests {
         let vals: Vec<i64> = column.values_for_doc(0u32).collect();
         assert_eq!(&vals, &[33]);
     }
+
+    // Stores the values as is, along with their min and max value.
+    struct RawCodec;
+
+    static RAW_CODEC_NUM_LOADS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    struct RawEstimator;
+
+    impl ColumnCodecEstimator for RawEstimator {
+        fn collect(&mut self, _value: u64) {}
+
+        fn estimate(&self, stats: &ColumnStats) -> Option<u64> {
+            Some(16 + stats.num_rows as u64 * 8)
+        }
+
+        fn serialize(
+            &self,
+            stats: &ColumnStats,
+            vals: &mut dyn Iterator<Item = u64>,
+            wrt: &mut dyn io::Write,
+        ) -> io::Result<()> {
+            wrt.write_all(&stats.min_value.to_le_bytes())?;
+            wrt.write_all(&stats.max_value.to_le_bytes())?;
+            for val in vals {
+                wrt.write_all(&val.to_le_bytes())?;
+            }
+            Ok(())
+        }
+    }
+
+    struct RawColumnValues {
+        min_value: u64,
+        max_value: u64,
+        vals: Vec<u64>,
+    }
+
+    impl ColumnValues for RawColumnValues {
+        fn get_val(&self, idx: u32) -> u64 {
+            self.vals[idx as usize]
+        }
+
+        fn min_value(&self) -> u64 {
+            self.min_value
+        }
+
+        fn max_value(&self) -> u64 {
+            self.max_value
+        }
+
+        fn num_vals(&self) -> u32 {
+            self.vals.len() as u32
+        }
+    }
+
+    impl FastFieldCodec for RawCodec {
+        fn estimator(&self) -> Box<dyn ColumnCodecEstimator> {
+            Box::new(RawEstimator)
+        }
+
+        fn load(&self, bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues>> {
+            RAW_CODEC_NUM_LOADS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let mut vals = bytes
+                .as_slice()
+                .chunks_exact(8)
+                .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()));
+            let min_value = vals.next().unwrap();
+            let max_value = vals.next().unwrap();
+            Ok(Arc::new(RawColumnValues {
+                min_value,
+                max_value,
+                vals: vals.collect(),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_fast_field_custom_codec() -> crate::Result<()> {
+        const RAW_CODEC_CODE: u8 = 200;
+        let mut schema_builder = Schema::builder();
+        let num_options = NumericOptions::default().set_fast_codec(RAW_CODEC_CODE)?;
+        assert_eq!(num_options.fast_codec(), Some(RAW_CODEC_CODE));
+        assert!(num_options.is_fast());
+        let timestamp = schema_builder.add_u64_field("timestamp", num_options.clone());
+        let delta = schema_builder.add_i64_field("delta", num_options | STORED);
+        let date_options = DateOptions::default().set_fast_codec(RAW_CODEC_CODE)?;
+        assert_eq!(date_options.fast_codec(), Some(RAW_CODEC_CODE));
+        let date = schema_builder.add_date_field("date", date_options);
+        let schema = schema_builder.build();
+        // The codec is part of the schema.
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        let deser_schema: Schema = serde_json::from_str(&schema_json).unwrap();
+        assert_eq!(
+            deser_schema
+                .get_field_entry(timestamp)
+                .field_type()
+                .fast_codec(),
+            Some(RAW_CODEC_CODE)
+        );
+        assert_eq!(
+            deser_schema.get_field_entry(date).field_type().fast_codec(),
+            Some(RAW_CODEC_CODE)
+        );
+
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema, crate::IndexSettings::default())?;
+        let codecs = index.fast_field_codecs();
+        codecs.register(RAW_CODEC_CODE, Arc::new(RawCodec))?;
+        assert!(codecs.register(RAW_CODEC_CODE, Arc::new(RawCodec)).is_err());
+        assert!(codecs.register(2, Arc::new(RawCodec)).is_err());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..10u64 {
+            index_writer.add_document(doc!(
+                timestamp => 1_000 + i * 7,
+                delta => 5 - i as i64,
+                date => DateTime::from_timestamp_secs(i as i64 * 60),
+            ))?;
+            if i % 5 == 4 {
+                index_writer.commit()?;
+            }
+        }
+        let check_values = |index: &Index| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            let mut vals: Vec<(u64, i64, DateTime)> = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let timestamps = segment_reader.fast_fields().u64("timestamp")?;
+                let deltas = segment_reader.fast_fields().i64("delta")?;
+                let dates = segment_reader.fast_fields().date("date")?;
+                for doc in 0..segment_reader.max_doc() {
+                    vals.push((
+                        timestamps.first(doc).unwrap(),
+                        deltas.first(doc).unwrap(),
+                        dates.first(doc).unwrap(),
+                    ));
+                }
+            }
+            vals.sort();
+            let expected: Vec<(u64, i64, DateTime)> = (0..10u64)
+                .map(|i| {
+                    (
+                        1_000 + i * 7,
+                        5 - i as i64,
+                        DateTime::from_timestamp_secs(i as i64 * 60),
+                    )
+                })
+                .collect();
+            assert_eq!(vals, expected);
+            Ok(())
+        };
+        let num_loads_before = RAW_CODEC_NUM_LOADS.load(std::sync::atomic::Ordering::SeqCst);
+        check_values(&index)?;
+        assert!(RAW_CODEC_NUM_LOADS.load(std::sync::atomic::Ordering::SeqCst) > num_loads_before);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        let num_loads_before = RAW_CODEC_NUM_LOADS.load(std::sync::atomic::Ordering::SeqCst);
+        check_values(&index)?;
+        assert!(RAW_CODEC_NUM_LOADS.load(std::sync::atomic::Ordering::SeqCst) > num_loads_before);
+
+        // The codecs are scoped to the index: another index over the same directory does not
+        // see them until they are registered in it.
+        let mut other_index = Index::open(directory)?;
+        assert!(other_index
+            .fast_field_codecs()
+            .get(RAW_CODEC_CODE)
+            .is_none());
+        assert!(check_values(&other_index).is_err());
+        let other_codecs = FastFieldCodecs::default();
+        other_codecs.register(RAW_CODEC_CODE, Arc::new(RawCodec))?;
+        other_index.set_fast_field_codecs(other_codecs);
+        check_values(&other_index)?;
+
+        assert!(codecs.unregister(RAW_CODEC_CODE).is_some());
+        assert!(codecs.unregister(RAW_CODEC_CODE).is_none());
+        assert!(check_values(&index).is_err());
+        Ok(())
+    }
 }
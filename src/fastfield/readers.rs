@@ -2,6 +2,7 @@ use std::io;
 use std::net::Ipv6Addr;
 use std::sync::Arc;
 
+use columnar::column_values::FastFieldCodecs;
 use columnar::{
     BytesColumn, Column, ColumnType, ColumnValues, ColumnarReader, DynamicColumn,
     DynamicColumnHandle, HasAssociatedColumnType, StrColumn,
@@ -10,9 +11,11 @@ use common::ByteCount;
 
 use crate::core::json_utils::encode_column_name;
 use crate::directory::FileSlice;
-use crate::schema::{Field, FieldEntry, FieldType, Schema};
+use crate::schema::{
+    value_type_to_column_type, Facet, Field, FieldEntry, FieldType, OwnedValue, Schema, Type,
+};
 use crate::space_usage::{FieldUsage, PerFieldSpaceUsage};
-use crate::TantivyError;
+use crate::{DocId, TantivyError};
 
 /// Provides access to all of the BitpackedFastFieldReader.
 ///
@@ -25,8 +28,22 @@ pub struct FastFieldReaders {
 }
 
 impl FastFieldReaders {
+    #[cfg(test)]
     pub(crate) fn open(fast_field_file: FileSlice, schema: Schema) -> io::Result<FastFieldReaders> {
-        let columnar = Arc::new(ColumnarReader::open(fast_field_file)?);
+        FastFieldReaders::open_with_codecs(fast_field_file, schema, FastFieldCodecs::default())
+    }
+
+    /// Opens the fast fields, decoding the columns encoded with a custom codec with the codecs of
+    /// `fast_field_codecs`.
+    pub(crate) fn open_with_codecs(
+        fast_field_file: FileSlice,
+        schema: Schema,
+        fast_field_codecs: FastFieldCodecs,
+    ) -> io::Result<FastFieldReaders> {
+        let columnar = Arc::new(ColumnarReader::open_with_codecs(
+            fast_field_file,
+            fast_field_codecs,
+        )?);
         Ok(FastFieldReaders { columnar, schema })
     }
 
@@ -381,6 +398,94 @@ impl FastFieldReaders {
     pub fn bool(&self, field_name: &str) -> crate::Result<Column<bool>> {
         self.column(field_name)
     }
+
+    /// Returns the column of the fast field `field_name`, with the type declared in the schema.
+    ///
+    /// This is the runtime counterpart of the typed accessors, such as [`FastFieldReaders::u64`]
+    /// or [`FastFieldReaders::str`], for the code discovering the fields from the [`Schema`] of
+    /// the index. Facets are stored in a `str` column.
+    ///
+    /// JSON fields can hold columns of several types, see
+    /// [`FastFieldReaders::dynamic_column_handles`] for them.
+    ///
+    /// Returns `None` if no document of the segment has a value for the field.
+    pub fn dynamic_column(&self, field_name: &str) -> crate::Result<Option<DynamicColumn>> {
+        let field_type = self.non_json_field_type(field_name)?;
+        let Some(column_type) = value_type_to_column_type(field_type.value_type()) else {
+            return Ok(None);
+        };
+        let Some(dynamic_column_handle) = self.dynamic_column_handle(field_name, column_type)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(dynamic_column_handle.open()?))
+    }
+
+    /// Returns the values of the fast field `field_name` for `doc`, as the values of the
+    /// documents.
+    ///
+    /// The column is opened on each call: to read the values of many documents, prefer
+    /// [`FastFieldReaders::dynamic_column`].
+    pub fn values(&self, field_name: &str, doc: DocId) -> crate::Result<Vec<OwnedValue>> {
+        let is_facet = self.non_json_field_type(field_name)?.value_type() == Type::Facet;
+        let Some(dynamic_column) = self.dynamic_column(field_name)? else {
+            return Ok(Vec::new());
+        };
+        let values = match dynamic_column {
+            DynamicColumn::Bool(column) => {
+                column.values_for_doc(doc).map(OwnedValue::Bool).collect()
+            }
+            DynamicColumn::I64(column) => column.values_for_doc(doc).map(OwnedValue::I64).collect(),
+            DynamicColumn::U64(column) => column.values_for_doc(doc).map(OwnedValue::U64).collect(),
+            DynamicColumn::F64(column) => column.values_for_doc(doc).map(OwnedValue::F64).collect(),
+            DynamicColumn::IpAddr(column) => {
+                column.values_for_doc(doc).map(OwnedValue::IpAddr).collect()
+            }
+            DynamicColumn::DateTime(column) => {
+                column.values_for_doc(doc).map(OwnedValue::Date).collect()
+            }
+            DynamicColumn::Bytes(column) => {
+                let mut values = Vec::new();
+                for ord in column.term_ords(doc) {
+                    let mut bytes = Vec::new();
+                    column.ord_to_bytes(ord, &mut bytes)?;
+                    values.push(OwnedValue::Bytes(bytes));
+                }
+                values
+            }
+            DynamicColumn::Str(column) => {
+                let mut values = Vec::new();
+                for ord in column.term_ords(doc) {
+                    let mut text = String::new();
+                    column.ord_to_str(ord, &mut text)?;
+                    values.push(if is_facet {
+                        OwnedValue::Facet(Facet::from_encoded_string(text))
+                    } else {
+                        OwnedValue::Str(text)
+                    });
+                }
+                values
+            }
+        };
+        Ok(values)
+    }
+
+    fn non_json_field_type(&self, field_name: &str) -> crate::Result<&FieldType> {
+        let field = self.schema.get_field(field_name)?;
+        let field_entry = self.schema.get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field `{field_name}` is not configured as a fast field."
+            )));
+        }
+        if field_entry.field_type().is_json() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Field `{field_name}` is a JSON field, its columns are listed by \
+                 `dynamic_column_handles`."
+            )));
+        }
+        Ok(field_entry.field_type())
+    }
 }
 
 #[cfg(test)]
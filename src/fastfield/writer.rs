@@ -1,11 +1,13 @@
 use std::io;
 
+use columnar::column_values::{CodecType, FastFieldCodecs};
 use columnar::{ColumnarWriter, NumericalValue};
 use common::{DateTimePrecision, JsonPathWriter};
 use tokenizer_api::Token;
 
+use crate::indexer::ComputedField;
 use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
-use crate::schema::{value_type_to_column_type, Field, FieldType, Schema, Type};
+use crate::schema::{value_type_to_column_type, Field, FieldType, OwnedValue, Schema, Type};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::{DocId, TantivyError};
 
@@ -88,6 +90,9 @@ impl FastFieldsWriter {
                     sort_values_within_row,
                 );
             }
+            if let Some(code) = field_entry.field_type().fast_codec() {
+                columnar_writer.set_column_codec(field_entry.name(), CodecType::Custom(code));
+            }
         }
         Ok(FastFieldsWriter {
             columnar_writer,
@@ -100,6 +105,12 @@ impl FastFieldsWriter {
         })
     }
 
+    /// Sets the registry of the custom codecs the fast fields may be encoded with.
+    pub(crate) fn set_fast_field_codecs(&mut self, fast_field_codecs: FastFieldCodecs) {
+        self.columnar_writer
+            .set_fast_field_codecs(fast_field_codecs);
+    }
+
     /// The memory used (inclusive childs)
     pub fn mem_usage(&self) -> usize {
         self.columnar_writer.mem_usage()
@@ -107,12 +118,37 @@ impl FastFieldsWriter {
 
     /// Indexes all of the fastfields of a new document.
     pub fn add_document<D: Document>(&mut self, doc: &D) -> crate::Result<()> {
+        self.add_document_with_computed_fields(doc, &[])
+    }
+
+    /// Indexes all of the fastfields of a new document, replacing the values of the target
+    /// fields of `computed_fields` by their computed value.
+    pub(crate) fn add_document_with_computed_fields<D: Document>(
+        &mut self,
+        doc: &D,
+        computed_fields: &[ComputedField],
+    ) -> crate::Result<()> {
         let doc_id = self.num_docs;
         for (field, value) in doc.iter_fields_and_values() {
+            if computed_fields
+                .iter()
+                .any(|computed_field| computed_field.target_field() == field)
+            {
+                continue;
+            }
             let value_access = value as D::Value<'_>;
 
             self.add_doc_value(doc_id, field, value_access)?;
         }
+        for computed_field in computed_fields {
+            if let Some(value) = computed_field.compute(doc) {
+                self.add_doc_value(
+                    doc_id,
+                    computed_field.target_field(),
+                    &OwnedValue::F64(value),
+                )?;
+            }
+        }
         self.num_docs += 1;
         Ok(())
     }
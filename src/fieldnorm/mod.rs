@@ -16,15 +16,66 @@
 //! in a very short array.
 //!
 //! This trick is used by the Bm25 similarity.
+//!
+//! For corpora where small length differences matter, a text field can instead record its
+//! exact fieldnorms on four bytes, see [`FieldNormPrecision`].
 mod code;
+mod overlay;
 mod reader;
 mod serializer;
 mod writer;
 
+use serde::{Deserialize, Serialize};
+
 use self::code::{fieldnorm_to_id, id_to_fieldnorm};
+pub use self::overlay::FieldNormOverlay;
 pub use self::reader::{FieldNormReader, FieldNormReaders};
 pub use self::serializer::FieldNormsSerializer;
 pub use self::writer::FieldNormsWriter;
+use crate::schema::{FieldEntry, FieldType};
+
+/// Index, within the fieldnorm composite file, of the fieldnorms of the fields
+/// recorded with [`FieldNormPrecision::Exact`].
+pub(crate) const EXACT_FIELDNORMS_IDX: usize = 1;
+
+/// Precision with which the fieldnorms of a text field are recorded.
+///
+/// The precision is selected in the schema using
+/// [`TextFieldIndexing::set_fieldnorm_precision`](crate::schema::TextFieldIndexing::set_fieldnorm_precision).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldNormPrecision {
+    /// Fieldnorms are encoded on one byte per document, on a log scale.
+    ///
+    /// Fieldnorms up to 40 are exact. Larger fieldnorms are rounded down, with a relative error
+    /// of up to 12%.
+    #[default]
+    Quantized,
+    /// Fieldnorms are encoded exactly, on four bytes per document.
+    ///
+    /// BM25 then uses the exact length of the field, at the cost of four times the space of
+    /// the fieldnorms of the field, as reported by the fieldnorms component of
+    /// [`space_usage`](crate::Searcher::space_usage).
+    Exact,
+}
+
+impl FieldNormPrecision {
+    /// Returns true if this is the default precision.
+    pub fn is_default(&self) -> bool {
+        *self == FieldNormPrecision::default()
+    }
+
+    /// Returns the precision of the fieldnorms of the given field.
+    pub(crate) fn for_field_entry(field_entry: &FieldEntry) -> FieldNormPrecision {
+        match field_entry.field_type() {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .map(|indexing_options| indexing_options.fieldnorm_precision())
+                .unwrap_or_default(),
+            _ => FieldNormPrecision::Quantized,
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -32,13 +83,18 @@ mod tests {
 
     use once_cell::sync::Lazy;
 
+    use crate::collector::TopDocs;
     use crate::directory::{CompositeFile, Directory, RamDirectory, WritePtr};
-    use crate::fieldnorm::{FieldNormReader, FieldNormsSerializer, FieldNormsWriter};
+    use crate::fieldnorm::{
+        fieldnorm_to_id, FieldNormPrecision, FieldNormReader, FieldNormsSerializer,
+        FieldNormsWriter,
+    };
+    use crate::indexer::NoMergePolicy;
     use crate::query::{EnableScoring, Query, TermQuery};
     use crate::schema::{
-        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED, TEXT,
+        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED, STRING, TEXT,
     };
-    use crate::{Index, Term, TERMINATED};
+    use crate::{DocAddress, DocSet, Index, IndexWriter, Term, TERMINATED};
 
     pub static SCHEMA: Lazy<Schema> = Lazy::new(|| {
         let mut schema_builder = Schema::builder();
@@ -151,4 +207,233 @@ mod tests {
         assert_eq!(scorer.advance(), TERMINATED);
         Ok(())
     }
+
+    #[test]
+    fn test_segment_reader_fieldnorms() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let stored = schema_builder.add_text_field("stored", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer = index.writer_for_tests()?;
+        let num_tokens_per_doc = [0usize, 1, 7, 40, 41, 100, 1_000, 12_345];
+        for &num_tokens in &num_tokens_per_doc {
+            writer.add_document(doc!(text => vec!["token"; num_tokens].join(" ")))?;
+        }
+        writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert!(segment_reader.fieldnorms(stored)?.is_none());
+        let fieldnorm_reader = segment_reader.fieldnorms(text)?.unwrap();
+        for (doc, &num_tokens) in num_tokens_per_doc.iter().enumerate() {
+            let num_tokens = num_tokens as u32;
+            let fieldnorm = fieldnorm_reader.fieldnorm(doc as u32);
+            assert!(fieldnorm <= num_tokens);
+            if num_tokens <= 40 {
+                assert_eq!(fieldnorm, num_tokens);
+            } else {
+                assert!(((num_tokens - fieldnorm) as f32) < num_tokens as f32 * 0.12f32);
+            }
+            let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc as u32);
+            assert_eq!(FieldNormReader::id_to_fieldnorm(fieldnorm_id), fieldnorm);
+        }
+        Ok(())
+    }
+
+    fn scores_for_precision(precision: FieldNormPrecision) -> crate::Result<Vec<f32>> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqs)
+                .set_fieldnorm_precision(precision),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer: IndexWriter = index.writer_for_tests()?;
+        let num_tokens_per_doc = [1_000usize, 1_010];
+        assert_eq!(fieldnorm_to_id(1_000), fieldnorm_to_id(1_010));
+        for &num_tokens in &num_tokens_per_doc {
+            let mut tokens = vec!["filler"; num_tokens - 1];
+            tokens.push("hello");
+            writer.add_document(doc!(text => tokens.join(" ")))?;
+            // Each document gets its own segment, to check that merging keeps the fieldnorms.
+            writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        writer.merge(&segment_ids).wait()?;
+        writer.wait_merging_threads()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let fieldnorm_reader = segment_reader.get_fieldnorms_reader(text)?;
+        assert_eq!(fieldnorm_reader.precision(), precision);
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::WithFreqs,
+        );
+        let weight = query.weight(EnableScoring::enabled_from_searcher(&searcher))?;
+        let mut scorer = weight.scorer(segment_reader, 1.0f32)?;
+        // The merge does not necessarily keep the documents in the order they were added.
+        let mut fieldnorms_and_scores = Vec::new();
+        for doc in 0..segment_reader.max_doc() {
+            assert_eq!(scorer.doc(), doc);
+            let explanation = query.explain(&searcher, DocAddress::new(0, doc))?;
+            assert!((scorer.score() - explanation.value()).abs() < 1e-6f32);
+            fieldnorms_and_scores.push((fieldnorm_reader.fieldnorm(doc), scorer.score()));
+            scorer.advance();
+        }
+        fieldnorms_and_scores.sort_by_key(|(fieldnorm, _)| *fieldnorm);
+        if precision == FieldNormPrecision::Exact {
+            let fieldnorms: Vec<u32> = fieldnorms_and_scores
+                .iter()
+                .map(|(fieldnorm, _)| *fieldnorm)
+                .collect();
+            assert_eq!(fieldnorms, [1_000, 1_010]);
+        }
+        Ok(fieldnorms_and_scores
+            .into_iter()
+            .map(|(_, score)| score)
+            .collect())
+    }
+
+    #[test]
+    fn test_fieldnorm_precision() -> crate::Result<()> {
+        let quantized_scores = scores_for_precision(FieldNormPrecision::Quantized)?;
+        assert_eq!(quantized_scores[0], quantized_scores[1]);
+        let exact_scores = scores_for_precision(FieldNormPrecision::Exact)?;
+        assert!(exact_scores[0] > exact_scores[1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_fieldnorm() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let key = schema_builder.add_text_field("key", STRING);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let exact_text = schema_builder.add_text_field(
+            "exact_text",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default().set_fieldnorm_precision(FieldNormPrecision::Exact),
+            ),
+        );
+        let stored = schema_builder.add_text_field("stored", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer: IndexWriter = index.writer_for_tests()?;
+        writer.set_merge_policy(Box::new(NoMergePolicy));
+        for doc_key in ["a", "b"] {
+            writer
+                .add_document(doc!(key => doc_key, text => "hello world", exact_text => "hello"))?;
+        }
+        writer.commit()?;
+        assert!(matches!(
+            writer.update_fieldnorm(Term::from_field_text(key, "a"), stored, 3),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        writer.update_fieldnorm(Term::from_field_text(key, "a"), text, 30)?;
+        writer.update_fieldnorm(Term::from_field_text(key, "a"), exact_text, 1_001)?;
+        // The update only affects the documents added before it, in the uncommitted segment too.
+        writer.add_document(doc!(key => "c", text => "hello world", exact_text => "hello"))?;
+        writer.update_fieldnorm(Term::from_field_text(key, "c"), text, 5)?;
+        writer.add_document(doc!(key => "c", text => "hello world", exact_text => "hello"))?;
+        writer.commit()?;
+
+        let fieldnorms_and_scores = |field: Field| -> crate::Result<Vec<(String, u32, f32)>> {
+            let searcher = index.reader()?.searcher();
+            let mut fieldnorms_and_scores = Vec::new();
+            for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                let fieldnorm_reader = segment_reader.get_fieldnorms_reader(field)?;
+                let key_reader = segment_reader.inverted_index(key)?;
+                let mut key_terms = key_reader.terms().stream()?;
+                while key_terms.advance() {
+                    let doc_key = String::from_utf8(key_terms.key().to_vec()).unwrap();
+                    let mut postings = key_reader
+                        .read_postings_from_terminfo(key_terms.value(), IndexRecordOption::Basic)?;
+                    while postings.doc() != TERMINATED {
+                        let doc = postings.doc();
+                        let query = TermQuery::new(
+                            Term::from_field_text(field, "hello"),
+                            IndexRecordOption::WithFreqs,
+                        );
+                        let score = query
+                            .explain(&searcher, DocAddress::new(segment_ord as u32, doc))?
+                            .value();
+                        fieldnorms_and_scores.push((
+                            doc_key.clone(),
+                            fieldnorm_reader.fieldnorm(doc),
+                            score,
+                        ));
+                        postings.advance();
+                    }
+                }
+            }
+            fieldnorms_and_scores.sort_by(|left, right| left.partial_cmp(right).unwrap());
+            Ok(fieldnorms_and_scores)
+        };
+        let check_fieldnorms = || -> crate::Result<()> {
+            let text_fieldnorms = fieldnorms_and_scores(text)?;
+            let fieldnorms: Vec<(&str, u32)> = text_fieldnorms
+                .iter()
+                .map(|(doc_key, fieldnorm, _)| (doc_key.as_str(), *fieldnorm))
+                .collect();
+            assert_eq!(fieldnorms, [("a", 30), ("b", 2), ("c", 2), ("c", 5)]);
+            // The longer field scores lower.
+            assert!(text_fieldnorms[0].2 < text_fieldnorms[1].2);
+            let exact_fieldnorms: Vec<u32> = fieldnorms_and_scores(exact_text)?
+                .iter()
+                .map(|(_, fieldnorm, _)| *fieldnorm)
+                .collect();
+            assert_eq!(exact_fieldnorms, [1_001, 1, 1, 1]);
+            Ok(())
+        };
+        check_fieldnorms()?;
+        let segment_metas = index.searchable_segment_metas()?;
+        assert!(segment_metas
+            .iter()
+            .all(|segment_meta| segment_meta.fieldnorm_overlay_opstamp().is_some()));
+
+        // Merges collapse the overlays into the fieldnorms of the merged segment.
+        let segment_ids = index.searchable_segment_ids()?;
+        writer.merge(&segment_ids).wait()?;
+        check_fieldnorms()?;
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(segment_metas[0].fieldnorm_overlay_opstamp(), None);
+        writer.wait_merging_threads()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_fieldnorm_top_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let key = schema_builder.add_text_field("key", STRING);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer: IndexWriter = index.writer_for_tests()?;
+        // Enough documents for the postings of "hello" to have full blocks, whose maxima are
+        // stored in the skip list. The short documents of the first block raise the threshold
+        // above the stored maximum of the second block.
+        for i in 0..300 {
+            let text_value = if i < 2 {
+                "hello a b c d"
+            } else {
+                "hello a b c d e f g h i"
+            };
+            writer.add_document(doc!(key => format!("k{i}"), text => text_value))?;
+        }
+        writer.commit()?;
+        writer.update_fieldnorm(Term::from_field_text(key, "k200"), text, 1)?;
+        writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        let (score, doc_address) = top_docs[0];
+        assert_eq!(doc_address, DocAddress::new(0, 200));
+        let explanation = query.explain(&searcher, doc_address)?;
+        assert!((score - explanation.value()).abs() < 1e-6f32);
+        Ok(())
+    }
 }
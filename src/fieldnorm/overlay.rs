@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use common::{BinarySerializable, OwnedBytes};
+
+use super::fieldnorm_to_id;
+use crate::schema::Field;
+use crate::DocId;
+
+/// Fieldnorms overriding, for a few documents, the fieldnorms of the fieldnorm component of a
+/// segment.
+///
+/// The fieldnorm component of a segment is never rewritten: the fieldnorms updated with
+/// [`IndexWriter::update_fieldnorm`](crate::IndexWriter::update_fieldnorm) are kept in
+/// this overlay instead, which is written in a small file of its own, and applied when the
+/// fieldnorms of the segment are read.
+///
+/// Merges collapse the overlays: the merged segment records the updated fieldnorms in its
+/// fieldnorm component, and starts without an overlay.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FieldNormOverlay {
+    fieldnorms: BTreeMap<(Field, DocId), u32>,
+}
+
+impl FieldNormOverlay {
+    /// Returns true if the overlay does not override any fieldnorm.
+    pub fn is_empty(&self) -> bool {
+        self.fieldnorms.is_empty()
+    }
+
+    /// Returns the number of fieldnorms overridden.
+    pub fn len(&self) -> usize {
+        self.fieldnorms.len()
+    }
+
+    /// Overrides the fieldnorm of a document for the given field.
+    pub fn set(&mut self, field: Field, doc_id: DocId, fieldnorm: u32) {
+        self.fieldnorms.insert((field, doc_id), fieldnorm);
+    }
+
+    /// Returns the fieldnorm overriding the one of a document for the given field, if any.
+    pub fn get(&self, field: Field, doc_id: DocId) -> Option<u32> {
+        self.fieldnorms.get(&(field, doc_id)).copied()
+    }
+
+    /// Returns the fieldnorms of the documents overridden for the given field, sorted by doc id.
+    pub fn field_fieldnorms(&self, field: Field) -> impl Iterator<Item = (DocId, u32)> + '_ {
+        self.fieldnorms
+            .range((field, 0)..=(field, DocId::MAX))
+            .map(|(&(_, doc_id), &fieldnorm)| (doc_id, fieldnorm))
+    }
+
+    /// Applies the overlay to the one byte fieldnorm ids of a field.
+    pub(crate) fn apply_to_fieldnorm_ids(&self, field: Field, fieldnorm_ids: &mut [u8]) {
+        for (doc_id, fieldnorm) in self.field_fieldnorms(field) {
+            fieldnorm_ids[doc_id as usize] = fieldnorm_to_id(fieldnorm);
+        }
+    }
+
+    /// Applies the overlay to the exact fieldnorms of a field, encoded on four bytes.
+    pub(crate) fn apply_to_exact_fieldnorms(&self, field: Field, exact_fieldnorms: &mut [u8]) {
+        for (doc_id, fieldnorm) in self.field_fieldnorms(field) {
+            let start = doc_id as usize * 4;
+            exact_fieldnorms[start..start + 4].copy_from_slice(&fieldnorm.to_le_bytes());
+        }
+    }
+
+    /// Serializes the overlay.
+    pub fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (self.fieldnorms.len() as u32).serialize(writer)?;
+        for (&(field, doc_id), &fieldnorm) in &self.fieldnorms {
+            field.field_id().serialize(writer)?;
+            doc_id.serialize(writer)?;
+            fieldnorm.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Opens an overlay serialized with [`FieldNormOverlay::serialize`], for a segment with
+    /// `max_doc` documents.
+    ///
+    /// Returns an error if the overlay holds the fieldnorm of a document past `max_doc`.
+    pub fn open(bytes: OwnedBytes, max_doc: DocId) -> io::Result<FieldNormOverlay> {
+        let mut data = bytes.as_slice();
+        let num_fieldnorms = u32::deserialize(&mut data)?;
+        let mut fieldnorms = BTreeMap::new();
+        for _ in 0..num_fieldnorms {
+            let field = Field::from_field_id(u32::deserialize(&mut data)?);
+            let doc_id = DocId::deserialize(&mut data)?;
+            if doc_id >= max_doc {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "The fieldnorm overlay holds the doc id {doc_id}, but the segment only \
+                         has {max_doc} documents"
+                    ),
+                ));
+            }
+            let fieldnorm = u32::deserialize(&mut data)?;
+            fieldnorms.insert((field, doc_id), fieldnorm);
+        }
+        Ok(FieldNormOverlay { fieldnorms })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::OwnedBytes;
+
+    use super::FieldNormOverlay;
+    use crate::fieldnorm::fieldnorm_to_id;
+    use crate::schema::Field;
+
+    #[test]
+    fn test_fieldnorm_overlay_serialization() {
+        let mut overlay = FieldNormOverlay::default();
+        overlay.set(Field::from_field_id(1), 3, 12);
+        overlay.set(Field::from_field_id(0), 7, 100_000);
+        overlay.set(Field::from_field_id(1), 0, 5);
+        overlay.set(Field::from_field_id(1), 3, 13);
+        let mut buffer = Vec::new();
+        overlay.serialize(&mut buffer).unwrap();
+        let opened_overlay = FieldNormOverlay::open(OwnedBytes::new(buffer.clone()), 8).unwrap();
+        assert_eq!(opened_overlay, overlay);
+        assert_eq!(opened_overlay.len(), 3);
+        assert_eq!(opened_overlay.get(Field::from_field_id(1), 3), Some(13));
+        assert_eq!(opened_overlay.get(Field::from_field_id(1), 7), None);
+        assert_eq!(
+            opened_overlay
+                .field_fieldnorms(Field::from_field_id(1))
+                .collect::<Vec<_>>(),
+            vec![(0, 5), (3, 13)]
+        );
+        // Doc 7 does not exist in a segment with 7 documents.
+        let err = FieldNormOverlay::open(OwnedBytes::new(buffer), 7).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_fieldnorm_overlay_apply() {
+        let field = Field::from_field_id(0);
+        let mut overlay = FieldNormOverlay::default();
+        overlay.set(field, 1, 1_000);
+        overlay.set(Field::from_field_id(1), 0, 3);
+        let mut fieldnorm_ids = vec![1u8, 2u8, 3u8];
+        overlay.apply_to_fieldnorm_ids(field, &mut fieldnorm_ids);
+        assert_eq!(fieldnorm_ids[0], 1u8);
+        assert_eq!(fieldnorm_ids[1], fieldnorm_to_id(1_000));
+        assert_eq!(fieldnorm_ids[2], 3u8);
+        let mut exact_fieldnorms: Vec<u8> =
+            [1u32, 2u32].iter().flat_map(|v| v.to_le_bytes()).collect();
+        overlay.apply_to_exact_fieldnorms(field, &mut exact_fieldnorms);
+        assert_eq!(&exact_fieldnorms[4..8], &1_000u32.to_le_bytes());
+    }
+}
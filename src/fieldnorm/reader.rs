@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use super::{fieldnorm_to_id, id_to_fieldnorm};
+use super::{
+    fieldnorm_to_id, id_to_fieldnorm, FieldNormOverlay, FieldNormPrecision, EXACT_FIELDNORMS_IDX,
+};
 use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
 use crate::schema::Field;
 use crate::space_usage::PerFieldSpaceUsage;
@@ -15,6 +17,7 @@ use crate::DocId;
 #[derive(Clone)]
 pub struct FieldNormReaders {
     data: Arc<CompositeFile>,
+    overlay: Option<Arc<FieldNormOverlay>>,
 }
 
 impl FieldNormReaders {
@@ -23,14 +26,43 @@ impl FieldNormReaders {
         let data = CompositeFile::open(&file)?;
         Ok(FieldNormReaders {
             data: Arc::new(data),
+            overlay: None,
         })
     }
 
+    /// Applies the fieldnorms of an overlay on top of the fieldnorms of the file.
+    pub fn with_overlay(self, overlay: FieldNormOverlay) -> FieldNormReaders {
+        FieldNormReaders {
+            data: self.data,
+            overlay: Some(Arc::new(overlay)).filter(|overlay| !overlay.is_empty()),
+        }
+    }
+
+    /// Returns the overlay applied on top of the fieldnorms of the file, if any.
+    pub fn overlay(&self) -> Option<&FieldNormOverlay> {
+        self.overlay.as_deref()
+    }
+
     /// Returns the FieldNormReader for a specific field.
+    ///
+    /// The fieldnorms of the overlay, if any, override the fieldnorms of the file.
     pub fn get_field(&self, field: Field) -> crate::Result<Option<FieldNormReader>> {
-        if let Some(file) = self.data.open_read(field) {
+        let overlay = self
+            .overlay
+            .as_deref()
+            .filter(|overlay| overlay.field_fieldnorms(field).next().is_some());
+        if let Some(file) = self.data.open_read_with_idx(field, EXACT_FIELDNORMS_IDX) {
+            let fieldnorm_reader = FieldNormReader::open_exact(file)?;
+            Ok(Some(match overlay {
+                Some(overlay) => fieldnorm_reader.with_overlay(field, overlay),
+                None => fieldnorm_reader,
+            }))
+        } else if let Some(file) = self.data.open_read(field) {
             let fieldnorm_reader = FieldNormReader::open(file)?;
-            Ok(Some(fieldnorm_reader))
+            Ok(Some(match overlay {
+                Some(overlay) => fieldnorm_reader.with_overlay(field, overlay),
+                None => fieldnorm_reader,
+            }))
         } else {
             Ok(None)
         }
@@ -51,18 +83,48 @@ impl FieldNormReaders {
 ///
 /// The [fieldnorm](FieldNormReader::fieldnorm) represents the length associated with
 /// a given Field of a given document.
+///
+/// # Quantization
+///
+/// Fieldnorms are stored on one byte per document, the `fieldnorm_id`.
+/// Lengths up to 40 tokens are encoded exactly. Larger lengths are placed on a
+/// log-scale, and decoded to the closest value of the scale that is lower or equal to
+/// the actual length. The relative error is below 12%.
+///
+/// BM25 only ever sees the decoded fieldnorm. A custom scorer reading
+/// [`FieldNormReader::fieldnorm`] therefore interprets lengths exactly like BM25 does.
+/// As there are only 256 possible `fieldnorm_id`s, a function of the fieldnorm can be
+/// cached in a 256 entries table indexed by [`FieldNormReader::fieldnorm_id`], built
+/// using [`FieldNormReader::id_to_fieldnorm`].
+///
+/// Fields recorded with [`FieldNormPrecision::Exact`] are not quantized. Their
+/// `fieldnorm_id` is still available, and is derived from the exact fieldnorm.
 #[derive(Clone)]
-pub struct FieldNormReader(ReaderImplEnum);
+pub struct FieldNormReader {
+    reader: ReaderImplEnum,
+    // True if fieldnorms of a `FieldNormOverlay` were applied.
+    has_overlay: bool,
+}
 
 impl From<ReaderImplEnum> for FieldNormReader {
-    fn from(reader_enum: ReaderImplEnum) -> FieldNormReader {
-        FieldNormReader(reader_enum)
+    fn from(reader: ReaderImplEnum) -> FieldNormReader {
+        FieldNormReader {
+            reader,
+            has_overlay: false,
+        }
     }
 }
 
+#[inline]
+fn read_exact_fieldnorm(data: &OwnedBytes, doc_id: DocId) -> u32 {
+    let start = doc_id as usize * 4;
+    u32::from_le_bytes(data.as_slice()[start..start + 4].try_into().unwrap())
+}
+
 #[derive(Clone)]
 enum ReaderImplEnum {
     FromData(OwnedBytes),
+    Exact(OwnedBytes),
     Const {
         num_docs: u32,
         fieldnorm_id: u8,
@@ -92,14 +154,80 @@ impl FieldNormReader {
         Ok(FieldNormReader::new(data))
     }
 
+    /// Opens a field norm reader given the file of a field recorded with
+    /// [`FieldNormPrecision::Exact`].
+    pub fn open_exact(fieldnorm_file: FileSlice) -> crate::Result<Self> {
+        let data = fieldnorm_file.read_bytes()?;
+        if data.len() % 4 != 0 {
+            return Err(crate::TantivyError::DataCorruption(
+                crate::error::DataCorruption::comment_only(
+                    "The length of an exact fieldnorm file is not a multiple of 4",
+                ),
+            ));
+        }
+        Ok(ReaderImplEnum::Exact(data).into())
+    }
+
     fn new(data: OwnedBytes) -> Self {
         ReaderImplEnum::FromData(data).into()
     }
 
+    /// Returns a copy of the reader, with the fieldnorms of `field` in the overlay applied.
+    ///
+    /// Only the fieldnorms of the field are copied in memory: the file itself is left untouched.
+    fn with_overlay(self, field: Field, overlay: &FieldNormOverlay) -> FieldNormReader {
+        let reader = match self.reader {
+            ReaderImplEnum::FromData(data) => {
+                let mut fieldnorm_ids = data.as_slice().to_vec();
+                overlay.apply_to_fieldnorm_ids(field, &mut fieldnorm_ids);
+                ReaderImplEnum::FromData(OwnedBytes::new(fieldnorm_ids))
+            }
+            ReaderImplEnum::Exact(data) => {
+                let mut exact_fieldnorms = data.as_slice().to_vec();
+                overlay.apply_to_exact_fieldnorms(field, &mut exact_fieldnorms);
+                ReaderImplEnum::Exact(OwnedBytes::new(exact_fieldnorms))
+            }
+            reader @ ReaderImplEnum::Const { .. } => reader,
+        };
+        FieldNormReader {
+            reader,
+            has_overlay: true,
+        }
+    }
+
+    /// Returns true if the fieldnorms of a [`FieldNormOverlay`] were applied to the reader.
+    ///
+    /// The block maxima stored with the postings were then computed from the former
+    /// fieldnorms, and cannot bound the scores.
+    pub(crate) fn has_overlay(&self) -> bool {
+        self.has_overlay
+    }
+
+    /// Returns the precision with which the fieldnorms were recorded.
+    pub fn precision(&self) -> FieldNormPrecision {
+        match &self.reader {
+            ReaderImplEnum::Exact(_) => FieldNormPrecision::Exact,
+            ReaderImplEnum::FromData(_) | ReaderImplEnum::Const { .. } => {
+                FieldNormPrecision::Quantized
+            }
+        }
+    }
+
+    /// Returns the exact fieldnorm of a document, if the fieldnorms were recorded with
+    /// [`FieldNormPrecision::Exact`].
+    #[inline]
+    pub(crate) fn exact_fieldnorm(&self, doc_id: DocId) -> Option<u32> {
+        match &self.reader {
+            ReaderImplEnum::Exact(data) => Some(read_exact_fieldnorm(data, doc_id)),
+            ReaderImplEnum::FromData(_) | ReaderImplEnum::Const { .. } => None,
+        }
+    }
+
     /// Returns the number of documents in this segment.
     pub fn num_docs(&self) -> u32 {
-        match &self.0 {
+        match &self.reader {
             ReaderImplEnum::FromData(data) => data.len() as u32,
+            ReaderImplEnum::Exact(data) => (data.len() / 4) as u32,
             ReaderImplEnum::Const { num_docs, .. } => *num_docs,
         }
     }
@@ -108,17 +236,18 @@ impl FieldNormReader {
     /// The fieldnorm is a value approximating the number
     /// of tokens in a given field of the `doc_id`.
     ///
-    /// It is imprecise, and equal or lower than
-    /// the actual number of tokens.
+    /// Unless the fieldnorms were recorded with [`FieldNormPrecision::Exact`],
+    /// it is imprecise, and equal or lower than the actual number of tokens.
     ///
-    /// The fieldnorm is effectively decoded from the
+    /// The fieldnorm is then effectively decoded from the
     /// `fieldnorm_id` by doing a simple table lookup.
     pub fn fieldnorm(&self, doc_id: DocId) -> u32 {
-        match &self.0 {
+        match &self.reader {
             ReaderImplEnum::FromData(data) => {
                 let fieldnorm_id = data.as_slice()[doc_id as usize];
                 id_to_fieldnorm(fieldnorm_id)
             }
+            ReaderImplEnum::Exact(data) => read_exact_fieldnorm(data, doc_id),
             ReaderImplEnum::Const { fieldnorm, .. } => *fieldnorm,
         }
     }
@@ -126,11 +255,12 @@ impl FieldNormReader {
     /// Returns the `fieldnorm_id` associated with a document.
     #[inline]
     pub fn fieldnorm_id(&self, doc_id: DocId) -> u8 {
-        match &self.0 {
+        match &self.reader {
             ReaderImplEnum::FromData(data) => {
                 let fieldnorm_id = data.as_slice()[doc_id as usize];
                 fieldnorm_id
             }
+            ReaderImplEnum::Exact(data) => fieldnorm_to_id(read_exact_fieldnorm(data, doc_id)),
             ReaderImplEnum::Const { fieldnorm_id, .. } => *fieldnorm_id,
         }
     }
@@ -1,6 +1,7 @@
 use std::io;
 use std::io::Write;
 
+use super::EXACT_FIELDNORMS_IDX;
 use crate::directory::{CompositeWrite, WritePtr};
 use crate::schema::Field;
 
@@ -26,6 +27,19 @@ impl FieldNormsSerializer {
         Ok(())
     }
 
+    /// Serialize the exact fieldnorms of a field recorded with
+    /// [`FieldNormPrecision::Exact`](super::FieldNormPrecision::Exact).
+    pub fn serialize_exact_field(&mut self, field: Field, fieldnorms: &[u32]) -> io::Result<()> {
+        let write = self
+            .composite_write
+            .for_field_with_idx(field, EXACT_FIELDNORMS_IDX);
+        for fieldnorm in fieldnorms {
+            write.write_all(&fieldnorm.to_le_bytes())?;
+        }
+        write.flush()?;
+        Ok(())
+    }
+
     /// Clean up / flush / close
     pub fn close(self) -> io::Result<()> {
         self.composite_write.close()?;
@@ -1,7 +1,7 @@
 use std::cmp::Ordering;
 use std::{io, iter};
 
-use super::{fieldnorm_to_id, FieldNormsSerializer};
+use super::{fieldnorm_to_id, FieldNormPrecision, FieldNormsSerializer};
 use crate::schema::{Field, Schema};
 use crate::DocId;
 
@@ -9,9 +9,45 @@ use crate::DocId;
 /// of each document for each field with field norms.
 ///
 /// `FieldNormsWriter` stores a `Vec<u8>` for each tracked field, using a
-/// byte per document per field.
+/// byte per document per field, or a `Vec<u32>` for the fields recorded with
+/// [`FieldNormPrecision::Exact`].
 pub struct FieldNormsWriter {
-    fieldnorms_buffers: Vec<Option<Vec<u8>>>,
+    fieldnorms_buffers: Vec<Option<FieldNormsBuffer>>,
+}
+
+enum FieldNormsBuffer {
+    Quantized(Vec<u8>),
+    Exact(Vec<u32>),
+}
+
+impl FieldNormsBuffer {
+    fn len(&self) -> usize {
+        match self {
+            FieldNormsBuffer::Quantized(buffer) => buffer.len(),
+            FieldNormsBuffer::Exact(buffer) => buffer.len(),
+        }
+    }
+
+    fn mem_usage(&self) -> usize {
+        match self {
+            FieldNormsBuffer::Quantized(buffer) => buffer.capacity(),
+            FieldNormsBuffer::Exact(buffer) => buffer.capacity() * 4,
+        }
+    }
+
+    fn resize(&mut self, num_docs: usize) {
+        match self {
+            FieldNormsBuffer::Quantized(buffer) => buffer.resize(num_docs, 0u8),
+            FieldNormsBuffer::Exact(buffer) => buffer.resize(num_docs, 0u32),
+        }
+    }
+
+    fn push(&mut self, fieldnorm: u32) {
+        match self {
+            FieldNormsBuffer::Quantized(buffer) => buffer.push(fieldnorm_to_id(fieldnorm)),
+            FieldNormsBuffer::Exact(buffer) => buffer.push(fieldnorm),
+        }
+    }
 }
 
 impl FieldNormsWriter {
@@ -33,11 +69,18 @@ impl FieldNormsWriter {
     /// Initialize with state for tracking the field norm fields
     /// specified in the schema.
     pub fn for_schema(schema: &Schema) -> FieldNormsWriter {
-        let mut fieldnorms_buffers: Vec<Option<Vec<u8>>> = iter::repeat_with(|| None)
+        let mut fieldnorms_buffers: Vec<Option<FieldNormsBuffer>> = iter::repeat_with(|| None)
             .take(schema.num_fields())
             .collect();
         for field in FieldNormsWriter::fields_with_fieldnorm(schema) {
-            fieldnorms_buffers[field.field_id() as usize] = Some(Vec::with_capacity(1_000));
+            let precision = FieldNormPrecision::for_field_entry(schema.get_field_entry(field));
+            let fieldnorms_buffer = match precision {
+                FieldNormPrecision::Quantized => {
+                    FieldNormsBuffer::Quantized(Vec::with_capacity(1_000))
+                }
+                FieldNormPrecision::Exact => FieldNormsBuffer::Exact(Vec::with_capacity(1_000)),
+            };
+            fieldnorms_buffers[field.field_id() as usize] = Some(fieldnorms_buffer);
         }
         FieldNormsWriter { fieldnorms_buffers }
     }
@@ -47,7 +90,7 @@ impl FieldNormsWriter {
         self.fieldnorms_buffers
             .iter()
             .flatten()
-            .map(|buf| buf.capacity())
+            .map(FieldNormsBuffer::mem_usage)
             .sum()
     }
     /// Ensure that all documents in 0..max_doc have a byte associated with them
@@ -57,7 +100,7 @@ impl FieldNormsWriter {
     pub fn fill_up_to_max_doc(&mut self, max_doc: DocId) {
         for fieldnorms_buffer_opt in self.fieldnorms_buffers.iter_mut() {
             if let Some(fieldnorms_buffer) = fieldnorms_buffer_opt.as_mut() {
-                fieldnorms_buffer.resize(max_doc as usize, 0u8);
+                fieldnorms_buffer.resize(max_doc as usize);
             }
         }
     }
@@ -79,14 +122,14 @@ impl FieldNormsWriter {
             match fieldnorm_buffer.len().cmp(&(doc as usize)) {
                 Ordering::Less => {
                     // we fill intermediary `DocId` as  having a fieldnorm of 0.
-                    fieldnorm_buffer.resize(doc as usize, 0u8);
+                    fieldnorm_buffer.resize(doc as usize);
                 }
                 Ordering::Equal => {}
                 Ordering::Greater => {
                     panic!("Cannot register a given fieldnorm twice")
                 }
             }
-            fieldnorm_buffer.push(fieldnorm_to_id(fieldnorm));
+            fieldnorm_buffer.push(fieldnorm);
         }
     }
 
@@ -99,7 +142,14 @@ impl FieldNormsWriter {
                 })
             },
         ) {
-            fieldnorms_serializer.serialize_field(field, fieldnorms_buffer)?;
+            match fieldnorms_buffer {
+                FieldNormsBuffer::Quantized(buffer) => {
+                    fieldnorms_serializer.serialize_field(field, buffer)?
+                }
+                FieldNormsBuffer::Exact(buffer) => {
+                    fieldnorms_serializer.serialize_exact_field(field, buffer)?
+                }
+            }
         }
         fieldnorms_serializer.close()?;
         Ok(())
@@ -5,8 +5,10 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::thread::available_parallelism;
 
+use columnar::column_values::FastFieldCodecs;
+
 use super::segment::Segment;
-use super::segment_reader::merge_field_meta_data;
+use super::segment_reader::{merge_field_meta_data, Fingerprinter};
 use super::{FieldMetadata, IndexSettings};
 use crate::core::{Executor, META_FILEPATH};
 use crate::directory::error::OpenReadError;
@@ -14,16 +16,17 @@ use crate::directory::error::OpenReadError;
 use crate::directory::MmapDirectory;
 use crate::directory::{Directory, ManagedDirectory, RamDirectory, INDEX_WRITER_LOCK};
 use crate::error::{DataCorruption, TantivyError};
-use crate::index::{IndexMeta, SegmentId, SegmentMeta, SegmentMetaInventory};
+use crate::index::{IndexMeta, SegmentId, SegmentMeta, SegmentMetaInfo, SegmentMetaInventory};
 use crate::indexer::index_writer::{
     IndexWriterOptions, MAX_NUM_THREAD, MEMORY_BUDGET_NUM_BYTES_MIN,
 };
 use crate::indexer::segment_updater::save_metas;
 use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
+use crate::postings::validate_postings_skip_interval;
 use crate::reader::{IndexReader, IndexReaderBuilder};
 use crate::schema::document::Document;
 use crate::schema::{Field, FieldType, Schema};
-use crate::tokenizer::{TextAnalyzer, TokenizerManager};
+use crate::tokenizer::{TextAnalyzer, Token, TokenizerManager};
 use crate::SegmentReader;
 
 fn load_metas(
@@ -106,6 +109,7 @@ pub struct IndexBuilder {
     index_settings: IndexSettings,
     tokenizer_manager: TokenizerManager,
     fast_field_tokenizer_manager: TokenizerManager,
+    fast_field_codecs: FastFieldCodecs,
 }
 impl Default for IndexBuilder {
     fn default() -> Self {
@@ -120,6 +124,7 @@ impl IndexBuilder {
             index_settings: IndexSettings::default(),
             tokenizer_manager: TokenizerManager::default(),
             fast_field_tokenizer_manager: TokenizerManager::default(),
+            fast_field_codecs: FastFieldCodecs::default(),
         }
     }
 
@@ -149,6 +154,12 @@ impl IndexBuilder {
         self
     }
 
+    /// Set the custom fast field codecs.
+    pub fn fast_field_codecs(mut self, codecs: FastFieldCodecs) -> Self {
+        self.fast_field_codecs = codecs;
+        self
+    }
+
     /// Creates a new index using the [`RamDirectory`].
     ///
     /// The index will be allocated in anonymous memory.
@@ -222,6 +233,7 @@ impl IndexBuilder {
         }
         let mut index = Index::open(dir)?;
         index.set_tokenizers(self.tokenizer_manager.clone());
+        index.set_fast_field_codecs(self.fast_field_codecs.clone());
         if index.schema() == self.get_expect_schema()? {
             Ok(index)
         } else {
@@ -232,13 +244,13 @@ impl IndexBuilder {
     }
 
     fn validate(&self) -> crate::Result<()> {
-        if let Some(_schema) = self.schema.as_ref() {
-            Ok(())
-        } else {
-            Err(TantivyError::InvalidArgument(
+        if self.schema.is_none() {
+            return Err(TantivyError::InvalidArgument(
                 "no schema passed".to_string(),
-            ))
+            ));
         }
+        self.index_settings.positions_compression.validate()?;
+        validate_postings_skip_interval(self.index_settings.postings_skip_interval)
     }
 
     /// Creates a new index given an implementation of the trait `Directory`.
@@ -258,6 +270,7 @@ impl IndexBuilder {
         let mut index = Index::open_from_metas(directory, &metas, SegmentMetaInventory::default());
         index.set_tokenizers(self.tokenizer_manager);
         index.set_fast_field_tokenizers(self.fast_field_tokenizer_manager);
+        index.set_fast_field_codecs(self.fast_field_codecs);
         Ok(index)
     }
 }
@@ -271,6 +284,7 @@ pub struct Index {
     executor: Executor,
     tokenizers: TokenizerManager,
     fast_field_tokenizers: TokenizerManager,
+    fast_field_codecs: FastFieldCodecs,
     inventory: SegmentMetaInventory,
 }
 
@@ -389,6 +403,7 @@ impl Index {
             schema,
             tokenizers: TokenizerManager::default(),
             fast_field_tokenizers: TokenizerManager::default(),
+            fast_field_codecs: FastFieldCodecs::default(),
             executor: Executor::single_thread(),
             inventory,
         }
@@ -414,6 +429,19 @@ impl Index {
         &self.fast_field_tokenizers
     }
 
+    /// Setter for the custom fast field codecs.
+    pub fn set_fast_field_codecs(&mut self, codecs: FastFieldCodecs) {
+        self.fast_field_codecs = codecs;
+    }
+
+    /// Accessor for the custom fast field codecs.
+    ///
+    /// The codecs registered here are used to write the fast fields of the segments of the index,
+    /// and to read them back.
+    pub fn fast_field_codecs(&self) -> &FastFieldCodecs {
+        &self.fast_field_codecs
+    }
+
     /// Get the tokenizer associated with a specific field.
     pub fn tokenizer_for_field(&self, field: Field) -> crate::Result<TextAnalyzer> {
         let field_entry = self.schema.get_field_entry(field);
@@ -444,6 +472,62 @@ impl Index {
             })
     }
 
+    /// Returns the tokens produced by the analyzer of a text field for `text`, with their
+    /// positions and offsets.
+    ///
+    /// This runs the same pipeline as the indexing of the field, and as the [`QueryParser`]
+    /// on the terms of this field, so it helps finding out why a query does not match.
+    ///
+    /// [`QueryParser`]: crate::query::QueryParser
+    pub fn analyze(&self, field: Field, text: &str) -> crate::Result<Vec<Token>> {
+        let mut text_analyzer = self.tokenizer_for_field(field)?;
+        let mut token_stream = text_analyzer.token_stream(text);
+        let mut tokens = Vec::new();
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+        Ok(tokens)
+    }
+
+    /// Checks that all of the tokenizers referenced by the schema are registered.
+    ///
+    /// The tokenizers of indexed text and JSON fields are looked up in the
+    /// [tokenizer manager](Index::tokenizers), and the tokenizers of text and JSON fast fields in
+    /// the [fast field tokenizer manager](Index::fast_field_tokenizer).
+    ///
+    /// On failure, returns the names of the missing tokenizers, deduplicated and sorted.
+    pub fn validate_tokenizers(&self) -> Result<(), Vec<String>> {
+        let mut missing_names = Vec::new();
+        for (_field, field_entry) in self.schema.fields() {
+            let (indexing_options_opt, fast_field_tokenizer_opt) = match field_entry.field_type() {
+                FieldType::Str(options) => (
+                    options.get_indexing_options(),
+                    options.get_fast_field_tokenizer_name(),
+                ),
+                FieldType::JsonObject(options) => (
+                    options.get_text_indexing_options(),
+                    options.get_fast_field_tokenizer_name(),
+                ),
+                _ => continue,
+            };
+            if let Some(indexing_options) = indexing_options_opt {
+                let tokenizer_name = indexing_options.tokenizer();
+                if self.tokenizers.get(tokenizer_name).is_none() {
+                    missing_names.push(tokenizer_name.to_string());
+                }
+            }
+            if let Some(tokenizer_name) = fast_field_tokenizer_opt {
+                if self.fast_field_tokenizers.get(tokenizer_name).is_none() {
+                    missing_names.push(tokenizer_name.to_string());
+                }
+            }
+        }
+        if missing_names.is_empty() {
+            return Ok(());
+        }
+        missing_names.sort_unstable();
+        missing_names.dedup();
+        Err(missing_names)
+    }
+
     /// Create a default [`IndexReader`] for the given index.
     ///
     /// See [`Index.reader_builder()`].
@@ -677,6 +761,34 @@ impl Index {
         Ok(self.load_metas()?.segments)
     }
 
+    /// Returns coarse statistics about the segments of the last commit.
+    ///
+    /// This only reads the `meta.json` file and the length of the segment files.
+    /// It is much cheaper than [`Searcher::space_usage`](crate::Searcher::space_usage), as no
+    /// segment data is opened.
+    pub fn segment_metas(&self) -> crate::Result<Vec<SegmentMetaInfo>> {
+        let mut segment_meta_infos = Vec::new();
+        for segment_meta in self.searchable_segment_metas()? {
+            let mut num_bytes = 0u64;
+            for path in segment_meta.list_files() {
+                match self.directory.file_num_bytes(&path) {
+                    Ok(file_num_bytes) => num_bytes += file_num_bytes,
+                    Err(OpenReadError::FileDoesNotExist(_)) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            segment_meta_infos.push(SegmentMetaInfo {
+                segment_id: segment_meta.id(),
+                max_doc: segment_meta.max_doc(),
+                num_docs: segment_meta.num_docs(),
+                num_deleted_docs: segment_meta.num_deleted_docs(),
+                creation_opstamp: segment_meta.creation_opstamp(),
+                num_bytes,
+            });
+        }
+        Ok(segment_meta_infos)
+    }
+
     /// Returns the list of segment ids that are searchable.
     pub fn searchable_segment_ids(&self) -> crate::Result<Vec<SegmentId>> {
         Ok(self
@@ -705,6 +817,34 @@ impl Index {
         }
         Ok(damaged_files)
     }
+
+    /// Returns a fingerprint of the content of the searchable segments of the index.
+    ///
+    /// It combines the [content fingerprints](SegmentReader::content_fingerprint) of the
+    /// segments, regardless of their order. The schema and the settings of the index are not
+    /// part of the fingerprint.
+    ///
+    /// Two indexes built from the same documents, with the same schema and settings, have the
+    /// same fingerprint as long as they end up with the same segments. Segments are not
+    /// deterministic when documents are indexed by several threads, or when background merges
+    /// run: to get reproducible fingerprints, index with a single thread and the same memory
+    /// budget, commit at the same points, and use a deterministic merge policy such as
+    /// [`NoMergePolicy`](crate::indexer::NoMergePolicy).
+    ///
+    /// This reads all of the data of the index.
+    pub fn fingerprint(&self) -> crate::Result<u128> {
+        let mut segment_fingerprints: Vec<u128> = self
+            .searchable_segments()?
+            .iter()
+            .map(|segment| Ok(SegmentReader::open(segment)?.content_fingerprint()?))
+            .collect::<crate::Result<_>>()?;
+        segment_fingerprints.sort_unstable();
+        let mut fingerprinter = Fingerprinter::new();
+        for segment_fingerprint in segment_fingerprints {
+            fingerprinter.write(&segment_fingerprint.to_le_bytes());
+        }
+        Ok(fingerprinter.finish())
+    }
 }
 
 impl fmt::Debug for Index {
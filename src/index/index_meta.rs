@@ -1,16 +1,18 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
 use super::SegmentComponent;
 use crate::index::SegmentId;
+use crate::positions::{PositionsCompression, PositionsOverflowPolicy};
 use crate::schema::Schema;
 use crate::store::Compressor;
-use crate::{Inventory, Opstamp, TrackedObject};
+use crate::{DateTime, Inventory, Opstamp, TantivyError, TrackedObject};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct DeleteMeta {
@@ -39,11 +41,43 @@ impl SegmentMetaInventory {
             max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: None,
+            fieldnorm_overlay_opstamp: None,
+            creation_opstamp: None,
+            creation_timestamp: Some(OffsetDateTime::now_utc().unix_timestamp()),
+            user_metadata: SegmentUserMetadata::new(),
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
 }
 
+/// Key-value metadata attached to a segment by the user, see
+/// [`IndexWriter::set_segment_user_metadata`](crate::IndexWriter::set_segment_user_metadata).
+///
+/// The segments without metadata have an empty map.
+pub type SegmentUserMetadata = BTreeMap<String, String>;
+
+/// Computes the user metadata of a merged segment from the user metadata of the segments
+/// merged, see
+/// [`IndexWriter::set_user_metadata_merger`](crate::IndexWriter::set_user_metadata_merger).
+pub type UserMetadataMerger = dyn Fn(&[&SegmentUserMetadata]) -> SegmentUserMetadata + Send + Sync;
+
+/// The default [`UserMetadataMerger`]: keeps the entries shared by all of the segments merged,
+/// with the same value.
+///
+/// A merged segment is tagged "hot" only if all of the merged segments were.
+pub fn merge_common_user_metadata(
+    segments_user_metadata: &[&SegmentUserMetadata],
+) -> SegmentUserMetadata {
+    let Some((first, others)) = segments_user_metadata.split_first() else {
+        return SegmentUserMetadata::new();
+    };
+    first
+        .iter()
+        .filter(|(key, value)| others.iter().all(|other| other.get(*key) == Some(*value)))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
 /// `SegmentMeta` contains simple meta information about a segment.
 ///
 /// For instance the number of docs it contains,
@@ -139,9 +173,14 @@ impl SegmentMeta {
             SegmentComponent::Terms => ".term".to_string(),
             SegmentComponent::Store => ".store".to_string(),
             SegmentComponent::TempStore => ".store.temp".to_string(),
+            SegmentComponent::Source => ".source".to_string(),
             SegmentComponent::FastFields => ".fast".to_string(),
             SegmentComponent::FieldNorms => ".fieldnorm".to_string(),
             SegmentComponent::Delete => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
+            SegmentComponent::FieldNormOverlay => format!(
+                ".{}.fieldnorm.overlay",
+                self.fieldnorm_overlay_opstamp().unwrap_or(0)
+            ),
         });
         PathBuf::from(path)
     }
@@ -169,6 +208,39 @@ impl SegmentMeta {
             .map(|delete_meta| delete_meta.opstamp)
     }
 
+    /// Returns the `Opstamp` of the last fieldnorm update taken in account in the fieldnorm
+    /// overlay of this segment, if the segment has one.
+    ///
+    /// See [`FieldNormOverlay`](crate::fieldnorm::FieldNormOverlay).
+    pub fn fieldnorm_overlay_opstamp(&self) -> Option<Opstamp> {
+        self.tracked.fieldnorm_overlay_opstamp
+    }
+
+    /// Returns the `Opstamp` of the creation of the segment: the opstamp of the last document
+    /// added to it for a segment written by the `IndexWriter`, and the largest creation opstamp
+    /// of the segments merged for a merged segment.
+    ///
+    /// Returns `None` for the segments written before it was recorded, or by the
+    /// [`SingleSegmentIndexWriter`](crate::SingleSegmentIndexWriter).
+    pub fn creation_opstamp(&self) -> Option<Opstamp> {
+        self.tracked.creation_opstamp
+    }
+
+    /// Returns the time of the creation of the segment, with a precision of one second.
+    ///
+    /// A merged segment is created by the merge, so that its creation time is the time of
+    /// the merge. Returns `None` for the segments written before it was recorded.
+    pub fn creation_timestamp(&self) -> Option<DateTime> {
+        self.tracked
+            .creation_timestamp
+            .map(DateTime::from_timestamp_secs)
+    }
+
+    /// Returns the metadata attached to the segment by the user.
+    pub fn user_metadata(&self) -> &SegmentUserMetadata {
+        &self.tracked.user_metadata
+    }
+
     /// Returns true iff the segment meta contains
     /// delete information.
     pub fn has_deletes(&self) -> bool {
@@ -183,7 +255,11 @@ impl SegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc,
             deletes: None,
+            fieldnorm_overlay_opstamp: None,
+            creation_opstamp: inner_meta.creation_opstamp,
+            creation_timestamp: inner_meta.creation_timestamp,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            user_metadata: inner_meta.user_metadata.clone(),
         });
         SegmentMeta { tracked }
     }
@@ -204,9 +280,91 @@ impl SegmentMeta {
             max_doc: inner_meta.max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: Some(delete_meta),
+            fieldnorm_overlay_opstamp: inner_meta.fieldnorm_overlay_opstamp,
+            creation_opstamp: inner_meta.creation_opstamp,
+            creation_timestamp: inner_meta.creation_timestamp,
+            user_metadata: inner_meta.user_metadata.clone(),
         });
         SegmentMeta { tracked }
     }
+
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_fieldnorm_overlay(self, opstamp: Opstamp) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            deletes: inner_meta.deletes.clone(),
+            fieldnorm_overlay_opstamp: Some(opstamp),
+            creation_opstamp: inner_meta.creation_opstamp,
+            creation_timestamp: inner_meta.creation_timestamp,
+            user_metadata: inner_meta.user_metadata.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Replaces the metadata attached to the segment by the user.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_user_metadata(self, user_metadata: SegmentUserMetadata) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            deletes: inner_meta.deletes.clone(),
+            fieldnorm_overlay_opstamp: inner_meta.fieldnorm_overlay_opstamp,
+            creation_opstamp: inner_meta.creation_opstamp,
+            creation_timestamp: inner_meta.creation_timestamp,
+            user_metadata,
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Records the `Opstamp` of the segment creation, see [`SegmentMeta::creation_opstamp`].
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_creation_opstamp(self, opstamp: Opstamp) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            deletes: inner_meta.deletes.clone(),
+            fieldnorm_overlay_opstamp: inner_meta.fieldnorm_overlay_opstamp,
+            creation_opstamp: Some(opstamp),
+            creation_timestamp: inner_meta.creation_timestamp,
+            user_metadata: inner_meta.user_metadata.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+}
+
+/// Coarse statistics about a segment, as returned by
+/// [`Index::segment_metas`](crate::Index::segment_metas).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentMetaInfo {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Number of documents in the segment, including deleted documents.
+    pub max_doc: u32,
+    /// Number of alive documents in the segment.
+    pub num_docs: u32,
+    /// Number of deleted documents in the segment.
+    pub num_deleted_docs: u32,
+    /// `Opstamp` of the creation of the segment, see [`SegmentMeta::creation_opstamp`].
+    pub creation_opstamp: Option<Opstamp>,
+    /// Sum of the length of the files of the segment, in bytes.
+    pub num_bytes: u64,
+}
+
+impl SegmentMetaInfo {
+    /// Returns the ratio of deleted documents in the segment, between 0 and 1.
+    pub fn delete_ratio(&self) -> f64 {
+        if self.max_doc == 0 {
+            return 0.0;
+        }
+        self.num_deleted_docs as f64 / self.max_doc as f64
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -214,11 +372,20 @@ struct InnerSegmentMeta {
     segment_id: SegmentId,
     max_doc: u32,
     deletes: Option<DeleteMeta>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fieldnorm_overlay_opstamp: Option<Opstamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creation_opstamp: Option<Opstamp>,
+    // Unix timestamp, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creation_timestamp: Option<i64>,
     /// If you want to avoid the SegmentComponent::TempStore file to be covered by
     /// garbage collection and deleted, set this to true. This is used during merge.
     #[serde(skip)]
     #[serde(default = "default_temp_store")]
     pub(crate) include_temp_doc_store: Arc<AtomicBool>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    user_metadata: SegmentUserMetadata,
 }
 fn default_temp_store() -> Arc<AtomicBool> {
     Arc::new(AtomicBool::new(false))
@@ -240,6 +407,10 @@ fn is_true(val: &bool) -> bool {
     *val
 }
 
+fn is_false(val: &bool) -> bool {
+    !*val
+}
+
 /// Search Index Settings.
 ///
 /// Contains settings which are applied on the whole
@@ -258,6 +429,68 @@ pub struct IndexSettings {
     #[serde(default = "default_docstore_blocksize")]
     /// The size of each block that will be compressed and written to disk
     pub docstore_blocksize: usize,
+    /// The parameters used to encode the positions of the terms.
+    ///
+    /// See [`PositionsCompression`] for the available tradeoffs.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PositionsCompression::is_default")]
+    pub positions_compression: PositionsCompression,
+    /// What happens when the positions of a field of a document exceed `u32::MAX`.
+    ///
+    /// See [`PositionsOverflowPolicy`].
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PositionsOverflowPolicy::is_default")]
+    pub positions_overflow_policy: PositionsOverflowPolicy,
+    /// Number of blocks of 128 documents skipped by each entry of the upper level of the skip
+    /// lists of the posting lists.
+    ///
+    /// With the default, `1`, the skip lists only have one level, with an entry per block.
+    /// A larger interval adds an upper level, with an entry every `postings_skip_interval`
+    /// blocks: seeking far ahead in a long posting list, as intersections do, reads fewer skip
+    /// entries, at the cost of a slightly larger index. Must be within
+    /// `1..=`[`MAX_POSTINGS_SKIP_INTERVAL`](crate::postings::MAX_POSTINGS_SKIP_INTERVAL).
+    #[serde(default = "default_postings_skip_interval")]
+    #[serde(skip_serializing_if = "is_default_postings_skip_interval")]
+    pub postings_skip_interval: usize,
+    /// If set to true, the documents stored while indexing a segment are written uncompressed
+    /// to a temporary file, instead of being buffered in memory by blocks. They are compressed
+    /// into the doc store when the segment is finalized, e.g. on commit.
+    ///
+    /// This lowers the peak memory usage when indexing very large documents, at the cost of
+    /// writing their stored fields twice. The temporary file is the `.store.temp` component of
+    /// the segment: it is deleted once the segment is finalized, and a file left behind by a
+    /// crash is removed by the garbage collection. Merges are not affected by this setting.
+    /// (defaults: false)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub docstore_temp_file: bool,
+    /// If set to true, the raw source of the documents added with
+    /// [`IndexWriter::add_document_with_source`](crate::IndexWriter::add_document_with_source)
+    /// is kept, and can be read back with [`Searcher::source`](crate::Searcher::source).
+    ///
+    /// The sources are compressed with `docstore_compression`, in the `.source` component of
+    /// the segments, separately from the stored fields. They cost about as much space as
+    /// storing every field of the documents: for a JSON source, typically more, as the field
+    /// names are repeated in each document. (defaults: false)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub store_source: bool,
+    /// If set to true, the values of the `u64`, `i64`, `f64` and `bool` fields which are both
+    /// stored and fast are not written to the doc store: they are read back from their fast
+    /// field column when a document is retrieved, which saves the space of the second copy.
+    ///
+    /// These columns hold the exact values of the documents, in the order they were added.
+    /// The other field types are stored as usual: the date columns are truncated to the
+    /// precision of the field, and the text, bytes, IP address and JSON values take more
+    /// than a lookup to be read back.
+    ///
+    /// The values read from the columns come after the values of the doc store in the
+    /// retrieved documents. The setting has to be kept for the whole life of the index: the
+    /// values of the documents written with it are only read back while it is set.
+    /// (defaults: false)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub docstore_skip_fast_fields: bool,
 }
 
 /// Must be a function to be compatible with serde defaults
@@ -265,12 +498,26 @@ fn default_docstore_blocksize() -> usize {
     16_384
 }
 
+fn default_postings_skip_interval() -> usize {
+    1
+}
+
+fn is_default_postings_skip_interval(postings_skip_interval: &usize) -> bool {
+    *postings_skip_interval == default_postings_skip_interval()
+}
+
 impl Default for IndexSettings {
     fn default() -> Self {
         Self {
             docstore_compression: Compressor::default(),
             docstore_blocksize: default_docstore_blocksize(),
             docstore_compress_dedicated_thread: true,
+            positions_compression: PositionsCompression::default(),
+            positions_overflow_policy: PositionsOverflowPolicy::default(),
+            postings_skip_interval: default_postings_skip_interval(),
+            docstore_temp_file: false,
+            store_source: false,
+            docstore_skip_fast_fields: false,
         }
     }
 }
@@ -294,6 +541,58 @@ impl Order {
     }
 }
 
+/// Defines how the documents without a value for a fast field are handled, when sorting or
+/// filtering on this field.
+///
+/// It is accepted by [`TopDocs::order_by_fast_field_with_missing`],
+/// [`RangeQuery::with_missing`] and [`AggregationVariants::set_missing_value_policy`].
+/// Aggregations express it through the `missing` parameter of their request: no `missing` is
+/// `Skip`, and a `missing` value is `Default(value)`. They do not support `First` and `Last`,
+/// see [`MissingValuePolicy::into_aggregation_missing`].
+///
+/// [`TopDocs::order_by_fast_field_with_missing`]: crate::collector::TopDocs::order_by_fast_field_with_missing
+/// [`RangeQuery::with_missing`]: crate::query::RangeQuery::with_missing
+/// [`AggregationVariants::set_missing_value_policy`]: crate::aggregation::agg_req::AggregationVariants::set_missing_value_policy
+#[derive(Clone, Debug, PartialEq)]
+pub enum MissingValuePolicy<T> {
+    /// The documents without a value are left out.
+    Skip,
+    /// The documents without a value are handled as if they had the given value.
+    Default(T),
+    /// The documents without a value come first.
+    ///
+    /// When sorting, they come before all of the documents with a value, whatever the order.
+    /// When filtering, the missing value is lower than any value.
+    First,
+    /// The documents without a value come last.
+    ///
+    /// When sorting, they come after all of the documents with a value, whatever the order.
+    /// When filtering, the missing value is greater than any value.
+    Last,
+}
+
+impl<T> MissingValuePolicy<T> {
+    /// Returns the `missing` parameter of an aggregation request following this policy.
+    ///
+    /// Aggregations only support `Skip`, which is no `missing` parameter, and `Default`. `First`
+    /// and `Last` are rejected with an [`InvalidArgument`](TantivyError::InvalidArgument)
+    /// error: an aggregation computes a value over the documents, and has no missing value it
+    /// could put before or after all of the other ones.
+    pub fn into_aggregation_missing(self) -> crate::Result<Option<T>> {
+        match self {
+            MissingValuePolicy::Skip => Ok(None),
+            MissingValuePolicy::Default(value) => Ok(Some(value)),
+            MissingValuePolicy::First | MissingValuePolicy::Last => {
+                Err(TantivyError::InvalidArgument(
+                    "Aggregations only support the `Skip` and `Default` missing value policies, \
+                     not `First` nor `Last`."
+                        .to_string(),
+                ))
+            }
+        }
+    }
+}
+
 /// Meta information about the `Index`.
 ///
 /// This object is serialized on disk in the `meta.json` file.
@@ -387,8 +686,11 @@ impl fmt::Debug for IndexMeta {
 #[cfg(test)]
 mod tests {
 
-    use super::IndexMeta;
+    use time::OffsetDateTime;
+
+    use super::{IndexMeta, InnerSegmentMeta, SegmentMetaInventory};
     use crate::index::index_meta::UntrackedIndexMeta;
+    use crate::index::SegmentId;
     use crate::schema::{Schema, TEXT};
     use crate::store::Compressor;
     #[cfg(feature = "zstd-compression")]
@@ -421,6 +723,64 @@ mod tests {
         assert_eq!(index_metas.opstamp, deser_meta.opstamp);
     }
 
+    #[test]
+    fn test_segment_meta_creation_opstamp() {
+        let inventory = SegmentMetaInventory::default();
+        let segment_meta = inventory
+            .new_segment_meta(SegmentId::generate_random(), 10)
+            .with_creation_opstamp(42);
+        let json = serde_json::to_string(&segment_meta).unwrap();
+        assert!(json.contains(r#""creation_opstamp":42"#));
+        let deser_segment_meta: InnerSegmentMeta = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            deser_segment_meta.track(&inventory).creation_opstamp(),
+            Some(42)
+        );
+
+        // The metas written before the creation opstamp was recorded still load.
+        let json = json.replace(r#","creation_opstamp":42"#, "");
+        let deser_segment_meta: InnerSegmentMeta = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            deser_segment_meta.track(&inventory).creation_opstamp(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_segment_meta_creation_timestamp() {
+        let inventory = SegmentMetaInventory::default();
+        let before = OffsetDateTime::now_utc().unix_timestamp();
+        let segment_meta = inventory.new_segment_meta(SegmentId::generate_random(), 0);
+        let after = OffsetDateTime::now_utc().unix_timestamp();
+        let creation_timestamp = segment_meta
+            .creation_timestamp()
+            .unwrap()
+            .into_utc()
+            .unix_timestamp();
+        assert!((before..=after).contains(&creation_timestamp));
+        let segment_meta = segment_meta.with_max_doc(10).with_delete_meta(2, 3);
+        assert_eq!(
+            segment_meta
+                .creation_timestamp()
+                .unwrap()
+                .into_utc()
+                .unix_timestamp(),
+            creation_timestamp
+        );
+
+        // The metas written before the creation timestamp was recorded still load.
+        let json = serde_json::to_string(&segment_meta).unwrap();
+        let json = json.replace(
+            &format!(r#","creation_timestamp":{creation_timestamp}"#),
+            "",
+        );
+        let deser_segment_meta: InnerSegmentMeta = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            deser_segment_meta.track(&inventory).creation_timestamp(),
+            None
+        );
+    }
+
     #[test]
     #[cfg(feature = "zstd-compression")]
     fn test_serialize_metas_zstd_compressor() {
@@ -436,6 +796,12 @@ mod tests {
                 }),
                 docstore_blocksize: 1_000_000,
                 docstore_compress_dedicated_thread: true,
+                positions_compression: Default::default(),
+                positions_overflow_policy: Default::default(),
+                postings_skip_interval: 1,
+                docstore_temp_file: false,
+                store_source: false,
+                docstore_skip_fast_fields: false,
             },
             segments: Vec::new(),
             schema,
@@ -499,7 +865,13 @@ mod tests {
             IndexSettings {
                 docstore_compression: Compressor::default(),
                 docstore_compress_dedicated_thread: true,
-                docstore_blocksize: 16_384
+                docstore_blocksize: 16_384,
+                positions_compression: Default::default(),
+                positions_overflow_policy: Default::default(),
+                postings_skip_interval: 1,
+                docstore_temp_file: false,
+                store_source: false,
+                docstore_skip_fast_fields: false,
             }
         );
         {
@@ -1,3 +1,5 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::io;
 
 use common::json_path_writer::JSON_END_OF_PATH;
@@ -12,6 +14,7 @@ use tantivy_fst::automaton::{AlwaysMatch, Automaton};
 
 use crate::directory::FileSlice;
 use crate::positions::PositionReader;
+use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
 use crate::postings::{BlockSegmentPostings, SegmentPostings, TermInfo};
 use crate::schema::{IndexRecordOption, Term, Type};
 use crate::termdict::TermDictionary;
@@ -32,6 +35,8 @@ pub struct InvertedIndexReader {
     termdict: TermDictionary,
     postings_file_slice: FileSlice,
     positions_file_slice: FileSlice,
+    positions_block_len: usize,
+    postings_skip_interval: usize,
     record_option: IndexRecordOption,
     total_num_tokens: u64,
 }
@@ -41,6 +46,8 @@ impl InvertedIndexReader {
         termdict: TermDictionary,
         postings_file_slice: FileSlice,
         positions_file_slice: FileSlice,
+        positions_block_len: usize,
+        postings_skip_interval: usize,
         record_option: IndexRecordOption,
     ) -> io::Result<InvertedIndexReader> {
         let (total_num_tokens_slice, postings_body) = postings_file_slice.split(8);
@@ -49,6 +56,8 @@ impl InvertedIndexReader {
             termdict,
             postings_file_slice: postings_body,
             positions_file_slice,
+            positions_block_len,
+            postings_skip_interval,
             record_option,
             total_num_tokens,
         })
@@ -61,6 +70,8 @@ impl InvertedIndexReader {
             termdict: TermDictionary::empty(),
             postings_file_slice: FileSlice::empty(),
             positions_file_slice: FileSlice::empty(),
+            positions_block_len: COMPRESSION_BLOCK_SIZE,
+            postings_skip_interval: 1,
             record_option,
             total_num_tokens: 0u64,
         }
@@ -152,6 +163,7 @@ impl InvertedIndexReader {
             postings_data,
             self.record_option,
             requested_option,
+            self.postings_skip_interval,
         )
     }
 
@@ -172,7 +184,8 @@ impl InvertedIndexReader {
                 let positions_data = self
                     .positions_file_slice
                     .read_bytes_slice(term_info.positions_range.clone())?;
-                let position_reader = PositionReader::open(positions_data)?;
+                let position_reader =
+                    PositionReader::open_with_block_len(positions_data, self.positions_block_len)?;
                 Some(position_reader)
             } else {
                 None
@@ -217,6 +230,66 @@ impl InvertedIndexReader {
             .map(|term_info| term_info.doc_freq)
             .unwrap_or(0u32))
     }
+
+    /// Streams all of the terms of the field in lexicographic order, each together with its
+    /// complete postings.
+    ///
+    /// Terms are returned as their serialized value bytes, as in the term dictionary. The
+    /// postings are read with the [`IndexRecordOption`] the field was indexed with:
+    /// - if the field was indexed without frequencies, `term_freq()` returns 1 for every document,
+    /// - if the field was indexed without positions, no positions are returned.
+    ///
+    /// Postings include deleted documents. Filter them with
+    /// [`SegmentReader::alive_bitset()`](crate::SegmentReader::alive_bitset) if needed.
+    ///
+    /// This is meant to export the content of a field, for instance to migrate to another engine.
+    pub fn stream_postings(
+        &self,
+    ) -> io::Result<impl Iterator<Item = io::Result<(Vec<u8>, SegmentPostings)>> + '_> {
+        let mut stream = self.termdict.stream()?;
+        Ok(std::iter::from_fn(move || {
+            let (term, term_info) = stream.next()?;
+            Some(
+                self.read_postings_from_terminfo(term_info, self.record_option)
+                    .map(|postings| (term.to_vec(), postings)),
+            )
+        }))
+    }
+
+    /// Returns the `n` terms with the highest document frequency, together with their
+    /// document frequency, sorted by decreasing document frequency.
+    ///
+    /// Ties are broken by keeping the lexicographically smallest terms. Terms are returned as
+    /// their serialized value bytes, as in the term dictionary.
+    ///
+    /// Document frequencies include deleted documents.
+    ///
+    /// This streams through the entire term dictionary, and keeps a heap of size `n`.
+    pub fn top_terms_by_doc_freq(&self, n: usize) -> io::Result<Vec<(Vec<u8>, u64)>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        // Min-heap on the doc freq. Among terms with the same doc freq, the largest term is
+        // evicted first.
+        let mut heap: BinaryHeap<Reverse<(u32, Reverse<Vec<u8>>)>> = BinaryHeap::with_capacity(n);
+        let mut stream = self.termdict.stream()?;
+        while let Some((term, term_info)) = stream.next() {
+            if heap.len() < n {
+                heap.push(Reverse((term_info.doc_freq, Reverse(term.to_vec()))));
+            } else if let Some(mut head) = heap.peek_mut() {
+                // Terms are streamed in lexicographic order, so a term with the same doc freq as
+                // the head never replaces it.
+                if term_info.doc_freq > head.0 .0 {
+                    *head = Reverse((term_info.doc_freq, Reverse(term.to_vec())));
+                }
+            }
+        }
+        Ok(heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((doc_freq, Reverse(term)))| (term, u64::from(doc_freq)))
+            .collect())
+    }
 }
 
 #[cfg(feature = "quickwit")]
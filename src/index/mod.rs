@@ -12,7 +12,10 @@ mod segment_reader;
 
 pub use self::index::{Index, IndexBuilder};
 pub(crate) use self::index_meta::SegmentMetaInventory;
-pub use self::index_meta::{IndexMeta, IndexSettings, Order, SegmentMeta};
+pub use self::index_meta::{
+    merge_common_user_metadata, IndexMeta, IndexSettings, MissingValuePolicy, Order, SegmentMeta,
+    SegmentMetaInfo, SegmentUserMetadata, UserMetadataMerger,
+};
 pub use self::inverted_index_reader::InvertedIndexReader;
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
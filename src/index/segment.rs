@@ -1,10 +1,13 @@
 use std::fmt;
+use std::io::BufWriter;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::SegmentComponent;
 use crate::directory::error::{OpenReadError, OpenWriteError};
 use crate::directory::{Directory, FileSlice, WritePtr};
 use crate::index::{Index, SegmentId, SegmentMeta};
+use crate::indexer::io_throttle::{IoThrottle, ThrottledWrite};
 use crate::schema::Schema;
 use crate::Opstamp;
 
@@ -13,6 +16,7 @@ use crate::Opstamp;
 pub struct Segment {
     index: Index,
     meta: SegmentMeta,
+    io_throttle: Option<Arc<IoThrottle>>,
 }
 
 impl fmt::Debug for Segment {
@@ -24,7 +28,11 @@ impl fmt::Debug for Segment {
 impl Segment {
     /// Creates a new segment given an `Index` and a `SegmentId`
     pub(crate) fn for_index(index: Index, meta: SegmentMeta) -> Segment {
-        Segment { index, meta }
+        Segment {
+            index,
+            meta,
+            io_throttle: None,
+        }
     }
 
     /// Returns the index the segment belongs to.
@@ -50,6 +58,7 @@ impl Segment {
         Segment {
             index: self.index,
             meta: self.meta.with_max_doc(max_doc),
+            io_throttle: self.io_throttle,
         }
     }
 
@@ -59,6 +68,25 @@ impl Segment {
         Segment {
             index: self.index,
             meta: self.meta.with_delete_meta(num_deleted_docs, opstamp),
+            io_throttle: self.io_throttle,
+        }
+    }
+
+    /// Records that the segment has a fieldnorm overlay, up to date with the fieldnorm updates
+    /// up to `opstamp`.
+    pub(crate) fn with_fieldnorm_overlay(self, opstamp: Opstamp) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self.meta.with_fieldnorm_overlay(opstamp),
+            io_throttle: self.io_throttle,
+        }
+    }
+
+    /// Throttles the writes of the component files of the segment.
+    pub(crate) fn with_io_throttle(self, io_throttle: Arc<IoThrottle>) -> Segment {
+        Segment {
+            io_throttle: Some(io_throttle),
+            ..self
         }
     }
 
@@ -85,6 +113,10 @@ impl Segment {
     pub fn open_write(&mut self, component: SegmentComponent) -> Result<WritePtr, OpenWriteError> {
         let path = self.relative_path(component);
         let write = self.index.directory_mut().open_write(&path)?;
+        if let Some(io_throttle) = &self.io_throttle {
+            let throttled_write = ThrottledWrite::wrap(write, io_throttle.clone());
+            return Ok(BufWriter::new(Box::new(throttled_write)));
+        }
         Ok(write)
     }
 }
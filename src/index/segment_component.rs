@@ -4,7 +4,8 @@ use std::slice;
 ///
 /// Each component is stored in its own file,
 /// using the pattern `segment_uuid`.`component_extension`,
-/// except the delete component that takes an `segment_uuid`.`delete_opstamp`.`component_extension`
+/// except the delete and fieldnorm overlay components that take an
+/// `segment_uuid`.`opstamp`.`component_extension`
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum SegmentComponent {
     /// Postings (or inverted list). Sorted lists of document ids, associated with terms
@@ -25,15 +26,23 @@ pub enum SegmentComponent {
     Store,
     /// Temporary storage of the documents, before streamed to `Store`.
     TempStore,
+    /// Row-oriented, compressed storage of the raw source of the documents.
+    ///
+    /// Only written if [`IndexSettings::store_source`](crate::IndexSettings::store_source)
+    /// is set.
+    Source,
     /// Bitset describing which document of the segment is alive.
     /// (It was representing deleted docs but changed to represent alive docs from v0.17)
     Delete,
+    /// Fieldnorms overriding the fieldnorms of a few documents, see
+    /// [`FieldNormOverlay`](crate::fieldnorm::FieldNormOverlay).
+    FieldNormOverlay,
 }
 
 impl SegmentComponent {
     /// Iterates through the components.
     pub fn iterator() -> slice::Iter<'static, SegmentComponent> {
-        static SEGMENT_COMPONENTS: [SegmentComponent; 8] = [
+        static SEGMENT_COMPONENTS: [SegmentComponent; 10] = [
             SegmentComponent::Postings,
             SegmentComponent::Positions,
             SegmentComponent::FastFields,
@@ -41,7 +50,9 @@ impl SegmentComponent {
             SegmentComponent::Terms,
             SegmentComponent::Store,
             SegmentComponent::TempStore,
+            SegmentComponent::Source,
             SegmentComponent::Delete,
+            SegmentComponent::FieldNormOverlay,
         ];
         SEGMENT_COMPONENTS.iter()
     }
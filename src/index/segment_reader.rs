@@ -1,22 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::BitOrAssign;
 use std::sync::{Arc, RwLock};
 use std::{fmt, io};
 
+use common::json_path_writer::JSON_PATH_SEGMENT_SEP_STR;
+use common::{BitSet, HasLen, OwnedBytes};
 use fnv::FnvHashMap;
 use itertools::Itertools;
 
 use crate::directory::{CompositeFile, FileSlice};
 use crate::error::DataCorruption;
 use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders};
-use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
-use crate::index::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
+use crate::fieldnorm::{FieldNormOverlay, FieldNormReader, FieldNormReaders};
+use crate::index::{
+    InvertedIndexReader, Segment, SegmentComponent, SegmentId, SegmentUserMetadata,
+};
+use crate::indexer::segment_serializer::SOURCE_PREFIX;
 use crate::json_utils::json_path_sep_to_dot;
-use crate::schema::{Field, IndexRecordOption, Schema, Type};
+use crate::positions::{read_positions_block_len, POSITIONS_HEADER_IDX};
+use crate::postings::{read_postings_skip_interval, POSTINGS_HEADER_IDX};
+use crate::schema::document::DocumentDeserialize;
+use crate::schema::{Field, IndexRecordOption, Schema, TantivyDocument, Type};
 use crate::space_usage::SegmentSpaceUsage;
-use crate::store::StoreReader;
-use crate::termdict::TermDictionary;
-use crate::{DocId, Opstamp};
+use crate::store::{ColumnarStoredFields, StoreReader};
+use crate::termdict::{TermDictionary, TermDictionaryAccess};
+use crate::{DocId, DocSet, Opstamp, TERMINATED};
 
 /// Entry point to access all of the datastructures of the `Segment`
 ///
@@ -34,6 +42,7 @@ pub struct SegmentReader {
 
     segment_id: SegmentId,
     delete_opstamp: Option<Opstamp>,
+    user_metadata: Arc<SegmentUserMetadata>,
 
     max_doc: DocId,
     num_docs: DocId,
@@ -45,8 +54,77 @@ pub struct SegmentReader {
     fieldnorm_readers: FieldNormReaders,
 
     store_file: FileSlice,
+    // Set with `IndexSettings::docstore_skip_fast_fields`.
+    columnar_stored_fields: Option<Arc<ColumnarStoredFields>>,
+    source_file: Option<FileSlice>,
+    source_reader: Option<Arc<StoreReader>>,
+    fieldnorm_overlay_file: Option<FileSlice>,
     alive_bitset_opt: Option<AliveBitSet>,
     schema: Schema,
+
+    // The data of the components of the segment, except the delete file.
+    component_files: Vec<FileSlice>,
+}
+
+/// Stable 128-bit hasher, used to compute content fingerprints.
+///
+/// It follows FNV-1a, but mixes the data 8 bytes at a time rather than byte by byte.
+/// Unlike the hashers of the standard library, its output is guaranteed to be stable.
+pub(crate) struct Fingerprinter(u128);
+
+impl Fingerprinter {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013B;
+
+    /// Number of bytes of a file read at once by [`Fingerprinter::write_file`].
+    const CHUNK_LEN: usize = 1 << 20;
+
+    pub fn new() -> Fingerprinter {
+        Fingerprinter(Self::OFFSET_BASIS)
+    }
+
+    fn write_word(&mut self, word: u64) {
+        self.0 ^= word as u128;
+        self.0 = self.0.wrapping_mul(Self::PRIME);
+    }
+
+    /// Hashes `chunk`. Every chunk but the last one of a byte string must have a length
+    /// multiple of 8, as the last word of a chunk is padded with zeros.
+    fn write_chunk(&mut self, chunk: &[u8]) {
+        let mut words = chunk.chunks_exact(8);
+        for word in &mut words {
+            self.write_word(u64::from_le_bytes(word.try_into().unwrap()));
+        }
+        let remainder = words.remainder();
+        if !remainder.is_empty() {
+            let mut last_word = [0u8; 8];
+            last_word[..remainder.len()].copy_from_slice(remainder);
+            self.write_word(u64::from_le_bytes(last_word));
+        }
+    }
+
+    /// Hashes `bytes`, prefixed by their length so that consecutive byte strings
+    /// cannot be confused with one another.
+    pub fn write(&mut self, bytes: &[u8]) {
+        self.write_word(bytes.len() as u64);
+        self.write_chunk(bytes);
+    }
+
+    /// Hashes the content of `file` like [`Fingerprinter::write`], reading it chunk by chunk.
+    pub fn write_file(&mut self, file: &FileSlice) -> io::Result<()> {
+        self.write_word(file.len() as u64);
+        let mut start = 0;
+        while start < file.len() {
+            let end = (start + Self::CHUNK_LEN).min(file.len());
+            self.write_chunk(file.read_bytes_slice(start..end)?.as_slice());
+            start = end;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&self) -> u128 {
+        self.0
+    }
 }
 
 impl SegmentReader {
@@ -126,6 +204,16 @@ impl SegmentReader {
         })
     }
 
+    /// Returns the `Field norms` reader of the given field, or `None` if the field
+    /// does not record fieldnorms.
+    ///
+    /// The decoded [fieldnorm](FieldNormReader::fieldnorm) of a document is the
+    /// approximated number of tokens of the field, as interpreted by BM25.
+    /// See [`FieldNormReader`] for details about the quantization.
+    pub fn fieldnorms(&self, field: Field) -> crate::Result<Option<FieldNormReader>> {
+        self.fieldnorm_readers.get_field(field)
+    }
+
     #[doc(hidden)]
     pub fn fieldnorms_readers(&self) -> &FieldNormReaders {
         &self.fieldnorm_readers
@@ -136,7 +224,67 @@ impl SegmentReader {
     /// `cache_num_blocks` sets the number of decompressed blocks to be cached in an LRU.
     /// The size of blocks is configurable, this should be reflexted in the
     pub fn get_store_reader(&self, cache_num_blocks: usize) -> io::Result<StoreReader> {
-        StoreReader::open(self.store_file.clone(), cache_num_blocks)
+        let store_reader = StoreReader::open(self.store_file.clone(), cache_num_blocks)?;
+        if let Some(columnar_stored_fields) = &self.columnar_stored_fields {
+            return Ok(store_reader.with_columnar_fields(columnar_stored_fields.clone()));
+        }
+        Ok(store_reader)
+    }
+
+    /// Returns the stored fields of a document, whether it is deleted or not.
+    ///
+    /// The returned document may have been deleted: check [`SegmentReader::is_deleted`]
+    /// to know if it is. The stored fields of a deleted document remain readable until
+    /// its segment gets merged, after which they are gone for good. This makes it possible
+    /// to audit deleted documents, but it should not be relied upon for anything else.
+    ///
+    /// No block cache is used. Prefer [`SegmentReader::get_store_reader`] to read
+    /// many documents.
+    pub fn doc_including_deleted<D: DocumentDeserialize>(&self, doc_id: DocId) -> crate::Result<D> {
+        if doc_id >= self.max_doc {
+            return Err(crate::TantivyError::InvalidArgument(format!(
+                "Doc id {doc_id} is out of range, the segment has {} documents",
+                self.max_doc
+            )));
+        }
+        self.get_store_reader(0)?.get(doc_id)
+    }
+
+    /// Accessor to the segment's source `StoreReader`, if the index keeps the source of
+    /// the documents.
+    ///
+    /// See [`IndexSettings::store_source`](crate::IndexSettings::store_source).
+    pub fn get_source_reader(&self, cache_num_blocks: usize) -> io::Result<Option<StoreReader>> {
+        self.source_file
+            .clone()
+            .map(|source_file| StoreReader::open(source_file, cache_num_blocks))
+            .transpose()
+    }
+
+    /// Returns the raw source of a document, whether it is deleted or not.
+    ///
+    /// Returns `None` if the index does not keep the source of the documents, or if the
+    /// document was added without a source. A document added with an empty source returns an
+    /// empty source.
+    pub fn source(&self, doc_id: DocId) -> crate::Result<Option<OwnedBytes>> {
+        if doc_id >= self.max_doc {
+            return Err(crate::TantivyError::InvalidArgument(format!(
+                "Doc id {doc_id} is out of range, the segment has {} documents",
+                self.max_doc
+            )));
+        }
+        let Some(source_reader) = self.source_reader.as_ref() else {
+            return Ok(None);
+        };
+        let source_bytes = source_reader.get_document_bytes(doc_id)?;
+        match source_bytes.as_slice().first() {
+            None => Ok(None),
+            Some(&SOURCE_PREFIX) => Ok(Some(source_bytes.slice(1..source_bytes.len()))),
+            Some(_) => Err(DataCorruption::comment_only(format!(
+                "The source of doc {doc_id} has an invalid prefix"
+            ))
+            .into()),
+        }
     }
 
     /// Open a new segment for reading.
@@ -159,9 +307,10 @@ impl SegmentReader {
         let postings_file = segment.open_read(SegmentComponent::Postings)?;
         let postings_composite = CompositeFile::open(&postings_file)?;
 
+        let positions_file = segment.open_read(SegmentComponent::Positions).ok();
         let positions_composite = {
-            if let Ok(positions_file) = segment.open_read(SegmentComponent::Positions) {
-                CompositeFile::open(&positions_file)?
+            if let Some(positions_file) = &positions_file {
+                CompositeFile::open(positions_file)?
             } else {
                 CompositeFile::empty()
             }
@@ -170,9 +319,53 @@ impl SegmentReader {
         let schema = segment.schema();
 
         let fast_fields_data = segment.open_read(SegmentComponent::FastFields)?;
-        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?;
+        let fast_fields_readers = FastFieldReaders::open_with_codecs(
+            fast_fields_data.clone(),
+            schema.clone(),
+            segment.index().fast_field_codecs().clone(),
+        )?;
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
-        let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
+        let mut fieldnorm_readers = FieldNormReaders::open(fieldnorm_data.clone())?;
+        let fieldnorm_overlay_file = if segment.meta().fieldnorm_overlay_opstamp().is_some() {
+            let fieldnorm_overlay_file = segment.open_read(SegmentComponent::FieldNormOverlay)?;
+            let fieldnorm_overlay = FieldNormOverlay::open(
+                fieldnorm_overlay_file.read_bytes()?,
+                segment.meta().max_doc(),
+            )?;
+            fieldnorm_readers = fieldnorm_readers.with_overlay(fieldnorm_overlay);
+            Some(fieldnorm_overlay_file)
+        } else {
+            None
+        };
+        let columnar_stored_fields = if segment.index().settings().docstore_skip_fast_fields {
+            Some(Arc::new(ColumnarStoredFields::open(
+                &schema,
+                &fast_fields_readers,
+            )?))
+        } else {
+            None
+        };
+        let source_file = if segment.index().settings().store_source {
+            Some(segment.open_read(SegmentComponent::Source)?)
+        } else {
+            None
+        };
+        let source_reader = source_file
+            .clone()
+            .map(|source_file| StoreReader::open(source_file, 0))
+            .transpose()?
+            .map(Arc::new);
+
+        let mut component_files = vec![
+            termdict_file,
+            store_file.clone(),
+            postings_file,
+            positions_file.unwrap_or_else(FileSlice::empty),
+            fast_fields_data,
+            fieldnorm_data,
+        ];
+        component_files.extend(source_file.clone());
+        component_files.extend(fieldnorm_overlay_file.clone());
 
         let original_bitset = if segment.meta().has_deletes() {
             let alive_doc_file_slice = segment.open_read(SegmentComponent::Delete)?;
@@ -200,10 +393,16 @@ impl SegmentReader {
             fieldnorm_readers,
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
+            user_metadata: Arc::new(segment.meta().user_metadata().clone()),
             store_file,
+            columnar_stored_fields,
+            source_file,
+            source_reader,
+            fieldnorm_overlay_file,
             alive_bitset_opt,
             positions_composite,
             schema,
+            component_files,
         })
     }
 
@@ -220,6 +419,23 @@ impl SegmentReader {
     /// Similarly, if the field is marked as indexed but no term has been indexed for the given
     /// index, an empty `InvertedIndexReader` is returned (but no warning is logged).
     pub fn inverted_index(&self, field: Field) -> crate::Result<Arc<InvertedIndexReader>> {
+        self.inverted_index_with_access(field, TermDictionaryAccess::Mmap)
+    }
+
+    /// Same as [`SegmentReader::inverted_index`], opening the term dictionary with the given
+    /// access.
+    ///
+    /// The inverted index of a field is opened once per segment reader, and then cached: if it is
+    /// already open, the cached `InvertedIndexReader` is returned and `term_dictionary_access` is
+    /// ignored. This is how
+    /// [`IndexReaderBuilder::term_dictionary_access`](crate::IndexReaderBuilder::term_dictionary_access)
+    /// applies: the segment readers open the inverted index with the configured access, and the
+    /// later calls to [`SegmentReader::inverted_index`] reuse it. See [`TermDictionaryAccess`].
+    pub fn inverted_index_with_access(
+        &self,
+        field: Field,
+        term_dictionary_access: TermDictionaryAccess,
+    ) -> crate::Result<Arc<InvertedIndexReader>> {
         if let Some(inv_idx_reader) = self
             .inv_idx_reader_cache
             .read()
@@ -268,10 +484,21 @@ impl SegmentReader {
             DataCorruption::comment_only(error_msg)
         })?;
 
+        let positions_block_len = read_positions_block_len(
+            self.positions_composite
+                .open_read_with_idx(field, POSITIONS_HEADER_IDX),
+        )?;
+        let postings_skip_interval = read_postings_skip_interval(
+            self.postings_composite
+                .open_read_with_idx(field, POSTINGS_HEADER_IDX),
+        )?;
+
         let inv_idx_reader = Arc::new(InvertedIndexReader::new(
-            TermDictionary::open(termdict_file)?,
+            TermDictionary::open_with_access(termdict_file, term_dictionary_access)?,
             postings_file,
             positions_file,
+            positions_block_len,
+            postings_skip_interval,
             record_option,
         )?);
 
@@ -382,6 +609,134 @@ impl SegmentReader {
         Ok(merged)
     }
 
+    /// Returns the set of schema fields that actually hold data in this segment.
+    ///
+    /// A field is considered populated if at least one of the following is true:
+    /// - it has at least one term in the inverted index,
+    /// - it has at least one non-empty column in the fast field columnar storage,
+    /// - at least one alive document has a stored value for it.
+    ///
+    /// This is useful to spot fields of the schema that are never written to.
+    ///
+    /// Notice: Detecting stored values requires scanning the doc store, which is **expensive**.
+    /// The scan stops as soon as all stored fields of the schema have been found.
+    pub fn populated_fields(&self) -> crate::Result<HashSet<Field>> {
+        let mut populated_fields = HashSet::new();
+        for (field, field_entry) in self.schema.fields() {
+            if !field_entry.is_indexed() || self.postings_composite.open_read(field).is_none() {
+                continue;
+            }
+            if self.inverted_index(field)?.terms().num_terms() > 0 {
+                populated_fields.insert(field);
+            }
+        }
+        for (column_name, column_handle) in self.fast_fields().columnar().iter_columns()? {
+            // Fast fields declared in the schema get a column even if no value was ever
+            // recorded, so we check that the column is not empty.
+            if column_handle.open()?.num_values() == 0 {
+                continue;
+            }
+            let root_field_name = column_name
+                .split(JSON_PATH_SEGMENT_SEP_STR)
+                .next()
+                .unwrap_or_default();
+            if let Ok(field) = self.schema.get_field(root_field_name) {
+                populated_fields.insert(field);
+            }
+        }
+        let mut stored_fields_to_find: HashSet<Field> = self
+            .schema
+            .fields()
+            .filter(|(field, field_entry)| {
+                field_entry.is_stored() && !populated_fields.contains(field)
+            })
+            .map(|(field, _)| field)
+            .collect();
+        if !stored_fields_to_find.is_empty() {
+            let store_reader = self.get_store_reader(1)?;
+            for doc_res in store_reader.iter::<TantivyDocument>(self.alive_bitset()) {
+                let doc = doc_res?;
+                for (field, _) in doc.field_values() {
+                    if stored_fields_to_find.remove(&field) {
+                        populated_fields.insert(field);
+                    }
+                }
+                if stored_fields_to_find.is_empty() {
+                    break;
+                }
+            }
+        }
+        Ok(populated_fields)
+    }
+
+    /// Returns, for each fast or indexed field, the number of alive documents having at least
+    /// one value for it.
+    ///
+    /// The values are detected with the fast field columns if the field is fast, with the
+    /// field norms if the field is indexed with field norms, and by reading the postings of all
+    /// of its terms otherwise. Fields that are neither fast nor indexed are not in the map.
+    pub fn num_docs_with_value_per_field(&self) -> crate::Result<HashMap<Field, u32>> {
+        let mut docs_with_value: HashMap<Field, BitSet> = HashMap::new();
+        let mut new_bitset = || BitSet::with_max_value(self.max_doc);
+        for (column_name, column_handle) in self.fast_fields().columnar().iter_columns()? {
+            let root_field_name = column_name
+                .split(JSON_PATH_SEGMENT_SEP_STR)
+                .next()
+                .unwrap_or_default();
+            let Ok(field) = self.schema.get_field(root_field_name) else {
+                continue;
+            };
+            let column = column_handle.open()?;
+            let bitset = docs_with_value.entry(field).or_insert_with(&mut new_bitset);
+            for doc in 0..self.max_doc {
+                if column.column_index().has_value(doc) {
+                    bitset.insert(doc);
+                }
+            }
+        }
+        for (field, field_entry) in self.schema.fields() {
+            if field_entry.is_fast() {
+                docs_with_value.entry(field).or_insert_with(&mut new_bitset);
+                continue;
+            }
+            if !field_entry.is_indexed() {
+                continue;
+            }
+            let mut bitset = new_bitset();
+            if let Some(fieldnorm_reader) = self.fieldnorms(field)? {
+                for doc in 0..self.max_doc {
+                    if fieldnorm_reader.fieldnorm_id(doc) != 0 {
+                        bitset.insert(doc);
+                    }
+                }
+            } else if self.postings_composite.open_read(field).is_some() {
+                let inverted_index = self.inverted_index(field)?;
+                let mut term_stream = inverted_index.terms().stream()?;
+                while term_stream.try_advance()? {
+                    let mut postings = inverted_index.read_postings_from_terminfo(
+                        term_stream.value(),
+                        IndexRecordOption::Basic,
+                    )?;
+                    while postings.doc() != TERMINATED {
+                        bitset.insert(postings.doc());
+                        postings.advance();
+                    }
+                }
+            }
+            docs_with_value.insert(field, bitset);
+        }
+        Ok(docs_with_value
+            .into_iter()
+            .map(|(field, bitset)| {
+                let num_docs = self
+                    .doc_ids_alive()
+                    .filter(|&doc| bitset.contains(doc))
+                    .count() as u32;
+                (field, num_docs)
+            })
+            .collect())
+    }
+
     /// Returns the segment id
     pub fn segment_id(&self) -> SegmentId {
         self.segment_id
@@ -392,11 +747,51 @@ impl SegmentReader {
         self.delete_opstamp
     }
 
+    /// Returns the metadata attached to the segment by the user, see
+    /// [`IndexWriter::set_segment_user_metadata`](crate::IndexWriter::set_segment_user_metadata).
+    pub fn user_metadata(&self) -> &SegmentUserMetadata {
+        &self.user_metadata
+    }
+
     /// Returns the bitset representing the alive `DocId`s.
     pub fn alive_bitset(&self) -> Option<&AliveBitSet> {
         self.alive_bitset_opt.as_ref()
     }
 
+    /// Returns the serialized bitset of the alive `DocId`s, or `None` if the segment has no
+    /// deletes.
+    ///
+    /// The bytes have the format of the delete file of the segment, and can be installed on a
+    /// copy of the segment with
+    /// [`IndexWriter::apply_delete_bitset`](crate::IndexWriter::apply_delete_bitset).
+    pub fn delete_bitset_bytes(&self) -> Option<OwnedBytes> {
+        self.alive_bitset_opt.as_ref().map(AliveBitSet::to_bytes)
+    }
+
+    /// Returns a fingerprint of the content of the segment.
+    ///
+    /// The fingerprint is computed from the bytes of the components of the segment and
+    /// of its alive bitset, so that deleting documents changes it. The segment id, the
+    /// delete opstamp and the file footers, which hold the version of tantivy, are left out.
+    ///
+    /// Two segments built by a single indexing thread from the same documents, with the same
+    /// schema and index settings, have the same fingerprint. The layout of the segments
+    /// depends on how the documents are split between indexing threads and on the memory
+    /// budget, so see [`Index::fingerprint`](crate::Index::fingerprint) before comparing
+    /// whole indexes.
+    ///
+    /// This reads all of the data of the segment, one chunk at a time.
+    pub fn content_fingerprint(&self) -> io::Result<u128> {
+        let mut fingerprinter = Fingerprinter::new();
+        for component_file in &self.component_files {
+            fingerprinter.write_file(component_file)?;
+        }
+        if let Some(alive_bitset) = &self.alive_bitset_opt {
+            fingerprinter.write(alive_bitset.to_bytes().as_slice());
+        }
+        Ok(fingerprinter.finish())
+    }
+
     /// Returns true if the `doc` is marked
     /// as deleted.
     pub fn is_deleted(&self, doc: DocId) -> bool {
@@ -418,12 +813,21 @@ impl SegmentReader {
     pub fn space_usage(&self) -> io::Result<SegmentSpaceUsage> {
         Ok(SegmentSpaceUsage::new(
             self.num_docs(),
+            self.num_deleted_docs(),
             self.termdict_composite.space_usage(),
             self.postings_composite.space_usage(),
             self.positions_composite.space_usage(),
             self.fast_fields_readers.space_usage(self.schema())?,
             self.fieldnorm_readers.space_usage(),
             self.get_store_reader(0)?.space_usage(),
+            self.source_file
+                .as_ref()
+                .map(FileSlice::num_bytes)
+                .unwrap_or_default(),
+            self.fieldnorm_overlay_file
+                .as_ref()
+                .map(FileSlice::num_bytes)
+                .unwrap_or_default(),
             self.alive_bitset_opt
                 .as_ref()
                 .map(AliveBitSet::space_usage)
@@ -516,7 +920,7 @@ impl fmt::Debug for SegmentReader {
 mod test {
     use super::*;
     use crate::index::Index;
-    use crate::schema::{SchemaBuilder, Term, STORED, TEXT};
+    use crate::schema::{SchemaBuilder, Term, Value, FAST, STORED, TEXT};
     use crate::IndexWriter;
 
     #[test]
@@ -662,6 +1066,70 @@ mod test {
         assert_eq!(4, searcher.segment_reader(0).max_doc());
         Ok(())
     }
+
+    #[test]
+    fn test_doc_including_deleted() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_text_field("name", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(name => "tantivy"))?;
+            index_writer.add_document(doc!(name => "horse"))?;
+            index_writer.commit()?;
+            index_writer.delete_term(Term::from_field_text(name, "horse"));
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert!(segment_reader.is_deleted(1));
+        let deleted_doc: TantivyDocument = segment_reader.doc_including_deleted(1)?;
+        assert_eq!(
+            deleted_doc.get_first(name).and_then(|value| value.as_str()),
+            Some("horse")
+        );
+        let alive_doc: TantivyDocument = segment_reader.doc_including_deleted(0)?;
+        assert_eq!(
+            alive_doc.get_first(name).and_then(|value| value.as_str()),
+            Some("tantivy")
+        );
+        assert!(matches!(
+            segment_reader.doc_including_deleted::<TantivyDocument>(2),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_populated_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let note = schema_builder.add_text_field("note", STORED);
+        let price = schema_builder.add_u64_field("price", FAST);
+        let attributes = schema_builder.add_json_field("attributes", FAST);
+        let unused = schema_builder.add_text_field("unused", TEXT | STORED | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(title => "tantivy", price => 3u64))?;
+            index_writer.add_document(doc!(
+                note => "stored only",
+                attributes => json!({"color": "red"}),
+            ))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let populated_fields = searcher.segment_reader(0).populated_fields()?;
+        assert_eq!(
+            populated_fields,
+            HashSet::from_iter([title, note, price, attributes])
+        );
+        assert!(!populated_fields.contains(&unused));
+        assert_eq!(searcher.populated_fields()?, populated_fields);
+        Ok(())
+    }
+
     #[test]
     fn test_alive_docs_iterator() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -693,4 +1161,20 @@ mod test {
         assert_eq!(vec![0u32, 2u32], docs);
         Ok(())
     }
+
+    #[test]
+    fn test_fingerprinter_write_file_reads_by_chunks() -> io::Result<()> {
+        let data: Vec<u8> = (0..Fingerprinter::CHUNK_LEN * 2 + 3)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let mut fingerprinter = Fingerprinter::new();
+        fingerprinter.write(&data);
+        let mut file_fingerprinter = Fingerprinter::new();
+        file_fingerprinter.write_file(&FileSlice::from(data.clone()))?;
+        assert_eq!(file_fingerprinter.finish(), fingerprinter.finish());
+        let mut other_fingerprinter = Fingerprinter::new();
+        other_fingerprinter.write(&data[..data.len() - 1]);
+        assert_ne!(other_fingerprinter.finish(), fingerprinter.finish());
+        Ok(())
+    }
 }
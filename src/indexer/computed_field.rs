@@ -0,0 +1,610 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, FieldType, Schema, TantivyDocument};
+use crate::TantivyError;
+
+// Maximum nesting depth of an expression, counting the parentheses, the function calls and
+// the operators. Evaluating an expression is recursive.
+const MAX_EXPRESSION_DEPTH: usize = 64;
+
+/// A fast field whose value is computed at indexing time from an expression over
+/// other numeric fields of the document.
+///
+/// This is typically used to compute static ranking signals once, rather than at
+/// every query. The target field must be a `f64` fast field, which is neither indexed nor
+/// stored.
+///
+/// The computed fields set in the
+/// [`IndexWriterOptions`](crate::indexer::IndexWriterOptions) of an [`IndexWriter`] are
+/// computed for every document added by the writer. The values of the target field in the
+/// added documents are replaced by the computed value.
+///
+/// The expression supports:
+/// - numeric literals,
+/// - the names of `u64`, `i64`, `f64` or `bool` fields, evaluated to the first value of the field
+///   in the document,
+/// - the operators `+`, `-`, `*`, `/` and parentheses,
+/// - the functions `log` (natural logarithm), `log10`, `log1p`, `exp`, `sqrt`, `abs`, `min`, `max`
+///   and `pow`.
+///
+/// If one of the fields of the expression has no value in a document, or if the result is
+/// not finite, no value is computed for this document.
+///
+/// ```rust
+/// use tantivy::indexer::{ComputedField, IndexWriterOptions};
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, IndexWriter, TantivyDocument};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let views = schema_builder.add_u64_field("views", FAST);
+/// let rating = schema_builder.add_f64_field("rating", FAST);
+/// let popularity = schema_builder.add_f64_field("popularity", FAST);
+/// let schema = schema_builder.build();
+///
+/// let computed_field =
+///     ComputedField::new(&schema, popularity, "0.3 * log(views) + 0.7 * rating")?;
+/// let index = Index::create_in_ram(schema);
+/// let options = IndexWriterOptions::builder()
+///     .memory_budget_per_thread(15_000_000)
+///     .computed_fields(vec![computed_field])
+///     .build();
+/// let mut index_writer: IndexWriter = index.writer_with_options::<TantivyDocument>(options)?;
+/// index_writer.add_document(doc!(views => 1_000u64, rating => 4.5))?;
+/// index_writer.commit()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`IndexWriter`]: crate::IndexWriter
+#[derive(Clone, Debug)]
+pub struct ComputedField {
+    target_field: Field,
+    expression: Expr,
+}
+
+impl ComputedField {
+    /// Parses `expression`, and creates a `ComputedField` writing its result to
+    /// `target_field`.
+    ///
+    /// Returns an error if the expression is invalid or too deeply nested, if it refers to a
+    /// non-numeric or unknown field, or if the target field is not a `f64` fast field, or is
+    /// indexed or stored.
+    pub fn new(
+        schema: &Schema,
+        target_field: Field,
+        expression: &str,
+    ) -> crate::Result<ComputedField> {
+        let target_entry = schema.get_field_entry(target_field);
+        if !matches!(target_entry.field_type(), FieldType::F64(options) if options.is_fast())
+            || target_entry.is_indexed()
+            || target_entry.is_stored()
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "The computed field {} must be a f64 fast field, neither indexed nor stored",
+                target_entry.name()
+            )));
+        }
+        let expression = ExprParser::new(schema, expression).parse()?;
+        Ok(ComputedField {
+            target_field,
+            expression,
+        })
+    }
+
+    /// Returns the field the computed value is written to.
+    pub fn target_field(&self) -> Field {
+        self.target_field
+    }
+
+    /// Evaluates the expression over the values of `doc`.
+    pub fn compute<D: Document>(&self, doc: &D) -> Option<f64> {
+        let value = self
+            .expression
+            .eval(&|field| first_numeric_value(doc, field))?;
+        value.is_finite().then_some(value)
+    }
+
+    /// Evaluates the expression over the values of `doc`, and replaces the values of the
+    /// target field of `doc` by the result.
+    ///
+    /// The target field of `doc` has no value if no value is computed. This is only needed for
+    /// the documents that are not added by an [`IndexWriter`] computing this field.
+    ///
+    /// [`IndexWriter`]: crate::IndexWriter
+    pub fn apply(&self, doc: &mut TantivyDocument) {
+        let computed_value = self.compute(doc);
+        if doc.get_first(self.target_field).is_some() {
+            let mut replaced_doc = TantivyDocument::new();
+            for (field, value) in doc.field_values() {
+                if field != self.target_field {
+                    replaced_doc.add_field_value(field, value);
+                }
+            }
+            *doc = replaced_doc;
+        }
+        if let Some(value) = computed_value {
+            doc.add_f64(self.target_field, value);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Log,
+    Log10,
+    Log1p,
+    Exp,
+    Sqrt,
+    Abs,
+    Min,
+    Max,
+    Pow,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        let function = match name {
+            "log" => Function::Log,
+            "log10" => Function::Log10,
+            "log1p" => Function::Log1p,
+            "exp" => Function::Exp,
+            "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "pow" => Function::Pow,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Min | Function::Max | Function::Pow => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match self {
+            Function::Log => args[0].ln(),
+            Function::Log10 => args[0].log10(),
+            Function::Log1p => args[0].ln_1p(),
+            Function::Exp => args[0].exp(),
+            Function::Sqrt => args[0].sqrt(),
+            Function::Abs => args[0].abs(),
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+            Function::Pow => args[0].powf(args[1]),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Literal(f64),
+    Field(Field),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+fn first_numeric_value<D: Document>(doc: &D, field: Field) -> Option<f64> {
+    let (_, value) = doc
+        .iter_fields_and_values()
+        .find(|(doc_field, _)| *doc_field == field)?;
+    value
+        .as_f64()
+        .or_else(|| value.as_u64().map(|val| val as f64))
+        .or_else(|| value.as_i64().map(|val| val as f64))
+        .or_else(|| value.as_bool().map(|val| if val { 1.0 } else { 0.0 }))
+}
+
+impl Expr {
+    fn eval(&self, field_value: &dyn Fn(Field) -> Option<f64>) -> Option<f64> {
+        match self {
+            Expr::Literal(val) => Some(*val),
+            Expr::Field(field) => field_value(*field),
+            Expr::Neg(expr) => expr.eval(field_value).map(|val| -val),
+            Expr::Binary(op, left, right) => {
+                let left = left.eval(field_value)?;
+                let right = right.eval(field_value)?;
+                Some(match op {
+                    BinaryOp::Add => left + right,
+                    BinaryOp::Sub => left - right,
+                    BinaryOp::Mul => left * right,
+                    BinaryOp::Div => left / right,
+                })
+            }
+            Expr::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(field_value))
+                    .collect::<Option<Vec<f64>>>()?;
+                Some(function.apply(&args))
+            }
+        }
+    }
+}
+
+/// Recursive descent parser for the grammar:
+///
+/// ```text
+/// expr   := term (('+' | '-') term)*
+/// term   := factor (('*' | '/') factor)*
+/// factor := '-' factor | number | ident | ident '(' expr (',' expr)* ')' | '(' expr ')'
+/// ```
+struct ExprParser<'a> {
+    schema: &'a Schema,
+    expression: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    depth: usize,
+}
+
+impl<'a> ExprParser<'a> {
+    fn new(schema: &'a Schema, expression: &'a str) -> ExprParser<'a> {
+        ExprParser {
+            schema,
+            expression,
+            chars: expression.char_indices().peekable(),
+            depth: 0,
+        }
+    }
+
+    fn enter(&mut self) -> crate::Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            return Err(self.error(&format!(
+                "the expression is nested more than {MAX_EXPRESSION_DEPTH} levels deep"
+            )));
+        }
+        Ok(())
+    }
+
+    fn error(&self, msg: &str) -> TantivyError {
+        TantivyError::InvalidArgument(format!("Invalid expression `{}`: {msg}", self.expression))
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespaces();
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn expect(&mut self, expected: char) -> crate::Result<()> {
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected `{expected}`")));
+        }
+        self.chars.next();
+        Ok(())
+    }
+
+    fn parse(mut self) -> crate::Result<Expr> {
+        let expr = self.parse_expr()?;
+        if let Some(c) = self.peek() {
+            return Err(self.error(&format!("unexpected `{c}`")));
+        }
+        Ok(expr)
+    }
+
+    fn parse_expr(&mut self) -> crate::Result<Expr> {
+        let depth = self.depth;
+        let mut expr = self.parse_term()?;
+        loop {
+            let op = match self.peek() {
+                Some('+') => BinaryOp::Add,
+                Some('-') => BinaryOp::Sub,
+                _ => break,
+            };
+            self.chars.next();
+            // Each operator nests the expression on its left one level deeper.
+            self.enter()?;
+            let right = self.parse_term()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn parse_term(&mut self) -> crate::Result<Expr> {
+        let depth = self.depth;
+        let mut expr = self.parse_factor()?;
+        loop {
+            let op = match self.peek() {
+                Some('*') => BinaryOp::Mul,
+                Some('/') => BinaryOp::Div,
+                _ => break,
+            };
+            self.chars.next();
+            self.enter()?;
+            let right = self.parse_factor()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self
+            .chars
+            .peek()
+            .map(|(offset, _)| *offset)
+            .unwrap_or(self.expression.len());
+        let mut end = start;
+        while let Some((offset, c)) = self.chars.next_if(|(_, c)| predicate(*c)) {
+            end = offset + c.len_utf8();
+        }
+        &self.expression[start..end]
+    }
+
+    fn parse_factor(&mut self) -> crate::Result<Expr> {
+        self.enter()?;
+        let factor = self.parse_factor_inner()?;
+        self.depth -= 1;
+        Ok(factor)
+    }
+
+    fn parse_factor_inner(&mut self) -> crate::Result<Expr> {
+        match self.peek() {
+            Some('-') => {
+                self.chars.next();
+                Ok(Expr::Neg(Box::new(self.parse_factor()?)))
+            }
+            Some('(') => {
+                self.chars.next();
+                let expr = self.parse_expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let literal = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                let val: f64 = literal
+                    .parse()
+                    .map_err(|_| self.error(&format!("invalid number `{literal}`")))?;
+                Ok(Expr::Literal(val))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let ident = self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '.');
+                if self.peek() == Some('(') {
+                    self.parse_call(ident)
+                } else {
+                    self.parse_field(ident)
+                }
+            }
+            Some(c) => Err(self.error(&format!("unexpected `{c}`"))),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn parse_call(&mut self, name: &str) -> crate::Result<Expr> {
+        let function = Function::from_name(name)
+            .ok_or_else(|| self.error(&format!("unknown function `{name}`")))?;
+        self.expect('(')?;
+        let mut args = vec![self.parse_expr()?];
+        while self.peek() == Some(',') {
+            self.chars.next();
+            args.push(self.parse_expr()?);
+        }
+        self.expect(')')?;
+        if args.len() != function.arity() {
+            return Err(self.error(&format!(
+                "`{name}` expects {} argument(s), got {}",
+                function.arity(),
+                args.len()
+            )));
+        }
+        Ok(Expr::Call(function, args))
+    }
+
+    fn parse_field(&mut self, field_name: &str) -> crate::Result<Expr> {
+        let field = self.schema.get_field(field_name)?;
+        match self.schema.get_field_entry(field).field_type() {
+            FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) | FieldType::Bool(_) => {
+                Ok(Expr::Field(field))
+            }
+            _ => Err(self.error(&format!("field `{field_name}` is not numeric"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComputedField, MAX_EXPRESSION_DEPTH};
+    use crate::collector::TopDocs;
+    use crate::indexer::IndexWriterOptions;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, Value, FAST, INDEXED, STORED, TEXT};
+    use crate::{Index, IndexWriter, Order, TantivyDocument, TantivyError};
+
+    #[test]
+    fn test_computed_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let views = schema_builder.add_u64_field("views", FAST | STORED);
+        let rating = schema_builder.add_f64_field("rating", FAST | STORED);
+        let boost = schema_builder.add_i64_field("boost", INDEXED);
+        let popularity = schema_builder.add_f64_field("popularity", FAST);
+        let schema = schema_builder.build();
+        let computed_field = ComputedField::new(
+            &schema,
+            popularity,
+            "0.3 * log(views) + 0.7 * rating - max(boost, 0) / -(2 + 2)",
+        )?;
+        let expected = |views: u64, rating: f64, boost: i64| {
+            0.3 * (views as f64).ln() + 0.7 * rating + (boost.max(0) as f64) / 4.0
+        };
+
+        let index = Index::create_in_ram(schema);
+        let options = IndexWriterOptions::builder()
+            .memory_budget_per_thread(15_000_000)
+            .computed_fields(vec![computed_field])
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        let docs: Vec<TantivyDocument> = vec![
+            doc!(views => 1_000u64, rating => 4.5, boost => 2i64),
+            // The values of the target field are replaced.
+            doc!(views => 10u64, rating => 5.0, boost => -3i64, popularity => 100.0, popularity => 200.0),
+            doc!(views => 100_000u64, rating => 1.0, boost => 0i64),
+            // No value is computed if a field is missing.
+            doc!(views => 100_000u64, boost => 0i64, popularity => 100.0),
+            // No value is computed if the result is not finite.
+            doc!(views => 0u64, rating => 1.0, boost => 0i64),
+        ];
+        for doc in docs {
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let column = searcher.segment_reader(0).fast_fields().f64("popularity")?;
+        assert_eq!(column.first(0), Some(expected(1_000, 4.5, 2)));
+        assert_eq!(
+            column.values_for_doc(1).collect::<Vec<f64>>(),
+            vec![expected(10, 5.0, -3)]
+        );
+        assert_eq!(column.first(2), Some(expected(100_000, 1.0, 0)));
+        assert_eq!(column.first(3), None);
+        assert_eq!(column.first(4), None);
+
+        let top_docs = searcher.search(
+            &AllQuery,
+            &TopDocs::with_limit(3).order_by_fast_field::<f64>("popularity", Order::Desc),
+        )?;
+        let doc_ids: Vec<u32> = top_docs
+            .into_iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect();
+        assert_eq!(doc_ids, vec![0, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_computed_field_apply_replaces_target_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let views = schema_builder.add_u64_field("views", FAST | STORED);
+        let popularity = schema_builder.add_f64_field("popularity", FAST);
+        let schema = schema_builder.build();
+        let computed_field = ComputedField::new(&schema, popularity, "views * 2")?;
+
+        let mut doc: TantivyDocument = doc!(popularity => 1.0, views => 3u64, popularity => 2.0);
+        computed_field.apply(&mut doc);
+        let popularity_values: Vec<f64> = doc
+            .get_all(popularity)
+            .flat_map(|value| value.as_f64())
+            .collect();
+        assert_eq!(popularity_values, vec![6.0]);
+        assert_eq!(
+            doc.get_first(views).and_then(|value| value.as_u64()),
+            Some(3)
+        );
+
+        let mut doc: TantivyDocument = doc!(popularity => 1.0);
+        computed_field.apply(&mut doc);
+        assert!(doc.get_first(popularity).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_computed_field_duplicate_target() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("views", FAST);
+        let popularity = schema_builder.add_f64_field("popularity", FAST);
+        let schema = schema_builder.build();
+        let computed_fields = vec![
+            ComputedField::new(&schema, popularity, "views")?,
+            ComputedField::new(&schema, popularity, "views * 2")?,
+        ];
+        let index = Index::create_in_ram(schema);
+        let options = IndexWriterOptions::builder()
+            .computed_fields(computed_fields)
+            .build();
+        assert!(matches!(
+            index.writer_with_options::<TantivyDocument>(options),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_computed_field_max_depth() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("views", FAST);
+        let score = schema_builder.add_f64_field("score", FAST);
+        let schema = schema_builder.build();
+        let nested = |depth: usize| format!("{}views{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(ComputedField::new(&schema, score, &nested(MAX_EXPRESSION_DEPTH - 1)).is_ok());
+        let too_deep = 10_000;
+        for expression in [
+            nested(too_deep),
+            "-".repeat(too_deep) + "views",
+            "views".to_string() + &"+1".repeat(too_deep),
+            "views".to_string() + &"*2".repeat(too_deep),
+            "log(".repeat(too_deep) + "views" + &")".repeat(too_deep),
+        ] {
+            assert!(matches!(
+                ComputedField::new(&schema, score, &expression),
+                Err(TantivyError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_computed_field_invalid() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("views", FAST);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let not_fast = schema_builder.add_f64_field("not_fast", INDEXED);
+        let stored = schema_builder.add_f64_field("stored", FAST | STORED);
+        let score = schema_builder.add_f64_field("score", FAST);
+        let schema = schema_builder.build();
+        assert!(ComputedField::new(&schema, score, "log(views) * 2").is_ok());
+        for expression in [
+            "",
+            "views +",
+            "(views",
+            "views)",
+            "unknown(views)",
+            "pow(views)",
+            "1..2",
+            "views $ 2",
+            "title",
+        ] {
+            assert!(
+                matches!(
+                    ComputedField::new(&schema, score, expression),
+                    Err(TantivyError::InvalidArgument(_))
+                ),
+                "{expression}"
+            );
+        }
+        assert!(matches!(
+            ComputedField::new(&schema, score, "missing * 2"),
+            Err(TantivyError::FieldNotFound(_))
+        ));
+        assert!(matches!(
+            ComputedField::new(&schema, title, "views"),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            ComputedField::new(&schema, not_fast, "views"),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            ComputedField::new(&schema, stored, "views"),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+}
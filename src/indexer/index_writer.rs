@@ -1,27 +1,35 @@
+use std::collections::HashSet;
+use std::num::NonZeroU64;
 use std::ops::Range;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
 
-use common::BitSet;
+use common::{BitSet, OwnedBytes};
+use itertools::Either;
 use smallvec::smallvec;
 
 use super::operation::{AddOperation, UserOperation};
-use super::segment_updater::SegmentUpdater;
+use super::segment_updater::{MergeTransform, SegmentUpdater};
 use super::{AddBatch, AddBatchReceiver, AddBatchSender, PreparedCommit};
 use crate::directory::{DirectoryLock, GarbageCollectionResult, TerminatingWrite};
 use crate::error::TantivyError;
-use crate::fastfield::write_alive_bitset;
-use crate::index::{Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+use crate::fastfield::{write_alive_bitset, AliveBitSet};
+use crate::fieldnorm::{FieldNormOverlay, FieldNormsWriter};
+use crate::index::{
+    Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader, SegmentUserMetadata,
+    UserMetadataMerger,
+};
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
 use crate::indexer::index_writer_status::IndexWriterStatus;
 use crate::indexer::operation::DeleteOperation;
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
+use crate::indexer::{ComputedField, MergeMode, MergePolicy, SegmentEntry, SegmentWriter};
 use crate::query::{EnableScoring, Query, TermQuery};
 use crate::schema::document::Document;
-use crate::schema::{IndexRecordOption, TantivyDocument, Term};
+use crate::schema::{Field, FieldType, IndexRecordOption, TantivyDocument, Term, Value};
+use crate::space_usage::MergeSpaceDelta;
 use crate::{FutureResult, Opstamp};
 
 // Size of the margin for the `memory_arena`. A segment is closed when the remaining memory
@@ -60,6 +68,11 @@ pub struct IndexWriterOptions {
     #[builder(default = 4)]
     /// Defines the number of merger threads to use.
     num_merge_threads: usize,
+    #[builder(default)]
+    /// The fields computed for every added document.
+    ///
+    /// See [`ComputedField`].
+    computed_fields: Vec<ComputedField>,
 }
 
 /// `IndexWriter` is the user entry-point to add document to an index.
@@ -83,6 +96,7 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
     operation_sender: AddBatchSender<D>,
 
     segment_updater: SegmentUpdater,
+    merge_mode: MergeMode,
 
     worker_id: usize,
 
@@ -90,10 +104,25 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
 
     stamper: Stamper,
     committed_opstamp: Opstamp,
+
+    dedup_field: Option<Field>,
+    // Content hashes of the documents added since the last commit.
+    dedup_seen_hashes: Mutex<HashSet<u64>>,
+
+    unique_key_field: Option<Field>,
+
+    // Error of the first document flagged by the indexing workers since the last rollback, see
+    // `PositionsOverflowPolicy::Error`. The document is indexed nonetheless, so the commits
+    // fail until the next rollback.
+    document_error: Arc<Mutex<Option<TantivyError>>>,
 }
 
+/// Applies the operations of the delete queue up to `target_opstamp`.
+///
+/// The deletes are applied to `alive_bitset`, and the fieldnorm updates to `fieldnorm_overlay`.
 fn compute_deleted_bitset(
     alive_bitset: &mut BitSet,
+    fieldnorm_overlay: &mut FieldNormOverlay,
     segment_reader: &SegmentReader,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &DocToOpstampMapping,
@@ -111,7 +140,12 @@ fn compute_deleted_bitset(
             .target
             .for_each_no_score(segment_reader, &mut |docs_matching_delete_query| {
                 for doc_matching_delete_query in docs_matching_delete_query.iter().cloned() {
-                    if doc_opstamps.is_deleted(doc_matching_delete_query, delete_op.opstamp) {
+                    if !doc_opstamps.is_deleted(doc_matching_delete_query, delete_op.opstamp) {
+                        continue;
+                    }
+                    if let Some((field, fieldnorm)) = delete_op.fieldnorm_update {
+                        fieldnorm_overlay.set(field, doc_matching_delete_query, fieldnorm);
+                    } else {
                         alive_bitset.remove(doc_matching_delete_query);
                         might_have_changed = true;
                     }
@@ -122,8 +156,22 @@ fn compute_deleted_bitset(
     Ok(might_have_changed)
 }
 
+fn write_fieldnorm_overlay(
+    segment: Segment,
+    fieldnorm_overlay: &FieldNormOverlay,
+    opstamp: Opstamp,
+) -> crate::Result<Segment> {
+    let mut segment = segment.with_fieldnorm_overlay(opstamp);
+    let mut fieldnorm_overlay_file = segment.open_write(SegmentComponent::FieldNormOverlay)?;
+    fieldnorm_overlay.serialize(&mut fieldnorm_overlay_file)?;
+    fieldnorm_overlay_file.terminate()?;
+    Ok(segment)
+}
+
 /// Advance delete for the given segment up to the target opstamp.
 ///
+/// The documents that are not alive in `applied_alive_bitset`, if any, are deleted too.
+///
 /// Note that there are no guarantee that the resulting `segment_entry` delete_opstamp
 /// is `==` target_opstamp.
 /// For instance, there was no delete operation between the state of the `segment_entry` and
@@ -132,13 +180,17 @@ pub(crate) fn advance_deletes(
     mut segment: Segment,
     segment_entry: &mut SegmentEntry,
     target_opstamp: Opstamp,
+    applied_alive_bitset: Option<&AliveBitSet>,
 ) -> crate::Result<()> {
     if segment_entry.meta().delete_opstamp() == Some(target_opstamp) {
         // We are already up-to-date here.
         return Ok(());
     }
 
-    if segment_entry.alive_bitset().is_none() && segment_entry.delete_cursor().get().is_none() {
+    if segment_entry.alive_bitset().is_none()
+        && segment_entry.delete_cursor().get().is_none()
+        && applied_alive_bitset.is_none()
+    {
         // There has been no `DeleteOperation` between the segment status and `target_opstamp`.
         return Ok(());
     }
@@ -152,9 +204,12 @@ pub(crate) fn advance_deletes(
     };
 
     let num_deleted_docs_before = segment.meta().num_deleted_docs();
+    let fieldnorm_overlay_before = segment_reader.fieldnorms_readers().overlay();
+    let mut fieldnorm_overlay = fieldnorm_overlay_before.cloned().unwrap_or_default();
 
     compute_deleted_bitset(
         &mut alive_bitset,
+        &mut fieldnorm_overlay,
         &segment_reader,
         segment_entry.delete_cursor(),
         &DocToOpstampMapping::None,
@@ -164,6 +219,9 @@ pub(crate) fn advance_deletes(
     if let Some(seg_alive_bitset) = segment_reader.alive_bitset() {
         alive_bitset.intersect_update(seg_alive_bitset.bitset());
     }
+    if let Some(applied_alive_bitset) = applied_alive_bitset {
+        alive_bitset.intersect_update(applied_alive_bitset.bitset());
+    }
 
     let num_alive_docs: u32 = alive_bitset.len() as u32;
     let num_deleted_docs = max_doc - num_alive_docs;
@@ -174,6 +232,12 @@ pub(crate) fn advance_deletes(
         write_alive_bitset(&alive_bitset, &mut alive_doc_file)?;
         alive_doc_file.terminate()?;
     }
+    if fieldnorm_overlay_before.map_or(!fieldnorm_overlay.is_empty(), |fieldnorm_overlay_before| {
+        *fieldnorm_overlay_before != fieldnorm_overlay
+    }) {
+        // There are new fieldnorm updates. We need to write a new fieldnorm overlay file.
+        segment = write_fieldnorm_overlay(segment, &fieldnorm_overlay, target_opstamp)?;
+    }
 
     segment_entry.set_meta(segment.meta().clone());
     Ok(())
@@ -185,11 +249,17 @@ fn index_documents<D: Document>(
     grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
     segment_updater: &SegmentUpdater,
     mut delete_cursor: DeleteCursor,
+    computed_fields: &Arc<[ComputedField]>,
+    document_error: &Mutex<Option<TantivyError>>,
 ) -> crate::Result<()> {
-    let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+    let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?
+        .with_computed_fields(computed_fields.clone());
     for document_group in grouped_document_iterator {
         for doc in document_group {
             segment_writer.add_document(doc)?;
+            if let Some(error) = segment_writer.take_positions_overflow_error() {
+                document_error.lock().unwrap().get_or_insert(error);
+            }
         }
         let mem_usage = segment_writer.mem_usage();
         if mem_usage >= memory_budget - MARGIN_IN_BYTES {
@@ -213,11 +283,20 @@ fn index_documents<D: Document>(
 
     let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
 
-    let segment_with_max_doc = segment.with_max_doc(max_doc);
+    let mut segment_with_max_doc = segment.with_max_doc(max_doc);
 
-    let alive_bitset_opt = apply_deletes(&segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
+    let (alive_bitset_opt, fieldnorm_overlay) =
+        apply_deletes(&segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
+    let max_doc_opstamp = doc_opstamps.iter().copied().max().unwrap_or(0);
+    if !fieldnorm_overlay.is_empty() {
+        segment_with_max_doc =
+            write_fieldnorm_overlay(segment_with_max_doc, &fieldnorm_overlay, max_doc_opstamp)?;
+    }
 
-    let meta = segment_with_max_doc.meta().clone();
+    let meta = segment_with_max_doc
+        .meta()
+        .clone()
+        .with_creation_opstamp(max_doc_opstamp);
     meta.untrack_temp_docstore();
     // update segment_updater inventory to remove tempstore
     let segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt);
@@ -226,15 +305,19 @@ fn index_documents<D: Document>(
 }
 
 /// `doc_opstamps` is required to be non-empty.
+///
+/// Returns the alive bitset of the segment, if it may have deletes, and the fieldnorm updates
+/// applying to its documents.
 fn apply_deletes(
     segment: &Segment,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &[Opstamp],
-) -> crate::Result<Option<BitSet>> {
+) -> crate::Result<(Option<BitSet>, FieldNormOverlay)> {
+    let mut fieldnorm_overlay = FieldNormOverlay::default();
     if delete_cursor.get().is_none() {
         // if there are no delete operation in the queue, no need
         // to even open the segment.
-        return Ok(None);
+        return Ok((None, fieldnorm_overlay));
     }
 
     let max_doc_opstamp: Opstamp = doc_opstamps
@@ -250,16 +333,18 @@ fn apply_deletes(
     let mut deleted_bitset = BitSet::with_max_value_and_full(max_doc);
     let may_have_deletes = compute_deleted_bitset(
         &mut deleted_bitset,
+        &mut fieldnorm_overlay,
         &segment_reader,
         delete_cursor,
         &doc_to_opstamps,
         max_doc_opstamp,
     )?;
-    Ok(if may_have_deletes {
+    let alive_bitset_opt = if may_have_deletes {
         Some(deleted_bitset)
     } else {
         None
-    })
+    };
+    Ok((alive_bitset_opt, fieldnorm_overlay))
 }
 
 impl<D: Document> IndexWriter<D> {
@@ -299,6 +384,18 @@ impl<D: Document> IndexWriter<D> {
             let err_msg = "At least one worker thread is required, got 0".to_string();
             return Err(TantivyError::InvalidArgument(err_msg));
         }
+        let mut computed_targets = HashSet::new();
+        for computed_field in &options.computed_fields {
+            if !computed_targets.insert(computed_field.target_field()) {
+                let field_name = index
+                    .schema()
+                    .get_field_name(computed_field.target_field())
+                    .to_string();
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The field {field_name} is computed more than once"
+                )));
+            }
+        }
 
         let (document_sender, document_receiver) =
             crossbeam_channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
@@ -325,6 +422,7 @@ impl<D: Document> IndexWriter<D> {
             operation_sender: document_sender,
 
             segment_updater,
+            merge_mode: MergeMode::default(),
 
             workers_join_handle: vec![],
 
@@ -334,6 +432,13 @@ impl<D: Document> IndexWriter<D> {
             stamper,
 
             worker_id: 0,
+
+            dedup_field: None,
+            dedup_seen_hashes: Mutex::default(),
+
+            unique_key_field: None,
+
+            document_error: Arc::default(),
         };
         index_writer.start_workers()?;
         Ok(index_writer)
@@ -420,7 +525,9 @@ impl<D: Document> IndexWriter<D> {
         let mut delete_cursor = self.delete_queue.cursor();
 
         let mem_budget = self.options.memory_budget_per_thread;
+        let computed_fields: Arc<[ComputedField]> = self.options.computed_fields.clone().into();
         let index = self.index.clone();
+        let document_error = self.document_error.clone();
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
             .spawn(move || {
@@ -453,6 +560,8 @@ impl<D: Document> IndexWriter<D> {
                         &mut document_iterator,
                         &segment_updater,
                         delete_cursor.clone(),
+                        &computed_fields,
+                        &document_error,
                     )?;
                 }
             })?;
@@ -471,6 +580,237 @@ impl<D: Document> IndexWriter<D> {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Accessor to the merge IO throttle, in bytes per second.
+    pub fn merge_io_throttle(&self) -> Option<NonZeroU64> {
+        self.segment_updater.merge_io_throttle()
+    }
+
+    /// Caps the write throughput of each merge to `bytes_per_sec`.
+    ///
+    /// The merging thread sleeps whenever it writes faster than the limit, which trades
+    /// merge latency for steadier IO: merges take longer, but they no longer starve the
+    /// reads of the queries running on the same disk. `None`, the default, disables
+    /// the throttling.
+    ///
+    /// The limit applies to each merge separately, and is picked up by the merges
+    /// started after the call.
+    pub fn set_merge_io_throttle(&self, bytes_per_sec: Option<NonZeroU64>) {
+        self.segment_updater.set_merge_io_throttle(bytes_per_sec);
+    }
+
+    /// Sets the function computing the user metadata of a merged segment from the user
+    /// metadata of the segments merged.
+    ///
+    /// The default, [`merge_common_user_metadata`](crate::index::merge_common_user_metadata),
+    /// keeps the entries shared by all of the segments merged.
+    pub fn set_user_metadata_merger(&self, user_metadata_merger: Box<UserMetadataMerger>) {
+        self.segment_updater
+            .set_user_metadata_merger(Arc::from(user_metadata_merger));
+    }
+
+    /// Returns true if the merges are paused, see [`IndexWriter::pause_merges`].
+    pub fn merges_paused(&self) -> bool {
+        self.segment_updater.merges_paused()
+    }
+
+    /// Stops scheduling new merges, until [`IndexWriter::resume_merges`] is called.
+    ///
+    /// While the merges are paused, the merge policy is not consulted on commits, which keeps
+    /// the disk for the indexing during an ingestion burst, at the price of a growing number of
+    /// segments. The merges already running are left to finish. The merges requested explicitly
+    /// with [`IndexWriter::merge`] are still executed.
+    ///
+    /// Once this returns, no new merge gets scheduled.
+    pub fn pause_merges(&self) -> crate::Result<()> {
+        self.segment_updater.schedule_pause_merges().wait()
+    }
+
+    /// Resumes the merges paused by [`IndexWriter::pause_merges`].
+    ///
+    /// The merge policy is consulted right away, and the merges it picks are started in the
+    /// background before this returns: this does not wait for the segments accumulated during
+    /// the pause to be merged.
+    pub fn resume_merges(&self) -> crate::Result<()> {
+        self.segment_updater.schedule_resume_merges().wait()
+    }
+
+    /// Accessor to the field used to drop duplicate documents, if any.
+    pub fn dedup_field(&self) -> Option<Field> {
+        self.dedup_field
+    }
+
+    /// Drops the documents whose content hash was already added in the current commit.
+    ///
+    /// `field` must be a `u64` fast field, holding a hash of the content of the document
+    /// computed by the client. Once set, [`IndexWriter::add_document`] and
+    /// [`IndexWriter::run`] skip the documents whose first value for `field` was already
+    /// seen since the last commit. Only the first occurrence of each hash is indexed.
+    /// Documents without a value for `field` are always indexed. `None`, the default,
+    /// disables the deduplication.
+    ///
+    /// Duplicates are only detected within a commit: a document whose hash was added by
+    /// a previous commit, and lives in another segment, is indexed again. The hashes
+    /// seen since the last commit are kept in memory, and are forgotten by
+    /// [`IndexWriter::commit`] and [`IndexWriter::rollback`].
+    ///
+    /// # Errors
+    /// Returns `TantivyError::SchemaError` if `field` is not a `u64` fast field.
+    pub fn set_dedup_field(&mut self, field: Option<Field>) -> crate::Result<()> {
+        if let Some(field) = field {
+            let field_entry = self.index.schema().get_field_entry(field).clone();
+            if !matches!(field_entry.field_type(), FieldType::U64(_)) || !field_entry.is_fast() {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a u64 fast field",
+                    field_entry.name()
+                )));
+            }
+        }
+        self.dedup_field = field;
+        self.dedup_seen_hashes.get_mut().unwrap().clear();
+        Ok(())
+    }
+
+    /// Returns true if the content hash of `document` was already added in the current
+    /// commit, and records it otherwise.
+    fn is_duplicate(&self, document: &D) -> bool {
+        let Some(dedup_field) = self.dedup_field else {
+            return false;
+        };
+        let Some(hash) = document
+            .iter_fields_and_values()
+            .find(|(field, _)| *field == dedup_field)
+            .and_then(|(_, value)| value.as_u64())
+        else {
+            return false;
+        };
+        !self.dedup_seen_hashes.lock().unwrap().insert(hash)
+    }
+
+    /// Accessor to the field whose values must be unique, if any.
+    pub fn unique_key_field(&self) -> Option<Field> {
+        self.unique_key_field
+    }
+
+    /// Makes the values of `field` unique: adding a document deletes the documents previously
+    /// added with the same key.
+    ///
+    /// Once set, [`IndexWriter::add_document`] and [`IndexWriter::run`] behave as an upsert:
+    /// each added document comes with a deletion of its key, ordered right before the
+    /// document itself. The documents added earlier with the same key, in previous commits or
+    /// in the current one, are deleted, and the latest document wins. `None`, the default,
+    /// disables the constraint.
+    ///
+    /// `field` must be an indexed `u64` or `i64` field, or a text field indexed with the `raw`
+    /// tokenizer, such as a [`STRING`](crate::schema::STRING) field, so that its values are
+    /// indexed as is.
+    ///
+    /// # Errors
+    /// Returns `TantivyError::SchemaError` if `field` is not a suitable key field. Once set,
+    /// adding a document without a value for `field`, or with several values, returns a
+    /// `TantivyError::InvalidArgument`.
+    pub fn set_unique_key_field(&mut self, field: Option<Field>) -> crate::Result<()> {
+        if let Some(field) = field {
+            let field_entry = self.index.schema().get_field_entry(field).clone();
+            let is_key_field = match field_entry.field_type() {
+                FieldType::U64(options) | FieldType::I64(options) => options.is_indexed(),
+                FieldType::Str(options) => options
+                    .get_indexing_options()
+                    .is_some_and(|indexing| indexing.tokenizer() == "raw"),
+                _ => false,
+            };
+            if !is_key_field {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} cannot be a unique key: it must be an indexed u64 or i64 field, \
+                     or a text field indexed with the raw tokenizer",
+                    field_entry.name()
+                )));
+            }
+        }
+        self.unique_key_field = field;
+        Ok(())
+    }
+
+    /// Returns the term holding the unique key of `document`, if a unique key field is set.
+    fn unique_key_term(&self, document: &D) -> crate::Result<Option<Term>> {
+        let Some(unique_key_field) = self.unique_key_field else {
+            return Ok(None);
+        };
+        let schema = self.index.schema();
+        let field_entry = schema.get_field_entry(unique_key_field);
+        let mut key_term = None;
+        for (field, value) in document.iter_fields_and_values() {
+            if field != unique_key_field {
+                continue;
+            }
+            if key_term.is_some() {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The document has several values for the unique key field {:?}",
+                    field_entry.name()
+                )));
+            }
+            let term = match field_entry.field_type() {
+                FieldType::U64(_) => value
+                    .as_u64()
+                    .map(|val| Term::from_field_u64(unique_key_field, val)),
+                FieldType::I64(_) => value
+                    .as_i64()
+                    .map(|val| Term::from_field_i64(unique_key_field, val)),
+                _ => value
+                    .as_str()
+                    .map(|val| Term::from_field_text(unique_key_field, val)),
+            };
+            key_term = Some(term.ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "Invalid value for the unique key field {:?}",
+                    field_entry.name()
+                ))
+            })?);
+        }
+        key_term.map(Some).ok_or_else(|| {
+            TantivyError::InvalidArgument(format!(
+                "The document has no value for the unique key field {:?}",
+                field_entry.name()
+            ))
+        })
+    }
+
+    /// Deletes the documents containing `key_term` added before `opstamp`.
+    fn delete_unique_key(&self, key_term: Term, opstamp: Opstamp) -> crate::Result<()> {
+        let delete_operation = self.term_delete_operation(key_term, opstamp)?;
+        self.delete_queue.push(delete_operation);
+        Ok(())
+    }
+
+    fn term_delete_operation(
+        &self,
+        term: Term,
+        opstamp: Opstamp,
+    ) -> crate::Result<DeleteOperation> {
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let weight = query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
+        Ok(DeleteOperation {
+            opstamp,
+            target: weight,
+            fieldnorm_update: None,
+        })
+    }
+
+    /// Accessor to the merge mode.
+    pub fn merge_mode(&self) -> MergeMode {
+        self.merge_mode
+    }
+
+    /// Defines whether [`IndexWriter::commit`] waits for the merges it triggers.
+    ///
+    /// Defaults to [`MergeMode::Background`]. With [`MergeMode::BlockingOnCommit`], the
+    /// segments are settled when `commit()` returns, which makes the segment
+    /// layout deterministic in tests and lets batch jobs control when merges happen.
+    ///
+    /// [`PreparedCommit::commit_future()`] never waits for the merges.
+    pub fn set_merge_mode(&mut self, merge_mode: MergeMode) {
+        self.merge_mode = merge_mode;
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
         for _ in 0..self.options.num_worker_threads {
             self.add_indexing_worker()?;
@@ -538,6 +878,108 @@ impl<D: Document> IndexWriter<D> {
         segment_updater.start_merge(merge_operation)
     }
 
+    /// Merges a given list of segments, passing each of their documents through `transform`.
+    ///
+    /// Contrary to [`IndexWriter::merge`], which copies the indexed data of the segments as is,
+    /// this merge rebuilds the documents from the doc store, applies `transform`, and indexes the
+    /// returned documents into the merged segment, as if they had been added with
+    /// [`IndexWriter::add_document`]. It makes it possible to rewrite the values of existing
+    /// documents, for instance to normalize a format, at the cost of reindexing the segments.
+    ///
+    /// The constraints are the following:
+    /// - the documents are rebuilt from their stored values, so every field of the schema has to be
+    ///   stored, otherwise the merge fails with [`TantivyError::InvalidArgument`]. The target
+    ///   fields of the [`ComputedField`]s of the writer are the exception: they cannot be stored,
+    ///   and are computed again for the returned documents.
+    /// - `transform` has to return documents of the index schema. Like the documents added to the
+    ///   index, the fields they leave out are missing from the merged segment.
+    /// - the documents are reindexed with the memory budget of an indexing thread. When it is
+    ///   reached, the reindexed documents are flushed to an intermediary segment, and the
+    ///   intermediary segments are merged once all of the documents are reindexed.
+    /// - the deleted documents are dropped, and the raw sources kept with
+    ///   [`IndexSettings::store_source`](crate::IndexSettings::store_source) are carried over
+    ///   untouched.
+    ///
+    /// If all segments are empty no new segment will be created.
+    ///
+    /// `segment_ids` is required to be non-empty.
+    pub fn merge_with_transform(
+        &mut self,
+        segment_ids: &[SegmentId],
+        transform: impl Fn(TantivyDocument) -> TantivyDocument + Send + 'static,
+    ) -> FutureResult<Option<SegmentMeta>> {
+        let schema = self.index.schema();
+        let is_computed = |field: Field| {
+            self.options
+                .computed_fields
+                .iter()
+                .any(|computed_field| computed_field.target_field() == field)
+        };
+        if let Some((_, field_entry)) = schema
+            .fields()
+            .find(|(field, field_entry)| !field_entry.is_stored() && !is_computed(*field))
+        {
+            return TantivyError::InvalidArgument(format!(
+                "Merging with a transform requires every field to be stored, {:?} is not",
+                field_entry.name()
+            ))
+            .into();
+        }
+        let merge_transform = MergeTransform {
+            transform: Box::new(transform),
+            memory_budget: self.options.memory_budget_per_thread,
+            computed_fields: self.options.computed_fields.clone().into(),
+        };
+        let merge_operation = self.segment_updater.make_merge_operation(segment_ids);
+        let segment_updater = self.segment_updater.clone();
+        segment_updater.start_merge_with_transform(merge_operation, Some(merge_transform))
+    }
+
+    /// Estimates the change of disk footprint [`IndexWriter::merge`] would produce on
+    /// `segment_ids`, before running it.
+    ///
+    /// The space usage of the segments is summed exactly, while the size of the merged segment
+    /// is estimated by [`MergeSpaceDelta::estimate`]: the deleted documents are dropped, as if
+    /// every document took the same space. This helps deciding whether compacting segments
+    /// with many deletes is worth the IO of the merge.
+    ///
+    /// The deletes taken into account are the ones of the segment metas, i.e. the deletes
+    /// committed, or applied to an uncommitted segment.
+    ///
+    /// Returns `TantivyError::InvalidArgument` if a segment is unknown to the index, or is
+    /// listed more than once.
+    pub fn merge_space_delta(&self, segment_ids: &[SegmentId]) -> crate::Result<MergeSpaceDelta> {
+        let mut unique_segment_ids = HashSet::with_capacity(segment_ids.len());
+        if let Some(repeated_segment_id) = segment_ids
+            .iter()
+            .find(|segment_id| !unique_segment_ids.insert(**segment_id))
+        {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The segment {} is listed more than once",
+                repeated_segment_id.short_uuid_string()
+            )));
+        }
+        let segment_metas = self.segment_updater.segment_metas(segment_ids);
+        if let Some(missing_segment_id) = segment_ids.iter().find(|segment_id| {
+            !segment_metas
+                .iter()
+                .any(|segment_meta| segment_meta.id() == **segment_id)
+        }) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The index has no segment {}",
+                missing_segment_id.short_uuid_string()
+            )));
+        }
+        let segment_space_usages = segment_metas
+            .into_iter()
+            .map(|segment_meta| {
+                let segment_reader = SegmentReader::open(&self.index.segment(segment_meta))?;
+                Ok(segment_reader.space_usage()?)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(MergeSpaceDelta::estimate(&segment_space_usages))
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -574,7 +1016,18 @@ impl<D: Document> IndexWriter<D> {
             .take()
             .expect("The IndexWriter does not have any lock. This is a bug, please report.");
 
-        let new_index_writer = IndexWriter::new(&self.index, self.options.clone(), directory_lock)?;
+        let mut new_index_writer =
+            IndexWriter::new(&self.index, self.options.clone(), directory_lock)?;
+        new_index_writer.dedup_field = self.dedup_field;
+        new_index_writer.unique_key_field = self.unique_key_field;
+        new_index_writer.merge_mode = self.merge_mode;
+        new_index_writer.set_merge_io_throttle(self.merge_io_throttle());
+        if self.merges_paused() {
+            new_index_writer.pause_merges()?;
+        }
+        new_index_writer
+            .segment_updater
+            .set_user_metadata_merger(self.segment_updater.user_metadata_merger());
 
         // the current `self` is dropped right away because of this call.
         //
@@ -631,6 +1084,7 @@ impl<D: Document> IndexWriter<D> {
         // this will drop the current document channel
         // and recreate a new one.
         self.recreate_document_channel();
+        self.dedup_seen_hashes.get_mut().unwrap().clear();
 
         let former_workers_join_handle = std::mem::take(&mut self.workers_join_handle);
 
@@ -641,6 +1095,9 @@ impl<D: Document> IndexWriter<D> {
             indexing_worker_result?;
             self.add_indexing_worker()?;
         }
+        if let Some(document_error) = self.document_error.lock().unwrap().clone() {
+            return Err(document_error);
+        }
 
         let commit_opstamp = self.stamper.stamp();
         let prepared_commit = PreparedCommit::new(self, commit_opstamp);
@@ -661,6 +1118,9 @@ impl<D: Document> IndexWriter<D> {
     ///
     /// Commit returns the `opstamp` of the last document
     /// that made it in the commit.
+    ///
+    /// Depending on the [`MergeMode`], it also waits for the
+    /// merges triggered by the commit.
     pub fn commit(&mut self) -> crate::Result<Opstamp> {
         self.prepare_commit()?.commit()
     }
@@ -701,11 +1161,92 @@ impl<D: Document> IndexWriter<D> {
         let delete_operation = DeleteOperation {
             opstamp,
             target: weight,
+            fieldnorm_update: None,
         };
         self.delete_queue.push(delete_operation);
         Ok(opstamp)
     }
 
+    /// Sets the fieldnorm of `field` to `fieldnorm` for all documents containing a given term.
+    ///
+    /// This is the fieldnorm part of a partial update, for documents whose text was changed
+    /// in place: the fieldnorm component of their segment is not rewritten. The updated
+    /// fieldnorms are recorded in a small [`FieldNormOverlay`] file per segment, applied when
+    /// the fieldnorms are read, and collapsed into the fieldnorm component of the merged
+    /// segment on merges.
+    ///
+    /// Only the fieldnorm changes: the postings of the documents stay the same. The
+    /// average fieldnorm BM25 normalizes with is computed from the number of tokens indexed,
+    /// and only reflects the updated fieldnorms once the segment gets merged.
+    ///
+    /// Like deletes, the update only affects documents that were added in previous commits,
+    /// and documents that were added previously in the same commit. It is visible only after
+    /// calling `commit()`.
+    ///
+    /// Returns an `Err` if `field` does not record fieldnorms.
+    pub fn update_fieldnorm(
+        &self,
+        term: Term,
+        field: Field,
+        fieldnorm: u32,
+    ) -> crate::Result<Opstamp> {
+        let schema = self.index.schema();
+        if !FieldNormsWriter::fields_with_fieldnorm(&schema).contains(&field) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} does not record fieldnorms",
+                schema.get_field_name(field)
+            )));
+        }
+        let opstamp = self.stamper.stamp();
+        let mut delete_operation = self.term_delete_operation(term, opstamp)?;
+        delete_operation.fieldnorm_update = Some((field, fieldnorm));
+        self.delete_queue.push(delete_operation);
+        Ok(opstamp)
+    }
+
+    /// Deletes the documents of a segment that are not alive in `delete_bitset_bytes`, as
+    /// returned by [`SegmentReader::delete_bitset_bytes`] on a copy of the segment.
+    ///
+    /// This makes it possible to replicate the deletes of an index by shipping its delete
+    /// bitsets only, the segments themselves being immutable.
+    ///
+    /// Returns an `Err` if the index has no segment `segment_id`, or if the bitset is not
+    /// the bitset of a segment with the same number of documents (`max_doc`).
+    ///
+    /// Documents deleted in the segment stay deleted, and applying a new bitset to the same
+    /// segment before the commit replaces the previous one. Like other deletes, the deletion
+    /// is visible only after calling `commit()`. The bitset is discarded if the segment gets
+    /// merged before the commit: indexes replicated this way should use the
+    /// [`NoMergePolicy`](crate::indexer::NoMergePolicy).
+    pub fn apply_delete_bitset(
+        &self,
+        segment_id: SegmentId,
+        delete_bitset_bytes: OwnedBytes,
+    ) -> crate::Result<()> {
+        self.segment_updater
+            .apply_alive_bitset(segment_id, delete_bitset_bytes)
+    }
+
+    /// Attaches `user_metadata` to a segment, replacing its previous metadata.
+    ///
+    /// The metadata is a small key-value map, for instance to tag the segments with their
+    /// data source or their tier. It is persisted in the `meta.json` file by the next
+    /// commit, and is then available at query time through
+    /// [`SegmentReader::user_metadata`](crate::SegmentReader::user_metadata). When segments
+    /// are merged, the merger set with [`IndexWriter::set_user_metadata_merger`] computes the
+    /// metadata of the merged segment.
+    ///
+    /// Returns an `Err` if the index has no segment `segment_id`, committed or not.
+    pub fn set_segment_user_metadata(
+        &self,
+        segment_id: SegmentId,
+        user_metadata: SegmentUserMetadata,
+    ) -> crate::Result<()> {
+        self.segment_updater
+            .schedule_set_user_metadata(segment_id, user_metadata)
+            .wait()
+    }
+
     /// Returns the opstamp of the last successful commit.
     ///
     /// This is, for instance, the opstamp the index will
@@ -724,9 +1265,64 @@ impl<D: Document> IndexWriter<D> {
     /// The opstamp is an increasing `u64` that can
     /// be used by the client to align commits with its own
     /// document queue.
+    ///
+    /// If a dedup field is set, duplicate documents are skipped but still
+    /// receive an opstamp. See [`IndexWriter::set_dedup_field`].
+    ///
+    /// If a unique key field is set, the documents previously added with the same key are
+    /// deleted. See [`IndexWriter::set_unique_key_field`].
     pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
+        let key_term = self.unique_key_term(&document)?;
+        let opstamp = self.stamper.stamp();
+        if self.is_duplicate(&document) {
+            return Ok(opstamp);
+        }
+        if let Some(key_term) = key_term {
+            // A delete only affects the documents with a lower opstamp.
+            self.delete_unique_key(key_term, opstamp)?;
+        }
+        self.send_add_documents_batch(smallvec![AddOperation {
+            opstamp,
+            document,
+            source: None,
+        }])?;
+        Ok(opstamp)
+    }
+
+    /// Adds a document along with its raw source.
+    ///
+    /// The source is kept as is, and can be read back with
+    /// [`Searcher::source`](crate::Searcher::source). It typically is the JSON the document
+    /// was parsed from. Apart from that, this behaves like [`IndexWriter::add_document`].
+    ///
+    /// # Errors
+    /// Returns `TantivyError::InvalidArgument` if the index does not keep the sources, see
+    /// [`IndexSettings::store_source`](crate::IndexSettings::store_source).
+    pub fn add_document_with_source(
+        &self,
+        document: D,
+        source: impl Into<Vec<u8>>,
+    ) -> crate::Result<Opstamp> {
+        if !self.index.settings().store_source {
+            return Err(TantivyError::InvalidArgument(
+                "The index does not keep the source of the documents, see \
+                 `IndexSettings::store_source`"
+                    .to_string(),
+            ));
+        }
+        let key_term = self.unique_key_term(&document)?;
         let opstamp = self.stamper.stamp();
-        self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
+        if self.is_duplicate(&document) {
+            return Ok(opstamp);
+        }
+        if let Some(key_term) = key_term {
+            self.delete_unique_key(key_term, opstamp)?;
+        }
+        self.send_add_documents_batch(smallvec![AddOperation {
+            opstamp,
+            document,
+            source: Some(source.into()),
+        }])?;
         Ok(opstamp)
     }
 
@@ -771,23 +1367,41 @@ impl<D: Document> IndexWriter<D> {
         }
         let (batch_opstamp, stamps) = self.get_batch_opstamps(count);
 
-        let mut adds = AddBatch::default();
-
+        // The whole batch is checked before any of its operations is queued, so that an
+        // invalid document does not leave behind the deletes of the operations preceding it.
+        let mut operations = Vec::with_capacity(count as usize);
         for (user_op, opstamp) in user_operations_it.zip(stamps) {
-            match user_op {
+            let operation = match user_op {
                 UserOperation::Delete(term) => {
-                    let query = TermQuery::new(term, IndexRecordOption::Basic);
-                    let weight =
-                        query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
-                    let delete_operation = DeleteOperation {
-                        opstamp,
-                        target: weight,
-                    };
-                    self.delete_queue.push(delete_operation);
+                    Either::Left(self.term_delete_operation(term, opstamp)?)
                 }
                 UserOperation::Add(document) => {
-                    let add_operation = AddOperation { opstamp, document };
-                    adds.push(add_operation);
+                    let key_delete_operation = self
+                        .unique_key_term(&document)?
+                        .map(|key_term| self.term_delete_operation(key_term, opstamp))
+                        .transpose()?;
+                    Either::Right((key_delete_operation, opstamp, document))
+                }
+            };
+            operations.push(operation);
+        }
+
+        let mut adds = AddBatch::default();
+        for operation in operations {
+            match operation {
+                Either::Left(delete_operation) => self.delete_queue.push(delete_operation),
+                Either::Right((key_delete_operation, opstamp, document)) => {
+                    if self.is_duplicate(&document) {
+                        continue;
+                    }
+                    if let Some(key_delete_operation) = key_delete_operation {
+                        self.delete_queue.push(key_delete_operation);
+                    }
+                    adds.push(AddOperation {
+                        opstamp,
+                        document,
+                        source: None,
+                    });
                 }
             }
         }
@@ -818,6 +1432,8 @@ impl<D: Document> Drop for IndexWriter<D> {
 mod tests {
     use std::collections::{HashMap, HashSet};
     use std::net::Ipv6Addr;
+    use std::num::NonZeroU64;
+    use std::sync::Arc;
 
     use columnar::{Column, MonotonicallyMappableToU128};
     use itertools::Itertools;
@@ -827,8 +1443,11 @@ mod tests {
     use crate::collector::{Count, TopDocs};
     use crate::directory::error::LockError;
     use crate::error::*;
+    use crate::index::SegmentId;
     use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
-    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
+    use crate::indexer::merge_policy::tests::MergeWheneverPossible;
+    use crate::indexer::segment_updater::MergeTransform;
+    use crate::indexer::{ComputedField, IndexWriterOptions, MergeMode, NoMergePolicy};
     use crate::query::{QueryParser, TermQuery};
     use crate::schema::{
         self, Facet, FacetOptions, IndexRecordOption, IpAddrOptions, JsonObjectOptions,
@@ -837,8 +1456,8 @@ mod tests {
     };
     use crate::store::DOCSTORE_CACHE_CAPACITY;
     use crate::{
-        DateTime, DocAddress, Index, IndexSettings, IndexWriter, ReloadPolicy, TantivyDocument,
-        Term,
+        DateTime, DocAddress, Index, IndexSettings, IndexWriter, ReloadPolicy, SegmentUserMetadata,
+        TantivyDocument, Term,
     };
 
     const LOREM: &str = "Doc Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
@@ -849,6 +1468,101 @@ mod tests {
                          sint occaecat cupidatat non proident, sunt in culpa qui officia deserunt \
                          mollit anim id est laborum.";
 
+    #[test]
+    fn test_apply_delete_bitset() -> crate::Result<()> {
+        use crate::directory::RamDirectory;
+        use crate::query::AllQuery;
+
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | STORED);
+        let schema = schema_builder.build();
+        let primary_directory = RamDirectory::create();
+        let primary = Index::create(primary_directory.clone(), schema, IndexSettings::default())?;
+        {
+            let mut index_writer: IndexWriter = primary.writer_for_tests()?;
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for id in 0..20u64 {
+                index_writer.add_document(doc!(id_field => id))?;
+                if id % 10 == 9 {
+                    index_writer.commit()?;
+                }
+            }
+            index_writer.wait_merging_threads()?;
+        }
+        // The base segments are shipped once.
+        let replica = Index::open(primary_directory.deep_clone())?;
+        let mut primary_writer: IndexWriter = primary.writer_for_tests()?;
+        primary_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let mut replica_writer: IndexWriter = replica.writer_for_tests()?;
+        replica_writer.set_merge_policy(Box::new(NoMergePolicy));
+
+        let alive_ids = |index: &Index| -> crate::Result<Vec<u64>> {
+            let searcher = index.reader()?.searcher();
+            let mut ids: Vec<u64> = searcher
+                .search(&AllQuery, &TopDocs::with_limit(100))?
+                .into_iter()
+                .map(|(_, doc_address)| {
+                    let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                    doc.get_first(id_field)
+                        .and_then(|value| value.as_u64())
+                        .unwrap()
+                })
+                .collect();
+            ids.sort();
+            Ok(ids)
+        };
+        let ship_delete_bitsets = |replica_writer: &mut IndexWriter| -> crate::Result<()> {
+            for segment_reader in primary.reader()?.searcher().segment_readers() {
+                if let Some(delete_bitset_bytes) = segment_reader.delete_bitset_bytes() {
+                    replica_writer
+                        .apply_delete_bitset(segment_reader.segment_id(), delete_bitset_bytes)?;
+                }
+            }
+            replica_writer.commit()?;
+            Ok(())
+        };
+
+        for deleted_ids in [&[1u64, 2, 15][..], &[3, 19]] {
+            for &id in deleted_ids {
+                primary_writer.delete_term(Term::from_field_u64(id_field, id));
+            }
+            primary_writer.commit()?;
+            ship_delete_bitsets(&mut replica_writer)?;
+            assert_eq!(alive_ids(&replica)?, alive_ids(&primary)?);
+        }
+        assert_eq!(alive_ids(&replica)?.len(), 15);
+
+        let searcher = primary.reader()?.searcher();
+        let segment_readers = searcher.segment_readers();
+        let delete_bitset_bytes = segment_readers[0].delete_bitset_bytes().unwrap();
+        // The bitset does not match the number of documents of the segment.
+        assert!(matches!(
+            replica_writer.apply_delete_bitset(
+                segment_readers[0].segment_id(),
+                delete_bitset_bytes.slice(0..8)
+            ),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let mut other_max_doc_bytes = delete_bitset_bytes.as_slice().to_vec();
+        other_max_doc_bytes[..4].copy_from_slice(&11u32.to_le_bytes());
+        assert!(matches!(
+            replica_writer.apply_delete_bitset(
+                segment_readers[0].segment_id(),
+                common::OwnedBytes::new(other_max_doc_bytes)
+            ),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        // The segment does not exist.
+        assert!(matches!(
+            replica_writer.apply_delete_bitset(
+                crate::index::SegmentId::generate_random(),
+                delete_bitset_bytes
+            ),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_operations_group() {
         // an operations group with 2 items should cause 3 opstamps 0, 1, and 2.
@@ -1013,6 +1727,201 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pause_and_resume_merges() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(MergeWheneverPossible));
+        index_writer.set_merge_mode(MergeMode::BlockingOnCommit);
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "b"))?;
+        index_writer.commit()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+
+        index_writer.pause_merges()?;
+        assert!(index_writer.merges_paused());
+        for text in ["c", "d", "e"] {
+            index_writer.add_document(doc!(text_field => text))?;
+            index_writer.commit()?;
+        }
+        // No merge started while paused.
+        assert_eq!(index.searchable_segment_ids()?.len(), 4);
+
+        index_writer.resume_merges()?;
+        assert!(!index_writer.merges_paused());
+        index_writer.segment_updater().wait_merging_thread()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_user_metadata() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let mut segment_ids = Vec::new();
+        for (text, source) in [("a", "crm"), ("b", "mail"), ("c", "")] {
+            index_writer.add_document(doc!(text_field => text))?;
+            index_writer.commit()?;
+            let (segment_id,) = index
+                .searchable_segment_ids()?
+                .into_iter()
+                .filter(|segment_id| !segment_ids.contains(segment_id))
+                .collect_tuple()
+                .unwrap();
+            if !source.is_empty() {
+                let user_metadata = SegmentUserMetadata::from([
+                    ("tier".to_string(), "hot".to_string()),
+                    ("source".to_string(), source.to_string()),
+                ]);
+                index_writer.set_segment_user_metadata(segment_id, user_metadata)?;
+            }
+            segment_ids.push(segment_id);
+        }
+        assert!(matches!(
+            index_writer
+                .set_segment_user_metadata(SegmentId::generate_random(), Default::default()),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        index_writer.commit()?;
+
+        // The metadata is persisted in the meta.json file, and read back by the segment readers.
+        let segment_metas = index.load_metas()?.segments;
+        let searcher = index.reader()?.searcher();
+        let user_metadata_of = |segment_id: SegmentId| -> SegmentUserMetadata {
+            let segment_meta = segment_metas
+                .iter()
+                .find(|segment_meta| segment_meta.id() == segment_id)
+                .unwrap();
+            let segment_reader = searcher
+                .segment_readers()
+                .iter()
+                .find(|segment_reader| segment_reader.segment_id() == segment_id)
+                .unwrap();
+            assert_eq!(segment_reader.user_metadata(), segment_meta.user_metadata());
+            segment_meta.user_metadata().clone()
+        };
+        assert_eq!(
+            user_metadata_of(segment_ids[0])
+                .get("source")
+                .map(String::as_str),
+            Some("crm")
+        );
+        assert_eq!(user_metadata_of(segment_ids[1]).len(), 2);
+        assert!(user_metadata_of(segment_ids[2]).is_empty());
+
+        // By default, the merged segment keeps the entries shared by the merged segments.
+        let merged_segment_meta = index_writer.merge(&segment_ids[..2]).wait()?.unwrap();
+        assert_eq!(
+            merged_segment_meta.user_metadata(),
+            &SegmentUserMetadata::from([("tier".to_string(), "hot".to_string())])
+        );
+
+        index_writer.set_user_metadata_merger(Box::new(|segments_user_metadata| {
+            let mut user_metadata = SegmentUserMetadata::new();
+            for segment_user_metadata in segments_user_metadata {
+                user_metadata.extend((*segment_user_metadata).clone());
+            }
+            user_metadata.insert("merged".to_string(), "true".to_string());
+            user_metadata
+        }));
+        let merged_segment_meta = index_writer
+            .merge(&[merged_segment_meta.id(), segment_ids[2]])
+            .wait()?
+            .unwrap();
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(
+            searcher.segment_reader(0).user_metadata(),
+            &SegmentUserMetadata::from([
+                ("merged".to_string(), "true".to_string()),
+                ("tier".to_string(), "hot".to_string()),
+            ])
+        );
+        assert_eq!(
+            searcher.segment_reader(0).user_metadata(),
+            merged_segment_meta.user_metadata()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_space_delta() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_ord in 0..2u64 {
+            for id in segment_ord * 100..(segment_ord + 1) * 100 {
+                index_writer.add_document(doc!(
+                    id_field => id,
+                    text_field => format!("document {id} of segment {segment_ord}"),
+                ))?;
+            }
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        let delta_without_deletes = index_writer.merge_space_delta(&segment_ids)?;
+        assert_eq!(
+            delta_without_deletes.before(),
+            delta_without_deletes.after_estimate()
+        );
+        assert_eq!(delta_without_deletes.reclaimed_estimate(), 0u64);
+
+        // Deletes half of the documents of the second segment.
+        for id in 100..150u64 {
+            index_writer.delete_term(Term::from_field_u64(id_field, id));
+        }
+        index_writer.commit()?;
+        let delta = index_writer.merge_space_delta(&segment_ids)?;
+        let searcher = index.reader()?.searcher();
+        let space_usage = searcher.space_usage()?;
+        assert_eq!(delta.before(), space_usage.total());
+        let segment_with_deletes = space_usage
+            .segments()
+            .iter()
+            .find(|segment| segment.num_deleted_docs() == 50)
+            .unwrap();
+        let non_delete_bytes =
+            segment_with_deletes.total().get_bytes() - segment_with_deletes.deletes().get_bytes();
+        assert_eq!(
+            delta.reclaimed_estimate().get_bytes(),
+            segment_with_deletes.deletes().get_bytes() + non_delete_bytes / 2
+        );
+        assert_eq!(
+            delta.after_estimate().get_bytes(),
+            delta.before().get_bytes() - delta.reclaimed_estimate().get_bytes()
+        );
+
+        index_writer.merge(&segment_ids).wait()?;
+        let searcher = index.reader()?.searcher();
+        let merged_space_usage = searcher.space_usage()?;
+        assert_eq!(merged_space_usage.segments().len(), 1);
+        assert!(merged_space_usage.total() < delta.before());
+
+        let merged_segment_ids = index.searchable_segment_ids()?;
+        assert!(matches!(
+            index_writer.merge_space_delta(&[merged_segment_ids[0], merged_segment_ids[0]]),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer.merge_space_delta(&segment_ids),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_lockfile_released_on_drop() {
         let schema_builder = schema::Schema::builder();
@@ -1060,6 +1969,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_rollback_keeps_settings() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_mode(MergeMode::BlockingOnCommit);
+        let merge_io_throttle = NonZeroU64::new(1_000_000);
+        index_writer.set_merge_io_throttle(merge_io_throttle);
+        index_writer.pause_merges()?;
+        index_writer.set_user_metadata_merger(Box::new(|_| {
+            SegmentUserMetadata::from([("merged".to_string(), "true".to_string())])
+        }));
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.rollback()?;
+        assert_eq!(index_writer.merge_mode(), MergeMode::BlockingOnCommit);
+        assert_eq!(index_writer.merge_io_throttle(), merge_io_throttle);
+        assert!(index_writer.merges_paused());
+        let user_metadata_merger = index_writer.segment_updater().user_metadata_merger();
+        assert_eq!(
+            user_metadata_merger(&[]).get("merged"),
+            Some(&"true".to_string())
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_merge_on_empty_segments_single_segment() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -1146,6 +2081,203 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_with_transform() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | STORED | FAST);
+        let color_field = schema_builder.add_text_field("color", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let colors = ["Red", "BLUE", "green", "Blue"];
+        for (id, color) in colors.iter().enumerate() {
+            index_writer.add_document(doc!(id_field => id as u64, color_field => *color))?;
+            if id % 2 == 1 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.delete_term(Term::from_field_u64(id_field, 0));
+        index_writer.commit()?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        index_writer
+            .merge_with_transform(&segment_ids, move |doc: TantivyDocument| {
+                let mut transformed_doc = TantivyDocument::default();
+                for (field, value) in doc.field_values() {
+                    if field == color_field {
+                        let color = value.as_value().as_str().unwrap().to_lowercase();
+                        transformed_doc.add_text(field, color);
+                    } else {
+                        transformed_doc.add_field_value(field, value);
+                    }
+                }
+                transformed_doc
+            })
+            .wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.num_docs(), 3);
+        assert!(!segment_reader.has_deletes());
+        let doc_freq = |color: &str| searcher.doc_freq(&Term::from_field_text(color_field, color));
+        assert_eq!(doc_freq("blue")?, 2);
+        assert_eq!(doc_freq("green")?, 1);
+        assert_eq!(doc_freq("BLUE")?, 0);
+        assert_eq!(doc_freq("red")?, 0);
+        let ids = segment_reader.fast_fields().u64("id")?;
+        let store_reader = segment_reader.get_store_reader(0)?;
+        let mut docs: Vec<(u64, String)> = Vec::new();
+        for doc_id in 0..segment_reader.max_doc() {
+            let doc: TantivyDocument = store_reader.get(doc_id)?;
+            let color = doc.get_first(color_field).unwrap().as_str().unwrap();
+            assert_eq!(doc.get_first(id_field).unwrap().as_u64(), ids.first(doc_id));
+            docs.push((ids.first(doc_id).unwrap(), color.to_string()));
+        }
+        docs.sort();
+        assert_eq!(
+            docs,
+            [
+                (1, "blue".to_string()),
+                (2, "green".to_string()),
+                (3, "blue".to_string())
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with_transform_flushes_when_memory_budget_is_reached() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | STORED | FAST);
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let num_docs = 20_000u64;
+        for id in 0..num_docs {
+            index_writer.add_document(doc!(
+                id_field => id,
+                text_field => format!("word{id} {LOREM}")
+            ))?;
+        }
+        index_writer.delete_term(Term::from_field_u64(id_field, 0));
+        index_writer.commit()?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        // The reindexed documents do not fit in this memory budget.
+        let merge_transform = MergeTransform {
+            transform: Box::new(move |doc: TantivyDocument| {
+                let mut transformed_doc = TantivyDocument::default();
+                for (field, value) in doc.field_values() {
+                    if field == text_field {
+                        let text = value.as_value().as_str().unwrap().replace("word", "mot");
+                        transformed_doc.add_text(field, text);
+                    } else {
+                        transformed_doc.add_field_value(field, value);
+                    }
+                }
+                transformed_doc
+            }),
+            memory_budget: MEMORY_BUDGET_NUM_BYTES_MIN,
+            computed_fields: Arc::new([]),
+        };
+        let merge_operation = index_writer
+            .segment_updater
+            .make_merge_operation(&segment_ids);
+        index_writer
+            .segment_updater
+            .start_merge_with_transform(merge_operation, Some(merge_transform))
+            .wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.num_docs() as u64, num_docs - 1);
+        assert!(!segment_reader.has_deletes());
+        let doc_freq = |text: &str| searcher.doc_freq(&Term::from_field_text(text_field, text));
+        assert_eq!(doc_freq("word1")?, 0);
+        assert_eq!(doc_freq("mot0")?, 0);
+        for id in [1, num_docs / 2, num_docs - 1] {
+            assert_eq!(doc_freq(&format!("mot{id}"))?, 1);
+        }
+        assert_eq!(doc_freq("lorem")?, num_docs - 1);
+        let ids = segment_reader.fast_fields().u64("id")?;
+        let mut ids: Vec<u64> = (0..segment_reader.max_doc())
+            .flat_map(|doc_id| ids.first(doc_id))
+            .collect();
+        ids.sort();
+        assert_eq!(ids, (1..num_docs).collect::<Vec<u64>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with_transform_recomputes_computed_fields() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let views = schema_builder.add_u64_field("views", FAST | STORED);
+        let popularity = schema_builder.add_f64_field("popularity", FAST);
+        let schema = schema_builder.build();
+        let computed_field = ComputedField::new(&schema, popularity, "views * 2")?;
+        let index = Index::create_in_ram(schema);
+        let options = IndexWriterOptions::builder()
+            .memory_budget_per_thread(MEMORY_BUDGET_NUM_BYTES_MIN)
+            .computed_fields(vec![computed_field])
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for num_views in [1u64, 2, 3] {
+            index_writer.add_document(doc!(views => num_views))?;
+            index_writer.commit()?;
+        }
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer
+            .merge_with_transform(&segment_ids, move |doc: TantivyDocument| {
+                let num_views = doc.get_first(views).unwrap().as_u64().unwrap();
+                doc!(views => num_views * 10)
+            })
+            .wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let views_column = fast_fields.u64("views")?;
+        let popularity_column = fast_fields.f64("popularity")?;
+        let mut values: Vec<(u64, f64)> = (0..3)
+            .map(|doc_id| {
+                (
+                    views_column.first(doc_id).unwrap(),
+                    popularity_column.first(doc_id).unwrap(),
+                )
+            })
+            .collect();
+        values.sort_by_key(|(num_views, _)| *num_views);
+        assert_eq!(values, vec![(10, 20.0), (20, 40.0), (30, 60.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_with_transform_requires_stored_fields() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | STORED);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id_field => 1u64, text_field => "hello"))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        let merge_res = index_writer
+            .merge_with_transform(&segment_ids, |doc| doc)
+            .wait();
+        assert!(matches!(merge_res, Err(TantivyError::InvalidArgument(_))));
+        assert_eq!(index.searchable_segment_ids()?, segment_ids);
+        Ok(())
+    }
+
     #[test]
     fn test_with_merges() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -1250,6 +2382,170 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_dedup_field() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let hash_field = schema_builder.add_u64_field("hash", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let num_docs_containing = |s: &str| {
+            reader.reload().unwrap();
+            let searcher = reader.searcher();
+            let term = Term::from_field_text(text_field, s);
+            searcher.doc_freq(&term).unwrap()
+        };
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(4, 60_000_000)?;
+        assert!(matches!(
+            index_writer.set_dedup_field(Some(text_field)),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert_eq!(index_writer.dedup_field(), None);
+        index_writer.set_dedup_field(Some(hash_field))?;
+        assert_eq!(index_writer.dedup_field(), Some(hash_field));
+
+        for hash in [1u64, 2, 1, 3, 2, 1] {
+            index_writer.add_document(doc!(text_field => "a", hash_field => hash))?;
+        }
+        // Documents without a hash are always indexed.
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.run(vec![
+            UserOperation::Add(doc!(text_field => "a", hash_field => 3u64)),
+            UserOperation::Add(doc!(text_field => "a", hash_field => 4u64)),
+        ])?;
+        index_writer.commit()?;
+        assert_eq!(num_docs_containing("a"), 6);
+
+        // Duplicates are only detected within a commit.
+        index_writer.add_document(doc!(text_field => "b", hash_field => 1u64))?;
+        index_writer.add_document(doc!(text_field => "b", hash_field => 1u64))?;
+        index_writer.commit()?;
+        assert_eq!(num_docs_containing("b"), 1);
+
+        // The hashes of the rolled back documents are forgotten.
+        index_writer.add_document(doc!(text_field => "c", hash_field => 5u64))?;
+        index_writer.rollback()?;
+        assert_eq!(index_writer.dedup_field(), Some(hash_field));
+        index_writer.add_document(doc!(text_field => "c", hash_field => 5u64))?;
+        index_writer.add_document(doc!(text_field => "c", hash_field => 5u64))?;
+        index_writer.commit()?;
+        assert_eq!(num_docs_containing("c"), 1);
+
+        index_writer.set_dedup_field(None)?;
+        index_writer.add_document(doc!(text_field => "d", hash_field => 1u64))?;
+        index_writer.add_document(doc!(text_field => "d", hash_field => 1u64))?;
+        index_writer.commit()?;
+        assert_eq!(num_docs_containing("d"), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_key_field() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let key_field = schema_builder.add_text_field("key", STRING);
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(4, 60_000_000)?;
+        for field in [text_field, fast_field] {
+            assert!(matches!(
+                index_writer.set_unique_key_field(Some(field)),
+                Err(TantivyError::SchemaError(_))
+            ));
+        }
+        assert_eq!(index_writer.unique_key_field(), None);
+        index_writer.set_unique_key_field(Some(key_field))?;
+        assert_eq!(index_writer.unique_key_field(), Some(key_field));
+
+        index_writer.add_document(doc!(key_field => "k1", text_field => "first"))?;
+        index_writer.add_document(doc!(key_field => "k2", text_field => "first"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(key_field => "k1", text_field => "second"))?;
+        index_writer.run(vec![
+            UserOperation::Add(doc!(key_field => "k2", text_field => "second")),
+            UserOperation::Add(doc!(key_field => "k2", text_field => "third")),
+        ])?;
+        index_writer.add_document(doc!(key_field => "k1", text_field => "third"))?;
+        assert!(matches!(
+            index_writer.add_document(doc!(text_field => "no key")),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            index_writer.add_document(doc!(key_field => "k1", key_field => "k3")),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        for key in ["k1", "k2"] {
+            let query = TermQuery::new(
+                Term::from_field_text(key_field, key),
+                IndexRecordOption::Basic,
+            );
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+            assert_eq!(top_docs.len(), 1);
+            let doc: TantivyDocument = searcher.doc(top_docs[0].1)?;
+            assert_eq!(doc.get_first(text_field).unwrap().as_str(), Some("third"));
+        }
+
+        // Numeric keys.
+        index_writer.set_unique_key_field(Some(id_field))?;
+        index_writer.add_document(doc!(id_field => 1u64, key_field => "k4"))?;
+        index_writer.add_document(doc!(id_field => 1u64, key_field => "k5"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_key_field_invalid_document_in_batch() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let key_field = schema_builder.add_text_field("key", STRING);
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_unique_key_field(Some(key_field))?;
+        index_writer.add_document(doc!(key_field => "k1", text_field => "first"))?;
+        index_writer.add_document(doc!(key_field => "k2", text_field => "first"))?;
+        index_writer.commit()?;
+
+        for invalid_doc in [
+            doc!(text_field => "no key"),
+            doc!(key_field => "k3", key_field => "k4"),
+        ] {
+            let batch_res = index_writer.run(vec![
+                UserOperation::Add(doc!(key_field => "k1", text_field => "second")),
+                UserOperation::Delete(Term::from_field_text(key_field, "k2")),
+                UserOperation::Add(invalid_doc),
+                UserOperation::Add(doc!(key_field => "k2", text_field => "second")),
+            ]);
+            assert!(matches!(batch_res, Err(TantivyError::InvalidArgument(_))));
+        }
+        index_writer.commit()?;
+
+        // None of the operations of the failed batches were applied.
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        for key in ["k1", "k2"] {
+            let query = TermQuery::new(
+                Term::from_field_text(key_field, key),
+                IndexRecordOption::Basic,
+            );
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+            assert_eq!(top_docs.len(), 1);
+            let doc: TantivyDocument = searcher.doc(top_docs[0].1)?;
+            assert_eq!(doc.get_first(text_field).unwrap().as_str(), Some("first"));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_add_then_delete_all_documents() {
         let mut schema_builder = schema::Schema::builder();
@@ -0,0 +1,136 @@
+use std::io::{self, Write};
+use std::num::NonZeroU64;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::{AntiCallToken, TerminatingWrite};
+
+/// Caps the write throughput shared by all of the files written through it.
+///
+/// The throttle keeps track of the number of bytes written since its creation,
+/// and makes the writer sleep whenever it gets ahead of the allowed rate.
+pub(crate) struct IoThrottle {
+    bytes_per_sec: NonZeroU64,
+    state: Mutex<IoThrottleState>,
+}
+
+struct IoThrottleState {
+    start: Instant,
+    num_bytes_written: u64,
+}
+
+impl IoThrottle {
+    pub fn new(bytes_per_sec: NonZeroU64) -> IoThrottle {
+        IoThrottle {
+            bytes_per_sec,
+            state: Mutex::new(IoThrottleState {
+                start: Instant::now(),
+                num_bytes_written: 0,
+            }),
+        }
+    }
+
+    /// Records `num_bytes` written bytes, and sleeps until the throughput is under the limit.
+    fn record_write(&self, num_bytes: usize) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            state.num_bytes_written += num_bytes as u64;
+            let target_elapsed = Duration::from_secs_f64(
+                state.num_bytes_written as f64 / self.bytes_per_sec.get() as f64,
+            );
+            target_elapsed.checked_sub(state.start.elapsed())
+        };
+        if let Some(wait) = wait {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// Writer throttled by an [`IoThrottle`].
+pub(crate) struct ThrottledWrite<W> {
+    underlying: W,
+    io_throttle: Arc<IoThrottle>,
+}
+
+impl<W> ThrottledWrite<W> {
+    pub fn wrap(underlying: W, io_throttle: Arc<IoThrottle>) -> ThrottledWrite<W> {
+        ThrottledWrite {
+            underlying,
+            io_throttle,
+        }
+    }
+}
+
+impl<W: Write> Write for ThrottledWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.underlying.write(buf)?;
+        self.io_throttle.record_write(num_bytes);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl<W: TerminatingWrite> TerminatingWrite for ThrottledWrite<W> {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.underlying.terminate_ref(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::time::{Duration, Instant};
+
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter};
+
+    /// Builds an index with two segments, and returns the time it takes to merge them,
+    /// along with the size of the merged segment.
+    fn merge_two_segments(bytes_per_sec: Option<NonZeroU64>) -> crate::Result<(Duration, u64)> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        assert_eq!(index_writer.merge_io_throttle(), None);
+        index_writer.set_merge_io_throttle(bytes_per_sec);
+        assert_eq!(index_writer.merge_io_throttle(), bytes_per_sec);
+        for _ in 0..2 {
+            for i in 0..5_000u64 {
+                index_writer.add_document(doc!(text => format!("word{} word{}", i, i * 7)))?;
+            }
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        let start = Instant::now();
+        index_writer.merge(&segment_ids).wait()?;
+        let elapsed = start.elapsed();
+        index_writer.wait_merging_threads()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), 10_000);
+        let num_bytes = searcher.space_usage()?.total().get_bytes();
+        Ok((elapsed, num_bytes))
+    }
+
+    #[test]
+    fn test_merge_io_throttle() -> crate::Result<()> {
+        let (unthrottled_elapsed, num_bytes) = merge_two_segments(None)?;
+        // The limit is picked so the throttled merge takes at least twice as long as the
+        // unthrottled one, and at least 200ms.
+        let target = (unthrottled_elapsed * 2).max(Duration::from_millis(200));
+        let bytes_per_sec = (num_bytes as f64 / target.as_secs_f64()) as u64;
+        let (throttled_elapsed, _) = merge_two_segments(NonZeroU64::new(bytes_per_sec.max(1)))?;
+        assert!(
+            throttled_elapsed > unthrottled_elapsed,
+            "throttled: {throttled_elapsed:?}, unthrottled: {unthrottled_elapsed:?}"
+        );
+        assert!(throttled_elapsed >= target.mul_f64(0.8));
+        Ok(())
+    }
+}
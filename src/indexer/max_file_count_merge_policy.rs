@@ -0,0 +1,213 @@
+use super::merge_policy::{MergeCandidate, MergePolicy};
+use crate::index::{IndexSettings, SegmentComponent, SegmentMeta};
+
+/// `MergePolicy` keeping the total number of segment files under a limit.
+///
+/// Each segment is made of several files, one per [`SegmentComponent`]: the components a segment
+/// only has in some cases, such as its deletes or its sources, are counted from its
+/// [`SegmentMeta`] and from the settings of the index (see
+/// [`MaxFileCountMergePolicy::set_index_settings`]). The files of the index itself, such as
+/// `meta.json`, are not counted.
+///
+/// Whenever the segments have more files than `max_num_files`, the policy merges the smallest
+/// segments together, picking as few of them as needed to get back under the limit. Contrary
+/// to a cap on the number of segments, this accounts for the delete files, which are purged by
+/// the merges.
+///
+/// The number of files only gets back under the limit once the merges are over: while a merge
+/// runs, the segments it merges and the segment it produces coexist, and the segments being
+/// merged are not counted by the following calls to the policy.
+///
+/// A low limit keeps the number of files small at the price of merge churn: once the limit is
+/// reached, about every commit triggers a merge, which rewrites the smallest segments over and
+/// over. A limit below the number of files of a couple of segments boils down to merging all of
+/// the segments on every commit.
+#[derive(Debug, Clone)]
+pub struct MaxFileCountMergePolicy {
+    max_num_files: usize,
+    index_settings: IndexSettings,
+}
+
+impl MaxFileCountMergePolicy {
+    /// Creates a policy keeping the segments under `max_num_files` files.
+    pub fn new(max_num_files: usize) -> MaxFileCountMergePolicy {
+        MaxFileCountMergePolicy {
+            max_num_files,
+            index_settings: IndexSettings::default(),
+        }
+    }
+
+    /// Returns the maximum number of segment files.
+    pub fn max_num_files(&self) -> usize {
+        self.max_num_files
+    }
+
+    /// Sets the settings of the index, which decide of some of the files of its segments.
+    ///
+    /// The policy assumes the default settings otherwise.
+    pub fn set_index_settings(&mut self, index_settings: &IndexSettings) {
+        self.index_settings = index_settings.clone();
+    }
+
+    /// Returns the number of files of the segment, or of a new segment, without deletes nor
+    /// fieldnorm overlay, such as the segment produced by a merge, if `segment_meta` is `None`.
+    fn num_files(&self, segment_meta: Option<&SegmentMeta>) -> usize {
+        SegmentComponent::iterator()
+            .filter(|component| match component {
+                // The temporary doc store only exists while the segment is being written.
+                SegmentComponent::TempStore => false,
+                SegmentComponent::Source => self.index_settings.store_source,
+                SegmentComponent::Delete => segment_meta.is_some_and(SegmentMeta::has_deletes),
+                SegmentComponent::FieldNormOverlay => segment_meta
+                    .is_some_and(|segment_meta| segment_meta.fieldnorm_overlay_opstamp().is_some()),
+                _ => true,
+            })
+            .count()
+    }
+}
+
+impl MergePolicy for MaxFileCountMergePolicy {
+    fn compute_merge_candidates(&self, segments: &[SegmentMeta]) -> Vec<MergeCandidate> {
+        let total_num_files: usize = segments
+            .iter()
+            .map(|segment_meta| self.num_files(Some(segment_meta)))
+            .sum();
+        if segments.len() < 2 || total_num_files <= self.max_num_files {
+            return Vec::new();
+        }
+        let mut sorted_segments: Vec<&SegmentMeta> = segments.iter().collect();
+        sorted_segments.sort_by_key(|segment_meta| segment_meta.num_docs());
+        let num_merged_segment_files = self.num_files(None);
+        let mut segment_ids = Vec::new();
+        // The files of the segments to merge, minus the files of the merged segment.
+        let mut num_files_removed: usize = 0;
+        for segment_meta in sorted_segments {
+            segment_ids.push(segment_meta.id());
+            num_files_removed += self.num_files(Some(segment_meta));
+            if segment_ids.len() >= 2
+                && total_num_files + num_merged_segment_files
+                    <= self.max_num_files + num_files_removed
+            {
+                break;
+            }
+        }
+        vec![MergeCandidate(segment_ids)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{SegmentId, SegmentMetaInventory};
+    use crate::indexer::{MergeMode, NoMergePolicy};
+    use crate::schema::{Schema, INDEXED, STORED, TEXT};
+    use crate::{Directory, Index, IndexWriter, Term};
+
+    fn num_segment_files(index: &Index) -> crate::Result<usize> {
+        let mut num_files = 0;
+        for segment_meta in index.searchable_segment_metas()? {
+            for path in segment_meta.list_files() {
+                if index.directory().exists(&path).unwrap() {
+                    num_files += 1;
+                }
+            }
+        }
+        Ok(num_files)
+    }
+
+    #[test]
+    fn test_max_file_count_merge_policy() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+
+        // The file count of the policy matches the files actually written.
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => 0u64, text => "doc"))?;
+        index_writer.add_document(doc!(id => 1u64, text => "doc"))?;
+        index_writer.commit()?;
+        let num_files_per_segment = MaxFileCountMergePolicy::new(0).num_files(None);
+        assert_eq!(num_segment_files(&index)?, num_files_per_segment);
+        index_writer.delete_term(Term::from_field_u64(id, 1));
+        index_writer.commit()?;
+        assert_eq!(num_segment_files(&index)?, num_files_per_segment + 1);
+
+        let max_num_files = 3 * num_files_per_segment + 2;
+        let merge_policy = MaxFileCountMergePolicy::new(max_num_files);
+        assert_eq!(merge_policy.max_num_files(), max_num_files);
+        index_writer.set_merge_policy(Box::new(merge_policy));
+        index_writer.set_merge_mode(MergeMode::BlockingOnCommit);
+        for i in 2..30u64 {
+            index_writer.add_document(doc!(id => i, text => "doc"))?;
+            if i % 4 == 0 {
+                index_writer.delete_term(Term::from_field_u64(id, i - 1));
+            }
+            index_writer.commit()?;
+            assert!(num_segment_files(&index)? <= max_num_files);
+        }
+        index_writer.wait_merging_threads()?;
+        assert!(num_segment_files(&index)? <= max_num_files);
+        assert!(index.searchable_segment_ids()?.len() >= 2);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 30 - 1 - 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_file_count_merge_policy_counts_source_files() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index_settings = IndexSettings {
+            store_source: true,
+            ..IndexSettings::default()
+        };
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(index_settings.clone())
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "doc"))?;
+        index_writer.commit()?;
+        let mut merge_policy = MaxFileCountMergePolicy::new(0);
+        merge_policy.set_index_settings(&index_settings);
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(
+            merge_policy.num_files(Some(&segment_metas[0])),
+            num_segment_files(&index)?
+        );
+        assert_eq!(
+            merge_policy.num_files(None),
+            MaxFileCountMergePolicy::new(0).num_files(None) + 1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_file_count_merge_policy_candidates() {
+        let num_files_per_segment = MaxFileCountMergePolicy::new(0).num_files(None);
+        let merge_policy = MaxFileCountMergePolicy::new(2 * num_files_per_segment);
+        let inventory = SegmentMetaInventory::default();
+        let segment_metas: Vec<SegmentMeta> = [10, 1, 5, 3]
+            .into_iter()
+            .map(|num_docs| inventory.new_segment_meta(SegmentId::generate_random(), num_docs))
+            .collect();
+        assert!(merge_policy
+            .compute_merge_candidates(&segment_metas[..2])
+            .is_empty());
+        // 4 segments for a limit of 2: the 3 smallest ones are merged.
+        let candidates = merge_policy.compute_merge_candidates(&segment_metas);
+        assert_eq!(candidates.len(), 1);
+        let mut expected = vec![
+            segment_metas[1].id(),
+            segment_metas[3].id(),
+            segment_metas[2].id(),
+        ];
+        let mut merged = candidates[0].0.clone();
+        expected.sort();
+        merged.sort();
+        assert_eq!(merged, expected);
+    }
+}
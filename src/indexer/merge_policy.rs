@@ -1,5 +1,6 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::marker;
+use std::sync::Arc;
 
 use crate::index::{SegmentId, SegmentMeta};
 
@@ -35,6 +36,99 @@ impl MergePolicy for NoMergePolicy {
     }
 }
 
+/// Defines whether [`IndexWriter::commit`](crate::IndexWriter::commit) waits for the merges it
+/// triggers.
+///
+/// See [`IndexWriter::set_merge_mode`](crate::IndexWriter::set_merge_mode).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Merges run in the background, and `commit()` returns as soon as the commit is
+    /// persisted.
+    #[default]
+    Background,
+    /// `commit()` returns once the merges triggered by the commit are finished, as well as
+    /// the merges these merges trigger in turn. The segments are then settled when `commit()`
+    /// returns.
+    ///
+    /// Merges that were already running when the commit started are waited for too.
+    BlockingOnCommit,
+}
+
+type MergeSelector = dyn Fn(&[SegmentMeta]) -> Option<Vec<SegmentId>> + Send + Sync;
+
+/// Merge policy delegating the merge decision to a user supplied closure.
+///
+/// The closure receives the metas of the segments that are neither being merged, nor
+/// scheduled for a merge, and returns the group of segments that should be merged next,
+/// if any. Committed and uncommitted segments are never merged together, so the closure
+/// may be called on both groups.
+///
+/// The metas expose the size of each segment ([`SegmentMeta::max_doc`]), its number of
+/// deleted documents ([`SegmentMeta::num_deleted_docs`]) and its age
+/// ([`SegmentMeta::creation_timestamp`]).
+///
+/// As for any [`MergePolicy`], the closure is called on the segment updater thread,
+/// and should return rapidly.
+///
+/// ```rust
+/// use tantivy::merge_policy::CustomMergePolicy;
+///
+/// // Purges the segments where more than half of the documents have been deleted.
+/// let merge_policy = CustomMergePolicy::new(|segment_metas| {
+///     let segment_ids: Vec<_> = segment_metas
+///         .iter()
+///         .filter(|segment_meta| segment_meta.num_deleted_docs() * 2 > segment_meta.max_doc())
+///         .map(|segment_meta| segment_meta.id())
+///         .collect();
+///     (!segment_ids.is_empty()).then_some(segment_ids)
+/// });
+///
+/// // Merges the segments created more than an hour ago.
+/// let merge_policy = CustomMergePolicy::new(|segment_metas| {
+///     let an_hour_ago = tantivy::time::OffsetDateTime::now_utc() - tantivy::time::Duration::HOUR;
+///     let segment_ids: Vec<_> = segment_metas
+///         .iter()
+///         .filter(|segment_meta| {
+///             segment_meta
+///                 .creation_timestamp()
+///                 .is_some_and(|creation_timestamp| creation_timestamp.into_utc() < an_hour_ago)
+///         })
+///         .map(|segment_meta| segment_meta.id())
+///         .collect();
+///     (segment_ids.len() >= 2).then_some(segment_ids)
+/// });
+/// ```
+#[derive(Clone)]
+pub struct CustomMergePolicy {
+    merge_selector: Arc<MergeSelector>,
+}
+
+impl CustomMergePolicy {
+    /// Creates a new `CustomMergePolicy` from a closure selecting the next group of
+    /// segments to merge.
+    pub fn new<F>(merge_selector: F) -> CustomMergePolicy
+    where F: Fn(&[SegmentMeta]) -> Option<Vec<SegmentId>> + Send + Sync + 'static {
+        CustomMergePolicy {
+            merge_selector: Arc::new(merge_selector),
+        }
+    }
+}
+
+impl Debug for CustomMergePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomMergePolicy").finish_non_exhaustive()
+    }
+}
+
+impl MergePolicy for CustomMergePolicy {
+    fn compute_merge_candidates(&self, segments: &[SegmentMeta]) -> Vec<MergeCandidate> {
+        match (self.merge_selector)(segments) {
+            Some(segment_ids) if !segment_ids.is_empty() => vec![MergeCandidate(segment_ids)],
+            _ => Vec::new(),
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 
@@ -60,4 +154,123 @@ pub(crate) mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_merge_mode_blocking_on_commit() -> crate::Result<()> {
+        use crate::schema::{Schema, STRING};
+        use crate::{Index, IndexWriter};
+
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        assert_eq!(index_writer.merge_mode(), MergeMode::Background);
+        index_writer.set_merge_policy(Box::new(MergeWheneverPossible));
+        index_writer.set_merge_mode(MergeMode::BlockingOnCommit);
+        for i in 0..5 {
+            index_writer.add_document(doc!(text => format!("doc{i}")))?;
+            index_writer.commit()?;
+            // The merge triggered by the commit, if any, is over.
+            assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        }
+        let reader = index.reader()?;
+        assert_eq!(reader.searcher().num_docs(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_merge_policy_high_delete_ratio() -> crate::Result<()> {
+        use crate::schema::{Schema, STRING};
+        use crate::{Index, IndexWriter, Term};
+
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(CustomMergePolicy::new(|segment_metas| {
+            let segment_ids: Vec<SegmentId> = segment_metas
+                .iter()
+                .filter(|segment_meta| segment_meta.num_deleted_docs() * 2 > segment_meta.max_doc())
+                .map(|segment_meta| segment_meta.id())
+                .collect();
+            (!segment_ids.is_empty()).then_some(segment_ids)
+        })));
+        for i in 0..10 {
+            let value = if i < 6 { "deleted" } else { "kept" };
+            index_writer.add_document(doc!(text => value))?;
+        }
+        index_writer.commit()?;
+        for i in 0..10 {
+            let value = if i < 4 { "deleted" } else { "kept" };
+            index_writer.add_document(doc!(text => value))?;
+        }
+        index_writer.commit()?;
+        let segment_ids_before = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids_before.len(), 2);
+        index_writer.delete_term(Term::from_field_text(text, "deleted"));
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 2);
+        // The segment with 6 deletes out of 10 docs got merged, purging its deletes.
+        let mut num_deleted_docs: Vec<u32> = segment_metas
+            .iter()
+            .map(SegmentMeta::num_deleted_docs)
+            .collect();
+        num_deleted_docs.sort();
+        assert_eq!(num_deleted_docs, vec![0, 4]);
+        let kept_segment = segment_metas
+            .iter()
+            .find(|segment_meta| segment_meta.num_deleted_docs() == 4)
+            .unwrap();
+        assert!(segment_ids_before.contains(&kept_segment.id()));
+        let merged_segment = segment_metas
+            .iter()
+            .find(|segment_meta| segment_meta.num_deleted_docs() == 0)
+            .unwrap();
+        assert!(!segment_ids_before.contains(&merged_segment.id()));
+        assert_eq!(merged_segment.max_doc(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_merge_policy_sees_creation_timestamps() -> crate::Result<()> {
+        use std::sync::Mutex;
+
+        use time::OffsetDateTime;
+
+        use crate::schema::{Schema, STRING};
+        use crate::{Index, IndexWriter};
+
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let creation_timestamps = Arc::new(Mutex::new(Vec::new()));
+        let creation_timestamps_clone = creation_timestamps.clone();
+        index_writer.set_merge_policy(Box::new(CustomMergePolicy::new(move |segment_metas| {
+            creation_timestamps_clone.lock().unwrap().extend(
+                segment_metas
+                    .iter()
+                    .map(|segment_meta| segment_meta.creation_timestamp()),
+            );
+            None
+        })));
+        let before = OffsetDateTime::now_utc().unix_timestamp();
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "b"))?;
+        index_writer.commit()?;
+        let after = OffsetDateTime::now_utc().unix_timestamp();
+        index_writer.wait_merging_threads()?;
+
+        let creation_timestamps = creation_timestamps.lock().unwrap();
+        assert!(!creation_timestamps.is_empty());
+        for creation_timestamp in creation_timestamps.iter() {
+            let creation_timestamp = creation_timestamp.unwrap().into_utc().unix_timestamp();
+            assert!((before..=after).contains(&creation_timestamp));
+        }
+        Ok(())
+    }
 }
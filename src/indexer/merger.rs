@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use columnar::column_values::{CodecType, FastFieldCodecs};
 use columnar::{
     ColumnType, ColumnarReader, MergeRowOrder, RowAddr, ShuffleMergeOrder, StackMergeOrder,
 };
@@ -11,13 +13,15 @@ use crate::directory::WritePtr;
 use crate::docset::{DocSet, TERMINATED};
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
-use crate::fieldnorm::{FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter};
+use crate::fieldnorm::{
+    FieldNormPrecision, FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter,
+};
 use crate::index::{Segment, SegmentComponent, SegmentReader};
 use crate::indexer::doc_id_mapping::{MappingType, SegmentDocIdMapping};
 use crate::indexer::SegmentSerializer;
 use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
 use crate::schema::{value_type_to_column_type, Field, FieldType, Schema};
-use crate::store::StoreWriter;
+use crate::store::{StoreReader, StoreWriter};
 use crate::termdict::{TermMerger, TermOrdinal};
 use crate::{DocAddress, DocId, InvertedIndexReader};
 
@@ -40,6 +44,12 @@ fn estimate_total_num_tokens_in_single_segment(
     // When there are deletes, we use an approximation either
     // by using the fieldnorm.
     if let Some(fieldnorm_reader) = reader.fieldnorms_readers().get_field(field)? {
+        if fieldnorm_reader.precision() == FieldNormPrecision::Exact {
+            return Ok(reader
+                .doc_ids_alive()
+                .map(|doc| u64::from(fieldnorm_reader.fieldnorm(doc)))
+                .sum());
+        }
         let mut count: [usize; 256] = [0; 256];
         for doc in reader.doc_ids_alive() {
             let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
@@ -80,6 +90,7 @@ pub struct IndexMerger {
     schema: Schema,
     pub(crate) readers: Vec<SegmentReader>,
     max_doc: u32,
+    fast_field_codecs: FastFieldCodecs,
 }
 
 struct DeltaComputer {
@@ -144,6 +155,49 @@ fn extract_fast_field_required_columns(schema: &Schema) -> Vec<(String, ColumnTy
         .collect()
 }
 
+fn extract_fast_field_codecs(schema: &Schema) -> HashMap<String, CodecType> {
+    schema
+        .fields()
+        .filter_map(|(_, field_entry)| {
+            let code = field_entry.field_type().fast_codec()?;
+            Some((field_entry.name().to_string(), CodecType::Custom(code)))
+        })
+        .collect()
+}
+
+/// Appends the documents of `store_reader`, the store of `reader`, to `store_writer`.
+fn write_store(
+    store_writer: &mut StoreWriter,
+    reader: &SegmentReader,
+    store_reader: StoreReader,
+) -> crate::Result<()> {
+    if reader.has_deletes()
+        // If there is not enough data in the store, we avoid stacking in order to
+        // avoid creating many small blocks in the doc store. Once we have 5 full blocks,
+        // we start stacking. In the worst case 2/7 of the blocks would be very small.
+        // [segment 1 - {1 doc}][segment 2 - {fullblock * 5}{1doc}]
+        // => 5 * full blocks, 2 * 1 document blocks
+        //
+        // In a more realistic scenario the segments are of the same size, so 1/6 of
+        // the doc stores would be on average half full, given total randomness (which
+        // is not the case here, but not sure how it behaves exactly).
+        //
+        // https://github.com/quickwit-oss/tantivy/issues/1053
+        //
+        // take 7 in order to not walk over all checkpoints.
+        || store_reader.block_checkpoints().take(7).count() < 6
+        || store_reader.decompressor() != store_writer.compressor().into()
+    {
+        for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
+            let doc_bytes = doc_bytes_res?;
+            store_writer.store_bytes(&doc_bytes)?;
+        }
+    } else {
+        store_writer.stack(store_reader)?;
+    }
+    Ok(())
+}
+
 impl IndexMerger {
     pub fn open(schema: Schema, segments: &[Segment]) -> crate::Result<IndexMerger> {
         let alive_bitset = segments.iter().map(|_| None).collect_vec();
@@ -185,10 +239,15 @@ impl IndexMerger {
             );
             return Err(crate::TantivyError::InvalidArgument(err_msg));
         }
+        let fast_field_codecs = segments
+            .first()
+            .map(|segment| segment.index().fast_field_codecs().clone())
+            .unwrap_or_default();
         Ok(IndexMerger {
             schema,
             readers,
             max_doc,
+            fast_field_codecs,
         })
     }
 
@@ -199,19 +258,37 @@ impl IndexMerger {
     ) -> crate::Result<()> {
         let fields = FieldNormsWriter::fields_with_fieldnorm(&self.schema);
         let mut fieldnorms_data = Vec::with_capacity(self.max_doc as usize);
+        let mut exact_fieldnorms_data = Vec::new();
         for field in fields {
-            fieldnorms_data.clear();
             let fieldnorms_readers: Vec<FieldNormReader> = self
                 .readers
                 .iter()
                 .map(|reader| reader.get_fieldnorms_reader(field))
                 .collect::<Result<_, _>>()?;
-            for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
-                let fieldnorms_reader = &fieldnorms_readers[old_doc_addr.segment_ord as usize];
-                let fieldnorm_id = fieldnorms_reader.fieldnorm_id(old_doc_addr.doc_id);
-                fieldnorms_data.push(fieldnorm_id);
+            let precision = FieldNormPrecision::for_field_entry(self.schema.get_field_entry(field));
+            match precision {
+                FieldNormPrecision::Quantized => {
+                    fieldnorms_data.clear();
+                    for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                        let fieldnorms_reader =
+                            &fieldnorms_readers[old_doc_addr.segment_ord as usize];
+                        let fieldnorm_id = fieldnorms_reader.fieldnorm_id(old_doc_addr.doc_id);
+                        fieldnorms_data.push(fieldnorm_id);
+                    }
+                    fieldnorms_serializer.serialize_field(field, &fieldnorms_data[..])?;
+                }
+                FieldNormPrecision::Exact => {
+                    exact_fieldnorms_data.clear();
+                    for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                        let fieldnorms_reader =
+                            &fieldnorms_readers[old_doc_addr.segment_ord as usize];
+                        exact_fieldnorms_data
+                            .push(fieldnorms_reader.fieldnorm(old_doc_addr.doc_id));
+                    }
+                    fieldnorms_serializer
+                        .serialize_exact_field(field, &exact_fieldnorms_data[..])?;
+                }
             }
-            fieldnorms_serializer.serialize_field(field, &fieldnorms_data[..])?;
         }
         fieldnorms_serializer.close()?;
         Ok(())
@@ -229,10 +306,13 @@ impl IndexMerger {
             .iter()
             .map(|reader| reader.fast_fields().columnar())
             .collect();
+        let column_codecs = extract_fast_field_codecs(&self.schema);
         let merge_row_order = convert_to_merge_order(&columnars[..], doc_id_mapping);
-        columnar::merge_columnar(
+        columnar::merge_columnar_with_codecs(
             &columnars[..],
             &required_columns,
+            &column_codecs,
+            &self.fast_field_codecs,
             merge_row_order,
             fast_field_wrt,
         )?;
@@ -364,7 +444,8 @@ impl IndexMerger {
             let mut total_doc_freq = 0;
 
             // Let's compute the list of non-empty posting lists
-            for (segment_ord, term_info) in merged_terms.current_segment_ords_and_term_infos() {
+            for segment_ord_and_term_info in merged_terms.current_segment_ords_and_term_infos() {
+                let (segment_ord, term_info) = segment_ord_and_term_info?;
                 let segment_reader = &self.readers[segment_ord];
                 let inverted_index: &InvertedIndexReader = &field_readers[segment_ord];
                 let segment_postings = inverted_index
@@ -492,29 +573,16 @@ impl IndexMerger {
 
         for reader in &self.readers {
             let store_reader = reader.get_store_reader(1)?;
-            if reader.has_deletes()
-                    // If there is not enough data in the store, we avoid stacking in order to
-                    // avoid creating many small blocks in the doc store. Once we have 5 full blocks,
-                    // we start stacking. In the worst case 2/7 of the blocks would be very small.
-                    // [segment 1 - {1 doc}][segment 2 - {fullblock * 5}{1doc}]
-                    // => 5 * full blocks, 2 * 1 document blocks
-                    //
-                    // In a more realistic scenario the segments are of the same size, so 1/6 of
-                    // the doc stores would be on average half full, given total randomness (which
-                    // is not the case here, but not sure how it behaves exactly).
-                    //
-                    // https://github.com/quickwit-oss/tantivy/issues/1053
-                    //
-                    // take 7 in order to not walk over all checkpoints.
-                    || store_reader.block_checkpoints().take(7).count() < 6
-                    || store_reader.decompressor() != store_writer.compressor().into()
-            {
-                for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
-                    let doc_bytes = doc_bytes_res?;
-                    store_writer.store_bytes(&doc_bytes)?;
-                }
-            } else {
-                store_writer.stack(store_reader)?;
+            write_store(store_writer, reader, store_reader)?;
+        }
+        Ok(())
+    }
+
+    fn write_sources(&self, source_writer: &mut StoreWriter) -> crate::Result<()> {
+        debug_time!("write-sources");
+        for reader in &self.readers {
+            if let Some(source_reader) = reader.get_source_reader(1)? {
+                write_store(source_writer, reader, source_reader)?;
             }
         }
         Ok(())
@@ -544,6 +612,10 @@ impl IndexMerger {
 
         debug!("write-storagefields");
         self.write_storable_fields(serializer.get_store_writer())?;
+        if let Some(source_writer) = serializer.get_source_writer() {
+            debug!("write-sources");
+            self.write_sources(source_writer)?;
+        }
         debug!("write-fastfields");
         self.write_fast_fields(serializer.get_fast_field_write(), doc_id_mapping)?;
 
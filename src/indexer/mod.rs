@@ -4,6 +4,7 @@
 //! `IndexWriter` is the main entry point for that, which created from
 //! [`Index::writer`](crate::Index::writer).
 
+mod computed_field;
 pub(crate) mod delete_queue;
 pub(crate) mod path_to_unordered_id;
 
@@ -12,7 +13,9 @@ mod doc_opstamp_mapping;
 mod flat_map_with_buffer;
 pub(crate) mod index_writer;
 pub(crate) mod index_writer_status;
+pub(crate) mod io_throttle;
 mod log_merge_policy;
+mod max_file_count_merge_policy;
 mod merge_index_test;
 mod merge_operation;
 pub(crate) mod merge_policy;
@@ -31,10 +34,14 @@ mod stamper;
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
+pub use self::computed_field::ComputedField;
 pub use self::index_writer::{IndexWriter, IndexWriterOptions};
 pub use self::log_merge_policy::LogMergePolicy;
+pub use self::max_file_count_merge_policy::MaxFileCountMergePolicy;
 pub use self::merge_operation::MergeOperation;
-pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
+pub use self::merge_policy::{
+    CustomMergePolicy, MergeCandidate, MergeMode, MergePolicy, NoMergePolicy,
+};
 use self::operation::AddOperation;
 pub use self::operation::UserOperation;
 pub use self::prepared_commit::PreparedCommit;
@@ -1,12 +1,17 @@
 use crate::query::Weight;
 use crate::schema::document::Document;
-use crate::schema::{TantivyDocument, Term};
+use crate::schema::{Field, TantivyDocument, Term};
 use crate::Opstamp;
 
 /// Timestamped Delete operation.
+///
+/// Fieldnorm updates go through the delete queue too, so that they are applied to the
+/// segments exactly like deletes. With `fieldnorm_update`, the documents matching `target`
+/// are not deleted: their fieldnorm for the field is set to the given value.
 pub struct DeleteOperation {
     pub opstamp: Opstamp,
     pub target: Box<dyn Weight>,
+    pub fieldnorm_update: Option<(Field, u32)>,
 }
 
 /// Timestamped Add operation.
@@ -14,6 +19,8 @@ pub struct DeleteOperation {
 pub struct AddOperation<D: Document = TantivyDocument> {
     pub opstamp: Opstamp,
     pub document: D,
+    /// The raw source of the document, see [`crate::IndexSettings::store_source`].
+    pub source: Option<Vec<u8>>,
 }
 
 /// UserOperation is an enum type that encapsulates other operation types.
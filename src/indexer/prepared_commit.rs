@@ -1,4 +1,4 @@
-use super::IndexWriter;
+use super::{IndexWriter, MergeMode};
 use crate::schema::document::Document;
 use crate::{FutureResult, Opstamp, TantivyDocument};
 
@@ -35,8 +35,17 @@ impl<'a, D: Document> PreparedCommit<'a, D> {
 
     /// Proceeds to commit.
     /// See `.commit_future()`.
+    ///
+    /// With [`MergeMode::BlockingOnCommit`], this also waits for the merges triggered by the
+    /// commit.
     pub fn commit(self) -> crate::Result<Opstamp> {
-        self.commit_future().wait()
+        let merge_mode = self.index_writer.merge_mode();
+        let segment_updater = self.index_writer.segment_updater().clone();
+        let opstamp = self.commit_future().wait()?;
+        if merge_mode == MergeMode::BlockingOnCommit {
+            segment_updater.wait_merging_thread()?;
+        }
+        Ok(opstamp)
     }
 
     /// Proceeds to commit.
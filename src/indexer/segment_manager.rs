@@ -4,7 +4,7 @@ use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use super::segment_register::SegmentRegister;
 use crate::error::TantivyError;
-use crate::index::{SegmentId, SegmentMeta};
+use crate::index::{SegmentId, SegmentMeta, SegmentUserMetadata};
 use crate::indexer::delete_queue::DeleteCursor;
 use crate::indexer::SegmentEntry;
 
@@ -214,6 +214,45 @@ impl SegmentManager {
         Ok(segments_status)
     }
 
+    /// Returns the metas of the segments, committed or uncommitted, among `segment_ids`.
+    pub fn segment_metas(&self, segment_ids: &[SegmentId]) -> Vec<SegmentMeta> {
+        let registers_lock = self.read();
+        segment_ids
+            .iter()
+            .flat_map(|segment_id| {
+                registers_lock
+                    .uncommitted
+                    .get(segment_id)
+                    .or_else(|| registers_lock.committed.get(segment_id))
+            })
+            .map(|segment_entry| segment_entry.meta().clone())
+            .collect()
+    }
+
+    /// Replaces the user metadata of a segment, committed or uncommitted.
+    ///
+    /// Returns false if the segment could not be found.
+    pub fn set_user_metadata(
+        &self,
+        segment_id: SegmentId,
+        user_metadata: SegmentUserMetadata,
+    ) -> bool {
+        let mut registers_lock = self.write();
+        let registers = &mut *registers_lock;
+        for register in [&mut registers.uncommitted, &mut registers.committed] {
+            if let Some(mut segment_entry) = register.get(&segment_id) {
+                let segment_meta = segment_entry
+                    .meta()
+                    .clone()
+                    .with_user_metadata(user_metadata);
+                segment_entry.set_meta(segment_meta);
+                register.add_segment_entry(segment_entry);
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn committed_segment_metas(&self) -> Vec<SegmentMeta> {
         self.remove_empty_segments();
         let registers_lock = self.read();
@@ -1,19 +1,79 @@
-use common::TerminatingWrite;
+use std::io;
 
-use crate::directory::WritePtr;
+use common::{CountingWriter, TerminatingWrite};
+
+use crate::directory::{Directory, WritePtr};
 use crate::fieldnorm::FieldNormsSerializer;
 use crate::index::{Segment, SegmentComponent};
 use crate::postings::InvertedIndexSerializer;
-use crate::store::StoreWriter;
+use crate::schema::document::{BinaryDocumentSerializer, Document};
+use crate::schema::{Field, Schema};
+use crate::store::{is_read_from_column, StoreWriter};
+
+/// Byte preceding each source in the source store.
+pub(crate) const SOURCE_PREFIX: u8 = 1;
+
+/// Writes the serialized documents, uncompressed, to the temporary store file of the segment.
+///
+/// See [`IndexSettings::docstore_temp_file`](crate::IndexSettings::docstore_temp_file).
+struct TempStoreWriter {
+    writer: CountingWriter<WritePtr>,
+    doc_end_offsets: Vec<u64>,
+}
+
+impl TempStoreWriter {
+    fn store<D: Document>(
+        &mut self,
+        document: &D,
+        schema: &Schema,
+        skipped_fields: &[Field],
+    ) -> io::Result<()> {
+        BinaryDocumentSerializer::new(&mut self.writer, schema)
+            .skip_fields(skipped_fields)
+            .serialize_doc(document)?;
+        self.doc_end_offsets.push(self.writer.written_bytes());
+        Ok(())
+    }
+
+    /// Copies the documents to the `store_writer`, and deletes the temporary store file.
+    fn write_to_store(
+        self,
+        segment: &Segment,
+        store_writer: &mut StoreWriter,
+    ) -> crate::Result<()> {
+        self.writer.finish().terminate()?;
+        let temp_store_file = segment.open_read(SegmentComponent::TempStore)?;
+        let mut doc_start_offset = 0;
+        for doc_end_offset in self.doc_end_offsets {
+            let doc_bytes = temp_store_file
+                .slice(doc_start_offset as usize..doc_end_offset as usize)
+                .read_bytes()?;
+            store_writer.store_bytes(doc_bytes.as_slice())?;
+            doc_start_offset = doc_end_offset;
+        }
+        drop(temp_store_file);
+        let temp_store_path = segment.relative_path(SegmentComponent::TempStore);
+        segment
+            .index()
+            .directory()
+            .delete(&temp_store_path)
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+}
 
 /// Segment serializer is in charge of laying out on disk
 /// the data accumulated and sorted by the `SegmentWriter`.
 pub struct SegmentSerializer {
     segment: Segment,
     pub(crate) store_writer: StoreWriter,
+    source_writer: Option<StoreWriter>,
     fast_field_write: WritePtr,
     fieldnorms_serializer: Option<FieldNormsSerializer>,
     postings_serializer: InvertedIndexSerializer,
+    temp_store_writer: Option<TempStoreWriter>,
+    // The fields left out of the doc store, see `IndexSettings::docstore_skip_fast_fields`.
+    fields_read_from_columns: Vec<Field>,
 }
 
 impl SegmentSerializer {
@@ -29,6 +89,17 @@ impl SegmentSerializer {
                 settings.docstore_compress_dedicated_thread,
             )?
         };
+        let source_writer = if settings.store_source {
+            let source_write = segment.open_write(SegmentComponent::Source)?;
+            Some(StoreWriter::new(
+                source_write,
+                settings.docstore_compression,
+                settings.docstore_blocksize,
+                false,
+            )?)
+        } else {
+            None
+        };
 
         let fast_field_write = segment.open_write(SegmentComponent::FastFields)?;
 
@@ -39,15 +110,94 @@ impl SegmentSerializer {
         Ok(SegmentSerializer {
             segment,
             store_writer,
+            source_writer,
             fast_field_write,
             fieldnorms_serializer: Some(fieldnorms_serializer),
             postings_serializer,
+            temp_store_writer: None,
+            fields_read_from_columns: Vec::new(),
         })
     }
 
+    /// Creates a new `SegmentSerializer` for a segment being indexed.
+    ///
+    /// Contrary to [`SegmentSerializer::for_segment`], the documents stored with
+    /// [`SegmentSerializer::store_document`] go through a temporary file if the index
+    /// settings ask for it.
+    pub fn for_indexing(segment: Segment) -> crate::Result<SegmentSerializer> {
+        let settings = segment.index().settings().clone();
+        let mut segment_serializer = SegmentSerializer::for_segment(segment)?;
+        if settings.docstore_skip_fast_fields {
+            segment_serializer.fields_read_from_columns = segment_serializer
+                .segment
+                .schema()
+                .fields()
+                .filter(|(_, field_entry)| is_read_from_column(field_entry))
+                .map(|(field, _)| field)
+                .collect();
+        }
+        if settings.docstore_temp_file {
+            let temp_store_write = segment_serializer
+                .segment
+                .open_write(SegmentComponent::TempStore)?;
+            segment_serializer.temp_store_writer = Some(TempStoreWriter {
+                writer: CountingWriter::wrap(temp_store_write),
+                doc_end_offsets: Vec::new(),
+            });
+        }
+        Ok(segment_serializer)
+    }
+
     /// The memory used (inclusive childs)
     pub fn mem_usage(&self) -> usize {
-        self.store_writer.mem_usage()
+        let temp_store_mem_usage = self
+            .temp_store_writer
+            .as_ref()
+            .map(|temp_store_writer| {
+                temp_store_writer.doc_end_offsets.capacity() * std::mem::size_of::<u64>()
+            })
+            .unwrap_or(0);
+        let source_mem_usage = self
+            .source_writer
+            .as_ref()
+            .map(StoreWriter::mem_usage)
+            .unwrap_or(0);
+        self.store_writer.mem_usage() + temp_store_mem_usage + source_mem_usage
+    }
+
+    /// Stores a new document.
+    ///
+    /// The document id is implicitly the current number of documents.
+    pub fn store_document<D: Document>(&mut self, document: &D, schema: &Schema) -> io::Result<()> {
+        if let Some(temp_store_writer) = self.temp_store_writer.as_mut() {
+            temp_store_writer.store(document, schema, &self.fields_read_from_columns)
+        } else {
+            self.store_writer.store_skipping_fields(
+                document,
+                schema,
+                &self.fields_read_from_columns,
+            )
+        }
+    }
+
+    /// Stores the raw source of a new document, if the index keeps the sources.
+    ///
+    /// The document id is implicitly the current number of documents. A document without a
+    /// source is stored as an empty entry, and a source is stored after a one byte prefix, so
+    /// that an empty source differs from a missing one.
+    pub fn store_source(&mut self, source: Option<&[u8]>) -> io::Result<()> {
+        if let Some(source_writer) = self.source_writer.as_mut() {
+            match source {
+                Some(source) => {
+                    let mut source_bytes = Vec::with_capacity(1 + source.len());
+                    source_bytes.push(SOURCE_PREFIX);
+                    source_bytes.extend_from_slice(source);
+                    source_writer.store_bytes(&source_bytes)?;
+                }
+                None => source_writer.store_bytes(&[])?,
+            }
+        }
+        Ok(())
     }
 
     pub fn segment(&self) -> &Segment {
@@ -76,6 +226,11 @@ impl SegmentSerializer {
         &mut self.store_writer
     }
 
+    /// Accessor to the `StoreWriter` of the sources, if the index keeps the sources.
+    pub fn get_source_writer(&mut self) -> Option<&mut StoreWriter> {
+        self.source_writer.as_mut()
+    }
+
     /// Finalize the segment serialization.
     pub fn close(mut self) -> crate::Result<()> {
         if let Some(fieldnorms_serializer) = self.extract_fieldnorms_serializer() {
@@ -83,7 +238,13 @@ impl SegmentSerializer {
         }
         self.fast_field_write.terminate()?;
         self.postings_serializer.close()?;
+        if let Some(temp_store_writer) = self.temp_store_writer.take() {
+            temp_store_writer.write_to_store(&self.segment, &mut self.store_writer)?;
+        }
         self.store_writer.close()?;
+        if let Some(source_writer) = self.source_writer {
+            source_writer.close()?;
+        }
         Ok(())
     }
 }
@@ -1,29 +1,37 @@
 use std::any::Any;
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
+use std::num::NonZeroU64;
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
+use common::OwnedBytes;
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use super::segment_manager::SegmentManager;
 use crate::core::META_FILEPATH;
 use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
 use crate::fastfield::AliveBitSet;
-use crate::index::{Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta};
+use crate::index::{
+    merge_common_user_metadata, Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta,
+    SegmentReader, SegmentUserMetadata, UserMetadataMerger,
+};
 use crate::indexer::delete_queue::DeleteCursor;
-use crate::indexer::index_writer::advance_deletes;
+use crate::indexer::index_writer::{advance_deletes, MARGIN_IN_BYTES};
+use crate::indexer::io_throttle::IoThrottle;
 use crate::indexer::merge_operation::MergeOperationInventory;
 use crate::indexer::merger::IndexMerger;
+use crate::indexer::operation::AddOperation;
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
 use crate::indexer::{
-    DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
-    SegmentSerializer,
+    ComputedField, DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
+    SegmentSerializer, SegmentWriter,
 };
+use crate::schema::TantivyDocument;
 use crate::{FutureResult, Opstamp, TantivyError};
 
 const PANIC_CAUGHT: &str = "Panic caught in merge thread";
@@ -82,12 +90,115 @@ fn garbage_collect_files(
         .garbage_collect(move || segment_updater.list_files())
 }
 
+/// The transformation applied to the documents of a merge, see
+/// [`IndexWriter::merge_with_transform`](crate::IndexWriter::merge_with_transform).
+pub(crate) struct MergeTransform {
+    pub transform: Box<dyn Fn(TantivyDocument) -> TantivyDocument + Send>,
+    /// Memory budget of the segment writers reindexing the documents.
+    pub memory_budget: usize,
+    /// The fields computed by the index writer, recomputed for the transformed documents.
+    pub computed_fields: Arc<[ComputedField]>,
+}
+
+/// Reindexes the alive documents of `segments`, rebuilding them from the doc store and
+/// passing them through the transformation.
+///
+/// Like in the indexing threads, the reindexed documents are flushed to a new segment whenever
+/// the memory budget is reached. These segments are then merged, so that all of the documents
+/// end up in a single segment.
+///
+/// Returns the segment holding the documents, and its number of documents.
+fn reindex_segments(
+    segments: &[Segment],
+    new_segment: &dyn Fn() -> Segment,
+    merge_transform: MergeTransform,
+    opstamp: Opstamp,
+) -> crate::Result<(Segment, u32)> {
+    let memory_budget = merge_transform.memory_budget;
+    let new_segment_writer = || -> crate::Result<(Segment, SegmentWriter)> {
+        let segment = new_segment();
+        let segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?
+            .with_computed_fields(merge_transform.computed_fields.clone());
+        Ok((segment, segment_writer))
+    };
+    let finalize = |(segment, segment_writer): (Segment, SegmentWriter)| {
+        let max_doc = segment_writer.max_doc();
+        segment_writer.finalize()?;
+        crate::Result::Ok(segment.with_max_doc(max_doc))
+    };
+    let mut reindexed_segments: Vec<Segment> = Vec::new();
+    let mut current_segment_writer = None;
+    for segment in segments {
+        let segment_reader = SegmentReader::open(segment)?;
+        let store_reader = segment_reader.get_store_reader(1)?;
+        let docs = store_reader.iter::<TantivyDocument>(segment_reader.alive_bitset());
+        for (doc_id, doc) in segment_reader.doc_ids_alive().zip(docs) {
+            let document = (merge_transform.transform)(doc?);
+            let source = segment_reader
+                .source(doc_id)?
+                .map(|source_bytes| source_bytes.as_slice().to_vec());
+            let (_, segment_writer) = match &mut current_segment_writer {
+                Some(current_segment_writer) => current_segment_writer,
+                None => current_segment_writer.insert(new_segment_writer()?),
+            };
+            segment_writer.add_document(AddOperation {
+                opstamp,
+                document,
+                source,
+            })?;
+            if let Some(error) = segment_writer.take_positions_overflow_error() {
+                return Err(error);
+            }
+            if let Some(full_segment_writer) =
+                current_segment_writer.take_if(|(_, segment_writer)| {
+                    segment_writer.mem_usage() >= memory_budget - MARGIN_IN_BYTES
+                })
+            {
+                info!(
+                    "Buffer limit reached, flushing reindexed segment with maxdoc={}.",
+                    full_segment_writer.1.max_doc()
+                );
+                reindexed_segments.push(finalize(full_segment_writer)?);
+            }
+        }
+    }
+    match current_segment_writer {
+        Some(current_segment_writer) => reindexed_segments.push(finalize(current_segment_writer)?),
+        None if reindexed_segments.is_empty() => {
+            reindexed_segments.push(finalize(new_segment_writer()?)?)
+        }
+        None => {}
+    }
+    if let [reindexed_segment] = &reindexed_segments[..] {
+        return Ok((
+            reindexed_segment.clone(),
+            reindexed_segment.meta().max_doc(),
+        ));
+    }
+    let merger = IndexMerger::open(segments[0].schema(), &reindexed_segments[..])?;
+    let merged_segment = new_segment();
+    let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
+    let num_docs = merger.write(segment_serializer)?;
+    Ok((merged_segment, num_docs))
+}
+
+/// Returns the creation opstamp of the segment merging `segments`: the largest of their
+/// creation opstamps, if any is known.
+fn merged_creation_opstamp(segments: &[Segment]) -> Option<Opstamp> {
+    segments
+        .iter()
+        .filter_map(|segment| segment.meta().creation_opstamp())
+        .max()
+}
+
 /// Merges a list of segments the list of segment givens in the `segment_entries`.
 /// This function happens in the calling thread and is computationally expensive.
 fn merge(
     index: &Index,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
+    merge_io_throttle: Option<NonZeroU64>,
+    merge_transform: Option<MergeTransform>,
 ) -> crate::Result<Option<SegmentEntry>> {
     let num_docs = segment_entries
         .iter()
@@ -97,13 +208,20 @@ fn merge(
         return Ok(None);
     }
 
-    // first we need to apply deletes to our segment.
-    let merged_segment = index.new_segment();
+    let io_throttle =
+        merge_io_throttle.map(|bytes_per_sec| Arc::new(IoThrottle::new(bytes_per_sec)));
+    let new_segment = || {
+        let segment = index.new_segment();
+        match &io_throttle {
+            Some(io_throttle) => segment.with_io_throttle(io_throttle.clone()),
+            None => segment,
+        }
+    };
 
     // First we apply all of the delete to the merged segment, up to the target opstamp.
     for segment_entry in &mut segment_entries {
         let segment = index.segment(segment_entry.meta().clone());
-        advance_deletes(segment, segment_entry, target_opstamp)?;
+        advance_deletes(segment, segment_entry, target_opstamp, None)?;
     }
 
     let delete_cursor = segment_entries[0].delete_cursor().clone();
@@ -113,17 +231,26 @@ fn merge(
         .map(|segment_entry| index.segment(segment_entry.meta().clone()))
         .collect();
 
-    // An IndexMerger is like a "view" of our merged segments.
-    let merger: IndexMerger = IndexMerger::open(index.schema(), &segments[..])?;
+    let (merged_segment, num_docs) = if let Some(merge_transform) = merge_transform {
+        reindex_segments(&segments[..], &new_segment, merge_transform, target_opstamp)?
+    } else {
+        let merged_segment = new_segment();
 
-    // ... we just serialize this index merger in our new segment to merge the segments.
-    let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
+        // An IndexMerger is like a "view" of our merged segments.
+        let merger: IndexMerger = IndexMerger::open(index.schema(), &segments[..])?;
 
-    let num_docs = merger.write(segment_serializer)?;
+        // ... we just serialize this index merger in our new segment to merge the segments.
+        let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
+
+        (merged_segment, merger.write(segment_serializer)?)
+    };
 
     let merged_segment_id = merged_segment.id();
 
-    let segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
+    let mut segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
+    if let Some(creation_opstamp) = merged_creation_opstamp(&segments) {
+        segment_meta = segment_meta.with_creation_opstamp(creation_opstamp);
+    }
     Ok(Some(SegmentEntry::new(segment_meta, delete_cursor, None)))
 }
 
@@ -223,7 +350,16 @@ pub fn merge_filtered_segments<T: Into<Box<dyn Directory>>>(
     let segment_serializer = SegmentSerializer::for_segment(merged_segment)?;
     let num_docs = merger.write(segment_serializer)?;
 
-    let segment_meta = merged_index.new_segment_meta(merged_segment_id, num_docs);
+    let segments_user_metadata: Vec<&SegmentUserMetadata> = segments
+        .iter()
+        .map(|segment| segment.meta().user_metadata())
+        .collect();
+    let mut segment_meta = merged_index
+        .new_segment_meta(merged_segment_id, num_docs)
+        .with_user_metadata(merge_common_user_metadata(&segments_user_metadata));
+    if let Some(creation_opstamp) = merged_creation_opstamp(segments) {
+        segment_meta = segment_meta.with_creation_opstamp(creation_opstamp);
+    }
 
     let stats = format!(
         "Segments Merge: [{}]",
@@ -264,9 +400,15 @@ pub(crate) struct InnerSegmentUpdater {
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Arc<dyn MergePolicy>>,
+    merge_io_throttle: RwLock<Option<NonZeroU64>>,
+    user_metadata_merger: RwLock<Arc<UserMetadataMerger>>,
+    // While true, the merge policy is not consulted, so no merge gets scheduled.
+    merges_paused: AtomicBool,
     killed: AtomicBool,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
+    // Alive bitsets to apply to the segments at the next commit.
+    applied_alive_bitsets: Mutex<HashMap<SegmentId, AliveBitSet>>,
 }
 
 impl SegmentUpdater {
@@ -313,9 +455,13 @@ impl SegmentUpdater {
             index,
             segment_manager,
             merge_policy: RwLock::new(Arc::new(DefaultMergePolicy::default())),
+            merge_io_throttle: RwLock::new(None),
+            user_metadata_merger: RwLock::new(Arc::new(merge_common_user_metadata)),
+            merges_paused: AtomicBool::new(false),
             killed: AtomicBool::new(false),
             stamper,
             merge_operations: Default::default(),
+            applied_alive_bitsets: Default::default(),
         })))
     }
 
@@ -328,6 +474,69 @@ impl SegmentUpdater {
         *self.merge_policy.write().unwrap() = arc_merge_policy;
     }
 
+    pub fn merge_io_throttle(&self) -> Option<NonZeroU64> {
+        *self.merge_io_throttle.read().unwrap()
+    }
+
+    pub fn set_merge_io_throttle(&self, bytes_per_sec: Option<NonZeroU64>) {
+        *self.merge_io_throttle.write().unwrap() = bytes_per_sec;
+    }
+
+    pub fn user_metadata_merger(&self) -> Arc<UserMetadataMerger> {
+        self.user_metadata_merger.read().unwrap().clone()
+    }
+
+    pub fn set_user_metadata_merger(&self, user_metadata_merger: Arc<UserMetadataMerger>) {
+        *self.user_metadata_merger.write().unwrap() = user_metadata_merger;
+    }
+
+    /// Replaces the user metadata of a segment, committed or uncommitted. The update is queued
+    /// with the merges ending, so that a merge running on the segment picks it up.
+    pub fn schedule_set_user_metadata(
+        &self,
+        segment_id: SegmentId,
+        user_metadata: SegmentUserMetadata,
+    ) -> FutureResult<()> {
+        let segment_updater = self.clone();
+        self.schedule_task(move || {
+            if segment_updater
+                .segment_manager
+                .set_user_metadata(segment_id, user_metadata)
+            {
+                Ok(())
+            } else {
+                Err(TantivyError::InvalidArgument(format!(
+                    "The index has no segment {}",
+                    segment_id.short_uuid_string()
+                )))
+            }
+        })
+    }
+
+    pub fn merges_paused(&self) -> bool {
+        self.merges_paused.load(Ordering::Acquire)
+    }
+
+    /// Pauses the scheduling of merges. The returned future resolves once the tasks
+    /// queued before the call are processed, so that no merge gets scheduled after that.
+    pub fn schedule_pause_merges(&self) -> FutureResult<()> {
+        self.merges_paused.store(true, Ordering::Release);
+        self.schedule_task(|| Ok(()))
+    }
+
+    /// Resumes the scheduling of merges, and lets the merge policy consider the segments
+    /// accumulated while the merges were paused.
+    pub fn schedule_resume_merges(&self) -> FutureResult<()> {
+        let segment_updater = self.clone();
+        self.schedule_task(move || {
+            segment_updater
+                .merges_paused
+                .store(false, Ordering::Release);
+            segment_updater.consider_merge_options();
+            Ok(())
+        })
+    }
+
     fn schedule_task<T: 'static + Send, F: FnOnce() -> crate::Result<T> + 'static + Send>(
         &self,
         task: F,
@@ -372,14 +581,54 @@ impl SegmentUpdater {
     /// The method returns copies of the segment entries,
     /// updated with the delete information.
     fn purge_deletes(&self, target_opstamp: Opstamp) -> crate::Result<Vec<SegmentEntry>> {
+        let mut applied_alive_bitsets =
+            std::mem::take(&mut *self.applied_alive_bitsets.lock().unwrap());
         let mut segment_entries = self.segment_manager.segment_entries();
         for segment_entry in &mut segment_entries {
             let segment = self.index.segment(segment_entry.meta().clone());
-            advance_deletes(segment, segment_entry, target_opstamp)?;
+            let applied_alive_bitset = applied_alive_bitsets.remove(&segment_entry.segment_id());
+            advance_deletes(
+                segment,
+                segment_entry,
+                target_opstamp,
+                applied_alive_bitset.as_ref(),
+            )?;
+        }
+        for segment_id in applied_alive_bitsets.keys() {
+            warn!(
+                "The delete bitset applied to segment {} was discarded, as the segment was merged",
+                segment_id.short_uuid_string()
+            );
         }
         Ok(segment_entries)
     }
 
+    /// Registers the alive bitset of a segment, applied at the next commit.
+    pub(crate) fn apply_alive_bitset(
+        &self,
+        segment_id: SegmentId,
+        alive_bitset_bytes: OwnedBytes,
+    ) -> crate::Result<()> {
+        let segment_entry = self
+            .segment_manager
+            .segment_entries()
+            .into_iter()
+            .find(|segment_entry| segment_entry.segment_id() == segment_id)
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The index has no segment {}",
+                    segment_id.short_uuid_string()
+                ))
+            })?;
+        let alive_bitset =
+            AliveBitSet::open_checked(alive_bitset_bytes, segment_entry.meta().max_doc())?;
+        self.applied_alive_bitsets
+            .lock()
+            .unwrap()
+            .insert(segment_id, alive_bitset);
+        Ok(())
+    }
+
     pub fn save_metas(
         &self,
         opstamp: Opstamp,
@@ -462,6 +711,11 @@ impl SegmentUpdater {
         self.active_index_meta.read().unwrap().clone()
     }
 
+    /// Returns the metas of the segments, committed or uncommitted, among `segment_ids`.
+    pub(crate) fn segment_metas(&self, segment_ids: &[SegmentId]) -> Vec<SegmentMeta> {
+        self.segment_manager.segment_metas(segment_ids)
+    }
+
     pub(crate) fn make_merge_operation(&self, segment_ids: &[SegmentId]) -> MergeOperation {
         let commit_opstamp = self.load_meta().opstamp;
         MergeOperation::new(&self.merge_operations, commit_opstamp, segment_ids.to_vec())
@@ -487,6 +741,16 @@ impl SegmentUpdater {
     pub fn start_merge(
         &self,
         merge_operation: MergeOperation,
+    ) -> FutureResult<Option<SegmentMeta>> {
+        self.start_merge_with_transform(merge_operation, None)
+    }
+
+    // Same as `start_merge`, except that with a `merge_transform`, the documents are reindexed
+    // after being passed through the transformation.
+    pub(crate) fn start_merge_with_transform(
+        &self,
+        merge_operation: MergeOperation,
+        merge_transform: Option<MergeTransform>,
     ) -> FutureResult<Option<SegmentMeta>> {
         assert!(
             !merge_operation.segment_ids().is_empty(),
@@ -494,6 +758,7 @@ impl SegmentUpdater {
         );
 
         let segment_updater = self.clone();
+        let merge_io_throttle = self.merge_io_throttle();
         let segment_entries: Vec<SegmentEntry> = match self
             .segment_manager
             .start_merge(merge_operation.segment_ids())
@@ -523,6 +788,8 @@ impl SegmentUpdater {
                     &segment_updater.index,
                     segment_entries,
                     merge_operation.target_opstamp(),
+                    merge_io_throttle,
+                    merge_transform,
                 )
             }));
             let merge_res = match merge_panic_res {
@@ -574,6 +841,9 @@ impl SegmentUpdater {
     }
 
     fn consider_merge_options(&self) {
+        if self.merges_paused() {
+            return;
+        }
         let (mut committed_segments, mut uncommitted_segments) = self.get_mergeable_segments();
         if committed_segments.len() == 1 && committed_segments[0].num_deleted_docs() == 0 {
             committed_segments.clear();
@@ -618,15 +888,12 @@ impl SegmentUpdater {
         mut after_merge_segment_entry: Option<SegmentEntry>,
     ) -> crate::Result<Option<SegmentMeta>> {
         let segment_updater = self.clone();
-        let after_merge_segment_meta = after_merge_segment_entry
-            .as_ref()
-            .map(|after_merge_segment_entry| after_merge_segment_entry.meta().clone());
         self.schedule_task(move || {
             info!(
                 "End merge {:?}",
                 after_merge_segment_entry.as_ref().map(|entry| entry.meta())
             );
-            {
+            let after_merge_segment_meta = {
                 if let Some(after_merge_segment_entry) = after_merge_segment_entry.as_mut() {
                     // Deletes and commits could have happened as we were merging.
                     // We need to make sure we are up to date with deletes before accepting the
@@ -643,6 +910,7 @@ impl SegmentUpdater {
                                 segment,
                                 after_merge_segment_entry,
                                 committed_opstamp,
+                                None,
                             ) {
                                 error!(
                                     "Merge of {:?} was cancelled (advancing deletes failed): {:?}",
@@ -659,6 +927,27 @@ impl SegmentUpdater {
                         }
                     }
                 }
+                if let Some(after_merge_segment_entry) = after_merge_segment_entry.as_mut() {
+                    // The user metadata is merged here rather than in the merging thread, to
+                    // account for the updates made during the merge.
+                    let before_merge_segment_metas = segment_updater
+                        .segment_manager
+                        .segment_metas(merge_operation.segment_ids());
+                    let segments_user_metadata: Vec<&SegmentUserMetadata> =
+                        before_merge_segment_metas
+                            .iter()
+                            .map(SegmentMeta::user_metadata)
+                            .collect();
+                    let user_metadata_merger = segment_updater.user_metadata_merger();
+                    let segment_meta = after_merge_segment_entry
+                        .meta()
+                        .clone()
+                        .with_user_metadata(user_metadata_merger(&segments_user_metadata));
+                    after_merge_segment_entry.set_meta(segment_meta);
+                }
+                let after_merge_segment_meta = after_merge_segment_entry
+                    .as_ref()
+                    .map(|after_merge_segment_entry| after_merge_segment_entry.meta().clone());
                 let previous_metas = segment_updater.load_meta();
                 let segments_status = segment_updater
                     .segment_manager
@@ -670,13 +959,13 @@ impl SegmentUpdater {
                 }
 
                 segment_updater.consider_merge_options();
-            } // we drop all possible handle to a now useless `SegmentMeta`.
+                after_merge_segment_meta
+            }; // we drop all possible handle to a now useless `SegmentMeta`.
 
             let _ = garbage_collect_files(segment_updater);
-            Ok(())
+            Ok(after_merge_segment_meta)
         })
-        .wait()?;
-        Ok(after_merge_segment_meta)
+        .wait()
     }
 
     /// Wait for current merging threads.
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use columnar::MonotonicallyMappableToU64;
 use common::JsonPathWriter;
 use itertools::Itertools;
@@ -7,8 +9,10 @@ use super::operation::AddOperation;
 use crate::fastfield::FastFieldsWriter;
 use crate::fieldnorm::{FieldNormReaders, FieldNormsWriter};
 use crate::index::{Segment, SegmentComponent};
+use crate::indexer::computed_field::ComputedField;
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::json_utils::{index_json_value, IndexingPositionsPerPath};
+use crate::positions::PositionsOverflowPolicy;
 use crate::postings::{
     compute_table_memory_size, serialize_postings, IndexingContext, IndexingPosition,
     PerFieldPostingsWriter, PostingsWriter,
@@ -55,8 +59,12 @@ pub struct SegmentWriter {
     pub(crate) json_positions_per_path: IndexingPositionsPerPath,
     pub(crate) doc_opstamps: Vec<Opstamp>,
     per_field_text_analyzers: Vec<TextAnalyzer>,
+    positions_overflow_policy: PositionsOverflowPolicy,
+    // Set under `PositionsOverflowPolicy::Error`, see `take_positions_overflow_error`.
+    positions_overflow_error: Option<TantivyError>,
     term_buffer: Term,
     schema: Schema,
+    computed_fields: Arc<[ComputedField]>,
 }
 
 impl SegmentWriter {
@@ -73,9 +81,16 @@ impl SegmentWriter {
         let schema = segment.schema();
         let tokenizer_manager = segment.index().tokenizers().clone();
         let tokenizer_manager_fast_field = segment.index().fast_field_tokenizer().clone();
+        let fast_field_codecs = segment.index().fast_field_codecs().clone();
+        let positions_overflow_policy = segment.index().settings().positions_overflow_policy;
         let table_size = compute_initial_table_size(memory_budget_in_bytes)?;
-        let segment_serializer = SegmentSerializer::for_segment(segment)?;
+        let segment_serializer = SegmentSerializer::for_indexing(segment)?;
         let per_field_postings_writers = PerFieldPostingsWriter::for_schema(&schema);
+        let mut fast_field_writers = FastFieldsWriter::from_schema_and_tokenizer_manager(
+            &schema,
+            tokenizer_manager_fast_field,
+        )?;
+        fast_field_writers.set_fast_field_codecs(fast_field_codecs);
         let per_field_text_analyzers = schema
             .fields()
             .map(|(_, field_entry): (_, &FieldEntry)| {
@@ -106,17 +121,28 @@ impl SegmentWriter {
             json_path_writer: JsonPathWriter::default(),
             json_positions_per_path: IndexingPositionsPerPath::default(),
             segment_serializer,
-            fast_field_writers: FastFieldsWriter::from_schema_and_tokenizer_manager(
-                &schema,
-                tokenizer_manager_fast_field,
-            )?,
+            fast_field_writers,
             doc_opstamps: Vec::with_capacity(1_000),
             per_field_text_analyzers,
+            positions_overflow_policy,
+            positions_overflow_error: None,
             term_buffer: Term::with_capacity(16),
             schema,
+            computed_fields: Arc::new([]),
         })
     }
 
+    /// Sets the fields computed for every added document.
+    ///
+    /// The computed values replace the values of the target fields of the added documents.
+    pub(crate) fn with_computed_fields(
+        mut self,
+        computed_fields: Arc<[ComputedField]>,
+    ) -> SegmentWriter {
+        self.computed_fields = computed_fields;
+        self
+    }
+
     /// Lay on disk the current content of the `SegmentWriter`
     ///
     /// Finalize consumes the `SegmentWriter`, so that it cannot
@@ -214,6 +240,11 @@ impl SegmentWriter {
                             &mut indexing_position,
                         );
                     }
+                    if indexing_position.positions_overflow {
+                        if let Some(error) = self.handle_positions_overflow(field_entry) {
+                            self.positions_overflow_error.get_or_insert(error);
+                        }
+                    }
                     if field_entry.has_fieldnorms() {
                         self.fieldnorms_writer
                             .record(doc_id, field, indexing_position.num_tokens);
@@ -322,6 +353,11 @@ impl SegmentWriter {
                             &mut self.json_positions_per_path,
                         );
                     }
+                    if self.json_positions_per_path.positions_overflow() {
+                        if let Some(error) = self.handle_positions_overflow(field_entry) {
+                            self.positions_overflow_error.get_or_insert(error);
+                        }
+                    }
                 }
                 FieldType::IpAddr(_) => {
                     let mut num_vals = 0;
@@ -342,6 +378,38 @@ impl SegmentWriter {
         Ok(())
     }
 
+    /// Applies the [`PositionsOverflowPolicy`] to the document being indexed, some of whose
+    /// tokens for `field_entry` were dropped because their position exceeded `u32::MAX`.
+    ///
+    /// Returns the error to report under [`PositionsOverflowPolicy::Error`]. Either way, the
+    /// document is indexed truncated.
+    fn handle_positions_overflow(&self, field_entry: &FieldEntry) -> Option<TantivyError> {
+        let opstamp = self.doc_opstamps.last().copied().unwrap_or_default();
+        match self.positions_overflow_policy {
+            PositionsOverflowPolicy::Truncate => {
+                warn!(
+                    "The positions of the field {:?} of the document with opstamp {opstamp} \
+                     exceed u32::MAX. The text of the field was truncated.",
+                    field_entry.name()
+                );
+                None
+            }
+            PositionsOverflowPolicy::Error => Some(TantivyError::InvalidArgument(format!(
+                "The positions of the field {:?} of the document with opstamp {opstamp} exceed \
+                 u32::MAX",
+                field_entry.name()
+            ))),
+        }
+    }
+
+    /// Returns the error of the first document added since the last call that exceeded the
+    /// positions limit under [`PositionsOverflowPolicy::Error`].
+    ///
+    /// The document was still added, truncated: the segment writer remains usable.
+    pub(crate) fn take_positions_overflow_error(&mut self) -> Option<TantivyError> {
+        self.positions_overflow_error.take()
+    }
+
     /// Indexes a new document
     ///
     /// As a user, you should rather use `IndexWriter`'s add_document.
@@ -349,12 +417,18 @@ impl SegmentWriter {
         &mut self,
         add_operation: AddOperation<D>,
     ) -> crate::Result<()> {
-        let AddOperation { document, opstamp } = add_operation;
+        let AddOperation {
+            document,
+            opstamp,
+            source,
+        } = add_operation;
         self.doc_opstamps.push(opstamp);
-        self.fast_field_writers.add_document(&document)?;
+        self.fast_field_writers
+            .add_document_with_computed_fields(&document, &self.computed_fields)?;
         self.index_document(&document)?;
-        let doc_writer = self.segment_serializer.get_store_writer();
-        doc_writer.store(&document, &self.schema)?;
+        self.segment_serializer
+            .store_document(&document, &self.schema)?;
+        self.segment_serializer.store_source(source.as_deref())?;
         self.max_doc += 1;
         Ok(())
     }
@@ -428,18 +502,19 @@ mod tests {
     use crate::collector::{Count, TopDocs};
     use crate::directory::RamDirectory;
     use crate::fastfield::FastValue;
+    use crate::positions::PositionsOverflowPolicy;
     use crate::postings::{Postings, TermInfo};
     use crate::query::{PhraseQuery, QueryParser};
     use crate::schema::{
-        Document, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions, Value,
-        DATE_TIME_PRECISION_INDEXED, FAST, STORED, STRING, TEXT,
+        Document, Field, IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions,
+        Value, DATE_TIME_PRECISION_INDEXED, FAST, STORED, STRING, TEXT,
     };
     use crate::store::{Compressor, StoreReader, StoreWriter};
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
     use crate::tokenizer::{PreTokenizedString, Token};
     use crate::{
-        DateTime, Directory, DocAddress, DocSet, Index, IndexWriter, SegmentReader,
+        DateTime, Directory, DocAddress, DocSet, Index, IndexSettings, IndexWriter, SegmentReader,
         TantivyDocument, Term, TERMINATED,
     };
 
@@ -1055,6 +1130,100 @@ mod tests {
         assert_eq!(positions, &[4]); //< as opposed to 3 if we had a position length of 1.
     }
 
+    fn positions_overflow_doc(text: Field) -> TantivyDocument {
+        let token = |position: usize, text: &str| Token {
+            offset_from: 0,
+            offset_to: 1,
+            position,
+            text: text.to_string(),
+            position_length: 1,
+        };
+        let limit = u32::MAX as usize;
+        let tokens = PreTokenizedString {
+            text: "synthetic".to_string(),
+            tokens: vec![
+                token(0, "first"),
+                token(limit - 1, "last"),
+                token(limit, "overflow"),
+                token(limit + 10, "overflow"),
+            ],
+        };
+        let mut doc = TantivyDocument::default();
+        doc.add_pre_tokenized_text(text, tokens);
+        doc.add_text(text, "next");
+        doc
+    }
+
+    #[test]
+    fn test_positions_overflow_truncate() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert_eq!(
+            index.settings().positions_overflow_policy,
+            PositionsOverflowPolicy::Truncate
+        );
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(positions_overflow_doc(text))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let seg_reader = searcher.segment_reader(0);
+        let inv_index = seg_reader.inverted_index(text)?;
+        let mut postings = inv_index
+            .read_postings(
+                &Term::from_field_text(text, "last"),
+                IndexRecordOption::WithFreqsAndPositions,
+            )?
+            .unwrap();
+        let mut positions = Vec::new();
+        postings.positions(&mut positions);
+        assert_eq!(positions, &[u32::MAX - 1]);
+        for dropped in ["overflow", "next"] {
+            assert_eq!(
+                inv_index.doc_freq(&Term::from_field_text(text, dropped))?,
+                0
+            );
+        }
+        let fieldnorm_reader = seg_reader.get_fieldnorms_reader(text)?;
+        assert_eq!(fieldnorm_reader.fieldnorm(0), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_positions_overflow_error() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                positions_overflow_policy: PositionsOverflowPolicy::Error,
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let opstamp = index_writer.add_document(positions_overflow_doc(text))?;
+        let expected_error = format!(
+            "An invalid argument was passed: 'The positions of the field \"text\" of the document \
+             with opstamp {opstamp} exceed u32::MAX'"
+        );
+        assert_eq!(
+            index_writer.commit().unwrap_err().to_string(),
+            expected_error
+        );
+        // The indexing workers are still running, but the commits fail until the rollback.
+        index_writer.add_document(doc!(text => "valid"))?;
+        assert_eq!(
+            index_writer.commit().unwrap_err().to_string(),
+            expected_error
+        );
+        index_writer.rollback()?;
+        index_writer.add_document(doc!(text => "valid"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_show_error_when_tokenizer_not_registered() {
         let text_field_indexing = TextFieldIndexing::default()
@@ -1082,4 +1251,53 @@ mod tests {
             "Schema error: 'Error getting tokenizer for field: title'"
         );
     }
+
+    #[test]
+    fn test_docstore_temp_file() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST | STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let directory = RamDirectory::create();
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                docstore_temp_file: true,
+                ..Default::default()
+            })
+            .open_or_create(directory.clone())?;
+        let large_body = |doc_id: u64| format!("{doc_id}").repeat(200_000);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc_id in 0..5u64 {
+            index_writer.add_document(doc!(id => doc_id, body => large_body(doc_id)))?;
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => 5u64, body => "small"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        for segment_reader in searcher.segment_readers() {
+            let store_reader = segment_reader.get_store_reader(0)?;
+            for doc_id in segment_reader.doc_ids_alive() {
+                let doc: TantivyDocument = store_reader.get(doc_id)?;
+                let id_value = doc.get_first(id).and_then(|value| value.as_u64()).unwrap();
+                let expected_body = if id_value == 5 {
+                    "small".to_string()
+                } else {
+                    large_body(id_value)
+                };
+                assert_eq!(
+                    doc.get_first(body).and_then(|value| value.as_str()),
+                    Some(expected_body.as_str())
+                );
+            }
+            // The temporary store file is deleted once the segment is finalized.
+            let temp_store_path = PathBuf::from(format!(
+                "{}.store.temp",
+                segment_reader.segment_id().uuid_string()
+            ));
+            assert!(!directory.exists(&temp_store_path)?);
+        }
+        Ok(())
+    }
 }
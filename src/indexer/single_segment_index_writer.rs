@@ -33,8 +33,15 @@ impl<D: Document> SingleSegmentIndexWriter<D> {
     pub fn add_document(&mut self, document: D) -> crate::Result<()> {
         let opstamp = self.opstamp;
         self.opstamp += 1;
-        self.segment_writer
-            .add_document(AddOperation { opstamp, document })
+        self.segment_writer.add_document(AddOperation {
+            opstamp,
+            document,
+            source: None,
+        })?;
+        match self.segment_writer.take_positions_overflow_error() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
     }
 
     pub fn finalize(self) -> crate::Result<Index> {
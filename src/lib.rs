@@ -211,18 +211,18 @@ pub mod snippet;
 use std::fmt;
 
 pub use census::{Inventory, TrackedObject};
-pub use common::{f64_to_u64, i64_to_u64, u64_to_f64, u64_to_i64, HasLen};
+pub use common::{f64_to_u64, i64_to_u64, u64_to_f64, u64_to_i64, BitSet, HasLen};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
 pub use self::docset::{DocSet, COLLECT_BLOCK_BUFFER_LEN, TERMINATED};
 #[doc(hidden)]
 pub use crate::core::json_utils;
-pub use crate::core::{Executor, Searcher, SearcherGeneration};
+pub use crate::core::{Executor, Searcher, SearcherGeneration, SegmentHits, SortedDocStream};
 pub use crate::directory::Directory;
 pub use crate::index::{
-    Index, IndexBuilder, IndexMeta, IndexSettings, InvertedIndexReader, Order, Segment,
-    SegmentMeta, SegmentReader,
+    Index, IndexBuilder, IndexMeta, IndexSettings, InvertedIndexReader, MissingValuePolicy, Order,
+    Segment, SegmentMeta, SegmentMetaInfo, SegmentReader, SegmentUserMetadata,
 };
 pub use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
 pub use crate::schema::{Document, TantivyDocument, Term};
@@ -281,7 +281,8 @@ pub fn version_string() -> &'static str {
 /// Defines tantivy's merging strategy
 pub mod merge_policy {
     pub use crate::indexer::{
-        DefaultMergePolicy, LogMergePolicy, MergeCandidate, MergePolicy, NoMergePolicy,
+        CustomMergePolicy, DefaultMergePolicy, LogMergePolicy, MaxFileCountMergePolicy,
+        MergeCandidate, MergeMode, MergePolicy, NoMergePolicy,
     };
 }
 
@@ -28,15 +28,165 @@
 //! * *VIntPosDeltas* := *VIntPosDelta*^(*P* % 128).
 //!
 //! The skip widths encoded separately makes it easy and fast to rapidly skip over n positions.
+//!
+//! The block length and the handling of the final incomplete block can be tuned using
+//! [`PositionsCompression`]. When the block length differs from the default, it is recorded
+//! in a small header stored next to the field's positions in the composite file, so that
+//! readers do not depend on the index settings.
 mod reader;
 mod serializer;
 
-use bitpacking::{BitPacker, BitPacker4x};
+use std::io;
+
+use common::{BinarySerializable, VInt};
+use serde::{Deserialize, Serialize};
 
 pub use self::reader::PositionReader;
 pub use self::serializer::PositionSerializer;
+use crate::directory::FileSlice;
+use crate::postings::compression::{COMPRESSION_BLOCK_SIZE, SMALL_COMPRESSION_BLOCK_SIZE};
+
+/// Index, within the positions composite file, of the header describing
+/// how the positions of a field were encoded.
+///
+/// The header is omitted when the default encoding is used.
+pub(crate) const POSITIONS_HEADER_IDX: usize = 1;
+
+/// Parameters of the encoding of positions.
+///
+/// Positions are often the largest component of an index containing long texts.
+/// Smaller blocks adapt better to the local distribution of the position deltas,
+/// which usually makes for a smaller index, at the cost of slower decoding.
+///
+/// ```rust
+/// use tantivy::positions::PositionsCompression;
+/// use tantivy::IndexSettings;
+///
+/// let settings = IndexSettings {
+///     positions_compression: PositionsCompression {
+///         block_len: 32,
+///         bitpacking_threshold: Some(8),
+///     },
+///     ..Default::default()
+/// };
+/// assert!(settings.positions_compression.validate().is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PositionsCompression {
+    /// Number of position deltas in each bitpacked block.
+    ///
+    /// Only `128` (the default) and `32` are supported.
+    #[serde(default = "default_block_len")]
+    pub block_len: usize,
+    /// Minimum number of position deltas in the final, incomplete, block of a term for it
+    /// to be padded and bitpacked rather than encoded using variable length integers.
+    ///
+    /// Must be within `1..=block_len`. `None` (the default) means that incomplete blocks are
+    /// always encoded using variable length integers.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitpacking_threshold: Option<usize>,
+}
+
+fn default_block_len() -> usize {
+    COMPRESSION_BLOCK_SIZE
+}
+
+impl Default for PositionsCompression {
+    fn default() -> Self {
+        PositionsCompression {
+            block_len: default_block_len(),
+            bitpacking_threshold: None,
+        }
+    }
+}
+
+impl PositionsCompression {
+    /// Returns true if these are the default parameters.
+    pub fn is_default(&self) -> bool {
+        *self == PositionsCompression::default()
+    }
+
+    /// Checks that the parameters are supported.
+    pub fn validate(&self) -> crate::Result<()> {
+        if self.block_len != COMPRESSION_BLOCK_SIZE
+            && self.block_len != SMALL_COMPRESSION_BLOCK_SIZE
+        {
+            return Err(crate::TantivyError::InvalidArgument(format!(
+                "Unsupported positions block length {}. Supported values are \
+                 {COMPRESSION_BLOCK_SIZE} and {SMALL_COMPRESSION_BLOCK_SIZE}.",
+                self.block_len
+            )));
+        }
+        if let Some(bitpacking_threshold) = self.bitpacking_threshold {
+            if bitpacking_threshold == 0 || bitpacking_threshold > self.block_len {
+                return Err(crate::TantivyError::InvalidArgument(format!(
+                    "Positions bitpacking threshold must be within 1..={}, got \
+                     {bitpacking_threshold}.",
+                    self.block_len
+                )));
+            }
+        }
+        Ok(())
+    }
 
-const COMPRESSION_BLOCK_SIZE: usize = BitPacker4x::BLOCK_LEN;
+    /// Number of deltas in an incomplete block from which the block gets bitpacked.
+    pub(crate) fn effective_bitpacking_threshold(&self) -> usize {
+        self.bitpacking_threshold.unwrap_or(self.block_len)
+    }
+
+    /// Serializes the header describing the encoding of a field's positions.
+    pub(crate) fn serialize_header<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.block_len as u64).serialize(writer)
+    }
+}
+
+/// Defines what happens when the positions of a field of a document exceed `u32::MAX`.
+///
+/// Positions are expressed as `u32` token ordinals, and the positions of all of the values of
+/// a field in a document are laid out one after the other. Concatenating extremely long
+/// texts in a document can therefore overflow the position space of a field.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionsOverflowPolicy {
+    /// The tokens past the limit are dropped, and a warning is logged.
+    ///
+    /// The document is indexed as if its text stopped at the limit: the dropped tokens are
+    /// not searchable and are not counted in its fieldnorm.
+    #[default]
+    Truncate,
+    /// The document is truncated as with `Truncate`, and an error naming the document
+    /// opstamp and the field is reported.
+    ///
+    /// Documents are indexed in the background, so the indexing threads keep running and
+    /// the error is returned by [`IndexWriter::commit`](crate::IndexWriter::commit). The
+    /// commits then fail until [`IndexWriter::rollback`](crate::IndexWriter::rollback)
+    /// discards the documents added since the last commit.
+    Error,
+}
+
+impl PositionsOverflowPolicy {
+    /// Returns true if this is the default policy.
+    pub fn is_default(&self) -> bool {
+        *self == PositionsOverflowPolicy::default()
+    }
+}
+
+/// Reads the positions block length from the optional positions header of a field.
+pub(crate) fn read_positions_block_len(header_opt: Option<FileSlice>) -> io::Result<usize> {
+    let Some(header) = header_opt else {
+        return Ok(COMPRESSION_BLOCK_SIZE);
+    };
+    let header_bytes = header.read_bytes()?;
+    let block_len = VInt::deserialize(&mut header_bytes.as_slice())?.0 as usize;
+    if block_len != COMPRESSION_BLOCK_SIZE && block_len != SMALL_COMPRESSION_BLOCK_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported positions block length {block_len}"),
+        ));
+    }
+    Ok(block_len)
+}
 
 #[cfg(test)]
 pub(crate) mod tests {
@@ -46,19 +196,40 @@ pub(crate) mod tests {
     use proptest::prelude::*;
     use proptest::sample::select;
 
-    use super::PositionSerializer;
+    use super::{PositionSerializer, PositionsCompression};
+    use crate::collector::Count;
     use crate::directory::OwnedBytes;
     use crate::positions::reader::PositionReader;
+    use crate::query::PhraseQuery;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, Term};
 
     fn create_positions_data(vals: &[u32]) -> crate::Result<OwnedBytes> {
+        create_positions_data_with_compression(vals, PositionsCompression::default())
+    }
+
+    fn create_positions_data_with_compression(
+        vals: &[u32],
+        compression: PositionsCompression,
+    ) -> crate::Result<OwnedBytes> {
         let mut positions_buffer = vec![];
-        let mut serializer = PositionSerializer::new(&mut positions_buffer);
+        let mut serializer =
+            PositionSerializer::with_compression(&mut positions_buffer, compression);
         serializer.write_positions_delta(vals);
         serializer.close_term()?;
         serializer.close()?;
         Ok(OwnedBytes::new(positions_buffer))
     }
 
+    fn gen_positions_compression() -> BoxedStrategy<PositionsCompression> {
+        (select(&[32usize, 128usize][..]), 0usize..=128usize)
+            .prop_map(|(block_len, threshold)| PositionsCompression {
+                block_len,
+                bitpacking_threshold: Some(threshold % block_len + 1),
+            })
+            .boxed()
+    }
+
     fn gen_delta_positions() -> BoxedStrategy<Vec<u32>> {
         select(&[0, 1, 70, 127, 128, 129, 200, 255, 256, 257, 270][..])
             .prop_flat_map(|num_delta_positions| {
@@ -83,6 +254,119 @@ pub(crate) mod tests {
         }
     }
 
+    proptest! {
+        #[test]
+        fn test_position_delta_with_compression(
+            delta_positions in gen_delta_positions(),
+            compression in gen_positions_compression(),
+        ) {
+            let delta_positions_data =
+                create_positions_data_with_compression(&delta_positions, compression).unwrap();
+            let mut position_reader =
+                PositionReader::open_with_block_len(delta_positions_data, compression.block_len)
+                    .unwrap();
+            let mut minibuf = [0u32; 1];
+            for (offset, &delta_position) in delta_positions.iter().enumerate() {
+                position_reader.read(offset as u64, &mut minibuf[..]);
+                assert_eq!(delta_position, minibuf[0]);
+            }
+            let mut all_positions = vec![0u32; delta_positions.len()];
+            position_reader.read(0, &mut all_positions[..]);
+            assert_eq!(&all_positions, &delta_positions);
+        }
+    }
+
+    #[test]
+    fn test_positions_compression_validate() {
+        assert!(PositionsCompression::default().validate().is_ok());
+        let small_blocks = PositionsCompression {
+            block_len: 32,
+            bitpacking_threshold: Some(32),
+        };
+        assert!(small_blocks.validate().is_ok());
+        let invalid_block_len = PositionsCompression {
+            block_len: 64,
+            bitpacking_threshold: None,
+        };
+        assert!(invalid_block_len.validate().is_err());
+        let threshold_too_large = PositionsCompression {
+            block_len: 32,
+            bitpacking_threshold: Some(33),
+        };
+        assert!(threshold_too_large.validate().is_err());
+        let threshold_zero = PositionsCompression {
+            block_len: 128,
+            bitpacking_threshold: Some(0),
+        };
+        assert!(threshold_zero.validate().is_err());
+        let schema = Schema::builder().build();
+        let settings = IndexSettings {
+            positions_compression: invalid_block_len,
+            ..Default::default()
+        };
+        assert!(Index::builder()
+            .schema(schema)
+            .settings(settings)
+            .create_in_ram()
+            .is_err());
+    }
+
+    fn positions_num_bytes_for_compression(
+        positions_compression: PositionsCompression,
+    ) -> crate::Result<u64> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let settings = IndexSettings {
+            positions_compression,
+            ..Default::default()
+        };
+        let index = Index::builder()
+            .schema(schema)
+            .settings(settings)
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // Each term appears 100 times, so that most of its positions end up in
+        // the final incomplete block.
+        let body: String = (0..20).map(|word_id| format!("word{word_id} ")).collect();
+        for _ in 0..100 {
+            index_writer.add_document(doc!(text => body.as_str()))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text, "word3"),
+            Term::from_field_text(text, "word4"),
+        ]);
+        assert_eq!(searcher.search(&phrase_query, &Count)?, 100);
+        let reversed_phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text, "word4"),
+            Term::from_field_text(text, "word3"),
+        ]);
+        assert_eq!(searcher.search(&reversed_phrase_query, &Count)?, 0);
+        let space_usage = searcher.space_usage()?;
+        let positions_num_bytes: u64 = space_usage
+            .segments()
+            .iter()
+            .map(|segment| segment.positions().total().get_bytes())
+            .sum();
+        Ok(positions_num_bytes)
+    }
+
+    #[test]
+    fn test_positions_compression_space_usage() -> crate::Result<()> {
+        let default_num_bytes = positions_num_bytes_for_compression(Default::default())?;
+        let compact_num_bytes = positions_num_bytes_for_compression(PositionsCompression {
+            block_len: 32,
+            bitpacking_threshold: Some(8),
+        })?;
+        assert!(
+            compact_num_bytes < default_num_bytes,
+            "{compact_num_bytes} >= {default_num_bytes}"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_position_read() -> crate::Result<()> {
         let position_deltas: Vec<u32> = (0..1000).collect();
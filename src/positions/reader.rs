@@ -3,8 +3,7 @@ use std::io;
 use common::{BinarySerializable, VInt};
 
 use crate::directory::OwnedBytes;
-use crate::positions::COMPRESSION_BLOCK_SIZE;
-use crate::postings::compression::{BlockDecoder, VIntDecoder};
+use crate::postings::compression::{BlockDecoder, VIntDecoder, COMPRESSION_BLOCK_SIZE};
 
 /// When accessing the positions of a term, we get a positions_idx from the `Terminfo`.
 /// This means we need to skip to the `nth` position efficiently.
@@ -12,7 +11,7 @@ use crate::postings::compression::{BlockDecoder, VIntDecoder};
 /// Blocks are compressed using bitpacking, so `skip_read` contains the number of bits
 /// (values can go from 0 to 32 bits) required to decompress every block.
 ///
-/// A given block obviously takes `(128 x  num_bit_for_the_block / num_bits_in_a_byte)`,
+/// A given block obviously takes `(block_len x  num_bit_for_the_block / num_bits_in_a_byte)`,
 /// so skipping a block without decompressing it is just a matter of advancing that many
 /// bytes.
 
@@ -22,9 +21,11 @@ pub struct PositionReader {
     positions: OwnedBytes,
 
     block_decoder: BlockDecoder,
+    // number of positions in a bitpacked block.
+    block_len: usize,
 
     // offset, expressed in positions, for the first position of the block currently loaded
-    // block_offset is a multiple of COMPRESSION_BLOCK_SIZE.
+    // block_offset is a multiple of `block_len`.
     block_offset: u64,
     // offset, expressed in positions, for the position of the first block encoded
     // in the `self.positions` bytes, and if bitpacked, compressed using the bitwidth in
@@ -40,13 +41,25 @@ pub struct PositionReader {
 
 impl PositionReader {
     /// Open and reads the term positions encoded into the positions_data owned bytes.
-    pub fn open(mut positions_data: OwnedBytes) -> io::Result<PositionReader> {
+    pub fn open(positions_data: OwnedBytes) -> io::Result<PositionReader> {
+        Self::open_with_block_len(positions_data, COMPRESSION_BLOCK_SIZE)
+    }
+
+    /// Open and reads the term positions encoded into the positions_data owned bytes,
+    /// using blocks of `block_len` positions.
+    ///
+    /// See [`PositionsCompression`](crate::positions::PositionsCompression).
+    pub fn open_with_block_len(
+        mut positions_data: OwnedBytes,
+        block_len: usize,
+    ) -> io::Result<PositionReader> {
         let num_positions_bitpacked_blocks = VInt::deserialize(&mut positions_data)?.0 as usize;
         let (bit_widths, positions) = positions_data.split(num_positions_bitpacked_blocks);
         Ok(PositionReader {
             bit_widths: bit_widths.clone(),
             positions: positions.clone(),
             block_decoder: BlockDecoder::default(),
+            block_len,
             block_offset: i64::MAX as u64,
             anchor_offset: 0u64,
             original_bit_widths: bit_widths,
@@ -70,10 +83,10 @@ impl PositionReader {
             .cloned()
             .map(|num_bits| num_bits as usize)
             .sum();
-        let num_bytes_to_skip = num_bits * COMPRESSION_BLOCK_SIZE / 8;
+        let num_bytes_to_skip = num_bits * self.block_len / 8;
         self.bit_widths.advance(num_blocks);
         self.positions.advance(num_bytes_to_skip);
-        self.anchor_offset += (num_blocks * COMPRESSION_BLOCK_SIZE) as u64;
+        self.anchor_offset += (num_blocks * self.block_len) as u64;
     }
 
     /// block_rel_id is counted relatively to the anchor.
@@ -85,20 +98,25 @@ impl PositionReader {
             .iter()
             .map(|&b| b as usize)
             .sum::<usize>()
-            * COMPRESSION_BLOCK_SIZE
+            * self.block_len
             / 8;
         let compressed_data = &self.positions.as_slice()[byte_offset..];
         if bit_widths.len() > block_rel_id {
             // that block is bitpacked.
             let bit_width = bit_widths[block_rel_id];
-            self.block_decoder
-                .uncompress_block_unsorted(compressed_data, bit_width, false);
+            if self.block_len == COMPRESSION_BLOCK_SIZE {
+                self.block_decoder
+                    .uncompress_block_unsorted(compressed_data, bit_width, false);
+            } else {
+                self.block_decoder
+                    .uncompress_small_block_unsorted(compressed_data, bit_width);
+            }
         } else {
             // that block is vint encoded.
             self.block_decoder
                 .uncompress_vint_unsorted_until_end(compressed_data);
         }
-        self.block_offset = self.anchor_offset + (block_rel_id * COMPRESSION_BLOCK_SIZE) as u64;
+        self.block_offset = self.anchor_offset + (block_rel_id * self.block_len) as u64;
     }
 
     /// Fills a buffer with the positions `[offset..offset+output.len())` integers.
@@ -109,20 +127,19 @@ impl PositionReader {
             self.reset();
         }
         let delta_to_block_offset = offset as i64 - self.block_offset as i64;
-        if !(0..128).contains(&delta_to_block_offset) {
+        if !(0..self.block_len as i64).contains(&delta_to_block_offset) {
             // The first position is not within the first block.
             // (Note that it could be before or after)
             // We need to possibly skip a few blocks, and decompress the first relevant  block.
             let delta_to_anchor_offset = offset - self.anchor_offset;
-            let num_blocks_to_skip =
-                (delta_to_anchor_offset / (COMPRESSION_BLOCK_SIZE as u64)) as usize;
+            let num_blocks_to_skip = (delta_to_anchor_offset / (self.block_len as u64)) as usize;
             self.advance_num_blocks(num_blocks_to_skip);
             self.load_block(0);
         } else {
             // The request offset is within the loaded block.
             // We still need to advance anchor_offset to our current block.
             let num_blocks_to_skip =
-                ((self.block_offset - self.anchor_offset) / COMPRESSION_BLOCK_SIZE as u64) as usize;
+                ((self.block_offset - self.anchor_offset) / self.block_len as u64) as usize;
             self.advance_num_blocks(num_blocks_to_skip);
         }
 
@@ -130,8 +147,8 @@ impl PositionReader {
         // been updated to point to it as well.
         for i in 1.. {
             // we copy the part from block i - 1 that is relevant.
-            let offset_in_block = (offset as usize) % COMPRESSION_BLOCK_SIZE;
-            let remaining_in_block = COMPRESSION_BLOCK_SIZE - offset_in_block;
+            let offset_in_block = (offset as usize) % self.block_len;
+            let remaining_in_block = self.block_len - offset_in_block;
             if remaining_in_block >= output.len() {
                 output.copy_from_slice(
                     &self.block_decoder.output_array()[offset_in_block..][..output.len()],
@@ -2,8 +2,8 @@ use std::io::{self, Write};
 
 use common::{BinarySerializable, CountingWriter, VInt};
 
-use crate::positions::COMPRESSION_BLOCK_SIZE;
-use crate::postings::compression::{BlockEncoder, VIntEncoder};
+use crate::positions::PositionsCompression;
+use crate::postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_SIZE};
 
 /// The PositionSerializer is in charge of serializing all of the positions
 /// of all of the terms of a given field.
@@ -15,17 +15,32 @@ pub struct PositionSerializer<W: io::Write> {
     positions_buffer: Vec<u8>,
     block: Vec<u32>,
     bit_widths: Vec<u8>,
+    block_len: usize,
+    bitpacking_threshold: usize,
 }
 
 impl<W: io::Write> PositionSerializer<W> {
     /// Creates a new PositionSerializer writing into the given positions_wrt.
     pub fn new(positions_wrt: W) -> PositionSerializer<W> {
+        Self::with_compression(positions_wrt, PositionsCompression::default())
+    }
+
+    /// Creates a new PositionSerializer writing into the given positions_wrt,
+    /// using the given encoding parameters.
+    ///
+    /// The parameters are expected to have been validated beforehand.
+    pub fn with_compression(
+        positions_wrt: W,
+        compression: PositionsCompression,
+    ) -> PositionSerializer<W> {
         PositionSerializer {
             block_encoder: BlockEncoder::new(),
             positions_wrt: CountingWriter::wrap(positions_wrt),
             positions_buffer: Vec::with_capacity(128_000),
-            block: Vec::with_capacity(128),
+            block: Vec::with_capacity(compression.block_len),
             bit_widths: Vec::new(),
+            block_len: compression.block_len,
+            bitpacking_threshold: compression.effective_bitpacking_threshold(),
         }
     }
 
@@ -40,7 +55,7 @@ impl<W: io::Write> PositionSerializer<W> {
     }
 
     fn remaining_block_len(&self) -> usize {
-        COMPRESSION_BLOCK_SIZE - self.block.len()
+        self.block_len - self.block.len()
     }
 
     /// Writes all of the given positions delta.
@@ -61,14 +76,22 @@ impl<W: io::Write> PositionSerializer<W> {
         if self.block.is_empty() {
             return;
         }
-        if self.block.len() == COMPRESSION_BLOCK_SIZE {
-            let (bit_width, block_encoded): (u8, &[u8]) = self
-                .block_encoder
-                .compress_block_unsorted(&self.block[..], false);
+        if self.block.len() >= self.bitpacking_threshold {
+            // Incomplete blocks are padded with zeros. Readers never read past the
+            // last position of a term, so the padding is never returned.
+            self.block.resize(self.block_len, 0u32);
+            let (bit_width, block_encoded): (u8, &[u8]) =
+                if self.block_len == COMPRESSION_BLOCK_SIZE {
+                    self.block_encoder
+                        .compress_block_unsorted(&self.block[..], false)
+                } else {
+                    self.block_encoder
+                        .compress_small_block_unsorted(&self.block[..])
+                };
             self.bit_widths.push(bit_width);
             self.positions_buffer.extend(block_encoded);
         } else {
-            debug_assert!(self.block.len() < COMPRESSION_BLOCK_SIZE);
+            debug_assert!(self.block.len() < self.block_len);
             let block_vint_encoded = self.block_encoder.compress_vint_unsorted(&self.block[..]);
             self.positions_buffer.extend_from_slice(block_vint_encoded);
         }
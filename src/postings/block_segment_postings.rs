@@ -5,6 +5,7 @@ use common::VInt;
 use crate::directory::{FileSlice, OwnedBytes};
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::compression::{BlockDecoder, VIntDecoder, COMPRESSION_BLOCK_SIZE};
+use crate::postings::skip::{num_upper_entries, upper_entry_len};
 use crate::postings::{BlockInfo, FreqReadingOption, SkipReader};
 use crate::query::Bm25Weight;
 use crate::schema::IndexRecordOption;
@@ -94,29 +95,35 @@ impl BlockSegmentPostings {
     /// `requested_option` is the amount of data requested by the user.
     /// If for instance, we do not request for term frequencies, this function will not decompress
     /// term frequency blocks.
+    /// `skip_interval` is the number of blocks per entry of the upper level of the skip list.
     pub(crate) fn open(
         doc_freq: u32,
         data: FileSlice,
         mut record_option: IndexRecordOption,
         requested_option: IndexRecordOption,
+        skip_interval: usize,
     ) -> io::Result<BlockSegmentPostings> {
         let bytes = data.read_bytes()?;
         let (skip_data_opt, postings_data) = split_into_skips_and_postings(doc_freq, bytes)?;
         let skip_reader = match skip_data_opt {
             Some(skip_data) => {
                 let block_count = doc_freq as usize / COMPRESSION_BLOCK_SIZE;
+                // The entries of the upper level are never smaller with frequencies than
+                // without, so that removing their size with frequencies keeps both cases apart.
+                let upper_level_len =
+                    num_upper_entries(doc_freq, skip_interval) * upper_entry_len(record_option);
                 // 8 is the minimum size of a block with frequency (can be more if pos are stored
                 // too)
-                if skip_data.len() < 8 * block_count {
+                if skip_data.len().saturating_sub(upper_level_len) < 8 * block_count {
                     // the field might be encoded with frequency, but this term in particular isn't.
                     // This can happen for JSON field with term frequencies:
                     // - text terms are encoded with term freqs.
                     // - numerical terms are encoded without term freqs.
                     record_option = IndexRecordOption::Basic;
                 }
-                SkipReader::new(skip_data, doc_freq, record_option)
+                SkipReader::new(skip_data, doc_freq, record_option, skip_interval)
             }
-            None => SkipReader::new(OwnedBytes::empty(), doc_freq, record_option),
+            None => SkipReader::new(OwnedBytes::empty(), doc_freq, record_option, skip_interval),
         };
 
         let freq_reading_option = match (record_option, requested_option) {
@@ -152,21 +159,24 @@ impl BlockSegmentPostings {
         if let Some(score) = self.block_max_score_cache {
             return score;
         }
-        if let Some(skip_reader_max_score) = self.skip_reader.block_max_score(bm25_weight) {
-            // if we are on a full block, the skip reader should have the block max information
-            // for us
-            self.block_max_score_cache = Some(skip_reader_max_score);
-            return skip_reader_max_score;
+        // The block maxima of the skip reader were computed with the fieldnorms of the
+        // segment, before the updates of its overlay.
+        if !fieldnorm_reader.has_overlay() {
+            if let Some(skip_reader_max_score) = self.skip_reader.block_max_score(bm25_weight) {
+                // if we are on a full block, the skip reader should have the block max
+                // information for us
+                self.block_max_score_cache = Some(skip_reader_max_score);
+                return skip_reader_max_score;
+            }
         }
         // this is the last block of the segment posting list.
         // If it is actually loaded, we can compute block max manually.
         if self.block_is_loaded() {
             let docs = self.doc_decoder.output_array().iter().cloned();
             let freqs = self.freq_decoder.output_array().iter().cloned();
-            let bm25_scores = docs.zip(freqs).map(|(doc, term_freq)| {
-                let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
-                bm25_weight.score(fieldnorm_id, term_freq)
-            });
+            let bm25_scores = docs
+                .zip(freqs)
+                .map(|(doc, term_freq)| bm25_weight.score_doc(fieldnorm_reader, doc, term_freq));
             let block_max_score = max_score(bm25_scores).unwrap_or(0.0);
             self.block_max_score_cache = Some(block_max_score);
             return block_max_score;
@@ -369,7 +379,7 @@ impl BlockSegmentPostings {
             block_max_score_cache: None,
             doc_freq: 0,
             data: OwnedBytes::empty(),
-            skip_reader: SkipReader::new(OwnedBytes::empty(), 0, IndexRecordOption::Basic),
+            skip_reader: SkipReader::new(OwnedBytes::empty(), 0, IndexRecordOption::Basic, 1),
         }
     }
 
@@ -1,7 +1,9 @@
-use bitpacking::{BitPacker, BitPacker4x};
+use bitpacking::{BitPacker, BitPacker1x, BitPacker4x};
 use common::FixedSize;
 
 pub const COMPRESSION_BLOCK_SIZE: usize = BitPacker4x::BLOCK_LEN;
+/// Size of the blocks compressed with the scalar bitpacker.
+pub const SMALL_COMPRESSION_BLOCK_SIZE: usize = BitPacker1x::BLOCK_LEN;
 const COMPRESSED_BLOCK_MAX_SIZE: usize = COMPRESSION_BLOCK_SIZE * u32::SIZE_IN_BYTES;
 
 mod vint;
@@ -13,6 +15,7 @@ pub fn compressed_block_size(num_bits: u8) -> usize {
 
 pub struct BlockEncoder {
     bitpacker: BitPacker4x,
+    small_bitpacker: BitPacker1x,
     pub output: [u8; COMPRESSED_BLOCK_MAX_SIZE],
 }
 
@@ -26,6 +29,7 @@ impl BlockEncoder {
     pub fn new() -> BlockEncoder {
         BlockEncoder {
             bitpacker: BitPacker4x::new(),
+            small_bitpacker: BitPacker1x::new(),
             output: [0u8; COMPRESSED_BLOCK_MAX_SIZE],
         }
     }
@@ -71,11 +75,21 @@ impl BlockEncoder {
             .compress(block, &mut self.output[..], num_bits);
         (num_bits, &self.output[..written_size])
     }
+
+    /// Compress a single block of `SMALL_COMPRESSION_BLOCK_SIZE` unsorted numbers.
+    pub fn compress_small_block_unsorted(&mut self, block: &[u32]) -> (u8, &[u8]) {
+        let num_bits = self.small_bitpacker.num_bits(block);
+        let written_size = self
+            .small_bitpacker
+            .compress(block, &mut self.output[..], num_bits);
+        (num_bits, &self.output[..written_size])
+    }
 }
 
 #[derive(Clone)]
 pub struct BlockDecoder {
     bitpacker: BitPacker4x,
+    small_bitpacker: BitPacker1x,
     output: [u32; COMPRESSION_BLOCK_SIZE],
     pub output_len: usize,
 }
@@ -90,6 +104,7 @@ impl BlockDecoder {
     pub fn with_val(val: u32) -> BlockDecoder {
         BlockDecoder {
             bitpacker: BitPacker4x::new(),
+            small_bitpacker: BitPacker1x::new(),
             output: [val; COMPRESSION_BLOCK_SIZE],
             output_len: 0,
         }
@@ -146,6 +161,21 @@ impl BlockDecoder {
         res
     }
 
+    /// Decompress a block of `SMALL_COMPRESSION_BLOCK_SIZE` unsorted integers, as compressed by
+    /// `BlockEncoder::compress_small_block_unsorted`.
+    pub fn uncompress_small_block_unsorted(
+        &mut self,
+        compressed_data: &[u8],
+        num_bits: u8,
+    ) -> usize {
+        self.output_len = SMALL_COMPRESSION_BLOCK_SIZE;
+        self.small_bitpacker.decompress(
+            compressed_data,
+            &mut self.output[..SMALL_COMPRESSION_BLOCK_SIZE],
+            num_bits,
+        )
+    }
+
     #[inline]
     pub fn output_array(&self) -> &[u32] {
         &self.output[..self.output_len]
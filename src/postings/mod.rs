@@ -28,7 +28,11 @@ pub use self::postings::Postings;
 pub(crate) use self::postings_writer::{serialize_postings, IndexingPosition, PostingsWriter};
 pub use self::segment_postings::SegmentPostings;
 pub use self::serializer::{FieldSerializer, InvertedIndexSerializer};
-pub(crate) use self::skip::{BlockInfo, SkipReader};
+pub use self::skip::MAX_POSTINGS_SKIP_INTERVAL;
+pub(crate) use self::skip::{
+    read_postings_skip_interval, validate_postings_skip_interval, BlockInfo, SkipReader,
+    POSTINGS_HEADER_IDX,
+};
 pub use self::term_info::TermInfo;
 
 #[expect(clippy::enum_variant_names)]
@@ -44,17 +48,18 @@ pub(crate) mod tests {
     use std::mem;
 
     use super::{InvertedIndexSerializer, Postings};
+    use crate::collector::DocSetCollector;
     use crate::docset::{DocSet, TERMINATED};
     use crate::fieldnorm::FieldNormReader;
     use crate::index::{Index, SegmentComponent, SegmentReader};
     use crate::indexer::operation::AddOperation;
     use crate::indexer::SegmentWriter;
-    use crate::query::Scorer;
+    use crate::query::{QueryParser, Scorer};
     use crate::schema::{
         Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, INDEXED, TEXT,
     };
     use crate::tokenizer::{SimpleTokenizer, MAX_TOKEN_LEN};
-    use crate::{DocId, HasLen, IndexWriter, Score};
+    use crate::{DocId, HasLen, IndexSettings, IndexWriter, Score};
 
     #[test]
     pub fn test_position_write() -> crate::Result<()> {
@@ -236,6 +241,7 @@ pub(crate) mod tests {
                        text_field => "a b a c a d a a.",
                        text_field => "d d d d a"
                     ),
+                    source: None,
                 };
                 segment_writer.add_document(op)?;
             }
@@ -243,6 +249,7 @@ pub(crate) mod tests {
                 let op = AddOperation {
                     opstamp: 1u64,
                     document: doc!(text_field => "b a"),
+                    source: None,
                 };
                 segment_writer.add_document(op).unwrap();
             }
@@ -252,6 +259,7 @@ pub(crate) mod tests {
                 let op = AddOperation {
                     opstamp: 2u64,
                     document: doc!(text_field => text),
+                    source: None,
                 };
                 segment_writer.add_document(op).unwrap();
             }
@@ -500,6 +508,69 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    fn search_with_skip_interval(postings_skip_interval: usize) -> crate::Result<Vec<Vec<u32>>> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let settings = IndexSettings {
+            postings_skip_interval,
+            ..Default::default()
+        };
+        let index = Index::builder()
+            .schema(schema)
+            .settings(settings)
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0u32..20_000 {
+            let mut body = String::from("x");
+            for (word, modulo) in [("a", 2), ("b", 3), ("c", 11), ("d y", 13)] {
+                if i % modulo == 0 {
+                    body.push(' ');
+                    body.push_str(word);
+                }
+            }
+            index_writer.add_document(doc!(text => body))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        let mut results = Vec::new();
+        for query_str in ["+a +b +c", "+x +c +\"d y\"", "+c +y -a", "\"x a b\""] {
+            let query = query_parser.parse_query(query_str)?;
+            let doc_addresses = searcher.search(&query, &DocSetCollector)?;
+            let mut docs: Vec<u32> = doc_addresses
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort_unstable();
+            results.push(docs);
+        }
+        Ok(results)
+    }
+
+    #[test]
+    fn test_postings_skip_interval() -> crate::Result<()> {
+        let expected = search_with_skip_interval(1)?;
+        assert_eq!(expected[0].len(), (0..20_000).step_by(66).count());
+        assert_eq!(expected[1].len(), (0..20_000).step_by(143).count());
+        assert!(!expected[2].is_empty());
+        assert_eq!(expected[3].len(), (0..20_000).step_by(6).count());
+        for postings_skip_interval in [2, 4, 7] {
+            assert_eq!(search_with_skip_interval(postings_skip_interval)?, expected);
+        }
+        let settings = IndexSettings {
+            postings_skip_interval: 0,
+            ..Default::default()
+        };
+        assert!(Index::builder()
+            .schema(Schema::builder().build())
+            .settings(settings)
+            .create_in_ram()
+            .is_err());
+        Ok(())
+    }
+
     /// Wraps a given docset, and forward all call but the
     /// `.skip_next(...)`. This is useful to test that a specialized
     /// implementation of `.skip_next(...)` is consistent
@@ -571,7 +642,7 @@ mod bench {
     use crate::docset::TERMINATED;
     use crate::query::Intersection;
     use crate::schema::{Field, IndexRecordOption, Schema, TantivyDocument, Term, STRING};
-    use crate::{tests, DocSet, Index, IndexWriter};
+    use crate::{tests, DocSet, Index, IndexSettings, IndexWriter};
 
     pub static TERM_A: Lazy<Term> = Lazy::new(|| {
         let field = Field::from_field_id(0);
@@ -590,14 +661,22 @@ mod bench {
         Term::from_field_text(field, "d")
     });
 
-    pub static INDEX: Lazy<Index> = Lazy::new(|| {
+    fn build_index(postings_skip_interval: usize) -> Index {
         let mut schema_builder = Schema::builder();
         let text_field = schema_builder.add_text_field("text", STRING);
         let schema = schema_builder.build();
 
         let mut rng: StdRng = StdRng::from_seed([1u8; 32]);
 
-        let index = Index::create_in_ram(schema);
+        let settings = IndexSettings {
+            postings_skip_interval,
+            ..Default::default()
+        };
+        let index = Index::builder()
+            .schema(schema)
+            .settings(settings)
+            .create_in_ram()
+            .unwrap();
         let posting_list_size = 1_000_000;
         {
             let mut index_writer: IndexWriter = index.writer_for_tests().unwrap();
@@ -618,7 +697,11 @@ mod bench {
             assert!(index_writer.commit().is_ok());
         }
         index
-    });
+    }
+
+    pub static INDEX: Lazy<Index> = Lazy::new(|| build_index(1));
+
+    pub static INDEX_SKIP_INTERVAL_8: Lazy<Index> = Lazy::new(|| build_index(8));
 
     #[bench]
     fn bench_segment_postings(b: &mut Bencher) {
@@ -639,7 +722,16 @@ mod bench {
 
     #[bench]
     fn bench_segment_intersection(b: &mut Bencher) {
-        let reader = INDEX.reader().unwrap();
+        bench_intersection(&INDEX, b);
+    }
+
+    #[bench]
+    fn bench_segment_intersection_skip_interval_8(b: &mut Bencher) {
+        bench_intersection(&INDEX_SKIP_INTERVAL_8, b);
+    }
+
+    fn bench_intersection(index: &Index, b: &mut Bencher) {
+        let reader = index.reader().unwrap();
         let searcher = reader.searcher();
         let segment_reader = searcher.segment_reader(0);
         b.iter(|| {
@@ -97,6 +97,8 @@ pub(crate) fn serialize_postings(
 pub(crate) struct IndexingPosition {
     pub num_tokens: u32,
     pub end_position: u32,
+    /// Set if some tokens were dropped because their position exceeded `u32::MAX`.
+    pub positions_overflow: bool,
 }
 
 /// The `PostingsWriter` is in charge of receiving documenting
@@ -135,6 +137,7 @@ pub(crate) trait PostingsWriter: Send + Sync {
         let end_of_path_idx = term_buffer.len_bytes();
         let mut num_tokens = 0;
         let mut end_position = indexing_position.end_position;
+        let mut positions_overflow = false;
         token_stream.process(&mut |token: &Token| {
             // We skip all tokens with a len greater than u16.
             if token.text.len() > MAX_TOKEN_LEN {
@@ -146,15 +149,24 @@ pub(crate) trait PostingsWriter: Send + Sync {
                 );
                 return;
             }
+            let start_position = u64::from(indexing_position.end_position) + token.position as u64;
+            let token_end_position = start_position + token.position_length as u64;
+            if token_end_position > u64::from(u32::MAX) {
+                // The position space of the field is exhausted: the token is dropped.
+                positions_overflow = true;
+                return;
+            }
             term_buffer.truncate_value_bytes(end_of_path_idx);
             term_buffer.append_bytes(token.text.as_bytes());
-            let start_position = indexing_position.end_position + token.position as u32;
-            end_position = end_position.max(start_position + token.position_length as u32);
-            self.subscribe(doc_id, start_position, term_buffer, ctx);
+            end_position = end_position.max(token_end_position as u32);
+            self.subscribe(doc_id, start_position as u32, term_buffer, ctx);
             num_tokens += 1;
         });
 
-        indexing_position.end_position = end_position + POSITION_GAP;
+        if positions_overflow {
+            indexing_position.positions_overflow = true;
+        }
+        indexing_position.end_position = end_position.saturating_add(POSITION_GAP);
         indexing_position.num_tokens += num_tokens;
         term_buffer.truncate_value_bytes(end_of_path_idx);
     }
@@ -70,7 +70,7 @@ impl SegmentPostings {
         let mut buffer = Vec::new();
         {
             let mut postings_serializer =
-                PostingsSerializer::new(&mut buffer, 0.0, IndexRecordOption::Basic, None);
+                PostingsSerializer::new(&mut buffer, 0.0, IndexRecordOption::Basic, 1, None);
             postings_serializer.new_term(docs.len() as u32, false);
             for &doc in docs {
                 postings_serializer.write_doc(doc, 1u32);
@@ -84,6 +84,7 @@ impl SegmentPostings {
             FileSlice::from(buffer),
             IndexRecordOption::Basic,
             IndexRecordOption::Basic,
+            1,
         )
         .unwrap();
         SegmentPostings::from_block_postings(block_segment_postings, None)
@@ -118,6 +119,7 @@ impl SegmentPostings {
             &mut buffer,
             average_field_norm,
             IndexRecordOption::WithFreqs,
+            1,
             fieldnorm_reader,
         );
         postings_serializer.new_term(doc_and_tfs.len() as u32, true);
@@ -132,6 +134,7 @@ impl SegmentPostings {
             FileSlice::from(buffer),
             IndexRecordOption::WithFreqs,
             IndexRecordOption::WithFreqs,
+            1,
         )
         .unwrap();
         SegmentPostings::from_block_postings(block_segment_postings, None)
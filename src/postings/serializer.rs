@@ -7,11 +7,12 @@ use super::TermInfo;
 use crate::directory::{CompositeWrite, WritePtr};
 use crate::fieldnorm::FieldNormReader;
 use crate::index::Segment;
-use crate::positions::PositionSerializer;
+use crate::positions::{PositionSerializer, PositionsCompression, POSITIONS_HEADER_IDX};
 use crate::postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_SIZE};
-use crate::postings::skip::SkipSerializer;
+use crate::postings::skip::{SkipSerializer, POSTINGS_HEADER_IDX};
 use crate::query::Bm25Weight;
 use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, Schema};
+use crate::store::Compressor;
 use crate::termdict::TermDictionaryBuilder;
 use crate::{DocId, Score};
 
@@ -50,6 +51,8 @@ pub struct InvertedIndexSerializer {
     terms_write: CompositeWrite<WritePtr>,
     postings_write: CompositeWrite<WritePtr>,
     positions_write: CompositeWrite<WritePtr>,
+    positions_compression: PositionsCompression,
+    postings_skip_interval: usize,
     schema: Schema,
 }
 
@@ -61,6 +64,8 @@ impl InvertedIndexSerializer {
             terms_write: CompositeWrite::wrap(segment.open_write(Terms)?),
             postings_write: CompositeWrite::wrap(segment.open_write(Postings)?),
             positions_write: CompositeWrite::wrap(segment.open_write(Positions)?),
+            positions_compression: segment.index().settings().positions_compression,
+            postings_skip_interval: segment.index().settings().postings_skip_interval,
             schema: segment.schema(),
         };
         Ok(inv_index_serializer)
@@ -78,15 +83,33 @@ impl InvertedIndexSerializer {
     ) -> io::Result<FieldSerializer> {
         let field_entry: &FieldEntry = self.schema.get_field_entry(field);
         let term_dictionary_write = self.terms_write.for_field(field);
+        if self.postings_skip_interval != 1 {
+            let header_write = self
+                .postings_write
+                .for_field_with_idx(field, POSTINGS_HEADER_IDX);
+            VInt(self.postings_skip_interval as u64).serialize(header_write)?;
+        }
         let postings_write = self.postings_write.for_field(field);
-        let positions_write = self.positions_write.for_field(field);
         let field_type: FieldType = (*field_entry.field_type()).clone();
+        let has_positions = field_type
+            .index_record_option()
+            .map(|record_option| record_option.has_positions())
+            .unwrap_or(false);
+        if has_positions && !self.positions_compression.is_default() {
+            let header_write = self
+                .positions_write
+                .for_field_with_idx(field, POSITIONS_HEADER_IDX);
+            self.positions_compression.serialize_header(header_write)?;
+        }
+        let positions_write = self.positions_write.for_field(field);
         FieldSerializer::create(
             &field_type,
             total_num_tokens,
             term_dictionary_write,
             postings_write,
             positions_write,
+            self.positions_compression,
+            self.postings_skip_interval,
             fieldnorm_reader,
         )
     }
@@ -111,19 +134,34 @@ pub struct FieldSerializer<'a> {
 }
 
 impl<'a> FieldSerializer<'a> {
+    #[expect(clippy::too_many_arguments)]
     fn create(
         field_type: &FieldType,
         total_num_tokens: u64,
         term_dictionary_write: &'a mut CountingWriter<WritePtr>,
         postings_write: &'a mut CountingWriter<WritePtr>,
         positions_write: &'a mut CountingWriter<WritePtr>,
+        positions_compression: PositionsCompression,
+        postings_skip_interval: usize,
         fieldnorm_reader: Option<FieldNormReader>,
     ) -> io::Result<FieldSerializer<'a>> {
         total_num_tokens.serialize(postings_write)?;
         let index_record_option = field_type
             .index_record_option()
             .unwrap_or(IndexRecordOption::Basic);
-        let term_dictionary_builder = TermDictionaryBuilder::create(term_dictionary_write)?;
+        let term_dict_compressor = match field_type {
+            FieldType::Str(text_options) => text_options.get_indexing_options(),
+            FieldType::JsonObject(json_object_options) => {
+                json_object_options.get_text_indexing_options()
+            }
+            _ => None,
+        }
+        .map(|text_indexing| text_indexing.term_dict_compressor())
+        .unwrap_or(Compressor::None);
+        let term_dictionary_builder = TermDictionaryBuilder::create_with_compressor(
+            term_dictionary_write,
+            term_dict_compressor,
+        )?;
         let average_fieldnorm = fieldnorm_reader
             .as_ref()
             .map(|ff_reader| (total_num_tokens as Score / ff_reader.num_docs() as Score))
@@ -132,10 +170,14 @@ impl<'a> FieldSerializer<'a> {
             postings_write,
             average_fieldnorm,
             index_record_option,
+            postings_skip_interval,
             fieldnorm_reader,
         );
         let positions_serializer_opt = if index_record_option.has_positions() {
-            Some(PositionSerializer::new(positions_write))
+            Some(PositionSerializer::with_compression(
+                positions_write,
+                positions_compression,
+            ))
         } else {
             None
         };
@@ -300,6 +342,8 @@ pub struct PostingsSerializer<W: Write> {
 
     postings_write: Vec<u8>,
     skip_write: SkipSerializer,
+    // sum of the term frequencies of the blocks of the current term written so far.
+    position_offset: u64,
 
     mode: IndexRecordOption,
     fieldnorm_reader: Option<FieldNormReader>,
@@ -315,6 +359,7 @@ impl<W: Write> PostingsSerializer<W> {
         write: W,
         avg_fieldnorm: Score,
         mode: IndexRecordOption,
+        skip_interval: usize,
         fieldnorm_reader: Option<FieldNormReader>,
     ) -> PostingsSerializer<W> {
         PostingsSerializer {
@@ -324,7 +369,8 @@ impl<W: Write> PostingsSerializer<W> {
             block: Box::new(Block::new()),
 
             postings_write: Vec::new(),
-            skip_write: SkipSerializer::new(),
+            skip_write: SkipSerializer::new(skip_interval),
+            position_offset: 0u64,
 
             last_doc_id_encoded: 0u32,
             mode,
@@ -385,6 +431,7 @@ impl<W: Write> PostingsSerializer<W> {
                 // in order to navigate through positions.
                 let sum_freq = self.block.term_freqs().iter().cloned().sum();
                 self.skip_write.write_total_term_freq(sum_freq);
+                self.position_offset += sum_freq as u64;
             }
             let mut blockwand_params = (0u8, 0u32);
             if let Some(bm25_weight) = self.bm25_weight.as_ref() {
@@ -412,6 +459,13 @@ impl<W: Write> PostingsSerializer<W> {
             let (fieldnorm_id, term_freq) = blockwand_params;
             self.skip_write.write_blockwand_max(fieldnorm_id, term_freq);
         }
+        let position_offset_opt =
+            (self.term_has_freq && self.mode.has_positions()).then_some(self.position_offset);
+        self.skip_write.end_block(
+            self.last_doc_id_encoded,
+            self.postings_write.len() as u64,
+            position_offset_opt,
+        );
         self.block.clear();
     }
 
@@ -457,6 +511,7 @@ impl<W: Write> PostingsSerializer<W> {
         self.output_write.write_all(&self.postings_write[..])?;
         self.skip_write.clear();
         self.postings_write.clear();
+        self.position_offset = 0u64;
         self.bm25_weight = None;
         Ok(())
     }
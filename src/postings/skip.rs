@@ -1,9 +1,50 @@
-use crate::directory::OwnedBytes;
+use std::io;
+
+use common::{BinarySerializable, VInt};
+
+use crate::directory::{FileSlice, OwnedBytes};
 use crate::postings::compression::{compressed_block_size, COMPRESSION_BLOCK_SIZE};
 use crate::query::Bm25Weight;
 use crate::schema::IndexRecordOption;
 use crate::{DocId, Score, TERMINATED};
 
+/// Index, within the postings composite file, of the header describing
+/// how the skip lists of a field were written.
+///
+/// The header is omitted when the default skip interval is used.
+pub(crate) const POSTINGS_HEADER_IDX: usize = 1;
+
+/// Maximum value of
+/// [`IndexSettings::postings_skip_interval`](crate::IndexSettings::postings_skip_interval).
+pub const MAX_POSTINGS_SKIP_INTERVAL: usize = 1_024;
+
+/// Checks that the skip interval of the posting lists is supported.
+pub(crate) fn validate_postings_skip_interval(skip_interval: usize) -> crate::Result<()> {
+    if skip_interval == 0 || skip_interval > MAX_POSTINGS_SKIP_INTERVAL {
+        return Err(crate::TantivyError::InvalidArgument(format!(
+            "Postings skip interval must be within 1..={MAX_POSTINGS_SKIP_INTERVAL}, got \
+             {skip_interval}."
+        )));
+    }
+    Ok(())
+}
+
+/// Reads the skip interval from the optional postings header of a field.
+pub(crate) fn read_postings_skip_interval(header_opt: Option<FileSlice>) -> io::Result<usize> {
+    let Some(header) = header_opt else {
+        return Ok(1);
+    };
+    let header_bytes = header.read_bytes()?;
+    let skip_interval = VInt::deserialize(&mut header_bytes.as_slice())?.0 as usize;
+    if skip_interval == 0 || skip_interval > MAX_POSTINGS_SKIP_INTERVAL {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported postings skip interval {skip_interval}"),
+        ));
+    }
+    Ok(skip_interval)
+}
+
 // doc num bits uses the following encoding:
 // given 0b a b cdefgh
 //         |1|2|   3  |
@@ -44,13 +85,63 @@ fn write_u32(val: u32, buf: &mut Vec<u8>) {
     buf.extend_from_slice(&val.to_le_bytes());
 }
 
+#[inline]
+fn read_u64(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+#[inline]
+fn write_u64(val: u64, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&val.to_le_bytes());
+}
+
+/// Number of bytes of the skip entry of a block.
+fn block_entry_len(skip_info: IndexRecordOption) -> usize {
+    match skip_info {
+        IndexRecordOption::Basic => 5,
+        IndexRecordOption::WithFreqs => 8,
+        IndexRecordOption::WithFreqsAndPositions => 12,
+    }
+}
+
+/// Number of bytes of an entry of the upper level of the skip list.
+///
+/// The position offset is only recorded if the block entries carry the sum of the term
+/// frequencies of their block.
+pub(crate) fn upper_entry_len(skip_info: IndexRecordOption) -> usize {
+    match skip_info {
+        IndexRecordOption::Basic | IndexRecordOption::WithFreqs => 12,
+        IndexRecordOption::WithFreqsAndPositions => 20,
+    }
+}
+
+/// Serializes the skip data of a posting list.
+///
+/// The skip data starts with one entry per block of [`COMPRESSION_BLOCK_SIZE`] documents,
+/// holding the last doc of the block, the bit widths needed to decode it, its sum of term
+/// frequencies and its block-wand maximum.
+///
+/// With a skip interval `k` larger than 1, an upper level follows, with one entry per group of
+/// `k` full blocks. It records the state of the reader after the last block of the group: the
+/// last doc of the group, the byte offset of the next block and, if the block entries carry
+/// the sum of their term frequencies, the offset of the positions of the next block. Seeking
+/// far ahead then goes through the upper level, and only reads the block entries of the group
+/// containing the target.
 pub struct SkipSerializer {
     buffer: Vec<u8>,
+    upper_level: Vec<u8>,
+    skip_interval: usize,
+    num_blocks: usize,
 }
 
 impl SkipSerializer {
-    pub fn new() -> SkipSerializer {
-        SkipSerializer { buffer: Vec::new() }
+    pub fn new(skip_interval: usize) -> SkipSerializer {
+        SkipSerializer {
+            buffer: Vec::new(),
+            upper_level: Vec::new(),
+            skip_interval,
+            num_blocks: 0,
+        }
     }
 
     pub fn write_doc(&mut self, last_doc: DocId, doc_num_bits: u8) {
@@ -72,12 +163,30 @@ impl SkipSerializer {
             .extend_from_slice(&[fieldnorm_id, block_wand_tf]);
     }
 
-    pub fn data(&self) -> &[u8] {
+    /// Must be called once the entry of a block has been written.
+    ///
+    /// `byte_offset` and `position_offset` are the offsets of the next block within the
+    /// postings and the positions of the term.
+    pub fn end_block(&mut self, last_doc: DocId, byte_offset: u64, position_offset: Option<u64>) {
+        self.num_blocks += 1;
+        if self.skip_interval > 1 && self.num_blocks % self.skip_interval == 0 {
+            write_u32(last_doc, &mut self.upper_level);
+            write_u64(byte_offset, &mut self.upper_level);
+            if let Some(position_offset) = position_offset {
+                write_u64(position_offset, &mut self.upper_level);
+            }
+        }
+    }
+
+    pub fn data(&mut self) -> &[u8] {
+        self.buffer.append(&mut self.upper_level);
         &self.buffer[..]
     }
 
     pub fn clear(&mut self) {
         self.buffer.clear();
+        self.upper_level.clear();
+        self.num_blocks = 0;
     }
 }
 
@@ -86,7 +195,14 @@ pub(crate) struct SkipReader {
     last_doc_in_block: DocId,
     pub(crate) last_doc_in_previous_block: DocId,
     owned_read: OwnedBytes,
+    // entries of the blocks, without the upper level.
+    blocks_data: OwnedBytes,
+    upper_level: OwnedBytes,
+    // first group of the upper level that has not been skipped yet.
+    next_group: usize,
     skip_info: IndexRecordOption,
+    skip_interval: usize,
+    doc_freq: u32,
     byte_offset: usize,
     remaining_docs: u32, // number of docs remaining, including the
     // documents in the current block.
@@ -117,42 +233,46 @@ impl Default for BlockInfo {
 }
 
 impl SkipReader {
-    pub fn new(data: OwnedBytes, doc_freq: u32, skip_info: IndexRecordOption) -> SkipReader {
+    pub fn new(
+        data: OwnedBytes,
+        doc_freq: u32,
+        skip_info: IndexRecordOption,
+        skip_interval: usize,
+    ) -> SkipReader {
         let mut skip_reader = SkipReader {
-            last_doc_in_block: if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
-                0
-            } else {
-                TERMINATED
-            },
+            last_doc_in_block: TERMINATED,
             last_doc_in_previous_block: 0u32,
-            owned_read: data,
+            owned_read: OwnedBytes::empty(),
+            blocks_data: OwnedBytes::empty(),
+            upper_level: OwnedBytes::empty(),
+            next_group: 0,
             skip_info,
+            skip_interval,
+            doc_freq,
             block_info: BlockInfo::VInt { num_docs: doc_freq },
             byte_offset: 0,
             remaining_docs: doc_freq,
             position_offset: 0u64,
         };
-        if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
-            skip_reader.read_block_info();
-        }
+        skip_reader.reset(data, doc_freq);
         skip_reader
     }
 
     pub fn reset(&mut self, data: OwnedBytes, doc_freq: u32) {
-        self.last_doc_in_block = if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
-            0
-        } else {
-            TERMINATED
-        };
+        let num_groups = num_upper_entries(doc_freq, self.skip_interval);
+        let upper_level_len = num_groups * upper_entry_len(self.skip_info);
+        let blocks_data_len = data.len().saturating_sub(upper_level_len);
+        let (blocks_data, upper_level) = data.split(blocks_data_len);
         self.last_doc_in_previous_block = 0u32;
-        self.owned_read = data;
-        self.block_info = BlockInfo::VInt { num_docs: doc_freq };
+        self.owned_read = blocks_data.clone();
+        self.blocks_data = blocks_data;
+        self.upper_level = upper_level;
+        self.next_group = 0;
+        self.doc_freq = doc_freq;
         self.byte_offset = 0;
         self.remaining_docs = doc_freq;
         self.position_offset = 0u64;
-        if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
-            self.read_block_info();
-        }
+        self.read_current_block();
     }
 
     // Returns the block max score for this block if available.
@@ -165,7 +285,7 @@ impl SkipReader {
                 block_wand_fieldnorm_id,
                 block_wand_term_freq,
                 ..
-            } => Some(bm25_weight.score(block_wand_fieldnorm_id, block_wand_term_freq)),
+            } => Some(bm25_weight.block_max_score(block_wand_fieldnorm_id, block_wand_term_freq)),
             BlockInfo::VInt { .. } => None,
         }
     }
@@ -245,12 +365,50 @@ impl SkipReader {
         if self.last_doc_in_block() >= target {
             return false;
         }
-        loop {
+        self.seek_upper_level(target);
+        while self.last_doc_in_block() < target {
             self.advance();
-            if self.last_doc_in_block() >= target {
-                return true;
+        }
+        true
+    }
+
+    /// Jumps past the groups of blocks of the upper level that only contain documents
+    /// lower than the target.
+    fn seek_upper_level(&mut self, target: DocId) {
+        let entry_len = upper_entry_len(self.skip_info);
+        let num_groups = self.upper_level.len() / entry_len;
+        let mut skipped_group_opt = None;
+        while self.next_group < num_groups {
+            let entry = &self.upper_level.as_slice()[self.next_group * entry_len..];
+            if read_u32(entry) >= target {
+                break;
             }
+            skipped_group_opt = Some(self.next_group);
+            self.next_group += 1;
+        }
+        let Some(skipped_group) = skipped_group_opt else {
+            return;
+        };
+        let next_block = (skipped_group + 1) * self.skip_interval;
+        let current_block = (self.doc_freq - self.remaining_docs) as usize / COMPRESSION_BLOCK_SIZE;
+        if next_block <= current_block {
+            // The reader was already advanced past this group, block by block.
+            return;
         }
+        let entry = &self.upper_level.as_slice()[skipped_group * entry_len..];
+        let last_doc = read_u32(entry);
+        let byte_offset = read_u64(&entry[4..]);
+        let position_offset_opt = (entry_len == 20).then(|| read_u64(&entry[12..]));
+        self.last_doc_in_previous_block = last_doc;
+        self.byte_offset = byte_offset as usize;
+        if let Some(position_offset) = position_offset_opt {
+            self.position_offset = position_offset;
+        }
+        self.remaining_docs = self.doc_freq - (next_block * COMPRESSION_BLOCK_SIZE) as u32;
+        self.owned_read = self.blocks_data.clone();
+        self.owned_read
+            .advance(next_block * block_entry_len(self.skip_info));
+        self.read_current_block();
     }
 
     pub fn advance(&mut self) {
@@ -272,6 +430,10 @@ impl SkipReader {
             }
         }
         self.last_doc_in_previous_block = self.last_doc_in_block;
+        self.read_current_block();
+    }
+
+    fn read_current_block(&mut self) {
         if self.remaining_docs >= COMPRESSION_BLOCK_SIZE as u32 {
             self.read_block_info();
         } else {
@@ -283,14 +445,26 @@ impl SkipReader {
     }
 }
 
+/// Number of entries of the upper level of the skip list of a posting list.
+pub(crate) fn num_upper_entries(doc_freq: u32, skip_interval: usize) -> usize {
+    if skip_interval <= 1 {
+        return 0;
+    }
+    doc_freq as usize / COMPRESSION_BLOCK_SIZE / skip_interval
+}
+
 #[cfg(test)]
 mod tests {
 
+    use common::{BinarySerializable, VInt};
+
     use super::{
-        decode_bitwidth, encode_bitwidth, BlockInfo, IndexRecordOption, SkipReader, SkipSerializer,
+        decode_bitwidth, encode_bitwidth, num_upper_entries, read_postings_skip_interval,
+        validate_postings_skip_interval, BlockInfo, IndexRecordOption, SkipReader, SkipSerializer,
+        MAX_POSTINGS_SKIP_INTERVAL,
     };
-    use crate::directory::OwnedBytes;
-    use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+    use crate::directory::{FileSlice, OwnedBytes};
+    use crate::postings::compression::{compressed_block_size, COMPRESSION_BLOCK_SIZE};
 
     #[test]
     fn test_encode_block_wand_max_tf() {
@@ -313,7 +487,7 @@ mod tests {
     #[test]
     fn test_skip_with_freq() {
         let buf = {
-            let mut skip_serializer = SkipSerializer::new();
+            let mut skip_serializer = SkipSerializer::new(1);
             skip_serializer.write_doc(1u32, 2u8);
             skip_serializer.write_term_freq(3u8);
             skip_serializer.write_blockwand_max(13u8, 3u32);
@@ -323,8 +497,12 @@ mod tests {
             skip_serializer.data().to_owned()
         };
         let doc_freq = 3u32 + (COMPRESSION_BLOCK_SIZE * 2) as u32;
-        let mut skip_reader =
-            SkipReader::new(OwnedBytes::new(buf), doc_freq, IndexRecordOption::WithFreqs);
+        let mut skip_reader = SkipReader::new(
+            OwnedBytes::new(buf),
+            doc_freq,
+            IndexRecordOption::WithFreqs,
+            1,
+        );
         assert_eq!(skip_reader.last_doc_in_block(), 1u32);
         assert_eq!(
             skip_reader.block_info,
@@ -361,14 +539,14 @@ mod tests {
     #[test]
     fn test_skip_no_freq() {
         let buf = {
-            let mut skip_serializer = SkipSerializer::new();
+            let mut skip_serializer = SkipSerializer::new(1);
             skip_serializer.write_doc(1u32, 2u8);
             skip_serializer.write_doc(5u32, 5u8);
             skip_serializer.data().to_owned()
         };
         let doc_freq = 3u32 + (COMPRESSION_BLOCK_SIZE * 2) as u32;
         let mut skip_reader =
-            SkipReader::new(OwnedBytes::new(buf), doc_freq, IndexRecordOption::Basic);
+            SkipReader::new(OwnedBytes::new(buf), doc_freq, IndexRecordOption::Basic, 1);
         assert_eq!(skip_reader.last_doc_in_block(), 1u32);
         assert_eq!(
             skip_reader.block_info(),
@@ -405,13 +583,13 @@ mod tests {
     #[test]
     fn test_skip_multiple_of_block_size() {
         let buf = {
-            let mut skip_serializer = SkipSerializer::new();
+            let mut skip_serializer = SkipSerializer::new(1);
             skip_serializer.write_doc(1u32, 2u8);
             skip_serializer.data().to_owned()
         };
         let doc_freq = COMPRESSION_BLOCK_SIZE as u32;
         let mut skip_reader =
-            SkipReader::new(OwnedBytes::new(buf), doc_freq, IndexRecordOption::Basic);
+            SkipReader::new(OwnedBytes::new(buf), doc_freq, IndexRecordOption::Basic, 1);
         assert_eq!(skip_reader.last_doc_in_block(), 1u32);
         assert_eq!(
             skip_reader.block_info(),
@@ -428,6 +606,95 @@ mod tests {
         assert_eq!(skip_reader.block_info(), BlockInfo::VInt { num_docs: 0u32 });
     }
 
+    fn skip_data_for_test(skip_interval: usize, num_blocks: usize) -> Vec<u8> {
+        let mut skip_serializer = SkipSerializer::new(skip_interval);
+        let mut byte_offset = 0u64;
+        let mut position_offset = 0u64;
+        for block in 0..num_blocks {
+            let doc_num_bits = (block % 20) as u8;
+            let tf_num_bits = (block % 3) as u8;
+            let tf_sum = 200 + block as u32;
+            skip_serializer.write_doc((block as u32 + 1) * 1_000, doc_num_bits);
+            skip_serializer.write_term_freq(tf_num_bits);
+            skip_serializer.write_total_term_freq(tf_sum);
+            skip_serializer.write_blockwand_max(block as u8, tf_sum);
+            byte_offset += compressed_block_size(doc_num_bits + tf_num_bits) as u64;
+            position_offset += tf_sum as u64;
+            skip_serializer.end_block(
+                (block as u32 + 1) * 1_000,
+                byte_offset,
+                Some(position_offset),
+            );
+        }
+        skip_serializer.data().to_owned()
+    }
+
+    #[test]
+    fn test_skip_upper_level() {
+        let num_blocks = 23;
+        let doc_freq = (num_blocks * COMPRESSION_BLOCK_SIZE + 7) as u32;
+        let one_level_data = skip_data_for_test(1, num_blocks);
+        let two_levels_data = skip_data_for_test(4, num_blocks);
+        assert_eq!(
+            two_levels_data.len(),
+            one_level_data.len() + num_upper_entries(doc_freq, 4) * 20
+        );
+        let new_reader = |data: &[u8], skip_interval: usize| {
+            SkipReader::new(
+                OwnedBytes::new(data.to_vec()),
+                doc_freq,
+                IndexRecordOption::WithFreqsAndPositions,
+                skip_interval,
+            )
+        };
+        let assert_same_state = |left: &SkipReader, right: &SkipReader| {
+            assert_eq!(left.last_doc_in_block(), right.last_doc_in_block());
+            assert_eq!(
+                left.last_doc_in_previous_block,
+                right.last_doc_in_previous_block
+            );
+            assert_eq!(left.byte_offset(), right.byte_offset());
+            assert_eq!(left.position_offset(), right.position_offset());
+            assert_eq!(left.block_info(), right.block_info());
+        };
+        for targets in [
+            vec![1, 2_500, 2_600, 9_001, 9_002, 17_000, 23_001],
+            vec![16_000, 16_001],
+            vec![4_000, 4_001, 8_000, 8_001, 30_000],
+        ] {
+            let mut one_level_reader = new_reader(&one_level_data, 1);
+            let mut two_levels_reader = new_reader(&two_levels_data, 4);
+            for target in targets {
+                assert_eq!(
+                    one_level_reader.seek(target),
+                    two_levels_reader.seek(target)
+                );
+                assert_same_state(&one_level_reader, &two_levels_reader);
+                one_level_reader.advance();
+                two_levels_reader.advance();
+                assert_same_state(&one_level_reader, &two_levels_reader);
+            }
+        }
+    }
+
+    #[test]
+    fn test_postings_skip_interval_header() {
+        assert_eq!(read_postings_skip_interval(None).unwrap(), 1);
+        let mut header = Vec::new();
+        VInt(16).serialize(&mut header).unwrap();
+        assert_eq!(
+            read_postings_skip_interval(Some(FileSlice::from(header))).unwrap(),
+            16
+        );
+        let mut invalid_header = Vec::new();
+        VInt(0).serialize(&mut invalid_header).unwrap();
+        assert!(read_postings_skip_interval(Some(FileSlice::from(invalid_header))).is_err());
+        assert!(validate_postings_skip_interval(1).is_ok());
+        assert!(validate_postings_skip_interval(MAX_POSTINGS_SKIP_INTERVAL).is_ok());
+        assert!(validate_postings_skip_interval(0).is_err());
+        assert!(validate_postings_skip_interval(MAX_POSTINGS_SKIP_INTERVAL + 1).is_err());
+    }
+
     #[test]
     fn test_encode_decode_bitwidth() {
         for bitwidth in 0..=32 {
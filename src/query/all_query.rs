@@ -3,7 +3,7 @@ use crate::index::SegmentReader;
 use crate::query::boost_query::BoostScorer;
 use crate::query::explanation::does_not_match;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
-use crate::{DocId, Score};
+use crate::{DocId, Score, Searcher};
 
 /// Query that matches all of the documents.
 ///
@@ -15,6 +15,10 @@ impl Query for AllQuery {
     fn weight(&self, _: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
         Ok(Box::new(AllWeight))
     }
+
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        Ok(Some(searcher.max_doc()))
+    }
 }
 
 /// Weight associated with the `AllQuery` query.
@@ -72,7 +72,7 @@ where
         let term_dict = inverted_index.terms();
         let mut term_stream = self.automaton_stream(term_dict)?;
         let mut term_infos = Vec::new();
-        while term_stream.advance() {
+        while term_stream.try_advance()? {
             term_infos.push(term_stream.value().clone());
         }
         Ok(term_infos)
@@ -90,7 +90,7 @@ where
         let inverted_index = reader.inverted_index(self.field)?;
         let term_dict = inverted_index.terms();
         let mut term_stream = self.automaton_stream(term_dict)?;
-        while term_stream.advance() {
+        while term_stream.try_advance()? {
             let term_info = term_stream.value();
             let mut block_segment_postings = inverted_index
                 .read_block_postings_from_terminfo(term_info, IndexRecordOption::Basic)?;
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use crate::fieldnorm::FieldNormReader;
 use crate::query::Explanation;
 use crate::schema::Field;
-use crate::{Score, Searcher, Term};
+use crate::{DocId, Score, Searcher, Term};
 
 const K1: Score = 1.2;
 const B: Score = 0.75;
@@ -68,6 +68,23 @@ fn compute_tf_cache(average_fieldnorm: Score) -> [Score; 256] {
     cache
 }
 
+#[inline]
+fn tf_factor_with_norm(norm: Score, term_freq: u32) -> Score {
+    let term_freq = term_freq as Score;
+    term_freq / (term_freq + norm)
+}
+
+/// Returns the BM25 score of a term, given its statistics and its occurrences in a document.
+pub(crate) fn bm25_term_score(
+    idf: Score,
+    average_fieldnorm: Score,
+    fieldnorm: u32,
+    term_freq: u32,
+) -> Score {
+    let norm = cached_tf_component(fieldnorm, average_fieldnorm);
+    idf * (1.0 + K1) * tf_factor_with_norm(norm, term_freq)
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Bm25Params {
     pub idf: Score,
@@ -81,6 +98,7 @@ pub struct Bm25Weight {
     weight: Score,
     cache: [Score; 256],
     average_fieldnorm: Score,
+    binary_term_freq: bool,
 }
 
 impl Bm25Weight {
@@ -91,6 +109,28 @@ impl Bm25Weight {
             weight: self.weight * boost,
             cache: self.cache,
             average_fieldnorm: self.average_fieldnorm,
+            binary_term_freq: self.binary_term_freq,
+        }
+    }
+
+    /// Returns a weight scoring the term frequencies as binary: a document containing the term
+    /// several times scores as if it contained it once.
+    ///
+    /// The length normalization still applies.
+    pub fn with_binary_term_freq(self) -> Bm25Weight {
+        Bm25Weight {
+            binary_term_freq: true,
+            ..self
+        }
+    }
+
+    /// Returns the term frequency used in the score, clamped to 1 for binary term frequencies.
+    #[inline]
+    fn scored_term_freq(&self, term_freq: u32) -> u32 {
+        if self.binary_term_freq {
+            term_freq.min(1)
+        } else {
+            term_freq
         }
     }
 
@@ -165,6 +205,7 @@ impl Bm25Weight {
             weight,
             cache: compute_tf_cache(average_fieldnorm),
             average_fieldnorm,
+            binary_term_freq: false,
         }
     }
     pub(crate) fn new_without_explain(idf: f32, average_fieldnorm: Score) -> Bm25Weight {
@@ -174,6 +215,7 @@ impl Bm25Weight {
             weight,
             cache: compute_tf_cache(average_fieldnorm),
             average_fieldnorm,
+            binary_term_freq: false,
         }
     }
 
@@ -183,40 +225,108 @@ impl Bm25Weight {
         self.weight * self.tf_factor(fieldnorm_id, term_freq)
     }
 
+    /// Compute the BM25 score of a single document, given its exact fieldnorm.
+    #[inline]
+    pub fn score_with_fieldnorm(&self, fieldnorm: u32, term_freq: u32) -> Score {
+        let norm = cached_tf_component(fieldnorm, self.average_fieldnorm);
+        self.weight * tf_factor_with_norm(norm, self.scored_term_freq(term_freq))
+    }
+
+    /// Compute the BM25 score of `doc`, using its exact fieldnorm if the
+    /// field records exact fieldnorms.
+    #[inline]
+    pub(crate) fn score_doc(
+        &self,
+        fieldnorm_reader: &FieldNormReader,
+        doc: DocId,
+        term_freq: u32,
+    ) -> Score {
+        match fieldnorm_reader.exact_fieldnorm(doc) {
+            Some(fieldnorm) => self.score_with_fieldnorm(fieldnorm, term_freq),
+            None => self.score(fieldnorm_reader.fieldnorm_id(doc), term_freq),
+        }
+    }
+
     /// Compute the maximum possible BM25 score given this weight.
     pub fn max_score(&self) -> Score {
-        self.score(255u8, 2_013_265_944)
+        if self.binary_term_freq {
+            // The term frequency is clamped to 1, so the score is the highest for the
+            // shortest fieldnorm.
+            self.score(0u8, 1)
+        } else {
+            self.score(255u8, 2_013_265_944)
+        }
+    }
+
+    /// Compute an upper bound of the BM25 scores of a block, given the (fieldnorm_id, term
+    /// frequency) pair maximizing the score of the block without binary term frequencies.
+    ///
+    /// With binary term frequencies, the pair may not maximize the score anymore, so the
+    /// maximum possible score is returned.
+    #[inline]
+    pub(crate) fn block_max_score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
+        if self.binary_term_freq {
+            self.max_score()
+        } else {
+            self.score(fieldnorm_id, term_freq)
+        }
     }
 
     #[inline]
     pub(crate) fn tf_factor(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
-        let term_freq = term_freq as Score;
-        let norm = self.cache[fieldnorm_id as usize];
-        term_freq / (term_freq + norm)
+        tf_factor_with_norm(
+            self.cache[fieldnorm_id as usize],
+            self.scored_term_freq(term_freq),
+        )
     }
 
     /// Produce an [Explanation] of a BM25 score.
     pub fn explain(&self, fieldnorm_id: u8, term_freq: u32) -> Explanation {
+        let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id);
+        self.explain_with_norm(self.cache[fieldnorm_id as usize], fieldnorm, term_freq)
+    }
+
+    /// Produce an [Explanation] of the BM25 score of `doc`, using its exact
+    /// fieldnorm if the field records exact fieldnorms.
+    pub(crate) fn explain_doc(
+        &self,
+        fieldnorm_reader: &FieldNormReader,
+        doc: DocId,
+        term_freq: u32,
+    ) -> Explanation {
+        match fieldnorm_reader.exact_fieldnorm(doc) {
+            Some(fieldnorm) => {
+                let norm = cached_tf_component(fieldnorm, self.average_fieldnorm);
+                self.explain_with_norm(norm, fieldnorm, term_freq)
+            }
+            None => self.explain(fieldnorm_reader.fieldnorm_id(doc), term_freq),
+        }
+    }
+
+    fn explain_with_norm(&self, norm: Score, fieldnorm: u32, term_freq: u32) -> Explanation {
         // The explain format is directly copied from Lucene's.
         // (So, Kudos to Lucene)
-        let score = self.score(fieldnorm_id, term_freq);
-
-        let norm = self.cache[fieldnorm_id as usize];
+        let term_freq = self.scored_term_freq(term_freq);
+        let right_factor = tf_factor_with_norm(norm, term_freq);
+        let score = self.weight * right_factor;
         let term_freq = term_freq as Score;
-        let right_factor = term_freq / (term_freq + norm);
 
         let mut tf_explanation = Explanation::new(
             "freq / (freq + k1 * (1 - b + b * dl / avgdl))",
             right_factor,
         );
 
-        tf_explanation.add_const("freq, occurrences of term within document", term_freq);
+        if self.binary_term_freq {
+            tf_explanation.add_const(
+                "freq, occurrences of term within document, clamped to 1",
+                term_freq,
+            );
+        } else {
+            tf_explanation.add_const("freq, occurrences of term within document", term_freq);
+        }
         tf_explanation.add_const("k1, term saturation parameter", K1);
         tf_explanation.add_const("b, length normalization parameter", B);
-        tf_explanation.add_const(
-            "dl, length of field",
-            FieldNormReader::id_to_fieldnorm(fieldnorm_id) as Score,
-        );
+        tf_explanation.add_const("dl, length of field", fieldnorm as Score);
         tf_explanation.add_const("avgdl, average length of field", self.average_fieldnorm);
 
         let mut explanation = Explanation::new("TermQuery, product of...", score);
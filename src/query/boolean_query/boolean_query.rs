@@ -1,6 +1,7 @@
 use super::boolean_weight::BooleanWeight;
 use crate::query::{EnableScoring, Occur, Query, SumCombiner, TermQuery, Weight};
 use crate::schema::{IndexRecordOption, Term};
+use crate::Searcher;
 
 /// The boolean query returns a set of documents
 /// that matches the Boolean combination of constituent subqueries.
@@ -168,6 +169,35 @@ impl Query for BooleanQuery {
         )))
     }
 
+    /// A conjunction costs as much as its cheapest required clause, which drives the
+    /// intersection, and a disjunction costs the sum of its clauses. The excluded clauses are
+    /// only probed for the matching documents, and are not counted.
+    ///
+    /// Without any required or optional clause, the cost is the number of documents of the
+    /// searcher.
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        let mut must_cost: Option<u64> = None;
+        let mut should_cost: Option<u64> = None;
+        for (occur, subquery) in &self.subqueries {
+            match occur {
+                Occur::Must => {
+                    let cost = searcher.query_cost(subquery.as_ref())?;
+                    must_cost = Some(must_cost.map_or(cost, |must_cost| must_cost.min(cost)));
+                }
+                Occur::Should => {
+                    let cost = searcher.query_cost(subquery.as_ref())?;
+                    should_cost = Some(should_cost.unwrap_or(0).saturating_add(cost));
+                }
+                Occur::MustNot => {}
+            }
+        }
+        Ok(Some(
+            must_cost
+                .or(should_cost)
+                .unwrap_or_else(|| searcher.max_doc()),
+        ))
+    }
+
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         for (_occur, subquery) in &self.subqueries {
             subquery.query_terms(visitor);
@@ -3,7 +3,7 @@ use std::fmt;
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
 use crate::fastfield::AliveBitSet;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
-use crate::{DocId, DocSet, Score, SegmentReader, Term};
+use crate::{DocId, DocSet, Score, Searcher, SegmentReader, Term};
 
 /// `BoostQuery` is a wrapper over a query used to boost its score.
 ///
@@ -48,6 +48,10 @@ impl Query for BoostQuery {
         Ok(boosted_weight)
     }
 
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        self.query.cost(searcher)
+    }
+
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor)
     }
@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
-use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
+use crate::{DocId, DocSet, Score, Searcher, SegmentReader, TantivyError, Term};
 
 /// `ConstScoreQuery` is a wrapper over a query to provide a constant score.
 /// It can avoid unnecessary score computation on the wrapped query.
@@ -46,18 +46,22 @@ impl Query for ConstScoreQuery {
         })
     }
 
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        self.query.cost(searcher)
+    }
+
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor);
     }
 }
 
-struct ConstWeight {
+pub(crate) struct ConstWeight {
     weight: Box<dyn Weight>,
     score: Score,
 }
 
 impl ConstWeight {
-    pub fn new(weight: Box<dyn Weight>, score: Score) -> Self {
+    pub(crate) fn new(weight: Box<dyn Weight>, score: Score) -> Self {
         ConstWeight { weight, score }
     }
 }
@@ -1,5 +1,5 @@
 use crate::query::{BooleanWeight, DisjunctionMaxCombiner, EnableScoring, Occur, Query, Weight};
-use crate::{Score, Term};
+use crate::{Score, Searcher, Term};
 
 /// The disjunction max query returns documents matching one or more wrapped queries,
 /// called query clauses or clauses.
@@ -104,6 +104,14 @@ impl Query for DisjunctionMaxQuery {
         )))
     }
 
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        let mut cost = 0u64;
+        for disjunct in &self.disjuncts {
+            cost = cost.saturating_add(searcher.query_cost(disjunct.as_ref())?);
+        }
+        Ok(Some(cost))
+    }
+
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         for disjunct in &self.disjuncts {
             disjunct.query_terms(visitor);
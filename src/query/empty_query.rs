@@ -16,6 +16,10 @@ impl Query for EmptyQuery {
         Ok(Box::new(EmptyWeight))
     }
 
+    fn cost(&self, _searcher: &Searcher) -> crate::Result<Option<u64>> {
+        Ok(Some(0))
+    }
+
     fn count(&self, _searcher: &Searcher) -> crate::Result<usize> {
         Ok(0)
     }
@@ -0,0 +1,277 @@
+use std::cmp::Ordering;
+
+use crate::query::{AllQuery, ColumnValue, EnableScoring, PredicateQuery, Query, Weight};
+use crate::schema::{Field, FieldType};
+use crate::TantivyError;
+
+/// A comparison between two values, as used by [`FieldComparisonQuery`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    /// `a < b`
+    LessThan,
+    /// `a <= b`
+    LessThanOrEqual,
+    /// `a == b`
+    Equal,
+    /// `a != b`
+    NotEqual,
+    /// `a >= b`
+    GreaterThanOrEqual,
+    /// `a > b`
+    GreaterThan,
+}
+
+impl ComparisonOperator {
+    /// Returns true if `a` and `b`, ordered by `ordering`, satisfy the comparison.
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            ComparisonOperator::LessThan => ordering.is_lt(),
+            ComparisonOperator::LessThanOrEqual => ordering.is_le(),
+            ComparisonOperator::Equal => ordering.is_eq(),
+            ComparisonOperator::NotEqual => ordering.is_ne(),
+            ComparisonOperator::GreaterThanOrEqual => ordering.is_ge(),
+            ComparisonOperator::GreaterThan => ordering.is_gt(),
+        }
+    }
+}
+
+/// Query matching the documents whose values for two fast fields satisfy a comparison, such as
+/// `sale_price < list_price`.
+///
+/// Both fields must be fast fields of the same type, among `u64`, `i64`, `f64`, `bool` and
+/// date. For each candidate document, the first value of each field is read from the fast
+/// fields. Documents lacking a value for either field are excluded, and so are the documents
+/// with a `NaN` value.
+///
+/// By default, every document of the index is a candidate, which results in a full scan of
+/// both columns. [`FieldComparisonQuery::with_base_query`] restricts the candidates to the
+/// documents matched by a cheaper query. The score is the score of the base query.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::{ComparisonOperator, FieldComparisonQuery};
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let sale_price = schema_builder.add_f64_field("sale_price", FAST);
+/// let list_price = schema_builder.add_f64_field("list_price", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(sale_price => 8.0, list_price => 10.0))?;
+/// index_writer.add_document(doc!(sale_price => 10.0, list_price => 10.0))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let discounted =
+///     FieldComparisonQuery::new(sale_price, ComparisonOperator::LessThan, list_price);
+/// assert_eq!(searcher.search(&discounted, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FieldComparisonQuery {
+    base_query: Box<dyn Query>,
+    field_a: Field,
+    operator: ComparisonOperator,
+    field_b: Field,
+}
+
+impl Clone for FieldComparisonQuery {
+    fn clone(&self) -> Self {
+        FieldComparisonQuery {
+            base_query: self.base_query.box_clone(),
+            field_a: self.field_a,
+            operator: self.operator,
+            field_b: self.field_b,
+        }
+    }
+}
+
+impl FieldComparisonQuery {
+    /// Creates a query matching the documents where `field_a operator field_b` holds.
+    ///
+    /// This constructor never fails, but executing the search with this query returns an
+    /// error if the fields are not fast fields of the same supported type.
+    pub fn new(
+        field_a: Field,
+        operator: ComparisonOperator,
+        field_b: Field,
+    ) -> FieldComparisonQuery {
+        FieldComparisonQuery {
+            base_query: Box::new(AllQuery),
+            field_a,
+            operator,
+            field_b,
+        }
+    }
+
+    /// Only considers the documents matched by `base_query`.
+    #[must_use]
+    pub fn with_base_query(self, base_query: Box<dyn Query>) -> FieldComparisonQuery {
+        FieldComparisonQuery { base_query, ..self }
+    }
+
+    /// Returns the comparison operator of the query.
+    pub fn operator(&self) -> ComparisonOperator {
+        self.operator
+    }
+}
+
+/// Compares two fast field values of the same type.
+fn compare(value_a: &ColumnValue, value_b: &ColumnValue) -> Option<Ordering> {
+    match (value_a, value_b) {
+        (ColumnValue::U64(a), ColumnValue::U64(b)) => Some(a.cmp(b)),
+        (ColumnValue::I64(a), ColumnValue::I64(b)) => Some(a.cmp(b)),
+        (ColumnValue::F64(a), ColumnValue::F64(b)) => a.partial_cmp(b),
+        (ColumnValue::Bool(a), ColumnValue::Bool(b)) => Some(a.cmp(b)),
+        (ColumnValue::Date(a), ColumnValue::Date(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+impl Query for FieldComparisonQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field_entry_a = schema.get_field_entry(self.field_a);
+        let field_entry_b = schema.get_field_entry(self.field_b);
+        if field_entry_a.field_type().value_type() != field_entry_b.field_type().value_type() {
+            return Err(TantivyError::SchemaError(format!(
+                "Cannot compare field {:?} of type {:?} with field {:?} of type {:?}",
+                field_entry_a.name(),
+                field_entry_a.field_type().value_type(),
+                field_entry_b.name(),
+                field_entry_b.field_type().value_type()
+            )));
+        }
+        if matches!(field_entry_a.field_type(), FieldType::Str(_)) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} of type Str cannot be compared",
+                field_entry_a.name()
+            )));
+        }
+        let operator = self.operator;
+        let predicate_query = PredicateQuery::new(
+            self.base_query.box_clone(),
+            vec![
+                field_entry_a.name().to_string(),
+                field_entry_b.name().to_string(),
+            ],
+            move |values: &[ColumnValue]| {
+                compare(&values[0], &values[1]).is_some_and(|ordering| operator.holds(ordering))
+            },
+        );
+        predicate_query.weight(enable_scoring)
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a crate::Term, bool)) {
+        self.base_query.query_terms(visitor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComparisonOperator, FieldComparisonQuery};
+    use crate::collector::DocSetCollector;
+    use crate::query::{Query, QueryParser};
+    use crate::schema::{Schema, FAST, INDEXED, TEXT};
+    use crate::{DateTime, Index, IndexWriter, Searcher, TantivyError};
+
+    fn matching_doc_ids(searcher: &Searcher, query: &dyn Query) -> crate::Result<Vec<u32>> {
+        let mut doc_ids: Vec<u32> = searcher
+            .search(query, &DocSetCollector)?
+            .into_iter()
+            .map(|doc_address| doc_address.doc_id)
+            .collect();
+        doc_ids.sort_unstable();
+        Ok(doc_ids)
+    }
+
+    #[test]
+    fn test_field_comparison_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let sale_price = schema_builder.add_f64_field("sale_price", FAST);
+        let list_price = schema_builder.add_f64_field("list_price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer
+            .add_document(doc!(title => "red shirt", sale_price => 8.0, list_price => 10.0))?;
+        index_writer
+            .add_document(doc!(title => "red hat", sale_price => 10.0, list_price => 10.0))?;
+        index_writer
+            .add_document(doc!(title => "blue hat", sale_price => 12.0, list_price => 10.0))?;
+        // Documents lacking a value are excluded, whatever the operator.
+        index_writer.add_document(doc!(title => "red scarf", sale_price => 5.0))?;
+        index_writer.add_document(doc!(title => "red shoes", list_price => 5.0))?;
+        index_writer
+            .add_document(doc!(title => "red socks", sale_price => f64::NAN, list_price => 1.0))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        for (operator, expected) in [
+            (ComparisonOperator::LessThan, vec![0]),
+            (ComparisonOperator::LessThanOrEqual, vec![0, 1]),
+            (ComparisonOperator::Equal, vec![1]),
+            (ComparisonOperator::NotEqual, vec![0, 2]),
+            (ComparisonOperator::GreaterThanOrEqual, vec![1, 2]),
+            (ComparisonOperator::GreaterThan, vec![2]),
+        ] {
+            let query = FieldComparisonQuery::new(sale_price, operator, list_price);
+            assert_eq!(query.operator(), operator);
+            assert_eq!(
+                matching_doc_ids(&searcher, &query)?,
+                expected,
+                "{operator:?}"
+            );
+        }
+
+        let red = QueryParser::for_index(&index, vec![title]).parse_query("red")?;
+        let query =
+            FieldComparisonQuery::new(sale_price, ComparisonOperator::LessThanOrEqual, list_price)
+                .with_base_query(red);
+        assert_eq!(matching_doc_ids(&searcher, &query)?, vec![0, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_comparison_query_dates() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let start = schema_builder.add_date_field("start", FAST);
+        let end = schema_builder.add_date_field("end", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let date = DateTime::from_timestamp_secs;
+        index_writer.add_document(doc!(start => date(1), end => date(2)))?;
+        index_writer.add_document(doc!(start => date(3), end => date(2)))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = FieldComparisonQuery::new(end, ComparisonOperator::LessThan, start);
+        assert_eq!(matching_doc_ids(&searcher, &query)?, vec![1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_comparison_query_invalid_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price_u64 = schema_builder.add_u64_field("price_u64", FAST);
+        let price_i64 = schema_builder.add_i64_field("price_i64", FAST);
+        let not_fast = schema_builder.add_i64_field("not_fast", INDEXED);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        for (field_a, field_b) in [
+            (price_u64, price_i64),
+            (price_i64, not_fast),
+            (title, title),
+        ] {
+            let query = FieldComparisonQuery::new(field_a, ComparisonOperator::Equal, field_b);
+            assert!(matches!(
+                searcher.search(&query, &DocSetCollector),
+                Err(TantivyError::SchemaError(_))
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::ops::Bound;
+
+use crate::fastfield::FastValue;
+use crate::query::{BooleanQuery, ConstScoreQuery, EnableScoring, Query, RangeQuery, Weight};
+use crate::schema::{Field, Term};
+use crate::TantivyError;
+
+/// Query matching the documents whose interval overlaps a query interval.
+///
+/// The interval of a document is indexed as two single-valued fast fields, holding its start
+/// and its end. A document matches if it has a value for both fields, and its interval shares at
+/// least one point with the query interval. Only the two columns are read, through the fast field
+/// range machinery of [`RangeQuery`].
+///
+/// # Boundaries
+///
+/// [`IntervalOverlapQuery::new`] treats both the document intervals and the query interval as
+/// closed: `[start, end]` overlaps `[query_start, query_end]` if
+/// `start <= query_end && end >= query_start`. Intervals touching at a single point overlap.
+///
+/// [`IntervalOverlapQuery::half_open`] treats them as half open instead: `[start, end)` overlaps
+/// `[query_start, query_end)` if `start < query_end && end > query_start`. Intervals touching
+/// at a single point do not overlap, which fits events following each other.
+///
+/// All of the matched documents get the score 1.0.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::IntervalOverlapQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let start = schema_builder.add_u64_field("start", FAST);
+/// let end = schema_builder.add_u64_field("end", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(start => 10u64, end => 20u64))?;
+/// index_writer.add_document(doc!(start => 30u64, end => 40u64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = IntervalOverlapQuery::new(start, end, 15u64, 30u64);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// let query = IntervalOverlapQuery::half_open(start, end, 20u64, 30u64);
+/// assert_eq!(searcher.search(&query, &Count)?, 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct IntervalOverlapQuery {
+    start_field: Field,
+    end_field: Field,
+    query_start: Term,
+    query_end: Term,
+    half_open: bool,
+}
+
+impl IntervalOverlapQuery {
+    /// Creates a query matching the documents whose closed interval
+    /// `[start_field, end_field]` overlaps `[query_start, query_end]`.
+    ///
+    /// This constructor never fails, but executing the search with this query returns an
+    /// error if one of the fields is not a fast field of type `T`.
+    pub fn new<T: FastValue>(
+        start_field: Field,
+        end_field: Field,
+        query_start: T,
+        query_end: T,
+    ) -> IntervalOverlapQuery {
+        IntervalOverlapQuery {
+            start_field,
+            end_field,
+            // The start of the query bounds the end of the documents, and vice versa.
+            query_start: Term::from_fast_value(end_field, &query_start),
+            query_end: Term::from_fast_value(start_field, &query_end),
+            half_open: false,
+        }
+    }
+
+    /// Creates a query matching the documents whose half open interval
+    /// `[start_field, end_field)` overlaps `[query_start, query_end)`.
+    ///
+    /// This constructor never fails, but executing the search with this query returns an
+    /// error if one of the fields is not a fast field of type `T`.
+    pub fn half_open<T: FastValue>(
+        start_field: Field,
+        end_field: Field,
+        query_start: T,
+        query_end: T,
+    ) -> IntervalOverlapQuery {
+        IntervalOverlapQuery {
+            half_open: true,
+            ..IntervalOverlapQuery::new(start_field, end_field, query_start, query_end)
+        }
+    }
+
+    /// Returns true if the intervals are half open.
+    pub fn is_half_open(&self) -> bool {
+        self.half_open
+    }
+}
+
+impl Query for IntervalOverlapQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        for (field, term) in [
+            (self.start_field, &self.query_end),
+            (self.end_field, &self.query_start),
+        ] {
+            let field_entry = schema.get_field_entry(field);
+            if !field_entry.is_fast() {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is not a fast field",
+                    field_entry.name()
+                )));
+            }
+            if field_entry.field_type().value_type() != term.typ() {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {:?} is of type {:?}, not {:?}",
+                    field_entry.name(),
+                    field_entry.field_type().value_type(),
+                    term.typ()
+                )));
+            }
+        }
+        let bound = |term: &Term| {
+            if self.half_open {
+                Bound::Excluded(term.clone())
+            } else {
+                Bound::Included(term.clone())
+            }
+        };
+        let starts_before_query_end = RangeQuery::new(Bound::Unbounded, bound(&self.query_end));
+        let ends_after_query_start = RangeQuery::new(bound(&self.query_start), Bound::Unbounded);
+        let overlap_query = BooleanQuery::intersection(vec![
+            Box::new(starts_before_query_end),
+            Box::new(ends_after_query_start),
+        ]);
+        ConstScoreQuery::new(Box::new(overlap_query), 1.0).weight(enable_scoring)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalOverlapQuery;
+    use crate::collector::DocSetCollector;
+    use crate::query::Query;
+    use crate::schema::{Schema, FAST, INDEXED, STORED};
+    use crate::{Index, IndexWriter, Searcher, TantivyError};
+
+    fn matching_ids(searcher: &Searcher, query: &dyn Query) -> crate::Result<Vec<u64>> {
+        let mut ids: Vec<u64> = searcher
+            .search(query, &DocSetCollector)?
+            .into_iter()
+            .map(|doc_address| {
+                let fast_field = searcher
+                    .segment_reader(doc_address.segment_ord)
+                    .fast_fields();
+                fast_field
+                    .u64("id")
+                    .unwrap()
+                    .first(doc_address.doc_id)
+                    .unwrap()
+            })
+            .collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    #[test]
+    fn test_interval_overlap_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let start = schema_builder.add_i64_field("start", FAST);
+        let end = schema_builder.add_i64_field("end", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => 0u64, start => -10i64, end => 0i64))?;
+        index_writer.add_document(doc!(id => 1u64, start => 0i64, end => 10i64))?;
+        index_writer.add_document(doc!(id => 2u64, start => 3i64, end => 5i64))?;
+        index_writer.add_document(doc!(id => 3u64, start => 10i64, end => 20i64))?;
+        index_writer.add_document(doc!(id => 4u64, start => 30i64, end => 40i64))?;
+        // Documents without an end are not matched.
+        index_writer.add_document(doc!(id => 5u64, start => 0i64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        // Nested intervals.
+        let query = IntervalOverlapQuery::new(start, end, 4i64, 4i64);
+        assert_eq!(matching_ids(&searcher, &query)?, [1, 2]);
+        let query = IntervalOverlapQuery::new(start, end, -20i64, 25i64);
+        assert_eq!(matching_ids(&searcher, &query)?, [0, 1, 2, 3]);
+        // Touching intervals.
+        let query = IntervalOverlapQuery::new(start, end, 10i64, 10i64);
+        assert_eq!(matching_ids(&searcher, &query)?, [1, 3]);
+        let query = IntervalOverlapQuery::half_open(start, end, 10i64, 11i64);
+        assert!(query.is_half_open());
+        assert_eq!(matching_ids(&searcher, &query)?, [3]);
+        let query = IntervalOverlapQuery::half_open(start, end, 20i64, 30i64);
+        assert!(matching_ids(&searcher, &query)?.is_empty());
+        // Disjoint intervals.
+        let query = IntervalOverlapQuery::new(start, end, 21i64, 29i64);
+        assert!(matching_ids(&searcher, &query)?.is_empty());
+        let query = IntervalOverlapQuery::new(start, end, 41i64, 50i64);
+        assert!(matching_ids(&searcher, &query)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_interval_overlap_query_invalid_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let start = schema_builder.add_u64_field("start", FAST);
+        let end = schema_builder.add_u64_field("end", INDEXED | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let query = IntervalOverlapQuery::new(start, end, 1u64, 2u64);
+        assert!(matches!(
+            searcher.search(&query, &DocSetCollector),
+            Err(TantivyError::SchemaError(_))
+        ));
+        let query = IntervalOverlapQuery::new(start, start, 1i64, 2i64);
+        assert!(matches!(
+            searcher.search(&query, &DocSetCollector),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -11,21 +11,28 @@ mod empty_query;
 mod exclude;
 mod exist_query;
 mod explanation;
+mod field_comparison_query;
 mod fuzzy_query;
 mod intersection;
+mod interval_overlap_query;
 mod more_like_this;
+mod numeric_set_query;
 mod phrase_prefix_query;
 mod phrase_query;
+mod predicate_query;
 mod query;
 mod query_parser;
 mod range_query;
+mod recency_decay_query;
 mod regex_query;
 mod reqopt_scorer;
 mod scorer;
 mod set_query;
 mod term_query;
 mod union;
+mod visibility_query;
 mod weight;
+mod weighted_terms_query;
 
 #[cfg(test)]
 mod vec_docset;
@@ -36,6 +43,7 @@ pub use query_grammar::Occur;
 pub use self::all_query::{AllQuery, AllScorer, AllWeight};
 pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
+pub(crate) use self::bm25::{bm25_term_score, idf};
 pub use self::bm25::{Bm25StatisticsProvider, Bm25Weight};
 pub use self::boolean_query::{BooleanQuery, BooleanWeight};
 pub use self::boost_query::{BoostQuery, BoostWeight};
@@ -45,17 +53,22 @@ pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
 pub use self::exist_query::ExistsQuery;
 pub use self::explanation::Explanation;
+pub use self::field_comparison_query::{ComparisonOperator, FieldComparisonQuery};
 #[cfg(test)]
 pub(crate) use self::fuzzy_query::DfaWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
 pub use self::intersection::{intersect_scorers, Intersection};
+pub use self::interval_overlap_query::IntervalOverlapQuery;
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
+pub use self::numeric_set_query::NumericSetQuery;
 pub use self::phrase_prefix_query::PhrasePrefixQuery;
 pub use self::phrase_query::regex_phrase_query::{wildcard_query_to_regex_str, RegexPhraseQuery};
 pub use self::phrase_query::PhraseQuery;
+pub use self::predicate_query::{ColumnValue, PredicateQuery, PredicateWeight};
 pub use self::query::{EnableScoring, Query, QueryClone};
-pub use self::query_parser::{QueryParser, QueryParserError};
+pub use self::query_parser::{QueryParser, QueryParserError, QueryTemplate};
 pub use self::range_query::*;
+pub use self::recency_decay_query::RecencyDecayQuery;
 pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
 pub use self::score_combiner::{DisjunctionMaxCombiner, ScoreCombiner, SumCombiner};
@@ -65,7 +78,9 @@ pub use self::term_query::TermQuery;
 pub use self::union::BufferedUnionScorer;
 #[cfg(test)]
 pub use self::vec_docset::VecDocSet;
+pub use self::visibility_query::{VisibilityQuery, VisibilityWeight};
 pub use self::weight::Weight;
+pub use self::weighted_terms_query::WeightedTermsQuery;
 
 #[cfg(test)]
 mod tests {
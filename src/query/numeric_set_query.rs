@@ -0,0 +1,410 @@
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use columnar::ColumnType;
+use common::BitSet;
+
+use crate::docset::DocSet;
+use crate::fastfield::FastValue;
+use crate::index::SegmentReader;
+use crate::query::const_score_query::ConstWeight;
+use crate::query::explanation::does_not_match;
+use crate::query::score_combiner::DoNothingCombiner;
+use crate::query::{
+    BitSetDocSet, BooleanWeight, ConstScorer, EmptyScorer, EmptyWeight, EnableScoring, Explanation,
+    Occur, Query, Scorer, TermQuery, TermSetQuery, Weight,
+};
+use crate::schema::{Field, FieldEntry, IndexRecordOption, Schema, Type};
+use crate::{DocId, Score, Searcher, TantivyError, Term};
+
+// Up to this number of values, reading the postings of each value is cheaper than streaming
+// the term dictionary through the automaton of the set.
+const MAX_NUM_TERM_QUERIES: usize = 128;
+
+// Up to this number of ranges of consecutive values, the column is scanned once per range,
+// which is vectorized, rather than decoding the values of each document.
+const MAX_NUM_RANGE_SCANS: usize = 4;
+
+/// Query matching the documents having one of a set of values in a numeric field, as in
+/// `status IN (1, 3, 7)`.
+///
+/// This is the numeric counterpart of the [`TermSetQuery`], and avoids the overhead of a
+/// [`BooleanQuery`](crate::query::BooleanQuery) over one [`TermQuery`](crate::query::TermQuery)
+/// per value. The strategy depends on the options of the field:
+/// - if the field is indexed, the postings of the values are merged. Large sets are looked up in
+///   the term dictionary with a single automaton, like the `TermSetQuery` does, rather than one
+///   value at a time.
+/// - otherwise, if the field is a fast field, its column is scanned. For a few values, or ranges of
+///   consecutive values, the scan looks for each of them in bulk. For more values, each value of
+///   the column is searched in the sorted set. The cost is linear in the number of documents.
+///
+/// All of the matching documents get the same score. An empty set matches no document.
+///
+/// The `u64`, `i64`, `f64` and `bool` fields are supported, the values having the type of the
+/// field. The values of date fields are truncated to different precisions in the index and in
+/// the fast fields, so date fields are not supported: a
+/// [`RangeQuery`](crate::query::RangeQuery) can match a date at a given precision.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::NumericSetQuery;
+/// use tantivy::schema::{Schema, FAST, INDEXED};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let status = schema_builder.add_u64_field("status", INDEXED);
+/// let priority = schema_builder.add_i64_field("priority", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(status => 1u64, priority => -1i64))?;
+/// index_writer.add_document(doc!(status => 2u64, priority => 0i64))?;
+/// index_writer.add_document(doc!(status => 3u64, priority => 1i64))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = NumericSetQuery::new(status, [1u64, 3, 7]);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// let query = NumericSetQuery::new(priority, [0i64, 1]);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NumericSetQuery {
+    field: Field,
+    value_type: Type,
+    // The values mapped to `u64`, sorted and deduplicated.
+    values: Arc<[u64]>,
+    term_set_query: TermSetQuery,
+}
+
+impl NumericSetQuery {
+    /// Creates a query matching the documents having one of `values` in `field`.
+    ///
+    /// This constructor never fails, but executing the search with this query returns an
+    /// error if the type of the values does not match the type of the field, or if the field
+    /// is neither indexed nor a fast field.
+    pub fn new<T: FastValue, I: IntoIterator<Item = T>>(field: Field, values: I) -> Self {
+        let mut values: Vec<u64> = values.into_iter().map(|value| value.to_u64()).collect();
+        values.sort_unstable();
+        values.dedup();
+        let terms = values
+            .iter()
+            .map(|&value| value_term(field, T::to_type(), value));
+        NumericSetQuery {
+            field,
+            value_type: T::to_type(),
+            term_set_query: TermSetQuery::new(terms),
+            values: values.into(),
+        }
+    }
+
+    fn term_queries_weight(&self, schema: &Schema) -> crate::Result<Box<dyn Weight>> {
+        let term_weights = self
+            .values
+            .iter()
+            .map(|&value| {
+                let term = value_term(self.field, self.value_type, value);
+                let term_query = TermQuery::new(term, IndexRecordOption::Basic);
+                let term_weight = term_query.weight(EnableScoring::disabled_from_schema(schema))?;
+                Ok((Occur::Should, term_weight))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        let union_weight = BooleanWeight::new(term_weights, false, Box::new(|| DoNothingCombiner));
+        // The union does not score its documents: they all get the boost as score.
+        Ok(Box::new(ConstWeight::new(Box::new(union_weight), 1.0)))
+    }
+
+    fn checked_field_entry<'a>(&self, schema: &'a Schema) -> crate::Result<&'a FieldEntry> {
+        let field_entry = schema.get_field_entry(self.field);
+        let field_value_type = field_entry.field_type().value_type();
+        if field_value_type == Type::Date {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is a date field, which is not supported by NumericSetQuery.",
+                field_entry.name()
+            )));
+        }
+        if field_value_type != self.value_type {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} of type {field_value_type:?} cannot be queried with {:?} values.",
+                field_entry.name(),
+                self.value_type
+            )));
+        }
+        if !field_entry.is_indexed() && !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is neither indexed nor a fast field.",
+                field_entry.name()
+            )));
+        }
+        Ok(field_entry)
+    }
+}
+
+impl Query for NumericSetQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = self.checked_field_entry(enable_scoring.schema())?;
+        if self.values.is_empty() {
+            return Ok(Box::new(EmptyWeight));
+        }
+        if field_entry.is_indexed() {
+            if self.values.len() <= MAX_NUM_TERM_QUERIES {
+                return self.term_queries_weight(enable_scoring.schema());
+            }
+            return self.term_set_query.weight(enable_scoring);
+        }
+        let column_type = match self.value_type {
+            Type::U64 => ColumnType::U64,
+            Type::I64 => ColumnType::I64,
+            Type::F64 => ColumnType::F64,
+            _ => ColumnType::Bool,
+        };
+        Ok(Box::new(NumericSetWeight {
+            field_name: field_entry.name().to_string(),
+            column_type,
+            values: self.values.clone(),
+        }))
+    }
+
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        if self.checked_field_entry(searcher.schema())?.is_indexed() {
+            self.term_set_query.cost(searcher)
+        } else {
+            // The column is scanned.
+            Ok(Some(searcher.num_docs()))
+        }
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.term_set_query.query_terms(visitor);
+    }
+}
+
+fn value_term(field: Field, value_type: Type, value: u64) -> Term {
+    let mut term = Term::with_type_and_field(value_type, field);
+    term.set_u64(value);
+    term
+}
+
+// Groups the sorted values into ranges of consecutive values.
+fn value_ranges(values: &[u64]) -> Vec<RangeInclusive<u64>> {
+    let mut ranges: Vec<RangeInclusive<u64>> = Vec::new();
+    for &value in values {
+        match ranges.last_mut() {
+            Some(range) if range.end().checked_add(1) == Some(value) => {
+                *range = *range.start()..=value;
+            }
+            _ => ranges.push(value..=value),
+        }
+    }
+    ranges
+}
+
+/// Weight scanning the column of a fast field, for the fields that are not indexed.
+struct NumericSetWeight {
+    field_name: String,
+    column_type: ColumnType,
+    values: Arc<[u64]>,
+}
+
+impl Weight for NumericSetWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some((column, _)) = reader
+            .fast_fields()
+            .u64_lenient_for_type(Some(&[self.column_type]), &self.field_name)?
+        else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let candidate_values = {
+            let start = self
+                .values
+                .partition_point(|&value| value < column.min_value());
+            let end = self
+                .values
+                .partition_point(|&value| value <= column.max_value());
+            &self.values[start..end.max(start)]
+        };
+        if candidate_values.is_empty() {
+            return Ok(Box::new(EmptyScorer));
+        }
+        let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+        let value_ranges = value_ranges(candidate_values);
+        if value_ranges.len() <= MAX_NUM_RANGE_SCANS {
+            let mut docs = Vec::new();
+            for value_range in value_ranges {
+                column.get_docids_for_value_range(value_range, 0..reader.max_doc(), &mut docs);
+                for &doc in &docs {
+                    doc_bitset.insert(doc);
+                }
+                docs.clear();
+            }
+            let doc_set = BitSetDocSet::from(doc_bitset);
+            return Ok(Box::new(ConstScorer::new(doc_set, boost)));
+        }
+        for doc in 0..reader.max_doc() {
+            if column
+                .values_for_doc(doc)
+                .any(|value| candidate_values.binary_search(&value).is_ok())
+            {
+                doc_bitset.insert(doc);
+            }
+        }
+        let doc_set = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_set, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("NumericSetQuery", scorer.score()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NumericSetQuery;
+    use crate::collector::{Count, DocSetCollector, TopDocs};
+    use crate::query::{BooleanQuery, BoostQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, NumericOptions, Schema, FAST, INDEXED};
+    use crate::{DocAddress, Index, IndexWriter, Score, Term};
+
+    #[test]
+    fn test_numeric_set_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let indexed = schema_builder.add_u64_field("indexed", INDEXED);
+        let fast = schema_builder.add_u64_field("fast", FAST);
+        let signed = schema_builder.add_i64_field("signed", FAST);
+        let float = schema_builder.add_f64_field("float", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..100u64 {
+            index_writer.add_document(doc!(
+                indexed => i % 10,
+                fast => i % 10,
+                signed => i as i64 % 10 - 5,
+                float => (i % 10) as f64 / 2.0,
+            ))?;
+            if i % 20 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        // A multivalued document, and a document without values.
+        index_writer.add_document(doc!(fast => 100u64, fast => 3u64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let docs = |query: &dyn Query| searcher.search(query, &DocSetCollector);
+
+        let expected = docs(&BooleanQuery::union(vec![
+            Box::new(TermQuery::new(
+                Term::from_field_u64(indexed, 1),
+                IndexRecordOption::Basic,
+            )),
+            Box::new(TermQuery::new(
+                Term::from_field_u64(indexed, 7),
+                IndexRecordOption::Basic,
+            )),
+        ]))?;
+        assert_eq!(expected.len(), 20);
+        let values = [7u64, 1, 7, 12];
+        assert_eq!(docs(&NumericSetQuery::new(indexed, values))?, expected);
+        assert_eq!(docs(&NumericSetQuery::new(fast, values))?, expected);
+
+        let count = |query: NumericSetQuery| searcher.search(&query, &Count);
+        assert_eq!(count(NumericSetQuery::new(fast, [3u64]))?, 11);
+        assert_eq!(count(NumericSetQuery::new(fast, [100u64, 1000]))?, 1);
+        assert_eq!(count(NumericSetQuery::new(fast, 2u64..=7))?, 61);
+        assert_eq!(count(NumericSetQuery::new(fast, Vec::<u64>::new()))?, 0);
+        assert_eq!(count(NumericSetQuery::new(indexed, Vec::<u64>::new()))?, 0);
+        assert_eq!(count(NumericSetQuery::new(signed, [-5i64, 4, 5]))?, 20);
+        assert_eq!(count(NumericSetQuery::new(float, [0.5f64, 4.0, 4.2]))?, 20);
+
+        // Large sets.
+        let odd_values = (0..10_000u64).filter(|value| value % 2 == 1);
+        assert_eq!(
+            count(NumericSetQuery::new(indexed, odd_values.clone()))?,
+            50
+        );
+        assert_eq!(count(NumericSetQuery::new(fast, odd_values))?, 51);
+
+        let top_docs = searcher.search(
+            &NumericSetQuery::new(fast, [1u64]),
+            &crate::collector::TopDocs::with_limit(1),
+        )?;
+        let doc_address: DocAddress = top_docs[0].1;
+        let explanation = NumericSetQuery::new(fast, [1u64]).explain(&searcher, doc_address)?;
+        assert_eq!(explanation.value(), 1.0);
+        assert!(NumericSetQuery::new(fast, [2u64])
+            .explain(&searcher, doc_address)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_numeric_set_query_boost() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let indexed = schema_builder.add_u64_field("indexed", INDEXED);
+        let fast = schema_builder.add_u64_field("fast", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..100u64 {
+            index_writer.add_document(doc!(indexed => i % 10, fast => i % 10))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let scores = |query: NumericSetQuery| -> crate::Result<Vec<Score>> {
+            let boosted_query = BoostQuery::new(Box::new(query), 2.5);
+            let top_docs = searcher.search(&boosted_query, &TopDocs::with_limit(100))?;
+            Ok(top_docs.into_iter().map(|(score, _)| score).collect())
+        };
+        // Postings of each value, and of a large set of values.
+        assert_eq!(
+            scores(NumericSetQuery::new(indexed, [3u64]))?,
+            vec![2.5; 10]
+        );
+        assert_eq!(
+            scores(NumericSetQuery::new(indexed, [1u64, 3, 7]))?,
+            vec![2.5; 30]
+        );
+        assert_eq!(
+            scores(NumericSetQuery::new(indexed, (0..1_000u64).step_by(3)))?,
+            vec![2.5; 40]
+        );
+        // Column scan.
+        assert_eq!(
+            scores(NumericSetQuery::new(fast, [1u64, 3, 7]))?,
+            vec![2.5; 30]
+        );
+        assert_eq!(
+            scores(NumericSetQuery::new(fast, (0..1_000u64).step_by(3)))?,
+            vec![2.5; 40]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_numeric_set_query_invalid_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let indexed = schema_builder.add_u64_field("indexed", INDEXED);
+        let stored = schema_builder.add_u64_field("stored", NumericOptions::default());
+        let date = schema_builder.add_date_field("date", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let is_schema_error = |query: NumericSetQuery| {
+            matches!(
+                searcher.search(&query, &Count),
+                Err(crate::TantivyError::SchemaError(_))
+            )
+        };
+        assert!(is_schema_error(NumericSetQuery::new(indexed, [1i64])));
+        assert!(is_schema_error(NumericSetQuery::new(stored, [1u64])));
+        assert!(is_schema_error(NumericSetQuery::new(
+            date,
+            [crate::DateTime::from_timestamp_secs(0)]
+        )));
+        Ok(())
+    }
+}
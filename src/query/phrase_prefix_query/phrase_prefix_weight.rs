@@ -87,7 +87,7 @@ impl PhrasePrefixWeight {
 
         let mut suffixes = Vec::with_capacity(self.max_expansions as usize);
         let mut new_term = self.prefix.1.clone();
-        while stream.advance() && (suffixes.len() as u32) < self.max_expansions {
+        while stream.try_advance()? && (suffixes.len() as u32) < self.max_expansions {
             new_term.clear_with_type(new_term.typ());
             new_term.append_bytes(stream.key());
             if reader.has_deletes() {
@@ -132,11 +132,14 @@ impl Weight for PhrasePrefixWeight {
             return Err(does_not_match(doc));
         }
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
-        let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
         let phrase_count = scorer.phrase_count();
         let mut explanation = Explanation::new("Phrase Prefix Scorer", scorer.score());
         if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
-            explanation.add_detail(similarity_weight.explain(fieldnorm_id, phrase_count));
+            explanation.add_detail(similarity_weight.explain_doc(
+                &fieldnorm_reader,
+                doc,
+                phrase_count,
+            ));
         }
         Ok(explanation)
     }
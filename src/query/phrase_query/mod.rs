@@ -273,6 +273,62 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_phrase_adjacent_any_order() -> crate::Result<()> {
+        let index = create_index(&[
+            "john smith",
+            "smith john",
+            "john and smith",
+            "mary smith",
+            "smith john smith",
+            "john john smith",
+            "c b a",
+            "a c x b",
+        ])?;
+        let text_field = index.schema().get_field("text").unwrap();
+        let searcher = index.reader()?.searcher();
+        let search = |texts: &[&str], slop: u32, adjacent_any_order: bool, with_score: bool| {
+            let terms: Vec<Term> = texts
+                .iter()
+                .map(|text| Term::from_field_text(text_field, text))
+                .collect();
+            let mut phrase_query = PhraseQuery::new(terms);
+            phrase_query.set_slop(slop);
+            phrase_query.set_adjacent_any_order(adjacent_any_order);
+            assert_eq!(phrase_query.adjacent_any_order(), adjacent_any_order);
+            let collector = if with_score {
+                TEST_COLLECTOR_WITH_SCORE
+            } else {
+                TEST_COLLECTOR_WITHOUT_SCORE
+            };
+            let fruits = searcher.search(&phrase_query, &collector).unwrap();
+            let doc_ids: Vec<DocId> = fruits.docs().iter().map(|doc| doc.doc_id).collect();
+            (doc_ids, fruits.scores().to_vec())
+        };
+        for with_score in [false, true] {
+            let (exact, _) = search(&["john", "smith"], 0, false, with_score);
+            assert_eq!(exact, vec![0, 4, 5]);
+            // The slop also allows the gap of "john and smith".
+            let (slop, _) = search(&["john", "smith"], 2, false, with_score);
+            assert_eq!(slop, vec![0, 1, 2, 4, 5]);
+            let (any_order, _) = search(&["john", "smith"], 0, true, with_score);
+            assert_eq!(any_order, vec![0, 1, 4, 5]);
+            // The slop is ignored.
+            let (any_order, _) = search(&["john", "smith"], 2, true, with_score);
+            assert_eq!(any_order, vec![0, 1, 4, 5]);
+            let (any_order, _) = search(&["a", "b", "c"], 0, true, with_score);
+            assert_eq!(any_order, vec![6]);
+            // A repeated term has to appear as many times in the block.
+            let (any_order, _) = search(&["john", "john", "smith"], 0, true, with_score);
+            assert_eq!(any_order, vec![5]);
+        }
+        // Both blocks of "smith john smith" are counted.
+        let (any_order, scores) = search(&["john", "smith"], 0, true, true);
+        assert_eq!(any_order[2], 4);
+        assert!(scores[2] > scores[0]);
+        Ok(())
+    }
+
     #[test] // motivated by #234
     pub fn test_phrase_query_docfreq_order() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
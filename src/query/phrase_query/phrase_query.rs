@@ -2,6 +2,7 @@ use super::PhraseWeight;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, Query, Weight};
 use crate::schema::{Field, IndexRecordOption, Term};
+use crate::Searcher;
 
 /// `PhraseQuery` matches a specific sequence of words.
 ///
@@ -24,6 +25,7 @@ pub struct PhraseQuery {
     field: Field,
     phrase_terms: Vec<(usize, Term)>,
     slop: u32,
+    adjacent_any_order: bool,
 }
 
 impl PhraseQuery {
@@ -60,6 +62,7 @@ impl PhraseQuery {
             field,
             phrase_terms: terms,
             slop,
+            adjacent_any_order: false,
         }
     }
 
@@ -81,6 +84,28 @@ impl PhraseQuery {
         self.slop = value;
     }
 
+    /// Requires the terms to be adjacent, in any order.
+    ///
+    /// The query then matches the documents where the terms fill a block of consecutive
+    /// positions, each term taking one position, whatever their order: "John Smith" matches
+    /// both "John Smith" and "Smith John", but not "John and Smith". Contrary to the slop,
+    /// which is a budget shared by the gaps and the reorderings, no gap is allowed.
+    ///
+    /// The offsets of the terms and the slop are ignored in this mode. A term repeated in the
+    /// phrase has to appear as many times in the block.
+    ///
+    /// Defaults to false.
+    pub fn set_adjacent_any_order(&mut self, adjacent_any_order: bool) {
+        self.adjacent_any_order = adjacent_any_order;
+    }
+
+    /// Returns true if the terms are required to be adjacent, in any order.
+    ///
+    /// See [`PhraseQuery::set_adjacent_any_order`].
+    pub fn adjacent_any_order(&self) -> bool {
+        self.adjacent_any_order
+    }
+
     /// The [`Field`] this `PhraseQuery` is targeting.
     pub fn field(&self) -> Field {
         self.field
@@ -128,6 +153,7 @@ impl PhraseQuery {
         if self.slop > 0 {
             weight.slop(self.slop);
         }
+        weight.adjacent_any_order(self.adjacent_any_order);
         Ok(weight)
     }
 }
@@ -141,6 +167,16 @@ impl Query for PhraseQuery {
         Ok(Box::new(phrase_weight))
     }
 
+    /// The intersection of the terms is driven by the rarest one, so the phrase costs as much as
+    /// its rarest term.
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        let mut cost = u64::MAX;
+        for (_, term) in &self.phrase_terms {
+            cost = cost.min(searcher.doc_freq(term)?);
+        }
+        Ok(Some(cost))
+    }
+
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         for (_, term) in &self.phrase_terms {
             visitor(term, true);
@@ -55,6 +55,85 @@ pub struct PhraseScorer<TPostings: Postings> {
     left_slops: Vec<u8>,
     positions_buffer: Vec<u32>,
     slops_buffer: Vec<u8>,
+    // If true, the terms have to fill a block of consecutive positions, in any order.
+    adjacent_any_order: bool,
+    term_positions: Vec<Vec<u32>>,
+    block_matcher: BlockMatcher,
+}
+
+/// Checks whether the terms fill a block of consecutive positions, in any order.
+///
+/// Each position of the block has to be taken by a different term of the phrase, which is
+/// a bipartite matching between the terms and the positions of the block. The terms of a
+/// phrase are few, so it runs a simple augmenting path search.
+#[derive(Default)]
+struct BlockMatcher {
+    // The term assigned to each position of the block, if any.
+    assigned_terms: Vec<Option<usize>>,
+    visited: Vec<bool>,
+}
+
+impl BlockMatcher {
+    fn block_matches(&mut self, term_positions: &[Vec<u32>], block_start: u32) -> bool {
+        let num_terms = term_positions.len();
+        self.assigned_terms.clear();
+        self.assigned_terms.resize(num_terms, None);
+        for term in 0..num_terms {
+            self.visited.clear();
+            self.visited.resize(num_terms, false);
+            if !self.assign(term_positions, block_start, term) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn assign(&mut self, term_positions: &[Vec<u32>], block_start: u32, term: usize) -> bool {
+        let block_end = block_start + term_positions.len() as u32;
+        let positions = &term_positions[term];
+        let first = positions.partition_point(|&position| position < block_start);
+        for &position in &positions[first..] {
+            if position >= block_end {
+                break;
+            }
+            let slot = (position - block_start) as usize;
+            if self.visited[slot] {
+                continue;
+            }
+            self.visited[slot] = true;
+            let slot_free = match self.assigned_terms[slot] {
+                None => true,
+                Some(other_term) => self.assign(term_positions, block_start, other_term),
+            };
+            if slot_free {
+                self.assigned_terms[slot] = Some(term);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the number of blocks filled by the terms, or 1 if there is at least one and
+    /// `stop_at_first` is true.
+    fn count_blocks(&mut self, term_positions: &[Vec<u32>], stop_at_first: bool) -> u32 {
+        let num_terms = term_positions.len() as u32;
+        let mut count = 0;
+        // The blocks starting before `next_block_start` have already been checked.
+        let mut next_block_start = 0;
+        for &position in &term_positions[0] {
+            let first_block_start = position.saturating_sub(num_terms - 1).max(next_block_start);
+            for block_start in first_block_start..=position {
+                if self.block_matches(term_positions, block_start) {
+                    count += 1;
+                    if stop_at_first {
+                        return count;
+                    }
+                }
+            }
+            next_block_start = position + 1;
+        }
+        count
+    }
 }
 
 /// Returns true if and only if the two sorted arrays contain a common element
@@ -367,6 +446,41 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         fieldnorm_reader: FieldNormReader,
         slop: u32,
         offset: usize,
+    ) -> PhraseScorer<TPostings> {
+        Self::create(
+            term_postings_with_offset,
+            similarity_weight_opt,
+            fieldnorm_reader,
+            slop,
+            offset,
+            false,
+        )
+    }
+
+    /// Creates a scorer matching the documents where the terms fill a block of consecutive
+    /// positions, in any order. The offsets of the terms are ignored.
+    pub(crate) fn new_adjacent_any_order(
+        term_postings: Vec<(usize, TPostings)>,
+        similarity_weight_opt: Option<Bm25Weight>,
+        fieldnorm_reader: FieldNormReader,
+    ) -> PhraseScorer<TPostings> {
+        Self::create(
+            term_postings,
+            similarity_weight_opt,
+            fieldnorm_reader,
+            0,
+            0,
+            true,
+        )
+    }
+
+    fn create(
+        term_postings_with_offset: Vec<(usize, TPostings)>,
+        similarity_weight_opt: Option<Bm25Weight>,
+        fieldnorm_reader: FieldNormReader,
+        slop: u32,
+        offset: usize,
+        adjacent_any_order: bool,
     ) -> PhraseScorer<TPostings> {
         let max_offset = term_postings_with_offset
             .iter()
@@ -393,6 +507,9 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
             left_slops: Vec::with_capacity(100),
             slops_buffer: Vec::with_capacity(100),
             positions_buffer: Vec::with_capacity(100),
+            adjacent_any_order,
+            term_positions: vec![Vec::new(); if adjacent_any_order { num_docsets } else { 0 }],
+            block_matcher: BlockMatcher::default(),
         };
         if scorer.doc() != TERMINATED && !scorer.phrase_match() {
             scorer.advance();
@@ -410,6 +527,11 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
     }
 
     fn phrase_match(&mut self) -> bool {
+        if self.adjacent_any_order {
+            let count = self.compute_adjacent_any_order_count();
+            self.phrase_count = count;
+            return count > 0u32;
+        }
         if self.similarity_weight_opt.is_some() {
             let count = self.compute_phrase_count();
             self.phrase_count = count;
@@ -458,6 +580,18 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         }
     }
 
+    fn compute_adjacent_any_order_count(&mut self) -> u32 {
+        for (term, positions) in self.term_positions.iter_mut().enumerate() {
+            self.intersection_docset
+                .docset_mut_specialized(term)
+                .postings
+                .positions(positions);
+        }
+        let stop_at_first = self.similarity_weight_opt.is_none();
+        self.block_matcher
+            .count_blocks(&self.term_positions, stop_at_first)
+    }
+
     fn compute_phrase_match(&mut self) {
         {
             self.intersection_docset
@@ -540,9 +674,8 @@ impl<TPostings: Postings> DocSet for PhraseScorer<TPostings> {
 impl<TPostings: Postings> Scorer for PhraseScorer<TPostings> {
     fn score(&mut self) -> Score {
         let doc = self.doc();
-        let fieldnorm_id = self.fieldnorm_reader.fieldnorm_id(doc);
         if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
-            similarity_weight.score(fieldnorm_id, self.phrase_count)
+            similarity_weight.score_doc(&self.fieldnorm_reader, doc, self.phrase_count)
         } else {
             1.0f32
         }
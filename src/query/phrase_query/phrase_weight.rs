@@ -12,6 +12,7 @@ pub struct PhraseWeight {
     phrase_terms: Vec<(usize, Term)>,
     similarity_weight_opt: Option<Bm25Weight>,
     slop: u32,
+    adjacent_any_order: bool,
 }
 
 impl PhraseWeight {
@@ -26,6 +27,7 @@ impl PhraseWeight {
             phrase_terms,
             similarity_weight_opt,
             slop,
+            adjacent_any_order: false,
         }
     }
 
@@ -60,6 +62,13 @@ impl PhraseWeight {
                 return Ok(None);
             }
         }
+        if self.adjacent_any_order {
+            return Ok(Some(PhraseScorer::new_adjacent_any_order(
+                term_postings_list,
+                similarity_weight_opt,
+                fieldnorm_reader,
+            )));
+        }
         Ok(Some(PhraseScorer::new(
             term_postings_list,
             similarity_weight_opt,
@@ -71,6 +80,10 @@ impl PhraseWeight {
     pub fn slop(&mut self, slop: u32) {
         self.slop = slop;
     }
+
+    pub fn adjacent_any_order(&mut self, adjacent_any_order: bool) {
+        self.adjacent_any_order = adjacent_any_order;
+    }
 }
 
 impl Weight for PhraseWeight {
@@ -92,11 +105,14 @@ impl Weight for PhraseWeight {
             return Err(does_not_match(doc));
         }
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
-        let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
         let phrase_count = scorer.phrase_count();
         let mut explanation = Explanation::new("Phrase Scorer", scorer.score());
         if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
-            explanation.add_detail(similarity_weight.explain(fieldnorm_id, phrase_count));
+            explanation.add_detail(similarity_weight.explain_doc(
+                &fieldnorm_reader,
+                doc,
+                phrase_count,
+            ));
         }
         Ok(explanation)
     }
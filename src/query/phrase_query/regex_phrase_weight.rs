@@ -288,11 +288,14 @@ impl Weight for RegexPhraseWeight {
             return Err(does_not_match(doc));
         }
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
-        let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
         let phrase_count = scorer.phrase_count();
         let mut explanation = Explanation::new("Phrase Scorer", scorer.score());
         if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
-            explanation.add_detail(similarity_weight.explain(fieldnorm_id, phrase_count));
+            explanation.add_detail(similarity_weight.explain_doc(
+                &fieldnorm_reader,
+                doc,
+                phrase_count,
+            ));
         }
         Ok(explanation)
     }
@@ -0,0 +1,415 @@
+use std::fmt;
+use std::sync::Arc;
+
+use columnar::Column;
+
+use crate::docset::{DocSet, TERMINATED};
+use crate::index::SegmentReader;
+use crate::query::explanation::does_not_match;
+use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::{FieldType, Schema};
+use crate::{DateTime, DocId, Score, TantivyError};
+
+/// The fast field value of a document, as passed to the predicate of a [`PredicateQuery`].
+///
+/// Only the first value of multivalued fields is passed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnValue {
+    /// The document has no value for this field.
+    Null,
+    /// A `u64` value.
+    U64(u64),
+    /// A `i64` value.
+    I64(i64),
+    /// A `f64` value.
+    F64(f64),
+    /// A `bool` value.
+    Bool(bool),
+    /// A `DateTime` value.
+    Date(DateTime),
+}
+
+type Predicate = dyn Fn(&[ColumnValue]) -> bool + Send + Sync;
+
+/// Query filtering the documents matched by a base query using an arbitrary
+/// predicate over their fast field values.
+///
+/// For each candidate document of the base query, the first value of each of the
+/// `fields` is read from the fast fields, and passed to the predicate, in the order of
+/// `fields`. The document is kept if and only if the predicate returns `true`.
+/// The score is the score of the base query.
+///
+/// This is an escape hatch for filters that cannot be expressed with a term or range
+/// query.
+///
+/// # Performance
+///
+/// The predicate is evaluated on every candidate document: the fast field values are
+/// decoded one document at a time, and nothing can be skipped. The base query should
+/// therefore express as much of the filter as possible, and `PredicateQuery` only what
+/// remains. Wrapping an [`AllQuery`](crate::query::AllQuery) results in a full scan.
+///
+/// Only `u64`, `i64`, `f64`, `bool` and date fast fields are supported.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::{AllQuery, ColumnValue, PredicateQuery};
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let width = schema_builder.add_u64_field("width", FAST);
+/// let height = schema_builder.add_u64_field("height", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(width => 40u64, height => 30u64))?;
+/// index_writer.add_document(doc!(width => 30u64, height => 40u64))?;
+/// index_writer.add_document(doc!(width => 10u64, height => 10u64))?;
+/// index_writer.commit()?;
+///
+/// // Landscape pictures.
+/// let query = PredicateQuery::new(
+///     Box::new(AllQuery),
+///     vec!["width".to_string(), "height".to_string()],
+///     |values: &[ColumnValue]| match values {
+///         [ColumnValue::U64(width), ColumnValue::U64(height)] => width > height,
+///         _ => false,
+///     },
+/// );
+/// let searcher = index.reader()?.searcher();
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct PredicateQuery {
+    base_query: Box<dyn Query>,
+    field_names: Vec<String>,
+    predicate: Arc<Predicate>,
+}
+
+impl Clone for PredicateQuery {
+    fn clone(&self) -> Self {
+        PredicateQuery {
+            base_query: self.base_query.box_clone(),
+            field_names: self.field_names.clone(),
+            predicate: self.predicate.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for PredicateQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PredicateQuery")
+            .field("base_query", &self.base_query)
+            .field("field_names", &self.field_names)
+            .finish()
+    }
+}
+
+impl PredicateQuery {
+    /// Creates a new `PredicateQuery`.
+    ///
+    /// This constructor never fails, but executing the search with this query will
+    /// return an error if one of the fields does not exist or is not a supported fast field.
+    pub fn new<F>(base_query: Box<dyn Query>, field_names: Vec<String>, predicate: F) -> Self
+    where F: Fn(&[ColumnValue]) -> bool + Send + Sync + 'static {
+        PredicateQuery {
+            base_query,
+            field_names,
+            predicate: Arc::new(predicate),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PredicateFieldType {
+    U64,
+    I64,
+    F64,
+    Bool,
+    Date,
+}
+
+fn predicate_field_type(schema: &Schema, field_name: &str) -> crate::Result<PredicateFieldType> {
+    let field = schema.get_field(field_name)?;
+    let field_type = schema.get_field_entry(field).field_type();
+    if !field_type.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {field_name} is not a fast field."
+        )));
+    }
+    match field_type {
+        FieldType::U64(_) => Ok(PredicateFieldType::U64),
+        FieldType::I64(_) => Ok(PredicateFieldType::I64),
+        FieldType::F64(_) => Ok(PredicateFieldType::F64),
+        FieldType::Bool(_) => Ok(PredicateFieldType::Bool),
+        FieldType::Date(_) => Ok(PredicateFieldType::Date),
+        _ => Err(TantivyError::SchemaError(format!(
+            "Field {field_name} of type {:?} is not supported by PredicateQuery.",
+            field_type.value_type()
+        ))),
+    }
+}
+
+impl Query for PredicateQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let fields = self
+            .field_names
+            .iter()
+            .map(|field_name| {
+                let field_type = predicate_field_type(schema, field_name)?;
+                Ok((field_name.clone(), field_type))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        let base_weight = self.base_query.weight(enable_scoring)?;
+        Ok(Box::new(PredicateWeight {
+            base_weight,
+            fields,
+            predicate: self.predicate.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a crate::Term, bool)) {
+        self.base_query.query_terms(visitor);
+    }
+}
+
+/// Weight associated with the `PredicateQuery` query.
+pub struct PredicateWeight {
+    base_weight: Box<dyn Weight>,
+    fields: Vec<(String, PredicateFieldType)>,
+    predicate: Arc<Predicate>,
+}
+
+enum PredicateColumn {
+    U64(Column<u64>),
+    I64(Column<i64>),
+    F64(Column<f64>),
+    Bool(Column<bool>),
+    Date(Column<DateTime>),
+    Missing,
+}
+
+impl PredicateColumn {
+    fn open(
+        reader: &SegmentReader,
+        field_name: &str,
+        field_type: PredicateFieldType,
+    ) -> crate::Result<PredicateColumn> {
+        let fast_fields = reader.fast_fields();
+        let column_opt = match field_type {
+            PredicateFieldType::U64 => fast_fields.column_opt(field_name)?.map(Self::U64),
+            PredicateFieldType::I64 => fast_fields.column_opt(field_name)?.map(Self::I64),
+            PredicateFieldType::F64 => fast_fields.column_opt(field_name)?.map(Self::F64),
+            PredicateFieldType::Bool => fast_fields.column_opt(field_name)?.map(Self::Bool),
+            PredicateFieldType::Date => fast_fields.column_opt(field_name)?.map(Self::Date),
+        };
+        Ok(column_opt.unwrap_or(PredicateColumn::Missing))
+    }
+
+    fn value(&self, doc: DocId) -> ColumnValue {
+        let value_opt = match self {
+            PredicateColumn::U64(column) => column.first(doc).map(ColumnValue::U64),
+            PredicateColumn::I64(column) => column.first(doc).map(ColumnValue::I64),
+            PredicateColumn::F64(column) => column.first(doc).map(ColumnValue::F64),
+            PredicateColumn::Bool(column) => column.first(doc).map(ColumnValue::Bool),
+            PredicateColumn::Date(column) => column.first(doc).map(ColumnValue::Date),
+            PredicateColumn::Missing => None,
+        };
+        value_opt.unwrap_or(ColumnValue::Null)
+    }
+}
+
+impl PredicateWeight {
+    fn predicate_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> crate::Result<PredicateScorer> {
+        let columns = self
+            .fields
+            .iter()
+            .map(|(field_name, field_type)| PredicateColumn::open(reader, field_name, *field_type))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let base_scorer = self.base_weight.scorer(reader, boost)?;
+        Ok(PredicateScorer::new(
+            base_scorer,
+            columns,
+            self.predicate.clone(),
+        ))
+    }
+}
+
+impl Weight for PredicateWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        Ok(Box::new(self.predicate_scorer(reader, boost)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.predicate_scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        self.base_weight.explain(reader, doc)
+    }
+}
+
+struct PredicateScorer {
+    base_scorer: Box<dyn Scorer>,
+    columns: Vec<PredicateColumn>,
+    predicate: Arc<Predicate>,
+    values: Vec<ColumnValue>,
+}
+
+impl PredicateScorer {
+    fn new(
+        base_scorer: Box<dyn Scorer>,
+        columns: Vec<PredicateColumn>,
+        predicate: Arc<Predicate>,
+    ) -> PredicateScorer {
+        let mut scorer = PredicateScorer {
+            base_scorer,
+            columns,
+            predicate,
+            values: Vec::new(),
+        };
+        if scorer.doc() != TERMINATED && !scorer.matches(scorer.doc()) {
+            scorer.advance();
+        }
+        scorer
+    }
+
+    fn matches(&mut self, doc: DocId) -> bool {
+        self.values.clear();
+        self.values
+            .extend(self.columns.iter().map(|column| column.value(doc)));
+        (self.predicate)(&self.values)
+    }
+}
+
+impl DocSet for PredicateScorer {
+    fn advance(&mut self) -> DocId {
+        loop {
+            let candidate = self.base_scorer.advance();
+            if candidate == TERMINATED || self.matches(candidate) {
+                return candidate;
+            }
+        }
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        let candidate = self.base_scorer.seek(target);
+        if candidate == TERMINATED || self.matches(candidate) {
+            return candidate;
+        }
+        self.advance()
+    }
+
+    fn doc(&self) -> DocId {
+        self.base_scorer.doc()
+    }
+
+    /// The size hint of the base query, ignoring the predicate.
+    fn size_hint(&self) -> u32 {
+        self.base_scorer.size_hint()
+    }
+}
+
+impl Scorer for PredicateScorer {
+    fn score(&mut self) -> Score {
+        self.base_scorer.score()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColumnValue, PredicateQuery};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, EnableScoring, Query, QueryParser};
+    use crate::schema::{Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, TantivyError};
+
+    #[test]
+    fn test_predicate_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let quantity = schema_builder.add_i64_field("quantity", FAST | INDEXED);
+        let on_sale = schema_builder.add_bool_field("on_sale", FAST);
+        schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(
+            doc!(title => "red shirt", price => 20.0, quantity => 3i64, on_sale => true),
+        )?;
+        index_writer.add_document(
+            doc!(title => "blue shirt", price => 20.0, quantity => 10i64, on_sale => false),
+        )?;
+        index_writer.add_document(doc!(title => "red hat", price => 5.0, quantity => 1i64))?;
+        index_writer.add_document(
+            doc!(title => "red shoes", price => 70.0, quantity => 2i64, on_sale => true),
+        )?;
+        index_writer
+            .add_document(doc!(title => "red scarf", quantity => 100i64, on_sale => true))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        // Red items whose stock is worth less than 100, or on sale with a missing price.
+        let red = QueryParser::for_index(&index, vec![title]).parse_query("red")?;
+        let query = PredicateQuery::new(
+            red,
+            vec![
+                "price".to_string(),
+                "quantity".to_string(),
+                "on_sale".to_string(),
+            ],
+            |values: &[ColumnValue]| match values {
+                [ColumnValue::F64(price), ColumnValue::I64(quantity), _] => {
+                    price * (*quantity as f64) < 100.0
+                }
+                [ColumnValue::Null, _, ColumnValue::Bool(on_sale)] => *on_sale,
+                _ => false,
+            },
+        );
+        let mut doc_ids: Vec<u32> = searcher
+            .search(&query, &TopDocs::with_limit(10))?
+            .into_iter()
+            .map(|(_, doc_address)| doc_address.doc_id)
+            .collect();
+        doc_ids.sort();
+        assert_eq!(doc_ids, vec![0, 2, 4]);
+
+        let explanation = query.explain(&searcher, DocAddress::new(0, 0))?;
+        assert!(explanation.value() > 0.0);
+        assert!(query.explain(&searcher, DocAddress::new(0, 3)).is_err());
+
+        let all_on_sale = PredicateQuery::new(
+            Box::new(AllQuery),
+            vec!["on_sale".to_string()],
+            |values: &[ColumnValue]| values == [ColumnValue::Bool(true)],
+        );
+        assert_eq!(searcher.search(&all_on_sale, &Count)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_predicate_query_unsupported_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("not_fast", INDEXED);
+        schema_builder.add_text_field("text", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        for field_name in ["not_fast", "text", "missing"] {
+            let query =
+                PredicateQuery::new(Box::new(AllQuery), vec![field_name.to_string()], |_| true);
+            let weight_res = query.weight(EnableScoring::disabled_from_searcher(&searcher));
+            assert!(matches!(
+                weight_res,
+                Err(TantivyError::SchemaError(_)) | Err(TantivyError::FieldNotFound(_))
+            ));
+        }
+        Ok(())
+    }
+}
@@ -160,6 +160,15 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
     /// Note that there can be multiple instances of any given term
     /// in a query and deduplication must be handled by the visitor.
     fn query_terms<'a>(&'a self, _visitor: &mut dyn FnMut(&'a Term, bool)) {}
+
+    /// Returns an a priori estimate of the number of postings entries read by the query, or
+    /// `None` if the query cannot estimate it.
+    ///
+    /// See [`Searcher::query_cost`], which falls back to a conservative cost for the queries
+    /// without an estimate.
+    fn cost(&self, _searcher: &Searcher) -> crate::Result<Option<u64>> {
+        Ok(None)
+    }
 }
 
 /// Implements `box_clone`.
@@ -188,6 +197,10 @@ impl Query for Box<dyn Query> {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.as_ref().query_terms(visitor);
     }
+
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        self.as_ref().cost(searcher)
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
@@ -23,6 +23,7 @@ pub enum LogicalLiteral {
     All,
 }
 
+#[derive(Clone)]
 pub enum LogicalAst {
     Clause(Vec<(Occur, LogicalAst)>),
     Leaf(Box<LogicalLiteral>),
@@ -1,7 +1,9 @@
+use std::borrow::Cow;
 use std::net::{AddrParseError, IpAddr};
 use std::num::{ParseFloatError, ParseIntError};
 use std::ops::Bound;
 use std::str::{FromStr, ParseBoolError};
+use std::sync::Arc;
 
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
@@ -24,7 +26,7 @@ use crate::schema::{
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
-use crate::{DateTime, Score};
+use crate::{DateTime, Score, Searcher};
 
 /// Possible error that may happen when parsing a query.
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -102,6 +104,9 @@ pub enum QueryParserError {
     /// The format for the ip field is invalid.
     #[error("The ip field is malformed: {0}")]
     IpFormatError(#[from] AddrParseError),
+    /// No value was given for a parameter of a [`QueryTemplate`](crate::query::QueryTemplate).
+    #[error("Missing value for the template parameter '{0}'")]
+    MissingTemplateParameter(String),
 }
 
 /// Recursively remove empty clause from the AST
@@ -206,6 +211,71 @@ pub struct QueryParser {
     tokenizer_manager: TokenizerManager,
     boost: FxHashMap<Field, Score>,
     fuzzy: FxHashMap<Field, Fuzzy>,
+    frequent_terms_filter: Option<FrequentTermsFilter>,
+    query_normalizer: Option<QueryNormalizer>,
+}
+
+type QueryNormalizer = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+#[derive(Clone)]
+struct FrequentTermsFilter {
+    searcher: Searcher,
+    max_doc_freq_ratio: f32,
+}
+
+impl FrequentTermsFilter {
+    fn is_frequent(&self, term: &Term, max_doc: u64) -> bool {
+        // On error, we keep the term: the query will report the error when executed.
+        let doc_freq = self.searcher.doc_freq(term).unwrap_or(0);
+        doc_freq as f64 > self.max_doc_freq_ratio as f64 * max_doc as f64
+    }
+
+    fn drop_frequent_terms(&self, ast: LogicalAst, occur: Occur, max_doc: u64) -> LogicalAst {
+        match ast {
+            LogicalAst::Leaf(literal) => match *literal {
+                LogicalLiteral::Term(ref term)
+                    if occur != Occur::MustNot && self.is_frequent(term, max_doc) =>
+                {
+                    LogicalAst::Clause(Vec::new())
+                }
+                literal => LogicalAst::Leaf(Box::new(literal)),
+            },
+            LogicalAst::Boost(child_ast, boost) => {
+                match trim_ast(self.drop_frequent_terms(*child_ast, occur, max_doc)) {
+                    Some(child_ast) => LogicalAst::Boost(Box::new(child_ast), boost),
+                    None => LogicalAst::Clause(Vec::new()),
+                }
+            }
+            LogicalAst::Clause(children) => LogicalAst::Clause(
+                children
+                    .into_iter()
+                    .map(|(child_occur, child)| {
+                        let occur = if occur == Occur::MustNot {
+                            Occur::MustNot
+                        } else {
+                            child_occur
+                        };
+                        (child_occur, self.drop_frequent_terms(child, occur, max_doc))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Removes the frequent terms from the ast, unless this leaves nothing to match.
+    fn filter(&self, ast: LogicalAst) -> LogicalAst {
+        let max_doc: u64 = self
+            .searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.max_doc() as u64)
+            .sum();
+        let filtered_ast = self.drop_frequent_terms(ast.clone(), Occur::Should, max_doc);
+        match trim_ast(filtered_ast) {
+            Some(filtered_ast) if !all_negative(&filtered_ast) => filtered_ast,
+            _ => ast,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -260,6 +330,8 @@ impl QueryParser {
             conjunction_by_default: false,
             boost: Default::default(),
             fuzzy: Default::default(),
+            frequent_terms_filter: None,
+            query_normalizer: None,
         }
     }
 
@@ -320,6 +392,51 @@ impl QueryParser {
         );
     }
 
+    /// Drops the terms appearing in too many documents from the parsed queries.
+    ///
+    /// A term is dropped if its document frequency in `searcher` exceeds
+    /// `max_doc_freq_ratio * max_doc`. This acts as a stop word removal based on the
+    /// actual statistics of the corpus, and does not require reindexing.
+    ///
+    /// Only term literals are dropped. Phrases, ranges and sets are left untouched,
+    /// as well as excluded (`-term`) terms.
+    ///
+    /// If dropping the frequent terms leaves nothing to match (for instance, the
+    /// query only contains frequent terms), the query is kept unchanged.
+    pub fn set_max_doc_freq_ratio(&mut self, searcher: Searcher, max_doc_freq_ratio: f32) {
+        self.frequent_terms_filter = Some(FrequentTermsFilter {
+            searcher,
+            max_doc_freq_ratio,
+        });
+    }
+
+    /// Sets a normalization applied to the text of the query before it is tokenized.
+    ///
+    /// The normalizer is applied to the values searched in text and JSON fields, whether the
+    /// field is one of the default fields or is explicitly given in the query, as well as
+    /// to the bounds of the ranges on text fields. It is not applied to the field names or to
+    /// the query syntax.
+    ///
+    /// This makes it possible to normalize the user input at query time only, for instance to
+    /// strip the accents of the queries run against an index whose documents were stripped of
+    /// their accents before being indexed.
+    ///
+    /// The normalizer runs before the tokenizer of the field, and is not applied at indexing
+    /// time. It must therefore not produce text that the tokenizer of the field transforms
+    /// into terms that do not appear in the index: for instance, stripping the accents of the
+    /// queries loses the matches on an index where the accents were kept.
+    pub fn set_query_normalizer<F>(&mut self, query_normalizer: F)
+    where F: Fn(&str) -> String + Send + Sync + 'static {
+        self.query_normalizer = Some(Arc::new(query_normalizer));
+    }
+
+    fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match &self.query_normalizer {
+            Some(query_normalizer) => Cow::Owned(query_normalizer(text)),
+            None => Cow::Borrowed(text),
+        }
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
@@ -414,6 +531,9 @@ impl QueryParser {
             err.push(QueryParserError::AllButQueryForbidden);
             make_non_negative(&mut ast);
         }
+        if let Some(frequent_terms_filter) = &self.frequent_terms_filter {
+            ast = frequent_terms_filter.filter(ast);
+        }
         (ast, err)
     }
 
@@ -473,7 +593,8 @@ impl QueryParser {
                             tokenizer: option.tokenizer().to_string(),
                         })?;
                 let mut terms: Vec<Term> = Vec::new();
-                let mut token_stream = text_analyzer.token_stream(phrase);
+                let phrase = self.normalize(phrase);
+                let mut token_stream = text_analyzer.token_stream(&phrase);
                 token_stream.process(&mut |token| {
                     let term = Term::from_field_text(field, &token.text);
                     terms.push(term);
@@ -586,7 +707,7 @@ impl QueryParser {
                 Ok(generate_literals_for_str(
                     field_name,
                     field,
-                    phrase,
+                    &self.normalize(phrase),
                     slop,
                     prefix,
                     indexing_options,
@@ -599,7 +720,7 @@ impl QueryParser {
                 field_name,
                 field,
                 json_path,
-                phrase,
+                &self.normalize(phrase),
                 &self.tokenizer_manager,
                 json_options,
             ),
@@ -1140,7 +1261,8 @@ mod test {
         assert_eq!(
             format!("{query:?}"),
             "RangeQuery { bounds: BoundsRange { lower_bound: Included(Term(field=0, type=Str, \
-             \"a\")), upper_bound: Included(Term(field=0, type=Str, \"b\")) } }"
+             \"a\")), upper_bound: Included(Term(field=0, type=Str, \"b\")) }, outside: false, \
+             missing: Skip }"
         );
     }
 
@@ -1922,4 +2044,146 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_query_parser_field_alias() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::directory::RamDirectory;
+        use crate::IndexWriter;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_alias("headline", title)?;
+        let directory = RamDirectory::create();
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "hello world", body => "foo"))?;
+        index_writer.add_document(doc!(title => "goodbye", body => "hello"))?;
+        index_writer.commit()?;
+
+        // Aliases are persisted with the schema.
+        let index = Index::open(directory)?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![body]);
+        let count = |query: &str| -> crate::Result<usize> {
+            let query = query_parser.parse_query(query)?;
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count("title:hello")?, 1);
+        assert_eq!(count("headline:hello")?, 1);
+        assert_eq!(count("headline:goodbye")?, 1);
+        assert_eq!(count("hello")?, 1);
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query("headline:hello")?),
+            format!("{:?}", query_parser.parse_query("title:hello")?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_parser_max_doc_freq_ratio() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::IndexWriter;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "the old man and the sea"))?;
+        index_writer.add_document(doc!(title => "the sea wolf"))?;
+        index_writer.add_document(doc!(title => "the wolf of wall street"))?;
+        index_writer.add_document(doc!(title => "the hobbit"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let mut query_parser = QueryParser::for_index(&index, vec![title]);
+        query_parser.set_conjunction_by_default();
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("the sea")?, &Count)?,
+            2
+        );
+        query_parser.set_max_doc_freq_ratio(searcher.clone(), 0.6);
+        // `the` appears in every document and is dropped. `sea` and `wolf` are kept.
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query("the sea wolf")?),
+            format!("{:?}", query_parser.parse_query("sea wolf")?)
+        );
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("the sea")?, &Count)?,
+            2
+        );
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("sea wolf")?, &Count)?,
+            1
+        );
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("the^2.0 hobbit")?, &Count)?,
+            1
+        );
+        // Excluded terms are never dropped.
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("sea -the")?, &Count)?,
+            0
+        );
+        // If all terms are frequent, the query is kept unchanged.
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("the")?, &Count)?,
+            4
+        );
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("the^2.0")?, &Count)?,
+            4
+        );
+        // Phrases are left untouched.
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("\"the hobbit\"")?, &Count)?,
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_parser_query_normalizer() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::IndexWriter;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // The documents were stripped of their accents before being indexed.
+        index_writer.add_document(doc!(title => "creme brulee", body => "cafe"))?;
+        index_writer.add_document(doc!(title => "eclair", body => "tea"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let mut query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let count = |query_parser: &QueryParser, query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count(&query_parser, "crème")?, 0);
+        query_parser.set_query_normalizer(|text| {
+            text.chars()
+                .map(|c| match c {
+                    'à' | 'â' => 'a',
+                    'é' | 'è' | 'ê' => 'e',
+                    'û' | 'ü' => 'u',
+                    'É' => 'E',
+                    c => c,
+                })
+                .collect()
+        });
+        assert_eq!(count(&query_parser, "crème")?, 1);
+        assert_eq!(count(&query_parser, "\"Crème Brûlée\"")?, 1);
+        assert_eq!(count(&query_parser, "body:café")?, 1);
+        assert_eq!(count(&query_parser, "Éclair OR café")?, 2);
+        assert_eq!(count(&query_parser, "title:[éclair TO éclair]")?, 1);
+        Ok(())
+    }
 }
@@ -0,0 +1,297 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use query_grammar::{UserInputAst, UserInputBound, UserInputLeaf};
+
+use super::{QueryParser, QueryParserError};
+use crate::query::Query;
+
+/// A query string with named placeholders, parsed once and bound to
+/// different parameter values.
+///
+/// Placeholders are written `{name}`, where `name` is made of ASCII alphanumeric
+/// characters and underscores, and does not start with a digit. Any other use of `{`, such as
+/// an exclusive range bound `{10 TO 20}`, is left untouched.
+///
+/// A placeholder can be used anywhere a value is expected: as a term, inside a phrase, as a range
+/// bound or as an element of a set. It cannot be used as a field name or as part of the query
+/// syntax.
+///
+/// Parameter values are substituted in the parsed query, and are never parsed by the query
+/// grammar: they do not need to be escaped, and cannot alter the structure of the query.
+/// They are then converted to the type of their field exactly as the values written in a
+/// query string, e.g. an integer field expects a value like `42`, and a date field expects a
+/// RFC 3339 date.
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use tantivy::query::{QueryParser, QueryTemplate};
+/// use tantivy::schema::{Schema, FAST, INDEXED, STRING};
+/// use tantivy::Index;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut schema_builder = Schema::builder();
+/// let status = schema_builder.add_text_field("status", STRING);
+/// let price = schema_builder.add_u64_field("price", INDEXED | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let query_parser = QueryParser::for_index(&index, vec![status, price]);
+/// let template =
+///     QueryTemplate::new(query_parser, "status:{status} AND price:[{min} TO {max}]")?;
+/// let params = HashMap::from([("status", "active"), ("min", "10"), ("max", "20")]);
+/// let _query = template.bind(&params)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct QueryTemplate {
+    query_parser: QueryParser,
+    user_input_ast: UserInputAst,
+    parameters: Vec<String>,
+}
+
+const PLACEHOLDER_PREFIX: &str = "__tantivy_template_param_";
+const PLACEHOLDER_SUFFIX: &str = "__";
+
+fn placeholder_token(ord: usize) -> String {
+    format!("{PLACEHOLDER_PREFIX}{ord}{PLACEHOLDER_SUFFIX}")
+}
+
+/// Replaces the placeholders of `value` with their value in `param_values`.
+///
+/// The value is scanned once: the parameter values are copied as is, even if they contain
+/// a placeholder.
+fn substitute_placeholders(value: &str, param_values: &[&str]) -> String {
+    let mut substituted = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+        substituted.push_str(&rest[..start]);
+        rest = &rest[start + PLACEHOLDER_PREFIX.len()..];
+        let num_digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let param_value = rest[num_digits..]
+            .starts_with(PLACEHOLDER_SUFFIX)
+            .then(|| rest[..num_digits].parse::<usize>().ok())
+            .flatten()
+            .and_then(|ord| param_values.get(ord));
+        if let Some(param_value) = param_value {
+            substituted.push_str(param_value);
+            rest = &rest[num_digits + PLACEHOLDER_SUFFIX.len()..];
+        } else {
+            substituted.push_str(PLACEHOLDER_PREFIX);
+        }
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+fn is_parameter_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Calls `visit` on every value of the user input AST.
+///
+/// Field names are not visited.
+fn visit_values(user_input_ast: &mut UserInputAst, visit: &mut impl FnMut(&mut String)) {
+    match user_input_ast {
+        UserInputAst::Clause(children) => {
+            for (_, child) in children {
+                visit_values(child, visit);
+            }
+        }
+        UserInputAst::Boost(child, _) => visit_values(child, visit),
+        UserInputAst::Leaf(leaf) => match leaf.as_mut() {
+            UserInputLeaf::Literal(literal) => visit(&mut literal.phrase),
+            UserInputLeaf::Range { lower, upper, .. } => {
+                for bound in [lower, upper] {
+                    match bound {
+                        UserInputBound::Inclusive(value) | UserInputBound::Exclusive(value) => {
+                            visit(value)
+                        }
+                        UserInputBound::Unbounded => {}
+                    }
+                }
+            }
+            UserInputLeaf::Set { elements, .. } => elements.iter_mut().for_each(visit),
+            UserInputLeaf::All | UserInputLeaf::Exists { .. } => {}
+        },
+    }
+}
+
+impl QueryTemplate {
+    /// Parses `template`, returning an error if it is not a valid query once its
+    /// placeholders are put aside, or if a placeholder is not used as a value.
+    ///
+    /// The `query_parser` is used to build the queries when binding the template.
+    pub fn new(
+        query_parser: QueryParser,
+        template: &str,
+    ) -> Result<QueryTemplate, QueryParserError> {
+        let mut parameters: Vec<String> = Vec::new();
+        let mut query = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            query.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let name = rest[1..]
+                .find('}')
+                .map(|end| &rest[1..end + 1])
+                .filter(|name| is_parameter_name(name));
+            if let Some(name) = name {
+                let ord = parameters
+                    .iter()
+                    .position(|parameter| parameter == name)
+                    .unwrap_or_else(|| {
+                        parameters.push(name.to_string());
+                        parameters.len() - 1
+                    });
+                query.push_str(&placeholder_token(ord));
+                rest = &rest[name.len() + 2..];
+            } else {
+                query.push('{');
+                rest = &rest[1..];
+            }
+        }
+        query.push_str(rest);
+
+        let mut user_input_ast = query_grammar::parse_query(&query)
+            .map_err(|_| QueryParserError::SyntaxError(template.to_string()))?;
+        let mut used = vec![false; parameters.len()];
+        visit_values(&mut user_input_ast, &mut |value| {
+            for (ord, is_used) in used.iter_mut().enumerate() {
+                *is_used |= value.contains(&placeholder_token(ord));
+            }
+        });
+        if let Some(ord) = used.iter().position(|is_used| !is_used) {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "The template parameter '{}' can only be used as a value",
+                parameters[ord]
+            )));
+        }
+        Ok(QueryTemplate {
+            query_parser,
+            user_input_ast,
+            parameters,
+        })
+    }
+
+    /// Returns the names of the parameters of the template, in order of first appearance.
+    pub fn parameters(&self) -> &[String] {
+        &self.parameters
+    }
+
+    /// Builds the query obtained by replacing each placeholder with its value in `params`.
+    ///
+    /// Returns [`QueryParserError::MissingTemplateParameter`] if a parameter has no value.
+    /// Values of unknown parameters are ignored.
+    pub fn bind<K, V>(&self, params: &HashMap<K, V>) -> Result<Box<dyn Query>, QueryParserError>
+    where
+        K: Borrow<str> + Eq + Hash,
+        V: AsRef<str>,
+    {
+        let values: Vec<&str> = self
+            .parameters
+            .iter()
+            .map(|name| {
+                params
+                    .get(name.as_str())
+                    .map(AsRef::as_ref)
+                    .ok_or_else(|| QueryParserError::MissingTemplateParameter(name.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        let mut user_input_ast = self.user_input_ast.clone();
+        visit_values(&mut user_input_ast, &mut |value| {
+            if value.contains(PLACEHOLDER_PREFIX) {
+                *value = substitute_placeholders(value, &values);
+            }
+        });
+        self.query_parser
+            .build_query_from_user_input_ast(user_input_ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::QueryTemplate;
+    use crate::query::{QueryParser, QueryParserError};
+    use crate::schema::{Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::Index;
+
+    fn query_parser() -> QueryParser {
+        let mut schema_builder = Schema::builder();
+        let status = schema_builder.add_text_field("status", STRING);
+        let title = schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("price", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        QueryParser::for_index(&index, vec![status, title])
+    }
+
+    #[test]
+    fn test_query_template_bind() -> crate::Result<()> {
+        let query_parser = query_parser();
+        let template = QueryTemplate::new(
+            query_parser.clone(),
+            "status:{status} AND price:[{min} TO {max}] AND title:\"{a} {b}\" AND price:{10 TO \
+             {max}}",
+        )?;
+        assert_eq!(template.parameters(), ["status", "min", "max", "a", "b"]);
+        let params = HashMap::from([
+            ("status", "AND (*"),
+            ("min", "10"),
+            ("max", "20"),
+            ("a", "old"),
+            ("b", "man"),
+            ("unused", "value"),
+        ]);
+        let query = template.bind(&params)?;
+        let expected_query = query_parser.parse_query(
+            r#"status:"AND (*" AND price:[10 TO 20] AND title:"old man" AND price:{10 TO 20}"#,
+        )?;
+        assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_template_bind_values_with_placeholders() -> crate::Result<()> {
+        let query_parser = query_parser();
+        let template = QueryTemplate::new(query_parser.clone(), "status:\"{a} {b}\"")?;
+        // The value of `a` is the placeholder of `b`, and is not substituted in turn.
+        let params = HashMap::from([("a", "__tantivy_template_param_1__"), ("b", "active")]);
+        let query = template.bind(&params)?;
+        let expected_query =
+            query_parser.parse_query(r#"status:"__tantivy_template_param_1__ active""#)?;
+        assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_template_errors() -> crate::Result<()> {
+        let template = QueryTemplate::new(query_parser(), "price:{price}")?;
+        assert_eq!(
+            template.bind(&HashMap::<&str, &str>::new()).err(),
+            Some(QueryParserError::MissingTemplateParameter(
+                "price".to_string()
+            ))
+        );
+        assert!(matches!(
+            template.bind(&HashMap::from([("price", "cheap")])),
+            Err(QueryParserError::ExpectedInt(_))
+        ));
+        assert!(matches!(
+            QueryTemplate::new(query_parser(), "{field}:value"),
+            Err(QueryParserError::UnsupportedQuery(_))
+        ));
+        assert!(matches!(
+            QueryTemplate::new(query_parser(), "status:("),
+            Err(QueryParserError::SyntaxError(_))
+        ));
+        Ok(())
+    }
+}
@@ -8,10 +8,13 @@ use super::range_query_fastfield::FastFieldRangeWeight;
 use crate::index::SegmentReader;
 use crate::query::explanation::does_not_match;
 use crate::query::range_query::is_type_valid_for_fastfield_range_query;
-use crate::query::{BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::query::{
+    AllQuery, BitSetDocSet, BooleanQuery, ConstScoreQuery, ConstScorer, EmptyQuery, EnableScoring,
+    ExistsQuery, Explanation, Occur, Query, Scorer, Weight,
+};
 use crate::schema::{Field, IndexRecordOption, Term, Type};
 use crate::termdict::{TermDictionary, TermStreamer};
-use crate::{DocId, Score};
+use crate::{DocId, MissingValuePolicy, Score};
 
 /// `RangeQuery` matches all documents that have at least one term within a defined range.
 ///
@@ -30,6 +33,13 @@ use crate::{DocId, Score};
 /// variant we can walk in a lazy fashion over it, since the fastfield is implicit orderered by
 /// DocId.
 ///
+/// ## Numeric fields indexed for points and ranges
+/// If a numeric field opted in with
+/// [`NumericOptions::set_point_and_range_indexed`](crate::schema::NumericOptions::set_point_and_range_indexed),
+/// a range matching a single value is run on the inverted index, as it only requires
+/// reading one posting list. Other ranges, and all of the ranges on the other fields, are run on
+/// the fast field when there is one.
+///
 ///
 /// # Example
 ///
@@ -67,9 +77,29 @@ use crate::{DocId, Score};
 /// # }
 /// # assert!(test().is_ok());
 /// ```
+///
+/// # Values outside of a range
+///
+/// [`RangeQuery::outside`] matches the documents having a value outside of a range instead.
+///
+/// # Documents without a value
+///
+/// By default, the documents without any value for the field are not matched.
+/// [`RangeQuery::with_missing`] makes it possible to match them.
 #[derive(Clone, Debug)]
 pub struct RangeQuery {
     bounds: BoundsRange<Term>,
+    outside: bool,
+    missing: MissingValuePolicy<Term>,
+}
+
+/// Returns the bound of the values that are not within `bound`, on the same side.
+fn complement_bound(bound: &Bound<Term>) -> Bound<Term> {
+    match bound {
+        Bound::Included(term) => Bound::Excluded(term.clone()),
+        Bound::Excluded(term) => Bound::Included(term.clone()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
 }
 
 impl RangeQuery {
@@ -80,9 +110,106 @@ impl RangeQuery {
     pub fn new(lower_bound: Bound<Term>, upper_bound: Bound<Term>) -> RangeQuery {
         RangeQuery {
             bounds: BoundsRange::new(lower_bound, upper_bound),
+            outside: false,
+            missing: MissingValuePolicy::Skip,
+        }
+    }
+
+    /// Creates a `RangeQuery` matching the documents having a value outside of the range
+    /// defined by the bounds, i.e. below `lower_bound` or above `upper_bound`.
+    ///
+    /// Bounds keep the same meaning as in [`RangeQuery::new`]: an included bound belongs to the
+    /// range and is therefore not matched, while an excluded bound is matched. An unbounded side
+    /// matches no value.
+    ///
+    /// Documents without any value for the field are not matched. For a multivalued field, a
+    /// document is matched as soon as one of its values is outside of the range, even if some
+    /// other value is within the range. Matching the documents having all of their values outside
+    /// the range is done by excluding the range with a `MustNot` clause instead.
+    ///
+    /// ```rust
+    /// use std::ops::Bound;
+    ///
+    /// use tantivy::query::RangeQuery;
+    /// use tantivy::schema::{Schema, FAST};
+    /// use tantivy::Term;
+    ///
+    /// let mut schema_builder = Schema::builder();
+    /// let price = schema_builder.add_u64_field("price", FAST);
+    /// // Matches the prices below 10 or above 20.
+    /// let _query = RangeQuery::outside(
+    ///     Bound::Included(Term::from_field_u64(price, 10)),
+    ///     Bound::Included(Term::from_field_u64(price, 20)),
+    /// );
+    /// ```
+    pub fn outside(lower_bound: Bound<Term>, upper_bound: Bound<Term>) -> RangeQuery {
+        RangeQuery {
+            bounds: BoundsRange::new(lower_bound, upper_bound),
+            outside: true,
+            missing: MissingValuePolicy::Skip,
         }
     }
 
+    /// Returns true if the query matches the values outside of its bounds.
+    pub fn is_outside(&self) -> bool {
+        self.outside
+    }
+
+    /// Sets how the documents without any value for the field are handled.
+    ///
+    /// - [`MissingValuePolicy::Skip`], the default, does not match them.
+    /// - [`MissingValuePolicy::Default`] matches them if the given term is matched. The term must
+    ///   belong to the field of the query, and have the same type as its bounds.
+    /// - [`MissingValuePolicy::First`] handles them as if they had a value lower than any value:
+    ///   they are matched if there is no lower bound, or by [`RangeQuery::outside`] if there is
+    ///   one.
+    /// - [`MissingValuePolicy::Last`] handles them as if they had a value greater than any value:
+    ///   they are matched if there is no upper bound, or by [`RangeQuery::outside`] if there is
+    ///   one.
+    ///
+    /// Matching the documents without a value requires the field to be a fast field, and is not
+    /// supported on JSON fields.
+    ///
+    /// ```rust
+    /// use std::ops::Bound;
+    ///
+    /// use tantivy::collector::Count;
+    /// use tantivy::query::RangeQuery;
+    /// use tantivy::schema::{Schema, FAST};
+    /// use tantivy::{doc, Index, IndexWriter, MissingValuePolicy, Term};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let price = schema_builder.add_u64_field("price", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(price => 5u64))?;
+    /// index_writer.add_document(doc!(price => 50u64))?;
+    /// index_writer.add_document(doc!())?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// // The documents without a price are handled as free.
+    /// let cheap = RangeQuery::new(
+    ///     Bound::Unbounded,
+    ///     Bound::Excluded(Term::from_field_u64(price, 10)),
+    /// )
+    /// .with_missing(MissingValuePolicy::Default(Term::from_field_u64(price, 0)));
+    /// assert_eq!(searcher.search(&cheap, &Count)?, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_missing(mut self, missing: MissingValuePolicy<Term>) -> RangeQuery {
+        self.missing = missing;
+        self
+    }
+
+    /// Returns how the documents without any value for the field are handled.
+    pub fn missing(&self) -> &MissingValuePolicy<Term> {
+        &self.missing
+    }
+
     /// Field to search over
     pub fn field(&self) -> Field {
         self.get_term().field()
@@ -93,18 +220,127 @@ impl RangeQuery {
         self.get_term().typ()
     }
 
+    /// Returns true if the range only contains a single value.
+    fn is_single_value(&self) -> bool {
+        match (&self.bounds.lower_bound, &self.bounds.upper_bound) {
+            (Bound::Included(lower), Bound::Included(upper)) => lower == upper,
+            _ => false,
+        }
+    }
+
     pub(crate) fn get_term(&self) -> &Term {
         self.bounds
             .get_inner()
             .expect("At least one bound must be set")
     }
+
+    /// Returns true if the documents without any value for the field are matched.
+    fn matches_missing(&self) -> crate::Result<bool> {
+        let within_range = match &self.missing {
+            MissingValuePolicy::Skip => return Ok(false),
+            MissingValuePolicy::First => matches!(self.bounds.lower_bound, Bound::Unbounded),
+            MissingValuePolicy::Last => matches!(self.bounds.upper_bound, Bound::Unbounded),
+            MissingValuePolicy::Default(term) => {
+                if term.field() != self.field() || term.typ() != self.value_type() {
+                    return Err(crate::TantivyError::InvalidArgument(format!(
+                        "The missing value {term:?} does not match the field and type of the range"
+                    )));
+                }
+                // The serialized values of the terms follow the order of the values.
+                let value = term.serialized_value_bytes();
+                let above_lower = match &self.bounds.lower_bound {
+                    Bound::Included(lower) => lower.serialized_value_bytes() <= value,
+                    Bound::Excluded(lower) => lower.serialized_value_bytes() < value,
+                    Bound::Unbounded => true,
+                };
+                let below_upper = match &self.bounds.upper_bound {
+                    Bound::Included(upper) => value <= upper.serialized_value_bytes(),
+                    Bound::Excluded(upper) => value < upper.serialized_value_bytes(),
+                    Bound::Unbounded => true,
+                };
+                above_lower && below_upper
+            }
+        };
+        Ok(within_range != self.outside)
+    }
+
+    /// Builds the weight matching the documents of the range, along with the documents without
+    /// any value for the field.
+    fn with_missing_weight(
+        &self,
+        enable_scoring: EnableScoring<'_>,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field_entry = schema.get_field_entry(self.field());
+        if field_entry.field_type().is_json() {
+            return Err(crate::TantivyError::InvalidArgument(
+                "Matching the missing values of a RangeQuery is not supported on JSON fields"
+                    .to_string(),
+            ));
+        }
+        let range_query = RangeQuery {
+            missing: MissingValuePolicy::Skip,
+            ..self.clone()
+        };
+        let missing_query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(AllQuery)),
+            (
+                Occur::MustNot,
+                Box::new(ExistsQuery::new(field_entry.name().to_string(), false)),
+            ),
+        ]);
+        let union = BooleanQuery::union(vec![Box::new(range_query), Box::new(missing_query)]);
+        ConstScoreQuery::new(Box::new(union), 1.0).weight(enable_scoring)
+    }
+
+    /// Builds the weight of an `outside` query, as the union of the ranges below and above
+    /// the bounds.
+    fn outside_weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let mut ranges: Vec<Box<dyn Query>> = Vec::new();
+        if !matches!(self.bounds.lower_bound, Bound::Unbounded) {
+            ranges.push(Box::new(RangeQuery::new(
+                Bound::Unbounded,
+                complement_bound(&self.bounds.lower_bound),
+            )));
+        }
+        if !matches!(self.bounds.upper_bound, Bound::Unbounded) {
+            ranges.push(Box::new(RangeQuery::new(
+                complement_bound(&self.bounds.upper_bound),
+                Bound::Unbounded,
+            )));
+        }
+        match ranges.len() {
+            0 => EmptyQuery.weight(enable_scoring),
+            1 => ranges[0].weight(enable_scoring),
+            _ => {
+                // A multivalued document can match both ranges, but still gets a score of one.
+                ConstScoreQuery::new(Box::new(BooleanQuery::union(ranges)), 1.0)
+                    .weight(enable_scoring)
+            }
+        }
+    }
 }
 
 impl Query for RangeQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        if self.matches_missing()? {
+            return self.with_missing_weight(enable_scoring);
+        }
+        if self.outside {
+            return self.outside_weight(enable_scoring);
+        }
         let schema = enable_scoring.schema();
-        let field_type = schema.get_field_entry(self.field()).field_type();
+        let field_entry = schema.get_field_entry(self.field());
+        let field_type = field_entry.field_type();
 
+        if field_type.is_point_and_range_indexed() && self.is_single_value() {
+            return Ok(Box::new(InvertedIndexRangeWeight::new(
+                self.field(),
+                &self.bounds.lower_bound,
+                &self.bounds.upper_bound,
+                None,
+            )));
+        }
         if field_type.is_fast() && is_type_valid_for_fastfield_range_query(self.value_type()) {
             Ok(Box::new(FastFieldRangeWeight::new(self.bounds.clone())))
         } else {
@@ -267,11 +503,151 @@ mod tests {
     use crate::collector::{Count, TopDocs};
     use crate::indexer::NoMergePolicy;
     use crate::query::range_query::range_query::InvertedIndexRangeQuery;
-    use crate::query::QueryParser;
+    use crate::query::{BitSetDocSet, ConstScorer, EnableScoring, Query, QueryParser, TermQuery};
     use crate::schema::{
-        Field, IntoIpv6Addr, Schema, TantivyDocument, FAST, INDEXED, STORED, TEXT,
+        Field, IndexRecordOption, IntoIpv6Addr, NumericOptions, Schema, TantivyDocument, FAST,
+        INDEXED, STORED, TEXT,
     };
-    use crate::{Index, IndexWriter, Term};
+    use crate::space_usage::PerFieldSpaceUsage;
+    use crate::{Index, IndexWriter, MissingValuePolicy, TantivyError, Term};
+
+    #[test]
+    fn test_range_query_outside() -> crate::Result<()> {
+        for options in [NumericOptions::from(FAST), NumericOptions::from(INDEXED)] {
+            let mut schema_builder = Schema::builder();
+            let price = schema_builder.add_u64_field("price", options);
+            let index = Index::create_in_ram(schema_builder.build());
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for prices in [&[5u64][..], &[10], &[15], &[20], &[25], &[], &[15, 30]] {
+                let mut doc = TantivyDocument::default();
+                for &value in prices {
+                    doc.add_u64(price, value);
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+            let matching_docs = |lower: Bound<u64>, upper: Bound<u64>| -> crate::Result<Vec<u32>> {
+                let term = |value: u64| Term::from_field_u64(price, value);
+                let query = RangeQuery::outside(lower.map(term), upper.map(term));
+                assert!(query.is_outside());
+                let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+                let mut docs: Vec<u32> = top_docs
+                    .into_iter()
+                    .map(|(score, doc_address)| {
+                        assert_eq!(score, 1.0);
+                        doc_address.doc_id
+                    })
+                    .collect();
+                docs.sort();
+                Ok(docs)
+            };
+            assert_eq!(
+                matching_docs(Bound::Included(10), Bound::Included(20))?,
+                vec![0, 4, 6]
+            );
+            assert_eq!(
+                matching_docs(Bound::Excluded(10), Bound::Excluded(20))?,
+                vec![0, 1, 3, 4, 6]
+            );
+            assert_eq!(
+                matching_docs(Bound::Included(10), Bound::Excluded(20))?,
+                vec![0, 3, 4, 6]
+            );
+            assert_eq!(
+                matching_docs(Bound::Unbounded, Bound::Included(20))?,
+                vec![4, 6]
+            );
+            assert_eq!(
+                matching_docs(Bound::Included(10), Bound::Unbounded)?,
+                vec![0]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_range_query_missing_value_policy() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(price => 5u64))?;
+        index_writer.add_document(doc!(price => 15u64))?;
+        index_writer.add_document(doc!(price => 25u64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term = |value: u64| Term::from_field_u64(price, value);
+        let matching_docs = |query: RangeQuery| -> crate::Result<Vec<u32>> {
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+            let mut docs: Vec<u32> = top_docs
+                .into_iter()
+                .map(|(score, doc_address)| {
+                    assert_eq!(score, 1.0);
+                    doc_address.doc_id
+                })
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+        let between = || RangeQuery::new(Bound::Included(term(10)), Bound::Included(term(20)));
+        let below = || RangeQuery::new(Bound::Unbounded, Bound::Excluded(term(10)));
+        let above = || RangeQuery::new(Bound::Excluded(term(20)), Bound::Unbounded);
+        let outside = || RangeQuery::outside(Bound::Included(term(10)), Bound::Unbounded);
+
+        assert_eq!(between().missing(), &MissingValuePolicy::Skip);
+        assert_eq!(matching_docs(between())?, vec![1]);
+        assert_eq!(matching_docs(below())?, vec![0]);
+        assert_eq!(matching_docs(outside())?, vec![0]);
+
+        let skip = MissingValuePolicy::Skip;
+        assert_eq!(matching_docs(below().with_missing(skip))?, vec![0]);
+
+        let first = MissingValuePolicy::First;
+        assert_eq!(
+            matching_docs(between().with_missing(first.clone()))?,
+            vec![1]
+        );
+        assert_eq!(
+            matching_docs(below().with_missing(first.clone()))?,
+            vec![0, 3]
+        );
+        assert_eq!(matching_docs(above().with_missing(first.clone()))?, vec![2]);
+        assert_eq!(matching_docs(outside().with_missing(first))?, vec![0, 3]);
+
+        let last = MissingValuePolicy::Last;
+        assert_eq!(
+            matching_docs(between().with_missing(last.clone()))?,
+            vec![1]
+        );
+        assert_eq!(matching_docs(below().with_missing(last.clone()))?, vec![0]);
+        assert_eq!(
+            matching_docs(above().with_missing(last.clone()))?,
+            vec![2, 3]
+        );
+        assert_eq!(matching_docs(outside().with_missing(last))?, vec![0]);
+
+        let default = |value: u64| MissingValuePolicy::Default(term(value));
+        assert_eq!(
+            matching_docs(between().with_missing(default(10)))?,
+            vec![1, 3]
+        );
+        assert_eq!(matching_docs(between().with_missing(default(30)))?, vec![1]);
+        assert_eq!(matching_docs(below().with_missing(default(10)))?, vec![0]);
+        assert_eq!(
+            matching_docs(outside().with_missing(default(0)))?,
+            vec![0, 3]
+        );
+
+        let other_field = Term::from_field_i64(price, 10);
+        let query = between().with_missing(MissingValuePolicy::Default(other_field));
+        assert!(matches!(
+            searcher.search(&query, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
 
     #[test]
     fn test_range_query_simple() -> crate::Result<()> {
@@ -304,6 +680,85 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_range_query_point_and_range_indexed() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let point_and_range_options = NumericOptions::default().set_point_and_range_indexed();
+        let dual_field = schema_builder.add_u64_field("dual", point_and_range_options);
+        let indexed_field = schema_builder.add_u64_field("indexed", INDEXED);
+        let fast_field = schema_builder.add_u64_field("fast", FAST);
+        let indexed_and_fast_field =
+            schema_builder.add_u64_field("indexed_and_fast", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for val in 0u64..1_000u64 {
+                let val = val % 100;
+                index_writer.add_document(doc!(
+                    dual_field => val,
+                    indexed_field => val,
+                    fast_field => val,
+                    indexed_and_fast_field => val,
+                ))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_u64(dual_field, 42),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&term_query, &Count)?, 10);
+        let range_query = RangeQuery::new(
+            Bound::Included(Term::from_field_u64(dual_field, 10)),
+            Bound::Excluded(Term::from_field_u64(dual_field, 20)),
+        );
+        assert_eq!(searcher.search(&range_query, &Count)?, 100);
+        let point_range_query = RangeQuery::new(
+            Bound::Included(Term::from_field_u64(dual_field, 42)),
+            Bound::Included(Term::from_field_u64(dual_field, 42)),
+        );
+        assert_eq!(searcher.search(&point_range_query, &Count)?, 10);
+
+        // Only the fields opting in run their single value ranges on the inverted index.
+        let runs_on_inverted_index = |field: Field| -> crate::Result<bool> {
+            let point_range_query = RangeQuery::new(
+                Bound::Included(Term::from_field_u64(field, 42)),
+                Bound::Included(Term::from_field_u64(field, 42)),
+            );
+            let weight =
+                point_range_query.weight(EnableScoring::disabled_from_searcher(&searcher))?;
+            let scorer = weight.scorer(searcher.segment_reader(0), 1.0)?;
+            Ok(scorer.is::<ConstScorer<BitSetDocSet>>())
+        };
+        assert!(runs_on_inverted_index(dual_field)?);
+        assert!(!runs_on_inverted_index(indexed_and_fast_field)?);
+
+        // The field pays for both the inverted index and the fast field.
+        let space_usage = searcher.space_usage()?;
+        let segment_space_usage = &space_usage.segments()[0];
+        let field_usage = |usage: &PerFieldSpaceUsage, field: Field| {
+            usage
+                .fields()
+                .find(|(usage_field, _)| **usage_field == field)
+                .map(|(_, field_usage)| field_usage.total())
+                .unwrap_or_default()
+        };
+        let postings = segment_space_usage.postings();
+        let fast_fields = segment_space_usage.fast_fields();
+        assert!(field_usage(postings, dual_field) > 0u64);
+        assert_eq!(
+            field_usage(postings, dual_field),
+            field_usage(postings, indexed_field)
+        );
+        assert!(field_usage(fast_fields, dual_field) > 0u64);
+        assert_eq!(
+            field_usage(fast_fields, dual_field),
+            field_usage(fast_fields, fast_field)
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_range_query_with_limit() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
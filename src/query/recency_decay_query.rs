@@ -0,0 +1,382 @@
+use std::fmt;
+use std::time::Duration;
+
+use columnar::Column;
+
+use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
+use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::Type;
+use crate::{DateTime, DocId, DocSet, Score, Searcher, SegmentReader, TantivyError, Term};
+
+/// `RecencyDecayQuery` is a wrapper over a query blending its score with the freshness of the
+/// documents.
+///
+/// The document set matched by the `RecencyDecayQuery` is strictly the same as the underlying
+/// query. The score of each document is the score of the underlying query multiplied by
+/// `0.5 ^ (age / half_life)`, where the age is the time elapsed between the value of the date
+/// fast field of the document and `now`: a document as old as the half-life gets half of its
+/// score, a document twice as old a quarter of it.
+///
+/// `now` is a parameter of the query rather than the wall clock, so that a search can be
+/// reproduced. The documents dated after `now` have an age of zero, and keep their score. The
+/// documents without a date are considered infinitely old, and get a score of zero. Only the
+/// first date of the documents with several dates is considered.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{QueryParser, RecencyDecayQuery};
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DateTime, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let published = schema_builder.add_date_field("published", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// let day = 24 * 3600;
+/// index_writer.add_document(doc!(
+///     title => "election election results",
+///     published => DateTime::from_timestamp_secs(10 * day),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "election results",
+///     published => DateTime::from_timestamp_secs(100 * day),
+/// ))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("election")?;
+/// let recency_query = RecencyDecayQuery::new(
+///     query,
+///     "published".to_string(),
+///     Duration::from_secs(7 * day as u64),
+///     DateTime::from_timestamp_secs(101 * day),
+/// );
+/// let top_docs = searcher.search(&recency_query, &TopDocs::with_limit(2))?;
+/// // The fresh document comes first, though the old one is more relevant.
+/// assert_eq!(top_docs[0].1.doc_id, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct RecencyDecayQuery {
+    query: Box<dyn Query>,
+    date_field: String,
+    half_life: Duration,
+    now: DateTime,
+}
+
+impl RecencyDecayQuery {
+    /// Builds a query decaying the score of `query` with the age of the documents, given by
+    /// the date fast field `date_field`, relative to `now`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `half_life` is zero.
+    pub fn new(
+        query: Box<dyn Query>,
+        date_field: String,
+        half_life: Duration,
+        now: DateTime,
+    ) -> RecencyDecayQuery {
+        assert!(!half_life.is_zero(), "The half-life has to be positive.");
+        RecencyDecayQuery {
+            query,
+            date_field,
+            half_life,
+            now,
+        }
+    }
+}
+
+impl Clone for RecencyDecayQuery {
+    fn clone(&self) -> Self {
+        RecencyDecayQuery {
+            query: self.query.box_clone(),
+            date_field: self.date_field.clone(),
+            half_life: self.half_life,
+            now: self.now,
+        }
+    }
+}
+
+impl fmt::Debug for RecencyDecayQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RecencyDecay(query={:?}, date_field={:?}, half_life={:?}, now={:?})",
+            self.query, self.date_field, self.half_life, self.now
+        )
+    }
+}
+
+impl Query for RecencyDecayQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field = schema.get_field(&self.date_field)?;
+        let field_entry = schema.get_field_entry(field);
+        if field_entry.field_type().value_type() != Type::Date || !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a date fast field.",
+                self.date_field
+            )));
+        }
+        let weight = self.query.weight(enable_scoring)?;
+        if !enable_scoring.is_scoring_enabled() {
+            return Ok(weight);
+        }
+        Ok(Box::new(RecencyDecayWeight {
+            weight,
+            date_field: self.date_field.clone(),
+            decay: RecencyDecay {
+                half_life_nanos: self.half_life.as_nanos() as f64,
+                now_nanos: self.now.into_timestamp_nanos(),
+            },
+        }))
+    }
+
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        self.query.cost(searcher)
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct RecencyDecay {
+    half_life_nanos: f64,
+    now_nanos: i64,
+}
+
+impl RecencyDecay {
+    fn factor(&self, date_opt: Option<DateTime>) -> Score {
+        let Some(date) = date_opt else {
+            return 0.0;
+        };
+        let age_nanos = self
+            .now_nanos
+            .saturating_sub(date.into_timestamp_nanos())
+            .max(0);
+        0.5f64.powf(age_nanos as f64 / self.half_life_nanos) as Score
+    }
+}
+
+/// Weight associated to the `RecencyDecayQuery`.
+struct RecencyDecayWeight {
+    weight: Box<dyn Weight>,
+    date_field: String,
+    decay: RecencyDecay,
+}
+
+impl RecencyDecayWeight {
+    fn date_column(&self, reader: &SegmentReader) -> crate::Result<Option<Column<DateTime>>> {
+        reader.fast_fields().column_opt(&self.date_field)
+    }
+}
+
+impl Weight for RecencyDecayWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        Ok(Box::new(RecencyDecayScorer {
+            underlying: self.weight.scorer(reader, boost)?,
+            date_column_opt: self.date_column(reader)?,
+            decay: self.decay,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let date_opt = self
+            .date_column(reader)?
+            .and_then(|date_column| date_column.first(doc));
+        let factor = self.decay.factor(date_opt);
+        let mut explanation = Explanation::new_with_string(
+            format!("Recency decay x{factor} of ..."),
+            underlying_explanation.value() * factor,
+        );
+        explanation.add_detail(underlying_explanation);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+struct RecencyDecayScorer {
+    underlying: Box<dyn Scorer>,
+    date_column_opt: Option<Column<DateTime>>,
+    decay: RecencyDecay,
+}
+
+impl DocSet for RecencyDecayScorer {
+    fn advance(&mut self) -> DocId {
+        self.underlying.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.underlying.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; COLLECT_BLOCK_BUFFER_LEN]) -> usize {
+        self.underlying.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> u32 {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.underlying.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.underlying.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.underlying.count_including_deleted()
+    }
+}
+
+impl Scorer for RecencyDecayScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.underlying.doc();
+        let date_opt = self
+            .date_column_opt
+            .as_ref()
+            .and_then(|date_column| date_column.first(doc));
+        self.underlying.score() * self.decay.factor(date_opt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RecencyDecayQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{Query, QueryParser};
+    use crate::schema::{Schema, Value, FAST, STORED, TEXT};
+    use crate::{DateTime, DocAddress, Index, IndexWriter, Score, TantivyDocument};
+
+    const DAY: i64 = 24 * 3600;
+
+    #[test]
+    fn test_recency_decay_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let published = schema_builder.add_date_field("published", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        // Title, and age in days.
+        let docs = [
+            ("storm storm storm", Some(30)),
+            ("storm storm", Some(10)),
+            ("storm", Some(0)),
+            ("storm warning", Some(-2)),
+            ("storm storm storm storm", None),
+        ];
+        let now = DateTime::from_timestamp_secs(1_000 * DAY);
+        for (i, (title_text, age_days)) in docs.iter().enumerate() {
+            let mut doc = TantivyDocument::default();
+            doc.add_text(title, title_text);
+            if let Some(age_days) = age_days {
+                doc.add_date(
+                    published,
+                    DateTime::from_timestamp_secs((1_000 - age_days) * DAY),
+                );
+            }
+            index_writer.add_document(doc)?;
+            if i == 2 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("storm")?;
+        let titles = |top_docs: &[(Score, DocAddress)]| -> Vec<String> {
+            top_docs
+                .iter()
+                .map(|(_, doc_address)| {
+                    let doc: TantivyDocument = searcher.doc(*doc_address).unwrap();
+                    doc.get_first(title).unwrap().as_str().unwrap().to_string()
+                })
+                .collect()
+        };
+
+        let relevance_top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        assert_eq!(
+            titles(&relevance_top_docs),
+            [
+                "storm storm storm storm",
+                "storm storm storm",
+                "storm storm",
+                "storm",
+                "storm warning"
+            ]
+        );
+
+        let recency_query = RecencyDecayQuery::new(
+            query.box_clone(),
+            "published".to_string(),
+            Duration::from_secs(10 * DAY as u64),
+            now,
+        );
+        assert_eq!(searcher.search(&recency_query, &Count)?, 5);
+        let recency_top_docs = searcher.search(&recency_query, &TopDocs::with_limit(5))?;
+        assert_eq!(
+            titles(&recency_top_docs),
+            [
+                "storm",
+                "storm warning",
+                "storm storm",
+                "storm storm storm",
+                "storm storm storm storm"
+            ]
+        );
+        for (score, doc_address) in &recency_top_docs {
+            let relevance = query.explain(&searcher, *doc_address)?.value();
+            let expected = match titles(&[(*score, *doc_address)])[0].as_str() {
+                // One half-life old.
+                "storm storm" => relevance * 0.5,
+                // Three half-lives old.
+                "storm storm storm" => relevance * 0.125,
+                // Without a date.
+                "storm storm storm storm" => 0.0,
+                // Published now, or in the future.
+                _ => relevance,
+            };
+            assert!((score - expected).abs() < 1e-5);
+            let explanation = recency_query.explain(&searcher, *doc_address)?;
+            assert!((explanation.value() - score).abs() < 1e-5);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_recency_decay_query_requires_date_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_date_field("published", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("storm")?;
+        for date_field in ["published", "title", "missing"] {
+            let recency_query = RecencyDecayQuery::new(
+                query.box_clone(),
+                date_field.to_string(),
+                Duration::from_secs(DAY as u64),
+                DateTime::from_timestamp_secs(0),
+            );
+            assert!(searcher.search(&recency_query, &Count).is_err());
+        }
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@ use tantivy_fst::{Automaton, Map};
 use crate::query::score_combiner::DoNothingCombiner;
 use crate::query::{AutomatonWeight, BooleanWeight, EnableScoring, Occur, Query, Weight};
 use crate::schema::{Field, Schema};
-use crate::Term;
+use crate::{Searcher, Term};
 
 /// A Term Set Query matches all of the documents containing any of the Term provided
 #[derive(Debug, Clone)]
@@ -73,6 +73,14 @@ impl Query for TermSetQuery {
         Ok(Box::new(self.specialized_weight(enable_scoring.schema())?))
     }
 
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        let mut cost = 0u64;
+        for term in self.terms_map.values().flatten() {
+            cost = cost.saturating_add(searcher.doc_freq(term)?);
+        }
+        Ok(Some(cost))
+    }
+
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         for terms in self.terms_map.values() {
             for term in terms {
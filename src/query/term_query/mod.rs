@@ -4,9 +4,11 @@ mod term_weight;
 
 pub use self::term_query::TermQuery;
 pub use self::term_scorer::TermScorer;
+pub(crate) use self::term_weight::TermWeight;
 #[cfg(test)]
 mod tests {
 
+    use crate::collector::tests::TestCollector;
     use crate::collector::TopDocs;
     use crate::docset::DocSet;
     use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
@@ -212,4 +214,80 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_term_query_binary_term_freq() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tags = schema_builder.add_text_field("tags", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tags => "rust rust rust rust"))?;
+        index_writer.add_document(doc!(tags => "rust java go python"))?;
+        index_writer.add_document(doc!(tags => "java"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(tags, "rust"),
+            IndexRecordOption::WithFreqs,
+        );
+        assert!(!term_query.is_binary_term_freq());
+
+        let top_docs = searcher.search(&term_query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+        assert!(top_docs[0].0 > top_docs[1].0);
+
+        // Both documents have the same length, and contain the term: they get the same score.
+        let binary_term_query = term_query.with_binary_term_freq();
+        assert!(binary_term_query.is_binary_term_freq());
+        let top_docs = searcher.search(&binary_term_query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs.len(), 2);
+        assert_nearly_equals!(top_docs[0].0, top_docs[1].0);
+        let explanation = binary_term_query.explain(&searcher, DocAddress::new(0, 0))?;
+        assert_nearly_equals!(explanation.value(), top_docs[0].0);
+        assert!(explanation
+            .to_pretty_json()
+            .contains("occurrences of term within document, clamped to 1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_query_binary_term_freq_pruning() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // The long documents repeating the term maximize the score without binary term
+        // frequencies, while the short documents score the best with binary term frequencies.
+        for i in 0..3 * COMPRESSION_BLOCK_SIZE {
+            let text_value = if i % 100 == 99 {
+                "rust".to_string()
+            } else {
+                let mut text_value = "rust ".repeat(1 + i % 5);
+                text_value.push_str(&"filler ".repeat(20 + i % 30));
+                text_value
+            };
+            index_writer.add_document(doc!(text => text_value))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text, "rust"),
+            IndexRecordOption::WithFreqs,
+        )
+        .with_binary_term_freq();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+        let exhaustive = searcher.search(
+            &query,
+            &TestCollector {
+                compute_score: true,
+            },
+        )?;
+        let mut exhaustive_scores = exhaustive.scores().to_vec();
+        exhaustive_scores.sort_by(|left, right| right.total_cmp(left));
+        assert_eq!(top_docs.len(), 3);
+        for ((score, _), exhaustive_score) in top_docs.iter().zip(&exhaustive_scores) {
+            assert_nearly_equals!(*score, *exhaustive_score);
+        }
+        Ok(())
+    }
 }
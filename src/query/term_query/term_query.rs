@@ -4,7 +4,7 @@ use super::term_weight::TermWeight;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, Explanation, Query, Weight};
 use crate::schema::IndexRecordOption;
-use crate::Term;
+use crate::{Searcher, Term};
 
 /// A Term query matches all of the documents
 /// containing a specific term.
@@ -58,6 +58,7 @@ use crate::Term;
 pub struct TermQuery {
     term: Term,
     index_record_option: IndexRecordOption,
+    binary_term_freq: bool,
 }
 
 impl fmt::Debug for TermQuery {
@@ -72,9 +73,27 @@ impl TermQuery {
         TermQuery {
             term,
             index_record_option: segment_postings_options,
+            binary_term_freq: false,
         }
     }
 
+    /// Scores the documents by the presence of the term only, ignoring how many times it
+    /// occurs: the BM25 term frequency is clamped to 1.
+    ///
+    /// Unlike querying with [`IndexRecordOption::Basic`], the term frequencies are still read,
+    /// so they remain available to the scorer, for instance to explain a score. The length
+    /// of the field still impacts the score.
+    #[must_use]
+    pub fn with_binary_term_freq(mut self) -> TermQuery {
+        self.binary_term_freq = true;
+        self
+    }
+
+    /// Returns true if the term frequency is clamped to 1 when scoring.
+    pub fn is_binary_term_freq(&self) -> bool {
+        self.binary_term_freq
+    }
+
     /// The `Term` this query is built out of.
     pub fn term(&self) -> &Term {
         &self.term
@@ -95,7 +114,7 @@ impl TermQuery {
             let error_msg = format!("Field {:?} is not indexed.", field_entry.name());
             return Err(crate::TantivyError::SchemaError(error_msg));
         }
-        let bm25_weight = match enable_scoring {
+        let mut bm25_weight = match enable_scoring {
             EnableScoring::Enabled {
                 statistics_provider,
                 ..
@@ -104,6 +123,9 @@ impl TermQuery {
                 Bm25Weight::new(Explanation::new("<no score>", 1.0f32), 1.0f32)
             }
         };
+        if self.binary_term_freq {
+            bm25_weight = bm25_weight.with_binary_term_freq();
+        }
         let scoring_enabled = enable_scoring.is_scoring_enabled();
         let index_record_option = if scoring_enabled {
             self.index_record_option
@@ -124,6 +146,10 @@ impl Query for TermQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
         Ok(Box::new(self.specialized_weight(enable_scoring)?))
     }
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        Ok(Some(searcher.doc_freq(&self.term)?))
+    }
+
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         visitor(&self.term, false);
     }
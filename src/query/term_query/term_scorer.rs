@@ -10,6 +10,8 @@ pub struct TermScorer {
     postings: SegmentPostings,
     fieldnorm_reader: FieldNormReader,
     similarity_weight: Bm25Weight,
+    // Set when scoring is disabled, to skip the BM25 computation.
+    constant_score: Option<Score>,
 }
 
 impl TermScorer {
@@ -22,9 +24,16 @@ impl TermScorer {
             postings,
             fieldnorm_reader,
             similarity_weight,
+            constant_score: None,
         }
     }
 
+    /// Makes the scorer return `score` for all of the documents, instead of their BM25 score.
+    pub(crate) fn with_constant_score(mut self, score: Score) -> TermScorer {
+        self.constant_score = Some(score);
+        self
+    }
+
     pub(crate) fn shallow_seek(&mut self, target_doc: DocId) {
         self.postings.block_cursor.shallow_seek(target_doc);
     }
@@ -78,14 +87,10 @@ impl TermScorer {
         self.postings.term_freq()
     }
 
-    pub fn fieldnorm_id(&self) -> u8 {
-        self.fieldnorm_reader.fieldnorm_id(self.doc())
-    }
-
     pub fn explain(&self) -> Explanation {
-        let fieldnorm_id = self.fieldnorm_id();
         let term_freq = self.term_freq();
-        self.similarity_weight.explain(fieldnorm_id, term_freq)
+        self.similarity_weight
+            .explain_doc(&self.fieldnorm_reader, self.doc(), term_freq)
     }
 
     pub fn max_score(&self) -> Score {
@@ -117,9 +122,12 @@ impl DocSet for TermScorer {
 
 impl Scorer for TermScorer {
     fn score(&mut self) -> Score {
-        let fieldnorm_id = self.fieldnorm_id();
+        if let Some(constant_score) = self.constant_score {
+            return constant_score;
+        }
         let term_freq = self.term_freq();
-        self.similarity_weight.score(fieldnorm_id, term_freq)
+        self.similarity_weight
+            .score_doc(&self.fieldnorm_reader, self.doc(), term_freq)
     }
 }
 
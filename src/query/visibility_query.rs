@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use common::BitSet;
+
+use crate::core::Searcher;
+use crate::docset::DocSet;
+use crate::index::{SegmentId, SegmentReader};
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::{DocId, Score, TantivyError};
+
+/// Query matching a precomputed set of documents, such as the documents visible to a user.
+///
+/// The set is given as a bitset of global doc ids, and is meant to be intersected with other
+/// queries through a [`BooleanQuery`](crate::query::BooleanQuery). It is split into one bitset
+/// per segment once, when the query is built, so the search itself only iterates over it.
+///
+/// # Global doc ids
+///
+/// The global doc ids are relative to a [`Searcher`]: the segments are laid out one after the
+/// other, in the order of [`Searcher::segment_readers`], and the document `doc_id` of the
+/// segment `segment_ord` gets the global doc id
+/// `sum(max_doc of the segments before segment_ord) + doc_id`. Deleted documents keep their
+/// global doc id.
+///
+/// # Reloads
+///
+/// The query only covers the segments of the searcher it was built from. Searching a segment
+/// it does not know about, for instance a segment created by a commit or a merge after a
+/// reload of the reader, returns a `TantivyError::InvalidArgument`. The query must then be
+/// rebuilt from the new searcher, with global doc ids computed against this searcher.
+///
+/// All of the matched documents get the score 0.0, so intersecting a query with a
+/// `VisibilityQuery` does not change its scores.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::{BooleanQuery, TermQuery, VisibilityQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, TEXT};
+/// use tantivy::{doc, BitSet, Index, IndexWriter, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "public report"))?;
+/// index_writer.add_document(doc!(title => "secret report"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let mut visible = BitSet::with_max_value(2);
+/// visible.insert(0);
+/// let visibility_query = VisibilityQuery::from_global_bitset(&searcher, &visible);
+/// let report_query = TermQuery::new(
+///     Term::from_field_text(title, "report"),
+///     IndexRecordOption::Basic,
+/// );
+/// let query = BooleanQuery::intersection(vec![
+///     Box::new(report_query),
+///     Box::new(visibility_query),
+/// ]);
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct VisibilityQuery {
+    segment_bitsets: Arc<HashMap<SegmentId, Arc<BitSet>>>,
+}
+
+impl fmt::Debug for VisibilityQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VisibilityQuery")
+            .field("num_segments", &self.segment_bitsets.len())
+            .finish()
+    }
+}
+
+impl VisibilityQuery {
+    /// Creates a query matching the documents whose global doc id, relative to `searcher`,
+    /// is in `global_bitset`.
+    ///
+    /// The global doc ids beyond the documents of `searcher` are ignored.
+    pub fn from_global_bitset(searcher: &Searcher, global_bitset: &BitSet) -> VisibilityQuery {
+        let mut segment_bitsets = HashMap::with_capacity(searcher.segment_readers().len());
+        let mut doc_base: u32 = 0;
+        for segment_reader in searcher.segment_readers() {
+            let max_doc = segment_reader.max_doc();
+            let mut segment_bitset = BitSet::with_max_value(max_doc);
+            let num_visible_docs = max_doc.min(global_bitset.max_value().saturating_sub(doc_base));
+            for doc in 0..num_visible_docs {
+                if global_bitset.contains(doc_base + doc) {
+                    segment_bitset.insert(doc);
+                }
+            }
+            segment_bitsets.insert(segment_reader.segment_id(), Arc::new(segment_bitset));
+            doc_base += max_doc;
+        }
+        VisibilityQuery {
+            segment_bitsets: Arc::new(segment_bitsets),
+        }
+    }
+}
+
+impl Query for VisibilityQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        Ok(Box::new(VisibilityWeight {
+            segment_bitsets: self.segment_bitsets.clone(),
+        }))
+    }
+}
+
+/// Weight associated with the `VisibilityQuery` query.
+pub struct VisibilityWeight {
+    segment_bitsets: Arc<HashMap<SegmentId, Arc<BitSet>>>,
+}
+
+impl VisibilityWeight {
+    fn segment_bitset(&self, reader: &SegmentReader) -> crate::Result<&Arc<BitSet>> {
+        self.segment_bitsets
+            .get(&reader.segment_id())
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The segment {} is not covered by the VisibilityQuery. The query needs to be \
+                     rebuilt from the current searcher.",
+                    reader.segment_id().short_uuid_string()
+                ))
+            })
+    }
+}
+
+impl Weight for VisibilityWeight {
+    fn scorer(&self, reader: &SegmentReader, _boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let docset = BitSetDocSet::from(Arc::clone(self.segment_bitset(reader)?));
+        Ok(Box::new(ConstScorer::new(docset, 0.0)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        if !self.segment_bitset(reader)?.contains(doc) {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("VisibilityQuery", 0.0))
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        let mut docset = BitSetDocSet::from(Arc::clone(self.segment_bitset(reader)?));
+        if let Some(alive_bitset) = reader.alive_bitset() {
+            Ok(docset.count(alive_bitset))
+        } else {
+            Ok(docset.count_including_deleted())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::BitSet;
+
+    use super::VisibilityQuery;
+    use crate::collector::{Count, DocSetCollector, TopDocs};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{BooleanQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Value, FAST, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, TantivyError, Term};
+
+    #[test]
+    fn test_visibility_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let id = schema_builder.add_u64_field("id", FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text => "a", id => 0u64))?;
+        index_writer.add_document(doc!(text => "a b", id => 1u64))?;
+        index_writer.add_document(doc!(text => "b", id => 2u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "a", id => 3u64))?;
+        index_writer.add_document(doc!(text => "a a", id => 4u64))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        // Global doc id of each document, following the order of the segment readers.
+        let mut global_doc_ids = Vec::new();
+        let mut doc_base = 0;
+        for segment_reader in searcher.segment_readers() {
+            let id_column = segment_reader.fast_fields().u64("id")?;
+            for doc in 0..segment_reader.max_doc() {
+                global_doc_ids.push((id_column.first(doc).unwrap(), doc_base + doc));
+            }
+            doc_base += segment_reader.max_doc();
+        }
+        let mut visible = BitSet::with_max_value(5);
+        for &(doc_id, global_doc_id) in &global_doc_ids {
+            if [0, 2, 4].contains(&doc_id) {
+                visible.insert(global_doc_id);
+            }
+        }
+        let visibility_query = VisibilityQuery::from_global_bitset(&searcher, &visible);
+        assert_eq!(searcher.search(&visibility_query, &Count)?, 3);
+
+        let term_query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let query = BooleanQuery::intersection(vec![
+            Box::new(term_query.clone()),
+            Box::new(visibility_query),
+        ]);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        let mut ids: Vec<u64> = top_docs
+            .iter()
+            .map(|(_, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(*doc_address).unwrap();
+                doc.get_first(id).unwrap().as_u64().unwrap()
+            })
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, [0, 4]);
+        // The visibility filter does not change the scores.
+        for (score, doc_address) in top_docs {
+            let explanation = term_query.explain(&searcher, doc_address)?;
+            assert_eq!(score, explanation.value());
+        }
+
+        // A smaller bitset only covers the first documents.
+        let mut visible = BitSet::with_max_value(1);
+        visible.insert(0);
+        let visibility_query = VisibilityQuery::from_global_bitset(&searcher, &visible);
+        assert_eq!(
+            searcher.search(&visibility_query, &DocSetCollector)?.len(),
+            1
+        );
+
+        // After a reload, the query has to be rebuilt.
+        index_writer.add_document(doc!(text => "a", id => 5u64))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let new_searcher = reader.searcher();
+        assert!(matches!(
+            new_searcher.search(&visibility_query, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let visibility_query = VisibilityQuery::from_global_bitset(&new_searcher, &visible);
+        assert_eq!(new_searcher.search(&visibility_query, &Count)?, 1);
+        Ok(())
+    }
+}
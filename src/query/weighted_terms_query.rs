@@ -0,0 +1,322 @@
+use crate::docset::DocSet;
+use crate::index::SegmentReader;
+use crate::postings::FreqReadingOption;
+use crate::query::boolean_query::{block_wand, block_wand_single_scorer};
+use crate::query::explanation::does_not_match;
+use crate::query::score_combiner::{DoNothingCombiner, SumCombiner};
+use crate::query::term_query::{TermScorer, TermWeight};
+use crate::query::weight::for_each_pruning_scorer;
+use crate::query::{
+    BufferedUnionScorer, EmptyScorer, EmptyWeight, EnableScoring, Explanation, Query, Scorer,
+    TermQuery, Weight,
+};
+use crate::schema::{Field, IndexRecordOption};
+use crate::{DocId, Score, Searcher, TantivyError, Term};
+
+/// Query matching the documents containing any of a set of terms, each term having its own
+/// boost.
+///
+/// The score of a document is the sum of the BM25 scores of the terms it contains, each
+/// multiplied by the boost of the term. This fits the query expansion, where the synonyms of
+/// a term are searched along with it, weighted by the confidence in the synonym:
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::WeightedTermsQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(body => "a fast car"))?;
+/// index_writer.add_document(doc!(body => "a quick car"))?;
+/// index_writer.commit()?;
+///
+/// let query = WeightedTermsQuery::new(
+///     body,
+///     vec![
+///         (Term::from_field_text(body, "fast"), 1.0),
+///         (Term::from_field_text(body, "quick"), 0.8),
+///     ],
+/// );
+/// let searcher = index.reader()?.searcher();
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert!(top_docs[0].0 > top_docs[1].0);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// This is equivalent to a [`BooleanQuery`](crate::query::BooleanQuery) of `Should`
+/// [`BoostQuery`](crate::query::BoostQuery)s over [`TermQuery`]s, but the boosts are applied
+/// to the term scorers directly: the union of these term scorers can then skip the blocks of
+/// documents that cannot make it to the top docs (Block-WAND).
+///
+/// A term given several times gets the sum of its boosts.
+#[derive(Clone, Debug)]
+pub struct WeightedTermsQuery {
+    field: Field,
+    // Sorted by term, without duplicates.
+    terms_with_boosts: Vec<(Term, Score)>,
+}
+
+impl WeightedTermsQuery {
+    /// Creates a query matching the documents containing any of the terms of `field`.
+    ///
+    /// This constructor never fails, but executing the search with this query returns an
+    /// error if one of the terms does not belong to `field`, or if one of the boosts is
+    /// negative or not finite.
+    pub fn new(field: Field, mut terms_with_boosts: Vec<(Term, Score)>) -> WeightedTermsQuery {
+        terms_with_boosts.sort_by(|(left, _), (right, _)| left.cmp(right));
+        let mut deduped_terms_with_boosts: Vec<(Term, Score)> =
+            Vec::with_capacity(terms_with_boosts.len());
+        for (term, boost) in terms_with_boosts {
+            match deduped_terms_with_boosts.last_mut() {
+                Some((last_term, last_boost)) if *last_term == term => *last_boost += boost,
+                _ => deduped_terms_with_boosts.push((term, boost)),
+            }
+        }
+        WeightedTermsQuery {
+            field,
+            terms_with_boosts: deduped_terms_with_boosts,
+        }
+    }
+
+    /// Returns the terms and their boosts, sorted by term.
+    pub fn terms_with_boosts(&self) -> &[(Term, Score)] {
+        &self.terms_with_boosts
+    }
+}
+
+impl Query for WeightedTermsQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        if self.terms_with_boosts.is_empty() {
+            return Ok(Box::new(EmptyWeight));
+        }
+        let term_weights = self
+            .terms_with_boosts
+            .iter()
+            .map(|(term, boost)| {
+                if term.field() != self.field {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Term {term:?} does not belong to the field of the WeightedTermsQuery."
+                    )));
+                }
+                if !boost.is_finite() || *boost < 0.0 {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Boost {boost} of term {term:?} is not a finite positive number."
+                    )));
+                }
+                let term_query = TermQuery::new(term.clone(), IndexRecordOption::WithFreqs);
+                let term_weight = term_query.specialized_weight(enable_scoring)?;
+                Ok((term_weight, *boost))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(Box::new(WeightedTermsWeight {
+            term_weights,
+            scoring_enabled: enable_scoring.is_scoring_enabled(),
+        }))
+    }
+
+    fn cost(&self, searcher: &Searcher) -> crate::Result<Option<u64>> {
+        let mut cost = 0u64;
+        for (term, _) in &self.terms_with_boosts {
+            cost = cost.saturating_add(searcher.doc_freq(term)?);
+        }
+        Ok(Some(cost))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        for (term, _) in &self.terms_with_boosts {
+            visitor(term, false);
+        }
+    }
+}
+
+struct WeightedTermsWeight {
+    term_weights: Vec<(TermWeight, Score)>,
+    scoring_enabled: bool,
+}
+
+impl WeightedTermsWeight {
+    fn term_scorers(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Vec<TermScorer>> {
+        self.term_weights
+            .iter()
+            .map(|(term_weight, term_boost)| {
+                term_weight.specialized_scorer(reader, boost * term_boost)
+            })
+            .collect()
+    }
+}
+
+impl Weight for WeightedTermsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let mut term_scorers = self.term_scorers(reader, boost)?;
+        match term_scorers.len() {
+            0 => Ok(Box::new(EmptyScorer)),
+            1 => Ok(Box::new(term_scorers.pop().unwrap())),
+            _ if self.scoring_enabled => Ok(Box::new(BufferedUnionScorer::build(
+                term_scorers,
+                SumCombiner::default,
+            ))),
+            _ => Ok(Box::new(BufferedUnionScorer::build(
+                term_scorers,
+                DoNothingCombiner::default,
+            ))),
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new("WeightedTermsQuery, sum of:", scorer.score());
+        for (term_weight, term_boost) in &self.term_weights {
+            let Ok(term_explanation) = term_weight.explain(reader, doc) else {
+                continue;
+            };
+            let mut boosted_explanation = Explanation::new_with_string(
+                format!("Boost={term_boost} x"),
+                term_boost * term_explanation.value(),
+            );
+            boosted_explanation.add_detail(term_explanation);
+            explanation.add_detail(boosted_explanation);
+        }
+        Ok(explanation)
+    }
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> crate::Result<()> {
+        let mut term_scorers = self.term_scorers(reader, 1.0)?;
+        // Block-WAND relies on the block max scores, which require the term frequencies.
+        let reads_freqs = term_scorers
+            .iter()
+            .all(|term_scorer| term_scorer.freq_reading_option() == FreqReadingOption::ReadFreq);
+        if !reads_freqs {
+            let mut scorer = self.scorer(reader, 1.0)?;
+            for_each_pruning_scorer(scorer.as_mut(), threshold, callback);
+        } else if term_scorers.len() == 1 {
+            block_wand_single_scorer(term_scorers.pop().unwrap(), threshold, callback);
+        } else {
+            block_wand(term_scorers, threshold, callback);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WeightedTermsQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Score, Term};
+
+    #[test]
+    fn test_weighted_terms_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body => "the car is red"))?;
+        index_writer.add_document(doc!(body => "the automobile is red"))?;
+        index_writer.add_document(doc!(body => "the vehicle is blue"))?;
+        index_writer.add_document(doc!(body => "car or automobile", tag => "both"))?;
+        for _ in 0..20 {
+            index_writer.add_document(doc!(body => "nothing to see here"))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term = |text: &str| Term::from_field_text(body, text);
+        let top_docs = |query: &dyn Query| searcher.search(query, &TopDocs::with_limit(10));
+
+        // "car" and "automobile" have the same document frequency, hence the same BM25 score
+        // alone on documents of the same length: the boost decides.
+        let query = WeightedTermsQuery::new(
+            body,
+            vec![
+                (term("car"), 1.0),
+                (term("automobile"), 0.5),
+                (term("truck"), 2.0),
+            ],
+        );
+        let results = top_docs(&query)?;
+        assert_eq!(results.len(), 3);
+        let score_of = |doc_address: DocAddress| -> Score {
+            results
+                .iter()
+                .find(|(_, address)| *address == doc_address)
+                .unwrap()
+                .0
+        };
+        let car_score = score_of(DocAddress::new(0, 0));
+        let automobile_score = score_of(DocAddress::new(0, 1));
+        assert!(car_score > automobile_score);
+        assert_nearly_equals!(automobile_score * 2.0, car_score);
+
+        // Matches a boolean query of boosted term queries.
+        let boosted_term_query = |text: &str, boost: Score| -> (Occur, Box<dyn Query>) {
+            let term_query = TermQuery::new(term(text), IndexRecordOption::WithFreqs);
+            (
+                Occur::Should,
+                Box::new(BoostQuery::new(Box::new(term_query), boost)),
+            )
+        };
+        let boolean_query = BooleanQuery::new(vec![
+            boosted_term_query("car", 1.0),
+            boosted_term_query("automobile", 0.5),
+        ]);
+        let boolean_results = top_docs(&boolean_query)?;
+        assert_eq!(boolean_results.len(), results.len());
+        for ((score, doc_address), (boolean_score, boolean_doc_address)) in
+            results.iter().zip(&boolean_results)
+        {
+            assert_eq!(doc_address, boolean_doc_address);
+            assert_nearly_equals!(*score, *boolean_score);
+        }
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+        let explanation = query.explain(&searcher, DocAddress::new(0, 3))?;
+        assert_nearly_equals!(explanation.value(), score_of(DocAddress::new(0, 3)));
+
+        // Duplicated terms sum their boosts.
+        let duplicated_query = WeightedTermsQuery::new(
+            body,
+            vec![
+                (term("car"), 0.25),
+                (term("automobile"), 0.5),
+                (term("car"), 0.75),
+            ],
+        );
+        assert_eq!(duplicated_query.terms_with_boosts().len(), 2);
+        assert_eq!(top_docs(&duplicated_query)?, results);
+
+        assert_eq!(
+            searcher.search(&WeightedTermsQuery::new(body, Vec::new()), &Count)?,
+            0
+        );
+        let is_invalid = |query: WeightedTermsQuery| {
+            matches!(
+                searcher.search(&query, &Count),
+                Err(crate::TantivyError::InvalidArgument(_))
+            )
+        };
+        assert!(is_invalid(WeightedTermsQuery::new(
+            body,
+            vec![(Term::from_field_text(tag, "both"), 1.0)]
+        )));
+        assert!(is_invalid(WeightedTermsQuery::new(
+            body,
+            vec![(term("car"), -1.0)]
+        )));
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 mod warming;
 
+use std::collections::HashMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::{atomic, Arc, Weak};
 
@@ -9,7 +10,9 @@ pub use warming::Warmer;
 use self::warming::WarmingState;
 use crate::core::searcher::{SearcherGeneration, SearcherInner};
 use crate::directory::{Directory, WatchCallback, WatchHandle, META_LOCK};
+use crate::schema::Field;
 use crate::store::DOCSTORE_CACHE_CAPACITY;
+use crate::termdict::TermDictionaryAccess;
 use crate::{Index, Inventory, Searcher, SegmentReader, TrackedObject};
 
 /// Defines when a new version of the index should be reloaded.
@@ -37,6 +40,8 @@ pub enum ReloadPolicy {
 /// - [`Warmer`] implementations
 /// - number of warming threads, for parallelizing warming work
 /// - The cache size of the underlying doc store readers.
+/// - A validator excluding the invalid segments.
+/// - The [`TermDictionaryAccess`] of the fields.
 #[derive(Clone)]
 pub struct IndexReaderBuilder {
     reload_policy: ReloadPolicy,
@@ -44,8 +49,12 @@ pub struct IndexReaderBuilder {
     warmers: Vec<Weak<dyn Warmer>>,
     num_warming_threads: usize,
     doc_store_cache_num_blocks: usize,
+    segment_validator_opt: Option<Arc<SegmentValidator>>,
+    term_dictionary_accesses: HashMap<Field, TermDictionaryAccess>,
 }
 
+type SegmentValidator = dyn Fn(&SegmentReader) -> crate::Result<()> + Send + Sync;
+
 impl IndexReaderBuilder {
     #[must_use]
     pub(crate) fn new(index: Index) -> IndexReaderBuilder {
@@ -55,6 +64,8 @@ impl IndexReaderBuilder {
             warmers: Vec::new(),
             num_warming_threads: 1,
             doc_store_cache_num_blocks: DOCSTORE_CACHE_CAPACITY,
+            segment_validator_opt: None,
+            term_dictionary_accesses: HashMap::new(),
         }
     }
 
@@ -75,6 +86,8 @@ impl IndexReaderBuilder {
             self.index,
             warming_state,
             searcher_generation_inventory,
+            self.segment_validator_opt,
+            self.term_dictionary_accesses,
         )?;
         let inner_reader_arc = Arc::new(inner_reader);
         let watch_handle_opt: Option<WatchHandle> = match self.reload_policy {
@@ -142,6 +155,46 @@ impl IndexReaderBuilder {
         self.num_warming_threads = num_warming_threads;
         self
     }
+
+    /// Sets a validator, invoked on each segment whenever the searchable segments are loaded.
+    ///
+    /// The segments for which the validator returns an error are excluded from the searcher,
+    /// and the reason is logged, instead of failing the whole reload. This keeps the index
+    /// online around a corrupt segment, at the price of hiding its documents: the excluded
+    /// segments count neither in [`Searcher::num_docs()`] nor in the document frequencies, and
+    /// their documents cannot be fetched.
+    ///
+    /// The segments which cannot be opened, e.g. because one of their files is missing or
+    /// truncated, are excluded the same way once a validator is set.
+    ///
+    /// The validator runs on every reload, including for the segments it already accepted, so
+    /// it should be cheap.
+    #[must_use]
+    pub fn segment_validator(
+        mut self,
+        segment_validator: Box<SegmentValidator>,
+    ) -> IndexReaderBuilder {
+        self.segment_validator_opt = Some(Arc::from(segment_validator));
+        self
+    }
+
+    /// Sets how the term dictionary of `field` is accessed.
+    ///
+    /// With [`TermDictionaryAccess::FullLoad`], the term dictionary of the field is loaded in
+    /// memory as each segment is opened, which gives predictable lookup latencies on a hot
+    /// field. [`TermDictionaryAccess::Capped`] bounds the memory used by the term dictionary of
+    /// the field instead. The other dictionaries keep the default [`TermDictionaryAccess::Mmap`]
+    /// access, and are opened on first use.
+    #[must_use]
+    pub fn term_dictionary_access(
+        mut self,
+        field: Field,
+        term_dictionary_access: TermDictionaryAccess,
+    ) -> IndexReaderBuilder {
+        self.term_dictionary_accesses
+            .insert(field, term_dictionary_access);
+        self
+    }
 }
 
 impl TryInto<IndexReader> for IndexReaderBuilder {
@@ -159,6 +212,8 @@ struct InnerIndexReader {
     searcher: arc_swap::ArcSwap<SearcherInner>,
     searcher_generation_counter: Arc<AtomicU64>,
     searcher_generation_inventory: Inventory<SearcherGeneration>,
+    segment_validator_opt: Option<Arc<SegmentValidator>>,
+    term_dictionary_accesses: HashMap<Field, TermDictionaryAccess>,
 }
 
 impl InnerIndexReader {
@@ -169,6 +224,8 @@ impl InnerIndexReader {
         // The searcher_generation_inventory is not used as source, but as target to track the
         // loaded segments.
         searcher_generation_inventory: Inventory<SearcherGeneration>,
+        segment_validator_opt: Option<Arc<SegmentValidator>>,
+        term_dictionary_accesses: HashMap<Field, TermDictionaryAccess>,
     ) -> crate::Result<Self> {
         let searcher_generation_counter: Arc<AtomicU64> = Default::default();
 
@@ -178,6 +235,8 @@ impl InnerIndexReader {
             &warming_state,
             &searcher_generation_counter,
             &searcher_generation_inventory,
+            segment_validator_opt.as_deref(),
+            &term_dictionary_accesses,
         )?;
         Ok(InnerIndexReader {
             doc_store_cache_num_blocks,
@@ -186,20 +245,56 @@ impl InnerIndexReader {
             searcher: ArcSwap::from(searcher),
             searcher_generation_counter,
             searcher_generation_inventory,
+            segment_validator_opt,
+            term_dictionary_accesses,
         })
     }
     /// Opens the freshest segments [`SegmentReader`].
     ///
     /// This function acquires a lock to prevent GC from removing files
     /// as we are opening our index.
-    fn open_segment_readers(index: &Index) -> crate::Result<Vec<SegmentReader>> {
+    ///
+    /// If a validator is set, the segments failing to open or rejected by the validator are left
+    /// out. The term dictionaries to load in memory are loaded.
+    fn open_segment_readers(
+        index: &Index,
+        segment_validator_opt: Option<&SegmentValidator>,
+        term_dictionary_accesses: &HashMap<Field, TermDictionaryAccess>,
+    ) -> crate::Result<Vec<SegmentReader>> {
         // Prevents segment files from getting deleted while we are in the process of opening them
         let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
         let searchable_segments = index.searchable_segments()?;
-        let segment_readers = searchable_segments
-            .iter()
-            .map(SegmentReader::open)
-            .collect::<crate::Result<_>>()?;
+        let mut segment_readers = Vec::with_capacity(searchable_segments.len());
+        for segment in &searchable_segments {
+            let segment_reader = match SegmentReader::open(segment) {
+                Ok(segment_reader) => segment_reader,
+                Err(err) if segment_validator_opt.is_some() => {
+                    warn!(
+                        "Excluding segment {} from the searcher, it could not be opened: {:?}",
+                        segment.id().uuid_string(),
+                        err
+                    );
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            if let Some(segment_validator) = segment_validator_opt {
+                if let Err(err) = segment_validator(&segment_reader) {
+                    warn!(
+                        "Excluding segment {} from the searcher, it failed validation: {:?}",
+                        segment.id().uuid_string(),
+                        err
+                    );
+                    continue;
+                }
+            }
+            for (&field, &term_dictionary_access) in term_dictionary_accesses {
+                if term_dictionary_access != TermDictionaryAccess::Mmap {
+                    segment_reader.inverted_index_with_access(field, term_dictionary_access)?;
+                }
+            }
+            segment_readers.push(segment_reader);
+        }
         Ok(segment_readers)
     }
 
@@ -220,8 +315,11 @@ impl InnerIndexReader {
         warming_state: &WarmingState,
         searcher_generation_counter: &Arc<AtomicU64>,
         searcher_generation_inventory: &Inventory<SearcherGeneration>,
+        segment_validator_opt: Option<&SegmentValidator>,
+        term_dictionary_accesses: &HashMap<Field, TermDictionaryAccess>,
     ) -> crate::Result<Arc<SearcherInner>> {
-        let segment_readers = Self::open_segment_readers(index)?;
+        let segment_readers =
+            Self::open_segment_readers(index, segment_validator_opt, term_dictionary_accesses)?;
         let searcher_generation = Self::track_segment_readers_in_inventory(
             &segment_readers,
             searcher_generation_counter,
@@ -248,6 +346,8 @@ impl InnerIndexReader {
             &self.warming_state,
             &self.searcher_generation_counter,
             &self.searcher_generation_inventory,
+            self.segment_validator_opt.as_deref(),
+            &self.term_dictionary_accesses,
         )?;
 
         self.searcher.store(searcher);
@@ -4,6 +4,7 @@ pub use common::DateTimePrecision;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
+use crate::schema::numeric_options::{check_custom_fast_codec, deserialize_fast_codec};
 
 /// The precision of the indexed date/time values in the inverted index.
 pub const DATE_TIME_PRECISION_INDEXED: DateTimePrecision = DateTimePrecision::Seconds;
@@ -21,6 +22,12 @@ pub struct DateOptions {
     // compression on fast fields.
     #[serde(default)]
     precision: DateTimePrecision,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_fast_codec"
+    )]
+    fast_codec: Option<u8>,
 }
 
 impl DateOptions {
@@ -89,6 +96,26 @@ impl DateOptions {
         self
     }
 
+    /// Set the field as a fast field, whose values are compressed with the custom codec
+    /// registered under the given `code`.
+    ///
+    /// See [`NumericOptions::set_fast_codec`](crate::schema::NumericOptions::set_fast_codec).
+    ///
+    /// Returns an error if `code` is below
+    /// [`FIRST_CUSTOM_CODEC_CODE`](crate::columnar::column_values::FIRST_CUSTOM_CODEC_CODE).
+    pub fn set_fast_codec(mut self, code: u8) -> crate::Result<DateOptions> {
+        check_custom_fast_codec(code)?;
+        self.fast = true;
+        self.fast_codec = Some(code);
+        Ok(self)
+    }
+
+    /// Returns the code of the custom codec compressing the fast field, if any.
+    #[inline]
+    pub fn fast_codec(&self) -> Option<u8> {
+        self.fast_codec
+    }
+
     /// Sets the precision for this DateTime field on the fast field.
     /// Indexed precision is always [`DATE_TIME_PRECISION_INDEXED`].
     ///
@@ -153,6 +180,7 @@ impl<T: Into<DateOptions>> BitOr<T> for DateOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             precision: self.precision,
+            fast_codec: self.fast_codec.or(other.fast_codec),
         }
     }
 }
@@ -176,7 +176,7 @@ pub use self::default_document::{
     CompactDocArrayIter, CompactDocObjectIter, CompactDocValue, DocParsingError, TantivyDocument,
 };
 pub use self::owned_value::OwnedValue;
-pub(crate) use self::se::BinaryDocumentSerializer;
+pub(crate) use self::se::{BinaryDocumentSerializer, BinaryValueSerializer};
 pub use self::value::{ReferenceValue, ReferenceValueLeaf, Value};
 use super::*;
 
@@ -7,12 +7,13 @@ use common::{f64_to_u64, BinarySerializable, VInt};
 
 use super::{OwnedValue, ReferenceValueLeaf};
 use crate::schema::document::{type_codes, Document, ReferenceValue, Value};
-use crate::schema::Schema;
+use crate::schema::{Field, Schema};
 
 /// A serializer writing documents which implement [`Document`] to a provided writer.
 pub struct BinaryDocumentSerializer<'se, W> {
     writer: &'se mut W,
     schema: &'se Schema,
+    skipped_fields: &'se [Field],
 }
 
 impl<'se, W> BinaryDocumentSerializer<'se, W>
@@ -20,7 +21,17 @@ where W: Write
 {
     /// Creates a new serializer with a provided writer.
     pub(crate) fn new(writer: &'se mut W, schema: &'se Schema) -> Self {
-        Self { writer, schema }
+        Self {
+            writer,
+            schema,
+            skipped_fields: &[],
+        }
+    }
+
+    /// Leaves the values of the given fields out, even if they are stored.
+    pub(crate) fn skip_fields(mut self, skipped_fields: &'se [Field]) -> Self {
+        self.skipped_fields = skipped_fields;
+        self
     }
 
     /// Attempts to serialize a given document and write the output
@@ -29,8 +40,10 @@ where W: Write
     pub(crate) fn serialize_doc<D>(&mut self, doc: &D) -> io::Result<()>
     where D: Document {
         let stored_field_values = || {
-            doc.iter_fields_and_values()
-                .filter(|(field, _)| self.schema.get_field_entry(*field).is_stored())
+            doc.iter_fields_and_values().filter(|(field, _)| {
+                self.schema.get_field_entry(*field).is_stored()
+                    && !self.skipped_fields.contains(field)
+            })
         };
         let num_field_values = stored_field_values().count();
         let mut actual_length = 0;
@@ -281,6 +281,34 @@ impl FieldType {
         }
     }
 
+    /// Returns the code of the custom codec compressing the fast field, if any.
+    ///
+    /// See [`NumericOptions::set_fast_codec`] and [`DateOptions::set_fast_codec`].
+    pub fn fast_codec(&self) -> Option<u8> {
+        match *self {
+            FieldType::U64(ref int_options)
+            | FieldType::I64(ref int_options)
+            | FieldType::F64(ref int_options)
+            | FieldType::Bool(ref int_options) => int_options.fast_codec(),
+            FieldType::Date(ref date_options) => date_options.fast_codec(),
+            _ => None,
+        }
+    }
+
+    /// Returns true iff the field is a numeric field indexed both for exact values and for
+    /// ranges.
+    ///
+    /// See [`NumericOptions::set_point_and_range_indexed`].
+    pub fn is_point_and_range_indexed(&self) -> bool {
+        match *self {
+            FieldType::U64(ref int_options)
+            | FieldType::I64(ref int_options)
+            | FieldType::F64(ref int_options)
+            | FieldType::Bool(ref int_options) => int_options.is_point_and_range_indexed(),
+            _ => false,
+        }
+    }
+
     /// returns true if the field is normed (see [fieldnorms](crate::fieldnorm)).
     pub fn has_fieldnorms(&self) -> bool {
         match *self {
@@ -1,9 +1,11 @@
 use std::ops::BitOr;
 
-use serde::{Deserialize, Serialize};
+use columnar::column_values::FIRST_CUSTOM_CODEC_CODE;
+use serde::{Deserialize, Deserializer, Serialize};
 
 use super::flags::CoerceFlag;
 use crate::schema::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
+use crate::TantivyError;
 
 /// Define how an `u64`, `i64`, or `f64` field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -16,6 +18,10 @@ pub struct NumericOptions {
     stored: bool,
     #[serde(skip_serializing_if = "is_false")]
     coerce: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fast_codec: Option<u8>,
+    #[serde(skip_serializing_if = "is_false")]
+    point_and_range_indexed: bool,
 }
 
 fn is_false(val: &bool) -> bool {
@@ -37,6 +43,37 @@ struct NumericOptionsDeser {
     stored: bool,
     #[serde(default)]
     coerce: bool,
+    #[serde(default, deserialize_with = "deserialize_fast_codec")]
+    fast_codec: Option<u8>,
+    #[serde(default)]
+    point_and_range_indexed: bool,
+}
+
+/// Returns an error if `code` is not in the range reserved for custom fast field codecs.
+pub(crate) fn check_custom_fast_codec(code: u8) -> crate::Result<()> {
+    if code < FIRST_CUSTOM_CODEC_CODE {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Fast field codec code {code} is reserved for built-in codecs, custom codecs use \
+             codes >= {FIRST_CUSTOM_CODEC_CODE}."
+        )));
+    }
+    Ok(())
+}
+
+/// Deserializes an optional custom fast field codec code, rejecting the codes reserved for
+/// built-in codecs.
+pub(crate) fn deserialize_fast_codec<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
+where D: Deserializer<'de> {
+    let fast_codec = Option::<u8>::deserialize(deserializer)?;
+    if let Some(code) = fast_codec {
+        if code < FIRST_CUSTOM_CODEC_CODE {
+            return Err(serde::de::Error::custom(format!(
+                "fast field codec code {code} is reserved for built-in codecs, custom codecs use \
+                 codes >= {FIRST_CUSTOM_CODEC_CODE}"
+            )));
+        }
+    }
+    Ok(fast_codec)
 }
 
 impl From<NumericOptionsDeser> for NumericOptions {
@@ -47,6 +84,8 @@ impl From<NumericOptionsDeser> for NumericOptions {
             fast: deser.fast,
             stored: deser.stored,
             coerce: deser.coerce,
+            fast_codec: deser.fast_codec,
+            point_and_range_indexed: deser.point_and_range_indexed,
         }
     }
 }
@@ -129,6 +168,58 @@ impl NumericOptions {
         self.fast = true;
         self
     }
+
+    /// Set the field as a fast field, whose values are compressed with the custom codec
+    /// registered under the given `code`.
+    ///
+    /// The codec has to be registered in the
+    /// [fast field codecs](crate::Index::fast_field_codecs) of the index before indexing
+    /// documents, merging segments or opening the fast fields.
+    ///
+    /// Returns an error if `code` is below
+    /// [`FIRST_CUSTOM_CODEC_CODE`](crate::columnar::column_values::FIRST_CUSTOM_CODEC_CODE),
+    /// as the codes below are reserved for the built-in codecs.
+    pub fn set_fast_codec(mut self, code: u8) -> crate::Result<NumericOptions> {
+        check_custom_fast_codec(code)?;
+        self.fast = true;
+        self.fast_codec = Some(code);
+        Ok(self)
+    }
+
+    /// Returns the code of the custom codec compressing the fast field, if any.
+    #[inline]
+    pub fn fast_codec(&self) -> Option<u8> {
+        self.fast_codec
+    }
+
+    /// Set the field as indexed both for exact values and for ranges.
+    ///
+    /// The field is set as indexed and fast:
+    /// - the inverted index holds one posting list per distinct value, making
+    ///   [`TermQuery`](crate::query::TermQuery) and single value ranges fast.
+    /// - the fast field holds a bitpacked column of the values, on which other
+    ///   [`RangeQuery`](crate::query::RangeQuery)s run without having to union the posting lists of
+    ///   all of the values within the range.
+    ///
+    /// On top of that, a [`RangeQuery`](crate::query::RangeQuery) matching a single value runs on
+    /// the inverted index instead of the fast field. A field which is merely indexed and fast
+    /// keeps running all of its ranges on the fast field.
+    ///
+    /// The space usage is the sum of both structures: on top of the fast field data, the term
+    /// dictionary and postings take roughly one posting list entry per document, plus one term
+    /// dictionary entry per distinct value.
+    #[must_use]
+    pub fn set_point_and_range_indexed(mut self) -> NumericOptions {
+        self.point_and_range_indexed = true;
+        self.set_indexed().set_fast()
+    }
+
+    /// Returns true iff the field is indexed both for exact values and for ranges, see
+    /// [`NumericOptions::set_point_and_range_indexed`].
+    #[inline]
+    pub fn is_point_and_range_indexed(&self) -> bool {
+        self.point_and_range_indexed
+    }
 }
 
 impl From<()> for NumericOptions {
@@ -145,6 +236,8 @@ impl From<CoerceFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: true,
+            fast_codec: None,
+            point_and_range_indexed: false,
         }
     }
 }
@@ -157,6 +250,8 @@ impl From<FastFlag> for NumericOptions {
             stored: false,
             fast: true,
             coerce: false,
+            fast_codec: None,
+            point_and_range_indexed: false,
         }
     }
 }
@@ -169,6 +264,8 @@ impl From<StoredFlag> for NumericOptions {
             stored: true,
             fast: false,
             coerce: false,
+            fast_codec: None,
+            point_and_range_indexed: false,
         }
     }
 }
@@ -181,6 +278,8 @@ impl From<IndexedFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: false,
+            fast_codec: None,
+            point_and_range_indexed: false,
         }
     }
 }
@@ -196,6 +295,8 @@ impl<T: Into<NumericOptions>> BitOr<T> for NumericOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            fast_codec: self.fast_codec.or(other.fast_codec),
+            point_and_range_indexed: self.point_and_range_indexed | other.point_and_range_indexed,
         }
     }
 }
@@ -230,6 +331,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                point_and_range_indexed: false,
             }
         );
     }
@@ -249,6 +352,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                point_and_range_indexed: false,
             }
         );
     }
@@ -269,6 +374,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                point_and_range_indexed: false,
             }
         );
     }
@@ -290,6 +397,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_codec: None,
+                point_and_range_indexed: false,
             }
         );
     }
@@ -312,7 +421,38 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: true,
+                fast_codec: None,
+                point_and_range_indexed: false,
             }
         );
     }
+
+    #[test]
+    fn test_int_options_fast_codec_reserved_code() {
+        let err = NumericOptions::default().set_fast_codec(2).unwrap_err();
+        assert!(matches!(err, crate::TantivyError::InvalidArgument(_)));
+        assert!(err.to_string().contains("reserved for built-in codecs"));
+    }
+
+    #[test]
+    fn test_int_options_deser_fast_codec_reserved_code() {
+        let json = r#"{
+            "indexed": false,
+            "fieldnorms": false,
+            "stored": false,
+            "fast": true,
+            "fast_codec": 2
+        }"#;
+        let err = serde_json::from_str::<NumericOptions>(json).unwrap_err();
+        assert!(err.to_string().contains("reserved for built-in codecs"));
+        let json = r#"{
+            "indexed": false,
+            "fieldnorms": false,
+            "stored": false,
+            "fast": true,
+            "fast_codec": 200
+        }"#;
+        let int_options: NumericOptions = serde_json::from_str(json).unwrap();
+        assert_eq!(int_options.fast_codec(), Some(200));
+    }
 }
@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::sync::Arc;
 
-use serde::de::{SeqAccess, Visitor};
-use serde::ser::SerializeSeq;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::*;
@@ -33,6 +33,7 @@ use crate::TantivyError;
 pub struct SchemaBuilder {
     fields: Vec<FieldEntry>,
     fields_map: HashMap<String, Field>,
+    aliases: BTreeMap<String, Field>,
 }
 
 impl SchemaBuilder {
@@ -205,12 +206,39 @@ impl SchemaBuilder {
         field
     }
 
+    /// Adds an alias for a field.
+    ///
+    /// Aliases only affect name resolution: [`Schema::get_field`], [`Schema::find_field`],
+    /// and hence the `QueryParser` and the parsing of documents, resolve an alias to its
+    /// canonical field. They do not affect storage.
+    ///
+    /// Several aliases can target the same field.
+    ///
+    /// Returns an error if the name is already used by a field or by another alias,
+    /// or if the field does not belong to the schema in build.
+    pub fn add_alias(&mut self, alias: &str, field: Field) -> crate::Result<()> {
+        if field.field_id() as usize >= self.fields.len() {
+            return Err(TantivyError::SchemaError(format!(
+                "Cannot add alias {alias:?}: unknown field {field:?}"
+            )));
+        }
+        if self.fields_map.contains_key(alias) {
+            return Err(TantivyError::SchemaError(format!(
+                "Cannot add alias {alias:?}: the name is already used by a field or an alias"
+            )));
+        }
+        self.fields_map.insert(alias.to_string(), field);
+        self.aliases.insert(alias.to_string(), field);
+        Ok(())
+    }
+
     /// Finalize the creation of a `Schema`
     /// This will consume your `SchemaBuilder`
     pub fn build(self) -> Schema {
         Schema(Arc::new(InnerSchema {
             fields: self.fields,
             fields_map: self.fields_map,
+            aliases: self.aliases,
         }))
     }
 }
@@ -218,11 +246,12 @@ impl SchemaBuilder {
 struct InnerSchema {
     fields: Vec<FieldEntry>,
     fields_map: HashMap<String, Field>, // transient
+    aliases: BTreeMap<String, Field>,
 }
 
 impl PartialEq for InnerSchema {
     fn eq(&self, other: &InnerSchema) -> bool {
-        self.fields == other.fields
+        self.fields == other.fields && self.aliases == other.aliases
     }
 }
 
@@ -301,12 +330,22 @@ impl Schema {
             .map(|(field_id, field_entry)| (Field::from_field_id(field_id as u32), field_entry))
     }
 
+    /// Returns the aliases of the schema, together with the field they target.
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, Field)> {
+        self.0
+            .aliases
+            .iter()
+            .map(|(alias, field)| (alias.as_str(), *field))
+    }
+
     /// Creates a new builder.
     pub fn builder() -> SchemaBuilder {
         SchemaBuilder::default()
     }
 
     /// Returns the field option associated with a given name.
+    ///
+    /// The name can either be the name of the field or one of its aliases.
     pub fn get_field(&self, field_name: &str) -> crate::Result<Field> {
         self.0
             .fields_map
@@ -315,6 +354,26 @@ impl Schema {
             .ok_or_else(|| TantivyError::FieldNotFound(field_name.to_string()))
     }
 
+    /// Returns the field associated with a given name, or `default` if there is none.
+    ///
+    /// Like [`Schema::get_field`], the name can either be the name of the field or one of its
+    /// aliases.
+    pub fn get_field_or_default(&self, field_name: &str, default: Field) -> Field {
+        self.0
+            .fields_map
+            .get(field_name)
+            .copied()
+            .unwrap_or(default)
+    }
+
+    /// Returns the map from the names of the fields and of their aliases to the fields.
+    ///
+    /// The map is built once with the schema. It is the one used by [`Schema::get_field`], and
+    /// can be used directly to resolve many names without handling an error for each of them.
+    pub fn field_index(&self) -> &HashMap<String, Field> {
+        &self.0.fields_map
+    }
+
     /// Searches for a full_path in the schema, returning the field name and a JSON path.
     ///
     /// This function works by checking if the field exists for the exact given full_path.
@@ -370,14 +429,36 @@ impl Schema {
     }
 }
 
+struct SerializableAliases<'a>(&'a Schema);
+
+impl Serialize for SerializableAliases<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let mut map = serializer.serialize_map(Some(self.0 .0.aliases.len()))?;
+        for (alias, field) in self.0.aliases() {
+            map.serialize_entry(alias, self.0.get_field_name(field))?;
+        }
+        map.end()
+    }
+}
+
 impl Serialize for Schema {
+    /// A schema without aliases is serialized as the list of its field entries.
+    /// Aliases, if any, require the schema to be serialized as an object with a `fields` and
+    /// an `aliases` key.
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: Serializer {
-        let mut seq = serializer.serialize_seq(Some(self.0.fields.len()))?;
-        for e in &self.0.fields {
-            seq.serialize_element(e)?;
+        if self.0.aliases.is_empty() {
+            let mut seq = serializer.serialize_seq(Some(self.0.fields.len()))?;
+            for e in &self.0.fields {
+                seq.serialize_element(e)?;
+            }
+            return seq.end();
         }
-        seq.end()
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("fields", &self.0.fields)?;
+        map.serialize_entry("aliases", &SerializableAliases(self))?;
+        map.end()
     }
 }
 
@@ -398,6 +479,7 @@ impl<'de> Deserialize<'de> for Schema {
                 let mut schema = SchemaBuilder {
                     fields: Vec::with_capacity(seq.size_hint().unwrap_or(0)),
                     fields_map: HashMap::with_capacity(seq.size_hint().unwrap_or(0)),
+                    aliases: BTreeMap::new(),
                 };
 
                 while let Some(value) = seq.next_element()? {
@@ -406,9 +488,36 @@ impl<'de> Deserialize<'de> for Schema {
 
                 Ok(schema.build())
             }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where A: MapAccess<'de> {
+                let mut fields: Option<Vec<FieldEntry>> = None;
+                let mut aliases: BTreeMap<String, String> = BTreeMap::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "fields" => fields = Some(map.next_value()?),
+                        "aliases" => aliases = map.next_value()?,
+                        _ => return Err(de::Error::unknown_field(&key, &["fields", "aliases"])),
+                    }
+                }
+                let fields = fields.ok_or_else(|| de::Error::missing_field("fields"))?;
+                let mut schema = SchemaBuilder::new();
+                for field_entry in fields {
+                    schema.add_field(field_entry);
+                }
+                for (alias, field_name) in aliases {
+                    let field = *schema.fields_map.get(&field_name).ok_or_else(|| {
+                        de::Error::custom(format!(
+                            "alias {alias:?} targets the unknown field {field_name:?}"
+                        ))
+                    })?;
+                    schema.add_alias(&alias, field).map_err(de::Error::custom)?;
+                }
+                Ok(schema.build())
+            }
         }
 
-        deserializer.deserialize_seq(SchemaVisitor)
+        deserializer.deserialize_any(SchemaVisitor)
     }
 }
 
@@ -423,6 +532,7 @@ mod tests {
     use crate::schema::field_type::ValueParsingError;
     use crate::schema::schema::DocParsingError::InvalidJson;
     use crate::schema::*;
+    use crate::TantivyError;
 
     #[test]
     fn test_locate_splitting_dots() {
@@ -1020,4 +1130,69 @@ mod tests {
             Some((default, "foobar"))
         );
     }
+
+    #[test]
+    fn test_schema_aliases() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_alias("headline", title).unwrap();
+        schema_builder.add_alias("name", title).unwrap();
+        assert!(matches!(
+            schema_builder.add_alias("body", title),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            schema_builder.add_alias("name", body),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            schema_builder.add_alias("other", Field::from_field_id(2)),
+            Err(TantivyError::SchemaError(_))
+        ));
+        let schema = schema_builder.build();
+        assert_eq!(schema.num_fields(), 2);
+        assert_eq!(schema.get_field("headline").unwrap(), title);
+        assert_eq!(schema.get_field("name").unwrap(), title);
+        assert_eq!(schema.get_field_name(title), "title");
+        assert_eq!(
+            schema.aliases().collect::<Vec<_>>(),
+            vec![("headline", title), ("name", title)]
+        );
+
+        let doc = TantivyDocument::parse_json(&schema, r#"{"headline": "my title"}"#).unwrap();
+        assert_eq!(doc.get_first(title).unwrap().as_str(), Some("my title"));
+        assert_eq!(doc.to_json(&schema), r#"{"title":["my title"]}"#);
+
+        let schema_json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(
+            schema_json["aliases"],
+            serde_json::json!({"headline": "title", "name": "title"})
+        );
+        let deser_schema: Schema = serde_json::from_value(schema_json).unwrap();
+        assert_eq!(deser_schema, schema);
+        assert_eq!(deser_schema.get_field("headline").unwrap(), title);
+    }
+
+    #[test]
+    fn test_schema_field_index() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_alias("headline", title).unwrap();
+        let schema = schema_builder.build();
+        let field_index = schema.field_index();
+        assert_eq!(field_index.len(), 3);
+        for (name, field) in field_index {
+            assert_eq!(schema.get_field(name).unwrap(), *field);
+            assert_eq!(schema.get_field_or_default(name, body), *field);
+        }
+        assert_eq!(field_index.get("headline"), Some(&title));
+        assert!(field_index.get("missing").is_none());
+        assert_eq!(schema.get_field_or_default("missing", body), body);
+
+        let deser_schema: Schema =
+            serde_json::from_value(serde_json::to_value(&schema).unwrap()).unwrap();
+        assert_eq!(deser_schema.field_index(), field_index);
+    }
 }
@@ -4,8 +4,10 @@ use std::ops::BitOr;
 use serde::{Deserialize, Serialize};
 
 use super::flags::{CoerceFlag, FastFlag};
+use crate::fieldnorm::FieldNormPrecision;
 use crate::schema::flags::{SchemaFlagList, StoredFlag};
 use crate::schema::IndexRecordOption;
+use crate::store::Compressor;
 
 /// Define how a text field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -194,6 +196,8 @@ impl TokenizerName {
 /// - The name of the `Tokenizer` that should be used to process the field.
 /// - Flag indicating, if fieldnorms should be stored (See [fieldnorm](crate::fieldnorm)). Defaults
 ///   to `true`.
+/// - The precision of the fieldnorms (See [`FieldNormPrecision`]). Defaults to
+///   [`FieldNormPrecision::Quantized`].
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     #[serde(default)]
@@ -201,19 +205,35 @@ pub struct TextFieldIndexing {
     #[serde(default = "default_fieldnorms")]
     fieldnorms: bool,
     #[serde(default)]
+    #[serde(skip_serializing_if = "FieldNormPrecision::is_default")]
+    fieldnorm_precision: FieldNormPrecision,
+    #[serde(default)]
     tokenizer: TokenizerName,
+    #[serde(default = "default_term_dict_compressor")]
+    #[serde(skip_serializing_if = "is_uncompressed")]
+    term_dict_compressor: Compressor,
 }
 
 pub(crate) fn default_fieldnorms() -> bool {
     true
 }
 
+fn default_term_dict_compressor() -> Compressor {
+    Compressor::None
+}
+
+fn is_uncompressed(compressor: &Compressor) -> bool {
+    *compressor == Compressor::None
+}
+
 impl Default for TextFieldIndexing {
     fn default() -> TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: TokenizerName::default(),
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            fieldnorm_precision: FieldNormPrecision::default(),
+            term_dict_compressor: default_term_dict_compressor(),
         }
     }
 }
@@ -243,6 +263,23 @@ impl TextFieldIndexing {
         self.fieldnorms
     }
 
+    /// Sets the precision of the [fieldnorms](crate::fieldnorm) of the field.
+    ///
+    /// See [`FieldNormPrecision`] for more detail.
+    #[must_use]
+    pub fn set_fieldnorm_precision(
+        mut self,
+        fieldnorm_precision: FieldNormPrecision,
+    ) -> TextFieldIndexing {
+        self.fieldnorm_precision = fieldnorm_precision;
+        self
+    }
+
+    /// Returns the precision of the [fieldnorms](crate::fieldnorm) of the field.
+    pub fn fieldnorm_precision(&self) -> FieldNormPrecision {
+        self.fieldnorm_precision
+    }
+
     /// Sets which information should be indexed with the tokens.
     ///
     /// See [`IndexRecordOption`] for more detail.
@@ -258,6 +295,26 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Sets the compressor applied to the term dictionary of the field.
+    ///
+    /// Defaults to [`Compressor::None`].
+    ///
+    /// Fields with very regular terms, such as identifiers sharing a common layout,
+    /// can have their term dictionary shrink significantly.
+    /// The dictionary is compressed in independent blocks, which its lookups decompress as
+    /// they read them. The tradeoff is that lookups are slower, in particular when they walk
+    /// many terms.
+    #[must_use]
+    pub fn set_term_dict_compressor(mut self, compressor: Compressor) -> TextFieldIndexing {
+        self.term_dict_compressor = compressor;
+        self
+    }
+
+    /// Returns the compressor applied to the term dictionary of the field.
+    pub fn term_dict_compressor(&self) -> Compressor {
+        self.term_dict_compressor
+    }
 }
 
 /// The field will be untokenized and indexed.
@@ -265,7 +322,9 @@ pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
         fieldnorms: true,
+        fieldnorm_precision: FieldNormPrecision::Quantized,
         record: IndexRecordOption::Basic,
+        term_dict_compressor: Compressor::None,
     }),
     stored: false,
     fast: FastFieldTextOptions::IsEnabled(false),
@@ -277,7 +336,9 @@ pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
         fieldnorms: true,
+        fieldnorm_precision: FieldNormPrecision::Quantized,
         record: IndexRecordOption::WithFreqsAndPositions,
+        term_dict_compressor: Compressor::None,
     }),
     stored: false,
     coerce: false,
@@ -63,6 +63,8 @@ impl SearcherSpaceUsage {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SegmentSpaceUsage {
     num_docs: u32,
+    #[serde(default)]
+    num_deleted_docs: u32,
 
     termdict: PerFieldSpaceUsage,
     postings: PerFieldSpaceUsage,
@@ -72,6 +74,11 @@ pub struct SegmentSpaceUsage {
 
     store: StoreSpaceUsage,
 
+    #[serde(default)]
+    source: ByteCount,
+    #[serde(default)]
+    fieldnorm_overlay: ByteCount,
+
     deletes: ByteCount,
 
     total: ByteCount,
@@ -81,12 +88,15 @@ impl SegmentSpaceUsage {
     #[expect(clippy::too_many_arguments)]
     pub(crate) fn new(
         num_docs: u32,
+        num_deleted_docs: u32,
         termdict: PerFieldSpaceUsage,
         postings: PerFieldSpaceUsage,
         positions: PerFieldSpaceUsage,
         fast_fields: PerFieldSpaceUsage,
         fieldnorms: PerFieldSpaceUsage,
         store: StoreSpaceUsage,
+        source: ByteCount,
+        fieldnorm_overlay: ByteCount,
         deletes: ByteCount,
     ) -> SegmentSpaceUsage {
         let total = termdict.total()
@@ -95,15 +105,20 @@ impl SegmentSpaceUsage {
             + fast_fields.total()
             + fieldnorms.total()
             + store.total()
+            + source
+            + fieldnorm_overlay
             + deletes;
         SegmentSpaceUsage {
             num_docs,
+            num_deleted_docs,
             termdict,
             postings,
             positions,
             fast_fields,
             fieldnorms,
             store,
+            source,
+            fieldnorm_overlay,
             deletes,
             total,
         }
@@ -124,7 +139,9 @@ impl SegmentSpaceUsage {
             Terms => PerField(self.termdict().clone()),
             SegmentComponent::Store => ComponentSpaceUsage::Store(self.store().clone()),
             SegmentComponent::TempStore => ComponentSpaceUsage::Store(self.store().clone()),
+            Source => Basic(self.source()),
             Delete => Basic(self.deletes()),
+            FieldNormOverlay => Basic(self.fieldnorm_overlay()),
         }
     }
 
@@ -133,6 +150,29 @@ impl SegmentSpaceUsage {
         self.num_docs
     }
 
+    /// Number of deleted docs in segment
+    pub fn num_deleted_docs(&self) -> u32 {
+        self.num_deleted_docs
+    }
+
+    /// Estimates the space taken by the deleted documents, which a merge of the segment would
+    /// reclaim.
+    ///
+    /// This is only an estimate: the size of all of the components but the deletes is
+    /// multiplied by the share of deleted documents, as if every document took the same space.
+    /// The actual reclaimed space also depends on the terms which only appear in the deleted
+    /// documents, and on the compression of the merged segment.
+    pub fn reclaimable_bytes(&self) -> ByteCount {
+        let max_doc = u64::from(self.num_docs) + u64::from(self.num_deleted_docs);
+        if max_doc == 0 {
+            return ByteCount::default();
+        }
+        let num_bytes = self.total.get_bytes() - self.deletes.get_bytes();
+        let reclaimable_bytes =
+            u128::from(num_bytes) * u128::from(self.num_deleted_docs) / u128::from(max_doc);
+        ByteCount::from(reclaimable_bytes as u64)
+    }
+
     /// Space usage for term dictionary
     pub fn termdict(&self) -> &PerFieldSpaceUsage {
         &self.termdict
@@ -163,6 +203,19 @@ impl SegmentSpaceUsage {
         &self.store
     }
 
+    /// Space usage for the raw source of the documents
+    ///
+    /// See [`IndexSettings::store_source`](crate::IndexSettings::store_source).
+    pub fn source(&self) -> ByteCount {
+        self.source
+    }
+
+    /// Space usage for the fieldnorm overlay, see
+    /// [`FieldNormOverlay`](crate::fieldnorm::FieldNormOverlay).
+    pub fn fieldnorm_overlay(&self) -> ByteCount {
+        self.fieldnorm_overlay
+    }
+
     /// Space usage for document deletions
     pub fn deletes(&self) -> ByteCount {
         self.deletes
@@ -174,6 +227,54 @@ impl SegmentSpaceUsage {
     }
 }
 
+/// Estimates the change of disk footprint a merge of some segments would produce.
+///
+/// See [`IndexWriter::merge_space_delta`](crate::IndexWriter::merge_space_delta).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeSpaceDelta {
+    before: ByteCount,
+    after_estimate: ByteCount,
+}
+
+impl MergeSpaceDelta {
+    /// Estimates the change of disk footprint of merging the segments of `segments`.
+    ///
+    /// The merged segment is estimated segment by segment, like
+    /// [`SegmentSpaceUsage::reclaimable_bytes`] does: the size of all of the components but
+    /// the deletes is multiplied by the share of alive documents, and the deletes are dropped.
+    pub fn estimate(segments: &[SegmentSpaceUsage]) -> MergeSpaceDelta {
+        let before = segments.iter().map(SegmentSpaceUsage::total).sum();
+        let after_estimate = segments
+            .iter()
+            .map(|segment| {
+                let num_bytes = segment.total().get_bytes()
+                    - segment.deletes().get_bytes()
+                    - segment.reclaimable_bytes().get_bytes();
+                ByteCount::from(num_bytes)
+            })
+            .sum();
+        MergeSpaceDelta {
+            before,
+            after_estimate,
+        }
+    }
+
+    /// Total space usage of the segments to merge, exact to the byte.
+    pub fn before(&self) -> ByteCount {
+        self.before
+    }
+
+    /// Estimated space usage of the merged segment.
+    pub fn after_estimate(&self) -> ByteCount {
+        self.after_estimate
+    }
+
+    /// Estimated space reclaimed by the merge, i.e. `before - after_estimate`.
+    pub fn reclaimed_estimate(&self) -> ByteCount {
+        ByteCount::from(self.before.get_bytes() - self.after_estimate.get_bytes())
+    }
+}
+
 /// Represents space usage for the Store for this segment.
 ///
 /// This is composed of two parts.
@@ -480,6 +581,91 @@ mod test {
         assert_eq!(segment_space_usage.fast_fields().total(), 0u64);
         expect_single_field(segment_space_usage.fieldnorms(), &name, 1, 512);
         assert!(segment_space_usage.deletes() > 0);
+        assert_eq!(segment_space_usage.num_deleted_docs(), 2);
+        // Half of the documents are deleted.
+        let num_bytes =
+            segment_space_usage.total().get_bytes() - segment_space_usage.deletes().get_bytes();
+        assert_eq!(segment_space_usage.reclaimable_bytes(), num_bytes / 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reclaimable_bytes_without_deletes() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_u64_field("name", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(name => 1u64))?;
+        index_writer.add_document(doc!(name => 2u64))?;
+        index_writer.add_document(doc!(name => 3u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_space_usage = searcher.space_usage()?.segments()[0].clone();
+        assert_eq!(segment_space_usage.num_deleted_docs(), 0);
+        assert_eq!(segment_space_usage.reclaimable_bytes(), 0u64);
+
+        index_writer.delete_term(Term::from_field_u64(name, 1u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_space_usage = searcher.space_usage()?.segments()[0].clone();
+        assert_eq!(segment_space_usage.num_deleted_docs(), 1);
+        let num_bytes =
+            segment_space_usage.total().get_bytes() - segment_space_usage.deletes().get_bytes();
+        assert_eq!(segment_space_usage.reclaimable_bytes(), num_bytes / 3);
+        Ok(())
+    }
+
+    #[cfg(feature = "lz4-compression")]
+    #[test]
+    fn test_term_dict_compressor() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::query::TermQuery;
+        use crate::schema::{IndexRecordOption, TextFieldIndexing, TextOptions, STRING};
+        use crate::store::Compressor;
+
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let compressed_id = schema_builder.add_text_field(
+            "compressed_id",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("raw")
+                    .set_term_dict_compressor(Compressor::Lz4),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for i in 0..10_000 {
+                let order_id = format!("order-2024-05-{:02}-{i:08}", i % 28 + 1);
+                index_writer
+                    .add_document(doc!(id => order_id.clone(), compressed_id => order_id))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let segment_space_usage = searcher.space_usage()?.segments()[0].clone();
+        let termdict_usage = |field: Field| {
+            segment_space_usage
+                .termdict()
+                .fields()
+                .find(|(termdict_field, _)| **termdict_field == field)
+                .map(|(_, field_usage)| field_usage.total())
+                .unwrap()
+        };
+        let plain_num_bytes = termdict_usage(id);
+        let compressed_num_bytes = termdict_usage(compressed_id);
+        assert!(
+            compressed_num_bytes.get_bytes() * 2 < plain_num_bytes.get_bytes(),
+            "compressed: {compressed_num_bytes}, plain: {plain_num_bytes}"
+        );
+
+        // The compressed dictionary can still be queried.
+        let term_query = TermQuery::new(
+            Term::from_field_text(compressed_id, "order-2024-05-05-00000004"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&term_query, &Count)?, 1);
         Ok(())
     }
 }
@@ -0,0 +1,91 @@
+use columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+use common::{BinarySerializable, OwnedBytes, VInt};
+
+use crate::fastfield::FastFieldReaders;
+use crate::schema::document::{
+    BinaryValueSerializer, OwnedValue, ReferenceValue, ReferenceValueLeaf,
+};
+use crate::schema::{Field, FieldEntry, Schema, Type};
+use crate::DocId;
+
+/// Returns true if the values of the field are left out of the doc store by
+/// [`IndexSettings::docstore_skip_fast_fields`](crate::IndexSettings::docstore_skip_fast_fields),
+/// and read back from its fast field column instead.
+pub(crate) fn is_read_from_column(field_entry: &FieldEntry) -> bool {
+    field_entry.is_stored()
+        && field_entry.is_fast()
+        && matches!(
+            field_entry.field_type().value_type(),
+            Type::U64 | Type::I64 | Type::F64 | Type::Bool
+        )
+}
+
+/// Reads back the stored values left out of the doc store from the fast field columns of a
+/// segment.
+pub(crate) struct ColumnarStoredFields {
+    columns: Vec<(Field, Column<u64>, ColumnType)>,
+}
+
+impl ColumnarStoredFields {
+    pub(crate) fn open(
+        schema: &Schema,
+        fast_fields: &FastFieldReaders,
+    ) -> crate::Result<ColumnarStoredFields> {
+        let mut columns = Vec::new();
+        for (field, field_entry) in schema.fields() {
+            if !is_read_from_column(field_entry) {
+                continue;
+            }
+            let column_type = match field_entry.field_type().value_type() {
+                Type::U64 => ColumnType::U64,
+                Type::I64 => ColumnType::I64,
+                Type::F64 => ColumnType::F64,
+                _ => ColumnType::Bool,
+            };
+            // A segment without any value has no column.
+            if let Some((column, column_type)) =
+                fast_fields.u64_lenient_for_type(Some(&[column_type]), field_entry.name())?
+            {
+                columns.push((field, column, column_type));
+            }
+        }
+        Ok(ColumnarStoredFields { columns })
+    }
+
+    /// Appends the column values of the document to its serialized stored fields.
+    ///
+    /// The doc store of a segment written with `docstore_skip_fast_fields` holds no value for
+    /// these fields, so that the stored values are not read: only their count is updated.
+    pub(crate) fn add_column_values(
+        &self,
+        doc_id: DocId,
+        doc_bytes: OwnedBytes,
+    ) -> crate::Result<OwnedBytes> {
+        let mut column_values: Vec<u8> = Vec::new();
+        let mut num_column_values = 0;
+        for (field, column, column_type) in &self.columns {
+            for value in column.values_for_doc(doc_id) {
+                let leaf: ReferenceValueLeaf = match column_type {
+                    ColumnType::I64 => ReferenceValueLeaf::I64(i64::from_u64(value)),
+                    ColumnType::F64 => ReferenceValueLeaf::F64(f64::from_u64(value)),
+                    ColumnType::Bool => ReferenceValueLeaf::Bool(bool::from_u64(value)),
+                    _ => ReferenceValueLeaf::U64(value),
+                };
+                field.serialize(&mut column_values)?;
+                BinaryValueSerializer::new(&mut column_values)
+                    .serialize_value(ReferenceValue::Leaf::<&OwnedValue>(leaf))?;
+                num_column_values += 1;
+            }
+        }
+        if num_column_values == 0 {
+            return Ok(doc_bytes);
+        }
+        let mut stored_values = doc_bytes.as_slice();
+        let num_stored_values = VInt::deserialize(&mut stored_values)?.val();
+        let mut patched_doc_bytes = Vec::with_capacity(doc_bytes.len() + column_values.len() + 1);
+        VInt(num_stored_values + num_column_values).serialize(&mut patched_doc_bytes)?;
+        patched_doc_bytes.extend_from_slice(stored_values);
+        patched_doc_bytes.extend_from_slice(&column_values);
+        Ok(OwnedBytes::new(patched_doc_bytes))
+    }
+}
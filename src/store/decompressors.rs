@@ -31,13 +31,22 @@ impl From<Compressor> for Decompressor {
 
 impl Decompressor {
     pub(crate) fn from_id(id: u8) -> Decompressor {
+        Self::try_from_id(id).unwrap_or_else(|_| panic!("unknown compressor id {id:?}"))
+    }
+
+    /// Returns the decompressor with the given id, or an `InvalidData` error if the id is
+    /// unknown, or its compression feature is not enabled.
+    pub(crate) fn try_from_id(id: u8) -> io::Result<Decompressor> {
         match id {
-            0 => Decompressor::None,
+            0 => Ok(Decompressor::None),
             #[cfg(feature = "lz4-compression")]
-            1 => Decompressor::Lz4,
+            1 => Ok(Decompressor::Lz4),
             #[cfg(feature = "zstd-compression")]
-            4 => Decompressor::Zstd,
-            _ => panic!("unknown compressor id {id:?}"),
+            4 => Ok(Decompressor::Zstd),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown compressor id {id:?}"),
+            )),
         }
     }
 
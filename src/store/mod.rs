@@ -29,6 +29,7 @@
 //!   method](../struct.SegmentReader.html#method.doc)
 //! - at the index level, the [`Searcher::doc()`](crate::Searcher::doc) method
 
+mod columnar_fields;
 mod compressors;
 mod decompressors;
 mod footer;
@@ -36,6 +37,7 @@ mod index;
 mod reader;
 mod writer;
 
+pub(crate) use self::columnar_fields::{is_read_from_column, ColumnarStoredFields};
 pub use self::compressors::{Compressor, ZstdCompressor};
 pub use self::decompressors::Decompressor;
 pub use self::reader::{CacheStats, StoreReader};
@@ -356,6 +358,120 @@ pub(crate) mod tests {
         assert_eq!(store.block_checkpoints().count(), 1);
         Ok(())
     }
+
+    #[test]
+    fn test_docstore_skip_fast_fields() -> crate::Result<()> {
+        use crate::collector::TopDocs;
+        use crate::indexer::NoMergePolicy;
+        use crate::query::TermQuery;
+        use crate::schema::{IndexRecordOption, OwnedValue, FAST, INDEXED};
+        use crate::{DateTime, IndexSettings};
+
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | STORED | FAST);
+        let price = schema_builder.add_f64_field("price", STORED | FAST);
+        let in_stock = schema_builder.add_bool_field("in_stock", STORED | FAST);
+        let ratings = schema_builder.add_i64_field("ratings", STORED | FAST);
+        let released = schema_builder.add_date_field("released", STORED | FAST);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let schema = schema_builder.build();
+        let create_index = |docstore_skip_fast_fields: bool| -> crate::Result<Index> {
+            let index = Index::builder()
+                .schema(schema.clone())
+                .settings(IndexSettings {
+                    docstore_compression: Compressor::None,
+                    docstore_skip_fast_fields,
+                    ..Default::default()
+                })
+                .create_in_ram()?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for i in 0..20u64 {
+                let mut doc = TantivyDocument::default();
+                doc.add_u64(id, i);
+                doc.add_text(title, format!("doc {i}"));
+                doc.add_date(released, DateTime::from_timestamp_nanos(i as i64 + 1));
+                if i % 2 == 0 {
+                    doc.add_f64(price, i as f64 * 1.5 - 3.0);
+                    doc.add_bool(in_stock, i % 4 == 0);
+                }
+                for rating in 0..i % 3 {
+                    doc.add_i64(ratings, -(rating as i64));
+                }
+                index_writer.add_document(doc)?;
+                if i == 9 {
+                    index_writer.commit()?;
+                }
+            }
+            index_writer.commit()?;
+            Ok(index)
+        };
+        let index = create_index(false)?;
+        let index_skipping = create_index(true)?;
+
+        // Each left out value saves its field, its type code and its 8-byte or 1-byte value.
+        let num_numerical_values: u64 = 20 + 10 + (0..20).map(|i| i % 3).sum::<u64>();
+        let num_bool_values: u64 = 10;
+        let store_size = |index: &Index| -> crate::Result<u64> {
+            let space_usage = index.reader()?.searcher().space_usage()?;
+            Ok(space_usage
+                .segments()
+                .iter()
+                .map(|segment| segment.store().data_usage().get_bytes())
+                .sum())
+        };
+        assert_eq!(
+            store_size(&index_skipping)? + 13 * num_numerical_values + 6 * num_bool_values,
+            store_size(&index)?
+        );
+
+        let sorted_docs = |index: &Index| -> crate::Result<Vec<Vec<Vec<OwnedValue>>>> {
+            let searcher = index.reader()?.searcher();
+            let mut docs = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let store_reader = segment_reader.get_store_reader(1)?;
+                for doc in store_reader.iter::<TantivyDocument>(None) {
+                    let doc = doc?;
+                    docs.push(
+                        [id, price, in_stock, ratings, released, title]
+                            .iter()
+                            .map(|field| doc.get_all(*field).map(OwnedValue::from).collect())
+                            .collect(),
+                    );
+                }
+            }
+            docs.sort_by_key(|doc: &Vec<Vec<OwnedValue>>| (&doc[0][0]).as_u64());
+            Ok(docs)
+        };
+        let expected_docs = sorted_docs(&index)?;
+        assert_eq!(expected_docs.len(), 20);
+        assert_eq!(sorted_docs(&index_skipping)?, expected_docs);
+        let searcher = index_skipping.reader()?.searcher();
+        let doc_address = searcher.search(
+            &TermQuery::new(Term::from_field_u64(id, 4), IndexRecordOption::Basic),
+            &TopDocs::with_limit(1),
+        )?[0]
+            .1;
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        assert_eq!(
+            doc.get_first(price).and_then(|value| value.as_f64()),
+            Some(3.0)
+        );
+        assert_eq!(
+            doc.get_first(in_stock).and_then(|value| value.as_bool()),
+            Some(true)
+        );
+        assert_eq!(doc.get_all(ratings).count(), 1);
+
+        // The merges copy the doc store as it is, and keep reading the values from the columns.
+        let segment_ids = index_skipping.searchable_segment_ids()?;
+        let mut index_writer: IndexWriter = index_skipping.writer_for_tests()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        assert_eq!(index_skipping.searchable_segment_ids()?.len(), 1);
+        assert_eq!(sorted_docs(&index_skipping)?, expected_docs);
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
@@ -18,6 +18,7 @@ use crate::fastfield::AliveBitSet;
 use crate::schema::document::{BinaryDocumentDeserializer, DocumentDeserialize};
 use crate::space_usage::StoreSpaceUsage;
 use crate::store::index::Checkpoint;
+use crate::store::ColumnarStoredFields;
 use crate::DocId;
 #[cfg(feature = "quickwit")]
 use crate::Executor;
@@ -67,6 +68,7 @@ pub struct StoreReader {
     skip_index: Arc<SkipIndex>,
     space_usage: StoreSpaceUsage,
     cache: BlockCache,
+    columnar_fields: Option<Arc<ColumnarStoredFields>>,
 }
 
 /// The cache for decompressed blocks.
@@ -174,9 +176,19 @@ impl StoreReader {
             },
             skip_index: Arc::new(skip_index),
             space_usage,
+            columnar_fields: None,
         })
     }
 
+    /// Reads back the stored values left out of the doc store from the fast field columns.
+    pub(crate) fn with_columnar_fields(
+        mut self,
+        columnar_fields: Arc<ColumnarStoredFields>,
+    ) -> StoreReader {
+        self.columnar_fields = Some(columnar_fields);
+        self
+    }
+
     pub(crate) fn block_checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
         self.skip_index.checkpoints()
     }
@@ -217,9 +229,7 @@ impl StoreReader {
             return Ok(block);
         }
 
-        let compressed_block = self.get_compressed_block(checkpoint)?;
-        let decompressed_block =
-            OwnedBytes::new(self.decompressor.decompress(compressed_block.as_ref())?);
+        let decompressed_block = self.decompress_block(checkpoint)?;
 
         self.cache
             .put_into_cache(cache_key, decompressed_block.clone());
@@ -237,14 +247,58 @@ impl StoreReader {
     /// It should not be called to score documents
     /// for instance.
     pub fn get<D: DocumentDeserialize>(&self, doc_id: DocId) -> crate::Result<D> {
-        let mut doc_bytes = self.get_document_bytes(doc_id)?;
+        let doc_bytes = self.get_document_bytes(doc_id)?;
+        self.deserialize_document(doc_id, doc_bytes)
+    }
 
+    fn deserialize_document<D: DocumentDeserialize>(
+        &self,
+        doc_id: DocId,
+        doc_bytes: OwnedBytes,
+    ) -> crate::Result<D> {
+        let mut doc_bytes = if let Some(columnar_fields) = &self.columnar_fields {
+            columnar_fields.add_column_values(doc_id, doc_bytes)?
+        } else {
+            doc_bytes
+        };
         let deserializer =
             BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
                 .map_err(crate::TantivyError::from)?;
         D::deserialize(deserializer).map_err(crate::TantivyError::from)
     }
 
+    /// Reads several documents, in the order of `doc_ids`.
+    ///
+    /// The documents are read by increasing doc id, so that each block is loaded and
+    /// decompressed at most once, even if the LRU cache is too small to hold the blocks of all
+    /// of the requested documents.
+    pub fn get_many<D: DocumentDeserialize>(&self, doc_ids: &[DocId]) -> crate::Result<Vec<D>> {
+        let mut order: Vec<usize> = (0..doc_ids.len()).collect();
+        order.sort_by_key(|&i| doc_ids[i]);
+        let mut docs: Vec<Option<D>> = std::iter::repeat_with(|| None)
+            .take(doc_ids.len())
+            .collect();
+        let mut current_block: Option<(Checkpoint, Block)> = None;
+        for i in order {
+            let doc_id = doc_ids[i];
+            let (checkpoint, block) = match current_block.take() {
+                Some((checkpoint, block)) if checkpoint.doc_range.contains(&doc_id) => {
+                    (checkpoint, block)
+                }
+                _ => {
+                    let checkpoint = self.block_checkpoint(doc_id)?;
+                    let block = self.read_block(&checkpoint)?;
+                    (checkpoint, block)
+                }
+            };
+            let doc_bytes =
+                Self::get_document_bytes_from_block(block.clone(), doc_id, &checkpoint)?;
+            docs[i] = Some(self.deserialize_document(doc_id, doc_bytes)?);
+            current_block = Some((checkpoint, block));
+        }
+        Ok(docs.into_iter().flatten().collect())
+    }
+
     /// Returns raw bytes of a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
@@ -274,18 +328,16 @@ impl StoreReader {
     /// Iterator over all Documents in their order as they are stored in the doc store.
     /// Use this, if you want to extract all Documents from the doc store.
     /// The `alive_bitset` has to be forwarded from the `SegmentReader` or the results may be wrong.
+    ///
+    /// Each block is decompressed once, and the blocks holding only deleted documents are not
+    /// read at all. The blocks do not go through the LRU cache, so that a full scan does not
+    /// evict the blocks cached for the random accesses.
     pub fn iter<'a: 'b, 'b, D: DocumentDeserialize>(
         &'b self,
         alive_bitset: Option<&'a AliveBitSet>,
     ) -> impl Iterator<Item = crate::Result<D>> + 'b {
-        self.iter_raw(alive_bitset).map(|doc_bytes_res| {
-            let mut doc_bytes = doc_bytes_res?;
-
-            let deserializer =
-                BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                    .map_err(crate::TantivyError::from)?;
-            D::deserialize(deserializer).map_err(crate::TantivyError::from)
-        })
+        self.iter_raw_with_doc_ids(alive_bitset)
+            .map(|(doc_id, doc_bytes_res)| self.deserialize_document(doc_id, doc_bytes_res?))
     }
 
     /// Iterator over all raw Documents in their order as they are stored in the doc store.
@@ -295,55 +347,59 @@ impl StoreReader {
         &'b self,
         alive_bitset: Option<&'a AliveBitSet>,
     ) -> impl Iterator<Item = crate::Result<OwnedBytes>> + 'b {
-        let last_doc_id = self
-            .block_checkpoints()
-            .last()
-            .map(|checkpoint| checkpoint.doc_range.end)
-            .unwrap_or(0);
-        let mut checkpoint_block_iter = self.block_checkpoints();
-        let mut curr_checkpoint = checkpoint_block_iter.next();
-        let mut curr_block = curr_checkpoint
-            .as_ref()
-            .map(|checkpoint| self.read_block(checkpoint).map_err(|e| e.kind())); // map error in order to enable cloning
-        let mut doc_pos = 0;
-        (0..last_doc_id)
-            .filter_map(move |doc_id| {
-                // filter_map is only used to resolve lifetime issues between the two closures on
-                // the outer variables
-
-                // check move to next checkpoint
-                if doc_id >= curr_checkpoint.as_ref().unwrap().doc_range.end {
-                    curr_checkpoint = checkpoint_block_iter.next();
-                    curr_block = curr_checkpoint
-                        .as_ref()
-                        .map(|checkpoint| self.read_block(checkpoint).map_err(|e| e.kind()));
-                    doc_pos = 0;
-                }
+        self.iter_raw_with_doc_ids(alive_bitset)
+            .map(|(_, doc_bytes_res)| doc_bytes_res)
+    }
 
-                let alive = alive_bitset.map_or(true, |bitset| bitset.is_alive(doc_id));
-                let res = if alive {
-                    Some((curr_block.clone(), doc_pos))
-                } else {
-                    None
-                };
-                doc_pos += 1;
-                res
-            })
-            .map(move |(block, doc_pos)| {
-                let block = block
-                    .ok_or_else(|| {
-                        DataCorruption::comment_only(
-                            "the current checkpoint in the doc store iterator is none, this \
-                             should never happen",
-                        )
-                    })?
-                    .map_err(|error_kind| {
-                        std::io::Error::new(error_kind, "error when reading block in doc store")
-                    })?;
-
-                let range = block_read_index(&block, doc_pos)?;
-                Ok(block.slice(range))
-            })
+    fn iter_raw_with_doc_ids<'a: 'b, 'b>(
+        &'b self,
+        alive_bitset: Option<&'a AliveBitSet>,
+    ) -> impl Iterator<Item = (DocId, crate::Result<OwnedBytes>)> + 'b {
+        let is_alive =
+            move |doc_id: DocId| alive_bitset.map_or(true, |bitset| bitset.is_alive(doc_id));
+        self.block_checkpoints().flat_map(move |checkpoint| {
+            let block = if checkpoint.doc_range.clone().any(is_alive) {
+                // map error in order to enable cloning
+                Some(self.decompress_block(&checkpoint).map_err(|e| e.kind()))
+            } else {
+                None
+            };
+            let doc_start = checkpoint.doc_range.start;
+            checkpoint
+                .doc_range
+                .filter(move |&doc_id| is_alive(doc_id))
+                .map(move |doc_id| {
+                    let doc_pos = doc_id - doc_start;
+                    (doc_id, Self::read_doc_from_block(block.clone(), doc_pos))
+                })
+        })
+    }
+
+    /// Loads and decompresses a block, without going through the cache.
+    fn decompress_block(&self, checkpoint: &Checkpoint) -> io::Result<Block> {
+        let compressed_block = self.get_compressed_block(checkpoint)?;
+        Ok(OwnedBytes::new(
+            self.decompressor.decompress(compressed_block.as_ref())?,
+        ))
+    }
+
+    fn read_doc_from_block(
+        block: Option<Result<Block, io::ErrorKind>>,
+        doc_pos: u32,
+    ) -> crate::Result<OwnedBytes> {
+        let block = block
+            .ok_or_else(|| {
+                DataCorruption::comment_only(
+                    "the block of an alive document in the doc store iterator was not read, this \
+                     should never happen",
+                )
+            })?
+            .map_err(|error_kind| {
+                std::io::Error::new(error_kind, "error when reading block in doc store")
+            })?;
+
+        let range = block_read_index(&block, doc_pos)?;
+        Ok(block.slice(range))
     }
 
     /// Summarize total space usage of this store reader.
@@ -427,12 +483,8 @@ impl StoreReader {
         doc_id: DocId,
         executor: &Executor,
     ) -> crate::Result<D> {
-        let mut doc_bytes = self.get_document_bytes_async(doc_id, executor).await?;
-
-        let deserializer =
-            BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                .map_err(crate::TantivyError::from)?;
-        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+        let doc_bytes = self.get_document_bytes_async(doc_id, executor).await?;
+        self.deserialize_document(doc_id, doc_bytes)
     }
 }
 
@@ -441,7 +493,7 @@ mod tests {
     use std::path::Path;
 
     use super::*;
-    use crate::directory::RamDirectory;
+    use crate::directory::{RamDirectory, TracingDirectory};
     use crate::schema::{Field, TantivyDocument, Value};
     use crate::store::tests::write_lorem_ipsum_store;
     use crate::store::Compressor;
@@ -501,4 +553,51 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_store_iter() -> crate::Result<()> {
+        let directory = TracingDirectory::wrap(Box::new(RamDirectory::create()));
+        let path = Path::new("store");
+        let writer = directory.open_write(path)?;
+        let schema = write_lorem_ipsum_store(writer, 500, Compressor::default(), BLOCK_SIZE, true);
+        let title = schema.get_field("title").unwrap();
+        let store_file = directory.open_read(path)?;
+        let store = StoreReader::open(store_file, DOCSTORE_CACHE_CAPACITY)?;
+        let checkpoints: Vec<Checkpoint> = store.block_checkpoints().collect();
+        assert!(checkpoints.len() >= 3);
+
+        // All of the documents of the first block are deleted, along with a few others.
+        let deleted_doc_ids: Vec<DocId> = checkpoints[0]
+            .doc_range
+            .clone()
+            .chain((checkpoints[1].doc_range.start..500).step_by(7))
+            .collect();
+        let alive_bitset = AliveBitSet::for_test_from_deleted_docs(&deleted_doc_ids, 500);
+        directory.reset_stats();
+        let docs: Vec<TantivyDocument> = store
+            .iter(Some(&alive_bitset))
+            .collect::<crate::Result<_>>()?;
+        // Each block with alive documents is read once, and the others are skipped.
+        assert_eq!(
+            directory.file_stats(path).num_reads,
+            checkpoints.len() as u64 - 1
+        );
+        let alive_doc_ids: Vec<DocId> = alive_bitset.iter_alive().collect();
+        assert_eq!(docs.len(), alive_doc_ids.len());
+        for (doc, doc_id) in docs.iter().zip(alive_doc_ids) {
+            let expected_doc: TantivyDocument = store.get(doc_id)?;
+            assert_eq!(doc, &expected_doc);
+            assert_eq!(
+                get_text_field(doc, &title),
+                Some(&format!("Doc {doc_id}")[..])
+            );
+        }
+
+        // The iteration does not go through the cache.
+        let cache_len = store.cache.len();
+        let docs: Vec<crate::Result<TantivyDocument>> = store.iter(None).collect();
+        assert_eq!(docs.len(), 500);
+        assert_eq!(store.cache.len(), cache_len);
+        Ok(())
+    }
 }
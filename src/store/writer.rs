@@ -6,7 +6,7 @@ use super::compressors::Compressor;
 use super::StoreReader;
 use crate::directory::WritePtr;
 use crate::schema::document::{BinaryDocumentSerializer, Document};
-use crate::schema::Schema;
+use crate::schema::{Field, Schema};
 use crate::store::store_compressor::BlockCompressor;
 use crate::DocId;
 
@@ -97,10 +97,21 @@ impl StoreWriter {
     /// The document id is implicitly the current number
     /// of documents.
     pub fn store<D: Document>(&mut self, document: &D, schema: &Schema) -> io::Result<()> {
+        self.store_skipping_fields(document, schema, &[])
+    }
+
+    /// Same as [`store`](Self::store), but leaves the values of the `skipped_fields` out.
+    pub(crate) fn store_skipping_fields<D: Document>(
+        &mut self,
+        document: &D,
+        schema: &Schema,
+        skipped_fields: &[Field],
+    ) -> io::Result<()> {
         self.doc_pos.push(self.current_block.len() as u32);
 
-        let mut serializer = BinaryDocumentSerializer::new(&mut self.current_block, schema);
-        serializer.serialize_doc(document)?;
+        BinaryDocumentSerializer::new(&mut self.current_block, schema)
+            .skip_fields(skipped_fields)
+            .serialize_doc(document)?;
 
         self.num_docs_in_current_block += 1;
         self.check_flush_block()?;
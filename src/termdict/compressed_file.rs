@@ -0,0 +1,257 @@
+//! Block-level compression of the term dictionaries written with a compressor.
+//!
+//! The bytes of the dictionary are cut in blocks of `BLOCK_LEN` bytes, compressed independently
+//! of each other. A [`CompressedFile`] decompresses the blocks a read touches, and keeps the most
+//! recently used ones in a small cache, so that the dictionary is read through it with the same
+//! random access as an uncompressed one.
+//!
+//! # Format
+//!
+//! ```text
+//! [compressed block]*[block end offsets: u64*][num_bytes: u64][num_blocks: u32]
+//! [block_len: u32][decompressor id: u8]
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use common::file_slice::FileHandle;
+use common::{BinarySerializable, HasLen};
+use lru::LruCache;
+
+use crate::directory::{FileSlice, OwnedBytes};
+use crate::store::{Compressor, Decompressor};
+
+/// Number of bytes of the dictionary compressed in each block.
+const BLOCK_LEN: usize = 16_384;
+
+/// Number of decompressed blocks kept in the cache of a [`CompressedFile`].
+const NUM_CACHED_BLOCKS: usize = 4;
+
+/// Size of the footer following the block end offsets.
+const FOOTER_LEN: usize = 8 + 4 + 4 + 1;
+
+/// Compresses `data` block by block with `compressor`, and writes it to `wrt`.
+pub(crate) fn write_compressed<W: Write>(
+    data: &[u8],
+    compressor: Compressor,
+    wrt: &mut W,
+) -> io::Result<()> {
+    let mut compressed_block = Vec::new();
+    let mut block_end_offsets = Vec::with_capacity(data.len().div_ceil(BLOCK_LEN));
+    let mut num_compressed_bytes = 0u64;
+    for block in data.chunks(BLOCK_LEN) {
+        compressor.compress_into(block, &mut compressed_block)?;
+        wrt.write_all(&compressed_block)?;
+        num_compressed_bytes += compressed_block.len() as u64;
+        block_end_offsets.push(num_compressed_bytes);
+    }
+    for block_end_offset in &block_end_offsets {
+        block_end_offset.serialize(wrt)?;
+    }
+    (data.len() as u64).serialize(wrt)?;
+    (block_end_offsets.len() as u32).serialize(wrt)?;
+    (BLOCK_LEN as u32).serialize(wrt)?;
+    Decompressor::from(compressor).get_id().serialize(wrt)?;
+    Ok(())
+}
+
+/// A file written with [`write_compressed`], decompressed block by block as it is read.
+pub(crate) struct CompressedFile {
+    blocks: FileSlice,
+    block_end_offsets: Vec<usize>,
+    num_bytes: usize,
+    block_len: usize,
+    decompressor: Decompressor,
+    cache: Mutex<LruCache<usize, OwnedBytes>>,
+}
+
+impl fmt::Debug for CompressedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompressedFile")
+            .field("num_bytes", &self.num_bytes)
+            .field("num_blocks", &self.block_end_offsets.len())
+            .field("decompressor", &self.decompressor)
+            .finish()
+    }
+}
+
+fn corrupted_error(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Compressed term dictionary is corrupted: {msg}"),
+    )
+}
+
+impl CompressedFile {
+    /// Opens a file written with [`write_compressed`].
+    ///
+    /// Only its footer is read: the blocks are decompressed as they are read.
+    pub fn open(file: FileSlice) -> io::Result<CompressedFile> {
+        if file.len() < FOOTER_LEN {
+            return Err(corrupted_error("the file is too short"));
+        }
+        let (main_slice, footer_slice) = file.split_from_end(FOOTER_LEN);
+        let footer_bytes = footer_slice.read_bytes()?;
+        let mut footer = footer_bytes.as_slice();
+        let num_bytes = u64::deserialize(&mut footer)? as usize;
+        let num_blocks = u32::deserialize(&mut footer)? as usize;
+        let block_len = u32::deserialize(&mut footer)? as usize;
+        let decompressor = Decompressor::try_from_id(u8::deserialize(&mut footer)?)?;
+        if block_len == 0 || num_blocks != num_bytes.div_ceil(block_len) {
+            return Err(corrupted_error("invalid number of blocks"));
+        }
+        if main_slice.len() < num_blocks * 8 {
+            return Err(corrupted_error("the file is too short"));
+        }
+        let (blocks, block_end_offsets_slice) = main_slice.split_from_end(num_blocks * 8);
+        let block_end_offsets_bytes = block_end_offsets_slice.read_bytes()?;
+        let mut block_end_offsets_data = block_end_offsets_bytes.as_slice();
+        let mut block_end_offsets = Vec::with_capacity(num_blocks);
+        for _ in 0..num_blocks {
+            let block_end_offset = u64::deserialize(&mut block_end_offsets_data)? as usize;
+            if block_end_offset > blocks.len()
+                || block_end_offsets
+                    .last()
+                    .is_some_and(|&previous_end_offset| block_end_offset < previous_end_offset)
+            {
+                return Err(corrupted_error("invalid block offsets"));
+            }
+            block_end_offsets.push(block_end_offset);
+        }
+        let num_cached_blocks = NonZeroUsize::new(NUM_CACHED_BLOCKS).unwrap();
+        Ok(CompressedFile {
+            blocks,
+            block_end_offsets,
+            num_bytes,
+            block_len,
+            decompressor,
+            cache: Mutex::new(LruCache::new(num_cached_blocks)),
+        })
+    }
+
+    /// Returns the decompressed block `block_ord`.
+    fn block(&self, block_ord: usize) -> io::Result<OwnedBytes> {
+        if let Some(block) = self
+            .cache
+            .lock()
+            .expect("Lock poisoned. This should never happen")
+            .get(&block_ord)
+        {
+            return Ok(block.clone());
+        }
+        let start = if block_ord == 0 {
+            0
+        } else {
+            self.block_end_offsets[block_ord - 1]
+        };
+        let end = self.block_end_offsets[block_ord];
+        let compressed_block = self.blocks.read_bytes_slice(start..end)?;
+        let block = self.decompressor.decompress(compressed_block.as_slice())?;
+        let expected_block_len = self
+            .block_len
+            .min(self.num_bytes - block_ord * self.block_len);
+        if block.len() != expected_block_len {
+            return Err(corrupted_error("invalid block length"));
+        }
+        let block = OwnedBytes::new(block);
+        self.cache
+            .lock()
+            .expect("Lock poisoned. This should never happen")
+            .put(block_ord, block.clone());
+        Ok(block)
+    }
+}
+
+impl HasLen for CompressedFile {
+    fn len(&self) -> usize {
+        self.num_bytes
+    }
+}
+
+impl FileHandle for CompressedFile {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let first_block_ord = range.start / self.block_len;
+        let last_block_ord = (range.end - 1) / self.block_len;
+        let block_start = |block_ord: usize| block_ord * self.block_len;
+        if first_block_ord == last_block_ord {
+            // Most reads fall within a single block, which is then shared without a copy.
+            let block = self.block(first_block_ord)?;
+            let offset = block_start(first_block_ord);
+            return Ok(block.slice(range.start - offset..range.end - offset));
+        }
+        let mut bytes = Vec::with_capacity(range.len());
+        for block_ord in first_block_ord..=last_block_ord {
+            let block = self.block(block_ord)?;
+            let offset = block_start(block_ord);
+            let start = range.start.max(offset) - offset;
+            let end = range.end.min(offset + block.len()) - offset;
+            bytes.extend_from_slice(&block.as_slice()[start..end]);
+        }
+        Ok(OwnedBytes::new(bytes))
+    }
+}
+
+#[cfg(all(test, feature = "lz4-compression"))]
+mod tests {
+    use std::sync::Arc;
+
+    use common::file_slice::FileHandle;
+    use common::HasLen;
+
+    use super::{write_compressed, CompressedFile, BLOCK_LEN};
+    use crate::directory::FileSlice;
+    use crate::store::Compressor;
+
+    #[test]
+    fn test_compressed_file() -> std::io::Result<()> {
+        let data: Vec<u8> = (0..BLOCK_LEN * 3 + 100).map(|i| (i % 251) as u8).collect();
+        let mut buffer = Vec::new();
+        write_compressed(&data, Compressor::Lz4, &mut buffer)?;
+        assert!(buffer.len() < data.len());
+        let compressed_file = CompressedFile::open(FileSlice::from(buffer))?;
+        assert_eq!(compressed_file.len(), data.len());
+        for range in [
+            0..10,
+            BLOCK_LEN - 5..BLOCK_LEN + 5,
+            BLOCK_LEN..BLOCK_LEN * 3 + 1,
+            BLOCK_LEN * 3..data.len(),
+            7..7,
+        ] {
+            let bytes = compressed_file.read_bytes(range.clone())?;
+            assert_eq!(bytes.as_slice(), &data[range]);
+        }
+        let file_slice = FileSlice::new(Arc::new(compressed_file));
+        assert_eq!(file_slice.read_bytes()?.as_slice(), &data[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_file_reads_only_the_blocks_it_needs() -> std::io::Result<()> {
+        let data = vec![1u8; BLOCK_LEN * 2];
+        let mut buffer = Vec::new();
+        write_compressed(&data, Compressor::Lz4, &mut buffer)?;
+        // Corrupting the first block does not prevent reading the second one.
+        buffer[0] ^= 0xFF;
+        let compressed_file = CompressedFile::open(FileSlice::from(buffer))?;
+        assert!(compressed_file.read_bytes(0..1).is_err());
+        let bytes = compressed_file.read_bytes(BLOCK_LEN..BLOCK_LEN + 3)?;
+        assert_eq!(bytes.as_slice(), &[1u8, 1u8, 1u8]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_file_empty() -> std::io::Result<()> {
+        let mut buffer = Vec::new();
+        write_compressed(&[], Compressor::Lz4, &mut buffer)?;
+        let compressed_file = CompressedFile::open(FileSlice::from(buffer))?;
+        assert_eq!(compressed_file.len(), 0);
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use std::io;
+
 use tantivy_fst::map::{OpBuilder, Union};
 use tantivy_fst::raw::IndexedValue;
 use tantivy_fst::Streamer;
@@ -74,20 +76,29 @@ impl<'a> TermMerger<'a> {
         &self.current_key
     }
 
-    /// Iterator over `(segment ordinal, TermInfo)` pairs sorted by the ordinal.
+    /// Iterator over the sorted ordinals of the segments containing the current term.
     ///
     /// This method may be called if [`Self::advance`] has been called before
     /// and `true` was returned.
-    pub fn current_segment_ords_and_term_infos<'b: 'a>(
-        &'b self,
-    ) -> impl 'b + Iterator<Item = (usize, TermInfo)> {
+    pub(crate) fn current_segment_ords(&self) -> impl '_ + Iterator<Item = usize> {
+        self.current_segment_and_term_ordinals
+            .iter()
+            .map(|iv| iv.index)
+    }
+
+    /// Iterator over `(segment ordinal, TermInfo)` pairs sorted by the ordinal.
+    ///
+    /// This method may be called if [`Self::advance`] has been called before
+    /// and `true` was returned. The term infos of a compressed dictionary are read as the
+    /// iterator reaches them, and the iterator returns an error if they cannot be read.
+    pub fn current_segment_ords_and_term_infos(
+        &self,
+    ) -> impl '_ + Iterator<Item = io::Result<(usize, TermInfo)>> {
         self.current_segment_and_term_ordinals
             .iter()
             .map(move |iv| {
-                (
-                    iv.index,
-                    self.dictionaries[iv.index].term_info_from_ord(iv.value),
-                )
+                let term_info = self.dictionaries[iv.index].term_info_from_ord(iv.value)?;
+                Ok((iv.index, term_info))
             })
     }
 }
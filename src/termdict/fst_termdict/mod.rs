@@ -19,6 +19,7 @@
 //! A second datastructure makes it possible to access a
 //! [`TermInfo`](crate::postings::TermInfo).
 mod merger;
+mod paged_fst;
 mod streamer;
 mod term_info_store;
 mod termdict;
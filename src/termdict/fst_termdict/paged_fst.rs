@@ -0,0 +1,555 @@
+//! Traversal of an FST read from its file, rather than from a contiguous slice of bytes in
+//! memory.
+//!
+//! `tantivy_fst` requires the whole FST as one slice of bytes. [`PagedFst`] decodes the nodes
+//! of the same format one at a time, from pages of the file kept in a bounded LRU cache, so
+//! that the memory used by a large FST does not exceed the size of the cache.
+
+use std::io;
+use std::num::NonZeroUsize;
+use std::ops::Bound;
+use std::sync::Mutex;
+
+use common::{BinarySerializable, HasLen};
+use lru::LruCache;
+use tantivy_fst::Automaton;
+
+use crate::directory::{FileSlice, OwnedBytes};
+use crate::termdict::TermOrdinal;
+
+/// Number of bytes of the pages read from the FST file.
+const PAGE_SIZE: usize = 4_096;
+
+/// Latest version of the FST format written by `tantivy_fst`.
+const MAX_FST_VERSION: u64 = 2;
+
+/// Address of the empty final node, which is never written.
+const EMPTY_ADDRESS: usize = 0;
+
+/// Number of transitions from which `tantivy_fst` writes a 256 bytes index of the inputs of a
+/// node.
+const TRANS_INDEX_THRESHOLD: usize = 32;
+
+/// Inputs encoded in the state byte of the nodes with a single transition, as defined by
+/// `tantivy_fst`, by decreasing frequency.
+const COMMON_INPUTS_INV: [u8; 256] = [
+    b't', b'e', b'/', b'o', b'a', b's', b'r', b'i', b'p', b'c', b'n', b'w', b'.', b'h', b'l', b'm',
+    b'-', b'd', b'u', b'0', b'1', b'2', b'g', b'=', b':', b'b', b'f', b'3', b'y', b'5', b'&', b'_',
+    b'4', b'v', b'9', b'6', b'7', b'8', b'k', b'%', b'?', b'x', b'C', b'D', b'A', b'S', b'F', b'I',
+    b'B', b'E', b'j', b'P', b'T', b'z', b'R', b'N', b'M', b'+', b'L', b'O', b'q', b'H', b'G', b'W',
+    b'U', b'V', b',', b'Y', b'K', b'J', b'Z', b'X', b'Q', b';', b')', b'(', b'~', b'[', b']', b'$',
+    b'!', b'\'', b'*', b'@', b'\x00', b'\x01', b'\x02', b'\x03', b'\x04', b'\x05', b'\x06',
+    b'\x07', b'\x08', b'\t', b'\n', b'\x0b', b'\x0c', b'\r', b'\x0e', b'\x0f', b'\x10', b'\x11',
+    b'\x12', b'\x13', b'\x14', b'\x15', b'\x16', b'\x17', b'\x18', b'\x19', b'\x1a', b'\x1b',
+    b'\x1c', b'\x1d', b'\x1e', b'\x1f', b' ', b'"', b'#', b'<', b'>', b'\\', b'^', b'`', b'{',
+    b'|', b'}', b'\x7f', b'\x80', b'\x81', b'\x82', b'\x83', b'\x84', b'\x85', b'\x86', b'\x87',
+    b'\x88', b'\x89', b'\x8a', b'\x8b', b'\x8c', b'\x8d', b'\x8e', b'\x8f', b'\x90', b'\x91',
+    b'\x92', b'\x93', b'\x94', b'\x95', b'\x96', b'\x97', b'\x98', b'\x99', b'\x9a', b'\x9b',
+    b'\x9c', b'\x9d', b'\x9e', b'\x9f', b'\xa0', b'\xa1', b'\xa2', b'\xa3', b'\xa4', b'\xa5',
+    b'\xa6', b'\xa7', b'\xa8', b'\xa9', b'\xaa', b'\xab', b'\xac', b'\xad', b'\xae', b'\xaf',
+    b'\xb0', b'\xb1', b'\xb2', b'\xb3', b'\xb4', b'\xb5', b'\xb6', b'\xb7', b'\xb8', b'\xb9',
+    b'\xba', b'\xbb', b'\xbc', b'\xbd', b'\xbe', b'\xbf', b'\xc0', b'\xc1', b'\xc2', b'\xc3',
+    b'\xc4', b'\xc5', b'\xc6', b'\xc7', b'\xc8', b'\xc9', b'\xca', b'\xcb', b'\xcc', b'\xcd',
+    b'\xce', b'\xcf', b'\xd0', b'\xd1', b'\xd2', b'\xd3', b'\xd4', b'\xd5', b'\xd6', b'\xd7',
+    b'\xd8', b'\xd9', b'\xda', b'\xdb', b'\xdc', b'\xdd', b'\xde', b'\xdf', b'\xe0', b'\xe1',
+    b'\xe2', b'\xe3', b'\xe4', b'\xe5', b'\xe6', b'\xe7', b'\xe8', b'\xe9', b'\xea', b'\xeb',
+    b'\xec', b'\xed', b'\xee', b'\xef', b'\xf0', b'\xf1', b'\xf2', b'\xf3', b'\xf4', b'\xf5',
+    b'\xf6', b'\xf7', b'\xf8', b'\xf9', b'\xfa', b'\xfb', b'\xfc', b'\xfd', b'\xfe', b'\xff',
+];
+
+fn common_input(idx: u8) -> Option<u8> {
+    if idx == 0 {
+        None
+    } else {
+        Some(COMMON_INPUTS_INV[idx as usize - 1])
+    }
+}
+
+fn corrupted_fst_error(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Fst data is corrupted: {msg}"),
+    )
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Transition {
+    input: u8,
+    output: u64,
+    addr: usize,
+}
+
+/// A decoded FST node, with its transitions sorted by input.
+#[derive(Debug)]
+struct Node {
+    is_final: bool,
+    final_output: u64,
+    transitions: Vec<Transition>,
+}
+
+impl Node {
+    fn find_input(&self, input: u8) -> Option<Transition> {
+        self.transitions
+            .binary_search_by_key(&input, |transition| transition.input)
+            .ok()
+            .map(|transition_ord| self.transitions[transition_ord])
+    }
+}
+
+/// An FST traversed from its file, through an LRU cache of its pages.
+pub struct PagedFst {
+    file: FileSlice,
+    version: u64,
+    root_addr: usize,
+    pages: Mutex<LruCache<usize, OwnedBytes>>,
+}
+
+impl PagedFst {
+    /// Opens the FST of `file`, caching at most `max_cached_bytes` of its pages (and at least one
+    /// page).
+    pub fn open(file: FileSlice, max_cached_bytes: usize) -> io::Result<PagedFst> {
+        let num_bytes = file.len();
+        if num_bytes < 32 {
+            return Err(corrupted_fst_error("the file is too short"));
+        }
+        let version = u64::deserialize(&mut file.read_bytes_slice(0..8)?.as_slice())?;
+        if version == 0 || version > MAX_FST_VERSION {
+            return Err(corrupted_fst_error(&format!(
+                "unsupported version {version}"
+            )));
+        }
+        let root_addr =
+            u64::deserialize(&mut file.read_bytes_slice(num_bytes - 8..num_bytes)?.as_slice())?
+                as usize;
+        if (root_addr != EMPTY_ADDRESS || num_bytes != 32) && root_addr + 17 != num_bytes {
+            return Err(corrupted_fst_error("invalid root address"));
+        }
+        let num_pages =
+            NonZeroUsize::new(max_cached_bytes / PAGE_SIZE).unwrap_or(NonZeroUsize::MIN);
+        Ok(PagedFst {
+            file,
+            version,
+            root_addr,
+            pages: Mutex::new(LruCache::new(num_pages)),
+        })
+    }
+
+    fn page(&self, page_ord: usize) -> io::Result<OwnedBytes> {
+        if let Some(page) = self
+            .pages
+            .lock()
+            .expect("Lock poisoned. This should never happen")
+            .get(&page_ord)
+        {
+            return Ok(page.clone());
+        }
+        let num_bytes = self.file.len();
+        let start = page_ord * PAGE_SIZE;
+        let end = (start + PAGE_SIZE).min(num_bytes);
+        let page = self.file.read_bytes_slice(start..end)?;
+        self.pages
+            .lock()
+            .expect("Lock poisoned. This should never happen")
+            .put(page_ord, page.clone());
+        Ok(page)
+    }
+
+    fn root(&self) -> io::Result<Node> {
+        self.node(self.root_addr)
+    }
+
+    /// Decodes the node at `addr`.
+    ///
+    /// Nodes are written backward: `addr` is the address of their last byte, which holds their
+    /// state.
+    fn node(&self, addr: usize) -> io::Result<Node> {
+        if addr == EMPTY_ADDRESS {
+            return Ok(Node {
+                is_final: true,
+                final_output: 0,
+                transitions: Vec::new(),
+            });
+        }
+        let mut reader = PageReader {
+            fst: self,
+            page: None,
+        };
+        let state = reader.byte(addr)?;
+        match state >> 6 {
+            // A single transition to the node written right before, without output.
+            0b11 => {
+                let (input, input_len) = match common_input(state & 0b11_1111) {
+                    Some(input) => (input, 0),
+                    None => (reader.byte(addr - 1)?, 1),
+                };
+                let end = addr - input_len;
+                Ok(Node {
+                    is_final: false,
+                    final_output: 0,
+                    transitions: vec![Transition {
+                        input,
+                        output: 0,
+                        addr: end - 1,
+                    }],
+                })
+            }
+            // A single transition.
+            0b10 => {
+                let (input, input_len) = match common_input(state & 0b11_1111) {
+                    Some(input) => (input, 0),
+                    None => (reader.byte(addr - 1)?, 1),
+                };
+                let (trans_size, output_size) = pack_sizes(reader.byte(addr - input_len - 1)?);
+                let trans_addr_at = addr - input_len - 1 - trans_size;
+                let end = trans_addr_at - output_size;
+                let delta = reader.uint(trans_addr_at, trans_size)?;
+                Ok(Node {
+                    is_final: false,
+                    final_output: 0,
+                    transitions: vec![Transition {
+                        input,
+                        output: reader.uint(end, output_size)?,
+                        addr: unpack_delta(delta, end),
+                    }],
+                })
+            }
+            // Any number of transitions.
+            _ => {
+                let is_final = state & 0b01_000000 != 0;
+                let (num_transitions, num_transitions_len) = match state & 0b11_1111 {
+                    0 => match reader.byte(addr - 1)? {
+                        // A single transition is always encoded in the state byte.
+                        1 => (256, 1),
+                        num_transitions => (num_transitions as usize, 1),
+                    },
+                    num_transitions => (num_transitions as usize, 0),
+                };
+                let (trans_size, output_size) =
+                    pack_sizes(reader.byte(addr - num_transitions_len - 1)?);
+                let index_size = if self.version >= 2 && num_transitions > TRANS_INDEX_THRESHOLD {
+                    256
+                } else {
+                    0
+                };
+                let inputs_at = addr - num_transitions_len - 1 - index_size;
+                let trans_addrs_at = inputs_at - num_transitions;
+                let outputs_at = trans_addrs_at - num_transitions * trans_size;
+                let final_output_at = outputs_at - num_transitions * output_size - output_size;
+                let end = if is_final {
+                    final_output_at
+                } else {
+                    final_output_at + output_size
+                };
+                let mut transitions = Vec::with_capacity(num_transitions);
+                for i in 0..num_transitions {
+                    let input = reader.byte(inputs_at - i - 1)?;
+                    let delta = reader.uint(trans_addrs_at - (i + 1) * trans_size, trans_size)?;
+                    let output = reader.uint(outputs_at - (i + 1) * output_size, output_size)?;
+                    transitions.push(Transition {
+                        input,
+                        output,
+                        addr: unpack_delta(delta, end),
+                    });
+                }
+                let final_output = if is_final {
+                    reader.uint(final_output_at, output_size)?
+                } else {
+                    0
+                };
+                Ok(Node {
+                    is_final,
+                    final_output,
+                    transitions,
+                })
+            }
+        }
+    }
+
+    /// Returns the output associated with `key`, that is its term ordinal.
+    pub fn get(&self, key: &[u8]) -> io::Result<Option<TermOrdinal>> {
+        let mut node = self.root()?;
+        let mut output = 0;
+        for &input in key {
+            let Some(transition) = node.find_input(input) else {
+                return Ok(None);
+            };
+            output += transition.output;
+            node = self.node(transition.addr)?;
+        }
+        Ok(node.is_final.then_some(output + node.final_output))
+    }
+
+    /// Stores the key associated with the output `ord` in `bytes`.
+    ///
+    /// Returns true if and only if the key has been found.
+    pub fn ord_to_term(&self, mut ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool> {
+        bytes.clear();
+        let mut node = self.root()?;
+        while ord != 0 || !node.is_final {
+            let Some(transition) = node
+                .transitions
+                .iter()
+                .take_while(|transition| transition.output <= ord)
+                .last()
+                .copied()
+            else {
+                return Ok(false);
+            };
+            ord -= transition.output;
+            bytes.push(transition.input);
+            node = self.node(transition.addr)?;
+        }
+        Ok(true)
+    }
+
+    /// Returns a builder of the stream of the keys matching `automaton`.
+    pub fn search<A: Automaton>(&self, automaton: A) -> PagedStreamBuilder<'_, A> {
+        PagedStreamBuilder {
+            fst: self,
+            automaton,
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+            backward: false,
+        }
+    }
+}
+
+/// Reads the bytes of a node, keeping the page of the last byte read.
+struct PageReader<'a> {
+    fst: &'a PagedFst,
+    page: Option<(usize, OwnedBytes)>,
+}
+
+impl PageReader<'_> {
+    fn byte(&mut self, addr: usize) -> io::Result<u8> {
+        let page_ord = addr / PAGE_SIZE;
+        let page = match &self.page {
+            Some((current_page_ord, page)) if *current_page_ord == page_ord => page,
+            _ => &self.page.insert((page_ord, self.fst.page(page_ord)?)).1,
+        };
+        page.as_slice()
+            .get(addr % PAGE_SIZE)
+            .copied()
+            .ok_or_else(|| corrupted_fst_error("address out of bounds"))
+    }
+
+    /// Reads an unsigned integer encoded in little endian on `num_bytes` bytes.
+    fn uint(&mut self, addr: usize, num_bytes: usize) -> io::Result<u64> {
+        let mut val = 0u64;
+        for i in 0..num_bytes {
+            val |= (self.byte(addr + i)? as u64) << (8 * i);
+        }
+        Ok(val)
+    }
+}
+
+/// Returns the number of bytes of the transition addresses and of the outputs of a node.
+fn pack_sizes(pack_sizes: u8) -> (usize, usize) {
+    ((pack_sizes >> 4) as usize, (pack_sizes & 0b1111) as usize)
+}
+
+fn unpack_delta(delta: u64, node_end: usize) -> usize {
+    if delta == EMPTY_ADDRESS as u64 {
+        EMPTY_ADDRESS
+    } else {
+        node_end - delta as usize
+    }
+}
+
+/// Builder of a [`PagedStream`], mirroring `tantivy_fst::map::StreamBuilder`.
+pub struct PagedStreamBuilder<'a, A: Automaton> {
+    fst: &'a PagedFst,
+    automaton: A,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+    backward: bool,
+}
+
+impl<'a, A: Automaton> PagedStreamBuilder<'a, A> {
+    pub fn ge<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.lower = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn gt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.lower = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn le<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.upper = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn lt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.upper = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    pub fn backward(mut self) -> Self {
+        self.backward = true;
+        self
+    }
+
+    pub fn into_stream(self) -> io::Result<PagedStream<'a, A>> {
+        let root = Frame {
+            node: self.fst.root()?,
+            num_visited_transitions: 0,
+            output: 0,
+            automaton_state: self.automaton.start(),
+            visited: false,
+        };
+        Ok(PagedStream {
+            fst: self.fst,
+            automaton: self.automaton,
+            lower: self.lower,
+            upper: self.upper,
+            backward: self.backward,
+            stack: vec![root],
+            prefix: Vec::new(),
+            key: Vec::new(),
+        })
+    }
+}
+
+struct Frame<S> {
+    node: Node,
+    num_visited_transitions: usize,
+    output: u64,
+    automaton_state: S,
+    visited: bool,
+}
+
+/// A depth-first traversal of a [`PagedFst`], yielding the keys within the bounds and matching
+/// the automaton, with their outputs.
+///
+/// Only the nodes on the path to the current key are decoded at a time.
+pub struct PagedStream<'a, A: Automaton> {
+    fst: &'a PagedFst,
+    automaton: A,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+    backward: bool,
+    stack: Vec<Frame<A::State>>,
+    // The input leading to the node at the top of the stack.
+    prefix: Vec<u8>,
+    key: Vec<u8>,
+}
+
+impl<A: Automaton> PagedStream<'_, A> {
+    /// Returns true if all of the keys starting with `prefix` are above the upper bound.
+    fn above_upper_bound(&self, prefix: &[u8]) -> bool {
+        match &self.upper {
+            Bound::Included(upper) => prefix > upper.as_slice(),
+            Bound::Excluded(upper) => prefix >= upper.as_slice(),
+            Bound::Unbounded => false,
+        }
+    }
+
+    /// Returns true if all of the keys starting with `prefix` are below the lower bound.
+    fn below_lower_bound(&self, prefix: &[u8]) -> bool {
+        match &self.lower {
+            Bound::Included(lower) | Bound::Excluded(lower) => {
+                prefix < lower.as_slice() && !lower.starts_with(prefix)
+            }
+            Bound::Unbounded => false,
+        }
+    }
+
+    fn within_bounds(&self, key: &[u8]) -> bool {
+        let above_lower = match &self.lower {
+            Bound::Included(lower) => key >= lower.as_slice(),
+            Bound::Excluded(lower) => key > lower.as_slice(),
+            Bound::Unbounded => true,
+        };
+        let below_upper = match &self.upper {
+            Bound::Included(upper) => key <= upper.as_slice(),
+            Bound::Excluded(upper) => key < upper.as_slice(),
+            Bound::Unbounded => true,
+        };
+        above_lower && below_upper
+    }
+
+    /// Returns the output of the key `prefix`, if it is a key of the stream ending on the node
+    /// of `frame`.
+    fn key_output(&self, frame: &Frame<A::State>) -> Option<u64> {
+        let is_key = frame.node.is_final
+            && self.automaton.is_match(&frame.automaton_state)
+            && self.within_bounds(&self.prefix);
+        is_key.then_some(frame.output + frame.node.final_output)
+    }
+
+    /// Advances to the next key, and returns it along with its output.
+    ///
+    /// Forward, the keys are yielded as their node is reached. Backward, they are yielded
+    /// once all of the keys they prefix have been.
+    pub fn next(&mut self) -> io::Result<Option<(&[u8], u64)>> {
+        loop {
+            let Some(frame) = self.stack.last_mut() else {
+                return Ok(None);
+            };
+            if !self.backward && !frame.visited {
+                frame.visited = true;
+                let frame = self.stack.last().unwrap();
+                if let Some(output) = self.key_output(frame) {
+                    self.key.clone_from(&self.prefix);
+                    return Ok(Some((&self.key, output)));
+                }
+                continue;
+            }
+            let num_transitions = frame.node.transitions.len();
+            if frame.num_visited_transitions == num_transitions {
+                let frame = self.stack.pop().unwrap();
+                let output_opt = if self.backward {
+                    self.key_output(&frame)
+                } else {
+                    None
+                };
+                if output_opt.is_some() {
+                    self.key.clone_from(&self.prefix);
+                }
+                self.prefix.pop();
+                if let Some(output) = output_opt {
+                    return Ok(Some((&self.key, output)));
+                }
+                continue;
+            }
+            let transition_ord = if self.backward {
+                num_transitions - frame.num_visited_transitions - 1
+            } else {
+                frame.num_visited_transitions
+            };
+            frame.num_visited_transitions += 1;
+            let transition = frame.node.transitions[transition_ord];
+            let automaton_state = self
+                .automaton
+                .accept(&frame.automaton_state, transition.input);
+            let output = frame.output + transition.output;
+            self.prefix.push(transition.input);
+            if self.above_upper_bound(&self.prefix) {
+                if !self.backward {
+                    // The following keys are all above the upper bound.
+                    self.stack.clear();
+                    return Ok(None);
+                }
+            } else if self.below_lower_bound(&self.prefix) {
+                if self.backward {
+                    // The following keys are all below the lower bound.
+                    self.stack.clear();
+                    return Ok(None);
+                }
+            } else if self.automaton.can_match(&automaton_state) {
+                let node = self.fst.node(transition.addr)?;
+                self.stack.push(Frame {
+                    node,
+                    num_visited_transitions: 0,
+                    output,
+                    automaton_state,
+                    visited: false,
+                });
+                continue;
+            }
+            self.prefix.pop();
+        }
+    }
+}
@@ -4,65 +4,122 @@ use tantivy_fst::automaton::AlwaysMatch;
 use tantivy_fst::map::{Stream, StreamBuilder};
 use tantivy_fst::{Automaton, IntoStreamer, Streamer};
 
+use super::paged_fst::{PagedStream, PagedStreamBuilder};
 use super::TermDictionary;
 use crate::postings::TermInfo;
 use crate::termdict::TermOrdinal;
 
+/// Builder of the stream of an FST, either resident or traversed from its file.
+pub(crate) enum FstStreamBuilder<'a, A: Automaton> {
+    Resident(StreamBuilder<'a, A>),
+    Paged(PagedStreamBuilder<'a, A>),
+}
+
+/// Stream of the keys of an FST, with their term ordinal.
+pub(crate) enum FstStream<'a, A: Automaton> {
+    Resident(Stream<'a, A>),
+    Paged(PagedStream<'a, A>),
+}
+
+impl<A: Automaton> FstStream<'_, A> {
+    /// Returns the next key with its term ordinal, or an error if the pages of a paged FST
+    /// cannot be read.
+    fn try_next(&mut self) -> io::Result<Option<(&[u8], u64)>> {
+        match self {
+            FstStream::Resident(stream) => Ok(stream.next()),
+            FstStream::Paged(stream) => stream.next(),
+        }
+    }
+}
+
+// The union of the `TermMerger` consumes its streams as `Streamer`s, which cannot return an
+// error.
+impl<'b, A: Automaton> Streamer<'b> for FstStream<'_, A> {
+    type Item = (&'b [u8], u64);
+
+    fn next(&'b mut self) -> Option<Self::Item> {
+        self.try_next()
+            .expect("Failed to read the FST of the term dictionary")
+    }
+}
+
 /// `TermStreamerBuilder` is a helper object used to define
 /// a range of terms that should be streamed.
 pub struct TermStreamerBuilder<'a, A = AlwaysMatch>
 where A: Automaton
 {
     fst_map: &'a TermDictionary,
-    stream_builder: StreamBuilder<'a, A>,
+    stream_builder: FstStreamBuilder<'a, A>,
 }
 
 impl<'a, A> TermStreamerBuilder<'a, A>
 where A: Automaton
 {
-    pub(crate) fn new(fst_map: &'a TermDictionary, stream_builder: StreamBuilder<'a, A>) -> Self {
+    pub(crate) fn new(
+        fst_map: &'a TermDictionary,
+        stream_builder: FstStreamBuilder<'a, A>,
+    ) -> Self {
         TermStreamerBuilder {
             fst_map,
             stream_builder,
         }
     }
 
-    /// Limit the range to terms greater or equal to the bound
-    pub fn ge<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.ge(bound);
+    fn map_stream_builder(
+        mut self,
+        resident: impl FnOnce(StreamBuilder<'a, A>) -> StreamBuilder<'a, A>,
+        paged: impl FnOnce(PagedStreamBuilder<'a, A>) -> PagedStreamBuilder<'a, A>,
+    ) -> Self {
+        self.stream_builder = match self.stream_builder {
+            FstStreamBuilder::Resident(stream_builder) => {
+                FstStreamBuilder::Resident(resident(stream_builder))
+            }
+            FstStreamBuilder::Paged(stream_builder) => {
+                FstStreamBuilder::Paged(paged(stream_builder))
+            }
+        };
         self
     }
 
+    /// Limit the range to terms greater or equal to the bound
+    pub fn ge<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        self.map_stream_builder(|builder| builder.ge(&bound), |builder| builder.ge(&bound))
+    }
+
     /// Limit the range to terms strictly greater than the bound
-    pub fn gt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.gt(bound);
-        self
+    pub fn gt<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        self.map_stream_builder(|builder| builder.gt(&bound), |builder| builder.gt(&bound))
     }
 
     /// Limit the range to terms lesser or equal to the bound
-    pub fn le<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.le(bound);
-        self
+    pub fn le<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        self.map_stream_builder(|builder| builder.le(&bound), |builder| builder.le(&bound))
     }
 
     /// Limit the range to terms lesser or equal to the bound
-    pub fn lt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
-        self.stream_builder = self.stream_builder.lt(bound);
-        self
+    pub fn lt<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        self.map_stream_builder(|builder| builder.lt(&bound), |builder| builder.lt(&bound))
     }
 
     /// Iterate over the range backwards.
-    pub fn backward(mut self) -> Self {
-        self.stream_builder = self.stream_builder.backward();
-        self
+    pub fn backward(self) -> Self {
+        self.map_stream_builder(|builder| builder.backward(), |builder| builder.backward())
     }
 
     /// Creates the stream corresponding to the range
     /// of terms defined using the `TermStreamerBuilder`.
     pub fn into_stream(self) -> io::Result<TermStreamer<'a, A>> {
+        let stream = match self.stream_builder {
+            FstStreamBuilder::Resident(stream_builder) => {
+                FstStream::Resident(stream_builder.into_stream())
+            }
+            FstStreamBuilder::Paged(stream_builder) => {
+                FstStream::Paged(stream_builder.into_stream()?)
+            }
+        };
         Ok(TermStreamer {
             fst_map: self.fst_map,
-            stream: self.stream_builder.into_stream(),
+            stream,
             term_ord: 0u64,
             current_key: Vec::with_capacity(100),
             current_value: TermInfo::default(),
@@ -76,7 +133,7 @@ pub struct TermStreamer<'a, A = AlwaysMatch>
 where A: Automaton
 {
     pub(crate) fst_map: &'a TermDictionary,
-    pub(crate) stream: Stream<'a, A>,
+    pub(crate) stream: FstStream<'a, A>,
     term_ord: TermOrdinal,
     current_key: Vec<u8>,
     current_value: TermInfo,
@@ -88,15 +145,29 @@ where A: Automaton
     /// Advance position the stream on the next item.
     /// Before the first call to `.advance()`, the stream
     /// is an uninitialized state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the term dictionary cannot be read, see [`TermStreamer::try_advance`].
     pub fn advance(&mut self) -> bool {
-        if let Some((term, term_ord)) = self.stream.next() {
+        self.try_advance()
+            .expect("Failed to read the term dictionary")
+    }
+
+    /// Advance position the stream on the next item, as [`TermStreamer::advance`].
+    ///
+    /// Returns an error if the term dictionary cannot be read, which may happen on any call
+    /// when its FST is traversed from its file, see
+    /// [`TermDictionaryAccess::Capped`](crate::termdict::TermDictionaryAccess::Capped).
+    pub fn try_advance(&mut self) -> io::Result<bool> {
+        if let Some((term, term_ord)) = self.stream.try_next()? {
             self.current_key.clear();
             self.current_key.extend_from_slice(term);
             self.term_ord = term_ord;
-            self.current_value = self.fst_map.term_info_from_ord(term_ord);
-            true
+            self.current_value = self.fst_map.term_info_from_ord(term_ord)?;
+            Ok(true)
         } else {
-            false
+            Ok(false)
         }
     }
 
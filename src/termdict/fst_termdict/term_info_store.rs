@@ -2,7 +2,7 @@ use std::cmp;
 use std::io::{self, Read, Write};
 
 use byteorder::{ByteOrder, LittleEndian};
-use common::{BinarySerializable, FixedSize};
+use common::{BinarySerializable, FixedSize, HasLen};
 use tantivy_bitpacker::{compute_num_bits, BitPacker};
 
 use crate::directory::{FileSlice, OwnedBytes};
@@ -93,11 +93,20 @@ impl TermInfoBlockMeta {
     }
 }
 
+/// The bitpacked term infos of a `TermInfoStore`.
+#[derive(Clone)]
+enum TermInfoBytes {
+    /// The term infos, read as a whole when the store is opened.
+    Resident(OwnedBytes),
+    /// The file of the term infos, from which the block of a term info is read on each access.
+    Lazy(FileSlice),
+}
+
 #[derive(Clone)]
 pub struct TermInfoStore {
     num_terms: usize,
     block_meta_bytes: OwnedBytes,
-    term_info_bytes: OwnedBytes,
+    term_info_bytes: TermInfoBytes,
 }
 
 fn extract_bits(data: &[u8], addr_bits: usize, num_bits: u8) -> u64 {
@@ -123,34 +132,72 @@ fn extract_bits(data: &[u8], addr_bits: usize, num_bits: u8) -> u64 {
 
 impl TermInfoStore {
     pub fn open(term_info_store_file: FileSlice) -> io::Result<TermInfoStore> {
+        let (num_terms, block_meta_bytes, term_info_file) =
+            Self::open_block_metas(term_info_store_file)?;
+        Ok(TermInfoStore {
+            num_terms,
+            block_meta_bytes,
+            term_info_bytes: TermInfoBytes::Resident(term_info_file.read_bytes()?),
+        })
+    }
+
+    /// Opens a `TermInfoStore` reading the block of a term info from `term_info_store_file`
+    /// on each access, rather than all of the term infos at once.
+    pub fn open_lazily(term_info_store_file: FileSlice) -> io::Result<TermInfoStore> {
+        let (num_terms, block_meta_bytes, term_info_file) =
+            Self::open_block_metas(term_info_store_file)?;
+        Ok(TermInfoStore {
+            num_terms,
+            block_meta_bytes,
+            term_info_bytes: TermInfoBytes::Lazy(term_info_file),
+        })
+    }
+
+    /// Reads the number of terms and the block metas, and returns them along with the file of
+    /// the term infos.
+    fn open_block_metas(
+        term_info_store_file: FileSlice,
+    ) -> io::Result<(usize, OwnedBytes, FileSlice)> {
         let (len_slice, main_slice) = term_info_store_file.split(16);
         let mut bytes = len_slice.read_bytes()?;
         let len = u64::deserialize(&mut bytes)? as usize;
         let num_terms = u64::deserialize(&mut bytes)? as usize;
         let (block_meta_file, term_info_file) = main_slice.split(len);
-        let term_info_bytes = term_info_file.read_bytes()?;
-        Ok(TermInfoStore {
-            num_terms,
-            block_meta_bytes: block_meta_file.read_bytes()?,
-            term_info_bytes,
-        })
+        Ok((num_terms, block_meta_file.read_bytes()?, term_info_file))
     }
 
-    pub fn get(&self, term_ord: TermOrdinal) -> TermInfo {
-        let block_id = (term_ord as usize) / BLOCK_LEN;
+    fn block_meta(&self, block_id: usize) -> TermInfoBlockMeta {
         let buffer = self.block_meta_bytes.as_slice();
         let mut block_data: &[u8] = &buffer[block_id * TermInfoBlockMeta::SIZE_IN_BYTES..];
-        let term_info_block_data = TermInfoBlockMeta::deserialize(&mut block_data)
-            .expect("Failed to deserialize terminfoblockmeta");
+        TermInfoBlockMeta::deserialize(&mut block_data)
+            .expect("Failed to deserialize terminfoblockmeta")
+    }
+
+    pub fn get(&self, term_ord: TermOrdinal) -> io::Result<TermInfo> {
+        let block_id = (term_ord as usize) / BLOCK_LEN;
+        let term_info_block_data = self.block_meta(block_id);
         let inner_offset = (term_ord as usize) % BLOCK_LEN;
         if inner_offset == 0 {
-            return term_info_block_data.ref_term_info;
+            return Ok(term_info_block_data.ref_term_info);
         }
-        let term_info_data = self.term_info_bytes.as_slice();
-        term_info_block_data.deserialize_term_info(
-            &term_info_data[term_info_block_data.offset as usize..],
-            inner_offset - 1,
-        )
+        let block_start = term_info_block_data.offset as usize;
+        let term_info = match &self.term_info_bytes {
+            TermInfoBytes::Resident(term_info_bytes) => term_info_block_data.deserialize_term_info(
+                &term_info_bytes.as_slice()[block_start..],
+                inner_offset - 1,
+            ),
+            TermInfoBytes::Lazy(term_info_file) => {
+                let num_blocks = self.block_meta_bytes.len() / TermInfoBlockMeta::SIZE_IN_BYTES;
+                let block_end = if block_id + 1 < num_blocks {
+                    self.block_meta(block_id + 1).offset as usize
+                } else {
+                    term_info_file.len()
+                };
+                let block_bytes = term_info_file.read_bytes_slice(block_start..block_end)?;
+                term_info_block_data.deserialize_term_info(block_bytes.as_slice(), inner_offset - 1)
+            }
+        };
+        Ok(term_info)
     }
 
     pub fn num_terms(&self) -> usize {
@@ -354,10 +401,16 @@ mod tests {
         }
         let mut buffer = Vec::new();
         store_writer.serialize(&mut buffer)?;
-        let term_info_store = TermInfoStore::open(FileSlice::from(buffer))?;
+        let term_info_store = TermInfoStore::open(FileSlice::from(buffer.clone()))?;
+        let lazy_term_info_store = TermInfoStore::open_lazily(FileSlice::from(buffer))?;
         for i in 0..1000 {
             assert_eq!(
-                term_info_store.get(i as u64),
+                term_info_store.get(i as u64)?,
+                term_infos[i],
+                "term info {i}"
+            );
+            assert_eq!(
+                lazy_term_info_store.get(i as u64)?,
                 term_infos[i],
                 "term info {i}"
             );
@@ -1,11 +1,14 @@
 use std::io::{self, Write};
 use std::sync::Arc;
 
-use common::{BinarySerializable, CountingWriter};
+use common::{BinarySerializable, CountingWriter, HasLen};
 use once_cell::sync::Lazy;
+use tantivy_fst::automaton::AlwaysMatch;
 use tantivy_fst::raw::Fst;
 use tantivy_fst::Automaton;
 
+use super::paged_fst::PagedFst;
+use super::streamer::FstStreamBuilder;
 use super::term_info_store::{TermInfoStore, TermInfoStoreWriter};
 use super::{TermStreamer, TermStreamerBuilder};
 use crate::directory::{FileSlice, OwnedBytes};
@@ -90,7 +93,10 @@ where W: Write
 }
 
 fn open_fst_index(fst_file: FileSlice) -> io::Result<tantivy_fst::Map<OwnedBytes>> {
-    let bytes = fst_file.read_bytes()?;
+    open_fst_index_from_bytes(fst_file.read_bytes()?)
+}
+
+fn open_fst_index_from_bytes(bytes: OwnedBytes) -> io::Result<tantivy_fst::Map<OwnedBytes>> {
     let fst = Fst::new(bytes).map_err(|err| {
         io::Error::new(
             io::ErrorKind::InvalidData,
@@ -100,6 +106,22 @@ fn open_fst_index(fst_file: FileSlice) -> io::Result<tantivy_fst::Map<OwnedBytes
     Ok(tantivy_fst::Map::from(fst))
 }
 
+/// Splits the file of a term dictionary into the file of its FST and the file of its term
+/// infos.
+fn split_fst_and_values(file: FileSlice) -> io::Result<(FileSlice, FileSlice)> {
+    let (main_slice, footer_len_slice) = file.split_from_end(12);
+    let mut footer_len_bytes = footer_len_slice.read_bytes()?;
+    let footer_size = u64::deserialize(&mut footer_len_bytes)?;
+    let version = u32::deserialize(&mut footer_len_bytes)?;
+    if version != FST_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Unsupported fst version, expected {version}, found {FST_VERSION}",),
+        ));
+    }
+    Ok(main_slice.split_from_end(footer_size as usize))
+}
+
 static EMPTY_TERM_DICT_FILE: Lazy<FileSlice> = Lazy::new(|| {
     let term_dictionary_data: Vec<u8> = TermDictionaryBuilder::create(Vec::<u8>::new())
         .expect("Creating a TermDictionaryBuilder in a Vec<u8> should never fail")
@@ -108,6 +130,14 @@ static EMPTY_TERM_DICT_FILE: Lazy<FileSlice> = Lazy::new(|| {
     FileSlice::from(term_dictionary_data)
 });
 
+/// The FST of a term dictionary, either as a contiguous slice of bytes, or traversed from its
+/// file when it is larger than the memory it is allowed to use.
+#[derive(Clone)]
+enum FstIndex {
+    Resident(Arc<tantivy_fst::Map<OwnedBytes>>),
+    Paged(Arc<PagedFst>),
+}
+
 /// The term dictionary contains all of the terms in
 /// `tantivy index` in a sorted manner.
 ///
@@ -116,29 +146,58 @@ static EMPTY_TERM_DICT_FILE: Lazy<FileSlice> = Lazy::new(|| {
 /// possible to fetch the associated `TermInfo`.
 #[derive(Clone)]
 pub struct TermDictionary {
-    fst_index: Arc<tantivy_fst::Map<OwnedBytes>>,
+    fst_index: FstIndex,
     term_info_store: TermInfoStore,
 }
 
 impl TermDictionary {
     /// Opens a `TermDictionary`.
     pub fn open(file: FileSlice) -> io::Result<Self> {
-        let (main_slice, footer_len_slice) = file.split_from_end(12);
-        let mut footer_len_bytes = footer_len_slice.read_bytes()?;
-        let footer_size = u64::deserialize(&mut footer_len_bytes)?;
-        let version = u32::deserialize(&mut footer_len_bytes)?;
-        if version != FST_VERSION {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("Unsupported fst version, expected {version}, found {FST_VERSION}",),
-            ));
-        }
+        Self::open_with_max_fst_bytes(file, None)
+    }
 
-        let (fst_file_slice, values_file_slice) = main_slice.split_from_end(footer_size as usize);
-        let fst_index = open_fst_index(fst_file_slice)?;
+    /// Opens a `TermDictionary`, bounding the memory used by its FST to `max_fst_bytes`.
+    ///
+    /// If the FST is not larger than `max_fst_bytes`, it is copied to the heap. Otherwise, it is
+    /// traversed from `file`, through a cache of at most `max_fst_bytes` of its pages.
+    /// Without a bound, the FST is read from `file` as a contiguous slice of bytes.
+    pub fn open_with_max_fst_bytes(
+        file: FileSlice,
+        max_fst_bytes: Option<usize>,
+    ) -> io::Result<Self> {
+        let (fst_file_slice, values_file_slice) = split_fst_and_values(file)?;
+        let fst_index = match max_fst_bytes {
+            None => FstIndex::Resident(Arc::new(open_fst_index(fst_file_slice)?)),
+            Some(max_fst_bytes) if fst_file_slice.len() <= max_fst_bytes => {
+                let fst_bytes = OwnedBytes::new(fst_file_slice.read_bytes()?.as_slice().to_vec());
+                FstIndex::Resident(Arc::new(open_fst_index_from_bytes(fst_bytes)?))
+            }
+            Some(max_fst_bytes) => {
+                FstIndex::Paged(Arc::new(PagedFst::open(fst_file_slice, max_fst_bytes)?))
+            }
+        };
         let term_info_store = TermInfoStore::open(values_file_slice)?;
         Ok(TermDictionary {
-            fst_index: Arc::new(fst_index),
+            fst_index,
+            term_info_store,
+        })
+    }
+
+    /// Opens a `TermDictionary` reading `file` only as its lookups need it.
+    ///
+    /// The FST is traversed from `file`, through a cache of at most `max_fst_bytes` of its pages,
+    /// or as many pages as it is made of without a bound. The term infos are read block by
+    /// block.
+    ///
+    /// This is meant for a `file` whose reads are costly, such as a compressed dictionary, which
+    /// is decompressed as it is read.
+    pub fn open_lazily(file: FileSlice, max_fst_bytes: Option<usize>) -> io::Result<Self> {
+        let (fst_file_slice, values_file_slice) = split_fst_and_values(file)?;
+        let max_fst_bytes = max_fst_bytes.unwrap_or(fst_file_slice.len());
+        let fst_index = FstIndex::Paged(Arc::new(PagedFst::open(fst_file_slice, max_fst_bytes)?));
+        let term_info_store = TermInfoStore::open_lazily(values_file_slice)?;
+        Ok(TermDictionary {
+            fst_index,
             term_info_store,
         })
     }
@@ -156,7 +215,10 @@ impl TermDictionary {
 
     /// Returns the ordinal associated with a given term.
     pub fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermOrdinal>> {
-        Ok(self.fst_index.get(key))
+        match &self.fst_index {
+            FstIndex::Resident(fst_index) => Ok(fst_index.get(key)),
+            FstIndex::Paged(fst_index) => fst_index.get(key.as_ref()),
+        }
     }
 
     /// Stores the term associated with a given term ordinal in
@@ -170,8 +232,12 @@ impl TermDictionary {
     /// Regardless of whether the term is found or not,
     /// the buffer may be modified.
     pub fn ord_to_term(&self, mut ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool> {
+        let fst_index = match &self.fst_index {
+            FstIndex::Resident(fst_index) => fst_index,
+            FstIndex::Paged(fst_index) => return fst_index.ord_to_term(ord, bytes),
+        };
         bytes.clear();
-        let fst = self.fst_index.as_fst();
+        let fst = fst_index.as_fst();
         let mut node = fst.root();
         while ord != 0 || !node.is_final() {
             if let Some(transition) = node
@@ -190,22 +256,25 @@ impl TermDictionary {
         Ok(true)
     }
 
-    /// Returns the number of terms in the dictionary.
-    pub fn term_info_from_ord(&self, term_ord: TermOrdinal) -> TermInfo {
+    /// Returns the `TermInfo` of the term of ordinal `term_ord`.
+    ///
+    /// Returns an error if the term infos cannot be read, which may happen when they are read
+    /// block by block from a compressed dictionary.
+    pub fn term_info_from_ord(&self, term_ord: TermOrdinal) -> io::Result<TermInfo> {
         self.term_info_store.get(term_ord)
     }
 
     /// Lookups the value corresponding to the key.
     pub fn get<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermInfo>> {
-        Ok(self
-            .term_ord(key)?
-            .map(|term_ord| self.term_info_from_ord(term_ord)))
+        self.term_ord(key)?
+            .map(|term_ord| self.term_info_store.get(term_ord))
+            .transpose()
     }
 
     /// Returns a range builder, to stream all of the terms
     /// within an interval.
     pub fn range(&self) -> TermStreamerBuilder<'_> {
-        TermStreamerBuilder::new(self, self.fst_index.range())
+        self.search(AlwaysMatch)
     }
 
     /// A stream of all the sorted terms.
@@ -216,7 +285,12 @@ impl TermDictionary {
     /// Returns a search builder, to stream all of the terms
     /// within the Automaton
     pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermStreamerBuilder<'a, A> {
-        let stream_builder = self.fst_index.search(automaton);
+        let stream_builder = match &self.fst_index {
+            FstIndex::Resident(fst_index) => {
+                FstStreamBuilder::Resident(fst_index.search(automaton))
+            }
+            FstIndex::Paged(fst_index) => FstStreamBuilder::Paged(fst_index.search(automaton)),
+        };
         TermStreamerBuilder::<A>::new(self, stream_builder)
     }
 }
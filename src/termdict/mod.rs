@@ -17,6 +17,27 @@
 //! as `u64`.
 //!
 //! A second datastructure makes it possible to access a [`TermInfo`].
+//!
+//! # Memory
+//!
+//! By default, the FST is read from the file of the segment as a contiguous slice of bytes. How
+//! much of it is resident is up to the [`Directory`](crate::Directory): with the
+//! `MmapDirectory`, it is made of pages the operating system loads on access, and reclaims
+//! under memory pressure.
+//!
+//! [`TermDictionaryAccess::Capped`] bounds the memory used by the FST instead: an FST larger
+//! than the cap is traversed node by node from its file, through a page cache no larger than the
+//! cap. Lookups and streams are slower this way, since each node is decoded as it is reached,
+//! but queries walking a large portion of the dictionary, like fuzzy or regex queries, keep
+//! their memory bounded.
+//!
+//! The SSTable based dictionary, enabled by the `quickwit` feature, only keeps its block index in
+//! memory, and reads the blocks it needs from the file slice on each access.
+//!
+//! A dictionary written with a compressor, see
+//! [`TextFieldIndexing::set_term_dict_compressor`](crate::schema::TextFieldIndexing::set_term_dict_compressor),
+//! is compressed in independent blocks of a few kilobytes. Its lookups only decompress the blocks
+//! they read, and a handful of the decompressed blocks are cached.
 
 #[cfg(not(feature = "quickwit"))]
 mod fst_termdict;
@@ -28,6 +49,7 @@ mod sstable_termdict;
 #[cfg(feature = "quickwit")]
 use sstable_termdict as termdict;
 
+mod compressed_file;
 #[cfg(test)]
 mod tests;
 
@@ -35,23 +57,28 @@ mod tests;
 pub type TermOrdinal = u64;
 
 use std::io;
+use std::sync::Arc;
 
 use common::file_slice::FileSlice;
 use common::BinarySerializable;
 use tantivy_fst::Automaton;
 
+use self::compressed_file::{write_compressed, CompressedFile};
 use self::termdict::{
     TermDictionary as InnerTermDict, TermDictionaryBuilder as InnerTermDictBuilder,
     TermStreamerBuilder,
 };
 pub use self::termdict::{TermMerger, TermStreamer};
 use crate::postings::TermInfo;
+use crate::store::Compressor;
 
 #[derive(Debug, Eq, PartialEq)]
 #[repr(u32)]
 enum DictionaryType {
     Fst = 1,
     SSTable = 2,
+    /// A compressed dictionary of the `CURRENT_TYPE`.
+    Compressed = 3,
 }
 
 impl TryFrom<u32> for DictionaryType {
@@ -61,6 +88,7 @@ impl TryFrom<u32> for DictionaryType {
         match value {
             1 => Ok(DictionaryType::Fst),
             2 => Ok(DictionaryType::SSTable),
+            3 => Ok(DictionaryType::Compressed),
             _ => Err("Invalid value for DictionaryType"),
         }
     }
@@ -72,6 +100,42 @@ const CURRENT_TYPE: DictionaryType = DictionaryType::Fst;
 #[cfg(feature = "quickwit")]
 const CURRENT_TYPE: DictionaryType = DictionaryType::SSTable;
 
+/// How the term dictionary of a field is accessed once its segment is opened, see
+/// [`IndexReaderBuilder::term_dictionary_access`](crate::IndexReaderBuilder::term_dictionary_access).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TermDictionaryAccess {
+    /// The dictionary is read from the file of the segment as the lookups need it. With the
+    /// `MmapDirectory`, it is memory-mapped: the pages of a cold dictionary are only loaded by
+    /// the lookups, and may be evicted by the OS.
+    #[default]
+    Mmap,
+    /// The dictionary is copied to the heap when its segment is opened, so that the lookups
+    /// never hit the disk.
+    ///
+    /// This costs as much memory as the term dictionary of the field in each segment, as
+    /// reported by [`Searcher::space_usage`](crate::Searcher::space_usage), for as long as the
+    /// segment is open.
+    FullLoad,
+    /// The memory used by the FST of the dictionary is bounded to `max_fst_bytes`.
+    ///
+    /// An FST no larger than `max_fst_bytes` is copied to the heap when its segment is opened.
+    /// A larger FST is traversed from the file of the segment, through a cache of at most
+    /// `max_fst_bytes` of its pages, which makes its lookups slower.
+    ///
+    /// With the `MmapDirectory`, the pages of the FST are slices of the memory map rather than
+    /// copies: the cap bounds the pages the dictionary refers to, but the OS still decides
+    /// which pages of the file are resident. The cap bounds the heap memory of directories
+    /// reading their files in buffers, and of compressed dictionaries, whose FST is always
+    /// traversed from its decompressed blocks.
+    ///
+    /// The SSTable based dictionary of the `quickwit` feature is accessed as with
+    /// [`TermDictionaryAccess::Mmap`].
+    Capped {
+        /// Maximum number of bytes of the FST kept in memory.
+        max_fst_bytes: usize,
+    },
+}
+
 // TODO in the future this should become an enum of supported dictionaries
 /// A TermDictionary wrapping either an FST based dictionary or a SSTable based one.
 #[derive(Clone)]
@@ -80,6 +144,10 @@ pub struct TermDictionary(InnerTermDict);
 impl TermDictionary {
     /// Opens a `TermDictionary`.
     pub fn open(file: FileSlice) -> io::Result<Self> {
+        Self::open_with_max_fst_bytes(file, None)
+    }
+
+    fn open_with_max_fst_bytes(file: FileSlice, max_fst_bytes: Option<usize>) -> io::Result<Self> {
         let (main_slice, dict_type) = file.split_from_end(4);
         let mut dict_type = dict_type.read_bytes()?;
         let dict_type = u32::deserialize(&mut dict_type)?;
@@ -90,6 +158,10 @@ impl TermDictionary {
             )
         })?;
 
+        if dict_type == DictionaryType::Compressed {
+            return Self::open_compressed(main_slice, max_fst_bytes);
+        }
+
         if dict_type != CURRENT_TYPE {
             return Err(io::Error::new(
                 io::ErrorKind::Other,
@@ -100,7 +172,51 @@ impl TermDictionary {
             ));
         }
 
-        InnerTermDict::open(main_slice).map(TermDictionary)
+        #[cfg(not(feature = "quickwit"))]
+        let inner_term_dict = InnerTermDict::open_with_max_fst_bytes(main_slice, max_fst_bytes)?;
+        #[cfg(feature = "quickwit")]
+        let inner_term_dict = InnerTermDict::open(main_slice)?;
+        Ok(TermDictionary(inner_term_dict))
+    }
+
+    /// Opens a `TermDictionary` with the given [`TermDictionaryAccess`].
+    pub fn open_with_access(file: FileSlice, access: TermDictionaryAccess) -> io::Result<Self> {
+        match access {
+            TermDictionaryAccess::Mmap => Self::open(file),
+            TermDictionaryAccess::FullLoad => {
+                let term_dictionary_data = file.read_bytes()?.as_slice().to_vec();
+                Self::open(FileSlice::from(term_dictionary_data))
+            }
+            TermDictionaryAccess::Capped { max_fst_bytes } => {
+                Self::open_with_max_fst_bytes(file, Some(max_fst_bytes))
+            }
+        }
+    }
+
+    /// Opens a dictionary written with a compressor.
+    ///
+    /// The dictionary is compressed in independent blocks, which are decompressed as the lookups
+    /// read them: the FST is traversed through a page cache, as with
+    /// [`TermDictionaryAccess::Capped`], and the term infos are read block by block.
+    #[cfg_attr(feature = "quickwit", allow(unused_variables))]
+    fn open_compressed(file: FileSlice, max_fst_bytes: Option<usize>) -> io::Result<Self> {
+        let compressed_file = CompressedFile::open(file)?;
+        let (main_slice, dict_type) = FileSlice::new(Arc::new(compressed_file)).split_from_end(4);
+        let dict_type = u32::deserialize(&mut dict_type.read_bytes()?)?;
+        if dict_type != CURRENT_TYPE as u32 {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "Unsupported compressed dictionary type, compiled tantivy with \
+                     {CURRENT_TYPE:?}, but got {dict_type}",
+                ),
+            ));
+        }
+        #[cfg(not(feature = "quickwit"))]
+        let inner_term_dict = InnerTermDict::open_lazily(main_slice, max_fst_bytes)?;
+        #[cfg(feature = "quickwit")]
+        let inner_term_dict = InnerTermDict::open(main_slice)?;
+        Ok(TermDictionary(inner_term_dict))
     }
 
     /// Creates an empty term dictionary which contains no terms.
@@ -186,13 +302,84 @@ impl TermDictionary {
     }
 }
 
+/// Merges several term dictionaries into a single stream of sorted unique terms.
+///
+/// Each term is yielded once, along with the sorted indexes in `dicts` of the dictionaries
+/// containing it. The dictionaries are streamed together, through a k-way merge: this does not
+/// load them in memory.
+///
+/// The dictionaries can belong to different segments, fields or indexes. This makes it possible
+/// to compute statistics across segments or shards.
+pub fn merge_term_dicts<'a>(
+    dicts: &[&'a TermDictionary],
+) -> io::Result<impl Iterator<Item = (Vec<u8>, Vec<usize>)> + 'a> {
+    let streams = dicts
+        .iter()
+        .map(|dict| dict.stream())
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut term_merger = TermMerger::new(streams);
+    Ok(std::iter::from_fn(move || {
+        if !term_merger.advance() {
+            return None;
+        }
+        let dict_ords = term_merger.current_segment_ords().collect();
+        Some((term_merger.key().to_vec(), dict_ords))
+    }))
+}
+
+/// Destination of the bytes of a term dictionary.
+///
+/// With a compressor, the dictionary is buffered in memory until it is finished,
+/// and then written compressed, block by block.
+enum TermDictionaryWrite<W: io::Write> {
+    Plain(W),
+    Compressed {
+        buffer: Vec<u8>,
+        compressor: Compressor,
+        wrt: W,
+    },
+}
+
+impl<W: io::Write> io::Write for TermDictionaryWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            TermDictionaryWrite::Plain(wrt) => wrt.write(buf),
+            TermDictionaryWrite::Compressed { buffer, .. } => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            TermDictionaryWrite::Plain(wrt) => wrt.flush(),
+            TermDictionaryWrite::Compressed { .. } => Ok(()),
+        }
+    }
+}
+
 /// A TermDictionaryBuilder wrapping either an FST or a SSTable dictionary builder.
-pub struct TermDictionaryBuilder<W: io::Write>(InnerTermDictBuilder<W>);
+pub struct TermDictionaryBuilder<W: io::Write>(InnerTermDictBuilder<TermDictionaryWrite<W>>);
 
 impl<W: io::Write> TermDictionaryBuilder<W> {
     /// Creates a new `TermDictionaryBuilder`
     pub fn create(w: W) -> io::Result<Self> {
-        InnerTermDictBuilder::create(w).map(TermDictionaryBuilder)
+        InnerTermDictBuilder::create(TermDictionaryWrite::Plain(w)).map(TermDictionaryBuilder)
+    }
+
+    /// Creates a new `TermDictionaryBuilder` compressing the dictionary with `compressor`.
+    ///
+    /// [`Compressor::None`] is equivalent to [`TermDictionaryBuilder::create`].
+    /// Otherwise, the dictionary is buffered in memory until [`TermDictionaryBuilder::finish`]
+    /// is called.
+    pub fn create_with_compressor(w: W, compressor: Compressor) -> io::Result<Self> {
+        if compressor == Compressor::None {
+            return Self::create(w);
+        }
+        let term_dictionary_write = TermDictionaryWrite::Compressed {
+            buffer: Vec::new(),
+            compressor,
+            wrt: w,
+        };
+        InnerTermDictBuilder::create(term_dictionary_write).map(TermDictionaryBuilder)
     }
 
     /// Inserts a `(key, value)` pair in the term dictionary.
@@ -225,6 +412,17 @@ impl<W: io::Write> TermDictionaryBuilder<W> {
     pub fn finish(self) -> io::Result<W> {
         let mut writer = self.0.finish()?;
         (CURRENT_TYPE as u32).serialize(&mut writer)?;
-        Ok(writer)
+        match writer {
+            TermDictionaryWrite::Plain(wrt) => Ok(wrt),
+            TermDictionaryWrite::Compressed {
+                buffer,
+                compressor,
+                mut wrt,
+            } => {
+                write_compressed(&buffer, compressor, &mut wrt)?;
+                (DictionaryType::Compressed as u32).serialize(&mut wrt)?;
+                Ok(wrt)
+            }
+        }
     }
 }
@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
+use std::io;
 
 use crate::postings::TermInfo;
 use crate::termdict::TermStreamer;
@@ -100,11 +101,23 @@ impl<'a> TermMerger<'a> {
     /// This method may be called
     /// if and only if advance() has been called before
     /// and "true" was returned.
-    pub fn current_segment_ords_and_term_infos<'b: 'a>(
-        &'b self,
-    ) -> impl 'b + Iterator<Item = (usize, TermInfo)> {
+    pub fn current_segment_ords_and_term_infos(
+        &self,
+    ) -> impl '_ + Iterator<Item = io::Result<(usize, TermInfo)>> {
         self.current_streamers
             .iter()
-            .map(|heap_item| (heap_item.segment_ord, heap_item.streamer.value().clone()))
+            .map(|heap_item| Ok((heap_item.segment_ord, heap_item.streamer.value().clone())))
+    }
+
+    /// Returns the sorted list of segment ordinals
+    /// that include the current term.
+    ///
+    /// This method may be called
+    /// if and only if advance() has been called before
+    /// and "true" was returned.
+    pub(crate) fn current_segment_ords(&self) -> impl '_ + Iterator<Item = usize> {
+        self.current_streamers
+            .iter()
+            .map(|heap_item| heap_item.segment_ord)
     }
 }
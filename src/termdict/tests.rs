@@ -429,3 +429,272 @@ fn test_automaton_search() -> crate::Result<()> {
     assert!(!range.advance());
     Ok(())
 }
+
+#[cfg(feature = "lz4-compression")]
+#[test]
+fn test_term_dictionary_compressed() -> crate::Result<()> {
+    use super::TermDictionaryAccess;
+    use crate::store::Compressor;
+
+    // Large enough to be compressed in several blocks.
+    let terms: Vec<String> = (0..20_000).map(|i| format!("term-{i:06}")).collect();
+    let mut term_dictionary_builder =
+        TermDictionaryBuilder::create_with_compressor(Vec::new(), Compressor::Lz4)?;
+    for (term_ord, term) in terms.iter().enumerate() {
+        term_dictionary_builder.insert(term.as_bytes(), &make_term_info(term_ord as u64))?;
+    }
+    let buffer: Vec<u8> = term_dictionary_builder.finish()?;
+    // The compressed dictionary is followed by the id of its decompressor and the dictionary
+    // type.
+    let mut corrupted_buffer = buffer.clone();
+    let decompressor_id_pos = corrupted_buffer.len() - 5;
+    corrupted_buffer[decompressor_id_pos] = 42;
+    let err = TermDictionary::open(FileSlice::from(corrupted_buffer))
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let file = FileSlice::from(buffer);
+    let term_dicts = [
+        TermDictionary::open(file.clone())?,
+        // The FST of a compressed dictionary is always paged, the cap bounds its page cache.
+        TermDictionary::open_with_access(file, TermDictionaryAccess::Capped { max_fst_bytes: 0 })?,
+    ];
+    for term_dict in &term_dicts {
+        assert_eq!(term_dict.num_terms(), terms.len());
+        for (term_ord, term) in terms.iter().enumerate() {
+            assert_eq!(term_dict.term_ord(term)?, Some(term_ord as u64));
+            assert_eq!(term_dict.get(term)?, Some(make_term_info(term_ord as u64)));
+        }
+        assert!(term_dict.get("term-")?.is_none());
+        let mut term_ord = 0;
+        let mut stream = term_dict.range().ge("term-010000").into_stream()?;
+        while let Some((term, term_info)) = stream.next() {
+            assert_eq!(term, terms[10_000 + term_ord].as_bytes());
+            assert_eq!(term_info, &make_term_info(10_000 + term_ord as u64));
+            term_ord += 1;
+        }
+        assert_eq!(term_ord, 10_000);
+    }
+    Ok(())
+}
+
+fn dictionary_of(terms: &[&str]) -> io::Result<TermDictionary> {
+    let mut term_dictionary_builder = TermDictionaryBuilder::create(Vec::new())?;
+    for (term_ord, term) in terms.iter().enumerate() {
+        term_dictionary_builder.insert(term.as_bytes(), &make_term_info(term_ord as u64))?;
+    }
+    let buffer = term_dictionary_builder.finish()?;
+    TermDictionary::open(FileSlice::from(buffer))
+}
+
+#[test]
+fn test_merge_term_dicts() -> io::Result<()> {
+    let first = dictionary_of(&["apple", "banana", "cherry"])?;
+    let second = dictionary_of(&["banana", "date"])?;
+    let third = dictionary_of(&["apple", "banana", "elderberry"])?;
+    let merged: Vec<(String, Vec<usize>)> =
+        super::merge_term_dicts(&[&first, &second, &TermDictionary::empty(), &third])?
+            .map(|(term, dict_ords)| (String::from_utf8(term).unwrap(), dict_ords))
+            .collect();
+    assert_eq!(
+        merged,
+        vec![
+            ("apple".to_string(), vec![0, 3]),
+            ("banana".to_string(), vec![0, 1, 3]),
+            ("cherry".to_string(), vec![0]),
+            ("date".to_string(), vec![1]),
+            ("elderberry".to_string(), vec![3]),
+        ]
+    );
+    assert!(super::merge_term_dicts(&[])?.next().is_none());
+    Ok(())
+}
+
+#[cfg(not(feature = "quickwit"))]
+#[test]
+fn test_term_dictionary_capped_fst_read_error() -> crate::Result<()> {
+    use std::ops::Range;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use common::HasLen;
+
+    use super::TermDictionaryAccess;
+    use crate::directory::{FileHandle, OwnedBytes};
+
+    /// A file whose reads fail once `fail` is set.
+    #[derive(Debug)]
+    struct FailingFile {
+        bytes: OwnedBytes,
+        fail: Arc<AtomicBool>,
+    }
+
+    impl HasLen for FailingFile {
+        fn len(&self) -> usize {
+            self.bytes.len()
+        }
+    }
+
+    impl FileHandle for FailingFile {
+        fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(io::Error::other("read failure"));
+            }
+            Ok(self.bytes.slice(range))
+        }
+    }
+
+    let mut term_dictionary_builder = TermDictionaryBuilder::create(Vec::new())?;
+    for term_ord in 0u64..3_000 {
+        let term = (term_ord * term_ord * 31).to_be_bytes();
+        term_dictionary_builder.insert(term, &make_term_info(term_ord))?;
+    }
+    let fail = Arc::new(AtomicBool::new(false));
+    let file = FileSlice::new(Arc::new(FailingFile {
+        bytes: OwnedBytes::new(term_dictionary_builder.finish()?),
+        fail: fail.clone(),
+    }));
+    // The FST is traversed from the file, through a single page.
+    let term_dict =
+        TermDictionary::open_with_access(file, TermDictionaryAccess::Capped { max_fst_bytes: 0 })?;
+    let mut stream = term_dict.stream()?;
+    assert!(stream.try_advance()?);
+    fail.store(true, Ordering::SeqCst);
+    let err = loop {
+        match stream.try_advance() {
+            Ok(has_term) => assert!(has_term, "the stream ended without reading another page"),
+            Err(err) => break err,
+        }
+    };
+    assert_eq!(err.to_string(), "read failure");
+    assert!(term_dict.term_ord(1u64.to_be_bytes()).is_err());
+    Ok(())
+}
+
+#[cfg(not(feature = "quickwit"))]
+#[test]
+fn test_term_dictionary_capped_fst() -> crate::Result<()> {
+    use std::collections::BTreeSet;
+    use std::ops::{Bound, RangeBounds};
+
+    use levenshtein_automata::LevenshteinAutomatonBuilder;
+    use tantivy_fst::Automaton;
+
+    use super::{TermDictionaryAccess, TermStreamerBuilder};
+    use crate::query::DfaWrapper;
+
+    // The empty term, terms sharing long prefixes, and binary terms with nodes of many
+    // transitions.
+    let mut terms: BTreeSet<Vec<u8>> = BTreeSet::new();
+    terms.insert(Vec::new());
+    for i in 0u64..3_000 {
+        terms.insert(format!("word{}", i * 7 % 1_000).into_bytes());
+        terms.insert((i * i * 31).to_be_bytes()[4..].to_vec());
+    }
+    let terms: Vec<Vec<u8>> = terms.into_iter().collect();
+    let mut term_dictionary_builder = TermDictionaryBuilder::create(Vec::new())?;
+    for (term_ord, term) in terms.iter().enumerate() {
+        term_dictionary_builder.insert(term, &make_term_info(term_ord as u64))?;
+    }
+    let file = FileSlice::from(term_dictionary_builder.finish()?);
+    let term_dicts = [
+        TermDictionary::open(file.clone())?,
+        // The FST is traversed from the file, through a single page.
+        TermDictionary::open_with_access(
+            file.clone(),
+            TermDictionaryAccess::Capped { max_fst_bytes: 0 },
+        )?,
+        TermDictionary::open_with_access(
+            file,
+            TermDictionaryAccess::Capped {
+                max_fst_bytes: usize::MAX,
+            },
+        )?,
+    ];
+
+    let collect = |mut stream: TermStreamer<'_, DfaWrapper>| {
+        let mut keys = Vec::new();
+        while stream.advance() {
+            assert_eq!(stream.value(), &make_term_info(stream.term_ord()));
+            keys.push((stream.key().to_vec(), stream.term_ord()));
+        }
+        keys
+    };
+    fn with_bounds<'a>(
+        builder: TermStreamerBuilder<'a, DfaWrapper>,
+        lower: Bound<&[u8]>,
+        upper: Bound<&[u8]>,
+    ) -> TermStreamerBuilder<'a, DfaWrapper> {
+        let builder = match lower {
+            Bound::Included(lower) => builder.ge(lower),
+            Bound::Excluded(lower) => builder.gt(lower),
+            Bound::Unbounded => builder,
+        };
+        match upper {
+            Bound::Included(upper) => builder.le(upper),
+            Bound::Excluded(upper) => builder.lt(upper),
+            Bound::Unbounded => builder,
+        }
+    }
+    let lev_automaton_builder = LevenshteinAutomatonBuilder::new(1, true);
+    type Bounds<'a> = (Bound<&'a [u8]>, Bound<&'a [u8]>);
+    let bounds: [Bounds; 5] = [
+        (Bound::Unbounded, Bound::Unbounded),
+        (Bound::Included(b"word5"), Bound::Excluded(b"word60")),
+        (Bound::Excluded(b"word5"), Bound::Included(b"word60")),
+        (Bound::Included(&[0, 0, 1]), Bound::Included(b"word")),
+        (Bound::Excluded(b""), Bound::Excluded(&[0, 5, 2, 3])),
+    ];
+    for query in ["word12", "word", "", "zzz"] {
+        let automaton = || DfaWrapper(lev_automaton_builder.build_dfa(query));
+        let is_match = |key: &[u8]| {
+            let automaton = automaton();
+            let state = key
+                .iter()
+                .fold(automaton.start(), |state, &b| automaton.accept(&state, b));
+            automaton.is_match(&state)
+        };
+        for (lower, upper) in bounds {
+            let expected: Vec<(Vec<u8>, u64)> = terms
+                .iter()
+                .enumerate()
+                .filter(|(_, term)| (lower, upper).contains(&term.as_slice()) && is_match(term))
+                .map(|(term_ord, term)| (term.clone(), term_ord as u64))
+                .collect();
+            let expected_backward: Vec<(Vec<u8>, u64)> = expected.iter().rev().cloned().collect();
+            for term_dict in &term_dicts {
+                let forward =
+                    with_bounds(term_dict.search(automaton()), lower, upper).into_stream()?;
+                assert_eq!(collect(forward), expected);
+                let backward = with_bounds(term_dict.search(automaton()), lower, upper)
+                    .backward()
+                    .into_stream()?;
+                assert_eq!(collect(backward), expected_backward);
+            }
+        }
+    }
+
+    for term_dict in &term_dicts {
+        assert_eq!(term_dict.num_terms(), terms.len());
+        let mut bytes = Vec::new();
+        for (term_ord, term) in terms.iter().enumerate() {
+            assert_eq!(term_dict.term_ord(term)?, Some(term_ord as u64));
+            assert_eq!(term_dict.get(term)?, Some(make_term_info(term_ord as u64)));
+            assert!(term_dict.ord_to_term(term_ord as u64, &mut bytes)?);
+            assert_eq!(&bytes, term);
+        }
+        assert_eq!(term_dict.term_ord("word1000")?, None);
+        assert_eq!(term_dict.term_ord("wor")?, None);
+        assert!(!term_dict.ord_to_term(terms.len() as u64, &mut bytes)?);
+        let all_terms: Vec<Vec<u8>> = {
+            let mut stream = term_dict.stream()?;
+            let mut keys = Vec::new();
+            while stream.advance() {
+                keys.push(stream.key().to_vec());
+            }
+            keys
+        };
+        assert_eq!(all_terms, terms);
+    }
+    Ok(())
+}
@@ -0,0 +1,263 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::{Token, TokenStream, Tokenizer};
+
+/// Tokenizer suited for text written in Chinese, Japanese or Korean (CJK),
+/// where words are not separated by whitespaces.
+///
+/// Runs of CJK characters are chopped into overlapping bigrams.
+/// A CJK run made of a single character is emitted as is.
+///
+/// Other alphanumeric characters are tokenized like in the [`SimpleTokenizer`],
+/// i.e. the text is split on whitespaces, punctuation and script changes.
+///
+/// Phrase queries over bigrams behave like substring matching,
+/// so the indexed field should be configured with positions.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let mut tokenizer = CjkBigramTokenizer::default();
+/// let mut stream = tokenizer.token_stream("東京都 in Japan");
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "東京");
+///     assert_eq!(token.offset_from, 0);
+///     assert_eq!(token.offset_to, 6);
+/// }
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "京都");
+///     assert_eq!(token.offset_from, 3);
+///     assert_eq!(token.offset_to, 9);
+/// }
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "in");
+/// }
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "Japan");
+/// }
+/// assert!(stream.next().is_none());
+/// ```
+///
+/// [`SimpleTokenizer`]: super::SimpleTokenizer
+#[derive(Clone, Default)]
+pub struct CjkBigramTokenizer {
+    token: Token,
+}
+
+/// TokenStream produced by the `CjkBigramTokenizer`.
+pub struct CjkBigramTokenStream<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    // Offset of the last CJK character emitted as the end of a bigram,
+    // if the CJK run is still ongoing.
+    cjk_run_offset: Option<usize>,
+    token: &'a mut Token,
+}
+
+impl Tokenizer for CjkBigramTokenizer {
+    type TokenStream<'a> = CjkBigramTokenStream<'a>;
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> CjkBigramTokenStream<'a> {
+        self.token.reset();
+        CjkBigramTokenStream {
+            text,
+            chars: text.char_indices().peekable(),
+            cjk_run_offset: None,
+            token: &mut self.token,
+        }
+    }
+}
+
+/// Returns true if the character belongs to one of the CJK scripts.
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x11FF // Hangul Jamo
+        | 0x2E80..=0x2FDF // CJK Radicals
+        | 0x3040..=0x309F // Hiragana
+        | 0x30A0..=0x30FF // Katakana
+        | 0x3100..=0x312F // Bopomofo
+        | 0x3130..=0x318F // Hangul Compatibility Jamo
+        | 0x31F0..=0x31FF // Katakana Phonetic Extensions
+        | 0x3400..=0x4DBF // CJK Unified Ideographs Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xA960..=0xA97F // Hangul Jamo Extended-A
+        | 0xAC00..=0xD7AF // Hangul Syllables
+        | 0xD7B0..=0xD7FF // Hangul Jamo Extended-B
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+        | 0xFF66..=0xFF9F // Halfwidth Katakana
+        | 0x20000..=0x2FA1F // CJK Unified Ideographs Extension B to F and Supplement
+    )
+}
+
+impl CjkBigramTokenStream<'_> {
+    // Emits the bigram starting at `offset_from` if the next character is a CJK character.
+    fn emit_bigram(&mut self, offset_from: usize) -> bool {
+        let Some(&(next_offset, next_char)) = self.chars.peek() else {
+            return false;
+        };
+        if !is_cjk(next_char) {
+            return false;
+        }
+        self.chars.next();
+        self.cjk_run_offset = Some(next_offset);
+        self.set_token(offset_from, next_offset + next_char.len_utf8());
+        true
+    }
+
+    // search for the end of the current non-CJK token.
+    fn search_token_end(&mut self) -> usize {
+        while let Some(&(offset, c)) = self.chars.peek() {
+            if !c.is_alphanumeric() || is_cjk(c) {
+                return offset;
+            }
+            self.chars.next();
+        }
+        self.text.len()
+    }
+
+    fn set_token(&mut self, offset_from: usize, offset_to: usize) {
+        self.token.offset_from = offset_from;
+        self.token.offset_to = offset_to;
+        self.token.text.push_str(&self.text[offset_from..offset_to]);
+    }
+}
+
+impl TokenStream for CjkBigramTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        self.token.position = self.token.position.wrapping_add(1);
+        if let Some(offset_from) = self.cjk_run_offset.take() {
+            if self.emit_bigram(offset_from) {
+                return true;
+            }
+        }
+        while let Some((offset_from, c)) = self.chars.next() {
+            if is_cjk(c) {
+                if !self.emit_bigram(offset_from) {
+                    self.set_token(offset_from, offset_from + c.len_utf8());
+                }
+                return true;
+            }
+            if c.is_alphanumeric() {
+                let offset_to = self.search_token_end();
+                self.set_token(offset_from, offset_to);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{CjkBigramTokenizer, TextAnalyzer, Token, TokenizerManager};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_cjk_bigram_tokenizer() {
+        let tokens = token_stream_helper("東京都");
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "東京", 0, 6);
+        assert_token(&tokens[1], 1, "京都", 3, 9);
+    }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer_mixed_scripts() {
+        let tokens = token_stream_helper("Hello 世界, tantivyは検索エンジン 2024年!");
+        assert_eq!(tokens.len(), 11);
+        assert_token(&tokens[0], 0, "Hello", 0, 5);
+        assert_token(&tokens[1], 1, "世界", 6, 12);
+        assert_token(&tokens[2], 2, "tantivy", 14, 21);
+        assert_token(&tokens[3], 3, "は検", 21, 27);
+        assert_token(&tokens[4], 4, "検索", 24, 30);
+        assert_token(&tokens[5], 5, "索エ", 27, 33);
+        assert_token(&tokens[6], 6, "エン", 30, 36);
+        assert_token(&tokens[7], 7, "ンジ", 33, 39);
+        assert_token(&tokens[8], 8, "ジン", 36, 42);
+        assert_token(&tokens[9], 9, "2024", 43, 47);
+        assert_token(&tokens[10], 10, "年", 47, 50);
+    }
+
+    #[test]
+    fn test_cjk_bigram_tokenizer_single_chars() {
+        let tokens = token_stream_helper("中 文。한");
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "中", 0, 3);
+        assert_token(&tokens[1], 1, "文", 4, 7);
+        assert_token(&tokens[2], 2, "한", 10, 13);
+        assert!(token_stream_helper("").is_empty());
+        assert!(token_stream_helper(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_cjk_tokenizer_query_matching() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("cjk")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "東京都庁は新宿にあります"))?;
+        index_writer.add_document(doc!(text => "京都 Kyoto station"))?;
+        index_writer.add_document(doc!(text => "서울특별시"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        let count = |query: &str| -> crate::Result<usize> {
+            let query = query_parser.parse_query(query)?;
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count("東京")?, 1);
+        assert_eq!(count("京都")?, 2);
+        assert_eq!(count("新宿")?, 1);
+        assert_eq!(count("東京都庁")?, 1);
+        assert_eq!(count("都京")?, 0);
+        assert_eq!(count("kyoto")?, 1);
+        assert_eq!(count("서울")?, 1);
+        assert_eq!(count("특별시")?, 1);
+        Ok(())
+    }
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let mut a = TextAnalyzer::from(CjkBigramTokenizer::default());
+        let mut token_stream = a.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_cjk_tokenizer_registered() {
+        let tokenizer_manager = TokenizerManager::default();
+        let mut cjk_tokenizer = tokenizer_manager.get("cjk").unwrap();
+        let mut tokens: Vec<Token> = vec![];
+        cjk_tokenizer
+            .token_stream("Tokyo 東京")
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "tokyo", 0, 5);
+        assert_token(&tokens[1], 1, "東京", 6, 12);
+    }
+}
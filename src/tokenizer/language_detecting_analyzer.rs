@@ -0,0 +1,334 @@
+use std::sync::Arc;
+
+use rustc_hash::FxHashSet;
+
+use super::stop_word_filter::stop_words;
+use super::{
+    BoxTokenStream, Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer,
+    TokenStream, Tokenizer,
+};
+use crate::TantivyError;
+
+/// Tokenizer detecting the language of each text, and stemming the text accordingly.
+///
+/// The language is picked among a list of candidate languages. The text is then tokenized
+/// like `en_stem` does, with the stemmer of the detected language: it is chopped on
+/// punctuation and whitespaces, the tokens longer than 40 bytes are removed, and the
+/// remaining tokens are lowercased and stemmed.
+///
+/// # Detection
+///
+/// The detection is lightweight, so as to keep indexing fast. Each word counts for the
+/// languages it is a stop word of (e.g. `the`, `le` or `der`), or for the language written
+/// in its script (Greek, Cyrillic for Russian, Arabic and Tamil). The language counting the
+/// most words wins, the earliest candidate winning the ties. When no word counts, the first
+/// candidate language, the default language, is picked: this is the case of the candidates
+/// which have neither a list of stop words nor a script of their own, which are never
+/// detected.
+///
+/// Short texts hold few stop words, so the detection is reliable for sentences, not for
+/// single words. The detected language is not indexed: to record it along with the
+/// document, call [`LanguageDetectingAnalyzer::detect_language`] on the field value and
+/// add the language to another field of the document.
+///
+/// # Querying
+///
+/// The [`QueryParser`](crate::query::QueryParser) analyzes the query with the tokenizer of
+/// the field, so it detects the language of the query, and stems the query accordingly.
+/// The query parser analyzes each word of the query on its own, except within quotes:
+/// `"le chat mange"` is detected as French, while the single words `le chat mange` fall
+/// back to the default language. A match therefore requires the query to be detected as
+/// the language of the document, or its words to be stemmed the same in both languages.
+///
+/// To search all the candidate languages instead, analyze the query with the pipeline of
+/// each language, see [`LanguageDetectingAnalyzer::for_language`], and build a query
+/// matching the terms of any of them.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::BooleanQuery;
+/// use tantivy::schema::{Schema, TextFieldIndexing, TextOptions};
+/// use tantivy::tokenizer::{Language, LanguageDetectingAnalyzer};
+/// use tantivy::{doc, Index, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let analyzer = LanguageDetectingAnalyzer::new([Language::English, Language::French])?;
+/// assert_eq!(
+///     analyzer.detect_language("Le chat mange dans le jardin"),
+///     Language::French
+/// );
+///
+/// let mut schema_builder = Schema::builder();
+/// let text_options = TextOptions::default()
+///     .set_indexing_options(TextFieldIndexing::default().set_tokenizer("multilingual"));
+/// let text = schema_builder.add_text_field("text", text_options);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// index.tokenizers().register("multilingual", analyzer.clone());
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(text => "Les chats mangeaient dans le jardin"))?;
+/// index_writer.commit()?;
+///
+/// // Searches the terms of the query stemmed in each of the candidate languages.
+/// let mut terms = Vec::new();
+/// for language in analyzer.languages() {
+///     let mut language_analyzer = analyzer.for_language(language).unwrap();
+///     let mut token_stream = language_analyzer.token_stream("mangeaient");
+///     token_stream.process(&mut |token| terms.push(Term::from_field_text(text, &token.text)));
+/// }
+/// let query = BooleanQuery::new_multiterms_query(terms);
+/// let searcher = index.reader()?.searcher();
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LanguageDetectingAnalyzer {
+    languages: Arc<[CandidateLanguage]>,
+    analyzers: Vec<TextAnalyzer>,
+    detected_language: Option<Language>,
+}
+
+struct CandidateLanguage {
+    language: Language,
+    stop_words: FxHashSet<&'static str>,
+}
+
+impl LanguageDetectingAnalyzer {
+    /// Creates a `LanguageDetectingAnalyzer` picking the language among `languages`.
+    ///
+    /// The first language is the default language, picked when the detection is
+    /// inconclusive. The repeated languages are ignored.
+    ///
+    /// Returns `TantivyError::InvalidArgument` if `languages` is empty.
+    pub fn new(
+        languages: impl IntoIterator<Item = Language>,
+    ) -> crate::Result<LanguageDetectingAnalyzer> {
+        let mut candidates: Vec<CandidateLanguage> = Vec::new();
+        for language in languages {
+            if candidates
+                .iter()
+                .any(|candidate| candidate.language == language)
+            {
+                continue;
+            }
+            let stop_words = stop_words(language)
+                .unwrap_or_default()
+                .iter()
+                .copied()
+                .collect();
+            candidates.push(CandidateLanguage {
+                language,
+                stop_words,
+            });
+        }
+        if candidates.is_empty() {
+            return Err(TantivyError::InvalidArgument(
+                "The language detecting analyzer requires at least one language".to_string(),
+            ));
+        }
+        let analyzers = candidates
+            .iter()
+            .map(|candidate| stemming_analyzer(candidate.language))
+            .collect();
+        Ok(LanguageDetectingAnalyzer {
+            languages: candidates.into(),
+            analyzers,
+            detected_language: None,
+        })
+    }
+
+    /// Returns the candidate languages, starting with the default language.
+    pub fn languages(&self) -> impl Iterator<Item = Language> + '_ {
+        self.languages.iter().map(|candidate| candidate.language)
+    }
+
+    /// Returns the analyzer used for the texts detected as `language`, or `None` if
+    /// `language` is not a candidate language.
+    pub fn for_language(&self, language: Language) -> Option<TextAnalyzer> {
+        let ord = self.language_ord(language)?;
+        Some(self.analyzers[ord].clone())
+    }
+
+    /// Returns the language detected for `text`, the default language if the detection
+    /// is inconclusive.
+    pub fn detect_language(&self, text: &str) -> Language {
+        self.languages[self.detect_language_ord(text)].language
+    }
+
+    /// Returns the language detected for the last text tokenized, if any.
+    pub fn detected_language(&self) -> Option<Language> {
+        self.detected_language
+    }
+
+    fn language_ord(&self, language: Language) -> Option<usize> {
+        self.languages()
+            .position(|candidate_language| candidate_language == language)
+    }
+
+    fn detect_language_ord(&self, text: &str) -> usize {
+        let mut counts = vec![0usize; self.languages.len()];
+        let mut tokenizer = SimpleTokenizer::default();
+        let mut token_stream = tokenizer.token_stream(text);
+        let mut word = String::new();
+        while let Some(token) = token_stream.next() {
+            word.clear();
+            word.extend(token.text.chars().flat_map(char::to_lowercase));
+            let script_language = word.chars().next().and_then(script_language);
+            for (count, candidate) in counts.iter_mut().zip(self.languages.iter()) {
+                if candidate.stop_words.contains(word.as_str())
+                    || script_language == Some(candidate.language)
+                {
+                    *count += 1;
+                }
+            }
+        }
+        // `max_by_key` returns the last maximum, the ties should go to the earliest language.
+        counts
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, count)| **count)
+            .map(|(ord, _)| ord)
+            .unwrap_or(0)
+    }
+}
+
+/// Returns the pipeline used for the texts in `language`, the same as `en_stem` for English.
+fn stemming_analyzer(language: Language) -> TextAnalyzer {
+    TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .filter(Stemmer::new(language))
+        .build()
+}
+
+/// Returns the language written in the script of `c`, if the script is specific to one of the
+/// stemmer languages.
+fn script_language(c: char) -> Option<Language> {
+    match c as u32 {
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Some(Language::Greek),
+        0x0400..=0x04FF => Some(Language::Russian),
+        0x0600..=0x06FF | 0x0750..=0x077F => Some(Language::Arabic),
+        0x0B80..=0x0BFF => Some(Language::Tamil),
+        _ => None,
+    }
+}
+
+impl Tokenizer for LanguageDetectingAnalyzer {
+    type TokenStream<'a> = BoxTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> BoxTokenStream<'a> {
+        let ord = self.detect_language_ord(text);
+        self.detected_language = Some(self.languages[ord].language);
+        self.analyzers[ord].token_stream(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::{Language, LanguageDetectingAnalyzer, TokenStream, Tokenizer};
+    use crate::{Index, IndexWriter};
+
+    fn token_texts(analyzer: &mut LanguageDetectingAnalyzer, text: &str) -> Vec<String> {
+        let mut token_texts = Vec::new();
+        let mut token_stream = analyzer.token_stream(text);
+        token_stream.process(&mut |token| token_texts.push(token.text.clone()));
+        token_texts
+    }
+
+    #[test]
+    fn test_language_detecting_analyzer_requires_a_language() {
+        assert!(LanguageDetectingAnalyzer::new([]).is_err());
+    }
+
+    #[test]
+    fn test_language_detection() {
+        let analyzer = LanguageDetectingAnalyzer::new([
+            Language::English,
+            Language::French,
+            Language::German,
+            Language::Greek,
+            Language::English,
+        ])
+        .unwrap();
+        assert_eq!(
+            analyzer.languages().collect::<Vec<_>>(),
+            [
+                Language::English,
+                Language::French,
+                Language::German,
+                Language::Greek
+            ]
+        );
+        assert_eq!(
+            analyzer.detect_language("The cat is sleeping in the garden"),
+            Language::English
+        );
+        assert_eq!(
+            analyzer.detect_language("Le chat dort dans le jardin"),
+            Language::French
+        );
+        assert_eq!(
+            analyzer.detect_language("Die Katze schläft im Garten"),
+            Language::German
+        );
+        assert_eq!(
+            analyzer.detect_language("Η γάτα κοιμάται στον κήπο"),
+            Language::Greek
+        );
+        // Inconclusive detections fall back to the default language.
+        assert_eq!(analyzer.detect_language("jardin"), Language::English);
+        assert_eq!(analyzer.detect_language(""), Language::English);
+    }
+
+    #[test]
+    fn test_language_detecting_analyzer_stems_in_the_detected_language() {
+        let mut analyzer =
+            LanguageDetectingAnalyzer::new([Language::English, Language::French]).unwrap();
+        assert_eq!(analyzer.detected_language(), None);
+        assert_eq!(
+            token_texts(&mut analyzer, "The cats are eating"),
+            ["the", "cat", "are", "eat"]
+        );
+        assert_eq!(analyzer.detected_language(), Some(Language::English));
+        assert_eq!(
+            token_texts(&mut analyzer, "Les chats mangeaient"),
+            ["le", "chat", "mang"]
+        );
+        assert_eq!(analyzer.detected_language(), Some(Language::French));
+    }
+
+    #[test]
+    fn test_language_detecting_analyzer_search() -> crate::Result<()> {
+        let analyzer = LanguageDetectingAnalyzer::new([Language::English, Language::French])?;
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("multilingual")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register("multilingual", analyzer);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "The cats were eating in the park in 2024"))?;
+        index_writer.add_document(doc!(text => "Les chats mangeaient dans le parc en 2024"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        // Stemmed in English.
+        assert_eq!(count("eat")?, 1);
+        // Stemmed the same in both languages.
+        assert_eq!(count("2024")?, 2);
+        // A single word falls back to English, while the document is stemmed in French.
+        assert_eq!(count("mangeaient")?, 0);
+        assert_eq!(count("\"les chats mangeaient\"")?, 1);
+        Ok(())
+    }
+}
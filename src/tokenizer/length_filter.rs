@@ -0,0 +1,242 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+//!   .filter(LengthFilter::new(2, 5).with_long_token_policy(LongTokenPolicy::Truncate))
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("a toolong nice");
+//! // `a` is shorter than 2 bytes, it is filtered out of the token stream,
+//! // while `toolong` is longer than 5 bytes, and is truncated.
+//! assert_eq!(stream.next().unwrap().text, "toolo");
+//! assert_eq!(stream.next().unwrap().text, "nice");
+//! assert!(stream.next().is_none());
+//! ```
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// What the [`LengthFilter`] does with the tokens longer than its maximum length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LongTokenPolicy {
+    /// The token is removed from the token stream.
+    #[default]
+    Drop,
+    /// The token is cut to the longest prefix fitting the maximum length, without splitting
+    /// a char.
+    ///
+    /// The end offset of the token is moved accordingly, as long as the token text still has
+    /// the length of the original text it comes from. If a previous filter changed this length,
+    /// for instance by folding a char to several ones, the offsets are left untouched.
+    Truncate,
+}
+
+/// `LengthFilter` keeps the tokens whose length, in bytes of their UTF-8 representation, is
+/// between a minimum and a maximum, both included.
+///
+/// The shorter tokens are dropped, and the longer tokens are either dropped or truncated,
+/// depending on the [`LongTokenPolicy`]. Like the [`RemoveLongFilter`](super::RemoveLongFilter),
+/// it protects the term dictionary from pathological inputs, such as mails containing base-64
+/// encoded pictures. The positions of the dropped tokens are left as gaps.
+#[derive(Clone)]
+pub struct LengthFilter {
+    min_length: usize,
+    max_length: usize,
+    long_token_policy: LongTokenPolicy,
+}
+
+impl LengthFilter {
+    /// Creates a `LengthFilter` keeping the tokens between `min_length` and `max_length`
+    /// bytes, and dropping the other ones.
+    pub fn new(min_length: usize, max_length: usize) -> LengthFilter {
+        LengthFilter {
+            min_length,
+            max_length,
+            long_token_policy: LongTokenPolicy::default(),
+        }
+    }
+
+    /// Sets the policy applied to the tokens longer than the maximum length.
+    #[must_use]
+    pub fn with_long_token_policy(mut self, long_token_policy: LongTokenPolicy) -> LengthFilter {
+        self.long_token_policy = long_token_policy;
+        self
+    }
+}
+
+impl TokenFilter for LengthFilter {
+    type Tokenizer<T: Tokenizer> = LengthFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> LengthFilterWrapper<T> {
+        LengthFilterWrapper {
+            filter: self,
+            inner: tokenizer,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LengthFilterWrapper<T: Tokenizer> {
+    filter: LengthFilter,
+    inner: T,
+}
+
+impl<T: Tokenizer> Tokenizer for LengthFilterWrapper<T> {
+    type TokenStream<'a> = LengthFilterStream<T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        LengthFilterStream {
+            filter: self.filter.clone(),
+            tail: self.inner.token_stream(text),
+        }
+    }
+}
+
+pub struct LengthFilterStream<T> {
+    filter: LengthFilter,
+    tail: T,
+}
+
+/// Truncates the token to at most `max_length` bytes.
+fn truncate_token(token: &mut Token, max_length: usize) {
+    let mut length = max_length;
+    while !token.text.is_char_boundary(length) {
+        length -= 1;
+    }
+    if token.offset_to - token.offset_from == token.text.len() {
+        token.offset_to = token.offset_from + length;
+    }
+    token.text.truncate(length);
+}
+
+impl<T: TokenStream> TokenStream for LengthFilterStream<T> {
+    fn advance(&mut self) -> bool {
+        while self.tail.advance() {
+            let token = self.tail.token_mut();
+            if token.text.len() < self.filter.min_length {
+                continue;
+            }
+            if token.text.len() > self.filter.max_length {
+                match self.filter.long_token_policy {
+                    LongTokenPolicy::Drop => continue,
+                    LongTokenPolicy::Truncate => {
+                        truncate_token(token, self.filter.max_length);
+                        // Cutting a char may bring the token below the minimum length.
+                        if token.text.len() < self.filter.min_length {
+                            continue;
+                        }
+                    }
+                }
+            }
+            return true;
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        AsciiFoldingFilter, LengthFilter, LongTokenPolicy, SimpleTokenizer, TextAnalyzer, Token,
+    };
+    use crate::{Index, IndexWriter, Term};
+
+    fn token_stream_helper(text: &str, long_token_policy: LongTokenPolicy) -> Vec<Token> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LengthFilter::new(2, 6).with_long_token_policy(long_token_policy))
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_length_filter_drop() {
+        let tokens =
+            token_stream_helper("a hello tantivy, happy searching!", LongTokenPolicy::Drop);
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 1, "hello", 2, 7);
+        assert_token(&tokens[1], 3, "happy", 17, 22);
+    }
+
+    #[test]
+    fn test_length_filter_truncate() {
+        let tokens = token_stream_helper(
+            "a hello tantivy, happy searching!",
+            LongTokenPolicy::Truncate,
+        );
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 1, "hello", 2, 7);
+        assert_token(&tokens[1], 2, "tantiv", 8, 14);
+        assert_token(&tokens[2], 3, "happy", 17, 22);
+        assert_token(&tokens[3], 4, "search", 23, 29);
+        // Chars are not split.
+        let tokens = token_stream_helper("ééééé", LongTokenPolicy::Truncate);
+        assert_token(&tokens[0], 0, "ééé", 0, 6);
+    }
+
+    #[test]
+    fn test_length_filter_truncate_keeps_offsets_of_changed_text() {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(AsciiFoldingFilter)
+            .filter(LengthFilter::new(1, 3).with_long_token_policy(LongTokenPolicy::Truncate))
+            .build();
+        let mut token_stream = analyzer.token_stream("éèàù");
+        let token = token_stream.next().unwrap();
+        assert_token(token, 0, "eea", 0, 8);
+    }
+
+    #[test]
+    fn test_length_filter_fieldnorms() -> crate::Result<()> {
+        let blob = "QUJD".repeat(1_000);
+        let text_value = format!("image {blob} attached");
+        for (long_token_policy, expected_fieldnorm, expected_truncated_count) in [
+            (LongTokenPolicy::Drop, 2, 0),
+            (LongTokenPolicy::Truncate, 3, 1),
+        ] {
+            let mut schema_builder = Schema::builder();
+            let text = schema_builder.add_text_field(
+                "text",
+                TextOptions::default()
+                    .set_indexing_options(TextFieldIndexing::default().set_tokenizer("length")),
+            );
+            let index = Index::create_in_ram(schema_builder.build());
+            index.tokenizers().register(
+                "length",
+                TextAnalyzer::builder(SimpleTokenizer::default())
+                    .filter(LengthFilter::new(1, 16).with_long_token_policy(long_token_policy))
+                    .build(),
+            );
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text => text_value.as_str()))?;
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+            let fieldnorm_reader = searcher.segment_reader(0).get_fieldnorms_reader(text)?;
+            assert_eq!(fieldnorm_reader.fieldnorm(0), expected_fieldnorm);
+            let count = |term: &str| {
+                let query =
+                    TermQuery::new(Term::from_field_text(text, term), IndexRecordOption::Basic);
+                searcher.search(&query, &Count)
+            };
+            assert_eq!(count(&blob[..16])?, expected_truncated_count);
+            assert_eq!(count(&blob)?, 0);
+            assert_eq!(count("attached")?, 1);
+        }
+        Ok(())
+    }
+}
@@ -52,6 +52,12 @@
 //! remove their inflection. This tokenizer is slower than the default one,
 //! but is recommended to improve recall.
 //!
+//! ## `cjk`
+//!
+//! Suited for Chinese, Japanese and Korean text, which is not separated by whitespaces.
+//! Runs of CJK characters are chopped into overlapping bigrams, while other words are
+//! tokenized like `default` does. See [`CjkBigramTokenizer`].
+//!
 //! # Custom tokenizer Library
 //! Avoid using tantivy as dependency and prefer `tantivy-tokenizer-api` instead.
 //!
@@ -123,13 +129,19 @@
 //! ```
 mod alphanum_only;
 mod ascii_folding_filter;
+mod cjk_bigram_tokenizer;
 mod empty_tokenizer;
 mod facet_tokenizer;
+#[cfg(feature = "stopwords")]
+mod language_detecting_analyzer;
+mod length_filter;
 mod lower_caser;
 mod ngram_tokenizer;
+mod protected_terms_tokenizer;
 mod raw_tokenizer;
 mod regex_tokenizer;
 mod remove_long;
+mod shingle_filter;
 mod simple_tokenizer;
 mod split_compound_words;
 mod stemmer;
@@ -143,12 +155,18 @@ pub use tokenizer_api::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokeniz
 
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::ascii_folding_filter::AsciiFoldingFilter;
+pub use self::cjk_bigram_tokenizer::{CjkBigramTokenStream, CjkBigramTokenizer};
 pub use self::facet_tokenizer::FacetTokenizer;
+#[cfg(feature = "stopwords")]
+pub use self::language_detecting_analyzer::LanguageDetectingAnalyzer;
+pub use self::length_filter::{LengthFilter, LongTokenPolicy};
 pub use self::lower_caser::LowerCaser;
 pub use self::ngram_tokenizer::NgramTokenizer;
+pub use self::protected_terms_tokenizer::{ProtectedTermsTokenStream, ProtectedTermsTokenizer};
 pub use self::raw_tokenizer::RawTokenizer;
 pub use self::regex_tokenizer::RegexTokenizer;
 pub use self::remove_long::RemoveLongFilter;
+pub use self::shingle_filter::ShingleFilter;
 pub use self::simple_tokenizer::{SimpleTokenStream, SimpleTokenizer};
 pub use self::split_compound_words::SplitCompoundWords;
 pub use self::stemmer::{Language, Stemmer};
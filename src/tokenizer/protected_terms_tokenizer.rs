@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use super::{Token, TokenStream, Tokenizer};
+
+/// Tokenize the text like the [`SimpleTokenizer`](super::SimpleTokenizer), except for a list of
+/// protected terms containing symbols, such as `C++`, `.NET` or `node.js`, which are kept as
+/// single tokens.
+///
+/// A protected term is matched regardless of the ASCII case, and only as a whole word: it must
+/// neither be preceded nor followed by an alphanumeric character. When several protected terms
+/// match at the same place, the longest one wins. The token keeps the text as it is written in
+/// the document, so that a [`LowerCaser`](super::LowerCaser) placed after the tokenizer
+/// lowercases it while keeping its symbols: `C++` is indexed as `c++`.
+///
+/// Outside of the protected terms, the text is split on whitespaces and punctuation: `C` alone
+/// is indexed as `c`.
+///
+/// Some of these symbols have a meaning in the syntax of the
+/// [`QueryParser`](crate::query::QueryParser), so the protected terms have to be quoted in the
+/// queries, as in `"C++"`.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let protected_terms = vec!["c++".to_string(), ".net".to_string()];
+/// let mut tokenizer = TextAnalyzer::builder(ProtectedTermsTokenizer::new(protected_terms))
+///     .filter(LowerCaser)
+///     .build();
+/// let mut stream = tokenizer.token_stream("C++ and .NET, or C");
+/// assert_eq!(stream.next().unwrap().text, "c++");
+/// assert_eq!(stream.next().unwrap().text, "and");
+/// assert_eq!(stream.next().unwrap().text, ".net");
+/// assert_eq!(stream.next().unwrap().text, "or");
+/// assert_eq!(stream.next().unwrap().text, "c");
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone)]
+pub struct ProtectedTermsTokenizer {
+    // Sorted by decreasing length, so that the longest term matches first.
+    protected_terms: Arc<Vec<String>>,
+    token: Token,
+}
+
+impl ProtectedTermsTokenizer {
+    /// Creates a tokenizer keeping the given terms as single tokens.
+    ///
+    /// Empty terms are ignored.
+    pub fn new<T: IntoIterator<Item = String>>(protected_terms: T) -> ProtectedTermsTokenizer {
+        let mut protected_terms: Vec<String> = protected_terms
+            .into_iter()
+            .filter(|term| !term.is_empty())
+            .collect();
+        protected_terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+        ProtectedTermsTokenizer {
+            protected_terms: Arc::new(protected_terms),
+            token: Token::default(),
+        }
+    }
+}
+
+/// TokenStream produced by the `ProtectedTermsTokenizer`.
+pub struct ProtectedTermsTokenStream<'a> {
+    text: &'a str,
+    protected_terms: &'a [String],
+    // Byte offset of the next char to read.
+    cursor: usize,
+    token: &'a mut Token,
+}
+
+impl Tokenizer for ProtectedTermsTokenizer {
+    type TokenStream<'a> = ProtectedTermsTokenStream<'a>;
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> ProtectedTermsTokenStream<'a> {
+        self.token.reset();
+        ProtectedTermsTokenStream {
+            text,
+            protected_terms: &self.protected_terms,
+            cursor: 0,
+            token: &mut self.token,
+        }
+    }
+}
+
+fn starts_with_alphanumeric(text: &str) -> bool {
+    text.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+impl ProtectedTermsTokenStream<'_> {
+    // Returns the length of the protected term starting at the cursor, if any.
+    fn protected_term_len(&self) -> Option<usize> {
+        let preceded_by_alphanumeric = self.text[..self.cursor]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric);
+        if preceded_by_alphanumeric {
+            return None;
+        }
+        let remaining = &self.text[self.cursor..];
+        self.protected_terms
+            .iter()
+            .find(|term| {
+                remaining
+                    .get(..term.len())
+                    .is_some_and(|candidate| candidate.eq_ignore_ascii_case(term))
+                    && !starts_with_alphanumeric(&remaining[term.len()..])
+            })
+            .map(String::len)
+    }
+
+    // Returns the end of the alphanumeric run starting at the cursor.
+    fn search_token_end(&self) -> usize {
+        self.text[self.cursor..]
+            .char_indices()
+            .find(|(_, c)| !c.is_alphanumeric())
+            .map(|(offset, _)| self.cursor + offset)
+            .unwrap_or(self.text.len())
+    }
+}
+
+impl TokenStream for ProtectedTermsTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        self.token.position = self.token.position.wrapping_add(1);
+        while let Some(c) = self.text[self.cursor..].chars().next() {
+            let offset_to = if let Some(len) = self.protected_term_len() {
+                self.cursor + len
+            } else if c.is_alphanumeric() {
+                self.search_token_end()
+            } else {
+                self.cursor += c.len_utf8();
+                continue;
+            };
+            self.token.offset_from = self.cursor;
+            self.token.offset_to = offset_to;
+            self.token.text.push_str(&self.text[self.cursor..offset_to]);
+            self.cursor = offset_to;
+            return true;
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProtectedTermsTokenizer;
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, TextAnalyzer, Token};
+    use crate::{Index, IndexWriter};
+
+    fn analyzer() -> TextAnalyzer {
+        let protected_terms = ["C++", ".NET", "node.js", "c"];
+        TextAnalyzer::builder(ProtectedTermsTokenizer::new(
+            protected_terms.iter().map(|term| term.to_string()),
+        ))
+        .filter(LowerCaser)
+        .build()
+    }
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let mut analyzer = analyzer();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_protected_terms_tokenizer() {
+        let tokens = token_stream_helper("I use c++, .Net and Node.js!");
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "i", 0, 1);
+        assert_token(&tokens[1], 1, "use", 2, 5);
+        assert_token(&tokens[2], 2, "c++", 6, 9);
+        assert_token(&tokens[3], 3, ".net", 11, 15);
+        assert_token(&tokens[4], 4, "and", 16, 19);
+        assert_token(&tokens[5], 5, "node.js", 20, 27);
+    }
+
+    #[test]
+    fn test_protected_terms_tokenizer_word_boundaries() {
+        let texts = |text: &str| -> Vec<String> {
+            token_stream_helper(text)
+                .into_iter()
+                .map(|token| token.text)
+                .collect()
+        };
+        // Protected terms are only matched as whole words.
+        assert_eq!(texts("abc++ c++d"), ["abc", "c", "d"]);
+        assert_eq!(texts("asp.net node.jsx"), ["asp", "net", "node", "jsx"]);
+        // The longest protected term wins.
+        assert_eq!(texts("C++ C+ C"), ["c++", "c", "c"]);
+        assert_eq!(texts("...NET,C++"), [".net", "c++"]);
+        assert!(texts("+-").is_empty());
+    }
+
+    #[test]
+    fn test_protected_terms_tokenizer_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field(
+            "text",
+            TextOptions::default()
+                .set_indexing_options(TextFieldIndexing::default().set_tokenizer("protected")),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register("protected", analyzer());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "Modern C++ programming"))?;
+        index_writer.add_document(doc!(text => "The C programming language"))?;
+        index_writer.add_document(doc!(text => "Building apps with .NET and Node.js"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        let count = |query: &str| -> crate::Result<usize> {
+            searcher.search(&query_parser.parse_query(query)?, &Count)
+        };
+        assert_eq!(count(r#""c++""#)?, 1);
+        assert_eq!(count(r#""C""#)?, 1);
+        assert_eq!(count(r#"".net""#)?, 1);
+        assert_eq!(count(r#""NODE.JS""#)?, 1);
+        assert_eq!(count("net")?, 0);
+        Ok(())
+    }
+}
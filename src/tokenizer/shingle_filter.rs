@@ -0,0 +1,339 @@
+use std::collections::VecDeque;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+use crate::TantivyError;
+
+/// Separator between the words of a shingle.
+const SHINGLE_SEPARATOR: &str = " ";
+
+/// [`TokenFilter`] emitting shingles, i.e. the combinations of adjacent tokens, along with the
+/// original tokens.
+///
+/// With shingles of 2 tokens, `"the quick brown fox"` yields `the`, `the quick`, `quick`,
+/// `quick brown`, `brown`, `brown fox` and `fox`. A shingle has the position of its first token,
+/// and spans the offsets of all of its tokens. The words of a shingle are separated by a space.
+///
+/// Indexing shingles makes it possible to search for a phrase of a few words with a single
+/// term lookup, instead of a [`PhraseQuery`](crate::query::PhraseQuery) intersecting the
+/// postings of each word and checking their positions: the field does not even need to be
+/// indexed with positions. The price is a larger index, as each token is part of several terms.
+/// The term to search for is built with [`ShingleFilter::shingle_text`], from the words
+/// processed by the same filters as the ones preceding the `ShingleFilter`.
+///
+/// # Token boundaries
+///
+/// Shingles are only made of tokens with consecutive positions. Since the positions of the
+/// tokens are kept when a filter removes some of them, no shingle spans a removed token: with
+/// a [`StopWordFilter`](super::StopWordFilter) removing `the` placed before the
+/// `ShingleFilter`, `"over the fence"` yields no `over fence` shingle. Placing the
+/// `StopWordFilter` after the `ShingleFilter` instead removes the stop words, but keeps the
+/// shingles containing them, such as `over the`. The values of a multivalued field are
+/// tokenized separately, so shingles never span two of them.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(ShingleFilter::new(2, 2).unwrap())
+///     .build();
+/// let mut stream = tokenizer.token_stream("quick brown fox");
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "quick");
+///     assert_eq!(token.position, 0);
+/// }
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "brown");
+///     assert_eq!(token.position, 1);
+/// }
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, ShingleFilter::shingle_text(&["quick", "brown"]));
+///     assert_eq!(token.position, 0);
+///     assert_eq!(token.position_length, 2);
+///     assert_eq!(token.offset_from, 0);
+///     assert_eq!(token.offset_to, 11);
+/// }
+/// assert_eq!(stream.next().unwrap().text, "fox");
+/// assert_eq!(stream.next().unwrap().text, "brown fox");
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShingleFilter {
+    min_shingle_size: usize,
+    max_shingle_size: usize,
+    output_unigrams: bool,
+}
+
+impl ShingleFilter {
+    /// Creates a filter emitting the shingles of `min_shingle_size` to `max_shingle_size`
+    /// tokens, both included, along with the original tokens.
+    ///
+    /// Returns `TantivyError::InvalidArgument` if `min_shingle_size` is lower than 2, or
+    /// greater than `max_shingle_size`.
+    pub fn new(min_shingle_size: usize, max_shingle_size: usize) -> crate::Result<ShingleFilter> {
+        if min_shingle_size < 2 {
+            return Err(TantivyError::InvalidArgument(
+                "min_shingle_size must be at least 2".to_string(),
+            ));
+        }
+        if min_shingle_size > max_shingle_size {
+            return Err(TantivyError::InvalidArgument(
+                "min_shingle_size must not be greater than max_shingle_size".to_string(),
+            ));
+        }
+        Ok(ShingleFilter {
+            min_shingle_size,
+            max_shingle_size,
+            output_unigrams: true,
+        })
+    }
+
+    /// Only emits the shingles, and drops the original tokens.
+    ///
+    /// Single words can then no longer be searched in the field.
+    #[must_use]
+    pub fn without_unigrams(self) -> ShingleFilter {
+        ShingleFilter {
+            output_unigrams: false,
+            ..self
+        }
+    }
+
+    /// Returns the text of the shingle made of `words`, to build the term to search for.
+    pub fn shingle_text<S: AsRef<str>>(words: &[S]) -> String {
+        let mut text = String::new();
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                text.push_str(SHINGLE_SEPARATOR);
+            }
+            text.push_str(word.as_ref());
+        }
+        text
+    }
+}
+
+impl TokenFilter for ShingleFilter {
+    type Tokenizer<T: Tokenizer> = ShingleFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> ShingleFilterWrapper<T> {
+        ShingleFilterWrapper {
+            filter: self,
+            inner: tokenizer,
+            window: VecDeque::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ShingleFilterWrapper<T> {
+    filter: ShingleFilter,
+    inner: T,
+    window: VecDeque<Token>,
+    pending: Vec<Token>,
+}
+
+impl<T: Tokenizer> Tokenizer for ShingleFilterWrapper<T> {
+    type TokenStream<'a> = ShingleTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.window.clear();
+        self.pending.clear();
+        ShingleTokenStream {
+            filter: &self.filter,
+            tail: self.inner.token_stream(text),
+            window: &mut self.window,
+            pending: &mut self.pending,
+        }
+    }
+}
+
+pub struct ShingleTokenStream<'a, T> {
+    filter: &'a ShingleFilter,
+    tail: T,
+    // The last tokens of the current run of consecutive tokens, up to `max_shingle_size`.
+    window: &'a mut VecDeque<Token>,
+    // The tokens to emit, in reverse order.
+    pending: &'a mut Vec<Token>,
+}
+
+impl<T: TokenStream> ShingleTokenStream<'_, T> {
+    /// Pushes the token of the tail to the window, and fills `self.pending` with the tokens
+    /// ending with it.
+    fn push_tail_token(&mut self) {
+        let token = self.tail.token();
+        if let Some(previous) = self.window.back() {
+            if previous.position + previous.position_length != token.position {
+                self.window.clear();
+            }
+        }
+        if self.window.len() == self.filter.max_shingle_size {
+            self.window.pop_front();
+        }
+        self.window.push_back(token.clone());
+
+        let max_shingle_size = self.filter.max_shingle_size.min(self.window.len());
+        for shingle_size in (self.filter.min_shingle_size..=max_shingle_size).rev() {
+            let shingle_tokens = self.window.range(self.window.len() - shingle_size..);
+            let mut shingle = Token::default();
+            for (i, shingle_token) in shingle_tokens.enumerate() {
+                if i == 0 {
+                    shingle.offset_from = shingle_token.offset_from;
+                    shingle.position = shingle_token.position;
+                } else {
+                    shingle.text.push_str(SHINGLE_SEPARATOR);
+                }
+                shingle.text.push_str(&shingle_token.text);
+                shingle.offset_to = shingle_token.offset_to;
+                shingle.position_length =
+                    shingle_token.position + shingle_token.position_length - shingle.position;
+            }
+            self.pending.push(shingle);
+        }
+        if self.filter.output_unigrams {
+            self.pending.push(token.clone());
+        }
+    }
+}
+
+impl<T: TokenStream> TokenStream for ShingleTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        self.pending.pop();
+        while self.pending.is_empty() {
+            if !self.tail.advance() {
+                return false;
+            }
+            self.push_tail_token();
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.pending.last().unwrap_or_else(|| self.tail.token())
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.pending
+            .last_mut()
+            .unwrap_or_else(|| self.tail.token_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShingleFilter;
+    use crate::collector::DocSetCollector;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, StopWordFilter, TextAnalyzer, Token};
+    use crate::{Index, IndexWriter, Term};
+
+    fn token_stream_helper(text: &str, analyzer: &mut TextAnalyzer) -> Vec<Token> {
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_shingle_filter() {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(ShingleFilter::new(2, 3).unwrap())
+            .build();
+        let tokens = token_stream_helper("a b c d", &mut analyzer);
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["a", "b", "a b", "c", "b c", "a b c", "d", "c d", "b c d"]
+        );
+        assert_token(&tokens[5], 0, "a b c", 0, 5);
+        assert_eq!(tokens[5].position_length, 3);
+        assert_token(&tokens[8], 1, "b c d", 2, 7);
+
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(ShingleFilter::new(2, 2).unwrap().without_unigrams())
+            .build();
+        let tokens = token_stream_helper("a b c", &mut analyzer);
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, ["a b", "b c"]);
+        assert!(token_stream_helper("a", &mut analyzer).is_empty());
+    }
+
+    #[test]
+    fn test_shingle_filter_stop_words() {
+        let stop_words = || StopWordFilter::remove(vec!["the".to_string()]);
+        // Shingles do not span the removed stop words.
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(stop_words())
+            .filter(ShingleFilter::new(2, 2).unwrap())
+            .build();
+        let tokens = token_stream_helper("over the fence and far", &mut analyzer);
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["over", "fence", "and", "fence and", "far", "and far"]
+        );
+        // Removing the stop words after building the shingles keeps the shingles.
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(ShingleFilter::new(2, 2).unwrap())
+            .filter(stop_words())
+            .build();
+        let tokens = token_stream_helper("over the fence", &mut analyzer);
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, ["over", "over the", "fence", "the fence"]);
+    }
+
+    #[test]
+    fn test_shingle_filter_invalid_sizes() {
+        assert!(ShingleFilter::new(1, 2).is_err());
+        assert!(ShingleFilter::new(3, 2).is_err());
+    }
+
+    #[test]
+    fn test_shingle_filter_term_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer("shingles")
+            .set_index_option(IndexRecordOption::Basic);
+        let text = schema_builder.add_text_field(
+            "text",
+            TextOptions::default().set_indexing_options(indexing),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "shingles",
+            TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(LowerCaser)
+                .filter(ShingleFilter::new(2, 2)?)
+                .build(),
+        );
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "The Quick Brown fox"))?;
+        index_writer.add_document(doc!(text => "brown and quick"))?;
+        index_writer.add_document(doc!(text => "quick", text => "brown"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let matching_doc_ids = |words: &[&str]| -> crate::Result<Vec<u32>> {
+            let term = Term::from_field_text(text, &ShingleFilter::shingle_text(words));
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            let mut doc_ids: Vec<u32> = searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            doc_ids.sort_unstable();
+            Ok(doc_ids)
+        };
+        assert_eq!(matching_doc_ids(&["quick", "brown"])?, [0]);
+        assert!(matching_doc_ids(&["brown", "quick"])?.is_empty());
+        assert_eq!(matching_doc_ids(&["quick"])?, [0, 1, 2]);
+        Ok(())
+    }
+}
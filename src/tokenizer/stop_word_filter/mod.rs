@@ -35,31 +35,7 @@ impl StopWordFilter {
     /// Returns `Some` if a list of stop words is available and `None` otherwise.
     #[cfg(feature = "stopwords")]
     pub fn new(language: Language) -> Option<Self> {
-        let words = match language {
-            Language::Danish => stopwords::DANISH,
-            Language::Dutch => stopwords::DUTCH,
-            Language::English => {
-                // This is the same list of words used by the Apache-licensed Lucene project,
-                // c.f. https://github.com/apache/lucene/blob/d5d6dc079395c47cd6d12dcce3bcfdd2c7d9dc63/lucene/analysis/common/src/java/org/apache/lucene/analysis/en/EnglishAnalyzer.java#L46
-                &[
-                    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in",
-                    "into", "is", "it", "no", "not", "of", "on", "or", "such", "that", "the",
-                    "their", "then", "there", "these", "they", "this", "to", "was", "will", "with",
-                ]
-            }
-            Language::Finnish => stopwords::FINNISH,
-            Language::French => stopwords::FRENCH,
-            Language::German => stopwords::GERMAN,
-            Language::Hungarian => stopwords::HUNGARIAN,
-            Language::Italian => stopwords::ITALIAN,
-            Language::Norwegian => stopwords::NORWEGIAN,
-            Language::Portuguese => stopwords::PORTUGUESE,
-            Language::Russian => stopwords::RUSSIAN,
-            Language::Spanish => stopwords::SPANISH,
-            Language::Swedish => stopwords::SWEDISH,
-            _ => return None,
-        };
-
+        let words = stop_words(language)?;
         Some(Self::remove(words.iter().map(|&word| word.to_owned())))
     }
 
@@ -71,6 +47,36 @@ impl StopWordFilter {
     }
 }
 
+/// Returns the list of stop words of `language`, if any.
+#[cfg(feature = "stopwords")]
+pub(crate) fn stop_words(language: Language) -> Option<&'static [&'static str]> {
+    let words: &'static [&'static str] = match language {
+        Language::Danish => stopwords::DANISH,
+        Language::Dutch => stopwords::DUTCH,
+        Language::English => {
+            // This is the same list of words used by the Apache-licensed Lucene project,
+            // c.f. https://github.com/apache/lucene/blob/d5d6dc079395c47cd6d12dcce3bcfdd2c7d9dc63/lucene/analysis/common/src/java/org/apache/lucene/analysis/en/EnglishAnalyzer.java#L46
+            &[
+                "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into",
+                "is", "it", "no", "not", "of", "on", "or", "such", "that", "the", "their", "then",
+                "there", "these", "they", "this", "to", "was", "will", "with",
+            ]
+        }
+        Language::Finnish => stopwords::FINNISH,
+        Language::French => stopwords::FRENCH,
+        Language::German => stopwords::GERMAN,
+        Language::Hungarian => stopwords::HUNGARIAN,
+        Language::Italian => stopwords::ITALIAN,
+        Language::Norwegian => stopwords::NORWEGIAN,
+        Language::Portuguese => stopwords::PORTUGUESE,
+        Language::Russian => stopwords::RUSSIAN,
+        Language::Spanish => stopwords::SPANISH,
+        Language::Swedish => stopwords::SWEDISH,
+        _ => return None,
+    };
+    Some(words)
+}
+
 impl TokenFilter for StopWordFilter {
     type Tokenizer<T: Tokenizer> = StopWordFilterWrapper<T>;
 
@@ -4,7 +4,8 @@ use std::sync::{Arc, RwLock};
 use crate::tokenizer::stemmer::Language;
 use crate::tokenizer::tokenizer::TextAnalyzer;
 use crate::tokenizer::{
-    LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, WhitespaceTokenizer,
+    CjkBigramTokenizer, LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    WhitespaceTokenizer,
 };
 
 /// The tokenizer manager serves as a store for
@@ -18,6 +19,8 @@ use crate::tokenizer::{
 /// - `en_stem` : Like `default`, but also applies stemming on the resulting tokens. Stemming can
 ///   improve the recall of your search engine.
 /// - `whitespace` : Splits the text on whitespaces.
+/// - `cjk` : Like `default`, but chops runs of Chinese, Japanese and Korean characters into
+///   overlapping bigrams.
 #[derive(Clone)]
 pub struct TokenizerManager {
     tokenizers: Arc<RwLock<HashMap<String, TextAnalyzer>>>,
@@ -49,6 +52,19 @@ impl TokenizerManager {
             .get(tokenizer_name)
             .cloned()
     }
+
+    /// Returns the names of all of the registered tokenizers, in lexicographic order.
+    pub fn registered_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .tokenizers
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .keys()
+            .cloned()
+            .collect();
+        names.sort_unstable();
+        names
+    }
 }
 
 impl Default for TokenizerManager {
@@ -73,6 +89,13 @@ impl Default for TokenizerManager {
                 .build(),
         );
         manager.register("whitespace", WhitespaceTokenizer::default());
+        manager.register(
+            "cjk",
+            TextAnalyzer::builder(CjkBigramTokenizer::default())
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+                .build(),
+        );
         manager
     }
 }
@@ -204,8 +204,19 @@ where
     /// Advance position the stream on the next item.
     /// Before the first call to `.advance()`, the stream
     /// is an uninitialized state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sstable cannot be read, see [`Streamer::try_advance`].
     pub fn advance(&mut self) -> bool {
-        while self.delta_reader.advance().unwrap() {
+        self.try_advance().unwrap()
+    }
+
+    /// Advance position the stream on the next item, as [`Streamer::advance`].
+    ///
+    /// Returns an error if the blocks of the sstable cannot be read.
+    pub fn try_advance(&mut self) -> io::Result<bool> {
+        while self.delta_reader.advance()? {
             self.term_ord = Some(
                 self.term_ord
                     .map(|term_ord| term_ord + 1u64)
@@ -236,13 +247,13 @@ where
                 Bound::Excluded(upper_bound_key) => upper_bound_key[..] > self.key[..],
             };
             if !match_upper_bound {
-                return false;
+                return Ok(false);
             }
             if self.automaton.is_match(&state) {
-                return true;
+                return Ok(true);
             }
         }
-        false
+        Ok(false)
     }
 
     /// Returns the `TermOrdinal` of the given term.